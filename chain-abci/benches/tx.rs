@@ -1,206 +1,160 @@
-use abci::{Application, RequestCheckTx, RequestInitChain};
+use abci::{Application, RequestCheckTx};
 use chain_abci::app::ChainNodeApp;
-use chain_abci::storage::account::AccountStorage;
-use chain_abci::storage::account::AccountWrapper;
-use chain_abci::storage::tx::StarlingFixedKey;
-use chain_abci::storage::{Storage, NUM_COLUMNS};
+use chain_abci::enclave_bridge::mock::MockClient;
 use chain_core::common::MerkleTree;
-use chain_core::compute_app_hash;
-use chain_core::init::config::AccountType;
-use chain_core::init::config::InitNetworkParameters;
-use chain_core::init::config::{InitialValidator, ValidatorKeyType};
-use chain_core::init::{address::RedeemAddress, coin::Coin, config::InitConfig};
-use chain_core::state::account::*;
-use chain_core::tx::fee::{LinearFee, Milli};
-use chain_core::tx::witness::EcdsaSignature;
-use chain_core::tx::witness::TxInWitness;
-use chain_core::tx::TransactionId;
-use chain_core::tx::{
-    data::{
-        access::{TxAccess, TxAccessPolicy},
-        address::ExtendedAddr,
-        attribute::TxAttributes,
-        input::TxoPointer,
-        output::TxOut,
-        Tx, TxId,
-    },
-    TxAux,
-};
-use criterion::Criterion;
-use criterion::{criterion_group, criterion_main};
-use kvdb::KeyValueDB;
-use kvdb_memorydb::create;
+use chain_core::init::coin::Coin;
+use chain_core::state::tendermint::BlockHeight;
+use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::input::TxoPointer;
+use chain_core::tx::data::output::TxOut;
+use chain_core::tx::data::Tx;
+use chain_core::tx::fee::Fee;
+use chain_core::tx::witness::tree::RawXOnlyPubkey;
+use chain_core::tx::witness::{TxInWitness, TxWitness};
+use chain_core::tx::{TransactionId, TxWithOutputs};
+use chain_core::ChainInfo;
+use chain_tx_validation::verify_transfer;
+use chain_tx_validation::witness::verify_tx_address;
+use criterion::{criterion_group, criterion_main, BatchSize};
+use criterion::{BenchmarkId, Criterion};
 use parity_scale_codec::Encode;
-use secp256k1::{
-    key::{PublicKey, SecretKey},
-    Message, Secp256k1, Signing,
-};
-use std::collections::BTreeMap;
-use std::sync::Arc;
+use secp256k1::schnorrsig::schnorr_sign;
+use secp256k1::{key::SecretKey, key::XOnlyPublicKey, Message};
+use test_common::chain_env::ChainEnv;
 
-fn create_db() -> Arc<dyn KeyValueDB> {
-    Arc::new(create(NUM_COLUMNS.unwrap()))
-}
+const ACCOUNTS: usize = 4;
+const TRANSFER_INPUTS: [usize; 4] = [1, 4, 16, 64];
+const CHAIN_HEX_ID: u8 = 0xab;
 
-fn create_account_db() -> AccountStorage {
-    AccountStorage::new(Storage::new_db(Arc::new(create(1))), 20).expect("account db")
+fn prepare_app() -> ChainNodeApp<MockClient> {
+    let (env, storage) = ChainEnv::new(Coin::max(), Coin::zero(), ACCOUNTS);
+    let mut app = env.chain_node(storage);
+    app.init_chain(&env.req_init_chain());
+    app
 }
 
-const TEST_CHAIN_ID: &str = "test-00";
+fn prepare_valid_reqs() -> Vec<RequestCheckTx> {
+    let (env, _storage) = ChainEnv::new(Coin::max(), Coin::zero(), ACCOUNTS);
+    (0..ACCOUNTS)
+        .map(|i| {
+            let mut creq = RequestCheckTx::default();
+            creq.set_tx(env.unbond_tx(Coin::unit(), 0, i).encode());
+            creq
+        })
+        .collect()
+}
 
-pub fn get_ecdsa_witness<C: Signing>(
-    secp: &Secp256k1<C>,
-    txid: &TxId,
-    secret_key: &SecretKey,
-) -> EcdsaSignature {
-    let message = Message::from_slice(&txid[..]).expect("32 bytes");
-    let sig = secp.sign_recoverable(&message, &secret_key);
-    return sig;
+fn check_x_tx(app: &mut ChainNodeApp<MockClient>, reqs: &[RequestCheckTx]) {
+    for creq in reqs.iter() {
+        let cresp = app.check_tx(&creq);
+        assert_eq!(0, cresp.code, "{}", cresp.log);
+    }
 }
 
-fn init_chain_for(addresses: &Vec<RedeemAddress>) -> ChainNodeApp {
-    let total = Coin::from((addresses.len() * 1_0000_0000usize) as u32);
-    let remaining = (Coin::max() - total).unwrap();
-    let validator_addr = "0x0e7c045110b8dbf29765047380898919c5cc56f4"
-        .parse::<RedeemAddress>()
-        .unwrap();
-    let mut distribution: BTreeMap<RedeemAddress, (Coin, AccountType)> = addresses
-        .iter()
-        .map(|address| {
+/// a transfer spending `count` outputs (of one unit), each sent to a different 1-of-1 address,
+/// with the transactions of the spent outputs
+fn prepare_transfer(count: usize) -> (Tx, TxWitness, Vec<TxWithOutputs>) {
+    let secp = secp256k1::SECP256K1;
+    let keys = (0..count)
+        .map(|i| {
+            let mut seed = [0xcd; 32];
+            seed[0] = i as u8;
+            let secret_key = SecretKey::from_slice(&seed).unwrap();
+            let public_key = RawXOnlyPubkey::from(
+                XOnlyPublicKey::from_secret_key(&secp, &secret_key).serialize(),
+            );
             (
-                *address,
-                (
-                    Coin::from(1_0000_0000 as u32),
-                    AccountType::ExternallyOwnedAccount,
-                ),
+                secret_key,
+                MerkleTree::new(vec![public_key.clone()]),
+                public_key,
             )
         })
-        .collect();
-    distribution.insert(
-        RedeemAddress::default(),
-        (Coin::zero(), AccountType::ExternallyOwnedAccount),
-    );
-    distribution.insert(
-        validator_addr,
-        (remaining, AccountType::ExternallyOwnedAccount),
-    );
-
-    let params = InitNetworkParameters {
-        initial_fee_policy: LinearFee::new(
-            Milli::try_new(1, 1).unwrap(),
-            Milli::try_new(1, 1).unwrap(),
-        ),
-        required_council_node_stake: remaining,
-        unbonding_period: 1,
-    };
-    let c = InitConfig::new(
-        distribution,
-        RedeemAddress::default(),
-        RedeemAddress::default(),
-        RedeemAddress::default(),
-        params,
-        vec![InitialValidator {
-            staking_account_address: validator_addr,
-            consensus_pubkey_type: ValidatorKeyType::Ed25519,
-            consensus_pubkey_b64: "MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA=".to_string(),
-        }],
+        .collect::<Vec<_>>();
+    let spent_txs = keys
+        .iter()
+        .map(|(_, tree, _)| {
+            Tx::new_with(
+                vec![],
+                vec![TxOut::new(
+                    ExtendedAddr::OrTree(tree.root_hash()),
+                    Coin::unit(),
+                )],
+                TxAttributes::new(CHAIN_HEX_ID),
+            )
+        })
+        .collect::<Vec<_>>();
+    let tx = Tx::new_with(
+        spent_txs
+            .iter()
+            .map(|tx| TxoPointer::new(tx.id(), 0))
+            .collect(),
+        vec![TxOut::new(
+            ExtendedAddr::OrTree(keys[0].1.root_hash()),
+            (Coin::unit() * count as u64).unwrap(),
+        )],
+        TxAttributes::new(CHAIN_HEX_ID),
     );
-    let t = ::protobuf::well_known_types::Timestamp::new();
-    let result = c.validate_config_get_genesis(t.get_seconds());
-    if let Ok((accounts, rp, _nodes)) = result {
-        let tx_tree = MerkleTree::empty();
-        let mut account_tree =
-            AccountStorage::new(Storage::new_db(Arc::new(create(1))), 20).expect("account db");
-
-        let keys: Vec<StarlingFixedKey> = accounts.iter().map(|x| x.key()).collect();
-        // TODO: get rid of the extra allocations
-        let wrapped: Vec<AccountWrapper> =
-            accounts.iter().map(|x| AccountWrapper(x.clone())).collect();
-        let new_account_root = account_tree
-            .insert(
-                None,
-                &mut keys.iter().collect::<Vec<_>>(),
-                &mut wrapped.iter().collect::<Vec<_>>(),
+    let message = Message::from_slice(&tx.id()).unwrap();
+    let witness = keys
+        .into_iter()
+        .map(|(secret_key, tree, public_key)| {
+            TxInWitness::TreeSig(
+                schnorr_sign(&secp, &message, &secret_key, &mut rand::thread_rng()),
+                tree.generate_proof(public_key).unwrap(),
             )
-            .expect("initial insert");
-
-        let genesis_app_hash = compute_app_hash(&tx_tree, &new_account_root, &rp);
-
-        let example_hash = hex::encode_upper(genesis_app_hash);
-        let mut app = ChainNodeApp::new_with_storage(
-            &example_hash,
-            TEST_CHAIN_ID,
-            Storage::new_db(create_db()),
-            create_account_db(),
-        );
-        let mut req = RequestInitChain::default();
-        req.set_time(t);
-        req.set_app_state_bytes(serde_json::to_vec(&c).unwrap());
-        req.set_chain_id(String::from(TEST_CHAIN_ID));
-        app.init_chain(&req);
-        return app;
-    } else {
-        panic!("distribution validation error: {}", result.err().unwrap());
-    }
-}
-
-fn prepare_app_valid_txs(upper: u8) -> (ChainNodeApp, Vec<TxAux>) {
-    let secp = Secp256k1::new();
-    let dummy_keys = 0x01..upper;
-    let secret_keys: Vec<SecretKey> = dummy_keys
-        .map(|x| SecretKey::from_slice(&[x; 32]).unwrap())
-        .collect();
-    let public_keys: Vec<PublicKey> = secret_keys
-        .iter()
-        .map(|secret_key| PublicKey::from_secret_key(&secp, &secret_key))
-        .collect();
-    let addrs = public_keys
-        .iter()
-        .map(|public_key| RedeemAddress::from(public_key))
-        .collect();
-    let app = init_chain_for(&addrs);
-    let mut txs = Vec::new();
-    for i in 0..addrs.len() {
-        let tx = WithdrawUnbondedTx::new(
-            0,
-            vec![TxOut::new_with_timelock(
-                ExtendedAddr::BasicRedeem(addrs[i]),
-                Coin::unit(),
-                0,
-            )],
-            TxAttributes::new_with_access(
-                0,
-                vec![TxAccessPolicy::new(public_keys[i], TxAccess::AllData)],
-            ),
-        );
-
-        let witness =
-            StakedStateOpWitness::new(get_ecdsa_witness(&secp, &tx.id(), &secret_keys[i]));
-        let txaux = TxAux::WithdrawUnbondedStakeTx(tx, witness);
-        txs.push(txaux)
-    }
-
-    (app, txs)
+        })
+        .collect::<Vec<_>>();
+    (
+        tx,
+        witness.into(),
+        spent_txs.into_iter().map(TxWithOutputs::Transfer).collect(),
+    )
 }
 
-fn check_x_tx(app: &mut ChainNodeApp, reqs: &Vec<RequestCheckTx>) {
-    for creq in reqs.iter() {
-        let _cresp = app.check_tx(&creq);
+fn transfer_benchmark(c: &mut Criterion) {
+    let info = ChainInfo {
+        min_fee_computed: Fee::new(Coin::zero()),
+        chain_hex_id: CHAIN_HEX_ID,
+        block_time: 0,
+        block_height: BlockHeight::genesis(),
+        max_evidence_age: 0,
+    };
+    let mut group = c.benchmark_group("transfer inputs");
+    for count in TRANSFER_INPUTS.iter() {
+        let (tx, witness, inputs) = prepare_transfer(*count);
+        let inclusion = vec![None; *count];
+        // the transfer validation, with all witnesses' signatures verified in one batch
+        group.bench_with_input(BenchmarkId::new("verify_transfer", count), count, |b, _| {
+            b.iter_batched(
+                || inputs.clone(),
+                |inputs| verify_transfer(&tx, &witness, &info, inputs, &inclusion).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+        // the baseline: every witness verified on its own
+        group.bench_with_input(BenchmarkId::new("per input", count), count, |b, _| {
+            b.iter(|| {
+                for (witness, input) in witness.iter().zip(inputs.iter()) {
+                    verify_tx_address(witness, &tx.id(), &input.outputs()[0].address).unwrap();
+                }
+            })
+        });
     }
+    group.finish();
 }
 
 fn criterion_benchmark(c: &mut Criterion) {
-    let (mut app, txs) = prepare_app_valid_txs(0x05);
-    let reqs = txs
-        .iter()
-        .map(|txaux| {
-            let mut creq = RequestCheckTx::default();
-            creq.set_tx(txaux.encode());
-            creq
-        })
-        .collect();
-    c.bench_function("checktx x", move |b| b.iter(|| check_x_tx(&mut app, &reqs)));
+    let reqs = prepare_valid_reqs();
+    // the mempool state advances the nonces, so every batch starts from a fresh chain
+    c.bench_function("checktx x", move |b| {
+        b.iter_batched(
+            prepare_app,
+            |mut app| check_x_tx(&mut app, &reqs),
+            BatchSize::SmallInput,
+        )
+    });
 }
 
-criterion_group!(benches, criterion_benchmark);
+criterion_group!(benches, criterion_benchmark, transfer_benchmark);
 criterion_main!(benches);
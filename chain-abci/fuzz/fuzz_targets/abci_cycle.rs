@@ -169,7 +169,7 @@ fuzz_target!(|data: &[u8]| {
                                 let r = check_validators(
                                     &state.validators,
                                     req.validators.clone().into_vec(),
-                                    &c.distribution,
+                                    &state.accounts,
                                 );
                                 if r.is_err() {
                                     defaultinit
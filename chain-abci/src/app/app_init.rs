@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::mem;
 
@@ -8,18 +8,18 @@ use parity_scale_codec::{Decode, Encode};
 use protobuf::Message;
 use serde::{Deserialize, Serialize};
 
+use super::mempool::StakingMempool;
+use crate::enclave_bridge::persistence::TdbeStatus;
 use crate::enclave_bridge::EnclaveProxy;
 use crate::staking::StakingTable;
 use chain_core::common::MerkleTree;
 use chain_core::common::Timespec;
 use chain_core::common::{H256, HASH_SIZE_256};
 use chain_core::compute_app_hash;
-use chain_core::init::address::RedeemAddress;
 use chain_core::init::coin::Coin;
 use chain_core::init::config::InitConfig;
 use chain_core::init::config::NetworkParameters;
-use chain_core::state::account::StakedStateDestination;
-use chain_core::state::account::{CouncilNodeMeta, StakedStateAddress};
+use chain_core::state::account::{CouncilNodeMeta, NodeState, StakedState, StakedStateAddress};
 use chain_core::state::tendermint::{BlockHeight, TendermintVotePower};
 use chain_core::state::{ChainState, RewardsPoolState};
 use chain_core::tx::TxAux;
@@ -28,7 +28,7 @@ use chain_storage::buffer::{
     flush_storage, GetStaking, KVBuffer, StakingBuffer, StoreKV, StoreStaking,
 };
 use chain_storage::jellyfish::{compute_staking_root, sum_staking_coins, StakingGetter, Version};
-use chain_storage::{Storage, StoredChainState};
+use chain_storage::{LookupItem, Storage, StoredChainState};
 
 /// ABCI app state snapshot
 #[derive(Serialize, Deserialize, Clone, Encode, Decode)]
@@ -142,6 +142,8 @@ pub struct ChainNodeApp<T: EnclaveProxy> {
     pub tx_query_address: Option<String>,
     /// Address of TDBE to supply to clients
     pub tdbe_address: String,
+    /// progress of the TDBE initial data fetch (running concurrently)
+    pub tdbe_status: TdbeStatus,
 
     /// consensus buffer of staking merkle trie storage
     pub staking_buffer: StakingBuffer,
@@ -151,6 +153,8 @@ pub struct ChainNodeApp<T: EnclaveProxy> {
     pub kv_buffer: KVBuffer,
     /// mempool buffer of key-value storage
     pub mempool_kv_buffer: KVBuffer,
+    /// staking transactions queued in the mempool connection
+    pub staking_mempool: StakingMempool,
 }
 
 pub fn get_validator_key(node: &CouncilNodeMeta) -> PubKey {
@@ -191,12 +195,12 @@ fn check_and_store_consensus_params(
 pub fn check_validators(
     nodes: &[(StakedStateAddress, CouncilNodeMeta)],
     mut req_validators: Vec<ValidatorUpdate>,
-    distribution: &BTreeMap<RedeemAddress, (StakedStateDestination, Coin)>,
+    accounts: &[StakedState],
 ) -> Result<(), ()> {
     let mut validators = Vec::with_capacity(nodes.len());
     for (address, node) in nodes.iter() {
         let mut validator = ValidatorUpdate::default();
-        let power = get_voting_power(distribution, address);
+        let power = get_voting_power(accounts, address).ok_or(())?;
         validator.set_power(power.into());
         let pk = get_validator_key(&node);
         validator.set_pub_key(pk);
@@ -219,12 +223,13 @@ pub fn check_validators(
 }
 
 fn get_voting_power(
-    distribution: &BTreeMap<RedeemAddress, (StakedStateDestination, Coin)>,
+    accounts: &[StakedState],
     node_address: &StakedStateAddress,
-) -> TendermintVotePower {
-    match node_address {
-        StakedStateAddress::BasicRedeem(a) => TendermintVotePower::from(distribution[a].1),
-    }
+) -> Option<TendermintVotePower> {
+    accounts
+        .iter()
+        .find(|account| &account.address == node_address)
+        .map(|account| TendermintVotePower::from(account.bonded))
 }
 
 pub fn init_app_hash(conf: &InitConfig, genesis_time: Timespec) -> H256 {
@@ -280,11 +285,13 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
             rewards_pool_updated: false,
             tx_query_address,
             tdbe_address,
+            tdbe_status: TdbeStatus::default(),
 
             staking_buffer: HashMap::new(),
             mempool_staking_buffer: HashMap::new(),
             kv_buffer: HashMap::new(),
             mempool_kv_buffer: HashMap::new(),
+            staking_mempool: StakingMempool::default(),
         }
     }
 
@@ -385,11 +392,13 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
                 rewards_pool_updated: false,
                 tx_query_address,
                 tdbe_address,
+                tdbe_status: TdbeStatus::default(),
 
                 staking_buffer: HashMap::new(),
                 mempool_staking_buffer: HashMap::new(),
                 kv_buffer: HashMap::new(),
                 mempool_kv_buffer: HashMap::new(),
+                staking_mempool: StakingMempool::default(),
             }
         }
     }
@@ -456,15 +465,25 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
         check_validators(
             &state.validators,
             req.validators.clone().into_vec(),
-            &conf.distribution,
+            &state.accounts,
         )
         .expect("validators in genesis configuration are not consistent with app_state");
 
-        let val_addresses = state
-            .validators
-            .iter()
-            .map(|(addr, _)| *addr)
-            .collect::<Vec<_>>();
+        let val_addresses = if conf.exported_state.is_some() {
+            // including the jailed / inactive validator records
+            state
+                .accounts
+                .iter()
+                .filter(|account| matches!(account.node_meta, Some(NodeState::CouncilNode(_))))
+                .map(|account| account.address)
+                .collect::<Vec<_>>()
+        } else {
+            state
+                .validators
+                .iter()
+                .map(|(addr, _)| *addr)
+                .collect::<Vec<_>>()
+        };
         let staking_table = StakingTable::from_genesis(
             &staking_getter!(self, 0),
             network_params.get_required_council_node_stake(),
@@ -472,7 +491,7 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
             &val_addresses,
         );
 
-        let genesis_state = ChainNodeState::genesis(
+        let mut genesis_state = ChainNodeState::genesis(
             genesis_app_hash,
             genesis_time,
             max_evidence_age,
@@ -482,6 +501,31 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
             staking_table,
             state.isv_svn,
         );
+        genesis_state.utxo_coins = state.utxo_coins;
+        if let Some(exported) = conf.exported_state.as_ref() {
+            let mut kv_store = kv_store!(self);
+            for outputs in exported.tx_outputs.iter() {
+                chain_storage::insert_item(
+                    &mut kv_store,
+                    LookupItem::TxMetaSpent,
+                    outputs.txid,
+                    outputs.spent.clone(),
+                );
+                if let Some(no_of_outputs) = outputs.no_of_outputs {
+                    chain_storage::store_no_of_outputs(&mut kv_store, &outputs.txid, no_of_outputs);
+                }
+                if let Some(inclusion) = outputs.inclusion.as_ref() {
+                    chain_storage::store_tx_inclusion(&mut kv_store, &outputs.txid, inclusion);
+                }
+                if !outputs.sealed_log.is_empty() {
+                    chain_storage::store_sealed_log(
+                        &mut kv_store,
+                        &outputs.txid,
+                        &outputs.sealed_log,
+                    );
+                }
+            }
+        }
         chain_storage::store_genesis_state(
             &mut kv_store!(self),
             &genesis_state,
@@ -2,10 +2,12 @@ use std::mem;
 
 use super::ChainNodeApp;
 use crate::enclave_bridge::EnclaveProxy;
+use crate::metrics::METRICS;
 use abci::*;
 use chain_core::common::MerkleTree;
 use chain_core::compute_app_hash;
 use chain_core::tx::data::input::{TxoPointer, TxoSize};
+use chain_core::tx::data::output::TxInclusion;
 use chain_core::tx::data::TxId;
 use chain_core::tx::{TxAux, TxEnclaveAux, TxPublicAux};
 use chain_storage::buffer::{flush_storage, StoreKV};
@@ -13,18 +15,21 @@ use chain_storage::jellyfish::flush_stakings;
 use parity_scale_codec::Encode;
 
 /// Given a db and a DB transaction, it will go through TX inputs and mark them as spent
-/// in the TX_META storage and it will create a new entry for TX in TX_META with all outputs marked as unspent.
+/// in the TX_META storage and it will create a new entry for TX in TX_META with all outputs marked as unspent
+/// (together with the block it was included in).
 fn update_utxos_commit(
     inputs: &[TxoPointer],
     no_of_outputs: TxoSize,
     txid: TxId,
+    inclusion: &TxInclusion,
     db: &mut impl StoreKV,
 ) {
     chain_storage::spend_utxos(db, &inputs);
     chain_storage::create_utxo(db, no_of_outputs, &txid);
+    chain_storage::store_tx_inclusion(db, &txid, inclusion);
 }
 
-fn process_txs(delivered_txs: &[TxAux], db: &mut impl StoreKV) {
+fn process_txs(delivered_txs: &[TxAux], inclusion: &TxInclusion, db: &mut impl StoreKV) {
     for txaux in delivered_txs.iter() {
         let txid: TxId = txaux.tx_id();
         match &txaux {
@@ -36,7 +41,7 @@ fn process_txs(delivered_txs: &[TxAux], db: &mut impl StoreKV) {
                 no_of_outputs,
                 ..
             }) => {
-                update_utxos_commit(&inputs, *no_of_outputs, txid, db);
+                update_utxos_commit(&inputs, *no_of_outputs, txid, inclusion, db);
             }
             TxAux::EnclaveTx(TxEnclaveAux::DepositStakeTx { tx, .. }) => {
                 chain_storage::store_tx_body(db, &txid, &tx.encode());
@@ -58,6 +63,7 @@ fn process_txs(delivered_txs: &[TxAux], db: &mut impl StoreKV) {
                 chain_storage::store_tx_witness(db, &txid, &witness.encode());
                 // account should be already updated in deliver_tx
                 chain_storage::create_utxo(db, *no_of_outputs, &txid);
+                chain_storage::store_tx_inclusion(db, &txid, inclusion);
             }
             TxAux::PublicTx(TxPublicAux::UnjailTx(tx, witness)) => {
                 chain_storage::store_tx_body(db, &txid, &tx.encode());
@@ -89,7 +95,11 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
         let tree = MerkleTree::new(ids);
 
         if !self.delivered_txs.is_empty() {
-            process_txs(&self.delivered_txs, &mut kv_store!(self));
+            let inclusion = TxInclusion {
+                block_height: new_state.block_height,
+                block_time: new_state.block_time,
+            };
+            process_txs(&self.delivered_txs, &inclusion, &mut kv_store!(self));
         }
         if self.rewards_pool_updated {
             top_level.rewards_pool.last_block_height = new_state.last_block_height;
@@ -102,6 +112,10 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
                 .staking_version
                 .checked_add(1)
                 .expect("staking version overflow, no way to recover");
+            let _timer = METRICS
+                .storage_flush_duration
+                .with_label_values(&["staking"])
+                .start_timer();
             top_level.account_root = flush_stakings(
                 &mut kv_store!(self),
                 new_state.staking_version,
@@ -127,8 +141,16 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
         );
 
         // flush key-value storage
-        flush_storage(&mut self.storage, mem::take(&mut self.kv_buffer))
+        let storage = &mut self.storage;
+        let kv_buffer = mem::take(&mut self.kv_buffer);
+        METRICS
+            .storage_flush_duration
+            .with_label_values(&["kv"])
+            .observe_closure_duration(|| flush_storage(storage, kv_buffer))
             .expect("kv storage io error");
+        METRICS
+            .block_height
+            .set(new_state.last_block_height.value() as i64);
 
         resp.data = new_state.last_apphash.to_vec();
 
@@ -136,6 +158,8 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
         self.delivered_txs.clear();
         self.mempool_kv_buffer.clear();
         self.mempool_staking_buffer.clear();
+        self.prune_staking_mempool();
+        self.update_mempool_metrics();
         resp
     }
 }
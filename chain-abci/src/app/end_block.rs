@@ -2,6 +2,7 @@ use std::convert::TryInto;
 
 use crate::app::app_init::ChainNodeApp;
 use crate::enclave_bridge::EnclaveProxy;
+use crate::metrics::METRICS;
 use abci::{Event, Pair as KVPair, PubKey, RequestEndBlock, ResponseEndBlock, ValidatorUpdate};
 use chain_core::common::TendermintEventType;
 use chain_tx_filter::BlockFilter;
@@ -12,9 +13,13 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
     pub fn end_block_handler(&mut self, req: &RequestEndBlock) -> ResponseEndBlock {
         let mut resp = ResponseEndBlock::new();
         if !self.delivered_txs.is_empty() {
-            let end_block_resp = self
-                .tx_validator
-                .process_request(IntraEnclaveRequest::EndBlock);
+            let tx_validator = &mut self.tx_validator;
+            let end_block_resp = METRICS
+                .enclave_request_duration
+                .with_label_values(&["end_block"])
+                .observe_closure_duration(|| {
+                    tx_validator.process_request(IntraEnclaveRequest::EndBlock)
+                });
             if let Ok(IntraEnclaveResponseOk::EndBlock(maybe_filter)) = end_block_resp {
                 if let Some(raw_filter) = maybe_filter {
                     let filter = BlockFilter::from(&*raw_filter);
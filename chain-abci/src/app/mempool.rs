@@ -0,0 +1,182 @@
+//! Staking transactions queued in the mempool connection, per account and nonce.
+//!
+//! Tendermint's mempool is FIFO (the transactions that passed CheckTx are included in blocks
+//! in the order they were checked), so CheckTx only accepts staking transactions
+//! that can be executed against the mempool state, i.e. the ones with the account's next nonce.
+//! Transactions with later nonces are rejected (with the `StakingTxQueued` code), but kept
+//! here (bounded per account) until they are broadcast again after the preceding ones
+//! were accepted. A queued transaction can be replaced by another one with the same nonce
+//! that pays a higher fee (the replaced one is then rejected when it's broadcast again).
+//! Accepted transactions can't be replaced, as Tendermint keeps them in its mempool.
+use std::collections::BTreeMap;
+
+use super::{BufferType, ChainNodeApp};
+use crate::enclave_bridge::EnclaveProxy;
+use crate::metrics::METRICS;
+use crate::storage::{process_public_tx, public_tx_fee, verify_public_tx_signer, TxPublicAction};
+use crate::tx_error::{PublicTxError, TxError};
+use chain_core::init::coin::Coin;
+use chain_core::state::account::{Nonce, StakedStateAddress};
+use chain_core::tx::data::TxId;
+use chain_core::tx::TxPublicAux;
+use chain_storage::buffer::GetStaking;
+
+/// How many staking transactions (with nonces after the account's next one)
+/// can be queued per account
+pub const MAX_QUEUED_STAKING_TXS: Nonce = 16;
+
+/// Staking transaction queued in the mempool connection
+#[derive(Debug, Clone)]
+pub struct PendingStakingTx {
+    pub txid: TxId,
+    pub tx: TxPublicAux,
+    /// the fee paid by the transaction (used for replacements)
+    pub fee: Coin,
+}
+
+/// Queued staking transactions by account and nonce
+#[derive(Debug, Default)]
+pub struct StakingMempool {
+    pending: BTreeMap<StakedStateAddress, BTreeMap<Nonce, PendingStakingTx>>,
+}
+
+impl StakingMempool {
+    pub fn get(&self, address: &StakedStateAddress, nonce: Nonce) -> Option<&PendingStakingTx> {
+        self.pending.get(address).and_then(|txs| txs.get(&nonce))
+    }
+
+    /// inserts or replaces the queued transaction with the same nonce
+    pub fn insert(&mut self, pending: PendingStakingTx) {
+        self.pending
+            .entry(pending.tx.staking_address())
+            .or_default()
+            .insert(pending.tx.nonce(), pending);
+    }
+
+    /// removes the queued transaction (if it has the given id)
+    pub fn remove(&mut self, address: &StakedStateAddress, nonce: Nonce, txid: &TxId) {
+        if let Some(txs) = self.pending.get_mut(address) {
+            if txs.get(&nonce).map(|pending| &pending.txid) == Some(txid) {
+                txs.remove(&nonce);
+            }
+            if txs.is_empty() {
+                self.pending.remove(address);
+            }
+        }
+    }
+
+    /// removes the queued transactions with nonces before the given one
+    pub fn prune(&mut self, address: &StakedStateAddress, next_nonce: Nonce) {
+        if let Some(txs) = self.pending.get_mut(address) {
+            *txs = txs.split_off(&next_nonce);
+            if txs.is_empty() {
+                self.pending.remove(address);
+            }
+        }
+    }
+
+    pub fn addresses(&self) -> Vec<StakedStateAddress> {
+        self.pending.keys().copied().collect()
+    }
+
+    /// number of queued transactions
+    pub fn len(&self) -> usize {
+        self.pending.values().map(BTreeMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
+    /// the account's next nonce in the mempool state
+    fn mempool_nonce(&self, address: &StakedStateAddress) -> Nonce {
+        let state = self.mempool_state.as_ref().expect("expect mempool_state");
+        staking_getter!(self, state.staking_version, BufferType::Mempool)
+            .get_or_default(address)
+            .nonce
+    }
+
+    /// CheckTx of staking transactions: executes the ones with the account's next nonce,
+    /// queues (or replaces) the ones with later nonces
+    pub fn check_staking_tx(&mut self, tx: &TxPublicAux, tx_len: usize) -> Result<(), TxError> {
+        let extra_info = self.tx_extra_info(tx_len);
+        let address = verify_public_tx_signer(&extra_info, tx)?;
+        let fee = public_tx_fee(&extra_info, tx)?.to_coin();
+        let txid = tx.tx_id();
+        let nonce = tx.nonce();
+        let expected = self.mempool_nonce(&address);
+        if nonce < expected || nonce > expected.saturating_add(MAX_QUEUED_STAKING_TXS) {
+            return Err(PublicTxError::IncorrectNonce {
+                expected,
+                actual: nonce,
+            }
+            .into());
+        }
+        if let Some(queued) = self.staking_mempool.get(&address, nonce) {
+            if queued.txid != txid && fee <= queued.fee {
+                return Err(PublicTxError::ReplacementUnderpriced {
+                    pending_fee: queued.fee,
+                    fee,
+                }
+                .into());
+            }
+        }
+        if nonce > expected {
+            self.staking_mempool.insert(PendingStakingTx {
+                txid,
+                tx: tx.clone(),
+                fee,
+            });
+            return Err(PublicTxError::Queued {
+                expected,
+                actual: nonce,
+            }
+            .into());
+        }
+        self.execute_staking_tx(tx, tx_len)?;
+        self.staking_mempool.prune(&address, nonce + 1);
+        Ok(())
+    }
+
+    fn execute_staking_tx(&mut self, tx: &TxPublicAux, tx_len: usize) -> Result<(), PublicTxError> {
+        let extra_info = self.tx_extra_info(tx_len);
+        let state = self.mempool_state.as_mut().expect("expect mempool_state");
+        let action = process_public_tx(
+            &mut staking_store!(self, state.staking_version, BufferType::Mempool),
+            &mut state.staking_table,
+            state.enclave_isv_svn,
+            &extra_info,
+            tx,
+        )?;
+        if let TxPublicAction::NodeJoin { isv_svn, .. } = action {
+            state.enclave_isv_svn = isv_svn;
+        }
+        Ok(())
+    }
+
+    /// updates the sizes of the mempool buffers in the metrics
+    pub fn update_mempool_metrics(&self) {
+        METRICS
+            .mempool_buffer_size
+            .with_label_values(&["staking"])
+            .set(self.mempool_staking_buffer.len() as i64);
+        METRICS
+            .mempool_buffer_size
+            .with_label_values(&["kv"])
+            .set(self.mempool_kv_buffer.len() as i64);
+        METRICS
+            .pending_staking_txs
+            .set(self.staking_mempool.len() as i64);
+    }
+
+    /// After commit (with reset mempool state): drops the queued transactions
+    /// with committed nonces
+    pub fn prune_staking_mempool(&mut self) {
+        for address in self.staking_mempool.addresses() {
+            let committed_nonce = self.mempool_nonce(&address);
+            self.staking_mempool.prune(&address, committed_nonce);
+        }
+    }
+}
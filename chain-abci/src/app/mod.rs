@@ -4,6 +4,7 @@ mod macros;
 mod app_init;
 mod commit;
 mod end_block;
+pub mod mempool;
 mod query;
 mod rewards;
 mod staking_event;
@@ -23,6 +24,7 @@ pub use self::app_init::{
 use crate::app::staking_event::StakingEvent;
 use crate::app::validate_tx::ResponseWithCodeAndLog;
 use crate::enclave_bridge::EnclaveProxy;
+use crate::metrics::METRICS;
 use crate::staking::RewardsDistribution;
 use crate::storage::{TxAction, TxEnclaveAction, TxPublicAction};
 use chain_core::common::{TendermintEventKey, TendermintEventType, Timespec};
@@ -85,6 +87,10 @@ impl<T: EnclaveProxy + 'static> abci::Application for ChainNodeApp<T> {
     /// the state of the app.
     fn query(&mut self, _req: &RequestQuery) -> ResponseQuery {
         info!("received query request");
+        let _timer = METRICS
+            .abci_request_duration
+            .with_label_values(&["query"])
+            .start_timer();
         ChainNodeApp::query_handler(self, _req)
     }
 
@@ -93,17 +99,24 @@ impl<T: EnclaveProxy + 'static> abci::Application for ChainNodeApp<T> {
     /// on the deliver_tx call below.
     fn check_tx(&mut self, req: &RequestCheckTx) -> ResponseCheckTx {
         info!("received checktx request");
+        let _timer = METRICS
+            .abci_request_duration
+            .with_label_values(&["check_tx"])
+            .start_timer();
         let mut resp = ResponseCheckTx::new();
-        match self.process_tx(req, BufferType::Mempool) {
+        match self.check_mempool_tx(req) {
             Ok(_) => {
                 resp.set_code(0);
             }
             Err(msg) => {
-                resp.set_code(1);
+                let rejection = msg.rejection();
+                METRICS.record_rejection("check_tx", &rejection);
+                resp.set_rejection(&rejection);
                 resp.add_log(&msg.to_string());
                 log::warn!("check tx failed: {}", msg);
             }
         }
+        self.update_mempool_metrics();
         resp
     }
 
@@ -122,6 +135,10 @@ impl<T: EnclaveProxy + 'static> abci::Application for ChainNodeApp<T> {
     /// commit()
     fn begin_block(&mut self, req: &RequestBeginBlock) -> ResponseBeginBlock {
         info!("received beginblock request");
+        let _timer = METRICS
+            .abci_request_duration
+            .with_label_values(&["begin_block"])
+            .start_timer();
         // TODO: Check security implications once https://github.com/tendermint/tendermint/issues/2653 is closed
         let header = req
             .header
@@ -250,7 +267,16 @@ impl<T: EnclaveProxy + 'static> abci::Application for ChainNodeApp<T> {
     /// state transistion.
     fn deliver_tx(&mut self, req: &RequestDeliverTx) -> ResponseDeliverTx {
         info!("received delivertx request");
+        let _timer = METRICS
+            .abci_request_duration
+            .with_label_values(&["deliver_tx"])
+            .start_timer();
         let mut resp = ResponseDeliverTx::new();
+        if let Ok(TxAux::PublicTx(tx)) = TxAux::decode(&mut &req.tx[..]) {
+            // a queued transaction may be included in a block (e.g. accepted by another node)
+            self.staking_mempool
+                .remove(&tx.staking_address(), tx.nonce(), &tx.tx_id());
+        }
         let result = self.process_tx(req, BufferType::Consensus);
         match result {
             Ok((txaux, tx_action)) => {
@@ -274,7 +300,9 @@ impl<T: EnclaveProxy + 'static> abci::Application for ChainNodeApp<T> {
                 }
             }
             Err(msg) => {
-                resp.set_code(1);
+                let rejection = msg.rejection();
+                METRICS.record_rejection("deliver_tx", &rejection);
+                resp.set_rejection(&rejection);
                 resp.add_log(&msg.to_string());
                 log::error!("deliver tx failed: {}", msg);
             }
@@ -285,12 +313,20 @@ impl<T: EnclaveProxy + 'static> abci::Application for ChainNodeApp<T> {
     /// Consensus Connection: Called at the end of the block. used to update the validator set.
     fn end_block(&mut self, req: &RequestEndBlock) -> ResponseEndBlock {
         info!("received endblock request");
+        let _timer = METRICS
+            .abci_request_duration
+            .with_label_values(&["end_block"])
+            .start_timer();
         ChainNodeApp::end_block_handler(self, req)
     }
 
     /// Consensus Connection: Commit the block with the latest state from the application.
     fn commit(&mut self, _req: &RequestCommit) -> ResponseCommit {
         info!("received commit request");
+        let _timer = METRICS
+            .abci_request_duration
+            .with_label_values(&["commit"])
+            .start_timer();
         let resp = ChainNodeApp::commit_handler(self, _req);
 
         if sanity_check_enabled() {
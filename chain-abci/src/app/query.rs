@@ -1,6 +1,7 @@
 use std::convert::{TryFrom, TryInto};
 
 use super::ChainNodeApp;
+use crate::enclave_bridge::persistence::TdbeProgress;
 use crate::enclave_bridge::EnclaveProxy;
 use abci::*;
 use chain_core::common::{MerkleTree, Proof as MerkleProof, H256, HASH_SIZE_256};
@@ -71,6 +72,33 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
         None
     }
 
+    /// Sets an error in the response if TDBE is still fetching historical sealed transactions
+    /// (which the query needs) or it stopped before finishing that
+    fn refuse_during_tdbe_catch_up(&self, resp: &mut ResponseQuery) -> bool {
+        let block = |last_fetched_block: Option<u32>| {
+            last_fetched_block.map_or_else(|| "none".to_owned(), |block| block.to_string())
+        };
+        match self.tdbe_status.progress() {
+            TdbeProgress::CatchingUp { last_fetched_block } => {
+                resp.log += &format!(
+                    "TDBE catch-up in progress (last fetched block: {})",
+                    block(last_fetched_block)
+                );
+                resp.code = 5;
+                true
+            }
+            TdbeProgress::Failed { last_fetched_block } => {
+                resp.log += &format!(
+                    "TDBE catch-up failed (last fetched block: {}), the node needs to be restarted",
+                    block(last_fetched_block)
+                );
+                resp.code = 5;
+                true
+            }
+            TdbeProgress::CaughtUp { .. } => false,
+        }
+    }
+
     /// Responds to query requests -- note that path is hex-encoded in the original request on the client side
     /// e.g. "store" == 0x73746f7265.
     pub fn query_handler(&self, _req: &RequestQuery) -> ResponseQuery {
@@ -88,7 +116,9 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
         match _req.path.as_ref() {
             "txquery" => match &self.tx_query_address {
                 Some(addr) => {
-                    resp.value = addr.clone().into_bytes();
+                    if !self.refuse_during_tdbe_catch_up(&mut resp) {
+                        resp.value = addr.clone().into_bytes();
+                    }
                 }
                 None => {
                     resp.code = 1;
@@ -258,13 +288,30 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
                     .into_bytes();
             }
             "sealed" => {
-                self.lookup(
-                    &mut resp,
-                    LookupItem::TxSealed,
-                    &_req.data[..],
-                    "sealed log not found",
-                );
+                if let Some(key) = get_key(&mut resp, &_req.data[..]) {
+                    match self.storage.lookup_item(LookupItem::TxSealed, &key) {
+                        Some(sealed_log) => {
+                            resp.value = sealed_log;
+                        }
+                        None => {
+                            // it may not have been fetched yet
+                            if !self.refuse_during_tdbe_catch_up(&mut resp) {
+                                resp.log += "sealed log not found";
+                                resp.code = 1;
+                            }
+                        }
+                    }
+                }
             }
+            "nodejoin" => match chain_storage::get_node_join_tx(&self.storage) {
+                Some(tx_payload) => {
+                    resp.value = tx_payload;
+                }
+                None => {
+                    resp.log += "node join request not constructed (yet)";
+                    resp.code = 1;
+                }
+            },
             _ => {
                 resp.log += "invalid path";
                 resp.code = 1;
@@ -2,6 +2,7 @@ use std::cmp::min;
 
 use crate::app::ChainNodeApp;
 use crate::enclave_bridge::EnclaveProxy;
+use crate::metrics::METRICS;
 use chain_core::fixed::monetary_expansion;
 use chain_core::init::coin::Coin;
 use chain_core::state::account::StakedStateAddress;
@@ -36,6 +37,7 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
         {
             return None;
         }
+        let _timer = METRICS.rewards_distribution_duration.start_timer();
         top_level.rewards_pool.last_distribution_time = state.block_time;
         self.rewards_pool_updated = true;
 
@@ -75,6 +77,13 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
         );
 
         top_level.rewards_pool.period_bonus = remainer;
+        METRICS.rewards_minted.inc_by(minted.into());
+        METRICS.rewards_distributed.inc_by(
+            reward_distribution
+                .iter()
+                .map(|(_, amount)| u64::from(*amount))
+                .sum(),
+        );
         Some((reward_distribution, minted))
     }
 }
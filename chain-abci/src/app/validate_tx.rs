@@ -6,9 +6,10 @@ use crate::storage::{
 use crate::tx_error::TxError;
 use abci::*;
 use chain_core::tx::data::TxId;
+use chain_core::tx::rejection::{TxRejection, REJECTION_CODESPACE};
 use chain_core::tx::TxAux;
 use chain_storage::buffer::{StoreKV, StoreStaking};
-use parity_scale_codec::Decode;
+use parity_scale_codec::{Decode, Encode};
 
 /// Wrapper to abstract over CheckTx and DeliverTx requests
 pub trait RequestWithTx {
@@ -38,6 +39,8 @@ impl RequestWithTx for RequestDeliverTx {
 pub trait ResponseWithCodeAndLog {
     fn set_code(&mut self, _: u32);
     fn add_log(&mut self, _: &str);
+    /// sets the code, codespace and (SCALE-encoded) data of a rejected transaction
+    fn set_rejection(&mut self, _: &TxRejection);
 }
 
 impl ResponseWithCodeAndLog for ResponseCheckTx {
@@ -48,6 +51,12 @@ impl ResponseWithCodeAndLog for ResponseCheckTx {
     fn add_log(&mut self, entry: &str) {
         self.log += entry;
     }
+
+    fn set_rejection(&mut self, rejection: &TxRejection) {
+        self.code = rejection.code.code();
+        self.codespace = REJECTION_CODESPACE.to_owned();
+        self.data = rejection.encode();
+    }
 }
 
 impl ResponseWithCodeAndLog for ResponseDeliverTx {
@@ -58,9 +67,24 @@ impl ResponseWithCodeAndLog for ResponseDeliverTx {
     fn add_log(&mut self, entry: &str) {
         self.log += entry;
     }
+
+    fn set_rejection(&mut self, rejection: &TxRejection) {
+        self.code = rejection.code.code();
+        self.codespace = REJECTION_CODESPACE.to_owned();
+        self.data = rejection.encode();
+    }
 }
 
 impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
+    /// CheckTx: staking transactions go through the pending staking transactions
+    /// (that allow nonce gaps and replacements), others are executed against the mempool state
+    pub fn check_mempool_tx(&mut self, req: &RequestCheckTx) -> Result<(), TxError> {
+        match TxAux::decode(&mut req.tx())? {
+            TxAux::PublicTx(tx) => self.check_staking_tx(&tx, req.tx().len()),
+            _ => self.process_tx(req, BufferType::Mempool).map(|_| ()),
+        }
+    }
+
     pub fn process_tx(
         &mut self,
         req: &impl RequestWithTx,
@@ -2,6 +2,7 @@ use crate::app::ChainNodeState;
 use crate::enclave_bridge::EnclaveProxy;
 use chain_core::state::account::StakedState;
 use chain_core::state::account::StakedStateOpWitness;
+use chain_core::tx::data::output::TxInclusion;
 use chain_core::tx::data::TxId;
 use chain_storage::buffer::Get;
 use chain_storage::jellyfish::StakingGetter;
@@ -59,6 +60,17 @@ impl<T: EnclaveProxy> TxValidationServer<T> {
         Some(result)
     }
 
+    /// blocks the transactions were included in (if recorded)
+    fn lookup_inclusion<I>(&self, inputs: I) -> Vec<Option<TxInclusion>>
+    where
+        I: IntoIterator<Item = TxId>,
+    {
+        inputs
+            .into_iter()
+            .map(|input| chain_storage::lookup_tx_inclusion(&self.storage, &input))
+            .collect()
+    }
+
     fn lookup_state(
         &self,
         txid: &TxId,
@@ -84,9 +96,12 @@ impl<T: EnclaveProxy> TxValidationServer<T> {
                     }
                     Ok(EnclaveRequest::EncryptTx(req)) => {
                         let result = {
-                            let tx_inputs = match req.tx_inputs {
-                                Some(inputs) => self.lookup_txids(inputs.iter().map(|x| x.id)),
-                                _ => None,
+                            let (tx_inputs, tx_inputs_inclusion) = match req.tx_inputs {
+                                Some(inputs) => (
+                                    self.lookup_txids(inputs.iter().map(|x| x.id)),
+                                    self.lookup_inclusion(inputs.iter().map(|x| x.id)),
+                                ),
+                                _ => (None, vec![]),
                             };
                             match self.storage.get_last_app_state() {
                                 Some(state) => {
@@ -118,6 +133,7 @@ impl<T: EnclaveProxy> TxValidationServer<T> {
                                         txid: req.txid,
                                         sealed_enc_request: req.sealed_enc_request,
                                         tx_inputs,
+                                        tx_inputs_inclusion,
                                         info,
                                         account,
                                     };
@@ -1,10 +1,9 @@
 use std::{
-    collections::HashMap,
     future::Future,
-    io::{Cursor, Seek, SeekFrom},
+    io::{self, Cursor, Seek, SeekFrom},
     os::unix::net::UnixStream,
     pin::Pin,
-    sync::Arc,
+    sync::{Arc, Mutex},
     thread,
     thread::JoinHandle,
 };
@@ -20,13 +19,13 @@ use chain_core::tx::data::TxId;
 use chain_storage::ReadOnlyStorage;
 use enclave_protocol::{
     codec::{StreamRead, StreamWrite},
-    tdbe_protocol::PersistenceCommand,
     EnclaveRequest, EnclaveResponse, SealedLog,
 };
 use ra_sp_server::config::SpRaConfig;
 
 use crate::enclave_bridge::{
     edp::{UserCallListener, UserCallStream},
+    persistence::{monitor_tdbe, TdbePersistence, TdbeStatus},
     TdbeConfig,
 };
 
@@ -34,8 +33,13 @@ use crate::enclave_bridge::{
 pub struct TdbeApp {
     /// UDS to connect to `chain-abci`
     chain_abci_stream: UnixStream,
-    /// UDS to persist data to `chain-storage`
-    persistence_stream: UnixStream,
+    /// UDS to persist data to `chain-storage` (handed over to enclave when it connects, so that
+    /// the persistence thread sees the stream closed once enclave stops)
+    persistence_stream: Mutex<Option<UnixStream>>,
+    /// Thread handling the commands sent over `persistence_stream`
+    persistence_thread: Option<JoinHandle<()>>,
+    /// Progress of the initial data fetch
+    status: TdbeStatus,
     /// `ra-sp-server` address for remote attestation. E.g. `0.0.0.0:8989`
     /// TODO:  Replace it with a local UDS (using `chain-abci` as launcher).
     sp_address: String,
@@ -49,29 +53,39 @@ pub struct TdbeApp {
 
 impl TdbeApp {
     /// Creates a new instance of TDBE app
+    /// (`status` is updated as the persistence commands from the enclave are handled)
     pub fn new(
         tdbe_config: &TdbeConfig,
         ra_config: &SpRaConfig,
-        _storage: Arc<dyn KeyValueDB>,
+        storage: Arc<dyn KeyValueDB>,
         tve_stream: UnixStream,
-    ) -> std::io::Result<Self> {
+        status: TdbeStatus,
+        chain_hex_id: u8,
+    ) -> io::Result<Self> {
         // - `chain_abci_stream` is passed to enclave. Encalve can send requests to chain-abci
         //   using this
         // - `chain_abci_receiver` listens to the requests sent by enclave and responds to them
-        let (chain_abci_stream, _chain_abci_receiver) = UnixStream::pair()?;
+        let (chain_abci_stream, chain_abci_receiver) = UnixStream::pair()?;
 
         // - `persistence_stream` is passed to enclave. Encalve can send requests to chain-storage
         //   using this
         // - `persistence_receiver` listens to the requests sent by enclave and responds to them
-        let (persistence_stream, _persistence_receiver) = UnixStream::pair()?;
+        let (persistence_stream, persistence_receiver) = UnixStream::pair()?;
 
-        // FIXME: spawn these when they actually do something
-        // spawn_chain_abci_thread(chain_abci_receiver, storage.clone());
-        // spawn_persistence_thread(persistence_receiver, storage);
+        spawn_chain_abci_thread(chain_abci_receiver, storage.clone());
+        let persistence = TdbePersistence::new(
+            storage,
+            status.clone(),
+            tdbe_config.node_join.clone(),
+            chain_hex_id,
+        );
+        let persistence_thread = thread::spawn(move || persistence.run(persistence_receiver));
 
         Ok(Self {
             chain_abci_stream,
-            persistence_stream,
+            persistence_stream: Mutex::new(Some(persistence_stream)),
+            persistence_thread: Some(persistence_thread),
+            status,
             sp_address: ra_config.address.clone(),
             remote_rpc_address: tdbe_config.remote_rpc_address.clone(),
             local_listen_address: tdbe_config.local_listen_address.clone(),
@@ -79,8 +93,15 @@ impl TdbeApp {
         })
     }
 
-    pub fn spawn(self) -> JoinHandle<()> {
-        thread::spawn(move || {
+    /// Runs enclave in a new thread; the returned thread monitors it and sets the status
+    /// to `TdbeProgress::Failed` if enclave stops before the initial data fetch finished
+    pub fn spawn(mut self) -> JoinHandle<()> {
+        let persistence_thread = self
+            .persistence_thread
+            .take()
+            .expect("TDBE app can only be spawned once");
+        let status = self.status.clone();
+        let tdbe_thread = thread::spawn(move || {
             let mut device = Device::new()
                 .expect("SGX device was not found")
                 .einittoken_provider(AesmClient::new())
@@ -96,11 +117,11 @@ impl TdbeApp {
                 .build(&mut device)
                 .expect("Failed to build enclave");
             enclave.run().expect("Failed to start enclave")
-        })
+        });
+        monitor_tdbe(tdbe_thread, persistence_thread, status)
     }
 }
 
-#[allow(dead_code)]
 fn spawn_chain_abci_thread(mut receiver: UnixStream, storage: Arc<dyn KeyValueDB>) {
     let _ = thread::spawn(move || {
         let storage = chain_storage::ReadOnlyStorage::new_db(storage);
@@ -134,35 +155,6 @@ fn get_sealed_tx_data(txids: Vec<TxId>, storage: &ReadOnlyStorage) -> Option<Vec
     Some(result)
 }
 
-/// FIXME: should this start a background thread if this is one-off and the main thread needs to wait for its completion?
-#[allow(dead_code)]
-fn spawn_persistence_thread(mut receiver: UnixStream, storage: Arc<dyn KeyValueDB>) {
-    let _ = thread::spawn(move || {
-        let mut storage = chain_storage::Storage::new_db(storage);
-        let mut buffer = HashMap::new();
-        let mut kvdb = chain_storage::buffer::BufferStore::new(&storage, &mut buffer);
-
-        while let Ok(persistence_command) = PersistenceCommand::read_from(&mut receiver) {
-            match persistence_command {
-                PersistenceCommand::Store {
-                    transaction_id,
-                    sealed_log,
-                } => chain_storage::store_sealed_log(&mut kvdb, &transaction_id, &sealed_log),
-                PersistenceCommand::Finish { last_fetched_block } => {
-                    chain_storage::set_last_fetched_block(&mut kvdb, last_fetched_block);
-                    break;
-                }
-                _ => {
-                    // FIXME
-                }
-            }
-        }
-
-        chain_storage::buffer::flush_storage(&mut storage, std::mem::take(&mut buffer))
-            .expect("Unable to flush storage");
-    });
-}
-
 impl UsercallExtension for TdbeApp {
     fn connect_stream<'future>(
         &'future self,
@@ -196,6 +188,22 @@ impl UsercallExtension for TdbeApp {
                         tokio::net::UnixStream::from_std(this.chain_abci_stream.try_clone()?)?;
                     Ok(Some(Box::new(stream)))
                 }
+                // Connects enclave to chain-storage
+                "persistence" => {
+                    let stream = this
+                        .persistence_stream
+                        .lock()
+                        .expect("persistence stream lock poisoned")
+                        .take()
+                        .ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::AlreadyExists,
+                                "TDBE persistence stream is already connected",
+                            )
+                        })?;
+                    let stream = tokio::net::UnixStream::from_std(stream)?;
+                    Ok(Some(Box::new(stream)))
+                }
                 // Connects enclave to ra-sp-server
                 "ra-sp-server" => {
                     let stream = TcpStream::connect(&this.sp_address).await?;
@@ -80,7 +80,11 @@ impl EnclaveProxy for MockClient {
                 // In mock mode, client will do the encryption on their own.
                 Err(chain_tx_validation::Error::EnclaveRejected)
             }
-            IntraEnclaveRequest::ValidateTx { request, tx_inputs } => {
+            IntraEnclaveRequest::ValidateTx {
+                request,
+                tx_inputs,
+                tx_inputs_inclusion,
+            } => {
                 let (tx, account, info) =
                     (request.tx.clone(), request.account.clone(), request.info);
 
@@ -112,7 +116,8 @@ impl EnclaveProxy for MockClient {
                 let plain_tx = decrypt(&payload)?;
                 match (tx, plain_tx) {
                     (TxEnclaveAux::TransferTx { .. }, PlainTxAux::TransferTx(maintx, witness)) => {
-                        let result = verify_transfer(&maintx, &witness, &info, inputs);
+                        let result =
+                            verify_transfer(&maintx, &witness, &info, inputs, &tx_inputs_inclusion);
                         match result {
                             Ok(fee) => {
                                 let txwo = TxWithOutputs::Transfer(maintx);
@@ -130,7 +135,13 @@ impl EnclaveProxy for MockClient {
                         TxEnclaveAux::DepositStakeTx { tx, .. },
                         PlainTxAux::DepositStakeTx(witness),
                     ) => {
-                        let result = verify_bonded_deposit_core(&tx, &witness, &info, inputs);
+                        let result = verify_bonded_deposit_core(
+                            &tx,
+                            &witness,
+                            &info,
+                            inputs,
+                            &tx_inputs_inclusion,
+                        );
                         match result {
                             Ok(input_coins) => {
                                 Ok(IntraEnclaveResponseOk::DepositStakeTx { input_coins })
@@ -8,6 +8,10 @@ pub mod mock;
 #[cfg(all(not(feature = "mock-enclave"), feature = "edp", target_os = "linux"))]
 pub mod edp;
 
+pub mod persistence;
+
+use persistence::NodeJoinConfig;
+
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct TdbeConfig {
     /// Optional TM RPC address of another TDBE server from where to fetch data
@@ -17,6 +21,9 @@ pub struct TdbeConfig {
     /// External TDBE server address, used by remote nodes to send RPC requests. E.g.
     /// `<public_ip>:<public_port>`
     pub external_listen_address: String,
+    /// Optional information for constructing the node join request from the payloads TDBE sends
+    #[serde(default)]
+    pub node_join: Option<NodeJoinConfig>,
 }
 
 /// Abstracts over communication with an external part that does enclave calls
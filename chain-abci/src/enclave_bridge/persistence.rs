@@ -0,0 +1,263 @@
+//! Handling of `PersistenceCommand`s sent by TDBE.
+//! TDBE runs concurrently with chain-abci: while it's fetching and sealing historical transactions,
+//! chain-abci serves everything except the queries that need that (sealed) data.
+use std::io::Read;
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
+
+use kvdb::KeyValueDB;
+use log::{error, info, warn};
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+use chain_core::state::account::{
+    ConfidentialInit, CouncilNodeMeta, MLSInit, NodeCommonInfo, NodeMetadata, Nonce,
+    StakedStateAddress, StakedStateOpAttributes,
+};
+use chain_core::state::tendermint::TendermintValidatorPubKey;
+use chain_core::state::validator::NodeJoinRequestTx;
+use chain_storage::buffer::{flush_storage, BufferStore, GetKV, KVBuffer};
+use chain_storage::jellyfish::get_with_proof;
+use chain_storage::Storage;
+use enclave_protocol::{codec::StreamRead, tdbe_protocol::PersistenceCommand};
+
+use crate::app::ChainNodeState;
+
+/// how many sealed logs are buffered before they are written to the storage
+const SEALED_LOG_FLUSH_INTERVAL: usize = 1000;
+
+/// Progress of the initial data fetch done by TDBE
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TdbeProgress {
+    /// TDBE is fetching historical sealed transactions
+    /// (`last_fetched_block` is the one from a previous finished fetch, if any)
+    CatchingUp { last_fetched_block: Option<u32> },
+    /// TDBE finished fetching (or there is nothing to fetch, e.g. with the mock enclave)
+    CaughtUp { last_fetched_block: Option<u32> },
+    /// TDBE stopped before it finished fetching
+    /// (restarting the node resumes the fetch after `last_fetched_block`)
+    Failed { last_fetched_block: Option<u32> },
+}
+
+/// TDBE progress shared between the persistence thread and the ABCI app
+#[derive(Debug, Clone)]
+pub struct TdbeStatus(Arc<RwLock<TdbeProgress>>);
+
+impl Default for TdbeStatus {
+    fn default() -> Self {
+        Self::new(TdbeProgress::CaughtUp {
+            last_fetched_block: None,
+        })
+    }
+}
+
+impl TdbeStatus {
+    pub fn new(progress: TdbeProgress) -> Self {
+        TdbeStatus(Arc::new(RwLock::new(progress)))
+    }
+
+    /// TDBE was started to fetch data; the progress so far is taken from `LAST_FETCHED_BLOCK_KEY`
+    pub fn catching_up(db: &impl GetKV) -> Self {
+        Self::new(TdbeProgress::CatchingUp {
+            last_fetched_block: chain_storage::get_last_fetched_block(db),
+        })
+    }
+
+    pub fn progress(&self) -> TdbeProgress {
+        *self.0.read().expect("TDBE status lock poisoned")
+    }
+
+    pub fn is_catching_up(&self) -> bool {
+        match self.progress() {
+            TdbeProgress::CatchingUp { .. } => true,
+            TdbeProgress::CaughtUp { .. } | TdbeProgress::Failed { .. } => false,
+        }
+    }
+
+    fn set(&self, progress: TdbeProgress) {
+        *self.0.write().expect("TDBE status lock poisoned") = progress;
+    }
+
+    /// TDBE stopped: an unfinished fetch is marked as failed (returns true in that case)
+    fn tdbe_stopped(&self) -> bool {
+        let mut progress = self.0.write().expect("TDBE status lock poisoned");
+        match *progress {
+            TdbeProgress::CatchingUp { last_fetched_block } => {
+                *progress = TdbeProgress::Failed { last_fetched_block };
+                true
+            }
+            TdbeProgress::CaughtUp { .. } | TdbeProgress::Failed { .. } => false,
+        }
+    }
+}
+
+/// Waits until the TDBE thread stops and the persistence thread has handled the remaining
+/// commands (it stops once the enclave's end of the persistence stream is closed);
+/// if the initial data fetch didn't finish by then, `status` is set to `TdbeProgress::Failed`
+pub fn monitor_tdbe(
+    tdbe_thread: JoinHandle<()>,
+    persistence_thread: JoinHandle<()>,
+    status: TdbeStatus,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let tdbe_result = tdbe_thread.join();
+        if persistence_thread.join().is_err() {
+            error!("TDBE persistence thread panicked");
+        }
+        match tdbe_result {
+            Ok(()) => warn!("TDBE stopped"),
+            Err(_) => error!("TDBE thread panicked"),
+        }
+        if status.tdbe_stopped() {
+            error!("TDBE stopped before the initial data fetch finished, restart the node to resume it");
+        }
+    })
+}
+
+/// Information of this node for constructing `NodeJoinRequestTx` from the TDBE payloads
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NodeJoinConfig {
+    /// staking address with the bonded stake (the transaction needs to be signed with its key)
+    pub staking_address: StakedStateAddress,
+    /// name / moniker
+    pub name: String,
+    /// optional security@... email address
+    #[serde(default)]
+    pub security_contact: Option<String>,
+    /// Tendermint consensus validator-associated public key
+    pub consensus_pubkey: TendermintValidatorPubKey,
+}
+
+/// Persists the data sent by TDBE
+pub struct TdbePersistence {
+    storage: Storage,
+    buffer: KVBuffer,
+    buffered_logs: usize,
+    status: TdbeStatus,
+    node_join: Option<NodeJoinConfig>,
+    chain_hex_id: u8,
+}
+
+impl TdbePersistence {
+    pub fn new(
+        db: Arc<dyn KeyValueDB>,
+        status: TdbeStatus,
+        node_join: Option<NodeJoinConfig>,
+        chain_hex_id: u8,
+    ) -> Self {
+        Self {
+            storage: Storage::new_db(db),
+            buffer: KVBuffer::new(),
+            buffered_logs: 0,
+            status,
+            node_join,
+            chain_hex_id,
+        }
+    }
+
+    /// Handles the commands until the stream is closed (or a command can't be decoded)
+    pub fn run(mut self, mut stream: impl Read) {
+        while let Ok(command) = PersistenceCommand::read_from(&mut stream) {
+            self.handle(command);
+        }
+        self.flush();
+    }
+
+    pub fn handle(&mut self, command: PersistenceCommand) {
+        match command {
+            PersistenceCommand::Store {
+                transaction_id,
+                sealed_log,
+            } => {
+                chain_storage::store_sealed_log(
+                    &mut BufferStore::new(&self.storage, &mut self.buffer),
+                    &transaction_id,
+                    &sealed_log,
+                );
+                self.buffered_logs += 1;
+                if self.buffered_logs >= SEALED_LOG_FLUSH_INTERVAL {
+                    self.flush();
+                }
+            }
+            PersistenceCommand::Finish { last_fetched_block } => {
+                chain_storage::set_last_fetched_block(
+                    &mut BufferStore::new(&self.storage, &mut self.buffer),
+                    last_fetched_block,
+                );
+                self.flush();
+                self.status.set(TdbeProgress::CaughtUp {
+                    last_fetched_block: Some(last_fetched_block),
+                });
+                info!(
+                    "TDBE finished fetching sealed transactions up to block {}",
+                    last_fetched_block
+                );
+            }
+            PersistenceCommand::NodeJoin { add, commit } => match self.node_join_tx(add, commit) {
+                Some(tx) => {
+                    chain_storage::set_node_join_tx(
+                        &mut BufferStore::new(&self.storage, &mut self.buffer),
+                        &tx.encode(),
+                    );
+                    self.flush();
+                    info!("node join request ready to be signed: {}", tx);
+                }
+                None => {
+                    error!("TDBE sent node join payloads, but `node_join` is not configured");
+                }
+            },
+            PersistenceCommand::SealEnclaveState { sealed_state } => {
+                chain_storage::set_sealed_enclave_state(
+                    &mut BufferStore::new(&self.storage, &mut self.buffer),
+                    &sealed_state,
+                );
+                self.flush();
+                info!("stored sealed enclave state");
+            }
+        }
+    }
+
+    fn node_join_tx(&self, add: Vec<u8>, commit: Vec<u8>) -> Option<NodeJoinRequestTx> {
+        let config = self.node_join.as_ref()?;
+        let node_meta = NodeMetadata::CouncilNode(CouncilNodeMeta {
+            node_info: NodeCommonInfo {
+                name: config.name.clone(),
+                security_contact: config.security_contact.clone(),
+                confidential_init: ConfidentialInit {
+                    init_payload: MLSInit::NodeJoin { add, commit },
+                },
+            },
+            consensus_pubkey: config.consensus_pubkey.clone(),
+        });
+        Some(NodeJoinRequestTx::new(
+            self.committed_nonce(&config.staking_address),
+            config.staking_address,
+            StakedStateOpAttributes::new(self.chain_hex_id),
+            node_meta,
+        ))
+    }
+
+    /// nonce of the staked state in the last committed state
+    fn committed_nonce(&self, address: &StakedStateAddress) -> Nonce {
+        let staking_version = self.storage.get_last_app_state().map(|data| {
+            ChainNodeState::decode(&mut data.as_slice())
+                .expect("deserialize app state")
+                .staking_version
+        });
+        let staking =
+            staking_version.and_then(|version| get_with_proof(&self.storage, version, address).0);
+        match staking {
+            Some(staking) => staking.nonce,
+            None => {
+                warn!("staked state {} not found, node join uses nonce 0", address);
+                0
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        flush_storage(&mut self.storage, std::mem::take(&mut self.buffer))
+            .expect("Unable to flush storage");
+        self.buffered_logs = 0;
+    }
+}
@@ -0,0 +1,420 @@
+//! Offline inspection of the committed chain-abci storage (`chain-abci inspect`)
+use std::collections::BTreeMap;
+
+use bit_vec::BitVec;
+use parity_scale_codec::Decode;
+use serde::Serialize;
+
+use crate::app::ChainNodeState;
+use crate::liveness::LivenessTracker;
+use crate::staking::{CouncilNodeMetadata, StakingTable};
+use chain_core::common::{MerkleTree, H256};
+use chain_core::compute_app_hash;
+use chain_core::init::coin::{Coin, CoinError};
+use chain_core::init::config::{ExportedState, ExportedTxOutputs, InitConfig, NetworkParameters};
+use chain_core::state::account::{NodeState, StakedState, StakedStateAddress};
+use chain_core::state::tendermint::{BlockHeight, TendermintVotePower};
+use chain_core::state::ChainState;
+use chain_core::tx::data::input::TxoSize;
+use chain_core::tx::data::output::TxInclusion;
+use chain_core::tx::data::TxId;
+use chain_storage::buffer::{Get, GetKV};
+use chain_storage::jellyfish::{
+    compute_staking_root, iter_stakings, sum_staking_coins, StakingGetter, Version,
+};
+use chain_storage::{LookupItem, ReadOnlyStorage};
+
+#[derive(thiserror::Error, Debug)]
+pub enum InspectError {
+    #[error("no committed app state found (was init chain executed?)")]
+    NoAppState,
+    #[error("decode stored data failed: {0}")]
+    Decode(#[from] parity_scale_codec::Error),
+    #[error("no staking version stored for block height {0}")]
+    NoStakingVersion(BlockHeight),
+    #[error("transaction metadata not found")]
+    TxNotFound,
+    #[error("block height {requested} is after the last committed one ({last})")]
+    ExportHeight {
+        requested: BlockHeight,
+        last: BlockHeight,
+    },
+    #[error(
+        "no top level state stored for block height {0} (only stored with a tx query address)"
+    )]
+    NoHistoricalState(BlockHeight),
+    #[error(
+        "the UTXOs changed after block height {0}, only the last committed state can be exported"
+    )]
+    UtxosChanged(BlockHeight),
+    #[error("staked state of {0} not found")]
+    StakingNotFound(StakedStateAddress),
+    #[error("validator {0} is not a council node")]
+    NotCouncilNode(StakedStateAddress),
+    #[error("invalid coins: {0}")]
+    Coin(#[from] CoinError),
+}
+
+/// The last committed application state
+pub fn last_state(storage: &impl GetKV) -> Result<ChainNodeState, InspectError> {
+    let data = chain_storage::get_last_app_state(storage).ok_or(InspectError::NoAppState)?;
+    let mut state = ChainNodeState::decode(&mut data.as_slice())?;
+    // populate the indexing structures in staking table
+    state.staking_table.initialize(
+        &StakingGetter::new(storage, state.staking_version),
+        state
+            .top_level
+            .network_params
+            .get_required_council_node_stake(),
+    );
+    Ok(state)
+}
+
+/// staking merkle trie version at the block height (the last committed one if not provided)
+pub fn staking_version(
+    storage: &impl GetKV,
+    height: Option<BlockHeight>,
+) -> Result<Version, InspectError> {
+    match height {
+        Some(height) => chain_storage::get_historical_staking_version(storage, height)
+            .ok_or(InspectError::NoStakingVersion(height)),
+        None => Ok(last_state(storage)?.staking_version),
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ChosenValidator {
+    pub staking_address: StakedStateAddress,
+    pub voting_power: TendermintVotePower,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ValidatorRecord {
+    pub staking: StakedState,
+    pub liveness: LivenessTracker,
+    /// sum of voting powers of the signed blocks in the current rewards period
+    pub reward_participation: Option<u64>,
+}
+
+/// Staking table of the last committed state
+#[derive(Serialize, Debug)]
+pub struct StakingTableView {
+    /// validator set of the last end block
+    pub chosen_validators: Vec<ChosenValidator>,
+    /// active council nodes
+    pub council_nodes: Vec<CouncilNodeMetadata>,
+    /// all the validator records (including jailed / inactive ones)
+    pub validators: Vec<ValidatorRecord>,
+}
+
+pub fn staking_table(storage: &impl GetKV) -> Result<StakingTableView, InspectError> {
+    let state = last_state(storage)?;
+    let heap = StakingGetter::new(storage, state.staking_version);
+    let table = &state.staking_table;
+    Ok(StakingTableView {
+        chosen_validators: table
+            .get_chosen_validators()
+            .iter()
+            .map(|(staking_address, voting_power)| ChosenValidator {
+                staking_address: *staking_address,
+                voting_power: *voting_power,
+            })
+            .collect(),
+        council_nodes: table.list_council_nodes(&heap),
+        validators: table
+            .get_liveness()
+            .iter()
+            .map(|(address, liveness)| {
+                Ok(ValidatorRecord {
+                    staking: heap
+                        .get(address)
+                        .ok_or(InspectError::StakingNotFound(*address))?,
+                    liveness: liveness.clone(),
+                    reward_participation: table.get_participator_stats().get(address).copied(),
+                })
+            })
+            .collect::<Result<_, InspectError>>()?,
+    })
+}
+
+/// Staked state at the block height (the last committed one if not provided)
+pub fn staked_state(
+    storage: &impl GetKV,
+    address: &StakedStateAddress,
+    height: Option<BlockHeight>,
+) -> Result<Option<StakedState>, InspectError> {
+    let version = staking_version(storage, height)?;
+    Ok(StakingGetter::new(storage, version).get(address))
+}
+
+/// All the staked states at the block height (the last committed one if not provided)
+pub fn dump_stakings(
+    storage: &impl GetKV,
+    height: Option<BlockHeight>,
+) -> Result<Vec<StakedState>, InspectError> {
+    let version = staking_version(storage, height)?;
+    Ok(iter_stakings(storage, version).collect())
+}
+
+#[derive(Serialize, Debug)]
+pub struct UtxoView {
+    pub txid: String,
+    /// spent flags of outputs (padded to whole bytes)
+    pub bitmap: String,
+    /// indices of spent outputs
+    pub spent: Vec<usize>,
+    pub inclusion: Option<TxInclusion>,
+}
+
+pub fn utxo(storage: &impl GetKV, txid: &TxId) -> Result<UtxoView, InspectError> {
+    let data = chain_storage::lookup_item(storage, LookupItem::TxMetaSpent, txid)
+        .ok_or(InspectError::TxNotFound)?;
+    let bits = BitVec::from_bytes(&data);
+    Ok(UtxoView {
+        txid: hex::encode(txid),
+        bitmap: bits
+            .iter()
+            .map(|spent| if spent { '1' } else { '0' })
+            .collect(),
+        spent: bits
+            .iter()
+            .enumerate()
+            .filter_map(|(index, spent)| if spent { Some(index) } else { None })
+            .collect(),
+        inclusion: chain_storage::lookup_tx_inclusion(storage, txid),
+    })
+}
+
+#[derive(Serialize, Debug)]
+pub struct AppHashEntry {
+    pub height: BlockHeight,
+    pub app_hash: String,
+    pub staking_version: Option<Version>,
+}
+
+/// Stored app hashes in the block height range (up to the last committed block)
+pub fn app_hash_history(
+    storage: &impl GetKV,
+    from: BlockHeight,
+    to: Option<BlockHeight>,
+) -> Result<Vec<AppHashEntry>, InspectError> {
+    let last_height = last_state(storage)?.last_block_height;
+    let to = to.map_or(last_height, |to| to.min(last_height));
+    Ok((from.value()..=to.value())
+        .map(BlockHeight::new)
+        .filter_map(|height| {
+            chain_storage::get_historical_app_hash(storage, height).map(|app_hash| AppHashEntry {
+                height,
+                app_hash: hex::encode(app_hash),
+                staking_version: chain_storage::get_historical_staking_version(storage, height),
+            })
+        })
+        .collect())
+}
+
+/// Result of recomputing the last app hash from the stored components
+#[derive(Serialize, Debug)]
+pub struct ConsistencyReport {
+    pub block_height: BlockHeight,
+    /// in the last state
+    pub app_hash: String,
+    /// from the transaction merkle tree, the staking trie leaves, rewards pool and network parameters
+    pub computed_app_hash: String,
+    /// in the app hash history
+    pub history_app_hash: Option<String>,
+    /// in the last state
+    pub account_root: String,
+    /// from all the staking trie leaves
+    pub computed_account_root: String,
+    pub consistent: bool,
+}
+
+pub fn check_consistency(storage: &impl GetKV) -> Result<ConsistencyReport, InspectError> {
+    let state = last_state(storage)?;
+    let tx_tree =
+        match chain_storage::lookup_item(storage, LookupItem::TxsMerkle, &state.last_apphash) {
+            Some(data) => MerkleTree::<H256>::decode(&mut data.as_slice())?,
+            // genesis
+            None => MerkleTree::empty(),
+        };
+    let stakings: Vec<StakedState> = iter_stakings(storage, state.staking_version).collect();
+    let computed_account_root = compute_staking_root(&stakings);
+    let top_level = &state.top_level;
+    let computed_app_hash = compute_app_hash(
+        &tx_tree,
+        &computed_account_root,
+        &top_level.rewards_pool,
+        &top_level.network_params,
+    );
+    let history_app_hash = chain_storage::get_historical_app_hash(storage, state.last_block_height);
+    let consistent = computed_app_hash == state.last_apphash
+        && computed_account_root == top_level.account_root
+        && history_app_hash == Some(state.last_apphash);
+    Ok(ConsistencyReport {
+        block_height: state.last_block_height,
+        app_hash: hex::encode(state.last_apphash),
+        computed_app_hash: hex::encode(computed_app_hash),
+        history_app_hash: history_app_hash.map(hex::encode),
+        account_root: hex::encode(top_level.account_root),
+        computed_account_root: hex::encode(computed_account_root),
+        consistent,
+    })
+}
+
+/// Exports the committed state at the block height (the last committed one if not provided)
+/// as the initial configuration of a new chain ("app_state" in genesis.json).
+///
+/// Only the staked states are versioned in the storage, so a block height before the last committed one
+/// can only be exported if its top level state was stored (nodes with a tx query address)
+/// and the UTXOs didn't change after it.
+/// The validators of the new chain are the ones it will choose from the exported council nodes.
+pub fn export_genesis(
+    storage: &ReadOnlyStorage,
+    height: Option<BlockHeight>,
+) -> Result<InitConfig, InspectError> {
+    let state = last_state(storage)?;
+    let height = height.unwrap_or(state.last_block_height);
+    if height > state.last_block_height {
+        return Err(InspectError::ExportHeight {
+            requested: height,
+            last: state.last_block_height,
+        });
+    }
+    let (staking_version, top_level, app_hash) = if height == state.last_block_height {
+        (
+            state.staking_version,
+            state.top_level.clone(),
+            state.last_apphash,
+        )
+    } else {
+        let top_level = chain_storage::get_historical_state(storage, height)
+            .ok_or(InspectError::NoHistoricalState(height))?;
+        let app_hash = chain_storage::get_historical_app_hash(storage, height)
+            .ok_or(InspectError::NoHistoricalState(height))?;
+        (
+            staking_version(storage, Some(height))?,
+            ChainState::decode(&mut top_level.as_slice())?,
+            app_hash,
+        )
+    };
+
+    let mut stakings: Vec<StakedState> = iter_stakings(storage, staking_version).collect();
+    if height != state.last_block_height {
+        // the coins in UTXOs are only recorded in the last state: if the supply accounting
+        // (as in `check_circulating_coins`) gives other ones at the block height,
+        // some UTXOs were spent after it
+        let circulating = ((Coin::max()
+            - top_level
+                .network_params
+                .get_rewards_monetary_expansion_cap())?
+            + top_level.rewards_pool.minted)?;
+        let utxo_coins = ((circulating - sum_staking_coins(storage, staking_version)?)?
+            - top_level.rewards_pool.period_bonus)?;
+        if utxo_coins != state.utxo_coins {
+            return Err(InspectError::UtxosChanged(height));
+        }
+    }
+
+    let heap = StakingGetter::new(storage, staking_version);
+    let network_params = &top_level.network_params;
+    let council_node_addresses = stakings
+        .iter()
+        .filter(|staking| matches!(staking.node_meta, Some(NodeState::CouncilNode(_))))
+        .map(|staking| staking.address)
+        .collect::<Vec<_>>();
+    // the same as the new chain does
+    let staking_table = StakingTable::from_genesis(
+        &heap,
+        network_params.get_required_council_node_stake(),
+        network_params.get_max_validators(),
+        &council_node_addresses,
+    );
+    let council_nodes = staking_table
+        .get_chosen_validators()
+        .keys()
+        .map(|address| {
+            let staking = heap
+                .get(address)
+                .ok_or(InspectError::StakingNotFound(*address))?;
+            let node = match staking.node_meta {
+                Some(NodeState::CouncilNode(val)) => val.council_node,
+                _ => return Err(InspectError::NotCouncilNode(*address)),
+            };
+            let StakedStateAddress::BasicRedeem(address) = address;
+            Ok((
+                *address,
+                (
+                    node.node_info.name,
+                    node.node_info.security_contact,
+                    node.consensus_pubkey,
+                    node.node_info.confidential_init,
+                ),
+            ))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let used_validator_addresses = stakings
+        .iter_mut()
+        .filter_map(|staking| match staking.node_meta.as_mut() {
+            Some(NodeState::CouncilNode(val)) if !val.used_validator_addresses.is_empty() => {
+                Some((
+                    staking.address,
+                    std::mem::take(&mut val.used_validator_addresses),
+                ))
+            }
+            _ => None,
+        })
+        .collect();
+    let mut tx_outputs = Vec::new();
+    for (txid, spent) in storage.iter_tx_meta_spent() {
+        let inclusion = chain_storage::lookup_tx_inclusion(storage, &txid);
+        if inclusion.map_or(false, |inclusion| inclusion.block_height > height) {
+            return Err(InspectError::UtxosChanged(height));
+        }
+        let sealed_log = storage.get_sealed_log(&txid).unwrap_or_default();
+        // only the transactions sealed by the mock enclave can be unsealed here
+        let outputs = mock_utils::unseal(&sealed_log)
+            .ok()
+            .filter(|tx| tx.id() == txid)
+            .map(|tx| tx.outputs().to_vec())
+            .unwrap_or_default();
+        let no_of_outputs = chain_storage::lookup_no_of_outputs(storage, &txid).or_else(|| {
+            if outputs.is_empty() {
+                None
+            } else {
+                Some(outputs.len() as TxoSize)
+            }
+        });
+        let bits = BitVec::from_bytes(&spent);
+        let unspent = bits
+            .iter()
+            .take(no_of_outputs.map_or(bits.len(), usize::from))
+            .any(|spent| !spent);
+        if unspent {
+            tx_outputs.push(ExportedTxOutputs {
+                txid,
+                spent,
+                no_of_outputs,
+                inclusion,
+                owners: outputs.into_iter().map(|output| output.address).collect(),
+                sealed_log,
+            });
+        }
+    }
+
+    let NetworkParameters::Genesis(network_params) = top_level.network_params;
+    Ok(InitConfig {
+        distribution: BTreeMap::new(),
+        network_params,
+        council_nodes,
+        exported_state: Some(ExportedState {
+            height,
+            app_hash,
+            stakings,
+            used_validator_addresses,
+            rewards_pool: top_level.rewards_pool,
+            utxo_coins: state.utxo_coins,
+            tx_outputs,
+        }),
+    })
+}
@@ -1,6 +1,9 @@
 pub mod app;
 pub mod enclave_bridge;
+pub mod inspect;
 pub mod liveness;
+pub mod metrics;
+pub mod replay;
 pub mod staking;
 pub mod storage;
 pub mod tx_error;
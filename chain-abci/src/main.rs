@@ -6,8 +6,16 @@ use chain_abci::enclave_bridge::edp::{
 };
 #[cfg(any(feature = "mock-enclave", not(target_os = "linux")))]
 use chain_abci::enclave_bridge::mock::MockClient;
+use chain_abci::enclave_bridge::persistence::TdbeStatus;
 use chain_abci::enclave_bridge::{EnclaveProxy, TdbeConfig};
+use chain_abci::inspect;
+use chain_abci::metrics::{self, MetricsConfig};
+use chain_abci::replay::{self, ReplayFixture};
+use chain_core::common::HASH_SIZE_256;
 use chain_core::init::network::{get_network, get_network_id, init_chain_id};
+use chain_core::state::account::StakedStateAddress;
+use chain_core::state::tendermint::BlockHeight;
+use chain_core::tx::data::TxId;
 use chain_storage::ReadOnlyStorage;
 use chain_storage::{Storage, StorageConfig, StorageType};
 use kvdb::KeyValueDB;
@@ -38,6 +46,9 @@ pub struct Config {
     launch_ra_proxy: bool,
     remote_attestation: SpRaConfig,
     data_bootstrap: TdbeConfig,
+    /// opt-in Prometheus metrics endpoint
+    #[serde(default)]
+    metrics: MetricsConfig,
 }
 
 impl Default for Config {
@@ -68,6 +79,7 @@ impl Default for Config {
                 ias_report_path: "/attestation/v4/report".into(),
             },
             data_bootstrap: TdbeConfig::default(),
+            metrics: MetricsConfig::default(),
         }
     }
 }
@@ -141,6 +153,128 @@ pub enum AbciApp {
         )]
         data: String,
     },
+
+    /// Read-only inspection of the committed storage
+    #[structopt(
+        name = "inspect",
+        about = "Inspect the committed storage of a (possibly running) node (prints JSON)"
+    )]
+    Inspect {
+        #[structopt(
+            short = "d",
+            long = "data",
+            default_value = ".cro-storage/",
+            help = "Sets a data storage directory"
+        )]
+        data: String,
+        #[structopt(subcommand)]
+        command: InspectCommand,
+    },
+
+    /// Replays blocks with the mock enclave and reports the state changes
+    #[structopt(
+        name = "replay",
+        about = "Replay blocks from a JSON fixture and report the state changes (prints JSON)"
+    )]
+    Replay {
+        #[structopt(help = "JSON fixture with the genesis and the blocks to replay")]
+        fixture: PathBuf,
+        #[structopt(
+            long = "compare",
+            help = "Data storage directory of a node to compare the app hashes with"
+        )]
+        compare: Option<String>,
+        #[structopt(
+            long = "from",
+            help = "Data storage directory of a node whose committed state the blocks are replayed \
+                    on top of (copied in memory, the directory isn't modified)"
+        )]
+        from: Option<String>,
+        #[structopt(
+            short = "o",
+            long = "output",
+            help = "Output JSON file (stdout if not provided)"
+        )]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum InspectCommand {
+    /// the last committed chain state
+    #[structopt(name = "state", about = "Print the last committed chain state")]
+    State,
+    /// the staking table of the last committed state
+    #[structopt(name = "staking-table", about = "Print the staking table")]
+    StakingTable,
+    /// a staked state
+    #[structopt(name = "staking", about = "Print a staked state")]
+    Staking {
+        #[structopt(name = "address", help = "Staking address")]
+        address: StakedStateAddress,
+        #[structopt(
+            long = "height",
+            help = "Block height (the last committed one if not provided)"
+        )]
+        height: Option<u64>,
+    },
+    /// spent flags of the transaction outputs
+    #[structopt(name = "utxo", about = "Print the spent bitmap of transaction outputs")]
+    Utxo {
+        #[structopt(name = "txid", help = "Transaction ID (hex)")]
+        txid: String,
+    },
+    /// stored app hashes
+    #[structopt(name = "app-hashes", about = "Print the app hash history")]
+    AppHashes {
+        #[structopt(long = "from", default_value = "0", help = "First block height")]
+        from: u64,
+        #[structopt(
+            long = "to",
+            help = "Last block height (the last committed one if not provided)"
+        )]
+        to: Option<u64>,
+    },
+    /// all the staked states
+    #[structopt(name = "dump-stakings", about = "Dump the staking trie")]
+    DumpStakings {
+        #[structopt(
+            long = "height",
+            help = "Block height (the last committed one if not provided)"
+        )]
+        height: Option<u64>,
+        #[structopt(
+            short = "o",
+            long = "output",
+            help = "Output JSON file (stdout if not provided)"
+        )]
+        output: Option<PathBuf>,
+    },
+    /// exports the committed state as a new genesis "app_state"
+    #[structopt(
+        name = "export-genesis",
+        about = "Export the committed state as the app_state of a new chain's genesis.json"
+    )]
+    ExportGenesis {
+        #[structopt(
+            long = "height",
+            help = "Block height (the last committed one if not provided; \
+                    the earlier ones need the stored states and no UTXO changes after them)"
+        )]
+        height: Option<u64>,
+        #[structopt(
+            short = "o",
+            long = "output",
+            help = "Output JSON file (stdout if not provided)"
+        )]
+        output: Option<PathBuf>,
+    },
+    /// recomputes the app hash from the stored components
+    #[structopt(
+        name = "check",
+        about = "Recompute the last app hash from the stored components"
+    )]
+    Check,
 }
 
 #[derive(Debug, StructOpt)]
@@ -180,28 +314,39 @@ pub struct AbciOpt {
 
 /// edp
 #[cfg(all(not(feature = "mock-enclave"), feature = "edp", target_os = "linux"))]
-fn get_enclave_proxy(config: &Config, storage: Arc<dyn KeyValueDB>) -> TxValidationApp {
+fn get_enclave_proxy(
+    config: &Config,
+    storage: Arc<dyn KeyValueDB>,
+) -> (TxValidationApp, TdbeStatus) {
     let (app, stream_from_tdbe) = launch_tx_validation(config.remote_attestation.address.clone());
+    // without a remote TDBE, there's nothing to fetch
+    let status = if config.data_bootstrap.remote_rpc_address.is_some() {
+        TdbeStatus::catching_up(&ReadOnlyStorage::new_db(storage.clone()))
+    } else {
+        TdbeStatus::default()
+    };
     let tdbe_app = TdbeApp::new(
         &config.data_bootstrap,
         &config.remote_attestation,
         storage,
         stream_from_tdbe,
+        status.clone(),
+        get_network_id(),
     )
     .expect("create tdbe app");
-    // FIXME: currently this blocks, but it'll need more involved signalling chain-abci:
-    // 1. after catching up and completing persistence of sealed transactions payloads,
-    // it'll also need to send back to chain-abci the Add+ Commit payloads for node join construction
-    // 2. it'll need to send back the trusted anchors + sealed keypackage secrets once the nodejoin is accepted
-    let _ = tdbe_app.spawn().join();
-    app
+    // TDBE keeps running (serving other nodes) alongside chain-abci;
+    // the fetched sealed transactions, node join payloads and sealed enclave state
+    // are persisted as they arrive, and the returned monitor thread sets the status
+    // to failed if TDBE stops before the initial fetch finished
+    let _ = tdbe_app.spawn();
+    (app, status)
 }
 
 /// for development
 #[cfg(any(feature = "mock-enclave", not(target_os = "linux")))]
-fn get_enclave_proxy(_config: &Config, _storage: Arc<dyn KeyValueDB>) -> MockClient {
+fn get_enclave_proxy(_config: &Config, _storage: Arc<dyn KeyValueDB>) -> (MockClient, TdbeStatus) {
     warn!("Using mock (non-enclave) infrastructure");
-    MockClient::new(get_network_id())
+    (MockClient::new(get_network_id()), TdbeStatus::default())
 }
 
 /// edp
@@ -249,6 +394,125 @@ fn start_up_ra_tx_query<T: EnclaveProxy + 'static>(
     // nothing
 }
 
+fn print_json<T: Serialize>(value: &T) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).expect("serialize to json")
+    );
+}
+
+fn parse_txid(txid: &str) -> Result<TxId, String> {
+    let bytes = hex::decode(txid).map_err(|e| e.to_string())?;
+    if bytes.len() != HASH_SIZE_256 {
+        return Err(format!("expected {} bytes", HASH_SIZE_256));
+    }
+    let mut result = TxId::default();
+    result.copy_from_slice(&bytes);
+    Ok(result)
+}
+
+fn run_inspect(storage: &ReadOnlyStorage, command: InspectCommand) -> Result<(), String> {
+    match command {
+        InspectCommand::State => {
+            print_json(&inspect::last_state(storage).map_err(|e| e.to_string())?)
+        }
+        InspectCommand::StakingTable => {
+            print_json(&inspect::staking_table(storage).map_err(|e| e.to_string())?)
+        }
+        InspectCommand::Staking { address, height } => {
+            let staking = inspect::staked_state(storage, &address, height.map(BlockHeight::new))
+                .map_err(|e| e.to_string())?;
+            print_json(&staking.ok_or("staked state not found")?)
+        }
+        InspectCommand::Utxo { txid } => {
+            let txid = parse_txid(&txid).map_err(|e| format!("invalid txid: {}", e))?;
+            print_json(&inspect::utxo(storage, &txid).map_err(|e| e.to_string())?)
+        }
+        InspectCommand::AppHashes { from, to } => print_json(
+            &inspect::app_hash_history(storage, BlockHeight::new(from), to.map(BlockHeight::new))
+                .map_err(|e| e.to_string())?,
+        ),
+        InspectCommand::DumpStakings { height, output } => {
+            let stakings = inspect::dump_stakings(storage, height.map(BlockHeight::new))
+                .map_err(|e| e.to_string())?;
+            if let Some(output) = output {
+                let file = File::create(&output).map_err(|e| e.to_string())?;
+                serde_json::to_writer_pretty(file, &stakings).map_err(|e| e.to_string())?;
+                info!(
+                    "{} staked states written to {}",
+                    stakings.len(),
+                    output.display()
+                );
+            } else {
+                print_json(&stakings);
+            }
+        }
+        InspectCommand::ExportGenesis { height, output } => {
+            let config = inspect::export_genesis(storage, height.map(BlockHeight::new))
+                .map_err(|e| e.to_string())?;
+            if let Some(output) = output {
+                let file = File::create(&output).map_err(|e| e.to_string())?;
+                serde_json::to_writer_pretty(file, &config).map_err(|e| e.to_string())?;
+                info!("exported genesis app_state written to {}", output.display());
+            } else {
+                print_json(&config);
+            }
+        }
+        InspectCommand::Check => {
+            let report = inspect::check_consistency(storage).map_err(|e| e.to_string())?;
+            print_json(&report);
+            if !report.consistent {
+                return Err("stored app hash doesn't match the stored components".to_owned());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_replay(
+    fixture: &Path,
+    compare: Option<&str>,
+    from: Option<&str>,
+    output: Option<&Path>,
+) -> Result<(), String> {
+    let file = File::open(fixture).map_err(|e| e.to_string())?;
+    let fixture: ReplayFixture =
+        serde_json::from_reader(BufReader::new(file)).map_err(|e| e.to_string())?;
+    let expected = match compare {
+        Some(data) => {
+            let storage = ReadOnlyStorage::new(&StorageConfig::new(data, StorageType::Node))
+                .map_err(|e| format!("failed to open the storage to compare with: {}", e))?;
+            replay::stored_app_hashes(&storage, &fixture)
+        }
+        None => Default::default(),
+    };
+    let report = match from {
+        Some(data) => {
+            let storage = ReadOnlyStorage::new(&StorageConfig::new(data, StorageType::Node))
+                .and_then(|storage| storage.copy_to_memory())
+                .map_err(|e| format!("failed to copy the storage to replay from: {}", e))?;
+            replay::replay_from(&fixture, storage, &expected)
+        }
+        None => replay::replay(&fixture, &expected),
+    }
+    .map_err(|e| e.to_string())?;
+    if let Some(output) = output {
+        let file = File::create(output).map_err(|e| e.to_string())?;
+        serde_json::to_writer_pretty(file, &report).map_err(|e| e.to_string())?;
+        info!(
+            "{} replayed blocks written to {}",
+            report.blocks.len(),
+            output.display()
+        );
+    } else {
+        print_json(&report);
+    }
+    match report.first_divergent_height {
+        Some(height) => Err(format!("app hash diverged at block height {}", height)),
+        None => Ok(()),
+    }
+}
+
 fn main() {
     env_logger::init();
     let app_command = AbciApp::from_args();
@@ -273,6 +537,30 @@ fn main() {
                 }
             };
         }
+        AbciApp::Inspect { data, command } => {
+            let storage = ReadOnlyStorage::new(&StorageConfig::new(&data, StorageType::Node))
+                .expect("failed to open the storage");
+            if let Err(e) = run_inspect(&storage, command) {
+                error!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        AbciApp::Replay {
+            fixture,
+            compare,
+            from,
+            output,
+        } => {
+            if let Err(e) = run_replay(
+                &fixture,
+                compare.as_deref(),
+                from.as_deref(),
+                output.as_deref(),
+            ) {
+                error!("{}", e);
+                std::process::exit(1);
+            }
+        }
         AbciApp::Run { run_command } => {
             let opt = run_command;
             // use DATA_PATH/config.yaml as default
@@ -299,7 +587,8 @@ fn main() {
             let addr = SocketAddr::new(host, config.port);
             let storage = Storage::new(&StorageConfig::new(&opt.data, StorageType::Node));
 
-            let tx_validator = get_enclave_proxy(&config, storage.temp_hack_for_tdbe());
+            let (tx_validator, tdbe_status) =
+                get_enclave_proxy(&config, storage.temp_hack_for_tdbe());
             if sanity_check_enabled() {
                 warn!("Enabled sanity checks");
             }
@@ -309,18 +598,20 @@ fn main() {
                 tx_validator.get_comm_only(),
                 storage.get_read_only(),
             );
+            if let Some(metrics_address) = config.metrics.listen_address.as_ref() {
+                metrics::serve(metrics_address).expect("failed to start the metrics endpoint");
+            }
             info!("starting up");
-            abci::run(
-                addr,
-                ChainNodeApp::new_with_storage(
-                    tx_validator,
-                    &config.genesis_app_hash,
-                    &config.chain_id,
-                    storage,
-                    config.tx_query,
-                    config.data_bootstrap.external_listen_address,
-                ),
+            let mut app = ChainNodeApp::new_with_storage(
+                tx_validator,
+                &config.genesis_app_hash,
+                &config.chain_id,
+                storage,
+                config.tx_query,
+                config.data_bootstrap.external_listen_address,
             );
+            app.tdbe_status = tdbe_status;
+            abci::run(addr, app);
         }
     }
 }
@@ -0,0 +1,260 @@
+//! Opt-in Prometheus metrics of the ABCI application.
+//!
+//! The metrics are always collected (in a process-wide registry),
+//! but they are only served (in the Prometheus text format) if `metrics.listen_address`
+//! is set in the configuration.
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use prometheus::{
+    exponential_buckets, Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use serde::{Deserialize, Serialize};
+
+use chain_core::tx::rejection::TxRejection;
+
+/// The metrics collected by chain-abci
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::new);
+
+/// whether the metrics are served (so that the more expensive ones should be computed)
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// the requests are served one at a time, so a stalled client mustn't block the others
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// maximum size of the request line and headers
+const MAX_REQUEST_SIZE: u64 = 8 * 1024;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct MetricsConfig {
+    /// Local address to serve the metrics on, e.g. `127.0.0.1:26660`
+    /// (the metrics endpoint is disabled if not set)
+    pub listen_address: Option<String>,
+}
+
+pub struct Metrics {
+    registry: Registry,
+    /// duration of ABCI requests (labelled by the method)
+    pub abci_request_duration: HistogramVec,
+    /// rejected transactions (labelled by the connection and the rejection reason)
+    pub rejected_txs: IntCounterVec,
+    /// number of entries in the mempool buffers (labelled by the buffer)
+    pub mempool_buffer_size: IntGaugeVec,
+    /// staking transactions pending in the mempool connection
+    pub pending_staking_txs: IntGauge,
+    /// round-trip time of enclave requests (labelled by the request)
+    pub enclave_request_duration: HistogramVec,
+    /// duration of storage flushes in commit (labelled by the storage)
+    pub storage_flush_duration: HistogramVec,
+    /// duration of staking table processing (labelled by the phase)
+    pub staking_table_duration: HistogramVec,
+    /// number of validator records in the staking table
+    pub staking_table_validators: IntGauge,
+    /// number of validators chosen for the current validator set
+    pub chosen_validators: IntGauge,
+    /// number of jailed validators (only computed if the metrics are served)
+    pub jailed_validators: IntGauge,
+    /// duration of rewards distributions
+    pub rewards_distribution_duration: Histogram,
+    /// minted rewards (in base units)
+    pub rewards_minted: IntCounter,
+    /// distributed rewards (in base units)
+    pub rewards_distributed: IntCounter,
+    /// height of the last committed block
+    pub block_height: IntGauge,
+}
+
+fn duration_opts(name: &str, help: &str) -> HistogramOpts {
+    // from 100 microseconds to ~6.5 seconds
+    HistogramOpts::new(name, help).buckets(exponential_buckets(0.0001, 2.0, 17).unwrap())
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("chain_abci".to_owned()), None)
+            .expect("valid metrics prefix");
+        let metrics = Metrics {
+            abci_request_duration: HistogramVec::new(
+                duration_opts("request_duration_seconds", "Duration of ABCI requests"),
+                &["method"],
+            )
+            .unwrap(),
+            rejected_txs: IntCounterVec::new(
+                Opts::new("rejected_txs_total", "Rejected transactions"),
+                &["connection", "reason"],
+            )
+            .unwrap(),
+            mempool_buffer_size: IntGaugeVec::new(
+                Opts::new(
+                    "mempool_buffer_size",
+                    "Number of entries in mempool buffers",
+                ),
+                &["buffer"],
+            )
+            .unwrap(),
+            pending_staking_txs: IntGauge::new(
+                "pending_staking_txs",
+                "Staking transactions queued in the mempool connection",
+            )
+            .unwrap(),
+            enclave_request_duration: HistogramVec::new(
+                duration_opts(
+                    "enclave_request_duration_seconds",
+                    "Round-trip time of enclave requests",
+                ),
+                &["request"],
+            )
+            .unwrap(),
+            storage_flush_duration: HistogramVec::new(
+                duration_opts(
+                    "storage_flush_duration_seconds",
+                    "Duration of storage flushes in commit",
+                ),
+                &["storage"],
+            )
+            .unwrap(),
+            staking_table_duration: HistogramVec::new(
+                duration_opts(
+                    "staking_table_duration_seconds",
+                    "Duration of staking table processing",
+                ),
+                &["phase"],
+            )
+            .unwrap(),
+            staking_table_validators: IntGauge::new(
+                "staking_table_validators",
+                "Number of validator records in the staking table",
+            )
+            .unwrap(),
+            chosen_validators: IntGauge::new(
+                "chosen_validators",
+                "Number of validators in the current validator set",
+            )
+            .unwrap(),
+            jailed_validators: IntGauge::new("jailed_validators", "Number of jailed validators")
+                .unwrap(),
+            rewards_distribution_duration: Histogram::with_opts(duration_opts(
+                "rewards_distribution_duration_seconds",
+                "Duration of rewards distributions",
+            ))
+            .unwrap(),
+            rewards_minted: IntCounter::new(
+                "rewards_minted_total",
+                "Minted rewards (in base units)",
+            )
+            .unwrap(),
+            rewards_distributed: IntCounter::new(
+                "rewards_distributed_total",
+                "Distributed rewards (in base units)",
+            )
+            .unwrap(),
+            block_height: IntGauge::new("block_height", "Height of the last committed block")
+                .unwrap(),
+            registry,
+        };
+        metrics.register();
+        metrics
+    }
+
+    fn register(&self) {
+        let collectors: Vec<Box<dyn prometheus::core::Collector>> = vec![
+            Box::new(self.abci_request_duration.clone()),
+            Box::new(self.rejected_txs.clone()),
+            Box::new(self.mempool_buffer_size.clone()),
+            Box::new(self.pending_staking_txs.clone()),
+            Box::new(self.enclave_request_duration.clone()),
+            Box::new(self.storage_flush_duration.clone()),
+            Box::new(self.staking_table_duration.clone()),
+            Box::new(self.staking_table_validators.clone()),
+            Box::new(self.chosen_validators.clone()),
+            Box::new(self.jailed_validators.clone()),
+            Box::new(self.rewards_distribution_duration.clone()),
+            Box::new(self.rewards_minted.clone()),
+            Box::new(self.rewards_distributed.clone()),
+            Box::new(self.block_height.clone()),
+        ];
+        for collector in collectors {
+            self.registry
+                .register(collector)
+                .expect("metrics registered once");
+        }
+    }
+
+    /// counts a rejected transaction
+    pub fn record_rejection(&self, connection: &str, rejection: &TxRejection) {
+        self.rejected_txs
+            .with_label_values(&[connection, &format!("{:?}", rejection.code)])
+            .inc();
+    }
+
+    /// the metrics in the Prometheus text format
+    pub fn gather(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("metrics text encoding");
+        buffer
+    }
+}
+
+/// whether the metrics are served
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Starts serving the metrics on `GET /metrics` of the given address.
+/// Returns the bound address (e.g. for the port assigned to `127.0.0.1:0`)
+pub fn serve(listen_address: &str) -> io::Result<(SocketAddr, JoinHandle<()>)> {
+    let listener = TcpListener::bind(listen_address)?;
+    let address = listener.local_addr()?;
+    ENABLED.store(true, Ordering::Relaxed);
+    log::info!("serving metrics on http://{}/metrics", address);
+    let handle = thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream.and_then(handle_request);
+            if let Err(e) = result {
+                log::warn!("metrics request failed: {}", e);
+            }
+        }
+    });
+    Ok((address, handle))
+}
+
+fn handle_request(mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?.take(MAX_REQUEST_SIZE));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // skip the headers
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => (
+            "200 OK",
+            TextEncoder::new().format_type().to_owned(),
+            METRICS.gather(),
+        ),
+        _ => (
+            "404 Not Found",
+            "text/plain".to_owned(),
+            b"not found".to_vec(),
+        ),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+    stream.flush()
+}
@@ -0,0 +1,518 @@
+//! Replays blocks from JSON fixtures into `ChainNodeApp` with the mock enclave (`chain-abci replay`)
+//! in order to find which block / transaction made the application state diverge.
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use abci::*;
+use parity_scale_codec::Decode;
+use protobuf::well_known_types::{Duration, Timestamp};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{get_validator_key, init_app_hash, ChainNodeApp, ChainNodeState};
+use crate::enclave_bridge::mock::MockClient;
+use chain_core::common::{MerkleTree, Timespec, H256};
+use chain_core::init::coin::Coin;
+use chain_core::init::config::{DistributionError, InitConfig};
+use chain_core::state::account::{StakedState, StakedStateAddress};
+use chain_core::state::tendermint::{BlockHeight, TendermintValidatorAddress, TendermintVotePower};
+use chain_core::state::RewardsPoolState;
+use chain_core::tx::data::input::TxoPointer;
+use chain_core::tx::{TxAux, TxEnclaveAux};
+use chain_storage::buffer::{Get, StakingBuffer};
+use chain_storage::{Storage, NUM_COLUMNS};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ReplayError {
+    #[error("invalid genesis app_state: {0}")]
+    Genesis(#[from] DistributionError),
+    #[error("invalid chain id (expected two last hex digits): {0}")]
+    ChainId(String),
+    #[error("block at height {actual} is not the next one ({expected})")]
+    HeightGap { expected: u64, actual: u64 },
+    #[error("invalid hex payload in block {0}: {1}")]
+    Hex(u64, hex::FromHexError),
+    #[error("stored state doesn't match the fixture: {0}")]
+    StoredState(String),
+}
+
+/// Genesis and blocks to replay
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReplayFixture {
+    pub chain_id: String,
+    /// genesis time (in seconds since the Unix epoch)
+    pub genesis_time: Timespec,
+    /// consensus parameters' evidence max age (in seconds)
+    #[serde(default = "default_max_evidence_age")]
+    pub max_evidence_age: Timespec,
+    /// "app_state" in genesis.json
+    pub app_state: InitConfig,
+    pub blocks: Vec<BlockFixture>,
+}
+
+fn default_max_evidence_age() -> Timespec {
+    172_800
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockFixture {
+    pub height: u64,
+    /// block time (in seconds since the Unix epoch)
+    pub time: Timespec,
+    /// hex-encoded transactions
+    #[serde(default)]
+    pub txs: Vec<String>,
+    /// votes for the previous block (no last commit info if not provided)
+    #[serde(default)]
+    pub last_commit: Option<Vec<VoteFixture>>,
+    #[serde(default)]
+    pub evidence: Vec<EvidenceFixture>,
+    /// app hash after committing this block (i.e. the one in the next block's header)
+    #[serde(default)]
+    pub app_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VoteFixture {
+    pub validator_address: TendermintValidatorAddress,
+    pub power: TendermintVotePower,
+    pub signed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EvidenceFixture {
+    pub validator_address: TendermintValidatorAddress,
+    pub height: u64,
+    pub time: Timespec,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Change<T> {
+    pub before: T,
+    pub after: T,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct StakingChange {
+    pub address: StakedStateAddress,
+    pub before: Option<StakedState>,
+    pub after: StakedState,
+}
+
+/// State changes made by a block phase or a transaction
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct StateChanges {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stakings: Vec<StakingChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub created_utxos: Vec<TxoPointer>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub spent_utxos: Vec<TxoPointer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rewards_pool: Option<Change<RewardsPoolState>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utxo_coins: Option<Change<Coin>>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TxReport {
+    /// if the payload could be decoded
+    pub txid: Option<String>,
+    pub code: u32,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub log: String,
+    pub changes: StateChanges,
+}
+
+/// The parts the app hash is computed from
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AppHashComponents {
+    pub tx_root: String,
+    pub account_root: String,
+    pub rewards_pool: String,
+    pub network_params: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BlockReport {
+    pub height: BlockHeight,
+    pub begin_block: StateChanges,
+    pub txs: Vec<TxReport>,
+    pub end_block: StateChanges,
+    pub app_hash: String,
+    pub app_hash_components: AppHashComponents,
+    pub expected_app_hash: Option<String>,
+}
+
+impl BlockReport {
+    pub fn diverged(&self) -> bool {
+        matches!(&self.expected_app_hash, Some(expected) if expected != &self.app_hash)
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ReplayReport {
+    pub blocks: Vec<BlockReport>,
+    /// the first block whose app hash doesn't match the expected one
+    pub first_divergent_height: Option<BlockHeight>,
+}
+
+/// The state before a block phase or a transaction
+struct Snapshot {
+    stakings: StakingBuffer,
+    rewards_pool: RewardsPoolState,
+    utxo_coins: Coin,
+}
+
+impl Snapshot {
+    fn take(app: &ChainNodeApp<MockClient>) -> Self {
+        let state = app.last_state.as_ref().expect("replayed app state");
+        Snapshot {
+            stakings: app.staking_buffer.clone(),
+            rewards_pool: state.top_level.rewards_pool.clone(),
+            utxo_coins: state.utxo_coins,
+        }
+    }
+
+    fn changes(&self, app: &ChainNodeApp<MockClient>) -> StateChanges {
+        let state = app.last_state.as_ref().expect("replayed app state");
+        let committed = app.staking_getter_committed();
+        let mut stakings = app
+            .staking_buffer
+            .iter()
+            .filter(|(address, staking)| self.stakings.get(*address) != Some(*staking))
+            .map(|(address, staking)| StakingChange {
+                address: *address,
+                before: self
+                    .stakings
+                    .get(address)
+                    .cloned()
+                    .or_else(|| committed.get(address)),
+                after: staking.clone(),
+            })
+            .collect::<Vec<_>>();
+        stakings.sort_by_key(|change| change.address);
+        StateChanges {
+            stakings,
+            rewards_pool: change(&self.rewards_pool, &state.top_level.rewards_pool),
+            utxo_coins: change(&self.utxo_coins, &state.utxo_coins),
+            ..Default::default()
+        }
+    }
+}
+
+fn change<T: PartialEq + Clone>(before: &T, after: &T) -> Option<Change<T>> {
+    if before == after {
+        None
+    } else {
+        Some(Change {
+            before: before.clone(),
+            after: after.clone(),
+        })
+    }
+}
+
+/// (created, spent) transaction outputs
+fn utxo_changes(tx: &TxAux) -> (Vec<TxoPointer>, Vec<TxoPointer>) {
+    let outputs = |no_of_outputs: u16| -> Vec<TxoPointer> {
+        (0..no_of_outputs as usize)
+            .map(|index| TxoPointer::new(tx.tx_id(), index))
+            .collect()
+    };
+    match tx {
+        TxAux::EnclaveTx(TxEnclaveAux::TransferTx {
+            inputs,
+            no_of_outputs,
+            ..
+        }) => (outputs(*no_of_outputs), inputs.clone()),
+        TxAux::EnclaveTx(TxEnclaveAux::DepositStakeTx { tx, .. }) => (vec![], tx.inputs.clone()),
+        TxAux::EnclaveTx(TxEnclaveAux::WithdrawUnbondedStakeTx { no_of_outputs, .. }) => {
+            (outputs(*no_of_outputs), vec![])
+        }
+        _ => (vec![], vec![]),
+    }
+}
+
+fn timestamp(seconds: Timespec) -> Timestamp {
+    Timestamp {
+        seconds: seconds as i64,
+        ..Default::default()
+    }
+}
+
+fn abci_validator(address: &TendermintValidatorAddress, power: TendermintVotePower) -> Validator {
+    Validator {
+        address: <[u8; 20]>::from(address).to_vec(),
+        power: power.into(),
+        ..Default::default()
+    }
+}
+
+fn req_init_chain(fixture: &ReplayFixture) -> Result<RequestInitChain, ReplayError> {
+    let genesis = fixture
+        .app_state
+        .validate_config_get_genesis(fixture.genesis_time)?;
+    let validators = genesis
+        .validators
+        .iter()
+        .map(|(address, node)| {
+            let bonded = genesis
+                .accounts
+                .iter()
+                .find(|account| &account.address == address)
+                .map_or(Coin::zero(), |account| account.bonded);
+            ValidatorUpdate {
+                pub_key: Some(get_validator_key(node)).into(),
+                power: TendermintVotePower::from(bonded).into(),
+                ..Default::default()
+            }
+        })
+        .collect();
+    Ok(RequestInitChain {
+        time: Some(timestamp(fixture.genesis_time)).into(),
+        app_state_bytes: serde_json::to_vec(&fixture.app_state).expect("serialize app state"),
+        chain_id: fixture.chain_id.clone(),
+        validators,
+        consensus_params: Some(ConsensusParams {
+            evidence: Some(EvidenceParams {
+                max_age_duration: Some(Duration {
+                    seconds: fixture.max_evidence_age as i64,
+                    ..Default::default()
+                })
+                .into(),
+                ..Default::default()
+            })
+            .into(),
+            ..Default::default()
+        })
+        .into(),
+        ..Default::default()
+    })
+}
+
+fn req_begin_block(chain_id: &str, block: &BlockFixture) -> RequestBeginBlock {
+    RequestBeginBlock {
+        header: Some(Header {
+            time: Some(timestamp(block.time)).into(),
+            chain_id: chain_id.to_owned(),
+            height: block.height as i64,
+            ..Default::default()
+        })
+        .into(),
+        last_commit_info: block
+            .last_commit
+            .as_ref()
+            .map(|votes| LastCommitInfo {
+                votes: votes
+                    .iter()
+                    .map(|vote| VoteInfo {
+                        validator: Some(abci_validator(&vote.validator_address, vote.power)).into(),
+                        signed_last_block: vote.signed,
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            })
+            .into(),
+        byzantine_validators: block
+            .evidence
+            .iter()
+            .map(|evidence| Evidence {
+                validator: Some(abci_validator(
+                    &evidence.validator_address,
+                    TendermintVotePower::zero(),
+                ))
+                .into(),
+                height: evidence.height as i64,
+                time: Some(timestamp(evidence.time)).into(),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Replays the fixture blocks from the genesis on an in-memory storage,
+/// comparing the app hashes with the expected ones
+/// (the fixture's ones are used for the heights not in `expected_app_hashes`)
+pub fn replay(
+    fixture: &ReplayFixture,
+    expected_app_hashes: &BTreeMap<BlockHeight, H256>,
+) -> Result<ReplayReport, ReplayError> {
+    let storage = Storage::new_db(Arc::new(kvdb_memorydb::create(NUM_COLUMNS)));
+    replay_from(fixture, storage, expected_app_hashes)
+}
+
+/// Replays the fixture blocks on top of the state committed in `storage` (e.g. a copy of
+/// a node's storage), or from the genesis if no state is stored there:
+/// the first block has to be the one after the stored state
+pub fn replay_from(
+    fixture: &ReplayFixture,
+    storage: Storage,
+    expected_app_hashes: &BTreeMap<BlockHeight, H256>,
+) -> Result<ReplayReport, ReplayError> {
+    let chain_id = &fixture.chain_id;
+    let chain_hex_id = chain_id
+        .len()
+        .checked_sub(2)
+        .and_then(|start| chain_id.get(start..))
+        .and_then(|digits| hex::decode(digits).ok())
+        .ok_or_else(|| ReplayError::ChainId(chain_id.clone()))?[0];
+    let genesis_app_hash = init_app_hash(&fixture.app_state, fixture.genesis_time);
+    let last_state = match storage.get_last_app_state() {
+        Some(data) => Some(
+            ChainNodeState::decode(&mut data.as_slice())
+                .map_err(|e| ReplayError::StoredState(e.to_string()))?,
+        ),
+        None => None,
+    };
+    let (first_height, tx_query_address) = match &last_state {
+        Some(state) => {
+            check_stored_genesis(&storage, &genesis_app_hash, chain_id)?;
+            // only a node with tx-query stores the historical states
+            let tx_query_address = storage
+                .get_historical_state(state.last_block_height)
+                .map(|_| String::new());
+            (state.last_block_height.value() + 1, tx_query_address)
+        }
+        None => (1, None),
+    };
+    let req_init_chain = match last_state {
+        Some(_) => None,
+        None => Some(req_init_chain(fixture)?),
+    };
+    let mut app = ChainNodeApp::new_with_storage(
+        MockClient::new(chain_hex_id),
+        &hex::encode_upper(genesis_app_hash),
+        chain_id,
+        storage,
+        tx_query_address,
+        String::new(),
+    );
+    if let Some(req_init_chain) = req_init_chain {
+        app.init_chain(&req_init_chain);
+    }
+
+    let mut blocks = Vec::with_capacity(fixture.blocks.len());
+    let mut first_divergent_height = None;
+    for (block, expected_height) in fixture.blocks.iter().zip(first_height..) {
+        if block.height != expected_height {
+            return Err(ReplayError::HeightGap {
+                expected: expected_height,
+                actual: block.height,
+            });
+        }
+        let height = BlockHeight::new(block.height);
+        let txs = block
+            .txs
+            .iter()
+            .map(hex::decode)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ReplayError::Hex(block.height, e))?;
+        let expected_app_hash = match expected_app_hashes.get(&height) {
+            Some(app_hash) => Some(hex::encode(app_hash)),
+            None => block
+                .app_hash
+                .as_ref()
+                .map(|app_hash| app_hash.to_lowercase()),
+        };
+
+        let snapshot = Snapshot::take(&app);
+        app.begin_block(&req_begin_block(chain_id, block));
+        let begin_block = snapshot.changes(&app);
+
+        let mut tx_reports = Vec::with_capacity(txs.len());
+        for tx in txs {
+            let snapshot = Snapshot::take(&app);
+            let rsp = app.deliver_tx(&RequestDeliverTx {
+                tx: tx.clone(),
+                ..Default::default()
+            });
+            let decoded = TxAux::decode(&mut tx.as_slice()).ok();
+            let mut changes = snapshot.changes(&app);
+            if let (0, Some(decoded)) = (rsp.code, decoded.as_ref()) {
+                let (created, spent) = utxo_changes(decoded);
+                changes.created_utxos = created;
+                changes.spent_utxos = spent;
+            }
+            tx_reports.push(TxReport {
+                txid: decoded.map(|tx| hex::encode(tx.tx_id())),
+                code: rsp.code,
+                log: rsp.log,
+                changes,
+            });
+        }
+
+        let snapshot = Snapshot::take(&app);
+        app.end_block(&RequestEndBlock {
+            height: block.height as i64,
+            ..Default::default()
+        });
+        let end_block = snapshot.changes(&app);
+
+        let tx_tree = MerkleTree::new(app.delivered_txs.iter().map(TxAux::tx_id).collect());
+        app.commit(&RequestCommit::new());
+        let state = app.last_state.as_ref().expect("replayed app state");
+        let report = BlockReport {
+            height,
+            begin_block,
+            txs: tx_reports,
+            end_block,
+            app_hash: hex::encode(state.last_apphash),
+            app_hash_components: AppHashComponents {
+                tx_root: hex::encode(tx_tree.root_hash()),
+                account_root: hex::encode(state.top_level.account_root),
+                rewards_pool: hex::encode(state.top_level.rewards_pool.hash()),
+                network_params: hex::encode(state.top_level.network_params.hash()),
+            },
+            expected_app_hash,
+        };
+        if first_divergent_height.is_none() && report.diverged() {
+            first_divergent_height = Some(height);
+        }
+        blocks.push(report);
+    }
+    Ok(ReplayReport {
+        blocks,
+        first_divergent_height,
+    })
+}
+
+/// The stored state has to be of the fixture's chain (the app panics otherwise)
+fn check_stored_genesis(
+    storage: &Storage,
+    genesis_app_hash: &H256,
+    chain_id: &str,
+) -> Result<(), ReplayError> {
+    match chain_storage::get_genesis_app_hash(storage) {
+        Some(stored) if &stored == genesis_app_hash => {}
+        _ => {
+            return Err(ReplayError::StoredState(format!(
+                "genesis app hash isn't {}",
+                hex::encode(genesis_app_hash)
+            )))
+        }
+    }
+    match chain_storage::get_stored_chain_id(storage) {
+        Some(stored) if stored == chain_id.as_bytes() => Ok(()),
+        _ => Err(ReplayError::StoredState(format!(
+            "chain id isn't {}",
+            chain_id
+        ))),
+    }
+}
+
+/// The stored app hashes (e.g. of another node) at the fixture block heights
+pub fn stored_app_hashes(
+    storage: &impl chain_storage::buffer::GetKV,
+    fixture: &ReplayFixture,
+) -> BTreeMap<BlockHeight, H256> {
+    fixture
+        .blocks
+        .iter()
+        .filter_map(|block| {
+            let height = BlockHeight::new(block.height);
+            chain_storage::get_historical_app_hash(storage, height)
+                .map(|app_hash| (height, app_hash))
+        })
+        .collect()
+}
@@ -1,7 +1,9 @@
+#[cfg(test)]
+mod model;
 mod table;
 mod tx;
 
-pub use table::{RewardsDistribution, StakingTable};
+pub use table::{CouncilNodeMetadata, RewardsDistribution, StakingTable};
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,687 @@
+//! Model-based tests of `StakingTable`: random sequences of staking events are applied both
+//! to the table and to a simple reference model, and the resulting stakings, voting powers,
+//! punishments and coin supply are compared after each event.
+//!
+//! quickcheck shrinks a failing sequence and prints it in the `Debug` form,
+//! which can be added as is to `regressions` below.
+use std::collections::BTreeMap;
+
+use quickcheck::{quickcheck, Arbitrary, Gen};
+use secp256k1::key::{PublicKey, SecretKey};
+
+use chain_core::common::Timespec;
+use chain_core::init::address::RedeemAddress;
+use chain_core::init::coin::{sum_coins, Coin};
+use chain_core::init::params::NetworkParameters;
+use chain_core::state::account::{
+    NodeState, PunishmentKind, StakedState, StakedStateAddress, UnbondTx, UnjailTx, Validator,
+};
+use chain_core::state::tendermint::{
+    BlockHeight, TendermintValidatorAddress, TendermintValidatorPubKey, TendermintVotePower,
+};
+use chain_core::state::validator::NodeJoinRequestTx;
+use chain_core::tx::fee::Fee;
+use chain_storage::buffer::{Get, MemStore, StoreStaking};
+use test_common::chain_env::{
+    get_init_network_params, mock_council_node_join, mock_council_node_meta, DEFAULT_GENESIS_TIME,
+};
+
+use super::table::{PunishmentOutcome, SlashedCoin};
+use super::StakingTable;
+use crate::app::BeginBlockInfo;
+
+type StakingMemStore = MemStore<StakedStateAddress, StakedState>;
+
+const SEEDS: [[u8; 32]; 4] = [[0xcc; 32], [0xcd; 32], [0xce; 32], [0xcf; 32]];
+/// the first accounts are the genesis validators
+const GENESIS_VALIDATORS: usize = 3;
+const MAX_VALIDATORS: usize = 3;
+const SIGNING_WINDOW: usize = 5;
+const MISSED_BLOCK_THRESHOLD: usize = 2;
+/// also the max evidence age
+const UNBONDING_PERIOD: Timespec = 10;
+
+fn minimal_required_staking() -> Coin {
+    Coin::new(10_0000_0000).unwrap()
+}
+
+fn units(amount: u64) -> Coin {
+    Coin::new(amount * 1_0000_0000).unwrap()
+}
+
+fn staking_address(account: usize) -> StakedStateAddress {
+    let secret_key = SecretKey::from_slice(&SEEDS[account]).expect("within curve order");
+    let secp = secp256k1::SECP256K1;
+    let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+    StakedStateAddress::BasicRedeem(RedeemAddress::from(&public_key))
+}
+
+fn validator_pubkey(account: usize) -> TendermintValidatorPubKey {
+    TendermintValidatorPubKey::Ed25519(SEEDS[account])
+}
+
+fn account_index(address: &StakedStateAddress) -> usize {
+    (0..SEEDS.len())
+        .find(|account| &staking_address(*account) == address)
+        .expect("known account")
+}
+
+#[derive(Debug, Clone)]
+enum Event {
+    /// amounts are in whole units
+    Deposit {
+        account: usize,
+        amount: u64,
+    },
+    Unbond {
+        account: usize,
+        amount: u64,
+    },
+    Withdraw {
+        account: usize,
+    },
+    NodeJoin {
+        account: usize,
+    },
+    Unjail {
+        account: usize,
+    },
+    /// new block in which the `missed` validators didn't sign the previous one
+    Block {
+        elapsed: Timespec,
+        missed: Vec<usize>,
+    },
+    /// new block with evidence of the validator double signing the previous one
+    Byzantine {
+        account: usize,
+        elapsed: Timespec,
+    },
+}
+
+impl Arbitrary for Event {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let account = usize::from(u8::arbitrary(g)) % SEEDS.len();
+        let amount = u64::from(u8::arbitrary(g) % 16);
+        let elapsed = Timespec::from(u8::arbitrary(g) % 8);
+        match u8::arbitrary(g) % 8 {
+            0 => Event::Deposit { account, amount },
+            1 => Event::Unbond { account, amount },
+            2 => Event::Withdraw { account },
+            3 => Event::NodeJoin { account },
+            4 => Event::Unjail { account },
+            5 => Event::Byzantine { account, elapsed },
+            _ => {
+                let mask = u8::arbitrary(g);
+                Event::Block {
+                    elapsed,
+                    missed: (0..SEEDS.len()).filter(|i| mask & (1 << i) != 0).collect(),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ModelValidator {
+    inactive_time: Option<Timespec>,
+    jailed_until: Option<Timespec>,
+    /// signed flags of the recent blocks, indexed by the block height modulo the window
+    signed: Vec<bool>,
+}
+
+impl ModelValidator {
+    fn new() -> Self {
+        ModelValidator {
+            inactive_time: None,
+            jailed_until: None,
+            signed: vec![true; SIGNING_WINDOW],
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.inactive_time.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct ModelAccount {
+    bonded: Coin,
+    unbonded: Coin,
+    unbonded_from: Timespec,
+    nonce: u64,
+    validator: Option<ModelValidator>,
+}
+
+impl ModelAccount {
+    fn is_jailed(&self) -> bool {
+        matches!(&self.validator, Some(val) if val.jailed_until.is_some())
+    }
+
+    fn sub_bonded(&mut self, amount: Coin, block_time: Timespec) {
+        self.bonded = (self.bonded - amount).unwrap();
+        if let Some(val) = self.validator.as_mut() {
+            if val.is_active() && self.bonded < minimal_required_staking() {
+                val.inactive_time = Some(block_time);
+            }
+        }
+    }
+}
+
+/// The specification of the staking rules
+struct Model {
+    block_time: Timespec,
+    block_height: u64,
+    accounts: BTreeMap<StakedStateAddress, ModelAccount>,
+    chosen: BTreeMap<StakedStateAddress, TendermintVotePower>,
+    deposited: Coin,
+    withdrawn: Coin,
+    slashed: Coin,
+}
+
+impl Model {
+    fn account(&self, account: usize) -> ModelAccount {
+        self.accounts
+            .get(&staking_address(account))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Applies the event to the model, returns if the transaction should be accepted
+    /// and the expected punishments / validator updates of a block
+    fn apply(
+        &mut self,
+        event: &Event,
+    ) -> (
+        bool,
+        Vec<PunishmentOutcome>,
+        Vec<(TendermintValidatorPubKey, TendermintVotePower)>,
+    ) {
+        let (address, mut account) = match event {
+            Event::Deposit { account, .. }
+            | Event::Unbond { account, .. }
+            | Event::Withdraw { account }
+            | Event::NodeJoin { account }
+            | Event::Unjail { account } => (staking_address(*account), self.account(*account)),
+            Event::Block { elapsed, missed } => {
+                let punished = self.new_block(*elapsed, missed, None);
+                return (true, punished, self.end_block());
+            }
+            Event::Byzantine { account, elapsed } => {
+                let punished = self.new_block(*elapsed, &[], Some(*account));
+                return (true, punished, self.end_block());
+            }
+        };
+        let accepted = match event {
+            Event::Deposit { amount, .. } if !account.is_jailed() => {
+                account.bonded = (account.bonded + units(*amount)).unwrap();
+                self.deposited = (self.deposited + units(*amount)).unwrap();
+                true
+            }
+            Event::Unbond { amount, .. }
+                if !account.is_jailed() && *amount > 0 && account.bonded >= units(*amount) =>
+            {
+                account.sub_bonded(units(*amount), self.block_time);
+                account.unbonded = (account.unbonded + units(*amount)).unwrap();
+                account.unbonded_from = self.block_time + UNBONDING_PERIOD;
+                account.nonce += 1;
+                true
+            }
+            Event::Withdraw { .. }
+                if !account.is_jailed() && self.block_time >= account.unbonded_from =>
+            {
+                self.withdrawn = (self.withdrawn + account.unbonded).unwrap();
+                account.unbonded = Coin::zero();
+                account.nonce += 1;
+                true
+            }
+            Event::NodeJoin { .. } if account.bonded >= minimal_required_staking() => {
+                match account.validator.as_mut() {
+                    None => {
+                        account.validator = Some(ModelValidator::new());
+                        account.nonce += 1;
+                        true
+                    }
+                    Some(val) if val.jailed_until.is_none() && !val.is_active() => {
+                        val.inactive_time = None;
+                        account.nonce += 1;
+                        true
+                    }
+                    Some(_) => false,
+                }
+            }
+            Event::Unjail { .. } => match account.validator.as_mut() {
+                Some(val)
+                    if val
+                        .jailed_until
+                        .map_or(false, |until| self.block_time >= until) =>
+                {
+                    val.jailed_until = None;
+                    account.nonce += 1;
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+        if accepted {
+            self.accounts.insert(address, account);
+        }
+        (accepted, vec![], vec![])
+    }
+
+    fn new_block(
+        &mut self,
+        elapsed: Timespec,
+        missed: &[usize],
+        double_signed: Option<usize>,
+    ) -> Vec<PunishmentOutcome> {
+        let evidence_time = self.block_time;
+        self.block_time += elapsed;
+        self.block_height += 1;
+        let block_time = self.block_time;
+
+        // validator records inactive for longer than the unbonding period are removed,
+        // unless they're jailed
+        for account in self.accounts.values_mut() {
+            if let Some(val) = &account.validator {
+                if val.jailed_until.is_none()
+                    && val
+                        .inactive_time
+                        .map_or(false, |time| block_time > time + UNBONDING_PERIOD)
+                {
+                    account.validator = None;
+                }
+            }
+        }
+
+        let mut punishments = vec![];
+        let slot = self.block_height as usize % SIGNING_WINDOW;
+        for (address, account) in self.accounts.iter_mut() {
+            if let Some(val) = account.validator.as_mut() {
+                val.signed[slot] = !missed.contains(&account_index(address));
+                let missed_blocks = val.signed.iter().filter(|signed| !**signed).count();
+                if missed_blocks >= MISSED_BLOCK_THRESHOLD {
+                    if val.is_active() {
+                        val.inactive_time = Some(block_time);
+                        punishments.push((*address, PunishmentKind::NonLive, None));
+                    }
+                    val.signed = vec![true; SIGNING_WINDOW];
+                }
+            }
+        }
+        if let Some(index) = double_signed {
+            let address = staking_address(index);
+            if let Some(val) = self
+                .accounts
+                .get_mut(&address)
+                .and_then(|account| account.validator.as_mut())
+            {
+                if evidence_time + UNBONDING_PERIOD > block_time && val.jailed_until.is_none() {
+                    val.jailed_until = Some(block_time + UNBONDING_PERIOD);
+                    if val.is_active() {
+                        val.inactive_time = Some(block_time);
+                    }
+                    punishments.push((
+                        address,
+                        PunishmentKind::ByzantineFault,
+                        Some(block_time + UNBONDING_PERIOD),
+                    ));
+                }
+            }
+        }
+
+        let params = network_params();
+        punishments
+            .into_iter()
+            .map(|(address, kind, jailed_until)| {
+                let ratio = match kind {
+                    PunishmentKind::NonLive => params.get_liveness_slash_percent(),
+                    PunishmentKind::ByzantineFault => params.get_byzantine_slash_percent(),
+                };
+                let account = self.accounts.get_mut(&address).unwrap();
+                let slashed_coin = SlashedCoin {
+                    bonded: account.bonded * ratio,
+                    unbonded: account.unbonded * ratio,
+                };
+                account.sub_bonded(slashed_coin.bonded, block_time);
+                account.unbonded = (account.unbonded - slashed_coin.unbonded).unwrap();
+                self.slashed = (self.slashed + slashed_coin.sum().unwrap()).unwrap();
+                PunishmentOutcome {
+                    staking_address: address,
+                    slashed_coin,
+                    punishment_kind: kind,
+                    jailed_until,
+                }
+            })
+            .collect()
+    }
+
+    /// the active validators with the most bonded coins
+    fn end_block(&mut self) -> Vec<(TendermintValidatorPubKey, TendermintVotePower)> {
+        let mut candidates = self
+            .accounts
+            .iter()
+            .filter(|(_, account)| matches!(&account.validator, Some(val) if val.is_active()))
+            .map(|(address, account)| (account.bonded, *address))
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        let chosen = candidates
+            .into_iter()
+            .take(MAX_VALIDATORS)
+            .map(|(bonded, address)| (address, TendermintVotePower::from(bonded)))
+            .collect::<BTreeMap<_, _>>();
+        let pubkey = |address: &StakedStateAddress| validator_pubkey(account_index(address));
+        let mut updates = chosen
+            .iter()
+            .filter(|(address, power)| self.chosen.get(address) != Some(power))
+            .map(|(address, power)| (pubkey(address), *power))
+            .collect::<Vec<_>>();
+        updates.extend(
+            self.chosen
+                .keys()
+                .filter(|address| !chosen.contains_key(address))
+                .map(|address| (pubkey(address), TendermintVotePower::zero())),
+        );
+        self.chosen = chosen;
+        updates
+    }
+}
+
+fn network_params() -> NetworkParameters {
+    let mut params = get_init_network_params(Coin::zero());
+    params.jailing_config.block_signing_window = SIGNING_WINDOW as u16;
+    params.jailing_config.missed_block_threshold = MISSED_BLOCK_THRESHOLD as u16;
+    NetworkParameters::Genesis(params)
+}
+
+/// The staking table and the model, starting from the same genesis
+struct Harness {
+    table: StakingTable,
+    store: StakingMemStore,
+    model: Model,
+    params: NetworkParameters,
+    genesis_supply: Coin,
+}
+
+impl Harness {
+    fn new() -> Self {
+        let mut store = StakingMemStore::new();
+        let mut accounts = BTreeMap::new();
+        for account in 0..GENESIS_VALIDATORS {
+            let address = staking_address(account);
+            let bonded = (minimal_required_staking() + units(account as u64 + 1)).unwrap();
+            let mut staking = StakedState::default(address);
+            staking.bonded = bonded;
+            staking.node_meta = Some(NodeState::CouncilNode(Validator::new(
+                mock_council_node_meta(validator_pubkey(account)),
+            )));
+            store.set_staking(staking);
+            accounts.insert(
+                address,
+                ModelAccount {
+                    bonded,
+                    validator: Some(ModelValidator::new()),
+                    ..Default::default()
+                },
+            );
+        }
+        let addresses = accounts.keys().copied().collect::<Vec<_>>();
+        let table = StakingTable::from_genesis(
+            &store,
+            minimal_required_staking(),
+            MAX_VALIDATORS,
+            &addresses,
+        );
+        let chosen = table.get_chosen_validators().clone();
+        let genesis_supply = sum_coins(accounts.values().map(|account| account.bonded)).unwrap();
+        Harness {
+            table,
+            store,
+            model: Model {
+                block_time: DEFAULT_GENESIS_TIME,
+                block_height: 0,
+                accounts,
+                chosen,
+                deposited: Coin::zero(),
+                withdrawn: Coin::zero(),
+                slashed: Coin::zero(),
+            },
+            params: network_params(),
+            genesis_supply,
+        }
+    }
+
+    fn begin_block(
+        &mut self,
+        evidences: &[(TendermintValidatorAddress, BlockHeight, Timespec)],
+        voters: &[(TendermintValidatorAddress, bool)],
+    ) -> Vec<PunishmentOutcome> {
+        self.table.begin_block(
+            &mut self.store,
+            &BeginBlockInfo {
+                params: &self.params,
+                block_time: self.model.block_time,
+                block_height: self.model.block_height.into(),
+                max_evidence_age: UNBONDING_PERIOD,
+                voters,
+                evidences,
+            },
+        )
+    }
+
+    fn apply(&mut self, event: &Event) {
+        let block_time = self.model.block_time;
+        let block_height = BlockHeight::new(self.model.block_height);
+        let before = self.model.accounts.clone();
+        let (accepted, punished, updates) = self.model.apply(event);
+        let nonce = |account: usize| {
+            before
+                .get(&staking_address(account))
+                .map_or(0, |account| account.nonce)
+        };
+        let actual_accepted = match event {
+            Event::Deposit { account, amount } => self
+                .table
+                .deposit(&mut self.store, &staking_address(*account), units(*amount))
+                .is_ok(),
+            Event::Unbond { account, amount } => {
+                let tx = UnbondTx {
+                    from_staked_account: staking_address(*account),
+                    nonce: nonce(*account),
+                    value: units(*amount),
+                    attributes: Default::default(),
+                };
+                self.table
+                    .unbond(
+                        &mut self.store,
+                        UNBONDING_PERIOD,
+                        block_time,
+                        block_height,
+                        &tx,
+                        Fee::zero(),
+                    )
+                    .is_ok()
+            }
+            Event::Withdraw { account } => {
+                let address = staking_address(*account);
+                let unbonded = before
+                    .get(&address)
+                    .map_or(Coin::zero(), |account| account.unbonded);
+                self.table
+                    .withdraw(&mut self.store, block_time, &address, unbonded)
+                    .is_ok()
+            }
+            Event::NodeJoin { account } => {
+                let tx = NodeJoinRequestTx {
+                    nonce: nonce(*account),
+                    address: staking_address(*account),
+                    attributes: Default::default(),
+                    node_meta: mock_council_node_join(validator_pubkey(*account)),
+                };
+                self.table
+                    .node_join(&mut self.store, block_time, UNBONDING_PERIOD, 0, &tx)
+                    .is_ok()
+            }
+            Event::Unjail { account } => {
+                let tx = UnjailTx {
+                    nonce: nonce(*account),
+                    address: staking_address(*account),
+                    attributes: Default::default(),
+                };
+                self.table.unjail(&mut self.store, block_time, &tx).is_ok()
+            }
+            Event::Block { missed, .. } => {
+                let voters = before
+                    .iter()
+                    .filter(|(_, account)| account.validator.is_some())
+                    .map(|(address, _)| {
+                        let index = account_index(address);
+                        (validator_pubkey(index).into(), !missed.contains(&index))
+                    })
+                    .collect::<Vec<_>>();
+                assert_eq!(punished, self.begin_block(&[], &voters), "{:?}", event);
+                true
+            }
+            Event::Byzantine { account, .. } => {
+                let evidence = (validator_pubkey(*account).into(), block_height, block_time);
+                assert_eq!(punished, self.begin_block(&[evidence], &[]), "{:?}", event);
+                true
+            }
+        };
+        assert_eq!(accepted, actual_accepted, "{:?}", event);
+        if let Event::Block { .. } | Event::Byzantine { .. } = event {
+            assert_eq!(
+                updates,
+                self.table.end_block(&self.store, MAX_VALIDATORS),
+                "{:?}",
+                event
+            );
+        }
+        self.check(event);
+    }
+
+    fn check(&mut self, event: &Event) {
+        let mut total = Coin::zero();
+        for account in 0..SEEDS.len() {
+            let address = staking_address(account);
+            let staking = self.store.get(&address);
+            let expected = self.model.accounts.get(&address);
+            assert_eq!(
+                staking.is_some(),
+                expected.is_some(),
+                "{:?}: account {}",
+                event,
+                account
+            );
+            if let (Some(staking), Some(expected)) = (staking, expected) {
+                assert_eq!(staking.bonded, expected.bonded, "{:?}", event);
+                assert_eq!(staking.unbonded, expected.unbonded, "{:?}", event);
+                assert_eq!(staking.unbonded_from, expected.unbonded_from, "{:?}", event);
+                assert_eq!(staking.nonce, expected.nonce, "{:?}", event);
+                let validator = match &staking.node_meta {
+                    Some(NodeState::CouncilNode(val)) => {
+                        Some((val.inactive_time, val.jailed_until))
+                    }
+                    _ => None,
+                };
+                assert_eq!(
+                    validator,
+                    expected
+                        .validator
+                        .as_ref()
+                        .map(|val| (val.inactive_time, val.jailed_until)),
+                    "{:?}: account {}",
+                    event,
+                    account
+                );
+                total =
+                    sum_coins(vec![total, staking.bonded, staking.unbonded].into_iter()).unwrap();
+            }
+        }
+        assert_eq!(
+            self.table.get_chosen_validators(),
+            &self.model.chosen,
+            "{:?}",
+            event
+        );
+        assert_eq!(
+            self.table.get_liveness().keys().collect::<Vec<_>>(),
+            self.model
+                .accounts
+                .iter()
+                .filter(|(_, account)| account.validator.is_some())
+                .map(|(address, _)| address)
+                .collect::<Vec<_>>(),
+            "{:?}",
+            event
+        );
+        // no coins are created or lost
+        assert_eq!(
+            (self.genesis_supply + self.model.deposited).unwrap(),
+            sum_coins(vec![total, self.model.withdrawn, self.model.slashed].into_iter()).unwrap(),
+            "{:?}",
+            event
+        );
+        #[cfg(debug_assertions)]
+        self.table.check_invariants(&self.store);
+    }
+}
+
+fn run(events: &[Event]) {
+    let mut harness = Harness::new();
+    for event in events {
+        harness.apply(event);
+    }
+}
+
+quickcheck! {
+    fn prop_staking_table_should_follow_model(events: Vec<Event>) -> bool {
+        run(&events);
+        true
+    }
+}
+
+#[test]
+fn regressions() {
+    // non-live, byzantine, unjail, rejoin and cleanup of an inactive validator
+    run(&[
+        Event::Block {
+            elapsed: 1,
+            missed: vec![0],
+        },
+        Event::Block {
+            elapsed: 1,
+            missed: vec![0, 3],
+        },
+        Event::Deposit {
+            account: 3,
+            amount: 15,
+        },
+        Event::NodeJoin { account: 3 },
+        Event::Byzantine {
+            account: 1,
+            elapsed: 1,
+        },
+        Event::Unbond {
+            account: 2,
+            amount: 5,
+        },
+        Event::Block {
+            elapsed: 7,
+            missed: vec![],
+        },
+        Event::Unjail { account: 1 },
+        Event::Block {
+            elapsed: 7,
+            missed: vec![],
+        },
+        Event::Unjail { account: 1 },
+        Event::NodeJoin { account: 0 },
+        Event::Withdraw { account: 2 },
+        Event::Block {
+            elapsed: 7,
+            missed: vec![],
+        },
+        Event::Withdraw { account: 2 },
+    ]);
+}
@@ -23,6 +23,7 @@ use chain_storage::buffer::{GetStaking, StoreStaking};
 
 use crate::app::BeginBlockInfo;
 use crate::liveness::LivenessTracker;
+use crate::metrics::{self, METRICS};
 
 pub type RewardsDistribution = Vec<(StakedStateAddress, Coin)>;
 
@@ -164,8 +165,14 @@ impl StakingTable {
         let mut tbl = Self::default();
         tbl.minimal_required_staking = minimal_required_staking;
         for addr in addresses.iter() {
-            tbl.insert_validator(&heap.get(addr).unwrap())
-                .expect("only validator");
+            let staking = heap.get(addr).unwrap();
+            tbl.insert_validator(&staking).expect("only validator");
+            // only non-empty for the states exported from a previous chain
+            if let Some(NodeState::CouncilNode(val)) = staking.node_meta.as_ref() {
+                for (val_addr, _) in val.used_validator_addresses.iter() {
+                    tbl.idx_validator_address.insert(val_addr.clone(), *addr);
+                }
+            }
         }
         tbl.chosen_validators = tbl.choose_validators(heap, max_validators);
         #[cfg(debug_assertions)]
@@ -213,6 +220,10 @@ impl StakingTable {
         heap: &mut impl StoreStaking,
         info: &BeginBlockInfo,
     ) -> Vec<PunishmentOutcome> {
+        let _timer = METRICS
+            .staking_table_duration
+            .with_label_values(&["begin_block"])
+            .start_timer();
         self.cleanup(heap, info.get_unbonding_period(), info.block_time);
         self.punish(heap, info)
     }
@@ -225,12 +236,35 @@ impl StakingTable {
         heap: &impl GetStaking,
         max_validators: usize,
     ) -> Vec<(TendermintValidatorPubKey, TendermintVotePower)> {
+        let _timer = METRICS
+            .staking_table_duration
+            .with_label_values(&["end_block"])
+            .start_timer();
         let updates = self.update_validators(heap, max_validators);
         #[cfg(debug_assertions)]
         self.check_invariants(heap);
+        self.update_metrics(heap);
         updates
     }
 
+    fn update_metrics(&self, heap: &impl GetStaking) {
+        METRICS
+            .staking_table_validators
+            .set(self.liveness.len() as i64);
+        METRICS
+            .chosen_validators
+            .set(self.chosen_validators.len() as i64);
+        // needs the validator records from the heap
+        if metrics::enabled() {
+            let jailed = self
+                .liveness
+                .keys()
+                .filter(|addr| heap.get(addr).map_or(false, |staking| staking.is_jailed()))
+                .count();
+            METRICS.jailed_validators.set(jailed as i64);
+        }
+    }
+
     /// Handle reward statistics record
     pub fn reward_record(
         &mut self,
@@ -340,6 +374,16 @@ impl StakingTable {
         &self.chosen_validators
     }
 
+    /// Query liveness trackers of all the validator records.
+    pub fn get_liveness(&self) -> &BTreeMap<StakedStateAddress, LivenessTracker> {
+        &self.liveness
+    }
+
+    /// Query rewards participation (sum of voting powers of signed blocks) in the current period.
+    pub fn get_participator_stats(&self) -> &BTreeMap<StakedStateAddress, u64> {
+        &self.participator_stats
+    }
+
     /// Insert validator (genesis or join-node tx)
     /// Caller should do the validations:
     /// - StakedState has validator record
@@ -30,7 +30,10 @@ impl StakingTable {
     ) -> Result<u16, PublicTxError> {
         let mut staking = self.get_or_default(heap, &tx.address);
         if tx.nonce != staking.nonce {
-            return Err(PublicTxError::IncorrectNonce);
+            return Err(PublicTxError::IncorrectNonce {
+                expected: staking.nonce,
+                actual: tx.nonce,
+            });
         }
         if staking.bonded < self.minimal_required_staking {
             return Err(NodeJoinError::BondedNotEnough.into());
@@ -138,7 +141,10 @@ impl StakingTable {
     ) -> Result<(), PublicTxError> {
         let mut staking = self.get_or_default(heap, &tx.address);
         if tx.nonce != staking.nonce {
-            return Err(PublicTxError::IncorrectNonce);
+            return Err(PublicTxError::IncorrectNonce {
+                expected: staking.nonce,
+                actual: tx.nonce,
+            });
         }
 
         if let Some(NodeState::CouncilNode(val)) = staking.node_meta.as_mut() {
@@ -195,7 +201,10 @@ impl StakingTable {
     ) -> Result<Timespec, PublicTxError> {
         let mut staking = self.get_or_default(heap, &tx.from_staked_account);
         if tx.nonce != staking.nonce {
-            return Err(PublicTxError::IncorrectNonce);
+            return Err(PublicTxError::IncorrectNonce {
+                expected: staking.nonce,
+                actual: tx.nonce,
+            });
         }
         if staking.is_jailed() {
             return Err(UnbondError::IsJailed.into());
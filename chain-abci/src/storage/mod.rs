@@ -1,12 +1,14 @@
 use crate::enclave_bridge::EnclaveProxy;
+use crate::metrics::METRICS;
 use crate::staking::StakingTable;
-use crate::tx_error::PublicTxError;
+use crate::tx_error::{PublicTxError, UnbondError};
 use chain_core::common::Timespec;
 use chain_core::init::coin::Coin;
 use chain_core::state::account::{
     CouncilNodeMeta, NodeMetadata, StakedStateAddress, StakedStateOpAttributes,
 };
 use chain_core::tx::data::input::{TxoPointer, TxoSize};
+use chain_core::tx::data::output::TxInclusion;
 use chain_core::tx::fee::Fee;
 use chain_core::tx::{TransactionId, TxEnclaveAux, TxObfuscated, TxPublicAux};
 use chain_storage::buffer::{GetKV, GetStaking, StoreStaking};
@@ -163,27 +165,32 @@ impl TxPublicAction {
     }
 }
 
+/// returns the sealed transactions of inputs and the blocks they were included in
 fn check_spent_input_lookup(
     kvdb: &impl GetKV,
     inputs: &[TxoPointer],
-) -> Result<Vec<SealedLog>, Error> {
+) -> Result<(Vec<SealedLog>, Vec<Option<TxInclusion>>), Error> {
     // check that there are inputs
     if inputs.is_empty() {
         return Err(Error::NoInputs);
     }
     let mut result = Vec::with_capacity(inputs.len());
-    for txin in inputs.iter() {
-        let spent = chain_storage::lookup_input(kvdb, txin).ok_or(Error::InvalidInput)?;
+    let mut inclusion = Vec::with_capacity(inputs.len());
+    for (i, txin) in inputs.iter().enumerate() {
+        let index = i as u32;
+        let spent = chain_storage::lookup_input(kvdb, txin).ok_or(Error::InvalidInput { index })?;
         if spent {
-            return Err(Error::InputSpent);
+            return Err(Error::InputSpent { index });
         } else {
             result.push(
                 chain_storage::get_sealed_log(kvdb, &txin.id)
                     .expect("valid unspent tx output should be stored"),
             );
+            // not recorded for transactions committed before relative timelocks were supported
+            inclusion.push(chain_storage::lookup_tx_inclusion(kvdb, &txin.id));
         }
     }
-    Ok(result)
+    Ok((result, inclusion))
 }
 
 /// Checks TX against the current DB, passes to the enclave and returns an `Error` if something fails.
@@ -201,10 +208,18 @@ pub fn verify_enclave_tx<T: EnclaveProxy>(
             no_of_outputs,
             ..
         } => {
-            let tx_inputs = check_spent_input_lookup(kvdb, &inputs)?;
-            let response = tx_validator.process_request(
-                IntraEnclaveRequest::new_validate_transfer(txaux.clone(), *extra_info, tx_inputs),
-            );
+            let (tx_inputs, tx_inputs_inclusion) = check_spent_input_lookup(kvdb, &inputs)?;
+            let response = METRICS
+                .enclave_request_duration
+                .with_label_values(&["validate_transfer"])
+                .observe_closure_duration(|| {
+                    tx_validator.process_request(IntraEnclaveRequest::new_validate_transfer(
+                        txaux.clone(),
+                        *extra_info,
+                        tx_inputs,
+                        tx_inputs_inclusion,
+                    ))
+                });
             match response {
                 Ok(IntraEnclaveResponseOk::TxWithOutputs {
                     paid_fee,
@@ -225,14 +240,20 @@ pub fn verify_enclave_tx<T: EnclaveProxy>(
                 verify_unjailed(account)?;
             }
 
-            let tx_inputs = check_spent_input_lookup(kvdb, &tx.inputs)?;
+            let (tx_inputs, tx_inputs_inclusion) = check_spent_input_lookup(kvdb, &tx.inputs)?;
 
-            let response = tx_validator.process_request(IntraEnclaveRequest::new_validate_deposit(
-                txaux.clone(),
-                *extra_info,
-                account,
-                tx_inputs,
-            ));
+            let response = METRICS
+                .enclave_request_duration
+                .with_label_values(&["validate_deposit"])
+                .observe_closure_duration(|| {
+                    tx_validator.process_request(IntraEnclaveRequest::new_validate_deposit(
+                        txaux.clone(),
+                        *extra_info,
+                        account,
+                        tx_inputs,
+                        tx_inputs_inclusion,
+                    ))
+                });
             match response {
                 Ok(IntraEnclaveResponseOk::DepositStakeTx { input_coins }) => {
                     let deposit_amount = (input_coins - extra_info.min_fee_computed.to_coin())
@@ -252,14 +273,21 @@ pub fn verify_enclave_tx<T: EnclaveProxy>(
             witness,
             no_of_outputs,
         } => {
-            let account_address =
-                verify_tx_recover_address(&witness, &txid).map_err(|_| Error::EcdsaCrypto)?;
+            let account_address = verify_tx_recover_address(&witness, &txid)
+                .map_err(|_| Error::EcdsaCrypto { index: None })?;
             let account = trie.get(&account_address).ok_or(Error::AccountNotFound)?;
             verify_unjailed(&account)?;
             let withdraw_amount = account.unbonded;
-            let response = tx_validator.process_request(
-                IntraEnclaveRequest::new_validate_withdraw(txaux.clone(), *extra_info, account),
-            );
+            let response = METRICS
+                .enclave_request_duration
+                .with_label_values(&["validate_withdraw"])
+                .observe_closure_duration(|| {
+                    tx_validator.process_request(IntraEnclaveRequest::new_validate_withdraw(
+                        txaux.clone(),
+                        *extra_info,
+                        account,
+                    ))
+                });
             match response {
                 Ok(IntraEnclaveResponseOk::TxWithOutputs {
                     paid_fee,
@@ -292,6 +320,41 @@ fn check_staking_attributes(
     Ok(())
 }
 
+/// Checks the attributes and the witness of public transactions
+/// (i.e. everything that doesn't depend on the staked state they operate on).
+/// If OK, returns the staking address that signed the transaction
+pub fn verify_public_tx_signer(
+    chain_info: &ChainInfo,
+    txaux: &TxPublicAux,
+) -> Result<StakedStateAddress, PublicTxError> {
+    check_staking_attributes(txaux.attributes(), chain_info.chain_hex_id)?;
+    let witness = match txaux {
+        TxPublicAux::UnbondStakeTx(_, witness) => witness,
+        TxPublicAux::UnjailTx(_, witness) => witness,
+        TxPublicAux::NodeJoinTx(_, witness) => witness,
+    };
+    // TODO: delay checking witness, as address is contained in Tx?
+    let address = verify_tx_recover_address(&witness, &txaux.tx_id())?;
+    if address != txaux.staking_address() {
+        return Err(PublicTxError::StakingWitnessNotMatch);
+    }
+    Ok(address)
+}
+
+/// The fee paid by public transactions: only unbonding pays fees
+/// (the minimal fee plus the optional priority fee, which can only be non-zero
+/// since app version 3 -- see the `priority-fee` feature)
+pub fn public_tx_fee(chain_info: &ChainInfo, txaux: &TxPublicAux) -> Result<Fee, PublicTxError> {
+    match txaux {
+        TxPublicAux::UnbondStakeTx(maintx, _) => {
+            let fee = (chain_info.min_fee_computed.to_coin() + maintx.attributes.priority_fee)
+                .map_err(UnbondError::CoinError)?;
+            Ok(Fee::new(fee))
+        }
+        TxPublicAux::UnjailTx(..) | TxPublicAux::NodeJoinTx(..) => Ok(Fee::new(Coin::zero())),
+    }
+}
+
 /// Execute public transactions against uncommitted db.
 /// If OK, returns the paid fee + affected staking address
 pub fn process_public_tx(
@@ -301,46 +364,31 @@ pub fn process_public_tx(
     chain_info: &ChainInfo,
     txaux: &TxPublicAux,
 ) -> Result<TxPublicAction, PublicTxError> {
-    check_staking_attributes(txaux.attributes(), chain_info.chain_hex_id)?;
+    let address = verify_public_tx_signer(chain_info, txaux)?;
     match txaux {
-        // TODO: delay checking witness, as address is contained in Tx?
-        TxPublicAux::UnbondStakeTx(maintx, witness) => {
-            let address = verify_tx_recover_address(&witness, &maintx.id())?;
-            if address != maintx.from_staked_account {
-                return Err(PublicTxError::StakingWitnessNotMatch);
-            }
+        TxPublicAux::UnbondStakeTx(maintx, _) => {
+            let fee = public_tx_fee(chain_info, txaux)?;
             let unbonded_from = staking_table.unbond(
                 staking_store,
                 chain_info.get_unbonding_period(),
                 chain_info.block_time,
                 chain_info.block_height,
                 &maintx,
-                chain_info.min_fee_computed,
+                fee,
             )?;
 
             Ok(TxPublicAction::unbond(
-                chain_info.min_fee_computed,
+                fee,
                 (address, maintx.value),
                 unbonded_from,
             ))
         }
-        // TODO: delay checking witness, as address is contained in Tx?
-        TxPublicAux::UnjailTx(maintx, witness) => {
-            let address = verify_tx_recover_address(&witness, &maintx.id())?;
-            if address != maintx.address {
-                return Err(PublicTxError::StakingWitnessNotMatch);
-            }
-
+        TxPublicAux::UnjailTx(maintx, _) => {
             staking_table.unjail(staking_store, chain_info.block_time, maintx)?;
 
             Ok(TxPublicAction::unjail(address))
         }
-        // TODO: delay checking witness, as address is contained in Tx?
-        TxPublicAux::NodeJoinTx(maintx, witness) => {
-            let address = verify_tx_recover_address(&witness, &maintx.id())?;
-            if address != maintx.address {
-                return Err(PublicTxError::StakingWitnessNotMatch);
-            }
+        TxPublicAux::NodeJoinTx(maintx, _) => {
             let isv_svn = staking_table.node_join(
                 staking_store,
                 chain_info.block_time,
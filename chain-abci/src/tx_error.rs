@@ -1,4 +1,6 @@
 use chain_core::init::coin::{Coin, CoinError};
+use chain_core::state::account::Nonce;
+use chain_core::tx::rejection::{RejectionCode, RejectionDetail, TxRejection};
 use mls::extras::{self};
 
 #[derive(thiserror::Error, Debug)]
@@ -13,6 +15,18 @@ pub enum TxError {
     WIPMLSData,
 }
 
+impl TxError {
+    /// the (stable) reason and details reported in CheckTx/DeliverTx responses
+    pub fn rejection(&self) -> TxRejection {
+        match self {
+            TxError::DeserializeTx(_) => TxRejection::new(RejectionCode::Deserialize),
+            TxError::Enclave(e) => e.rejection(),
+            TxError::Public(e) => e.rejection(),
+            TxError::WIPMLSData => TxRejection::new(RejectionCode::Unsupported),
+        }
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum PublicTxError {
     #[error("public tx wrong chain_hex_id")]
@@ -23,8 +37,12 @@ pub enum PublicTxError {
     StakingWitnessVerify(#[from] secp256k1::Error),
     #[error("staking witness and address don't match")]
     StakingWitnessNotMatch,
-    #[error("tx nonce don't match staking state")]
-    IncorrectNonce,
+    #[error("tx nonce don't match staking state (expected: {expected}, actual: {actual})")]
+    IncorrectNonce { expected: Nonce, actual: Nonce },
+    #[error("pending tx with the same nonce pays at least the same fee (pending: {pending_fee}, tx: {fee})")]
+    ReplacementUnderpriced { pending_fee: Coin, fee: Coin },
+    #[error("tx queued until the preceding nonces are accepted (account's next nonce: {expected}, tx: {actual})")]
+    Queued { expected: Nonce, actual: Nonce },
     #[error("unjail tx process failed: {0}")]
     Unjail(#[from] UnjailError),
     #[error("node join tx process failed: {0}")]
@@ -33,6 +51,68 @@ pub enum PublicTxError {
     Unbond(#[from] UnbondError),
 }
 
+impl PublicTxError {
+    fn rejection(&self) -> TxRejection {
+        let code = match self {
+            PublicTxError::WrongChainHexId => RejectionCode::WrongChainHexId,
+            PublicTxError::UnsupportedVersion => RejectionCode::UnsupportedVersion,
+            PublicTxError::StakingWitnessVerify(_) => RejectionCode::InvalidSignature,
+            PublicTxError::StakingWitnessNotMatch => RejectionCode::MismatchAccountAddress,
+            PublicTxError::IncorrectNonce { expected, actual } => {
+                return TxRejection::with_detail(
+                    RejectionCode::IncorrectNonce,
+                    RejectionDetail::Nonce {
+                        expected: *expected,
+                        actual: *actual,
+                    },
+                )
+            }
+            PublicTxError::Queued { expected, actual } => {
+                return TxRejection::with_detail(
+                    RejectionCode::StakingTxQueued,
+                    RejectionDetail::Nonce {
+                        expected: *expected,
+                        actual: *actual,
+                    },
+                )
+            }
+            PublicTxError::ReplacementUnderpriced { pending_fee, fee } => {
+                return TxRejection::with_detail(
+                    RejectionCode::ReplacementUnderpriced,
+                    RejectionDetail::Replacement {
+                        pending_fee: *pending_fee,
+                        fee: *fee,
+                    },
+                )
+            }
+            PublicTxError::Unjail(UnjailError::NotJailed) => RejectionCode::AccountNotJailed,
+            PublicTxError::Unjail(UnjailError::JailTimeNotExpired) => {
+                RejectionCode::JailTimeNotExpired
+            }
+            PublicTxError::NodeJoin(e) => match e {
+                NodeJoinError::BondedNotEnough => RejectionCode::BondedNotEnough,
+                NodeJoinError::DuplicateValidatorAddress => {
+                    RejectionCode::DuplicateValidatorAddress
+                }
+                NodeJoinError::AlreadyJoined => RejectionCode::AlreadyJoined,
+                NodeJoinError::IsJailed => RejectionCode::AccountJailed,
+                NodeJoinError::UsedValidatorAddrFull => RejectionCode::UsedValidatorAddressesFull,
+                NodeJoinError::InvalidMLSInitData | NodeJoinError::MLSInitVerifyError(_) => {
+                    RejectionCode::InvalidMLSInitData
+                }
+                NodeJoinError::WIPNotValidator => RejectionCode::Unsupported,
+            },
+            PublicTxError::Unbond(e) => match e {
+                UnbondError::NonceNotMatch => RejectionCode::IncorrectNonce,
+                UnbondError::CoinError(_) => RejectionCode::InvalidSum,
+                UnbondError::IsJailed => RejectionCode::AccountJailed,
+                UnbondError::ZeroValue => RejectionCode::ZeroCoin,
+            },
+        };
+        TxRejection::new(code)
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum UnjailError {
     #[error("the staking address is not jailed")]
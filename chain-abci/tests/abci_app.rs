@@ -23,6 +23,7 @@ use chain_core::state::tendermint::{
 use chain_core::state::validator::NodeJoinRequestTx;
 use chain_core::state::{ChainState, RewardsPoolState};
 use chain_core::tx::fee::{LinearFee, Milli};
+use chain_core::tx::rejection::{RejectionCode, TxRejection, REJECTION_CODESPACE};
 use chain_core::tx::witness::tree::RawXOnlyPubkey;
 use chain_core::tx::witness::EcdsaSignature;
 use chain_core::tx::{
@@ -438,6 +439,12 @@ fn check_tx_should_reject_empty_tx() {
     let creq = RequestCheckTx::default();
     let cresp = app.check_tx(&creq);
     assert_ne!(0, cresp.code);
+    assert_eq!(RejectionCode::Deserialize.code(), cresp.code);
+    assert_eq!(REJECTION_CODESPACE, cresp.codespace);
+    assert_eq!(
+        TxRejection::new(RejectionCode::Deserialize),
+        TxRejection::decode(&mut cresp.data.as_slice()).unwrap()
+    );
 }
 
 #[test]
@@ -795,6 +802,10 @@ fn valid_commit_should_persist() {
             .unwrap(),
     );
     assert!(!new_utxos.any());
+    let inclusion = chain_storage::lookup_tx_inclusion(&app.storage, &tx.id()).unwrap();
+    let last_state = app.last_state.as_ref().unwrap();
+    assert_eq!(last_state.block_height, inclusion.block_height);
+    assert_eq!(last_state.block_time, inclusion.block_time);
 }
 
 #[test]
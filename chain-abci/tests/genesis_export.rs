@@ -0,0 +1,260 @@
+use abci::*;
+use bit_vec::BitVec;
+use chain_abci::app::ChainNodeApp;
+use chain_abci::enclave_bridge::mock::MockClient;
+use chain_abci::inspect;
+use chain_core::common::MerkleTree;
+use chain_core::init::coin::Coin;
+use chain_core::init::config::InitConfig;
+use chain_core::state::account::{
+    DepositBondTx, StakedStateOpAttributes, StakedStateOpWitness, WithdrawUnbondedTx,
+};
+use chain_core::state::tendermint::BlockHeight;
+use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::input::{TxoPointer, TxoSize};
+use chain_core::tx::data::output::TxOut;
+use chain_core::tx::witness::tree::RawXOnlyPubkey;
+use chain_core::tx::witness::TxInWitness;
+use chain_core::tx::{PlainTxAux, TransactionId, TxAux, TxEnclaveAux};
+use chain_storage::LookupItem;
+use mock_utils::encrypt;
+use parity_scale_codec::Encode;
+use secp256k1::key::XOnlyPublicKey;
+use secp256k1::schnorrsig::schnorr_sign;
+use secp256k1::Message;
+use test_common::chain_env::{get_account, get_ecdsa_witness, ChainEnv, DEFAULT_GENESIS_TIME};
+
+fn commit_block(
+    app: &mut ChainNodeApp<MockClient>,
+    env: &ChainEnv,
+    height: i64,
+    time: u64,
+    txs: &[TxAux],
+) {
+    let mut req = env.req_begin_block_with_time(height, 0, time as i64);
+    if height > 1 {
+        req.set_last_commit_info(env.last_commit_info_signed());
+    }
+    app.begin_block(&req);
+    for tx in txs {
+        let rsp = app.deliver_tx(&RequestDeliverTx {
+            tx: tx.encode(),
+            ..Default::default()
+        });
+        assert_eq!(0, rsp.code, "{}", rsp.log);
+    }
+    app.end_block(&RequestEndBlock {
+        height,
+        ..Default::default()
+    });
+    app.commit(&RequestCommit::new());
+}
+
+#[test]
+fn exported_genesis_should_restart_with_the_same_stakings() {
+    let (env, storage) = ChainEnv::new(Coin::max(), Coin::zero(), 2);
+    let read_only = storage.get_read_only();
+    let mut app = env.chain_node(storage);
+    let _rsp = app.init_chain(&env.req_init_chain());
+
+    for height in 1..=2 {
+        let mut req = env.req_begin_block(height, 0);
+        if height == 1 {
+            // jails and slashes the second validator
+            req.byzantine_validators = vec![env.byzantine_evidence(1)].into();
+        }
+        app.begin_block(&req);
+        let tx = env.unbond_tx(Coin::new(10_000_000_000).unwrap(), height as u64 - 1, 0);
+        let rsp = app.deliver_tx(&RequestDeliverTx {
+            tx: tx.encode(),
+            ..Default::default()
+        });
+        assert_eq!(0, rsp.code);
+        app.end_block(&RequestEndBlock {
+            height,
+            ..Default::default()
+        });
+        app.commit(&RequestCommit::new());
+    }
+
+    assert!(matches!(
+        inspect::export_genesis(&read_only, Some(BlockHeight::new(3))),
+        Err(inspect::InspectError::ExportHeight { .. })
+    ));
+    // the top level states are not stored without a tx query address
+    assert!(matches!(
+        inspect::export_genesis(&read_only, Some(BlockHeight::new(1))),
+        Err(inspect::InspectError::NoHistoricalState(_))
+    ));
+    let config = inspect::export_genesis(&read_only, Some(BlockHeight::new(2))).unwrap();
+    // only the active validator
+    assert_eq!(1, config.council_nodes.len());
+    assert!(config.council_nodes.contains_key(&env.accounts[0].address));
+    let json = serde_json::to_string(&config).unwrap();
+    let config: InitConfig = serde_json::from_str(&json).unwrap();
+    let genesis = config
+        .validate_config_get_genesis(DEFAULT_GENESIS_TIME)
+        .unwrap();
+    assert_eq!(2, genesis.accounts.len());
+
+    let (new_env, new_storage) = ChainEnv::new_from_init_config(config, env.accounts.clone());
+    let mut new_app = new_env.chain_node(new_storage);
+    let _rsp = new_app.init_chain(&new_env.req_init_chain());
+
+    let last_state = app.last_state.as_ref().unwrap();
+    let new_state = new_app.last_state.as_ref().unwrap();
+    assert_eq!(
+        last_state.top_level.account_root,
+        new_state.top_level.account_root
+    );
+    assert_eq!(
+        last_state.top_level.rewards_pool,
+        new_state.top_level.rewards_pool
+    );
+    assert_eq!(last_state.utxo_coins, new_state.utxo_coins);
+    assert_eq!(
+        last_state.staking_table.get_chosen_validators(),
+        new_state.staking_table.get_chosen_validators()
+    );
+    new_app.check_circulating_coins();
+
+    let jailed = get_account(&env.accounts[1].staking_address(), &new_app);
+    assert!(jailed.is_jailed());
+    assert!(jailed.last_slash.is_some());
+
+    // the nonces are carried over
+    new_app.begin_block(&new_env.req_begin_block(1, 0));
+    let tx = new_env.unbond_tx(Coin::new(10_000_000_000).unwrap(), 2, 0);
+    let rsp = new_app.deliver_tx(&RequestDeliverTx {
+        tx: tx.encode(),
+        ..Default::default()
+    });
+    assert_eq!(0, rsp.code, "{}", rsp.log);
+}
+
+#[test]
+fn exported_genesis_should_carry_the_unspent_outputs() {
+    let (env, storage) = ChainEnv::new(Coin::max(), Coin::zero(), 1);
+    let read_only = storage.get_read_only();
+    let mut app = env.chain_node(storage);
+    // the top level states are only stored with a tx query address
+    app.tx_query_address = Some("localhost:3443".to_owned());
+    let _rsp = app.init_chain(&env.req_init_chain());
+
+    let secp = secp256k1::SECP256K1;
+    let account = &env.accounts[0];
+    let x_public_key = XOnlyPublicKey::from_secret_key(&secp, &account.secret_key);
+    let merkle_tree = MerkleTree::new(vec![RawXOnlyPubkey::from(x_public_key.serialize())]);
+    let address = ExtendedAddr::OrTree(merkle_tree.root_hash());
+    let amount = Coin::new(10_000_000_000).unwrap();
+    let withdraw_tx = |nonce: u64, values: &[u64], unbonded_from: u64| {
+        let tx = WithdrawUnbondedTx::new(
+            nonce,
+            values
+                .iter()
+                .map(|value| {
+                    TxOut::new_with_timelock(
+                        address.clone(),
+                        Coin::new(*value).unwrap(),
+                        unbonded_from,
+                    )
+                })
+                .collect(),
+            TxAttributes::new(0),
+        );
+        let witness =
+            StakedStateOpWitness::new(get_ecdsa_witness(&secp, &tx.id(), &account.secret_key));
+        let txid = tx.id();
+        let txaux = TxAux::EnclaveTx(TxEnclaveAux::WithdrawUnbondedStakeTx {
+            no_of_outputs: tx.outputs.len() as TxoSize,
+            witness,
+            payload: encrypt(&PlainTxAux::WithdrawUnbondedStakeTx(tx.clone()), tx.id()),
+        });
+        (txid, txaux)
+    };
+    let deposit_tx = |inputs: Vec<TxoPointer>| {
+        let tx = DepositBondTx::new(
+            inputs.clone(),
+            account.staking_address(),
+            StakedStateOpAttributes::new(0),
+        );
+        let witness = inputs
+            .iter()
+            .map(|_| {
+                TxInWitness::TreeSig(
+                    schnorr_sign(
+                        &secp,
+                        &Message::from_slice(&tx.id()).unwrap(),
+                        &account.secret_key,
+                        &mut rand::thread_rng(),
+                    ),
+                    merkle_tree
+                        .generate_proof(RawXOnlyPubkey::from(x_public_key.serialize()))
+                        .unwrap(),
+                )
+            })
+            .collect::<Vec<_>>()
+            .into();
+        TxAux::EnclaveTx(TxEnclaveAux::DepositStakeTx {
+            tx: tx.clone(),
+            payload: encrypt(&PlainTxAux::DepositStakeTx(witness), tx.id()),
+        })
+    };
+
+    let time = DEFAULT_GENESIS_TIME;
+    commit_block(&mut app, &env, 1, time, &[env.unbond_tx(amount, 0, 0)]);
+    let (spent_txid, withdraw) = withdraw_tx(1, &[10_000_000_000], time + 10);
+    commit_block(&mut app, &env, 2, time + 10, &[withdraw]);
+    commit_block(&mut app, &env, 3, time + 10, &[env.unbond_tx(amount, 2, 0)]);
+    let (txid, withdraw) = withdraw_tx(3, &[4_000_000_000, 6_000_000_000], time + 20);
+    commit_block(&mut app, &env, 4, time + 20, &[withdraw]);
+    let deposit = deposit_tx(vec![
+        TxoPointer::new(spent_txid, 0),
+        TxoPointer::new(txid, 0),
+    ]);
+    commit_block(&mut app, &env, 5, time + 20, &[deposit]);
+    commit_block(&mut app, &env, 6, time + 20, &[env.unbond_tx(amount, 4, 0)]);
+
+    // the deposit spent UTXOs after block height 4
+    assert!(matches!(
+        inspect::export_genesis(&read_only, Some(BlockHeight::new(4))),
+        Err(inspect::InspectError::UtxosChanged(_))
+    ));
+    // only staked states changed after block height 5
+    let config = inspect::export_genesis(&read_only, Some(BlockHeight::new(5))).unwrap();
+    let exported = config.exported_state.as_ref().unwrap();
+    assert_eq!(BlockHeight::new(5), exported.height);
+    assert_eq!(4, exported.stakings[0].nonce);
+    // the fully spent outputs are not exported
+    assert_eq!(1, exported.tx_outputs.len());
+    let outputs = &exported.tx_outputs[0];
+    assert_eq!(txid, outputs.txid);
+    assert_eq!(Some(2), outputs.no_of_outputs);
+    assert_eq!(vec![address.clone(), address.clone()], outputs.owners);
+    assert!(!outputs.sealed_log.is_empty());
+    assert_eq!(Coin::new(6_000_000_000).unwrap(), exported.utxo_coins);
+
+    let json = serde_json::to_string(&config).unwrap();
+    let config: InitConfig = serde_json::from_str(&json).unwrap();
+    let (new_env, new_storage) = ChainEnv::new_from_init_config(config, env.accounts.clone());
+    let mut new_app = new_env.chain_node(new_storage);
+    let _rsp = new_app.init_chain(&new_env.req_init_chain());
+    new_app.check_circulating_coins();
+
+    // the exported output can be spent in the new chain (with its sealed transaction)
+    let deposit = deposit_tx(vec![TxoPointer::new(txid, 1)]);
+    commit_block(&mut new_app, &new_env, 1, time + 20, &[deposit]);
+    let spent = BitVec::from_bytes(
+        &new_app
+            .storage
+            .lookup_item(LookupItem::TxMetaSpent, &txid)
+            .unwrap(),
+    );
+    assert!(spent[0] && spent[1]);
+    assert_eq!(
+        Coin::zero(),
+        new_app.last_state.as_ref().unwrap().utxo_coins
+    );
+    new_app.check_circulating_coins();
+}
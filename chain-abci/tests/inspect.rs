@@ -0,0 +1,139 @@
+use abci::*;
+use bit_vec::BitVec;
+use chain_abci::inspect::{self, InspectError};
+use chain_core::init::coin::Coin;
+use chain_core::state::tendermint::BlockHeight;
+use chain_storage::buffer::Get;
+use chain_storage::{
+    ReadOnlyStorage, Storage, StorageConfig, StorageType, CHAIN_ID_KEY, COL_EXTRA, COL_TX_META,
+    NUM_COLUMNS,
+};
+use kvdb::KeyValueDB;
+use parity_scale_codec::Encode;
+use std::io::ErrorKind;
+use std::sync::Arc;
+use test_common::chain_env::ChainEnv;
+
+#[test]
+fn inspect_should_read_committed_storage() {
+    let (env, storage) = ChainEnv::new(Coin::max(), Coin::zero(), 1);
+    let read_only = storage.get_read_only();
+    let mut app = env.chain_node(storage);
+    let _rsp = app.init_chain(&env.req_init_chain());
+
+    for height in 1..=2 {
+        let tx = env.unbond_tx(Coin::new(10_000_000_000).unwrap(), height as u64 - 1, 0);
+        app.begin_block(&env.req_begin_block(height, 0));
+        let rsp = app.deliver_tx(&RequestDeliverTx {
+            tx: tx.encode(),
+            ..Default::default()
+        });
+        assert_eq!(0, rsp.code);
+        app.end_block(&RequestEndBlock {
+            height,
+            ..Default::default()
+        });
+        app.commit(&RequestCommit::new());
+    }
+
+    let state = inspect::last_state(&read_only).unwrap();
+    assert_eq!(BlockHeight::new(2), state.last_block_height);
+    assert_eq!(
+        app.last_state.as_ref().unwrap().last_apphash,
+        state.last_apphash
+    );
+
+    let table = inspect::staking_table(&read_only).unwrap();
+    assert_eq!(1, table.chosen_validators.len());
+    assert_eq!(1, table.validators.len());
+    assert_eq!(
+        env.accounts[0].staking_address(),
+        table.validators[0].staking.address
+    );
+
+    let address = env.accounts[0].staking_address();
+    for (height, nonce) in &[(Some(0), 0), (Some(1), 1), (Some(2), 2), (None, 2)] {
+        let staking = inspect::staked_state(&read_only, &address, height.map(BlockHeight::new))
+            .unwrap()
+            .unwrap();
+        assert_eq!(*nonce, staking.nonce);
+    }
+    assert!(matches!(
+        inspect::staked_state(&read_only, &address, Some(BlockHeight::new(3))),
+        Err(InspectError::NoStakingVersion(_))
+    ));
+
+    let stakings = inspect::dump_stakings(&read_only, None).unwrap();
+    assert_eq!(1, stakings.len());
+
+    let history = inspect::app_hash_history(&read_only, BlockHeight::genesis(), None).unwrap();
+    assert_eq!(3, history.len());
+    assert_eq!(hex::encode(state.last_apphash), history[2].app_hash);
+
+    let report = inspect::check_consistency(&read_only).unwrap();
+    assert!(report.consistent, "{:?}", report);
+
+    assert!(matches!(
+        inspect::utxo(&read_only, &[0u8; 32]),
+        Err(InspectError::TxNotFound)
+    ));
+}
+
+#[test]
+fn inspect_should_report_missing_state_and_utxos() {
+    let db = Arc::new(kvdb_memorydb::create(NUM_COLUMNS));
+    let read_only = ReadOnlyStorage::new_db(db.clone());
+    assert!(matches!(
+        inspect::last_state(&read_only),
+        Err(InspectError::NoAppState)
+    ));
+    assert!(matches!(
+        inspect::staking_table(&read_only),
+        Err(InspectError::NoAppState)
+    ));
+    assert!(matches!(
+        inspect::check_consistency(&read_only),
+        Err(InspectError::NoAppState)
+    ));
+    assert!(matches!(
+        inspect::export_genesis(&read_only, None),
+        Err(InspectError::NoAppState)
+    ));
+
+    let txid = [1u8; 32];
+    let mut spent = BitVec::from_elem(3, false);
+    spent.set(1, true);
+    let mut transaction = db.transaction();
+    transaction.put(COL_TX_META, &txid, &spent.to_bytes());
+    db.write(transaction).unwrap();
+    let utxo = inspect::utxo(&read_only, &txid).unwrap();
+    assert_eq!(hex::encode(txid), utxo.txid);
+    assert_eq!("01000000", utxo.bitmap);
+    assert_eq!(vec![1], utxo.spent);
+    assert!(utxo.inclusion.is_none());
+}
+
+#[test]
+fn read_only_storage_should_open_database_of_running_node() {
+    let base = std::env::temp_dir().join(format!("chain-abci-inspect-{}", std::process::id()));
+    let missing = base.join("missing");
+    let result = ReadOnlyStorage::new(&StorageConfig::new(
+        missing.to_str().unwrap(),
+        StorageType::Node,
+    ));
+    assert_eq!(ErrorKind::NotFound, result.err().unwrap().kind());
+    assert!(!missing.exists());
+
+    let config = StorageConfig::new(base.to_str().unwrap(), StorageType::Node);
+    let mut storage = Storage::new(&config);
+    storage.write_genesis_chain_id(&[0u8; 32], "test-ab");
+    // while the node keeps the database open
+    let read_only = ReadOnlyStorage::new(&config).unwrap();
+    assert_eq!(
+        Some(b"test-ab".to_vec()),
+        read_only.get(&(COL_EXTRA, CHAIN_ID_KEY.to_vec()))
+    );
+    drop(read_only);
+    drop(storage);
+    std::fs::remove_dir_all(base).unwrap();
+}
@@ -0,0 +1,209 @@
+use abci::*;
+use chain_abci::app::mempool::MAX_QUEUED_STAKING_TXS;
+use chain_abci::app::{BufferType, ChainNodeApp};
+use chain_abci::enclave_bridge::mock::MockClient;
+use chain_core::init::coin::Coin;
+use chain_core::state::account::Nonce;
+use chain_core::tx::rejection::{RejectionCode, RejectionDetail, TxRejection};
+use chain_core::tx::TxAux;
+use chain_storage::buffer::Get;
+use parity_scale_codec::{Decode, Encode};
+use test_common::chain_env::{get_account, ChainEnv};
+
+fn unbond_amount() -> Coin {
+    Coin::new(10_000_000_000).unwrap()
+}
+
+fn init_app() -> (ChainEnv, ChainNodeApp<MockClient>) {
+    let (env, storage) = ChainEnv::new(Coin::max(), Coin::zero(), 1);
+    let mut app = env.chain_node(storage);
+    let _rsp = app.init_chain(&env.req_init_chain());
+    (env, app)
+}
+
+fn rejection(resp: &ResponseCheckTx) -> TxRejection {
+    TxRejection::decode(&mut resp.data.as_slice()).unwrap()
+}
+
+fn mempool_nonce(env: &ChainEnv, app: &ChainNodeApp<MockClient>) -> Nonce {
+    app.staking_getter(BufferType::Mempool)
+        .get(&env.accounts[0].staking_address())
+        .unwrap()
+        .nonce
+}
+
+/// Tendermint's FIFO mempool: the transactions that passed CheckTx
+#[derive(Default)]
+struct Mempool(Vec<TxAux>);
+
+impl Mempool {
+    fn check_tx(&mut self, app: &mut ChainNodeApp<MockClient>, tx: &TxAux) -> ResponseCheckTx {
+        let rsp = app.check_tx(&RequestCheckTx {
+            tx: tx.encode(),
+            ..Default::default()
+        });
+        if rsp.code == 0 {
+            self.0.push(tx.clone());
+        }
+        rsp
+    }
+
+    /// delivers all the transactions that passed CheckTx (in FIFO order) in a new block
+    /// and commits it
+    fn commit_block(&mut self, app: &mut ChainNodeApp<MockClient>, env: &ChainEnv, height: i64) {
+        app.begin_block(&env.req_begin_block(height, 0));
+        for tx in self.0.drain(..) {
+            let rsp = app.deliver_tx(&RequestDeliverTx {
+                tx: tx.encode(),
+                ..Default::default()
+            });
+            assert_eq!(0, rsp.code, "{}", rsp.log);
+        }
+        app.end_block(&RequestEndBlock {
+            height,
+            ..Default::default()
+        });
+        app.commit(&RequestCommit::new());
+    }
+}
+
+#[test]
+fn queued_staking_txs_should_be_accepted_in_nonce_order() {
+    let (env, mut app) = init_app();
+    let mut mempool = Mempool::default();
+    let tx0 = env.unbond_tx(unbond_amount(), 0, 0);
+    let tx1 = env.unbond_tx(unbond_amount(), 1, 0);
+    let tx2 = env.unbond_tx(unbond_amount(), 2, 0);
+    let tx3 = env.unbond_tx(unbond_amount(), 3, 0);
+
+    // nonce gap: queued (and not accepted into Tendermint's mempool)
+    for tx in [&tx3, &tx2, &tx1].iter() {
+        let rsp = mempool.check_tx(&mut app, tx);
+        assert_eq!(RejectionCode::StakingTxQueued.code(), rsp.code);
+    }
+    assert_eq!(
+        RejectionDetail::Nonce {
+            expected: 0,
+            actual: 1
+        },
+        rejection(&mempool.check_tx(&mut app, &tx1)).detail
+    );
+    assert_eq!(3, app.staking_mempool.len());
+    assert_eq!(0, mempool_nonce(&env, &app));
+
+    assert_eq!(0, mempool.check_tx(&mut app, &tx0).code);
+    assert_eq!(1, mempool_nonce(&env, &app));
+    // broadcast again when the preceding ones were accepted
+    let rsp = mempool.check_tx(&mut app, &tx2);
+    assert_eq!(RejectionCode::StakingTxQueued.code(), rsp.code);
+    assert_eq!(0, mempool.check_tx(&mut app, &tx1).code);
+    assert_eq!(2, app.staking_mempool.len());
+
+    mempool.commit_block(&mut app, &env, 1);
+    let account = get_account(&env.accounts[0].staking_address(), &app);
+    assert_eq!(2, account.nonce);
+    assert_eq!(2, mempool_nonce(&env, &app));
+    let rsp = mempool.check_tx(&mut app, &tx1);
+    assert_eq!(RejectionCode::IncorrectNonce.code(), rsp.code);
+
+    assert_eq!(0, mempool.check_tx(&mut app, &tx2).code);
+    assert_eq!(0, mempool.check_tx(&mut app, &tx3).code);
+    assert!(app.staking_mempool.is_empty());
+    mempool.commit_block(&mut app, &env, 2);
+    let account = get_account(&env.accounts[0].staking_address(), &app);
+    assert_eq!(4, account.nonce);
+}
+
+#[test]
+fn queued_staking_txs_should_be_bounded_per_account() {
+    let (env, mut app) = init_app();
+    let mut mempool = Mempool::default();
+    let last = env.unbond_tx(unbond_amount(), MAX_QUEUED_STAKING_TXS, 0);
+    let rsp = mempool.check_tx(&mut app, &last);
+    assert_eq!(RejectionCode::StakingTxQueued.code(), rsp.code);
+
+    let too_far = env.unbond_tx(unbond_amount(), MAX_QUEUED_STAKING_TXS + 1, 0);
+    let rsp = mempool.check_tx(&mut app, &too_far);
+    assert_eq!(RejectionCode::IncorrectNonce.code(), rsp.code);
+    assert_eq!(
+        RejectionDetail::Nonce {
+            expected: 0,
+            actual: MAX_QUEUED_STAKING_TXS + 1
+        },
+        rejection(&rsp).detail
+    );
+    assert_eq!(1, app.staking_mempool.len());
+}
+
+#[test]
+fn queued_staking_tx_should_not_be_replaced_by_the_same_fee() {
+    let (env, mut app) = init_app();
+    let mut mempool = Mempool::default();
+    let tx1 = env.unbond_tx(unbond_amount(), 1, 0);
+    let tx1_same_fee = env.unbond_tx((unbond_amount() + Coin::unit()).unwrap(), 1, 0);
+
+    let rsp = mempool.check_tx(&mut app, &tx1);
+    assert_eq!(RejectionCode::StakingTxQueued.code(), rsp.code);
+    let rsp = mempool.check_tx(&mut app, &tx1_same_fee);
+    assert_eq!(RejectionCode::ReplacementUnderpriced.code(), rsp.code);
+    assert!(matches!(
+        rejection(&rsp).detail,
+        RejectionDetail::Replacement { pending_fee, fee } if pending_fee == fee
+    ));
+    assert_eq!(1, app.staking_mempool.len());
+}
+
+#[cfg(feature = "priority-fee")]
+#[test]
+fn queued_staking_tx_should_be_replaced_by_higher_fee() {
+    let (env, mut app) = init_app();
+    let mut mempool = Mempool::default();
+    let tx0 = env.unbond_tx(unbond_amount(), 0, 0);
+    let tx1 = env.unbond_tx(unbond_amount(), 1, 0);
+    let tx1_bumped = env.unbond_tx_with_priority_fee(unbond_amount(), 1, 0, Coin::unit());
+
+    let rsp = mempool.check_tx(&mut app, &tx1);
+    assert_eq!(RejectionCode::StakingTxQueued.code(), rsp.code);
+    let rsp = mempool.check_tx(&mut app, &tx1_bumped);
+    assert_eq!(RejectionCode::StakingTxQueued.code(), rsp.code);
+    assert_eq!(1, app.staking_mempool.len());
+
+    assert_eq!(0, mempool.check_tx(&mut app, &tx0).code);
+    // the replaced one is rejected when its nonce is the next one
+    let rsp = mempool.check_tx(&mut app, &tx1);
+    assert_eq!(RejectionCode::ReplacementUnderpriced.code(), rsp.code);
+    assert_eq!(0, mempool.check_tx(&mut app, &tx1_bumped).code);
+
+    mempool.commit_block(&mut app, &env, 1);
+    let account = get_account(&env.accounts[0].staking_address(), &app);
+    assert_eq!(2, account.nonce);
+    assert_eq!((unbond_amount() * 2).unwrap(), account.unbonded);
+}
+
+#[cfg(not(feature = "priority-fee"))]
+#[test]
+fn priority_fee_should_be_rejected_before_app_version_3() {
+    let (env, mut app) = init_app();
+    let mut mempool = Mempool::default();
+    let tx0_bumped = env.unbond_tx_with_priority_fee(unbond_amount(), 0, 0, Coin::unit());
+    let rsp = mempool.check_tx(&mut app, &tx0_bumped);
+    assert_eq!(RejectionCode::Deserialize.code(), rsp.code);
+}
+
+#[test]
+fn accepted_staking_tx_should_not_be_replaced() {
+    let (env, mut app) = init_app();
+    let mut mempool = Mempool::default();
+    let tx0 = env.unbond_tx(unbond_amount(), 0, 0);
+    assert_eq!(0, mempool.check_tx(&mut app, &tx0).code);
+
+    // Tendermint keeps the accepted one in its mempool
+    let tx0_other = env.unbond_tx((unbond_amount() + Coin::unit()).unwrap(), 0, 0);
+    let rsp = mempool.check_tx(&mut app, &tx0_other);
+    assert_eq!(RejectionCode::IncorrectNonce.code(), rsp.code);
+    assert_eq!(1, mempool_nonce(&env, &app));
+
+    mempool.commit_block(&mut app, &env, 1);
+    let account = get_account(&env.accounts[0].staking_address(), &app);
+    assert_eq!(1, account.nonce);
+}
@@ -0,0 +1,99 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use abci::*;
+use chain_abci::metrics;
+use chain_core::init::coin::Coin;
+use parity_scale_codec::Encode;
+use test_common::chain_env::ChainEnv;
+
+fn scrape(address: &std::net::SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: {}\r\nAccept: */*\r\n\r\n",
+        path, address
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn metrics_endpoint_should_report_abci_requests() {
+    let (address, _handle) = metrics::serve("127.0.0.1:0").unwrap();
+
+    let (env, storage) = ChainEnv::new(Coin::max(), Coin::zero(), 1);
+    let mut app = env.chain_node(storage);
+    let _rsp = app.init_chain(&env.req_init_chain());
+
+    let rejected = app.check_tx(&RequestCheckTx {
+        tx: vec![0xff],
+        ..Default::default()
+    });
+    assert_ne!(0, rejected.code);
+
+    for height in 1..=2 {
+        let tx = env.unbond_tx(Coin::new(10_000_000_000).unwrap(), height as u64 - 1, 0);
+        let rsp = app.check_tx(&RequestCheckTx {
+            tx: tx.encode(),
+            ..Default::default()
+        });
+        assert_eq!(0, rsp.code);
+        app.begin_block(&env.req_begin_block(height, 0));
+        let rsp = app.deliver_tx(&RequestDeliverTx {
+            tx: tx.encode(),
+            ..Default::default()
+        });
+        assert_eq!(0, rsp.code);
+        app.end_block(&RequestEndBlock {
+            height,
+            ..Default::default()
+        });
+        app.commit(&RequestCommit::new());
+    }
+
+    let response = scrape(&address, "/metrics");
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    for expected in &[
+        "chain_abci_request_duration_seconds_count{method=\"check_tx\"} 3",
+        "chain_abci_request_duration_seconds_count{method=\"deliver_tx\"} 2",
+        "chain_abci_request_duration_seconds_count{method=\"commit\"} 2",
+        "chain_abci_rejected_txs_total{connection=\"check_tx\",reason=\"Deserialize\"} 1",
+        "chain_abci_staking_table_duration_seconds_count{phase=\"end_block\"} 2",
+        "chain_abci_storage_flush_duration_seconds_count{storage=\"kv\"} 2",
+        "chain_abci_staking_table_validators 1",
+        "chain_abci_chosen_validators 1",
+        "chain_abci_jailed_validators 0",
+        "chain_abci_block_height 2",
+        "chain_abci_pending_staking_txs 0",
+    ] {
+        assert!(
+            response.contains(expected),
+            "missing `{}` in:\n{}",
+            expected,
+            response
+        );
+    }
+
+    assert!(scrape(&address, "/").starts_with("HTTP/1.1 404 Not Found"));
+}
+
+#[test]
+fn metrics_endpoint_should_time_out_stalled_clients() {
+    let (address, _handle) = metrics::serve("127.0.0.1:0").unwrap();
+
+    // connected, but never sends the request
+    let mut stalled = TcpStream::connect(&address).unwrap();
+    stalled
+        .set_read_timeout(Some(Duration::from_secs(30)))
+        .unwrap();
+    let start = Instant::now();
+    assert!(scrape(&address, "/metrics").starts_with("HTTP/1.1 200 OK"));
+    assert!(start.elapsed() < Duration::from_secs(30));
+    // closed by the server
+    let mut response = Vec::new();
+    assert_eq!(0, stalled.read_to_end(&mut response).unwrap());
+}
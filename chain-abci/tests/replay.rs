@@ -0,0 +1,110 @@
+use abci::*;
+use chain_abci::replay::{self, BlockFixture, ReplayFixture};
+use chain_core::init::coin::Coin;
+use chain_core::state::tendermint::BlockHeight;
+use chain_storage::{Storage, NUM_COLUMNS};
+use parity_scale_codec::Encode;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use test_common::chain_env::{ChainEnv, DEFAULT_GENESIS_TIME};
+
+#[test]
+fn replay_should_match_the_app_hashes_and_find_the_divergent_block() {
+    let (env, storage) = ChainEnv::new(Coin::max(), Coin::zero(), 1);
+    let mut app = env.chain_node(storage);
+    let _rsp = app.init_chain(&env.req_init_chain());
+
+    let mut blocks = vec![];
+    for height in 1..=3 {
+        let tx = env.unbond_tx(Coin::new(10_000_000_000).unwrap(), height - 1, 0);
+        app.begin_block(&env.req_begin_block(height as i64, 0));
+        let rsp = app.deliver_tx(&RequestDeliverTx {
+            tx: tx.encode(),
+            ..Default::default()
+        });
+        assert_eq!(0, rsp.code);
+        app.end_block(&RequestEndBlock {
+            height: height as i64,
+            ..Default::default()
+        });
+        let rsp = app.commit(&RequestCommit::new());
+        blocks.push(BlockFixture {
+            height,
+            time: DEFAULT_GENESIS_TIME,
+            txs: vec![hex::encode(tx.encode())],
+            last_commit: None,
+            evidence: vec![],
+            app_hash: Some(hex::encode_upper(&rsp.data)),
+        });
+    }
+
+    let mut fixture = ReplayFixture {
+        chain_id: "test-00".to_owned(),
+        genesis_time: DEFAULT_GENESIS_TIME,
+        max_evidence_age: env.max_evidence_age,
+        app_state: env.init_config.clone(),
+        blocks,
+    };
+    let report = replay::replay(&fixture, &BTreeMap::new()).unwrap();
+    assert_eq!(None, report.first_divergent_height);
+    assert_eq!(3, report.blocks.len());
+    for (block, nonce) in report.blocks.iter().zip(1..) {
+        assert!(!block.diverged());
+        assert_eq!(1, block.txs.len());
+        assert_eq!(0, block.txs[0].code);
+        let changes = &block.txs[0].changes.stakings;
+        assert_eq!(1, changes.len());
+        assert_eq!(env.accounts[0].staking_address(), changes[0].address);
+        assert_eq!(nonce - 1, changes[0].before.as_ref().unwrap().nonce);
+        assert_eq!(nonce, changes[0].after.nonce);
+    }
+
+    // the other node's app hashes take precedence over the fixture ones
+    let mut expected = BTreeMap::new();
+    expected.insert(BlockHeight::new(2), [0u8; 32]);
+    let report = replay::replay(&fixture, &expected).unwrap();
+    assert_eq!(Some(BlockHeight::new(2)), report.first_divergent_height);
+    assert!(!report.blocks[0].diverged());
+
+    // replays the rest of the blocks on top of the state stored after the first one
+    let db = Arc::new(kvdb_memorydb::create(NUM_COLUMNS));
+    let mut first = fixture.clone();
+    first.blocks.truncate(1);
+    replay::replay_from(&first, Storage::new_db(db.clone()), &BTreeMap::new()).unwrap();
+    let mut rest = fixture.clone();
+    rest.blocks.remove(0);
+    let report = replay::replay_from(&rest, Storage::new_db(db), &BTreeMap::new()).unwrap();
+    assert_eq!(None, report.first_divergent_height);
+    assert_eq!(
+        vec![BlockHeight::new(2), BlockHeight::new(3)],
+        report
+            .blocks
+            .iter()
+            .map(|block| block.height)
+            .collect::<Vec<_>>()
+    );
+    // which can't be replayed from the genesis
+    assert!(matches!(
+        replay::replay(&rest, &BTreeMap::new()),
+        Err(replay::ReplayError::HeightGap {
+            expected: 1,
+            actual: 2
+        })
+    ));
+
+    let mut other_chain = fixture.clone();
+    other_chain.chain_id = "test-€".to_owned();
+    assert!(matches!(
+        replay::replay(&other_chain, &BTreeMap::new()),
+        Err(replay::ReplayError::ChainId(_))
+    ));
+
+    fixture.blocks.remove(1);
+    assert!(matches!(
+        replay::replay(&fixture, &BTreeMap::new()),
+        Err(replay::ReplayError::HeightGap {
+            expected: 2,
+            actual: 3
+        })
+    ));
+}
@@ -0,0 +1,52 @@
+use chain_core::init::coin::Coin;
+use test_common::chain_env::{get_account, ChainEnv};
+use test_common::simulator::Simulator;
+
+#[test]
+fn simulated_network_should_agree_on_punishments_and_rewards() {
+    let dist = Coin::new(9_0000_0000_0000_0000).unwrap();
+    let (env, _storage) = ChainEnv::new(dist, dist, 3);
+    let mut sim = Simulator::new(env, 3);
+
+    let results = sim.run(2);
+    assert!(results
+        .iter()
+        .all(|result| result.validator_updates.is_empty()));
+    assert_eq!(3, sim.validators().len());
+    assert_ne!(results[0].proposer, results[1].proposer);
+
+    // missing a vote makes the validator inactive (the update takes effect two blocks later)
+    sim.set_offline(2, true);
+    let result = sim.next_block();
+    assert_eq!(1, result.validator_updates.len());
+    assert_eq!(0, result.validator_updates[0].power);
+    assert!(sim.validator_power(2).is_some());
+    sim.next_block();
+    assert!(sim.validator_power(2).is_none());
+
+    sim.double_sign(1);
+    let result = sim.next_block();
+    assert_eq!(1, result.validator_updates.len());
+    assert_eq!(0, result.validator_updates[0].power);
+    let jailed = get_account(&sim.env.accounts[1].staking_address(), &sim.nodes[0]);
+    assert!(jailed.is_jailed());
+    sim.next_block();
+    assert_eq!(1, sim.validators().len());
+    assert!(sim.validator_power(0).is_some());
+
+    let tx = sim.env.unbond_tx(Coin::unit(), 0, 0);
+    sim.submit_tx_delayed(tx, 1);
+    assert!(sim.next_block().tx_results.is_empty());
+    let result = sim.next_block();
+    assert_eq!(1, result.tx_results.len());
+    assert_eq!(0, result.tx_results[0].code);
+
+    let before = get_account(&sim.env.accounts[0].staking_address(), &sim.nodes[0]);
+    sim.block_interval = 24 * 60 * 60;
+    sim.next_block();
+    let after = get_account(&sim.env.accounts[0].staking_address(), &sim.nodes[0]);
+    assert!(after.bonded > before.bonded);
+    for node in sim.nodes.iter() {
+        node.check_circulating_coins();
+    }
+}
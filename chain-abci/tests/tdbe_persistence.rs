@@ -0,0 +1,280 @@
+use std::os::unix::net::UnixStream;
+use std::thread;
+
+use abci::*;
+use chain_abci::app::ChainNodeApp;
+use chain_abci::enclave_bridge::mock::MockClient;
+use chain_abci::enclave_bridge::persistence::{
+    monitor_tdbe, NodeJoinConfig, TdbePersistence, TdbeProgress, TdbeStatus,
+};
+use chain_core::init::coin::Coin;
+use chain_core::state::account::{MLSInit, NodeMetadata};
+use chain_core::state::validator::NodeJoinRequestTx;
+use enclave_protocol::{codec::StreamWrite, tdbe_protocol::PersistenceCommand};
+use parity_scale_codec::{Decode, Encode};
+use test_common::chain_env::{mock_confidential_init_node_join, ChainEnv};
+
+fn query(app: &ChainNodeApp<MockClient>, path: &str, data: Vec<u8>) -> ResponseQuery {
+    app.query(&RequestQuery {
+        path: path.into(),
+        data,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn tdbe_catch_up_runs_concurrently() {
+    let (env, storage) =
+        ChainEnv::new_with_customizer(Coin::max(), Coin::zero(), 2, |parameters| {
+            parameters.required_council_node_stake = (Coin::max() / 10).unwrap();
+        });
+    let mut app = env.chain_node(storage);
+    let _rsp = app.init_chain(&env.req_init_chain());
+
+    // bump the nonce of the first account
+    app.begin_block(&env.req_begin_block(1, 0));
+    let tx_aux = env.unbond_tx(Coin::new(5000000000000000000).unwrap(), 0, 0);
+    let rsp_tx = app.deliver_tx(&RequestDeliverTx {
+        tx: tx_aux.encode(),
+        ..Default::default()
+    });
+    assert_eq!(0, rsp_tx.code);
+    app.end_block(&RequestEndBlock {
+        height: 1,
+        ..Default::default()
+    });
+    app.commit(&RequestCommit::default());
+
+    app.tx_query_address = Some("localhost:3443".to_owned());
+    app.tdbe_status = TdbeStatus::catching_up(&app.storage);
+    assert_eq!(
+        app.tdbe_status.progress(),
+        TdbeProgress::CatchingUp {
+            last_fetched_block: None
+        }
+    );
+
+    let (mut tdbe_stream, persistence_receiver) = UnixStream::pair().unwrap();
+    let (name, security_contact, consensus_pubkey) = {
+        let node = &env.council_nodes[0].1;
+        (
+            node.node_info.name.clone(),
+            node.node_info.security_contact.clone(),
+            node.consensus_pubkey.clone(),
+        )
+    };
+    let persistence = TdbePersistence::new(
+        app.storage.temp_hack_for_tdbe(),
+        app.tdbe_status.clone(),
+        Some(NodeJoinConfig {
+            staking_address: env.accounts[0].staking_address(),
+            name: name.clone(),
+            security_contact,
+            consensus_pubkey: consensus_pubkey.clone(),
+        }),
+        0,
+    );
+    let persistence_thread = thread::spawn(move || persistence.run(persistence_receiver));
+
+    // queries not needing the sealed data are served during the catch-up
+    assert_eq!(query(&app, "store", tx_aux.tx_id().to_vec()).code, 0);
+    let resp = query(&app, "txquery", vec![]);
+    assert_eq!(resp.code, 5);
+    assert!(resp.log.contains("TDBE catch-up in progress"));
+    let sealed_txid = [1u8; 32];
+    assert_eq!(query(&app, "sealed", sealed_txid.to_vec()).code, 5);
+
+    PersistenceCommand::Store {
+        transaction_id: sealed_txid,
+        sealed_log: vec![2u8; 64],
+    }
+    .write_to(&mut tdbe_stream)
+    .unwrap();
+    PersistenceCommand::Finish {
+        last_fetched_block: 1,
+    }
+    .write_to(&mut tdbe_stream)
+    .unwrap();
+
+    let (add, commit) = match mock_confidential_init_node_join().init_payload {
+        MLSInit::NodeJoin { add, commit } => (add, commit),
+        MLSInit::Genesis(_) => unreachable!(),
+    };
+    PersistenceCommand::NodeJoin {
+        add: add.clone(),
+        commit: commit.clone(),
+    }
+    .write_to(&mut tdbe_stream)
+    .unwrap();
+    PersistenceCommand::SealEnclaveState {
+        sealed_state: vec![3u8; 16],
+    }
+    .write_to(&mut tdbe_stream)
+    .unwrap();
+    drop(tdbe_stream);
+    persistence_thread.join().unwrap();
+
+    assert_eq!(
+        app.tdbe_status.progress(),
+        TdbeProgress::CaughtUp {
+            last_fetched_block: Some(1)
+        }
+    );
+    assert_eq!(chain_storage::get_last_fetched_block(&app.storage), Some(1));
+    assert_eq!(
+        chain_storage::get_sealed_enclave_state(&app.storage),
+        Some(vec![3u8; 16])
+    );
+    let resp = query(&app, "sealed", sealed_txid.to_vec());
+    assert_eq!(resp.code, 0);
+    assert_eq!(resp.value, vec![2u8; 64]);
+    assert_eq!(query(&app, "sealed", [4u8; 32].to_vec()).code, 1);
+    let resp = query(&app, "txquery", vec![]);
+    assert_eq!(resp.code, 0);
+    assert_eq!(resp.value, b"localhost:3443".to_vec());
+
+    let resp = query(&app, "nodejoin", vec![]);
+    assert_eq!(resp.code, 0);
+    let tx = NodeJoinRequestTx::decode(&mut resp.value.as_slice()).unwrap();
+    assert_eq!(tx.nonce, 1);
+    assert_eq!(tx.address, env.accounts[0].staking_address());
+    match tx.node_meta {
+        NodeMetadata::CouncilNode(node) => {
+            assert_eq!(node.node_info.name, name);
+            assert_eq!(node.consensus_pubkey, consensus_pubkey);
+            assert_eq!(
+                node.node_info.confidential_init.init_payload,
+                MLSInit::NodeJoin { add, commit }
+            );
+        }
+        NodeMetadata::CommunityNode(_) => unreachable!(),
+    }
+}
+
+#[test]
+fn tdbe_status_is_tracked_via_last_fetched_block() {
+    let (env, storage) = ChainEnv::new(Coin::max(), Coin::zero(), 1);
+    let app = env.chain_node(storage);
+    let status = TdbeStatus::catching_up(&app.storage);
+    assert_eq!(
+        status.progress(),
+        TdbeProgress::CatchingUp {
+            last_fetched_block: None
+        }
+    );
+
+    // the stream is closed before `Finish`: the data fetched so far is kept
+    let mut commands = Vec::new();
+    PersistenceCommand::Store {
+        transaction_id: [1u8; 32],
+        sealed_log: vec![2u8; 64],
+    }
+    .write_to(&mut commands)
+    .unwrap();
+    TdbePersistence::new(app.storage.temp_hack_for_tdbe(), status.clone(), None, 0)
+        .run(commands.as_slice());
+    assert!(status.is_catching_up());
+    assert_eq!(
+        chain_storage::get_sealed_log(&app.storage, &[1u8; 32]),
+        Some(vec![2u8; 64])
+    );
+
+    let mut commands = Vec::new();
+    PersistenceCommand::Finish {
+        last_fetched_block: 10,
+    }
+    .write_to(&mut commands)
+    .unwrap();
+    // node join payloads without the node join config are dropped
+    PersistenceCommand::NodeJoin {
+        add: vec![],
+        commit: vec![],
+    }
+    .write_to(&mut commands)
+    .unwrap();
+    TdbePersistence::new(app.storage.temp_hack_for_tdbe(), status.clone(), None, 0)
+        .run(commands.as_slice());
+    assert!(!status.is_catching_up());
+    assert_eq!(chain_storage::get_node_join_tx(&app.storage), None);
+
+    // a restarted catch-up continues from the last fetched block
+    assert_eq!(
+        TdbeStatus::catching_up(&app.storage).progress(),
+        TdbeProgress::CatchingUp {
+            last_fetched_block: Some(10)
+        }
+    );
+}
+
+#[test]
+fn tdbe_stopping_before_finish_fails_the_catch_up() {
+    let (env, storage) = ChainEnv::new(Coin::max(), Coin::zero(), 1);
+    let mut app = env.chain_node(storage);
+    app.tx_query_address = Some("localhost:3443".to_owned());
+    app.tdbe_status = TdbeStatus::catching_up(&app.storage);
+
+    let (mut tdbe_stream, persistence_receiver) = UnixStream::pair().unwrap();
+    let persistence = TdbePersistence::new(
+        app.storage.temp_hack_for_tdbe(),
+        app.tdbe_status.clone(),
+        None,
+        0,
+    );
+    let persistence_thread = thread::spawn(move || persistence.run(persistence_receiver));
+    // TDBE's end of the stream is closed when its thread panics
+    let tdbe_thread = thread::spawn(move || {
+        PersistenceCommand::Store {
+            transaction_id: [1u8; 32],
+            sealed_log: vec![2u8; 64],
+        }
+        .write_to(&mut tdbe_stream)
+        .unwrap();
+        panic!("TDBE failed");
+    });
+    monitor_tdbe(tdbe_thread, persistence_thread, app.tdbe_status.clone())
+        .join()
+        .unwrap();
+
+    assert_eq!(
+        app.tdbe_status.progress(),
+        TdbeProgress::Failed {
+            last_fetched_block: None
+        }
+    );
+    assert_eq!(
+        chain_storage::get_sealed_log(&app.storage, &[1u8; 32]),
+        Some(vec![2u8; 64])
+    );
+    let resp = query(&app, "txquery", vec![]);
+    assert_eq!(resp.code, 5);
+    assert!(resp.log.contains("TDBE catch-up failed"));
+
+    // TDBE stopping after the fetch finished doesn't affect the status
+    app.tdbe_status = TdbeStatus::catching_up(&app.storage);
+    let (mut tdbe_stream, persistence_receiver) = UnixStream::pair().unwrap();
+    let persistence = TdbePersistence::new(
+        app.storage.temp_hack_for_tdbe(),
+        app.tdbe_status.clone(),
+        None,
+        0,
+    );
+    let persistence_thread = thread::spawn(move || persistence.run(persistence_receiver));
+    let tdbe_thread = thread::spawn(move || {
+        PersistenceCommand::Finish {
+            last_fetched_block: 3,
+        }
+        .write_to(&mut tdbe_stream)
+        .unwrap();
+    });
+    monitor_tdbe(tdbe_thread, persistence_thread, app.tdbe_status.clone())
+        .join()
+        .unwrap();
+
+    assert_eq!(
+        app.tdbe_status.progress(),
+        TdbeProgress::CaughtUp {
+            last_fetched_block: Some(3)
+        }
+    );
+    assert_eq!(query(&app, "txquery", vec![]).code, 0);
+}
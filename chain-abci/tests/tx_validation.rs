@@ -25,19 +25,21 @@ use chain_core::state::validator::NodeJoinRequestTx;
 use chain_core::tx::data::{
     address::ExtendedAddr,
     attribute::TxAttributes,
+    htlc::{HtlcCondition, HtlcPreimage, HtlcSpend},
     input::{TxoPointer, TxoSize},
-    output::TxOut,
+    output::{RelativeTimelock, TxInclusion, TxOut},
 };
 use chain_core::tx::data::{Tx, TxId};
 use chain_core::tx::fee::FeeAlgorithm;
 use chain_core::tx::fee::{Fee, LinearFee, Milli};
+use chain_core::tx::rejection::{RejectionCode, RejectionDetail, TxRejection};
 use chain_core::tx::witness::tree::RawXOnlyPubkey;
 use chain_core::tx::witness::{TxInWitness, TxWitness};
 use chain_core::tx::PlainTxAux;
 use chain_core::tx::TransactionId;
 use chain_core::tx::TxObfuscated;
 use chain_core::tx::{TxAux, TxEnclaveAux, TxPublicAux};
-use chain_storage::buffer::Get;
+use chain_storage::buffer::{flush_kvdb, BufferStore, Get, KVBuffer};
 use chain_storage::jellyfish::{StakingBufferStore, StakingGetter, Version};
 use chain_storage::{Storage, COL_ENCLAVE_TX, COL_TX_META, NUM_COLUMNS};
 use chain_tx_validation::{
@@ -327,7 +329,13 @@ fn test_account_unbond_verify_fail() {
             0,
             &create_storage(),
         );
-        expect_error_public(&result, PublicTxError::IncorrectNonce);
+        expect_error_public(
+            &result,
+            PublicTxError::IncorrectNonce {
+                expected: 0,
+                actual: 0,
+            },
+        );
     }
     // AccountIncorrectNonce
     {
@@ -338,7 +346,13 @@ fn test_account_unbond_verify_fail() {
             get_account_op_witness(secp256k1::SECP256K1, &tx.id(), &secret_key),
         );
         let result = verify_public_tx(&txaux, &extra_info, NodeInfoWrap::default(), 0, &storage);
-        expect_error_public(&result, PublicTxError::IncorrectNonce);
+        expect_error_public(
+            &result,
+            PublicTxError::IncorrectNonce {
+                expected: 0,
+                actual: 0,
+            },
+        );
     }
     // ZeroCoin
     {
@@ -521,7 +535,14 @@ fn test_account_withdraw_verify_fail() {
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
         assert!(result.is_err());
         let result = verify_unbonded_withdraw_core(&tx, &extra_info, &account);
-        expect_error(&result, Error::InputOutputDoNotMatch);
+        expect_error(
+            &result,
+            Error::InputOutputDoNotMatch {
+                inputs: Coin::zero(),
+                outputs: Coin::zero(),
+                required_fee: Coin::zero(),
+            },
+        );
     }
     // AccountNotFound
     {
@@ -542,7 +563,23 @@ fn test_account_withdraw_verify_fail() {
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
         assert!(result.is_err());
         let result = verify_unbonded_withdraw_core(&tx, &extra_info, &account);
-        expect_error(&result, Error::AccountIncorrectNonce);
+        expect_error(
+            &result,
+            Error::AccountIncorrectNonce {
+                expected: 0,
+                actual: 0,
+            },
+        );
+        assert_eq!(
+            result.unwrap_err().rejection(),
+            TxRejection::with_detail(
+                RejectionCode::IncorrectNonce,
+                RejectionDetail::Nonce {
+                    expected: account.nonce,
+                    actual: 0,
+                },
+            )
+        );
     }
     // AccountWithdrawOutputNotLocked
     {
@@ -675,7 +712,7 @@ fn test_deposit_verify_fail() {
         extra_info.chain_hex_id = DEFAULT_CHAIN_ID + 1;
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
         assert!(result.is_err());
-        let result = verify_bonded_deposit_core(&tx, &witness, &extra_info, vec![]);
+        let result = verify_bonded_deposit_core(&tx, &witness, &extra_info, vec![], &[]);
         expect_error(&result, Error::WrongChainHexId);
     }
     // UnsupportedVersion
@@ -690,7 +727,7 @@ fn test_deposit_verify_fail() {
         );
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
         assert!(result.is_err());
-        let result = verify_bonded_deposit_core(&tx, &witness, &extra_info, vec![]);
+        let result = verify_bonded_deposit_core(&tx, &witness, &extra_info, vec![], &[]);
         expect_error(&result, Error::UnsupportedVersion);
     }
     // NoInputs
@@ -705,7 +742,7 @@ fn test_deposit_verify_fail() {
         );
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
         assert!(result.is_err());
-        let result = verify_bonded_deposit_core(&tx, &witness, &extra_info, vec![]);
+        let result = verify_bonded_deposit_core(&tx, &witness, &extra_info, vec![], &[]);
         expect_error(&result, Error::NoInputs);
     }
     // DuplicateInputs
@@ -721,7 +758,7 @@ fn test_deposit_verify_fail() {
         );
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
         assert!(result.is_err());
-        let result = verify_bonded_deposit_core(&tx, &witness, &extra_info, vec![]);
+        let result = verify_bonded_deposit_core(&tx, &witness, &extra_info, vec![], &[]);
         expect_error(&result, Error::DuplicateInputs);
     }
     // UnexpectedWitnesses
@@ -737,7 +774,7 @@ fn test_deposit_verify_fail() {
         );
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
         assert!(result.is_err());
-        let result = verify_bonded_deposit_core(&tx, &witness, &extra_info, vec![]);
+        let result = verify_bonded_deposit_core(&tx, &witness, &extra_info, vec![], &[]);
         expect_error(&result, Error::UnexpectedWitnesses);
     }
     // MissingWitnesses
@@ -750,7 +787,7 @@ fn test_deposit_verify_fail() {
         );
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
         assert!(result.is_err());
-        let result = verify_bonded_deposit_core(&tx, &vec![].into(), &extra_info, vec![]);
+        let result = verify_bonded_deposit_core(&tx, &vec![].into(), &extra_info, vec![], &[]);
         expect_error(&result, Error::MissingWitnesses);
     }
     // InputSpent
@@ -764,7 +801,7 @@ fn test_deposit_verify_fail() {
         db.write(inittx).unwrap();
 
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
-        expect_error(&result, Error::InputSpent);
+        expect_error(&result, Error::InputSpent { index: 0 });
 
         let mut reset = db.transaction();
         reset.put(
@@ -798,11 +835,9 @@ fn test_deposit_verify_fail() {
             &witness,
             &extra_info,
             vec![TxWithOutputs::Transfer(input_tx)],
+            &[],
         );
-        expect_error(
-            &result,
-            Error::EcdsaCrypto, // FIXME: Error::EcdsaCrypto(secp256k1::Error::InvalidPublicKey),
-        );
+        expect_error(&result, Error::EcdsaCrypto { index: Some(0) });
         let txaux = replace_tx_payload(
             txaux.clone(),
             PlainTxAux::DepositStakeTx(witness),
@@ -815,7 +850,7 @@ fn test_deposit_verify_fail() {
     // InvalidInput
     {
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &create_storage());
-        expect_error(&result, Error::InvalidInput);
+        expect_error(&result, Error::InvalidInput { index: 0 });
     }
     // InputOutputDoNotMatch
     {
@@ -823,8 +858,15 @@ fn test_deposit_verify_fail() {
         extra_info.min_fee_computed = Fee::new(Coin::one());
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
         assert!(result.is_err());
-        let result = verify_bonded_deposit_core(&tx, &witness, &extra_info, vec![]);
-        expect_error(&result, Error::InputOutputDoNotMatch);
+        let result = verify_bonded_deposit_core(&tx, &witness, &extra_info, vec![], &[]);
+        expect_error(
+            &result,
+            Error::InputOutputDoNotMatch {
+                inputs: Coin::zero(),
+                outputs: Coin::zero(),
+                required_fee: Coin::zero(),
+            },
+        );
     }
 }
 
@@ -914,7 +956,7 @@ fn test_transfer_verify_fail() {
         extra_info.chain_hex_id = DEFAULT_CHAIN_ID + 1;
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
         assert!(result.is_err());
-        let result = verify_transfer(&tx, &witness, &extra_info, vec![]);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![], &[]);
         expect_error(&result, Error::WrongChainHexId);
     }
     // UnsupportedVersion
@@ -949,7 +991,7 @@ fn test_transfer_verify_fail() {
     {
         let mut tx = tx.clone();
         tx.outputs.clear();
-        let result = verify_transfer(&tx, &witness, &extra_info, vec![]);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![], &[]);
         expect_error(&result, Error::NoOutputs);
         let txaux = replace_tx_payload(
             txaux.clone(),
@@ -965,7 +1007,7 @@ fn test_transfer_verify_fail() {
         let mut tx = tx.clone();
         let inp = tx.inputs[0].clone();
         tx.inputs.push(inp);
-        let result = verify_transfer(&tx, &witness, &extra_info, vec![]);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![], &[]);
         expect_error(&result, Error::DuplicateInputs);
         let txaux = replace_tx_payload(
             txaux.clone(),
@@ -980,7 +1022,7 @@ fn test_transfer_verify_fail() {
     {
         let mut tx = tx.clone();
         tx.outputs[0].value = Coin::zero();
-        let result = verify_transfer(&tx, &witness, &extra_info, vec![]);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![], &[]);
         expect_error(&result, Error::ZeroCoin);
         let txaux = replace_tx_payload(
             txaux.clone(),
@@ -996,7 +1038,7 @@ fn test_transfer_verify_fail() {
         let mut witness = witness.clone();
         let wp = witness[0].clone();
         witness.push(wp);
-        let result = verify_transfer(&tx, &witness, &extra_info, vec![]);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![], &[]);
         expect_error(&result, Error::UnexpectedWitnesses);
         let txaux = replace_tx_payload(
             txaux.clone(),
@@ -1017,7 +1059,7 @@ fn test_transfer_verify_fail() {
         );
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
         assert!(result.is_err());
-        let result = verify_transfer(&tx.clone(), &vec![].into(), &extra_info, vec![]);
+        let result = verify_transfer(&tx.clone(), &vec![].into(), &extra_info, vec![], &[]);
         expect_error(&result, Error::MissingWitnesses);
     }
     // InvalidSum
@@ -1028,7 +1070,7 @@ fn test_transfer_verify_fail() {
         tx.outputs.push(outp);
         let mut witness = witness.clone();
         witness[0] = get_tx_witness(secp256k1::SECP256K1, &tx.id(), &secret_key, &merkle_tree);
-        let result = verify_transfer(&tx, &witness, &extra_info, vec![]);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![], &[]);
         expect_error(
             &result,
             Error::InvalidSum, // FIXME: Error::InvalidSum(CoinError::OutOfBound(Coin::max().into())),
@@ -1053,7 +1095,7 @@ fn test_transfer_verify_fail() {
         db.write(inittx).unwrap();
 
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
-        expect_error(&result, Error::InputSpent);
+        expect_error(&result, Error::InputSpent { index: 0 });
 
         let mut reset = db.transaction();
         reset.put(
@@ -1087,11 +1129,9 @@ fn test_transfer_verify_fail() {
             &witness,
             &extra_info,
             vec![TxWithOutputs::Transfer(input_tx)],
+            &[],
         );
-        expect_error(
-            &result,
-            Error::EcdsaCrypto, // FIXME: Error::EcdsaCrypto(secp256k1::Error::InvalidPublicKey),
-        );
+        expect_error(&result, Error::EcdsaCrypto { index: Some(0) });
         let txaux = replace_tx_payload(
             txaux.clone(),
             PlainTxAux::TransferTx(tx.clone(), witness),
@@ -1104,7 +1144,7 @@ fn test_transfer_verify_fail() {
     // InvalidInput
     {
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &create_storage());
-        expect_error(&result, Error::InvalidInput);
+        expect_error(&result, Error::InvalidInput { index: 0 });
     }
     // InputOutputDoNotMatch
     {
@@ -1113,8 +1153,15 @@ fn test_transfer_verify_fail() {
 
         tx.outputs[0].value = (tx.outputs[0].value + Coin::one()).unwrap();
         witness[0] = get_tx_witness(secp256k1::SECP256K1, &tx.id(), &secret_key, &merkle_tree);
-        let result = verify_transfer(&tx, &witness, &extra_info, vec![]);
-        expect_error(&result, Error::InputOutputDoNotMatch);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![], &[]);
+        expect_error(
+            &result,
+            Error::InputOutputDoNotMatch {
+                inputs: Coin::zero(),
+                outputs: Coin::zero(),
+                required_fee: Coin::zero(),
+            },
+        );
         let txaux = replace_tx_payload(txaux, PlainTxAux::TransferTx(tx, witness), None, None);
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
         assert!(result.is_err());
@@ -1129,13 +1176,296 @@ fn test_transfer_verify_fail() {
             &witness,
             &extra_info,
             vec![TxWithOutputs::Transfer(input_tx)],
+            &[],
         );
-        expect_error(&result, Error::OutputInTimelock);
+        expect_error(&result, Error::OutputInTimelock { index: 0 });
         let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
         assert!(result.is_err());
     }
 }
 
+const HTLC_PREIMAGE: HtlcPreimage = [0x42; 32];
+const HTLC_DEADLINE: Timespec = DEFAULT_GENESIS_TIME + 20;
+
+fn get_htlc_chain_info(block_time: Timespec) -> ChainInfo {
+    ChainInfo {
+        min_fee_computed: Fee::new(Coin::zero()),
+        chain_hex_id: DEFAULT_CHAIN_ID,
+        block_time,
+        block_height: BlockHeight::genesis(),
+        max_evidence_age: 1,
+    }
+}
+
+/// spends an output locked to the hash of `HTLC_PREIMAGE` until `HTLC_DEADLINE`
+/// (claim key: 0xcd, refund key: 0xde) with given branch, signed by the claim or refund key
+fn prepare_htlc_spend_tx(
+    spend: HtlcSpend,
+    signed_by_claim_key: bool,
+) -> (TxEnclaveAux, Tx, TxWitness, TxWithOutputs, Storage) {
+    let db = create_db();
+    let secp = secp256k1::SECP256K1;
+    let claim_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
+    let refund_key = SecretKey::from_slice(&[0xde; 32]).expect("32 bytes, within curve order");
+    let (_, claim_tree) = get_address(&secp, &claim_key);
+    let (_, refund_tree) = get_address(&secp, &refund_key);
+    let condition = HtlcCondition::new(
+        &HTLC_PREIMAGE,
+        HTLC_DEADLINE,
+        claim_tree.root_hash(),
+        refund_tree.root_hash(),
+    );
+
+    let mut old_tx = Tx::new();
+    old_tx.add_output(TxOut::new(condition.address(), Coin::one()));
+    let old_tx_id = old_tx.id();
+    let old_tx = TxWithOutputs::Transfer(old_tx);
+    let mut inittx = db.transaction();
+    inittx.put(COL_ENCLAVE_TX, &old_tx_id[..], &seal(&old_tx));
+    inittx.put(
+        COL_TX_META,
+        &old_tx_id[..],
+        &BitVec::from_elem(1, false).to_bytes(),
+    );
+    db.write(inittx).unwrap();
+
+    let mut tx = Tx::new();
+    tx.add_input(TxoPointer::new(old_tx_id, 0));
+    let sk2 = SecretKey::from_slice(&[0x11; 32]).expect("32 bytes, within curve order");
+    tx.add_output(TxOut::new(get_address(&secp, &sk2).0, Coin::one()));
+
+    let (secret_key, merkle_tree) = if signed_by_claim_key {
+        (&claim_key, &claim_tree)
+    } else {
+        (&refund_key, &refund_tree)
+    };
+    let witness = match get_tx_witness(secp, &tx.id(), secret_key, merkle_tree) {
+        TxInWitness::TreeSig(signature, proof) => {
+            TxInWitness::HashTimeLock(condition, spend, signature, proof)
+        }
+        _ => unreachable!(),
+    };
+    let witness: TxWitness = vec![witness].into();
+    let plain_txaux = PlainTxAux::new(tx.clone(), witness.clone());
+    let txaux = TxEnclaveAux::TransferTx {
+        inputs: tx.inputs.clone(),
+        no_of_outputs: tx.outputs.len() as TxoSize,
+        payload: encrypt(&plain_txaux, tx.id()),
+    };
+    (txaux, tx, witness, old_tx, Storage::new_db(db))
+}
+
+#[test]
+fn htlc_claim_before_deadline_should_verify() {
+    let mut mock_bridge = get_enclave_bridge_mock();
+    let (txaux, tx, witness, old_tx, storage) =
+        prepare_htlc_spend_tx(HtlcSpend::Claim(HTLC_PREIMAGE), true);
+    let extra_info = get_htlc_chain_info(HTLC_DEADLINE - 1);
+    verify_transfer(&tx, &witness, &extra_info, vec![old_tx], &[]).unwrap();
+    verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage).unwrap();
+}
+
+#[test]
+fn htlc_refund_after_deadline_should_verify() {
+    let mut mock_bridge = get_enclave_bridge_mock();
+    let (txaux, tx, witness, old_tx, storage) = prepare_htlc_spend_tx(HtlcSpend::Refund, false);
+    let extra_info = get_htlc_chain_info(HTLC_DEADLINE);
+    verify_transfer(&tx, &witness, &extra_info, vec![old_tx], &[]).unwrap();
+    verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage).unwrap();
+}
+
+#[test]
+fn test_htlc_verify_fail() {
+    let mut mock_bridge = get_enclave_bridge_mock();
+    // HashLockExpired
+    {
+        let (txaux, tx, witness, old_tx, storage) =
+            prepare_htlc_spend_tx(HtlcSpend::Claim(HTLC_PREIMAGE), true);
+        let extra_info = get_htlc_chain_info(HTLC_DEADLINE);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![old_tx], &[]);
+        expect_error(&result, Error::HashLockExpired { index: 0 });
+        let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
+        expect_error(&result, Error::HashLockExpired { index: 0 });
+    }
+    // OutputInTimelock (refund before deadline)
+    {
+        let (txaux, tx, witness, old_tx, storage) = prepare_htlc_spend_tx(HtlcSpend::Refund, false);
+        let extra_info = get_htlc_chain_info(HTLC_DEADLINE - 1);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![old_tx], &[]);
+        expect_error(&result, Error::OutputInTimelock { index: 0 });
+        let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
+        expect_error(&result, Error::OutputInTimelock { index: 0 });
+    }
+    // EcdsaCrypto (wrong preimage)
+    {
+        let (_, tx, witness, old_tx, _) = prepare_htlc_spend_tx(HtlcSpend::Claim([0x43; 32]), true);
+        let extra_info = get_htlc_chain_info(HTLC_DEADLINE - 1);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![old_tx], &[]);
+        expect_error(&result, Error::EcdsaCrypto { index: Some(0) });
+    }
+    // EcdsaCrypto (refund key claiming)
+    {
+        let (_, tx, witness, old_tx, _) =
+            prepare_htlc_spend_tx(HtlcSpend::Claim(HTLC_PREIMAGE), false);
+        let extra_info = get_htlc_chain_info(HTLC_DEADLINE - 1);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![old_tx], &[]);
+        expect_error(&result, Error::EcdsaCrypto { index: Some(0) });
+    }
+    // EcdsaCrypto (claim key refunding)
+    {
+        let (_, tx, witness, old_tx, _) = prepare_htlc_spend_tx(HtlcSpend::Refund, true);
+        let extra_info = get_htlc_chain_info(HTLC_DEADLINE);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![old_tx], &[]);
+        expect_error(&result, Error::EcdsaCrypto { index: Some(0) });
+    }
+    // EcdsaCrypto (revealed condition with a different deadline)
+    {
+        let (_, tx, witness, old_tx, _) =
+            prepare_htlc_spend_tx(HtlcSpend::Claim(HTLC_PREIMAGE), true);
+        let witness: TxWitness = match witness[0].clone() {
+            TxInWitness::HashTimeLock(mut condition, spend, signature, proof) => {
+                condition.deadline += 100;
+                vec![TxInWitness::HashTimeLock(
+                    condition, spend, signature, proof,
+                )]
+                .into()
+            }
+            _ => unreachable!(),
+        };
+        let extra_info = get_htlc_chain_info(HTLC_DEADLINE);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![old_tx], &[]);
+        expect_error(&result, Error::EcdsaCrypto { index: Some(0) });
+    }
+}
+
+fn get_relative_lock_chain_info(block_time: Timespec, block_height: u64) -> ChainInfo {
+    ChainInfo {
+        min_fee_computed: Fee::new(Coin::zero()),
+        chain_hex_id: DEFAULT_CHAIN_ID,
+        block_time,
+        block_height: block_height.into(),
+        max_evidence_age: 1,
+    }
+}
+
+/// block the funding transaction in `prepare_relative_lock_spend_tx` was included in
+fn get_relative_lock_inclusion() -> TxInclusion {
+    TxInclusion {
+        block_height: 10.into(),
+        block_time: DEFAULT_GENESIS_TIME,
+    }
+}
+
+/// spends an output with the given relative timelock
+/// (its inclusion is only recorded in the storage if `record_inclusion`)
+fn prepare_relative_lock_spend_tx(
+    relative_lock: RelativeTimelock,
+    record_inclusion: bool,
+) -> (TxEnclaveAux, Tx, TxWitness, TxWithOutputs, Storage) {
+    let db = create_db();
+    let secp = secp256k1::SECP256K1;
+    let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
+    let (addr, merkle_tree) = get_address(&secp, &secret_key);
+
+    let mut old_tx = Tx::new();
+    old_tx.add_output(TxOut::new_with_relative_lock(
+        addr,
+        Coin::one(),
+        relative_lock,
+    ));
+    let old_tx_id = old_tx.id();
+    let old_tx = TxWithOutputs::Transfer(old_tx);
+    let mut inittx = db.transaction();
+    inittx.put(COL_ENCLAVE_TX, &old_tx_id[..], &seal(&old_tx));
+    inittx.put(
+        COL_TX_META,
+        &old_tx_id[..],
+        &BitVec::from_elem(1, false).to_bytes(),
+    );
+    db.write(inittx).unwrap();
+    let storage = Storage::new_db(db.clone());
+    if record_inclusion {
+        let mut kv_buffer = KVBuffer::new();
+        chain_storage::store_tx_inclusion(
+            &mut BufferStore::new(&storage, &mut kv_buffer),
+            &old_tx_id,
+            &get_relative_lock_inclusion(),
+        );
+        flush_kvdb(&*db, kv_buffer).unwrap();
+    }
+
+    let mut tx = Tx::new();
+    tx.add_input(TxoPointer::new(old_tx_id, 0));
+    let sk2 = SecretKey::from_slice(&[0x11; 32]).expect("32 bytes, within curve order");
+    tx.add_output(TxOut::new(get_address(&secp, &sk2).0, Coin::one()));
+    let witness: TxWitness = vec![get_tx_witness(secp, &tx.id(), &secret_key, &merkle_tree)].into();
+    let plain_txaux = PlainTxAux::new(tx.clone(), witness.clone());
+    let txaux = TxEnclaveAux::TransferTx {
+        inputs: tx.inputs.clone(),
+        no_of_outputs: tx.outputs.len() as TxoSize,
+        payload: encrypt(&plain_txaux, tx.id()),
+    };
+    (txaux, tx, witness, old_tx, storage)
+}
+
+#[test]
+fn unlocked_relative_lock_should_verify() {
+    let mut mock_bridge = get_enclave_bridge_mock();
+    let inclusion = [Some(get_relative_lock_inclusion())];
+    // seconds since the inclusion
+    {
+        let (txaux, tx, witness, old_tx, storage) =
+            prepare_relative_lock_spend_tx(RelativeTimelock::Seconds(20), true);
+        let extra_info = get_relative_lock_chain_info(DEFAULT_GENESIS_TIME + 20, 11);
+        verify_transfer(&tx, &witness, &extra_info, vec![old_tx], &inclusion).unwrap();
+        verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage).unwrap();
+    }
+    // blocks since the inclusion
+    {
+        let (txaux, tx, witness, old_tx, storage) =
+            prepare_relative_lock_spend_tx(RelativeTimelock::Blocks(5), true);
+        let extra_info = get_relative_lock_chain_info(DEFAULT_GENESIS_TIME + 1, 15);
+        verify_transfer(&tx, &witness, &extra_info, vec![old_tx], &inclusion).unwrap();
+        verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage).unwrap();
+    }
+}
+
+#[test]
+fn test_relative_lock_verify_fail() {
+    let mut mock_bridge = get_enclave_bridge_mock();
+    let inclusion = [Some(get_relative_lock_inclusion())];
+    // OutputInTimelock (not enough seconds)
+    {
+        let (txaux, tx, witness, old_tx, storage) =
+            prepare_relative_lock_spend_tx(RelativeTimelock::Seconds(20), true);
+        let extra_info = get_relative_lock_chain_info(DEFAULT_GENESIS_TIME + 19, 20);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![old_tx], &inclusion);
+        expect_error(&result, Error::OutputInTimelock { index: 0 });
+        let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
+        expect_error(&result, Error::OutputInTimelock { index: 0 });
+    }
+    // OutputInTimelock (not enough blocks)
+    {
+        let (txaux, tx, witness, old_tx, storage) =
+            prepare_relative_lock_spend_tx(RelativeTimelock::Blocks(5), true);
+        let extra_info = get_relative_lock_chain_info(DEFAULT_GENESIS_TIME + 100, 14);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![old_tx], &inclusion);
+        expect_error(&result, Error::OutputInTimelock { index: 0 });
+        let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
+        expect_error(&result, Error::OutputInTimelock { index: 0 });
+    }
+    // OutputInTimelock (inclusion not recorded)
+    {
+        let (txaux, tx, witness, old_tx, storage) =
+            prepare_relative_lock_spend_tx(RelativeTimelock::Blocks(5), false);
+        let extra_info = get_relative_lock_chain_info(DEFAULT_GENESIS_TIME + 100, 15);
+        let result = verify_transfer(&tx, &witness, &extra_info, vec![old_tx], &[None]);
+        expect_error(&result, Error::OutputInTimelock { index: 0 });
+        let result = verify_enclave_tx(&mut mock_bridge, &txaux, &extra_info, 0, &storage);
+        expect_error(&result, Error::OutputInTimelock { index: 0 });
+    }
+}
+
 fn prepare_jailed_accounts() -> (
     Storage,
     SecretKey,
@@ -1294,7 +1624,10 @@ fn check_unjail_transaction() {
 
     expect_error_public(
         &verify_public_tx(&txaux, &extra_info, NodeInfoWrap::default(), 0, &storage),
-        PublicTxError::IncorrectNonce,
+        PublicTxError::IncorrectNonce {
+            expected: 0,
+            actual: 0,
+        },
     );
 
     // Before `jailed_until`
@@ -1433,7 +1766,13 @@ fn test_nodejoin_fail() {
             0,
             &create_storage(),
         );
-        expect_error_public(&result, PublicTxError::IncorrectNonce);
+        expect_error_public(
+            &result,
+            PublicTxError::IncorrectNonce {
+                expected: 0,
+                actual: 0,
+            },
+        );
     }
     // AccountIncorrectNonce
     {
@@ -1444,7 +1783,13 @@ fn test_nodejoin_fail() {
             get_account_op_witness(secp256k1::SECP256K1, &tx.id(), &secret_key),
         );
         let result = verify_public_tx(&txaux, &extra_info, NodeInfoWrap::default(), 0, &storage);
-        expect_error_public(&result, PublicTxError::IncorrectNonce);
+        expect_error_public(
+            &result,
+            PublicTxError::IncorrectNonce {
+                expected: 0,
+                actual: 0,
+            },
+        );
     }
     // MismatchAccountAddress
     {
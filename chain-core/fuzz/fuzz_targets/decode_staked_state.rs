@@ -0,0 +1,8 @@
+#![no_main]
+use chain_core::state::account::StakedState;
+use fuzz_common::scale as roundtrip;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    roundtrip::check_roundtrip::<StakedState>(data);
+});
@@ -0,0 +1,229 @@
+#![no_main]
+use chain_core::init::address::RedeemAddress;
+use chain_core::init::coin::Coin;
+use chain_core::state::account::{
+    DepositBondTx, StakedState, StakedStateAddress, StakedStateOpAttributes, UnbondTx, UnjailTx,
+    WithdrawUnbondedTx,
+};
+use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::input::TxoPointer;
+use chain_core::tx::data::output::{RelativeTimelock, TxOut};
+use chain_core::tx::data::Tx;
+use chain_core::tx::{PlainTxAux, TransactionId};
+use fuzz_common::scale as roundtrip;
+use libfuzzer_sys::arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use parity_scale_codec::{Decode, Encode};
+use std::fmt::Debug;
+
+#[derive(Arbitrary, Debug)]
+enum Address {
+    OrTree([u8; 32]),
+    HashTimeLock([u8; 32]),
+}
+
+#[derive(Arbitrary, Debug)]
+enum Lock {
+    None,
+    ValidFrom(u64),
+    Seconds(u64),
+    Blocks(u64),
+}
+
+#[derive(Arbitrary, Debug)]
+struct Output {
+    address: Address,
+    value: u64,
+    lock: Lock,
+}
+
+#[derive(Arbitrary, Debug)]
+struct Attributes {
+    chain_hex_id: u8,
+    app_version: u64,
+}
+
+#[derive(Arbitrary, Debug)]
+struct OpAttributes {
+    chain_hex_id: u8,
+    app_version: u64,
+    priority_fee: u64,
+}
+
+/// Generates well-formed transaction data (instead of random bytes
+/// that mostly fail to decode early)
+#[derive(Arbitrary, Debug)]
+enum Input {
+    Transfer {
+        inputs: Vec<([u8; 32], u16)>,
+        outputs: Vec<Output>,
+        attributes: Attributes,
+    },
+    Deposit {
+        inputs: Vec<([u8; 32], u16)>,
+        to: [u8; 20],
+        attributes: OpAttributes,
+    },
+    Withdraw {
+        nonce: u64,
+        outputs: Vec<Output>,
+        attributes: Attributes,
+    },
+    Unbond {
+        from: [u8; 20],
+        nonce: u64,
+        value: u64,
+        attributes: OpAttributes,
+    },
+    Unjail {
+        address: [u8; 20],
+        nonce: u64,
+        attributes: OpAttributes,
+    },
+    Account {
+        address: [u8; 20],
+        nonce: u64,
+        bonded: u64,
+        unbonded: u64,
+        unbonded_from: u64,
+    },
+}
+
+fn coin(value: u64) -> Coin {
+    Coin::new(value).unwrap_or_else(|_| Coin::max())
+}
+
+fn staking_address(address: [u8; 20]) -> StakedStateAddress {
+    StakedStateAddress::BasicRedeem(RedeemAddress(address))
+}
+
+fn inputs(inputs: Vec<([u8; 32], u16)>) -> Vec<TxoPointer> {
+    inputs
+        .into_iter()
+        .map(|(id, index)| TxoPointer::new(id, index as usize))
+        .collect()
+}
+
+fn outputs(outputs: Vec<Output>) -> Vec<TxOut> {
+    outputs
+        .into_iter()
+        .map(|output| {
+            let address = match output.address {
+                Address::OrTree(root) => ExtendedAddr::OrTree(root),
+                Address::HashTimeLock(hash) => ExtendedAddr::HashTimeLock(hash),
+            };
+            let value = coin(output.value);
+            match output.lock {
+                Lock::None => TxOut::new(address, value),
+                Lock::ValidFrom(time) => TxOut::new_with_timelock(address, value, time),
+                Lock::Seconds(seconds) => TxOut::new_with_relative_lock(
+                    address,
+                    value,
+                    RelativeTimelock::Seconds(seconds),
+                ),
+                Lock::Blocks(blocks) => {
+                    TxOut::new_with_relative_lock(address, value, RelativeTimelock::Blocks(blocks))
+                }
+            }
+        })
+        .collect()
+}
+
+fn attributes(attributes: Attributes) -> TxAttributes {
+    TxAttributes {
+        chain_hex_id: attributes.chain_hex_id,
+        allowed_view: vec![],
+        app_version: attributes.app_version,
+    }
+}
+
+fn op_attributes(attributes: OpAttributes) -> StakedStateOpAttributes {
+    StakedStateOpAttributes {
+        chain_hex_id: attributes.chain_hex_id,
+        app_version: attributes.app_version,
+        priority_fee: coin(attributes.priority_fee),
+    }
+}
+
+/// The transaction id is computed from the encoding, so it must survive the round-trip
+fn check_tx<T: TransactionId + Decode + Encode + PartialEq + Debug>(tx: T) {
+    roundtrip::check_encoding(&tx);
+    let id = tx.id();
+    let decoded = T::decode(&mut tx.encode().as_slice()).expect("decode own encoding");
+    assert_eq!(id, decoded.id());
+}
+
+fuzz_target!(|input: Input| {
+    match input {
+        Input::Transfer {
+            inputs: ins,
+            outputs: outs,
+            attributes: attr,
+        } => {
+            let tx = Tx::new_with(inputs(ins), outputs(outs), attributes(attr));
+            roundtrip::check_encoding(&tx.attributes);
+            check_tx(tx);
+        }
+        Input::Deposit {
+            inputs: ins,
+            to,
+            attributes: attr,
+        } => {
+            check_tx(DepositBondTx {
+                inputs: inputs(ins),
+                to_staked_account: staking_address(to),
+                attributes: op_attributes(attr),
+            });
+        }
+        Input::Withdraw {
+            nonce,
+            outputs: outs,
+            attributes: attr,
+        } => {
+            let tx = WithdrawUnbondedTx::new(nonce, outputs(outs), attributes(attr));
+            roundtrip::check_encoding(&PlainTxAux::WithdrawUnbondedStakeTx(tx.clone()));
+            check_tx(tx);
+        }
+        Input::Unbond {
+            from,
+            nonce,
+            value,
+            attributes: attr,
+        } => {
+            check_tx(UnbondTx {
+                from_staked_account: staking_address(from),
+                nonce,
+                value: coin(value),
+                attributes: op_attributes(attr),
+            });
+        }
+        Input::Unjail {
+            address,
+            nonce,
+            attributes: attr,
+        } => {
+            check_tx(UnjailTx {
+                nonce,
+                address: staking_address(address),
+                attributes: op_attributes(attr),
+            });
+        }
+        Input::Account {
+            address,
+            nonce,
+            bonded,
+            unbonded,
+            unbonded_from,
+        } => {
+            roundtrip::check_encoding(&StakedState::new(
+                nonce,
+                coin(bonded),
+                coin(unbonded),
+                unbonded_from,
+                staking_address(address),
+                None,
+            ));
+        }
+    }
+});
@@ -1,13 +1,19 @@
-use crate::common::Timespec;
+use crate::common::{Timespec, H256};
 use crate::init::address::RedeemAddress;
 use crate::init::coin::{sum_coins, Coin, CoinError};
 pub use crate::init::params::*;
 use crate::state::account::{
-    ConfidentialInit, CouncilNodeMeta, MLSInit, NodeName, NodeSecurityContact, StakedState,
-    StakedStateAddress, StakedStateDestination,
+    ConfidentialInit, CouncilNodeMeta, MLSInit, NodeName, NodeSecurityContact, NodeState,
+    StakedState, StakedStateAddress, StakedStateDestination,
+};
+use crate::state::tendermint::{
+    BlockHeight, TendermintValidatorAddress, TendermintValidatorPubKey, TendermintVotePower,
 };
-use crate::state::tendermint::{TendermintValidatorPubKey, TendermintVotePower};
 use crate::state::RewardsPoolState;
+use crate::tx::data::address::ExtendedAddr;
+use crate::tx::data::input::TxoSize;
+use crate::tx::data::output::TxInclusion;
+use crate::tx::data::TxId;
 use mls::{error::KeyPackageError, Codec, DefaultCipherSuite, KeyPackage};
 use ra_client::ENCLAVE_CERT_VERIFIER;
 use serde::{Deserialize, Serialize};
@@ -56,6 +62,12 @@ pub enum DistributionError {
     /// keypackage verify error
     #[error("invalid key package: {0}")]
     KeyPackageVerifyError(#[from] KeyPackageError),
+    /// address in both the distribution and the exported state (or twice in the exported state)
+    #[error("Duplicate account ({0})")]
+    DuplicateAccount(RedeemAddress),
+    /// council node doesn't match the exported validator record
+    #[error("Council node ({0}) does not match the exported validator")]
+    ExportedValidatorMismatch(RedeemAddress),
 }
 
 /// Initial configuration ("app_state" in genesis.json of Tendermint config)
@@ -76,6 +88,92 @@ pub struct InitConfig {
             ConfidentialInit,
         ),
     >,
+    /// state exported from a previous chain ("zero-height" restart)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_state: Option<ExportedState>,
+}
+
+/// State exported from a running chain at some block height
+/// which a new chain can start from (`chain-abci inspect export-genesis`)
+///
+/// The supply is accounted as: distribution + staked states + UTXOs + period rewards
+/// + (monetary expansion cap - minted) = maximum supply
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct ExportedState {
+    /// the last committed block height of the exported chain
+    pub height: BlockHeight,
+    /// the app hash of the exported chain at that height
+    #[serde(with = "hex_bytes")]
+    pub app_hash: H256,
+    /// all the staked states (with their nonces, jailing and slashing records)
+    pub stakings: Vec<StakedState>,
+    /// previously used validator addresses of council nodes
+    /// (they are not included in the JSON of staked states)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub used_validator_addresses: Vec<(
+        StakedStateAddress,
+        Vec<(TendermintValidatorAddress, Timespec)>,
+    )>,
+    /// the rewards pool (undistributed rewards, minted coins and the decayed tau)
+    pub rewards_pool: RewardsPoolState,
+    /// the sum of coins in unspent transaction outputs
+    pub utxo_coins: Coin,
+    /// transactions with unspent outputs: the outputs themselves are sealed
+    /// (their values are only known to the enclaves which unseal the transactions)
+    #[serde(default)]
+    pub tx_outputs: Vec<ExportedTxOutputs>,
+}
+
+/// A transaction with unspent outputs in an exported state
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct ExportedTxOutputs {
+    /// transaction id
+    #[serde(with = "hex_bytes")]
+    pub txid: TxId,
+    /// spent flags of the outputs (bit vector padded to whole bytes)
+    #[serde(with = "hex_bytes")]
+    pub spent: Vec<u8>,
+    /// the number of outputs (if recorded by the exported chain)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_of_outputs: Option<TxoSize>,
+    /// the block of the exported chain the transaction was included in
+    pub inclusion: Option<TxInclusion>,
+    /// owners of the outputs (only known if the transaction could be unsealed outside of the enclaves)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<ExtendedAddr>,
+    /// the sealed transaction (the enclaves of the new chain need it to spend the outputs)
+    #[serde(default, with = "hex_bytes", skip_serializing_if = "Vec::is_empty")]
+    pub sealed_log: Vec<u8>,
+}
+
+impl ExportedState {
+    /// the staked states with the used validator addresses restored
+    pub fn get_stakings(&self) -> Vec<StakedState> {
+        let used_addresses = self
+            .used_validator_addresses
+            .iter()
+            .cloned()
+            .collect::<BTreeMap<_, _>>();
+        self.stakings
+            .iter()
+            .cloned()
+            .map(|mut staking| {
+                if let (Some(NodeState::CouncilNode(val)), Some(used)) = (
+                    staking.node_meta.as_mut(),
+                    used_addresses.get(&staking.address),
+                ) {
+                    val.used_validator_addresses = used.clone();
+                }
+                staking
+            })
+            .collect()
+    }
+
+    fn get_staking(&self, address: &RedeemAddress) -> Option<&StakedState> {
+        self.stakings
+            .iter()
+            .find(|staking| staking.address == StakedStateAddress::BasicRedeem(*address))
+    }
 }
 
 /// the initial state at genesis
@@ -88,6 +186,8 @@ pub struct GenesisState {
     pub validators: Vec<(StakedStateAddress, CouncilNodeMeta)>,
     /// enclave ISVSVN in genesis keypackage
     pub isv_svn: u16,
+    /// coins in unspent transaction outputs (carried over from an exported state)
+    pub utxo_coins: Coin,
 }
 
 impl InitConfig {
@@ -109,11 +209,29 @@ impl InitConfig {
             distribution: owners,
             network_params,
             council_nodes,
+            exported_state: None,
         }
     }
 
     fn check_validator_address(&self, address: &RedeemAddress) -> Result<(), DistributionError> {
         let expected = self.network_params.required_council_node_stake;
+        let exported = self
+            .exported_state
+            .as_ref()
+            .and_then(|state| state.get_staking(address));
+        if let Some(staking) = exported {
+            return match &staking.node_meta {
+                Some(NodeState::CouncilNode(val))
+                    if val.is_active() && staking.bonded >= expected =>
+                {
+                    Ok(())
+                }
+                _ => Err(DistributionError::DoesNotMatchRequiredAmount(
+                    *address,
+                    staking.bonded,
+                )),
+            };
+        }
         match self.distribution.get(address) {
             Some((d, c)) if *d == StakedStateDestination::Bonded && *c >= expected => Ok(()),
             Some((_, c)) => Err(DistributionError::DoesNotMatchRequiredAmount(*address, *c)),
@@ -124,6 +242,11 @@ impl InitConfig {
     /// returns the initial accounts
     /// assumes one called [validate_config_get_genesis], otherwise it may panic
     pub fn get_account(&self, genesis_time: Timespec) -> Vec<StakedState> {
+        let exported = self
+            .exported_state
+            .as_ref()
+            .map(ExportedState::get_stakings)
+            .unwrap_or_default();
         self.distribution
             .iter()
             .map(|(address, (destination, amount))| {
@@ -136,6 +259,7 @@ impl InitConfig {
                     self.get_council_node(address).ok(),
                 )
             })
+            .chain(exported)
             .collect()
     }
 
@@ -148,6 +272,20 @@ impl InitConfig {
             self.council_nodes
                 .get(address)
                 .ok_or(DistributionError::InvalidValidatorAccount)?;
+        let exported = self
+            .exported_state
+            .as_ref()
+            .and_then(|state| state.get_staking(address));
+        if let Some(StakedState {
+            node_meta: Some(NodeState::CouncilNode(val)),
+            ..
+        }) = exported
+        {
+            // the keypackage may be a fresh one (e.g. if the node joined with `NodeJoin` payloads)
+            if &val.council_node.consensus_pubkey != pubkey {
+                return Err(DistributionError::ExportedValidatorMismatch(*address));
+            }
+        }
         Ok(CouncilNodeMeta::new_with_details(
             name.clone(),
             security_contact.clone(),
@@ -156,15 +294,30 @@ impl InitConfig {
         ))
     }
 
-    /// checks if the config is valid:
-    /// - initial validator configuration is correct
-    /// - the total amount doesn't go over the maximum supply
-    /// - ...
-    /// if valid, it'll return the genesis "state"
-    pub fn validate_config_get_genesis(
-        &self,
-        genesis_time: Timespec,
-    ) -> Result<GenesisState, DistributionError> {
+    /// checks the exported accounts don't overlap with the distribution
+    /// and returns the exported part of the supply (including the unminted coins)
+    fn get_exported_supply(&self, exported: &ExportedState) -> Result<Coin, DistributionError> {
+        let mut addresses = HashSet::new();
+        for staking in exported.stakings.iter() {
+            let StakedStateAddress::BasicRedeem(address) = staking.address;
+            if self.distribution.contains_key(&address) || !addresses.insert(address) {
+                return Err(DistributionError::DuplicateAccount(address));
+            }
+        }
+        let stakings = sum_coins(
+            exported
+                .stakings
+                .iter()
+                .flat_map(|staking| vec![staking.bonded, staking.unbonded].into_iter()),
+        )?;
+        let unminted = (self.network_params.rewards_config.monetary_expansion_cap
+            - exported.rewards_pool.minted)?;
+        let sum = ((stakings + exported.utxo_coins)? + exported.rewards_pool.period_bonus)?;
+        Ok((sum + unminted)?)
+    }
+
+    /// checks the jailing and rewards parameters
+    fn check_network_params(&self) -> Result<(), DistributionError> {
         let jailing_config = &self.network_params.jailing_config;
         if jailing_config.missed_block_threshold > jailing_config.block_signing_window {
             return Err(DistributionError::InvalidPunishmentParamter);
@@ -172,7 +325,11 @@ impl InitConfig {
         self.network_params
             .rewards_config
             .validate()
-            .map_err(DistributionError::InvalidRewardsParamter)?;
+            .map_err(DistributionError::InvalidRewardsParamter)
+    }
+
+    /// checks there is at least one council node and their validator keys are unique
+    fn check_council_node_keys(&self) -> Result<(), DistributionError> {
         if self.council_nodes.is_empty() {
             return Err(DistributionError::NoValidators);
         }
@@ -185,54 +342,101 @@ impl InitConfig {
         if pub_keys.len() != self.council_nodes.len() {
             return Err(DistributionError::DuplicateValidatorKey);
         }
+        Ok(())
+    }
 
-        let validators = self
-            .council_nodes
-            .keys()
-            .map(|address| {
-                let council_node = self.get_council_node(address)?;
-                Ok((StakedStateAddress::BasicRedeem(*address), council_node))
-            })
-            .collect::<Result<Vec<_>, DistributionError>>()?;
-
-        let isv_svn = validators
-            .iter()
-            .map(|v| match &v.1.node_info.confidential_init.init_payload {
-                MLSInit::Genesis(ref kp) => verify_keypackage(genesis_time, &kp),
-                _ => Err(DistributionError::KeyPackageDecodeError),
-            })
-            .collect::<Result<Vec<_>, _>>()?
-            .into_iter()
-            .max()
-            .unwrap_or(0);
+    /// returns the council node metadata and the enclave ISVSVN in its keypackage
+    fn get_verified_council_node(
+        &self,
+        address: &RedeemAddress,
+        genesis_time: Timespec,
+    ) -> Result<(CouncilNodeMeta, u16), DistributionError> {
+        let council_node = self.get_council_node(address)?;
+        let isv_svn = match &council_node.node_info.confidential_init.init_payload {
+            MLSInit::Genesis(kp) => verify_keypackage(genesis_time, kp)?,
+            _ => return Err(DistributionError::KeyPackageDecodeError),
+        };
+        Ok((council_node, isv_svn))
+    }
 
+    /// checks the total amount is the maximum supply
+    fn check_total_supply(&self) -> Result<(), DistributionError> {
         Coin::new(
             u64::from(self.network_params.required_council_node_stake)
                 * self.council_nodes.len() as u64,
         )
         .map_err(DistributionError::InvalidMinimalStake)?;
 
-        // check the total amount
         let sum = sum_coins(self.distribution.iter().map(|(_, (_, amount))| *amount))?;
-        let sum = (sum + self.network_params.rewards_config.monetary_expansion_cap)?;
+        let sum = match &self.exported_state {
+            None => (sum + self.network_params.rewards_config.monetary_expansion_cap)?,
+            Some(exported) => (sum + self.get_exported_supply(exported)?)?,
+        };
         if sum != Coin::max() {
             return Err(DistributionError::DoesNotMatchMaxSupply(sum));
         }
+        Ok(())
+    }
+
+    /// returns all the problems [validate_config_get_genesis] would find
+    /// (it only returns the first one), e.g. for reporting them at once in tools
+    pub fn get_config_errors(&self, genesis_time: Timespec) -> Vec<DistributionError> {
+        let mut errors = vec![];
+        errors.extend(self.check_network_params().err());
+        errors.extend(self.check_council_node_keys().err());
+        errors.extend(
+            self.council_nodes
+                .keys()
+                .filter_map(|address| self.get_verified_council_node(address, genesis_time).err()),
+        );
+        errors.extend(self.check_total_supply().err());
+        errors
+    }
+
+    /// checks if the config is valid:
+    /// - initial validator configuration is correct
+    /// - the total amount doesn't go over the maximum supply
+    /// - ...
+    /// if valid, it'll return the genesis "state"
+    pub fn validate_config_get_genesis(
+        &self,
+        genesis_time: Timespec,
+    ) -> Result<GenesisState, DistributionError> {
+        self.check_network_params()?;
+        self.check_council_node_keys()?;
+
+        let mut validators = Vec::with_capacity(self.council_nodes.len());
+        let mut isv_svn = 0;
+        for address in self.council_nodes.keys() {
+            let (council_node, node_isv_svn) =
+                self.get_verified_council_node(address, genesis_time)?;
+            isv_svn = isv_svn.max(node_isv_svn);
+            validators.push((StakedStateAddress::BasicRedeem(*address), council_node));
+        }
+
+        self.check_total_supply()?;
 
         let accounts = self.get_account(genesis_time);
         #[cfg(debug_assertions)]
         for staking in accounts.iter() {
             staking.check_invariants(self.network_params.required_council_node_stake);
         }
-        let rewards_pool = RewardsPoolState::new(
-            genesis_time,
-            self.network_params.rewards_config.monetary_expansion_tau,
-        );
+        let (rewards_pool, utxo_coins) = match &self.exported_state {
+            None => (
+                RewardsPoolState::new(
+                    genesis_time,
+                    self.network_params.rewards_config.monetary_expansion_tau,
+                ),
+                Coin::zero(),
+            ),
+            Some(exported) => (exported.rewards_pool.clone(), exported.utxo_coins),
+        };
         Ok(GenesisState {
             accounts,
             rewards_pool,
             validators,
             isv_svn,
+            utxo_coins,
         })
     }
 }
@@ -254,6 +458,26 @@ pub struct LightGenesis {
     pub validators: BTreeMap<TendermintValidatorPubKey, TendermintVotePower>,
 }
 
+mod hex_bytes {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::convert::TryFrom;
+
+    pub fn serialize<S: Serializer, T: AsRef<[u8]>>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(value))
+    }
+
+    pub fn deserialize<'de, D, T: TryFrom<Vec<u8>>>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes = hex::decode(String::deserialize(deserializer)?).map_err(D::Error::custom)?;
+        T::try_from(bytes).map_err(|_| D::Error::custom("invalid length"))
+    }
+}
+
 mod map_as_vec {
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
     use std::collections::BTreeMap;
@@ -32,9 +32,12 @@ use tx::fee::Fee;
 /// version 1 -- 0.5.0 release (wire format didn't change, but unbond tx semantics changed: https://github.com/crypto-com/chain/pull/1516)
 #[cfg(not(feature = "new-txid"))]
 pub const APP_VERSION: u64 = 1;
-#[cfg(feature = "new-txid")]
+#[cfg(all(feature = "new-txid", not(feature = "priority-fee")))]
 /// version 2 -- 0.6.0 (not yet released --> transaction data bootstrapping, new TX types, genesis changes, TXID calculation change, app hash calculation change);
 pub const APP_VERSION: u64 = 2;
+#[cfg(feature = "priority-fee")]
+/// version 3 -- (not yet released --> staking operation attributes with a priority fee, charged in unbond transactions)
+pub const APP_VERSION: u64 = 3;
 
 /// computes the "global" application hash (used by Tendermint to check consistency + block replaying)
 /// currently: app_hash = blake3(b"app_hash" || root of valid TX merkle tree
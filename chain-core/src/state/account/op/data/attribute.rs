@@ -1,6 +1,13 @@
 use parity_scale_codec::{Decode, Encode, Error, Input, Output};
 use serde::{Deserialize, Serialize};
 
+use crate::init::coin::Coin;
+
+/// attributes without a priority fee (the original encoding)
+const BASIC_ATTRIBUTES_TAG: u8 = 0;
+/// attributes with a priority fee (only decoded since app version 3)
+const PRIORITY_FEE_ATTRIBUTES_TAG: u8 = 1;
+
 /// attributes in StakedState-related transactions
 #[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct StakedStateOpAttributes {
@@ -8,6 +15,16 @@ pub struct StakedStateOpAttributes {
     pub chain_hex_id: u8,
     /// overall app version (shown in chain-abci)
     pub app_version: u64,
+    /// fee paid on top of the minimal fee, so that the transaction can replace
+    /// a queued one with the same nonce in mempools
+    /// (only charged in unbond transactions -- the other operations don't pay fees;
+    /// a non-zero one is only valid since app version 3, i.e. with the `priority-fee` feature)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub priority_fee: Coin,
+}
+
+fn is_zero(coin: &Coin) -> bool {
+    *coin == Coin::zero()
 }
 
 impl StakedStateOpAttributes {
@@ -16,33 +33,112 @@ impl StakedStateOpAttributes {
         StakedStateOpAttributes {
             chain_hex_id,
             app_version: crate::APP_VERSION,
+            priority_fee: Coin::zero(),
+        }
+    }
+
+    /// initializes attributes with desired network id + current app version
+    /// and the fee paid on top of the minimal fee
+    pub fn new_with_priority_fee(chain_hex_id: u8, priority_fee: Coin) -> Self {
+        StakedStateOpAttributes {
+            priority_fee,
+            ..Self::new(chain_hex_id)
         }
     }
 }
 
 impl Encode for StakedStateOpAttributes {
     fn encode_to<EncOut: Output>(&self, dest: &mut EncOut) {
-        dest.push_byte(0);
+        if self.priority_fee == Coin::zero() {
+            dest.push_byte(BASIC_ATTRIBUTES_TAG);
+        } else {
+            dest.push_byte(PRIORITY_FEE_ATTRIBUTES_TAG);
+        }
         dest.push_byte(self.chain_hex_id);
         dest.push(&self.app_version);
+        if self.priority_fee != Coin::zero() {
+            dest.push(&self.priority_fee);
+        }
     }
 
     fn size_hint(&self) -> usize {
-        1 + self.chain_hex_id.size_hint() + self.app_version.size_hint()
+        let priority_fee_size = if self.priority_fee == Coin::zero() {
+            0
+        } else {
+            self.priority_fee.size_hint()
+        };
+        1 + self.chain_hex_id.size_hint() + self.app_version.size_hint() + priority_fee_size
     }
 }
 
 impl Decode for StakedStateOpAttributes {
     fn decode<DecIn: Input>(input: &mut DecIn) -> Result<Self, Error> {
         let tag = input.read_byte()?;
-        if tag != 0 {
+        let priority_fee_supported = cfg!(feature = "priority-fee");
+        if tag != BASIC_ATTRIBUTES_TAG
+            && !(priority_fee_supported && tag == PRIORITY_FEE_ATTRIBUTES_TAG)
+        {
             return Err(Error::from("Unsupported TxAttributes variant"));
         }
         let chain_hex_id = input.read_byte()?;
         let app_version = u64::decode(input)?;
+        let priority_fee = if tag == PRIORITY_FEE_ATTRIBUTES_TAG {
+            let priority_fee = Coin::decode(input)?;
+            // zero priority fees have a single encoding
+            if priority_fee == Coin::zero() {
+                return Err(Error::from("Zero priority fee in TxAttributes"));
+            }
+            priority_fee
+        } else {
+            Coin::zero()
+        };
         Ok(StakedStateOpAttributes {
             chain_hex_id,
             app_version,
+            priority_fee,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_attributes_encoding() {
+        let basic = StakedStateOpAttributes::new(0xab);
+        let encoded = basic.encode();
+        assert_eq!(encoded[0], BASIC_ATTRIBUTES_TAG);
+        assert_eq!(encoded.len(), basic.size_hint());
+        assert_eq!(
+            StakedStateOpAttributes::decode(&mut encoded.as_slice()).unwrap(),
+            basic
+        );
+
+        let mut zero_fee = basic.encode();
+        zero_fee[0] = PRIORITY_FEE_ATTRIBUTES_TAG;
+        zero_fee.extend(Coin::zero().encode());
+        assert!(StakedStateOpAttributes::decode(&mut zero_fee.as_slice()).is_err());
+    }
+
+    #[cfg(feature = "priority-fee")]
+    #[test]
+    fn check_priority_fee_attributes_encoding() {
+        let with_fee = StakedStateOpAttributes::new_with_priority_fee(0xab, Coin::unit());
+        let encoded = with_fee.encode();
+        assert_eq!(encoded[0], PRIORITY_FEE_ATTRIBUTES_TAG);
+        assert_eq!(encoded.len(), with_fee.size_hint());
+        assert_eq!(
+            StakedStateOpAttributes::decode(&mut encoded.as_slice()).unwrap(),
+            with_fee
+        );
+    }
+
+    #[cfg(not(feature = "priority-fee"))]
+    #[test]
+    fn priority_fee_attributes_should_not_decode_before_app_version_3() {
+        let encoded = StakedStateOpAttributes::new_with_priority_fee(0xab, Coin::unit()).encode();
+        assert_eq!(encoded[0], PRIORITY_FEE_ATTRIBUTES_TAG);
+        assert!(StakedStateOpAttributes::decode(&mut encoded.as_slice()).is_err());
+    }
+}
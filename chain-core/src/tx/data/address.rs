@@ -16,6 +16,10 @@ use crate::init::network::{get_bech32_human_part_from_network, get_network, Netw
 
 type TreeRoot = H256;
 
+/// Version byte prefixed to the hash in textual hash-time-locked addresses
+/// (textual OrTree addresses are the bare 32-byte root)
+const HASH_TIME_LOCK_ADDRESS_VERSION: u8 = 1;
+
 /// MAST of Or operations (records the root).
 /// Root of a Merkle tree where leafs are X-only
 /// (potentially summed up / combined) pubkeys
@@ -25,6 +29,10 @@ pub enum ExtendedAddr {
     /// but each operation is "OR"
     /// (root of such tree)
     OrTree(TreeRoot),
+    /// hash of the revealed spending condition (`HtlcCondition`):
+    /// claimable with the preimage of a hash lock before a deadline,
+    /// refundable after it
+    HashTimeLock(H256),
 }
 
 impl Encode for ExtendedAddr {
@@ -34,12 +42,17 @@ impl Encode for ExtendedAddr {
                 dest.push_byte(0);
                 dest.push(aa);
             }
+            ExtendedAddr::HashTimeLock(ref aa) => {
+                dest.push_byte(1);
+                dest.push(aa);
+            }
         }
     }
 
     fn size_hint(&self) -> usize {
         (match self {
             ExtendedAddr::OrTree(ref aa) => aa.size_hint(),
+            ExtendedAddr::HashTimeLock(ref aa) => aa.size_hint(),
         }) + 1
     }
 }
@@ -47,13 +60,15 @@ impl Encode for ExtendedAddr {
 impl Decode for ExtendedAddr {
     fn decode<DecIn: Input>(input: &mut DecIn) -> Result<Self, Error> {
         let tag = input.read_byte()?;
-        // NOTE: tag 1 may be used for other address types -- e.g. one to denote
-        // requiring a different witness type (leaf may be a combination of root + timelock)
         match tag {
             0 => Ok(ExtendedAddr::OrTree({
                 let address: TreeRoot = Decode::decode(input)?;
                 address
             })),
+            1 => Ok(ExtendedAddr::HashTimeLock({
+                let address: H256 = Decode::decode(input)?;
+                address
+            })),
             _ => Err("No such variant in enum ExtendedAddr".into()),
         }
     }
@@ -61,15 +76,18 @@ impl Decode for ExtendedAddr {
 
 impl CroAddress<ExtendedAddr> for ExtendedAddr {
     fn to_cro(&self, network: Network) -> Result<String, CroAddressError> {
-        match self {
-            ExtendedAddr::OrTree(hash) => {
-                let checked_data: Vec<u5> = hash.to_vec().to_base32();
-                let encoded =
-                    bech32::encode(get_bech32_human_part_from_network(network), checked_data)
-                        .expect("bech32 encoding error");
-                Ok(encoded)
+        let data = match self {
+            ExtendedAddr::OrTree(hash) => hash.to_vec(),
+            ExtendedAddr::HashTimeLock(hash) => {
+                let mut data = vec![HASH_TIME_LOCK_ADDRESS_VERSION];
+                data.extend_from_slice(hash);
+                data
             }
-        }
+        };
+        let checked_data: Vec<u5> = data.to_base32();
+        let encoded = bech32::encode(get_bech32_human_part_from_network(network), checked_data)
+            .expect("bech32 encoding error");
+        Ok(encoded)
     }
 
     fn from_cro(encoded_addr: &str, network: Network) -> Result<Self, CroAddressError> {
@@ -82,10 +100,18 @@ impl CroAddress<ExtendedAddr> for ExtendedAddr {
             .and_then(|decoded| {
                 Vec::from_base32(&decoded.1).map_err(|_e| CroAddressError::ConvertError)
             })
-            .map(|hash| {
-                let mut tree_root_hash: TreeRoot = [0 as u8; 32];
-                tree_root_hash.copy_from_slice(&hash.as_slice());
-                ExtendedAddr::OrTree(tree_root_hash)
+            .and_then(|data| match data.len() {
+                32 => {
+                    let mut tree_root_hash: TreeRoot = [0 as u8; 32];
+                    tree_root_hash.copy_from_slice(&data);
+                    Ok(ExtendedAddr::OrTree(tree_root_hash))
+                }
+                33 if data[0] == HASH_TIME_LOCK_ADDRESS_VERSION => {
+                    let mut hash: H256 = [0 as u8; 32];
+                    hash.copy_from_slice(&data[1..]);
+                    Ok(ExtendedAddr::HashTimeLock(hash))
+                }
+                _ => Err(CroAddressError::ConvertError),
             })
     }
 }
@@ -143,6 +169,25 @@ mod test {
         assert_eq!(extended_addr_from_hash, extended_addr_from_str);
     }
 
+    #[test]
+    fn should_be_correct_hash_time_lock_address() {
+        let network = Network::Devnet;
+
+        let extended_addr = ExtendedAddr::HashTimeLock([0xab; 32]);
+        let bech32_addr = extended_addr.to_cro(network).unwrap();
+        assert_ne!(
+            ExtendedAddr::OrTree([0xab; 32]).to_cro(network).unwrap(),
+            bech32_addr
+        );
+
+        let restored_extended_addr = ExtendedAddr::from_cro(&bech32_addr, network).unwrap();
+        assert_eq!(restored_extended_addr, extended_addr);
+        assert_eq!(
+            ExtendedAddr::decode(&mut extended_addr.encode().as_slice()).unwrap(),
+            extended_addr
+        );
+    }
+
     mod from_cro {
         use super::*;
 
@@ -166,5 +211,13 @@ mod test {
 
             assert!(result.is_ok());
         }
+
+        #[test]
+        fn should_return_convert_error_when_length_is_incorrect() {
+            let encoded = bech32::encode("dcro", [0xab; 20].to_vec().to_base32()).unwrap();
+            let result = ExtendedAddr::from_cro(&encoded, Network::Devnet);
+
+            assert_eq!(result.unwrap_err(), CroAddressError::ConvertError);
+        }
     }
 }
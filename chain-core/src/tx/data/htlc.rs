@@ -0,0 +1,111 @@
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::common::{hash256, Timespec, H256};
+use crate::tx::data::address::ExtendedAddr;
+
+/// Secret whose SHA-256 hash locks a hash-time-locked output
+/// (SHA-256 is used, so that the same secret can lock outputs on other chains in atomic swaps)
+pub type HtlcPreimage = H256;
+
+/// Spending condition of a hash-time-locked address (`ExtendedAddr::HashTimeLock`).
+/// Before the deadline, the output can be claimed by one of the claim keys that reveals
+/// the preimage of the hash lock. From the deadline on, it can only be refunded
+/// by one of the refund keys.
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode, Serialize, Deserialize)]
+pub struct HtlcCondition {
+    /// SHA-256 hash of the secret preimage
+    pub hash_lock: H256,
+    /// block time (seconds since UNIX epoch) from which the output can no longer be claimed,
+    /// but can be refunded
+    pub deadline: Timespec,
+    /// root of the Merkle tree of X-only pubkeys which can claim the output
+    pub claim_root: H256,
+    /// root of the Merkle tree of X-only pubkeys which can refund the output
+    pub refund_root: H256,
+}
+
+impl HtlcCondition {
+    /// creates a new condition locked to the hash of the given preimage
+    pub fn new(
+        preimage: &HtlcPreimage,
+        deadline: Timespec,
+        claim_root: H256,
+        refund_root: H256,
+    ) -> Self {
+        HtlcCondition {
+            hash_lock: hash256::<Sha256>(preimage),
+            deadline,
+            claim_root,
+            refund_root,
+        }
+    }
+
+    /// retrieves the root recorded in the address (currently blake3(scale_codec_bytes(condition)))
+    pub fn address_root(&self) -> H256 {
+        blake3::hash(&self.encode()).into()
+    }
+
+    /// the hash-time-locked address of this condition
+    pub fn address(&self) -> ExtendedAddr {
+        ExtendedAddr::HashTimeLock(self.address_root())
+    }
+
+    /// checks if the preimage unlocks the hash lock
+    pub fn check_preimage(&self, preimage: &HtlcPreimage) -> bool {
+        hash256::<Sha256>(preimage) == self.hash_lock
+    }
+
+    /// the root of the keys which can take the given branch
+    pub fn spending_root(&self, spend: &HtlcSpend) -> &H256 {
+        match spend {
+            HtlcSpend::Claim(_) => &self.claim_root,
+            HtlcSpend::Refund => &self.refund_root,
+        }
+    }
+
+    /// checks if the given branch can be taken at the given block time
+    pub fn is_spendable(&self, spend: &HtlcSpend, block_time: Timespec) -> bool {
+        match spend {
+            HtlcSpend::Claim(_) => block_time < self.deadline,
+            HtlcSpend::Refund => block_time >= self.deadline,
+        }
+    }
+}
+
+/// Branch of a hash-time-locked condition taken when spending the output
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode, Serialize, Deserialize)]
+pub enum HtlcSpend {
+    /// claim before the deadline (with the revealed preimage of the hash lock)
+    Claim(HtlcPreimage),
+    /// refund from the deadline on
+    Refund,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn check_preimage_and_deadline() {
+        let condition = HtlcCondition::new(&[1; 32], 100, [2; 32], [3; 32]);
+        assert!(condition.check_preimage(&[1; 32]));
+        assert!(!condition.check_preimage(&[2; 32]));
+
+        let claim = HtlcSpend::Claim([1; 32]);
+        assert!(condition.is_spendable(&claim, 99));
+        assert!(!condition.is_spendable(&claim, 100));
+        assert!(!condition.is_spendable(&HtlcSpend::Refund, 99));
+        assert!(condition.is_spendable(&HtlcSpend::Refund, 100));
+    }
+
+    #[test]
+    fn address_commits_to_condition() {
+        let condition = HtlcCondition::new(&[1; 32], 100, [2; 32], [3; 32]);
+        let mut later = condition.clone();
+        later.deadline += 1;
+        assert_eq!(condition.address(), condition.clone().address());
+        assert_ne!(condition.address(), later.address());
+    }
+}
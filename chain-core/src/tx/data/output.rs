@@ -10,6 +10,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::common::Timespec;
 use crate::init::coin::Coin;
+use crate::state::tendermint::BlockHeight;
 use crate::tx::data::address::ExtendedAddr;
 
 /// Tx Output composed of an address and a coin value
@@ -23,17 +24,74 @@ pub struct TxOut {
     pub value: Coin,
     /// the optional timelock
     pub valid_from: Option<Timespec>,
+    /// the optional timelock relative to the inclusion of the transaction creating this output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_lock: Option<RelativeTimelock>,
 }
 
+/// Timelock which is relative to the block the output was included in
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelativeTimelock {
+    /// number of seconds after the block time of the inclusion
+    Seconds(Timespec),
+    /// number of blocks after the block height of the inclusion
+    Blocks(u64),
+}
+
+impl RelativeTimelock {
+    /// checks if an output included in `inclusion` can be spent in a block with given time and height
+    pub fn is_unlocked(
+        &self,
+        inclusion: &TxInclusion,
+        block_time: Timespec,
+        block_height: BlockHeight,
+    ) -> bool {
+        match self {
+            RelativeTimelock::Seconds(seconds) => {
+                inclusion.block_time.saturating_add(*seconds) <= block_time
+            }
+            RelativeTimelock::Blocks(blocks) => {
+                inclusion.block_height.value().saturating_add(*blocks) <= block_height.value()
+            }
+        }
+    }
+}
+
+/// Block in which a transaction was included (needed for checking relative timelocks)
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Encode, Decode, Serialize, Deserialize)]
+pub struct TxInclusion {
+    /// height of the block
+    pub block_height: BlockHeight,
+    /// time of the block
+    pub block_time: Timespec,
+}
+
+/// encoding tag of `TxOut` with a relative timelock
+/// (0 and 1 are the `None` and `Some` tags of the `valid_from` option)
+const RELATIVE_LOCK_TAG: u8 = 2;
+
 impl Encode for TxOut {
     fn encode_to<EncOut: Output>(&self, dest: &mut EncOut) {
         dest.push(&self.address);
         dest.push(&self.value);
-        dest.push(&self.valid_from);
+        match &self.relative_lock {
+            None => dest.push(&self.valid_from),
+            Some(relative_lock) => {
+                // outputs without relative locks keep their original encoding
+                dest.push_byte(RELATIVE_LOCK_TAG);
+                dest.push(relative_lock);
+                dest.push(&self.valid_from);
+            }
+        }
     }
 
     fn size_hint(&self) -> usize {
-        self.address.size_hint() + self.value.size_hint() + self.valid_from.size_hint()
+        let lock_size = match &self.relative_lock {
+            None => 0,
+            Some(relative_lock) => 1 + relative_lock.size_hint(),
+        };
+        self.address.size_hint() + self.value.size_hint() + lock_size + self.valid_from.size_hint()
     }
 }
 
@@ -47,11 +105,21 @@ impl Decode for TxOut {
         // but one needs to careful that "None" isn't required to be encoded
         let address = ExtendedAddr::decode(input)?;
         let value = Coin::decode(input)?;
-        let valid_from: Option<Timespec> = Option::decode(input)?;
+        let (valid_from, relative_lock) = match input.read_byte()? {
+            0 => (None, None),
+            1 => (Some(Timespec::decode(input)?), None),
+            RELATIVE_LOCK_TAG => {
+                let relative_lock = RelativeTimelock::decode(input)?;
+                let valid_from: Option<Timespec> = Option::decode(input)?;
+                (valid_from, Some(relative_lock))
+            }
+            _ => return Err("No such variant in TxOut timelock".into()),
+        };
         Ok(TxOut {
             address,
             value,
             valid_from,
+            relative_lock,
         })
     }
 }
@@ -104,6 +172,7 @@ impl TxOut {
             address,
             value,
             valid_from: None,
+            relative_lock: None,
         }
     }
 
@@ -113,6 +182,65 @@ impl TxOut {
             address,
             value,
             valid_from: Some(valid_from),
+            relative_lock: None,
+        }
+    }
+
+    /// creates a TX output with timelock relative to the inclusion of the transaction
+    pub fn new_with_relative_lock(
+        address: ExtendedAddr,
+        value: Coin,
+        relative_lock: RelativeTimelock,
+    ) -> Self {
+        TxOut {
+            address,
+            value,
+            valid_from: None,
+            relative_lock: Some(relative_lock),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_relative_lock_encoding() {
+        let address = ExtendedAddr::OrTree([1u8; 32]);
+        let value = Coin::unit();
+        let old = TxOut::new_with_timelock(address.clone(), value, 10);
+        let mut old_encoding = address.encode();
+        old_encoding.extend(value.encode());
+        old_encoding.extend(Some(10u64).encode());
+        assert_eq!(old_encoding, old.encode());
+
+        let mut relative =
+            TxOut::new_with_relative_lock(address, value, RelativeTimelock::Blocks(5));
+        assert_eq!(
+            relative,
+            TxOut::decode(&mut relative.encode().as_slice()).unwrap()
+        );
+        relative.valid_from = Some(10);
+        assert_eq!(
+            relative,
+            TxOut::decode(&mut relative.encode().as_slice()).unwrap()
+        );
+        assert_eq!(relative.size_hint(), relative.encode().len());
+    }
+
+    #[test]
+    fn check_relative_lock_unlocking() {
+        let inclusion = TxInclusion {
+            block_height: 10.into(),
+            block_time: 100,
+        };
+        let seconds = RelativeTimelock::Seconds(50);
+        assert!(!seconds.is_unlocked(&inclusion, 149, 20.into()));
+        assert!(seconds.is_unlocked(&inclusion, 150, 11.into()));
+
+        let blocks = RelativeTimelock::Blocks(5);
+        assert!(!blocks.is_unlocked(&inclusion, 1000, 14.into()));
+        assert!(blocks.is_unlocked(&inclusion, 100, 15.into()));
+    }
+}
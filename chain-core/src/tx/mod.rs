@@ -4,6 +4,8 @@ use std::prelude::v1::Vec;
 pub mod data;
 /// Transaction fee calculation
 pub mod fee;
+/// Reasons and details of transaction rejections
+pub mod rejection;
 /// Witness structures (e.g. signatures) for transactions
 pub mod witness;
 
@@ -15,8 +17,8 @@ use self::data::Tx;
 use self::witness::TxWitness;
 use crate::mls::MLSHandshakeAux;
 use crate::state::account::{
-    DepositBondTx, StakedStateOpAttributes, StakedStateOpWitness, UnbondTx, UnjailTx,
-    WithdrawUnbondedTx,
+    DepositBondTx, Nonce, StakedStateAddress, StakedStateOpAttributes, StakedStateOpWitness,
+    UnbondTx, UnjailTx, WithdrawUnbondedTx,
 };
 use crate::state::tendermint::BlockHeight;
 use crate::state::validator::NodeJoinRequestTx;
@@ -469,6 +471,24 @@ impl TxPublicAux {
     pub fn chain_hex_id(&self) -> u8 {
         self.attributes().chain_hex_id
     }
+
+    /// returns the staked state the transaction operates on
+    pub fn staking_address(&self) -> StakedStateAddress {
+        match self {
+            TxPublicAux::UnbondStakeTx(tx, _) => tx.from_staked_account,
+            TxPublicAux::UnjailTx(tx, _) => tx.address,
+            TxPublicAux::NodeJoinTx(tx, _) => tx.address,
+        }
+    }
+
+    /// returns the expected nonce of the staked state
+    pub fn nonce(&self) -> Nonce {
+        match self {
+            TxPublicAux::UnbondStakeTx(tx, _) => tx.nonce,
+            TxPublicAux::UnjailTx(tx, _) => tx.nonce,
+            TxPublicAux::NodeJoinTx(tx, _) => tx.nonce,
+        }
+    }
 }
 
 /// Outer transaction type (broadcast in Tendermint tx payloads)
@@ -0,0 +1,229 @@
+use std::fmt;
+
+use parity_scale_codec::{Decode, Encode, Error, Input, Output};
+use serde::{Deserialize, Serialize};
+
+use crate::init::coin::Coin;
+use crate::state::account::Nonce;
+
+/// ABCI codespace of the codes of rejected transactions
+pub const REJECTION_CODESPACE: &str = "chain";
+
+macro_rules! rejection_codes {
+    ($($name:ident = $code:literal => $description:literal,)*) => {
+        /// Reason of a transaction rejection, sent as the (stable) ABCI response code
+        /// (0 is reserved for accepted transactions)
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        #[repr(u32)]
+        pub enum RejectionCode {
+            $(
+                #[doc = $description]
+                $name = $code,
+            )*
+        }
+
+        impl RejectionCode {
+            /// ABCI response code
+            pub fn code(self) -> u32 {
+                self as u32
+            }
+
+            /// the reason for the given ABCI response code (if it's a known one)
+            pub fn from_code(code: u32) -> Option<Self> {
+                match code {
+                    $($code => Some(RejectionCode::$name),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl fmt::Display for RejectionCode {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $(RejectionCode::$name => f.write_str($description),)*
+                }
+            }
+        }
+    };
+}
+
+rejection_codes! {
+    Deserialize = 1 => "transaction could not be decoded",
+    Unsupported = 2 => "transaction type not yet supported",
+    WrongChainHexId = 3 => "chain hex ID does not match",
+    UnsupportedVersion = 4 => "transaction was generated by potentially incompatible software",
+    NoInputs = 5 => "transaction has no inputs",
+    NoOutputs = 6 => "transaction has no outputs",
+    DuplicateInputs = 7 => "transaction has duplicated inputs",
+    ZeroCoin = 8 => "no credited value",
+    InvalidSum = 9 => "input or output sum error",
+    UnexpectedWitnesses = 10 => "transaction has more witnesses than inputs",
+    MissingWitnesses = 11 => "transaction has more inputs than witnesses",
+    InvalidInput = 12 => "transaction spends an invalid input",
+    InputSpent = 13 => "transaction spends an input that was already spent",
+    InputOutputDoNotMatch = 14 => "transaction input output coin (plus fee) sums don't match",
+    OutputInTimelock = 15 => "output transaction is in timelock",
+    InvalidSignature = 16 => "signature verification or public key recovery failed",
+    StorageError = 17 => "database lookup error",
+    EnclaveRejected = 18 => "enclave error or invalid transaction",
+    AccountNotFound = 19 => "account does not exist for given staking address",
+    AccountNotUnbonded = 20 => "account not unbonded for withdrawal",
+    AccountWithdrawOutputNotLocked = 21 => "account withdrawal outputs not time-locked to unbonded_from",
+    MismatchAccountAddress = 22 => "witness doesn't match the account address",
+    IncorrectNonce = 23 => "incorrect transaction count for account operation",
+    AccountJailed = 24 => "account is jailed",
+    HashLockExpired = 25 => "hash-time-locked output claimed after its deadline",
+    AccountNotJailed = 26 => "account is not jailed",
+    JailTimeNotExpired = 27 => "jail duration is not reached yet",
+    BondedNotEnough = 28 => "bonded coins not enough to become validator",
+    DuplicateValidatorAddress = 29 => "validator address already exists",
+    AlreadyJoined = 30 => "account is already an active validator",
+    UsedValidatorAddressesFull = 31 => "used validator addresses queue is full",
+    InvalidMLSInitData = 32 => "invalid MLS init data of node join",
+    ReplacementUnderpriced = 33 => "pending transaction with the same nonce pays at least the same fee",
+    StakingTxQueued = 34 => "staking transaction queued until the preceding nonces are accepted (broadcast it again then)",
+}
+
+impl Encode for RejectionCode {
+    fn encode_to<W: Output>(&self, dest: &mut W) {
+        self.code().encode_to(dest);
+    }
+
+    fn size_hint(&self) -> usize {
+        4
+    }
+}
+
+impl Decode for RejectionCode {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        RejectionCode::from_code(u32::decode(input)?).ok_or_else(|| "Unknown rejection code".into())
+    }
+}
+
+/// Structured details of a transaction rejection
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum RejectionDetail {
+    /// nothing more than the reason
+    None,
+    /// the rejection is about the input at this index of the transaction's inputs
+    Input {
+        /// index of the offending input
+        index: u32,
+    },
+    /// input and output sums don't leave the required fee
+    /// (deposits have no outputs, so their inputs need to exceed the fee)
+    Fee {
+        /// sum of inputs (or of the withdrawn amount)
+        inputs: Coin,
+        /// sum of outputs
+        outputs: Coin,
+        /// the minimal fee of the transaction
+        required_fee: Coin,
+    },
+    /// the nonce of the staking operation doesn't match the account
+    Nonce {
+        /// the account's nonce
+        expected: Nonce,
+        /// the nonce in the transaction
+        actual: Nonce,
+    },
+    /// a replacement needs to pay more than the pending transaction
+    Replacement {
+        /// fee paid by the pending transaction with the same nonce
+        pending_fee: Coin,
+        /// fee paid by the rejected transaction
+        fee: Coin,
+    },
+}
+
+/// Reason and details of a rejected transaction
+/// (SCALE-encoded in the data of ABCI CheckTx/DeliverTx responses)
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+pub struct TxRejection {
+    /// the reason (same as the ABCI response code)
+    pub code: RejectionCode,
+    /// structured details
+    pub detail: RejectionDetail,
+}
+
+impl TxRejection {
+    /// rejection without details
+    pub fn new(code: RejectionCode) -> Self {
+        TxRejection {
+            code,
+            detail: RejectionDetail::None,
+        }
+    }
+
+    /// rejection with details
+    pub fn with_detail(code: RejectionCode, detail: RejectionDetail) -> Self {
+        TxRejection { code, detail }
+    }
+}
+
+impl fmt::Display for TxRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code)?;
+        match &self.detail {
+            RejectionDetail::None => Ok(()),
+            RejectionDetail::Input { index } => write!(f, " (input {})", index),
+            RejectionDetail::Fee {
+                inputs,
+                outputs,
+                required_fee,
+            } => write!(
+                f,
+                " (inputs: {}, outputs: {}, required fee: {})",
+                inputs, outputs, required_fee
+            ),
+            RejectionDetail::Nonce { expected, actual } => {
+                write!(f, " (expected nonce: {}, actual: {})", expected, actual)
+            }
+            RejectionDetail::Replacement { pending_fee, fee } => {
+                write!(f, " (pending fee: {}, fee: {})", pending_fee, fee)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TxRejection {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejection_codes_are_stable() {
+        assert_eq!(RejectionCode::Deserialize.code(), 1);
+        assert_eq!(RejectionCode::InputSpent.code(), 13);
+        assert_eq!(RejectionCode::ReplacementUnderpriced.code(), 33);
+        assert_eq!(RejectionCode::StakingTxQueued.code(), 34);
+        for code in 1..=34 {
+            assert_eq!(RejectionCode::from_code(code).unwrap().code(), code);
+        }
+        assert_eq!(RejectionCode::from_code(0), None);
+        assert_eq!(RejectionCode::from_code(35), None);
+    }
+
+    #[test]
+    fn rejection_encoding() {
+        let rejection = TxRejection::with_detail(
+            RejectionCode::IncorrectNonce,
+            RejectionDetail::Nonce {
+                expected: 2,
+                actual: 1,
+            },
+        );
+        let encoded = rejection.encode();
+        assert_eq!(
+            TxRejection::decode(&mut encoded.as_slice()).unwrap(),
+            rejection
+        );
+        assert_eq!(
+            rejection.to_string(),
+            "incorrect transaction count for account operation (expected nonce: 2, actual: 1)"
+        );
+    }
+}
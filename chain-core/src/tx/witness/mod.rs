@@ -8,6 +8,7 @@ use std::prelude::v1::Vec;
 use secp256k1::{self, recovery::RecoverableSignature, schnorrsig::SchnorrSignature};
 
 use crate::common::Proof;
+use crate::tx::data::htlc::{HtlcCondition, HtlcSpend};
 use crate::tx::witness::tree::{RawSignature, RawXOnlyPubkey};
 
 /// ETH-style recoverable ECDSA
@@ -19,6 +20,8 @@ pub type EcdsaSignature = RecoverableSignature;
 ///
 /// If we want to support a maximum of 1024 leaf nodes in merkle tree, the maximum size of merkle proof will be around
 /// 32 + 33 + 660 = 725 bytes. So, each witness will be around 64 + 725 = 789 bytes == 800 bytes
+/// A hash-time-locked witness additionally reveals its condition (104 bytes) and branch (up to 33 bytes),
+/// its claim and refund trees are expected to be small (e.g. a single key each)
 ///
 /// Assuming maximum 64 witnesses are allowed, maximum witness size will be 800 * 64 = 51200
 const MAX_WITNESS_SIZE: usize = 51200; // 800 bytes for each of 64 witnesses = 51200 bytes
@@ -101,6 +104,15 @@ pub enum TxInWitness {
     /// BIP340-compatible Schnorr signature
     /// + Merkle proof from the pubkey leaf to the address root
     TreeSig(SchnorrSignature, Proof<RawXOnlyPubkey>),
+    /// revealed condition of a hash-time-locked address + the branch taken
+    /// + BIP340-compatible Schnorr signature
+    /// + Merkle proof from the pubkey leaf to the claim (or refund) root of the condition
+    HashTimeLock(
+        HtlcCondition,
+        HtlcSpend,
+        SchnorrSignature,
+        Proof<RawXOnlyPubkey>,
+    ),
 }
 
 impl fmt::Display for TxInWitness {
@@ -117,12 +129,22 @@ impl Encode for TxInWitness {
                 schnorrsig.serialize_default().encode_to(dest);
                 proof.encode_to(dest);
             }
+            TxInWitness::HashTimeLock(ref condition, ref spend, ref schnorrsig, ref proof) => {
+                dest.push_byte(1);
+                condition.encode_to(dest);
+                spend.encode_to(dest);
+                schnorrsig.serialize_default().encode_to(dest);
+                proof.encode_to(dest);
+            }
         }
     }
 
     fn size_hint(&self) -> usize {
         match self {
             TxInWitness::TreeSig(_, ref proof) => 65 + proof.size_hint(),
+            TxInWitness::HashTimeLock(ref condition, ref spend, _, ref proof) => {
+                65 + condition.size_hint() + spend.size_hint() + proof.size_hint()
+            }
         }
     }
 }
@@ -138,6 +160,17 @@ impl Decode for TxInWitness {
                 let proof = Proof::decode(input)?;
                 Ok(TxInWitness::TreeSig(schnorrsig, proof))
             }
+            1 => {
+                let condition = HtlcCondition::decode(input)?;
+                let spend = HtlcSpend::decode(input)?;
+                let raw_sig = RawSignature::decode(input)?;
+                let schnorrsig = SchnorrSignature::from_default(&raw_sig)
+                    .map_err(|_| Error::from("Unable to parse schnorr signature"))?;
+                let proof = Proof::decode(input)?;
+                Ok(TxInWitness::HashTimeLock(
+                    condition, spend, schnorrsig, proof,
+                ))
+            }
             _ => Err(Error::from("Invalid tag")),
         }
     }
@@ -1,8 +1,8 @@
 use chain_core::init::address::RedeemAddress;
 use chain_core::init::coin::Coin;
 use chain_core::init::config::{
-    InitConfig, InitNetworkParameters, JailingParameters, RewardsParameters, SlashRatio,
-    SlashingParameters,
+    DistributionError, InitConfig, InitNetworkParameters, JailingParameters, RewardsParameters,
+    SlashRatio, SlashingParameters,
 };
 use chain_core::state::account::StakedStateDestination;
 use chain_core::state::tendermint::TendermintValidatorPubKey;
@@ -95,3 +95,87 @@ fn test_verify_test_example_snapshot() {
         .validate_config_get_genesis(DEFAULT_GENESIS_TIME)
         .is_err());
 }
+
+#[test]
+fn test_config_errors_are_collected() {
+    let node_address = "0x2440ad2533c66d91eb97807a339be13556d04990"
+        .parse::<RedeemAddress>()
+        .unwrap();
+    let missing_address = "0x3ae55c16800dc4bd0e3397a9d7806fb1f11639de"
+        .parse::<RedeemAddress>()
+        .unwrap();
+    let mut nodes = BTreeMap::new();
+    nodes.insert(
+        node_address,
+        (
+            "node".to_owned(),
+            None,
+            TendermintValidatorPubKey::from_base64(b"EIosObgfONUsnWCBGRpFlRFq5lSxjGIChRlVrVWVkcE=")
+                .unwrap(),
+            mock_confidential_init(),
+        ),
+    );
+    // not in the distribution
+    nodes.insert(
+        missing_address,
+        (
+            "missing".to_owned(),
+            None,
+            TendermintValidatorPubKey::from_base64(b"pPxJH5wUamXcCvSxgywzmANuO0UNR5x3nCbUzsrCeX8=")
+                .unwrap(),
+            mock_confidential_init(),
+        ),
+    );
+    let mut dist = BTreeMap::new();
+    dist.insert(
+        node_address,
+        (StakedStateDestination::Bonded, Coin::new(1000).unwrap()),
+    );
+    let params = InitNetworkParameters {
+        initial_fee_policy: LinearFee::new(
+            Milli::try_new(0, 0).unwrap(),
+            Milli::try_new(0, 0).unwrap(),
+        ),
+        required_council_node_stake: Coin::new(1000).unwrap(),
+        required_community_node_stake: Coin::unit(),
+        // the threshold is larger than the window
+        jailing_config: JailingParameters {
+            block_signing_window: 10,
+            missed_block_threshold: 50,
+        },
+        slashing_config: SlashingParameters {
+            liveness_slash_percent: SlashRatio::from_str("0.1").unwrap(),
+            byzantine_slash_percent: SlashRatio::from_str("0.2").unwrap(),
+            invalid_commit_slash_percent: SlashRatio::from_str("0.3").unwrap(),
+        },
+        rewards_config: RewardsParameters {
+            monetary_expansion_cap: Coin::new(1000).unwrap(),
+            reward_period_seconds: 24 * 60 * 60,
+            monetary_expansion_r0: "0.5".parse().unwrap(),
+            monetary_expansion_tau: 166666600,
+            monetary_expansion_decay: 999860,
+        },
+        max_validators: 2,
+    };
+    let config = InitConfig::new(dist, params, nodes);
+
+    let errors = config.get_config_errors(DEFAULT_GENESIS_TIME);
+    assert_eq!(3, errors.len(), "{:?}", errors);
+    assert!(matches!(
+        errors[0],
+        DistributionError::InvalidPunishmentParamter
+    ));
+    assert!(matches!(
+        errors[1],
+        DistributionError::AddressNotInDistribution(address) if address == missing_address
+    ));
+    assert!(matches!(
+        errors[2],
+        DistributionError::DoesNotMatchMaxSupply(_)
+    ));
+    // the first one
+    assert!(matches!(
+        config.validate_config_get_genesis(DEFAULT_GENESIS_TIME),
+        Err(DistributionError::InvalidPunishmentParamter)
+    ));
+}
@@ -8,16 +8,23 @@ use chain_core::common::H256;
 use chain_core::state::tendermint::BlockHeight;
 use chain_core::tx::data::{
     input::{TxoPointer, TxoSize},
+    output::TxInclusion,
     TxId,
 };
 
 use super::buffer::{GetKV, StoreKV};
 use super::{
     LookupItem, StoredChainState, CHAIN_ID_KEY, COL_APP_HASHS, COL_APP_STATES, COL_EXTRA,
-    COL_NODE_INFO, COL_STAKING_VERSIONS, GENESIS_APP_HASH_KEY, LAST_FETCHED_BLOCK_KEY,
-    LAST_STATE_KEY,
+    COL_NODE_INFO, COL_STAKING_VERSIONS, COL_TX_META, GENESIS_APP_HASH_KEY, LAST_FETCHED_BLOCK_KEY,
+    LAST_STATE_KEY, NODE_JOIN_TX_KEY, SEALED_ENCLAVE_STATE_KEY,
 };
 
+/// suffix of the `COL_TX_META` keys which store the block a transaction was included in
+const TX_INCLUSION_KEY_SUFFIX: &[u8] = b"inclusion";
+/// suffix of the `COL_TX_META` keys which store the number of outputs of a transaction
+/// (the spent flags are padded to whole bytes)
+const TX_OUTPUTS_KEY_SUFFIX: &[u8] = b"outputs";
+
 pub fn get_last_app_state(db: &impl GetKV) -> Option<Vec<u8>> {
     db.get(&(COL_NODE_INFO, LAST_STATE_KEY.to_vec()))
 }
@@ -42,6 +49,32 @@ pub fn set_last_fetched_block(db: &mut impl StoreKV, last_fetched_block: u32) {
     )
 }
 
+pub fn get_last_fetched_block(db: &impl GetKV) -> Option<u32> {
+    db.get(&(COL_EXTRA, LAST_FETCHED_BLOCK_KEY.to_vec()))
+        .map(|value| u32::decode(&mut value.as_slice()).expect("last fetched block decode"))
+}
+
+/// trusted anchor + keypackage secrets sealed by TDBE
+pub fn set_sealed_enclave_state(db: &mut impl StoreKV, sealed_state: &[u8]) {
+    db.set(
+        (COL_EXTRA, SEALED_ENCLAVE_STATE_KEY.to_vec()),
+        sealed_state.to_vec(),
+    )
+}
+
+pub fn get_sealed_enclave_state(db: &impl GetKV) -> Option<Vec<u8>> {
+    db.get(&(COL_EXTRA, SEALED_ENCLAVE_STATE_KEY.to_vec()))
+}
+
+/// encoded `NodeJoinRequestTx` (constructed from TDBE payloads) waiting to be signed
+pub fn set_node_join_tx(db: &mut impl StoreKV, tx_payload: &[u8]) {
+    db.set((COL_EXTRA, NODE_JOIN_TX_KEY.to_vec()), tx_payload.to_vec())
+}
+
+pub fn get_node_join_tx(db: &impl GetKV) -> Option<Vec<u8>> {
+    db.get(&(COL_EXTRA, NODE_JOIN_TX_KEY.to_vec()))
+}
+
 pub fn insert_item(
     db: &mut impl StoreKV,
     item_type: LookupItem,
@@ -155,6 +188,24 @@ pub fn create_utxo(db: &mut impl StoreKV, no_of_outputs: TxoSize, txid: &TxId) {
         *txid,
         BitVec::from_elem(no_of_outputs as usize, false).to_bytes(),
     );
+    store_no_of_outputs(db, txid, no_of_outputs);
+}
+
+fn tx_outputs_key(txid: &TxId) -> Vec<u8> {
+    let mut key = txid.to_vec();
+    key.extend_from_slice(TX_OUTPUTS_KEY_SUFFIX);
+    key
+}
+
+pub fn store_no_of_outputs(db: &mut impl StoreKV, txid: &TxId, no_of_outputs: TxoSize) {
+    db.set((COL_TX_META, tx_outputs_key(txid)), no_of_outputs.encode())
+}
+
+/// number of outputs of a transaction which created UTXOs
+/// (not recorded for the ones committed by the previous versions)
+pub fn lookup_no_of_outputs(db: &impl GetKV, txid: &TxId) -> Option<TxoSize> {
+    db.get(&(COL_TX_META, tx_outputs_key(txid)))
+        .map(|bytes| TxoSize::decode(&mut bytes.as_slice()).expect("number of outputs decode"))
 }
 
 pub fn store_sealed_log(db: &mut impl StoreKV, txid: &TxId, sealed_log: &[u8]) {
@@ -173,6 +224,23 @@ pub fn store_txs_merkle_tree(db: &mut impl StoreKV, app_hash: &H256, tree_payloa
     insert_item(db, LookupItem::TxsMerkle, *app_hash, tree_payload.to_vec());
 }
 
+fn tx_inclusion_key(txid: &TxId) -> Vec<u8> {
+    let mut key = txid.to_vec();
+    key.extend_from_slice(TX_INCLUSION_KEY_SUFFIX);
+    key
+}
+
+/// records the block a transaction which created UTXOs was included in
+/// (for checking relative timelocks of its outputs)
+pub fn store_tx_inclusion(db: &mut impl StoreKV, txid: &TxId, inclusion: &TxInclusion) {
+    db.set((COL_TX_META, tx_inclusion_key(txid)), inclusion.encode())
+}
+
+pub fn lookup_tx_inclusion(db: &impl GetKV, txid: &TxId) -> Option<TxInclusion> {
+    db.get(&(COL_TX_META, tx_inclusion_key(txid)))
+        .map(|bytes| TxInclusion::decode(&mut bytes.as_slice()).expect("tx inclusion decode"))
+}
+
 pub fn lookup_input(db: &impl GetKV, txin: &TxoPointer) -> Option<bool> {
     lookup_item(db, LookupItem::TxMetaSpent, &txin.id)
         .and_then(|v| BitVec::from_bytes(&v).get(txin.index as usize))
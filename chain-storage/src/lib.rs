@@ -9,6 +9,7 @@ use chain_core::state::account::StakedState;
 use chain_core::state::tendermint::BlockHeight;
 use chain_core::tx::data::TxId;
 use kvdb::{DBTransaction, KeyValueDB};
+use std::convert::TryFrom;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -16,6 +17,8 @@ pub use api::*;
 
 // database columns
 /// Column for UTXOs: TxId => BitVec (where each bit indicates whether the output was spent or not, e.g. b[0] == true if output 0 was spent in a given TX)
+/// and TxId ++ b"inclusion" => TxInclusion (block height and time the TX was included in)
+/// and TxId ++ b"outputs" => TxoSize (number of outputs, as the bit vector is padded to whole bytes)
 pub const COL_TX_META: u32 = 0;
 /// Column for TX witnesses: TxId => TxWitness
 pub const COL_WITNESS: u32 = 1;
@@ -46,6 +49,8 @@ pub const CHAIN_ID_KEY: &[u8] = b"chain_id";
 pub const GENESIS_APP_HASH_KEY: &[u8] = b"genesis_app_hash";
 pub const LAST_STATE_KEY: &[u8] = b"last_state";
 pub const LAST_FETCHED_BLOCK_KEY: &[u8] = b"last_fetched_block";
+pub const SEALED_ENCLAVE_STATE_KEY: &[u8] = b"sealed_enclave_state";
+pub const NODE_JOIN_TX_KEY: &[u8] = b"node_join_tx";
 
 pub enum StorageType {
     Node,
@@ -103,6 +108,8 @@ impl Get for Storage {
 /// committed storage only
 pub struct ReadOnlyStorage {
     db: Arc<dyn KeyValueDB>,
+    /// info logs of a RocksDB secondary instance (removed after the database is closed)
+    _secondary_dir: Option<tempfile::TempDir>,
 }
 
 impl Get for ReadOnlyStorage {
@@ -116,7 +123,41 @@ impl Get for ReadOnlyStorage {
 
 impl ReadOnlyStorage {
     pub fn new_db(db: Arc<dyn KeyValueDB>) -> Self {
-        Self { db }
+        Self {
+            db,
+            _secondary_dir: None,
+        }
+    }
+
+    /// opens an existing database for inspection, as a RocksDB secondary instance
+    /// (it never writes to the database and the node may keep running)
+    #[cfg(feature = "kvdb-rocksdb")]
+    pub fn new(config: &StorageConfig<'_>) -> std::io::Result<Self> {
+        let db_path = config.db_path();
+        if !Path::new(&db_path).is_dir() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("no database found at {}", db_path),
+            ));
+        }
+        // the secondary instance keeps its own info logs
+        let secondary_dir = tempfile::Builder::new()
+            .prefix("chain-storage-secondary-")
+            .tempdir()?;
+        let secondary_path = secondary_dir
+            .path()
+            .to_str()
+            .expect("invalid secondary storage path")
+            .to_string();
+        let mut db_config = kvdb_rocksdb::DatabaseConfig::with_columns(NUM_COLUMNS);
+        // required by RocksDB for secondary instances
+        db_config.max_open_files = -1;
+        db_config.secondary = Some(secondary_path);
+        let db = kvdb_rocksdb::Database::open(&db_config, &db_path)?;
+        Ok(Self {
+            db: Arc::new(db),
+            _secondary_dir: Some(secondary_dir),
+        })
     }
 
     pub fn get_last_app_state(&self) -> Option<Vec<u8>> {
@@ -132,6 +173,29 @@ impl ReadOnlyStorage {
             .expect("IO fail")
             .map(|x| x.to_vec())
     }
+
+    /// all the transactions which created UTXOs with the spent flags of their outputs
+    pub fn iter_tx_meta_spent(&self) -> impl Iterator<Item = (TxId, Vec<u8>)> + '_ {
+        self.db.iter(COL_TX_META).filter_map(|(key, value)| {
+            // skip the `TxInclusion` and number of outputs entries
+            let txid = TxId::try_from(key.as_ref()).ok()?;
+            Some((txid, value.to_vec()))
+        })
+    }
+
+    /// copies the whole database into an in-memory storage,
+    /// which can then be written to (e.g. to replay blocks on top of the committed state)
+    pub fn copy_to_memory(&self) -> std::io::Result<Storage> {
+        let db = kvdb_memorydb::create(NUM_COLUMNS);
+        let mut dbtx = db.transaction();
+        for col in 0..NUM_COLUMNS {
+            for (key, value) in self.db.iter(col) {
+                dbtx.put(col, &key, &value);
+            }
+        }
+        db.write(dbtx)?;
+        Ok(Storage::new_db(Arc::new(db)))
+    }
 }
 
 pub trait StoredChainState {
@@ -156,17 +220,11 @@ pub enum LookupItem {
 }
 
 impl Storage {
-    /// current TDBE app code takes directly `Arc<dyn KeyValueDB>`
-    /// for some reason,
-    /// which defeats the purpose of encapsulation / that Storage is exposed
-    /// in a controlled way and random threads aren't writing to it.
-    /// --
-    /// As tdbe<->chain-abci persistence seems to be only required on a "one-off" basis
-    /// during the initial data boostrap,
-    /// it's unclear why it needs to spin off a background thread
-    /// (instead of handling this one-off data fetch on the main thread)
-    /// FIXME: once the TDBE persistence of initially fetched sealed tx is actually used,
-    /// remove / rethink this
+    /// TDBE runs concurrently with chain-abci, so its persistence thread
+    /// takes directly `Arc<dyn KeyValueDB>`.
+    /// It only writes to the sealed logs column and its own `COL_EXTRA` keys
+    /// (`LAST_FETCHED_BLOCK_KEY`, `SEALED_ENCLAVE_STATE_KEY`, `NODE_JOIN_TX_KEY`).
+    /// FIXME: expose a narrower handle for that instead of the whole KV DB
     pub fn temp_hack_for_tdbe(&self) -> Arc<dyn KeyValueDB> {
         self.db.clone()
     }
@@ -174,6 +232,7 @@ impl Storage {
     pub fn get_read_only(&self) -> ReadOnlyStorage {
         ReadOnlyStorage {
             db: self.db.clone(),
+            _secondary_dir: None,
         }
     }
 
@@ -285,3 +344,21 @@ impl Storage {
         root_hash
     }
 }
+
+#[cfg(all(test, feature = "kvdb-rocksdb"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_read_only_storage_removes_secondary_dir() {
+        let base = tempfile::tempdir().unwrap();
+        let config = StorageConfig::new(base.path().to_str().unwrap(), StorageType::Node);
+        let storage = Storage::new(&config);
+        let read_only = ReadOnlyStorage::new(&config).unwrap();
+        let secondary_path = read_only._secondary_dir.as_ref().unwrap().path().to_owned();
+        assert!(secondary_path.is_dir());
+        drop(read_only);
+        assert!(!secondary_path.exists());
+        drop(storage);
+    }
+}
@@ -0,0 +1,9 @@
+#![no_main]
+use fuzz_common::mls as roundtrip;
+use libfuzzer_sys::fuzz_target;
+use mls::DefaultCipherSuite;
+use mls::KeyPackage;
+
+fuzz_target!(|data: &[u8]| {
+    roundtrip::check_roundtrip::<KeyPackage<DefaultCipherSuite>>(data);
+});
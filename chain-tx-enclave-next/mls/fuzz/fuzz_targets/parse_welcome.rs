@@ -0,0 +1,9 @@
+#![no_main]
+use fuzz_common::mls as roundtrip;
+use libfuzzer_sys::fuzz_target;
+use mls::message::Welcome;
+use mls::DefaultCipherSuite;
+
+fuzz_target!(|data: &[u8]| {
+    roundtrip::check_roundtrip::<Welcome<DefaultCipherSuite>>(data);
+});
@@ -0,0 +1,155 @@
+#![no_main]
+use std::convert::TryFrom;
+
+use fuzz_common::mls as roundtrip;
+use libfuzzer_sys::arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use mls::ciphersuite::HashValue;
+use mls::message::{
+    Commit, ContentType, EncryptedGroupSecrets, HPKECiphertext, MLSPlaintext, MLSPlaintextCommon,
+    Proposal, ProposalId, Remove, Sender, SenderType, Welcome,
+};
+use mls::tree_math::LeafSize;
+use mls::DefaultCipherSuite;
+
+#[derive(Arbitrary, Debug)]
+enum Content {
+    Application {
+        application_data: Vec<u8>,
+    },
+    Remove {
+        removed: u32,
+    },
+    Commit {
+        proposals: Vec<[u8; 32]>,
+        confirmation_tag: [u8; 32],
+    },
+}
+
+#[derive(Arbitrary, Debug)]
+struct GroupSecrets {
+    kem_output: [u8; 32],
+    ciphertext: Vec<u8>,
+    key_package_hash: [u8; 32],
+}
+
+/// Generates well-formed MLS messages (instead of random bytes
+/// that mostly fail to parse early)
+#[derive(Arbitrary, Debug)]
+enum Input {
+    Plaintext {
+        group_id: Vec<u8>,
+        epoch: u64,
+        sender_type: u8,
+        sender: u32,
+        authenticated_data: Vec<u8>,
+        content: Content,
+        signature: Vec<u8>,
+    },
+    Welcome {
+        version: u8,
+        cipher_suite: u16,
+        secrets: Vec<GroupSecrets>,
+        encrypted_group_info: Vec<u8>,
+    },
+}
+
+/// the length limits of the wire encoding
+fn limit(mut bytes: Vec<u8>, max_len: usize) -> Vec<u8> {
+    bytes.truncate(max_len);
+    bytes
+}
+
+fn hash_value(bytes: [u8; 32]) -> HashValue<DefaultCipherSuite> {
+    HashValue::try_from(&bytes[..]).expect("SHA-256 hash size")
+}
+
+fn sender_type(sender_type: u8) -> SenderType {
+    match sender_type % 3 {
+        0 => SenderType::Member,
+        1 => SenderType::Preconfigured,
+        _ => SenderType::NewMember,
+    }
+}
+
+fn content(content: Content) -> ContentType<DefaultCipherSuite> {
+    match content {
+        Content::Application { application_data } => ContentType::Application { application_data },
+        Content::Remove { removed } => ContentType::Proposal(Proposal::Remove(Remove {
+            removed: LeafSize(removed),
+        })),
+        Content::Commit {
+            proposals,
+            confirmation_tag,
+        } => ContentType::Commit {
+            commit: Commit {
+                proposals: proposals
+                    .into_iter()
+                    .map(|id| ProposalId(hash_value(id)))
+                    .collect(),
+                path: None,
+            },
+            confirmation_tag: hash_value(confirmation_tag),
+        },
+    }
+}
+
+fn group_secrets(secrets: GroupSecrets) -> EncryptedGroupSecrets<DefaultCipherSuite> {
+    let mut encrypted_group_secrets = HPKECiphertext {
+        kem_output: Default::default(),
+        ciphertext: limit(secrets.ciphertext, 0xffff),
+    };
+    // the KEM output is a P-256 point (not validated when parsing)
+    for (byte, seed) in encrypted_group_secrets
+        .kem_output
+        .iter_mut()
+        .zip(secrets.kem_output.iter().cycle())
+    {
+        *byte = *seed;
+    }
+    EncryptedGroupSecrets {
+        encrypted_group_secrets,
+        key_package_hash: hash_value(secrets.key_package_hash),
+    }
+}
+
+fuzz_target!(|input: Input| {
+    match input {
+        Input::Plaintext {
+            group_id,
+            epoch,
+            sender_type: sender_t,
+            sender,
+            authenticated_data,
+            content: c,
+            signature,
+        } => {
+            roundtrip::check_encoding(&MLSPlaintext::<DefaultCipherSuite> {
+                content: MLSPlaintextCommon {
+                    group_id: limit(group_id, 0xff),
+                    epoch,
+                    sender: Sender {
+                        sender_type: sender_type(sender_t),
+                        sender: LeafSize(sender),
+                    },
+                    authenticated_data,
+                    content: content(c),
+                },
+                signature: limit(signature, 0xffff),
+            });
+        }
+        Input::Welcome {
+            version,
+            cipher_suite,
+            secrets,
+            encrypted_group_info,
+        } => {
+            roundtrip::check_encoding(&Welcome::<DefaultCipherSuite> {
+                version,
+                cipher_suite,
+                secrets: secrets.into_iter().map(group_secrets).collect(),
+                encrypted_group_info,
+            });
+        }
+    }
+});
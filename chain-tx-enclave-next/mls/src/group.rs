@@ -1060,6 +1060,8 @@ pub mod test {
             0,
         )
         .expect("group init");
+        // the welcome message survives the wire encoding
+        let welcome = Welcome::read_bytes(&welcome.get_encoding()).expect("decode welcome");
         let added_group = GroupAux::init_group_from_welcome(
             to_be_added,
             to_be_added_secret,
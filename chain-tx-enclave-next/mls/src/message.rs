@@ -419,6 +419,28 @@ pub struct Welcome<CS: CipherSuite> {
     pub encrypted_group_info: Vec<u8>,
 }
 
+impl<CS: CipherSuite> Codec for Welcome<CS> {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.version.encode(bytes);
+        self.cipher_suite.encode(bytes);
+        encode_vec_u32(bytes, &self.secrets);
+        encode_vec_u32(bytes, &self.encrypted_group_info);
+    }
+
+    fn read(r: &mut Reader) -> Option<Self> {
+        let version = ProtocolVersion::read(r)?;
+        let cipher_suite = CipherSuiteTag::read(r)?;
+        let secrets = read_vec_u32(r)?;
+        let encrypted_group_info = read_vec_u32(r)?;
+        Some(Welcome {
+            version,
+            cipher_suite,
+            secrets,
+            encrypted_group_info,
+        })
+    }
+}
+
 /// spec: draft-ietf-mls-protocol.md#Welcoming-New-Members
 pub struct PathSecret<CS: CipherSuite> {
     /// 1..255
@@ -481,6 +503,22 @@ pub struct EncryptedGroupSecrets<CS: CipherSuite> {
     pub key_package_hash: HashValue<CS>,
 }
 
+impl<CS: CipherSuite> Codec for EncryptedGroupSecrets<CS> {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.encrypted_group_secrets.encode(bytes);
+        self.key_package_hash.encode(bytes);
+    }
+
+    fn read(r: &mut Reader) -> Option<Self> {
+        let encrypted_group_secrets = HPKECiphertext::read(r)?;
+        let key_package_hash = HashValue::read(r)?;
+        Some(EncryptedGroupSecrets {
+            encrypted_group_secrets,
+            key_package_hash,
+        })
+    }
+}
+
 /// spec: draft-ietf-mls-protocol.md#Direct-Paths
 #[derive(Debug, Clone)]
 pub struct HPKECiphertext<CS: CipherSuite> {
@@ -89,12 +89,17 @@ fn handling_loop<I: Read + Write, J: Read + Write>(
                         let _ = s.send(());
                     }
                 }
-                Ok(IntraEnclaveRequest::ValidateTx { request, tx_inputs }) => {
+                Ok(IntraEnclaveRequest::ValidateTx {
+                    request,
+                    tx_inputs,
+                    tx_inputs_inclusion,
+                }) => {
                     log::debug!("validate tx request");
                     validate::handle_validate_tx(
                         &aead,
                         request,
                         tx_inputs,
+                        &tx_inputs_inclusion,
                         &mut filter,
                         &mut chain_abci,
                     );
@@ -373,6 +378,7 @@ mod tests {
                 info,
             }),
             tx_inputs: None,
+            tx_inputs_inclusion: vec![],
         };
         push_bytes(stream2.clone(), &request0.encode());
         let _ = receiver.recv().unwrap();
@@ -437,6 +443,7 @@ mod tests {
                 info,
             }),
             tx_inputs: Some(vec![sealedtx.clone()]),
+            tx_inputs_inclusion: vec![None],
         };
         push_bytes(stream2.clone(), &request1.encode());
         let _ = receiver.recv().unwrap();
@@ -482,6 +489,7 @@ mod tests {
                 info,
             }),
             tx_inputs: Some(vec![sealedtx]),
+            tx_inputs_inclusion: vec![None],
         };
         push_bytes(stream2.clone(), &request2.encode());
         let _ = receiver.recv().unwrap();
@@ -101,7 +101,13 @@ pub(crate) fn handle_encrypt_request<I: Write>(
         (Some(EncryptionRequest::TransferTx(tx, witness)), Some(sealed_inputs)) => {
             let unsealed_inputs = check_unseal(tx.inputs.iter().map(|x| x.id), sealed_inputs);
             if let Some(inputs) = unsealed_inputs {
-                let result = verify_transfer(&tx, &witness, &request.info, inputs);
+                let result = verify_transfer(
+                    &tx,
+                    &witness,
+                    &request.info,
+                    inputs,
+                    &request.tx_inputs_inclusion,
+                );
                 let txid = tx.id();
                 let response: IntraEnclaveResponse = result.map(|_| {
                     IntraEnclaveResponseOk::Encrypt(encrypt(
@@ -119,7 +125,13 @@ pub(crate) fn handle_encrypt_request<I: Write>(
         (Some(EncryptionRequest::DepositStake(tx, witness)), Some(sealed_inputs)) => {
             let unsealed_inputs = check_unseal(tx.inputs.iter().map(|x| x.id), sealed_inputs);
             if let Some(inputs) = unsealed_inputs {
-                let result = verify_bonded_deposit_core(&tx, &witness, &request.info, inputs);
+                let result = verify_bonded_deposit_core(
+                    &tx,
+                    &witness,
+                    &request.info,
+                    inputs,
+                    &request.tx_inputs_inclusion,
+                );
                 let txid = tx.id();
                 let response: IntraEnclaveResponse = result.map(|_| {
                     IntraEnclaveResponseOk::Encrypt(encrypt(
@@ -2,6 +2,7 @@ use crate::sgx_module::obfuscate::check_unseal;
 use crate::sgx_module::write_response;
 use aes_gcm_siv::Aes128GcmSiv;
 use chain_core::init::coin::Coin;
+use chain_core::tx::data::output::TxInclusion;
 use chain_core::tx::data::TxId;
 use chain_core::tx::fee::Fee;
 use chain_core::tx::TransactionId;
@@ -74,6 +75,7 @@ pub(crate) fn handle_validate_tx<I: Write>(
     alg: &Aes128GcmSiv,
     request: Box<VerifyTxRequest>,
     tx_inputs: Option<Vec<Vec<u8>>>,
+    tx_inputs_inclusion: &[Option<TxInclusion>],
     filter: &mut BlockFilter,
     output: &mut I,
 ) {
@@ -98,7 +100,13 @@ pub(crate) fn handle_validate_tx<I: Write>(
                         if tx.id() != payload.txid || tx.outputs.len() as TxoSize != no_of_outputs {
                             log::error!("input invalid txid or outputs index not match!");
                         } else {
-                            let result = verify_transfer(&tx, &witness, &request.info, inputs);
+                            let result = verify_transfer(
+                                &tx,
+                                &witness,
+                                &request.info,
+                                inputs,
+                                tx_inputs_inclusion,
+                            );
                             let response = construct_sealed_response(
                                 result,
                                 &payload.txid,
@@ -119,8 +127,13 @@ pub(crate) fn handle_validate_tx<I: Write>(
                 let inputs = check_unseal(tx.inputs.iter().map(|x| x.id), sealed_inputs);
                 match (plaintx, inputs) {
                     (Ok(PlainTxAux::DepositStakeTx(witness)), Some(inputs)) => {
-                        let result =
-                            verify_bonded_deposit_core(&tx, &witness, &request.info, inputs);
+                        let result = verify_bonded_deposit_core(
+                            &tx,
+                            &witness,
+                            &request.info,
+                            inputs,
+                            tx_inputs_inclusion,
+                        );
                         let response = construct_simple_response(result);
                         write_response(response, output);
                     }
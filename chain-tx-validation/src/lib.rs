@@ -6,13 +6,15 @@
 pub mod witness;
 
 use chain_core::init::coin::Coin;
-use chain_core::state::account::{DepositBondTx, StakedState, WithdrawUnbondedTx};
+use chain_core::state::account::{DepositBondTx, Nonce, StakedState, WithdrawUnbondedTx};
+use chain_core::tx::data::htlc::HtlcSpend;
 use chain_core::tx::data::input::TxoPointer;
-use chain_core::tx::data::output::TxOut;
+use chain_core::tx::data::output::{TxInclusion, TxOut};
 use chain_core::tx::data::Tx;
 use chain_core::tx::data::TxId;
 use chain_core::tx::fee::Fee;
-use chain_core::tx::witness::TxWitness;
+use chain_core::tx::rejection::{RejectionCode, RejectionDetail, TxRejection};
+use chain_core::tx::witness::{TxInWitness, TxWitness};
 use chain_core::tx::TransactionId;
 pub use chain_core::tx::TxWithOutputs;
 pub use chain_core::ChainInfo;
@@ -20,7 +22,7 @@ use parity_scale_codec::{Decode, Encode};
 use std::collections::BTreeSet;
 use std::fmt;
 use std::prelude::v1::Vec;
-use witness::verify_tx_address;
+use witness::{check_tx_address, verify_tx_signatures, verify_tx_timelock};
 
 /// All possible TX validation errors
 #[derive(Debug, Encode, Decode)]
@@ -46,16 +48,35 @@ pub enum Error {
     /// transaction has more inputs than witnesses
     MissingWitnesses,
     /// transaction spends an invalid input
-    InvalidInput,
+    InvalidInput {
+        /// index of the input in the transaction
+        index: u32,
+    },
     /// transaction spends an input that was already spent
-    InputSpent,
+    InputSpent {
+        /// index of the input in the transaction
+        index: u32,
+    },
     /// transaction input output coin (plus fee) sums don't match
-    InputOutputDoNotMatch,
+    InputOutputDoNotMatch {
+        /// sum of inputs (or the withdrawn amount)
+        inputs: Coin,
+        /// sum of outputs (zero for deposits)
+        outputs: Coin,
+        /// minimal fee of the transaction
+        required_fee: Coin,
+    },
     /// output transaction is in timelock that hasn't passed
-    OutputInTimelock,
+    OutputInTimelock {
+        /// index of the input in the transaction
+        index: u32,
+    },
     /// cryptographic library error
-    /// FIXME: EcdsaCrypto(secp256k1::Error),
-    EcdsaCrypto,
+    /// (the library error itself isn't kept, as this error is passed out of enclaves)
+    EcdsaCrypto {
+        /// index of the input whose witness failed (`None` for staked state witnesses)
+        index: Option<u32>,
+    },
     /// DB read error
     /// FIXME: IoError(io::Error),
     IoError,
@@ -70,9 +91,19 @@ pub enum Error {
     /// mismatch staked address from witness
     MismatchAccountAddress,
     /// incorrect nonce supplied in staked state operation
-    AccountIncorrectNonce,
+    AccountIncorrectNonce {
+        /// the staked state's nonce
+        expected: Nonce,
+        /// the nonce in the transaction
+        actual: Nonce,
+    },
     /// Account is jailed
     AccountJailed,
+    /// hash-time-locked output claimed after its deadline
+    HashLockExpired {
+        /// index of the input in the transaction
+        index: u32,
+    },
 }
 
 impl fmt::Display for Error {
@@ -95,15 +126,32 @@ impl fmt::Display for Error {
                 f,
                 "input or output sum error (summation more than the total supply)"
             ),
-            InvalidInput => write!(f, "transaction spends an invalid input"),
-            InputSpent => write!(f, "transaction spends an input that was already spent"),
-            InputOutputDoNotMatch => write!(
+            InvalidInput { index } => {
+                write!(f, "transaction spends an invalid input (input {})", index)
+            }
+            InputSpent { index } => write!(
+                f,
+                "transaction spends an input that was already spent (input {})",
+                index
+            ),
+            InputOutputDoNotMatch {
+                inputs,
+                outputs,
+                required_fee,
+            } => write!(
                 f,
-                "transaction input output coin (plus fee) sums don't match"
+                "transaction input output coin (plus fee) sums don't match (inputs: {}, outputs: {}, required fee: {})",
+                inputs, outputs, required_fee
             ),
-            OutputInTimelock => write!(f, "output transaction is in timelock"),
-            // FIXME: EcdsaCrypto(ref err) => write!(f, "ECDSA crypto error: {}", err),
-            EcdsaCrypto => write!(
+            OutputInTimelock { index } => {
+                write!(f, "output transaction is in timelock (input {})", index)
+            }
+            EcdsaCrypto { index: Some(index) } => write!(
+                f,
+                "cryptographic error (signature verification failed for input {})",
+                index
+            ),
+            EcdsaCrypto { index: None } => write!(
                 f,
                 "cryptographic error (signature verification or public key recovery failed)"
             ),
@@ -119,9 +167,81 @@ impl fmt::Display for Error {
                 f,
                 "account withdrawal outputs not time-locked to unbonded_from"
             ),
-            AccountIncorrectNonce => write!(f, "incorrect transaction count for account operation"),
+            AccountIncorrectNonce { expected, actual } => write!(
+                f,
+                "incorrect transaction count for account operation (expected: {}, actual: {})",
+                expected, actual
+            ),
             MismatchAccountAddress => write!(f, "mismatch account address"),
             AccountJailed => write!(f, "account is jailed"),
+            HashLockExpired { index } => write!(
+                f,
+                "hash-time-locked output claimed after its deadline (input {})",
+                index
+            ),
+        }
+    }
+}
+
+impl Error {
+    /// the (stable) reason and details reported to clients when a transaction is rejected
+    pub fn rejection(&self) -> TxRejection {
+        use self::Error::*;
+        let input = |index: u32| RejectionDetail::Input { index };
+        match self {
+            WrongChainHexId => TxRejection::new(RejectionCode::WrongChainHexId),
+            UnsupportedVersion => TxRejection::new(RejectionCode::UnsupportedVersion),
+            NoInputs => TxRejection::new(RejectionCode::NoInputs),
+            NoOutputs => TxRejection::new(RejectionCode::NoOutputs),
+            DuplicateInputs => TxRejection::new(RejectionCode::DuplicateInputs),
+            ZeroCoin => TxRejection::new(RejectionCode::ZeroCoin),
+            InvalidSum => TxRejection::new(RejectionCode::InvalidSum),
+            UnexpectedWitnesses => TxRejection::new(RejectionCode::UnexpectedWitnesses),
+            MissingWitnesses => TxRejection::new(RejectionCode::MissingWitnesses),
+            InvalidInput { index } => {
+                TxRejection::with_detail(RejectionCode::InvalidInput, input(*index))
+            }
+            InputSpent { index } => {
+                TxRejection::with_detail(RejectionCode::InputSpent, input(*index))
+            }
+            InputOutputDoNotMatch {
+                inputs,
+                outputs,
+                required_fee,
+            } => TxRejection::with_detail(
+                RejectionCode::InputOutputDoNotMatch,
+                RejectionDetail::Fee {
+                    inputs: *inputs,
+                    outputs: *outputs,
+                    required_fee: *required_fee,
+                },
+            ),
+            OutputInTimelock { index } => {
+                TxRejection::with_detail(RejectionCode::OutputInTimelock, input(*index))
+            }
+            EcdsaCrypto { index: Some(index) } => {
+                TxRejection::with_detail(RejectionCode::InvalidSignature, input(*index))
+            }
+            EcdsaCrypto { index: None } => TxRejection::new(RejectionCode::InvalidSignature),
+            IoError => TxRejection::new(RejectionCode::StorageError),
+            EnclaveRejected => TxRejection::new(RejectionCode::EnclaveRejected),
+            AccountNotFound => TxRejection::new(RejectionCode::AccountNotFound),
+            AccountNotUnbonded => TxRejection::new(RejectionCode::AccountNotUnbonded),
+            AccountWithdrawOutputNotLocked => {
+                TxRejection::new(RejectionCode::AccountWithdrawOutputNotLocked)
+            }
+            MismatchAccountAddress => TxRejection::new(RejectionCode::MismatchAccountAddress),
+            AccountIncorrectNonce { expected, actual } => TxRejection::with_detail(
+                RejectionCode::IncorrectNonce,
+                RejectionDetail::Nonce {
+                    expected: *expected,
+                    actual: *actual,
+                },
+            ),
+            AccountJailed => TxRejection::new(RejectionCode::AccountJailed),
+            HashLockExpired { index } => {
+                TxRejection::with_detail(RejectionCode::HashLockExpired, input(*index))
+            }
         }
     }
 }
@@ -179,32 +299,56 @@ fn check_inputs(
     witness: &TxWitness,
     extra_info: &ChainInfo,
     transaction_inputs: Vec<TxWithOutputs>,
+    inputs_inclusion: &[Option<TxInclusion>],
 ) -> Result<Coin, Error> {
     let mut incoins = Coin::zero();
+    let mut signature_checks = Vec::with_capacity(witness.len());
     // verify that txids of inputs correspond to the owner/signer
     // and it'd check they are not spent
     // TODO: zip3 / itertools?
-    for (txin, (tx, in_witness)) in inputs
+    for (i, (txin, (tx, in_witness))) in inputs
         .iter()
         .zip(transaction_inputs.iter().zip(witness.iter()))
+        .enumerate()
     {
+        let index = i as u32;
         if txin.id != tx.id() {
-            return Err(Error::InvalidInput);
+            return Err(Error::InvalidInput { index });
         }
         let input_index = txin.index as usize;
         let outputs = tx.outputs();
         if input_index >= outputs.len() {
-            return Err(Error::InvalidInput);
+            return Err(Error::InvalidInput { index });
         }
         let txout = &outputs[input_index];
         if let Some(valid_from) = &txout.valid_from {
             if *valid_from > extra_info.block_time {
-                return Err(Error::OutputInTimelock);
+                return Err(Error::OutputInTimelock { index });
             }
         }
-        let wv = verify_tx_address(&in_witness, main_txid, &txout.address);
-        if let Err(_e) = wv {
-            return Err(Error::EcdsaCrypto); // FIXME: Err(Error::EcdsaCrypto(e));
+        if let Some(relative_lock) = &txout.relative_lock {
+            // outputs of transactions without a recorded inclusion can't be unlocked
+            match inputs_inclusion.get(i).copied().flatten() {
+                Some(inclusion)
+                    if relative_lock.is_unlocked(
+                        &inclusion,
+                        extra_info.block_time,
+                        extra_info.block_height,
+                    ) => {}
+                _ => return Err(Error::OutputInTimelock { index }),
+            }
+        }
+        if !verify_tx_timelock(&in_witness, extra_info.block_time) {
+            return Err(match in_witness {
+                TxInWitness::HashTimeLock(_, HtlcSpend::Claim(_), ..) => {
+                    Error::HashLockExpired { index }
+                }
+                _ => Error::OutputInTimelock { index },
+            });
+        }
+        match check_tx_address(&in_witness, &txout.address) {
+            Ok(check) => signature_checks.push(check),
+            Err(_e) => return Err(Error::EcdsaCrypto { index: Some(index) }),
         }
         let sum = incoins + txout.value;
         if let Err(_e) = sum {
@@ -213,6 +357,13 @@ fn check_inputs(
             incoins = sum.unwrap();
         }
     }
+    // all witnesses sign the same transaction, so their signatures are verified once all
+    // addresses were checked (the first input with an invalid signature is reported)
+    if let Err((i, _e)) = verify_tx_signatures(&signature_checks, main_txid) {
+        return Err(Error::EcdsaCrypto {
+            index: Some(i as u32),
+        });
+    }
     Ok(incoins)
 }
 
@@ -247,18 +398,24 @@ fn check_input_output_sums(
         return Err(Error::InvalidSum); // FIXME: Err(Error::InvalidSum(coin_err));
     }
     if incoins != total_outsum.unwrap() {
-        return Err(Error::InputOutputDoNotMatch);
+        return Err(Error::InputOutputDoNotMatch {
+            inputs: incoins,
+            outputs: outcoins,
+            required_fee: min_fee,
+        });
     }
     Ok(Fee::new(min_fee))
 }
 
 /// checks TransferTx -- TODO: this will be moved to an enclave
 /// WARNING: it assumes double-spending BitVec of inputs is checked in chain-abci
+/// (`inputs_inclusion` are the blocks the transactions of inputs were included in, as recorded in chain-abci)
 pub fn verify_transfer(
     maintx: &Tx,
     witness: &TxWitness,
     extra_info: &ChainInfo,
     transaction_inputs: Vec<TxWithOutputs>,
+    inputs_inclusion: &[Option<TxInclusion>],
 ) -> Result<Fee, Error> {
     check_attributes(
         maintx.attributes.chain_hex_id,
@@ -273,6 +430,7 @@ pub fn verify_transfer(
         witness,
         extra_info,
         transaction_inputs,
+        inputs_inclusion,
     )?;
     let outcoins = maintx.get_output_total().map_err(|_| {
         // FIXME: Err(Error::InvalidSum(coin_err));
@@ -288,6 +446,7 @@ pub fn verify_bonded_deposit_core(
     witness: &TxWitness,
     extra_info: &ChainInfo,
     transaction_inputs: Vec<TxWithOutputs>,
+    inputs_inclusion: &[Option<TxInclusion>],
 ) -> Result<Coin, Error> {
     check_attributes(
         maintx.attributes.chain_hex_id,
@@ -301,9 +460,15 @@ pub fn verify_bonded_deposit_core(
         witness,
         extra_info,
         transaction_inputs,
+        inputs_inclusion,
     )?;
-    if incoins <= extra_info.min_fee_computed.to_coin() {
-        return Err(Error::InputOutputDoNotMatch);
+    let min_fee = extra_info.min_fee_computed.to_coin();
+    if incoins <= min_fee {
+        return Err(Error::InputOutputDoNotMatch {
+            inputs: incoins,
+            outputs: Coin::zero(),
+            required_fee: min_fee,
+        });
     }
     Ok(incoins)
 }
@@ -325,7 +490,10 @@ pub fn verify_unbonded_withdraw_core(
     check_outputs_basic(&maintx.outputs)?;
     // checks that account transaction count matches to the one in transaction
     if maintx.nonce != account.nonce {
-        return Err(Error::AccountIncorrectNonce);
+        return Err(Error::AccountIncorrectNonce {
+            expected: account.nonce,
+            actual: maintx.nonce,
+        });
     }
     // checks that account can withdraw to outputs
     if account.unbonded_from > extra_info.block_time {
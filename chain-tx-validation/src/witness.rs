@@ -1,12 +1,85 @@
+use chain_core::common::Timespec;
 use chain_core::init::address::RedeemAddress;
 use chain_core::state::account::{StakedStateAddress, StakedStateOpWitness};
 use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::htlc::HtlcSpend;
 use chain_core::tx::data::TxId;
 use chain_core::tx::witness::TxInWitness;
-use secp256k1::{key::XOnlyPublicKey, schnorrsig::schnorr_verify, Message};
+use secp256k1::{
+    key::{PublicKey, SecretKey, XOnlyPublicKey},
+    schnorrsig::{schnorr_verify, SchnorrSignature},
+    Message,
+};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// signature of a witness that remains to be checked against the transaction,
+/// once the witness was matched to its address (see `check_tx_address`)
+#[derive(Debug, Clone)]
+pub struct SignatureCheck {
+    signature: SchnorrSignature,
+    public_key: XOnlyPublicKey,
+}
+
+impl SignatureCheck {
+    /// verify the signature against the given transaction message
+    pub fn verify(&self, message: &Message) -> Result<(), secp256k1::Error> {
+        schnorr_verify(
+            secp256k1::SECP256K1,
+            message,
+            &self.signature,
+            &self.public_key,
+        )
+    }
+}
+
+/// verify a given extended address is associated to the witness
+/// (merkle proof and, for hash-time-locked addresses, the revealed condition and preimage)
+/// and return the signature left to verify against the transaction
+pub fn check_tx_address(
+    witness: &TxInWitness,
+    address: &ExtendedAddr,
+) -> Result<SignatureCheck, secp256k1::Error> {
+    match (witness, address) {
+        (TxInWitness::TreeSig(sig, proof), ExtendedAddr::OrTree(root_hash)) => {
+            if !proof.verify(root_hash) {
+                Err(secp256k1::Error::InvalidPublicKey)
+            } else {
+                Ok(SignatureCheck {
+                    signature: *sig,
+                    public_key: XOnlyPublicKey::from_slice(proof.value().as_bytes())?,
+                })
+            }
+        }
+        (
+            TxInWitness::HashTimeLock(condition, spend, sig, proof),
+            ExtendedAddr::HashTimeLock(address_root),
+        ) => {
+            if condition.address_root() != *address_root {
+                return Err(secp256k1::Error::InvalidPublicKey);
+            }
+            if let HtlcSpend::Claim(preimage) = spend {
+                if !condition.check_preimage(preimage) {
+                    return Err(secp256k1::Error::InvalidMessage);
+                }
+            }
+            if !proof.verify(condition.spending_root(spend)) {
+                Err(secp256k1::Error::InvalidPublicKey)
+            } else {
+                Ok(SignatureCheck {
+                    signature: *sig,
+                    public_key: XOnlyPublicKey::from_slice(proof.value().as_bytes())?,
+                })
+            }
+        }
+        _ => Err(secp256k1::Error::InvalidPublicKey),
+    }
+}
 
 /// verify a given extended address is associated to the witness
 /// and the signature against the given transaction `Tx`
+/// (for hash-time-locked addresses, the revealed preimage is checked as well,
+/// but not the deadline -- see `verify_tx_timelock`)
 /// TODO: capture possible errors in enum?
 ///
 pub fn verify_tx_address(
@@ -14,23 +87,165 @@ pub fn verify_tx_address(
     txid: &TxId,
     address: &ExtendedAddr,
 ) -> Result<(), secp256k1::Error> {
-    let secp = secp256k1::SECP256K1;
     let message = Message::from_slice(&txid[..])?;
+    check_tx_address(witness, address)?.verify(&message)
+}
 
-    match (witness, address) {
-        (TxInWitness::TreeSig(sig, proof), ExtendedAddr::OrTree(root_hash)) => {
-            if !proof.verify(root_hash) {
-                Err(secp256k1::Error::InvalidPublicKey)
-            } else {
-                schnorr_verify(
-                    &secp,
-                    &message,
-                    &sig,
-                    &XOnlyPublicKey::from_slice(proof.value().as_bytes())?,
-                )
+/// tag of the hash deriving the batch verification randomizers
+const BATCH_RANDOMIZER_TAG: &[u8] = b"chain-tx-validation/batch";
+
+/// BIP340 tagged hash: SHA256(SHA256(tag) || SHA256(tag) || data...)
+fn tagged_hash(tag: &[u8], data: &[&[u8]]) -> [u8; 32] {
+    let tag_hash = Sha256::digest(tag);
+    let mut hasher = Sha256::new();
+    hasher.update(&tag_hash);
+    hasher.update(&tag_hash);
+    for chunk in data {
+        hasher.update(chunk);
+    }
+    hasher.finalize().into()
+}
+
+/// the point with the given X coordinate and an even Y coordinate
+/// (how BIP340 lifts X-only public keys and signature nonces)
+fn lift_x(x: &[u8]) -> Result<PublicKey, secp256k1::Error> {
+    let mut compressed = [0x02u8; 33];
+    compressed[1..].copy_from_slice(x);
+    PublicKey::from_slice(&compressed)
+}
+
+/// checks all signatures at once with the BIP340 batch verification equation:
+/// (a_0*s_0 + ... + a_n*s_n)*G = a_0*R_0 + ... + a_n*R_n + (a_0*e_0)*P_0 + ... + (a_n*e_n)*P_n
+/// where the randomizers a_i (a_0 = 1) are derived from all signatures, public keys and the message
+/// (and the terms of inputs signed by the same key are summed up before multiplying the key)
+/// an error doesn't say which signature is invalid -- nor that one is, as the equation
+/// is also rejected in the cases with a negligible probability
+/// (e.g. a challenge that isn't a valid scalar), so it needs the per-input fallback
+fn verify_batch(checks: &[SignatureCheck], txid: &TxId) -> Result<(), secp256k1::Error> {
+    let secp = secp256k1::SECP256K1;
+    let mut seed = Sha256::new();
+    seed.update(txid);
+    for check in checks {
+        seed.update(&check.signature.serialize_default()[..]);
+        seed.update(&check.public_key.serialize()[..]);
+    }
+    // the randomizers only need to be unpredictable before the signatures are fixed
+    let seed: [u8; 32] = seed.finalize().into();
+
+    let mut s_sum: Option<SecretKey> = None;
+    let mut r_sum: Option<PublicKey> = None;
+    // a_i*e_i summed up per public key
+    let mut key_factors: BTreeMap<[u8; 32], SecretKey> = BTreeMap::new();
+    for (i, check) in checks.iter().enumerate() {
+        let signature = check.signature.serialize_default();
+        let public_key = check.public_key.serialize();
+        let (r_x, s) = signature.split_at(32);
+        let mut s = SecretKey::from_slice(s)?;
+        let mut r = lift_x(r_x)?;
+        let mut factor = SecretKey::from_slice(&tagged_hash(
+            b"BIP0340/challenge",
+            &[r_x, &public_key[..], &txid[..]],
+        ))?;
+        if i > 0 {
+            let randomizer = SecretKey::from_slice(&tagged_hash(
+                BATCH_RANDOMIZER_TAG,
+                &[&seed[..], &(i as u64).to_le_bytes()[..]],
+            ))?;
+            s.mul_assign(&randomizer[..])?;
+            r.mul_assign(secp, &randomizer[..])?;
+            factor.mul_assign(&randomizer[..])?;
+        }
+        s_sum = Some(match s_sum {
+            None => s,
+            Some(mut sum) => {
+                sum.add_assign(&s[..])?;
+                sum
+            }
+        });
+        r_sum = Some(match r_sum {
+            None => r,
+            Some(sum) => sum.combine(&r)?,
+        });
+        match key_factors.get_mut(&public_key) {
+            Some(sum) => sum.add_assign(&factor[..])?,
+            None => {
+                key_factors.insert(public_key, factor);
             }
         }
     }
+    let (s_sum, mut rhs) = match (s_sum, r_sum) {
+        (Some(s_sum), Some(r_sum)) => (s_sum, r_sum),
+        // nothing to verify
+        _ => return Ok(()),
+    };
+    for (public_key, factor) in key_factors.iter() {
+        let mut term = lift_x(&public_key[..])?;
+        term.mul_assign(secp, &factor[..])?;
+        rhs = rhs.combine(&term)?;
+    }
+    if PublicKey::from_secret_key(secp, &s_sum).serialize()[..] == rhs.serialize()[..] {
+        Ok(())
+    } else {
+        Err(secp256k1::Error::IncorrectSignature)
+    }
+}
+
+/// verify the signatures of all inputs' witnesses against the given transaction `Tx`,
+/// after the addresses of all witnesses were checked by `check_tx_address`:
+/// they are verified together in one batch, and only if the batch is rejected,
+/// one by one to find the input with an invalid signature
+/// on failure, the index of the first input with an invalid signature is returned,
+/// i.e. the same input `verify_tx_address` would have rejected first
+pub fn verify_tx_signatures(
+    checks: &[SignatureCheck],
+    txid: &TxId,
+) -> Result<(), (usize, secp256k1::Error)> {
+    let message = Message::from_slice(&txid[..]).map_err(|e| (0, e))?;
+    if checks.len() > 1 && verify_batch(checks, txid).is_ok() {
+        return Ok(());
+    }
+    checks
+        .iter()
+        .enumerate()
+        .try_for_each(|(i, check)| check.verify(&message).map_err(|e| (i, e)))
+}
+
+/// verify all witnesses against the addresses of the outputs they spend
+/// and the given transaction `Tx` -- i.e. `verify_tx_address` for every input,
+/// with signatures checked after all addresses (see `verify_tx_signatures`)
+/// on failure, the index of the first failing input is returned
+pub fn verify_tx_addresses<'a>(
+    witnesses: impl IntoIterator<Item = (&'a TxInWitness, &'a ExtendedAddr)>,
+    txid: &TxId,
+) -> Result<(), (usize, secp256k1::Error)> {
+    let mut checks = Vec::new();
+    let mut address_failure = None;
+    for (i, (witness, address)) in witnesses.into_iter().enumerate() {
+        match check_tx_address(witness, address) {
+            Ok(check) => checks.push(check),
+            Err(e) => {
+                address_failure = Some((i, e));
+                break;
+            }
+        }
+    }
+    // an invalid signature on an earlier input takes precedence over a failed address check
+    verify_tx_signatures(&checks, txid)?;
+    match address_failure {
+        Some(failure) => Err(failure),
+        None => Ok(()),
+    }
+}
+
+/// verify the branch taken by a hash-time-locked witness is allowed at the given block time
+/// (claims before the deadline, refunds from it on); other witnesses aren't time-locked
+pub fn verify_tx_timelock(witness: &TxInWitness, block_time: Timespec) -> bool {
+    match witness {
+        TxInWitness::TreeSig(..) => true,
+        TxInWitness::HashTimeLock(condition, spend, ..) => {
+            condition.is_spendable(spend, block_time)
+        }
+    }
 }
 
 /// verify the signature against the given transation `Tx`
@@ -59,9 +274,11 @@ pub mod tests {
     use secp256k1::{PublicKey, SecretKey};
 
     use chain_core::common::MerkleTree;
+    use chain_core::tx::data::htlc::HtlcCondition;
     use chain_core::tx::data::Tx;
     use chain_core::tx::witness::tree::RawXOnlyPubkey;
     use chain_core::tx::TransactionId;
+    use quickcheck::quickcheck;
 
     #[test]
     fn check_1_of_1_verify() {
@@ -162,6 +379,227 @@ pub mod tests {
         assert!(verify_tx_address(&witness, &transation.id(), &address).is_err())
     }
 
+    fn htlc_trees() -> (
+        HtlcCondition,
+        MerkleTree<RawXOnlyPubkey>,
+        MerkleTree<RawXOnlyPubkey>,
+    ) {
+        let secp = secp256k1::SECP256K1;
+        let claim_key = SecretKey::from_slice(&[0xcd; 32]).unwrap();
+        let refund_key = SecretKey::from_slice(&[0xde; 32]).unwrap();
+        let claim_tree = MerkleTree::new(vec![RawXOnlyPubkey::from(
+            XOnlyPublicKey::from_secret_key(&secp, &claim_key).serialize(),
+        )]);
+        let refund_tree = MerkleTree::new(vec![RawXOnlyPubkey::from(
+            XOnlyPublicKey::from_secret_key(&secp, &refund_key).serialize(),
+        )]);
+        let condition = HtlcCondition::new(
+            &[0x42; 32],
+            100,
+            claim_tree.root_hash(),
+            refund_tree.root_hash(),
+        );
+        (condition, claim_tree, refund_tree)
+    }
+
+    fn htlc_witness(
+        txid: &TxId,
+        condition: &HtlcCondition,
+        spend: HtlcSpend,
+        secret_key: &SecretKey,
+        tree: &MerkleTree<RawXOnlyPubkey>,
+    ) -> TxInWitness {
+        let secp = secp256k1::SECP256K1;
+        let public_key = XOnlyPublicKey::from_secret_key(&secp, secret_key);
+        TxInWitness::HashTimeLock(
+            condition.clone(),
+            spend,
+            schnorr_sign(
+                &secp,
+                &Message::from_slice(txid).unwrap(),
+                secret_key,
+                &mut rand::thread_rng(),
+            ),
+            tree.generate_proof(RawXOnlyPubkey::from(public_key.serialize()))
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn check_htlc_claim_and_refund_verify() {
+        let txid = Tx::new().id();
+        let claim_key = SecretKey::from_slice(&[0xcd; 32]).unwrap();
+        let refund_key = SecretKey::from_slice(&[0xde; 32]).unwrap();
+        let (condition, claim_tree, refund_tree) = htlc_trees();
+        let address = condition.address();
+
+        let witness = htlc_witness(
+            &txid,
+            &condition,
+            HtlcSpend::Claim([0x42; 32]),
+            &claim_key,
+            &claim_tree,
+        );
+        assert!(verify_tx_address(&witness, &txid, &address).is_ok());
+        assert!(verify_tx_timelock(&witness, 99));
+        assert!(!verify_tx_timelock(&witness, 100));
+
+        let witness = htlc_witness(
+            &txid,
+            &condition,
+            HtlcSpend::Refund,
+            &refund_key,
+            &refund_tree,
+        );
+        assert!(verify_tx_address(&witness, &txid, &address).is_ok());
+        assert!(!verify_tx_timelock(&witness, 99));
+        assert!(verify_tx_timelock(&witness, 100));
+    }
+
+    #[test]
+    fn check_htlc_incorrect_spend() {
+        let txid = Tx::new().id();
+        let claim_key = SecretKey::from_slice(&[0xcd; 32]).unwrap();
+        let refund_key = SecretKey::from_slice(&[0xde; 32]).unwrap();
+        let (condition, claim_tree, refund_tree) = htlc_trees();
+        let address = condition.address();
+
+        // wrong preimage
+        let witness = htlc_witness(
+            &txid,
+            &condition,
+            HtlcSpend::Claim([0x43; 32]),
+            &claim_key,
+            &claim_tree,
+        );
+        assert!(verify_tx_address(&witness, &txid, &address).is_err());
+
+        // refund key can't claim
+        let witness = htlc_witness(
+            &txid,
+            &condition,
+            HtlcSpend::Claim([0x42; 32]),
+            &refund_key,
+            &refund_tree,
+        );
+        assert!(verify_tx_address(&witness, &txid, &address).is_err());
+
+        // claim key can't refund
+        let witness = htlc_witness(
+            &txid,
+            &condition,
+            HtlcSpend::Refund,
+            &claim_key,
+            &claim_tree,
+        );
+        assert!(verify_tx_address(&witness, &txid, &address).is_err());
+
+        // revealed condition must match the address
+        let witness = htlc_witness(
+            &txid,
+            &condition,
+            HtlcSpend::Claim([0x42; 32]),
+            &claim_key,
+            &claim_tree,
+        );
+        assert!(verify_tx_address(&witness, &txid, &ExtendedAddr::HashTimeLock([0; 32])).is_err());
+        assert!(verify_tx_address(
+            &witness,
+            &txid,
+            &ExtendedAddr::OrTree(claim_tree.root_hash())
+        )
+        .is_err());
+    }
+
+    /// witness of a 1-of-1 address, corrupted according to `corruption`
+    /// (0: valid, 1: signed by another key, 2: signed another tx, 3: spends another address, 4: htlc witness)
+    fn corrupted_input(txid: &TxId, seed: u8, corruption: u8) -> (TxInWitness, ExtendedAddr) {
+        let secp = secp256k1::SECP256K1;
+        let secret_key = SecretKey::from_slice(&[seed | 1; 32]).unwrap();
+        let public_key =
+            RawXOnlyPubkey::from(XOnlyPublicKey::from_secret_key(&secp, &secret_key).serialize());
+        let merkle_tree = MerkleTree::new(vec![public_key.clone()]);
+        let signing_key = if corruption == 1 {
+            SecretKey::from_slice(&[(seed | 1) ^ 0x02; 32]).unwrap()
+        } else {
+            secret_key
+        };
+        let message = if corruption == 2 { [0xff; 32] } else { *txid };
+        let sig = schnorr_sign(
+            &secp,
+            &Message::from_slice(&message).unwrap(),
+            &signing_key,
+            &mut rand::thread_rng(),
+        );
+        let witness = match corruption {
+            4 => {
+                let (condition, claim_tree, _) = htlc_trees();
+                htlc_witness(
+                    txid,
+                    &condition,
+                    HtlcSpend::Claim([0x42; 32]),
+                    &SecretKey::from_slice(&[0xcd; 32]).unwrap(),
+                    &claim_tree,
+                )
+            }
+            _ => TxInWitness::TreeSig(sig, merkle_tree.generate_proof(public_key).unwrap()),
+        };
+        let address = if corruption == 3 {
+            ExtendedAddr::OrTree([seed; 32])
+        } else {
+            ExtendedAddr::OrTree(merkle_tree.root_hash())
+        };
+        (witness, address)
+    }
+
+    #[test]
+    fn check_batch_verify() {
+        let txid = Tx::new().id();
+        // inputs 0 and 1 are signed by one key, inputs 2 and 3 by another one
+        let mut inputs = (0..4u8)
+            .map(|i| corrupted_input(&txid, i.min(2), 0))
+            .collect::<Vec<_>>();
+        let checks = |inputs: &[(TxInWitness, ExtendedAddr)]| {
+            inputs
+                .iter()
+                .map(|(witness, address)| check_tx_address(witness, address).unwrap())
+                .collect::<Vec<_>>()
+        };
+        // the batch equation holds on its own, without the per-input fallback
+        assert!(verify_batch(&checks(&inputs), &txid).is_ok());
+        assert!(verify_tx_signatures(&checks(&inputs), &txid).is_ok());
+
+        // signed another transaction
+        inputs[2] = corrupted_input(&txid, 2, 2);
+        assert!(verify_batch(&checks(&inputs), &txid).is_err());
+        assert_eq!(
+            verify_tx_signatures(&checks(&inputs), &txid).unwrap_err().0,
+            2
+        );
+    }
+
+    quickcheck! {
+
+        // checking all addresses before the signatures accepts and rejects exactly what
+        // per-input verification does, and pinpoints the same (first) failing input
+        fn verify_addresses_matches_per_input(corruptions: Vec<u8>) -> bool {
+            let txid = Tx::new().id();
+            let inputs = corruptions
+                .iter()
+                .take(16)
+                .enumerate()
+                .map(|(i, c)| corrupted_input(&txid, i as u8, c % 5))
+                .collect::<Vec<_>>();
+            let per_input = inputs
+                .iter()
+                .position(|(witness, address)| verify_tx_address(witness, &txid, address).is_err());
+            let all_inputs = verify_tx_addresses(inputs.iter().map(|(w, a)| (w, a)), &txid)
+                .err()
+                .map(|(i, _)| i);
+            per_input == all_inputs
+        }
+    }
+
     #[test]
     fn check_staked_verify() {
         let transation = Tx::new();
@@ -1,13 +1,46 @@
 #!/bin/bash
 set -xe
-cd chain-abci
+ROOT=$(pwd)
 ## build fuzzer
 cargo install cargo-fuzz
 # dummy values
 export NETWORK_ID="ab"
 export MRSIGNER="0000000000000000000000000000000000000000000000000000000000000000"
 export TQE_MRENCLAVE="0000000000000000000000000000000000000000000000000000000000000000"
-cargo fuzz run abci-cycle -- -runs=0
+# seed corpus from the test vectors
+CORPUS=$ROOT/fuzz-seed-corpus
+cargo run -p dev-utils -- test-vectors --corpus-dir "$CORPUS" > /dev/null
+# fuzzing targets of each crate
+CHAIN_CORE_TARGETS="decode-tx-aux decode-plain-tx-aux decode-tx-attributes decode-staked-state structured-tx"
+ENCLAVE_PROTOCOL_TARGETS="decode-intra-enclave-request decode-decryption-request decode-tdbe-protocol structured-request"
+MLS_TARGETS="parse-keypackage parse-mls-plaintext parse-welcome structured-message"
+ABCI_TARGETS="abci-cycle"
+# smoke-run the targets, the decoding ones on the seed corpus
+smoke_run() {
+  cd "$ROOT/$1"
+  for target in $2; do
+    if [[ $target == structured-* || $target == abci-* ]]; then
+      cargo fuzz run $target -- -runs=0
+    else
+      mkdir -p "$CORPUS/$target"
+      cargo fuzz run $target "$CORPUS/$target" -- -runs=0
+    fi
+  done
+}
+smoke_run chain-core "$CHAIN_CORE_TARGETS"
+smoke_run enclave-protocol "$ENCLAVE_PROTOCOL_TARGETS"
+smoke_run chain-tx-enclave-next/mls "$MLS_TARGETS"
+smoke_run chain-abci "$ABCI_TARGETS"
+# register the targets with fuzzit
+cd $ROOT
 wget -q -O fuzzit https://github.com/fuzzitdev/fuzzit/releases/download/v2.4.77/fuzzit_Linux_x86_64
 chmod a+x fuzzit
-./fuzzit create job --type fuzzing abci-cycle ./fuzz/target/x86_64-unknown-linux-gnu/release/abci-cycle
\ No newline at end of file
+create_jobs() {
+  for target in $2; do
+    ./fuzzit create job --type fuzzing $target "$ROOT/$1/fuzz/target/x86_64-unknown-linux-gnu/release/$target"
+  done
+}
+create_jobs chain-core "$CHAIN_CORE_TARGETS"
+create_jobs enclave-protocol "$ENCLAVE_PROTOCOL_TARGETS"
+create_jobs chain-tx-enclave-next/mls "$MLS_TARGETS"
+create_jobs chain-abci "$ABCI_TARGETS"
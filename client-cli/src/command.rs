@@ -1,4 +1,6 @@
+mod address_book_command;
 mod address_command;
+mod history_command;
 mod multisig_command;
 mod transaction_command;
 mod wallet_command;
@@ -14,28 +16,33 @@ use hex::encode;
 use log::warn;
 use pbr::ProgressBar;
 use quest::{ask, success};
+use structopt::clap::AppSettings;
 use structopt::StructOpt;
 
 use chain_core::init::coin::Coin;
 use chain_core::state::account::{NodeState, StakedStateAddress};
 use client_common::storage::SledStorage;
+use client_common::tendermint::lite::EvidenceReporter;
 #[cfg(not(feature = "mock-enclave"))]
 use client_common::tendermint::types::AbciQueryExt;
 use client_common::tendermint::types::GenesisExt;
-use client_common::tendermint::{Client, WebsocketRpcClient};
+use client_common::tendermint::{Client, NewBlockSubscriber, WebsocketRpcClient};
 use client_common::TransactionObfuscation;
 use client_common::{ErrorKind, Result, ResultExt, SecKey, Storage};
 use client_core::signer::WalletSignerManager;
 use client_core::transaction_builder::DefaultWalletTransactionBuilder;
-use client_core::types::BalanceChange;
+use client_core::types::{BalanceChange, TransactionChange};
+use client_core::wallet::sync_daemon::{FollowOptions, SyncDaemon};
 use client_core::wallet::syncer::{
-    spawn_light_client_supervisor, Handle, ObfuscationSyncerConfig, ProgressReport, SyncerOptions,
-    WalletSyncer,
+    spawn_light_client_supervisor, Handle, LightClientHandle, ObfuscationSyncerConfig,
+    ProgressReport, SyncerOptions, WalletSyncer,
 };
 use client_core::wallet::{DefaultWalletClient, WalletClient};
 use client_network::network_ops::{DefaultNetworkOpsClient, NetworkOpsClient};
 
+use self::address_book_command::AddressBookCommand;
 use self::address_command::AddressCommand;
+use self::history_command::HistoryCommand;
 use self::multisig_command::MultiSigCommand;
 use self::transaction_command::TransactionCommand;
 use self::wallet_command::WalletCommand;
@@ -45,7 +52,7 @@ use chain_core::tx::fee::LinearFee;
 use client_core::hd_wallet::HardwareKind;
 #[cfg(feature = "mock-hardware-wallet")]
 use client_core::service::LedgerServiceZemu;
-use client_core::service::{HwKeyService, LedgerServiceHID, WalletService};
+use client_core::service::{open_sled_storage, HwKeyService, LedgerServiceHID, WalletService};
 use once_cell::sync::Lazy;
 use std::env;
 
@@ -103,6 +110,14 @@ pub enum Command {
         #[structopt(subcommand)]
         address_command: AddressCommand,
     },
+    #[structopt(
+        name = "address-book",
+        about = "Address book and transaction labels operations"
+    )]
+    AddressBook {
+        #[structopt(subcommand)]
+        address_book_command: AddressBookCommand,
+    },
     #[structopt(name = "view-key", about = "Shows the view key of a wallet")]
     ViewKey {
         #[structopt(
@@ -125,15 +140,20 @@ pub enum Command {
         )]
         name: String,
     },
-    #[structopt(name = "history", about = "Get transaction history of a wallet")]
+    #[structopt(
+        name = "history",
+        about = "Get transaction history of a wallet",
+        setting = AppSettings::SubcommandsNegateReqs
+    )]
     History {
         #[structopt(
             name = "wallet name",
             short = "n",
             long = "name",
+            required = true,
             help = "Name of wallet"
         )]
-        name: String,
+        name: Option<String>,
         #[structopt(name = "offset", short, long, help = "Offset", default_value = "0")]
         offset: usize,
         #[structopt(name = "limit", short, long, help = "Limit", default_value = "100")]
@@ -145,6 +165,8 @@ pub enum Command {
             help = "Reverse order (default is from old to new)"
         )]
         reversed: bool,
+        #[structopt(subcommand)]
+        history_command: Option<HistoryCommand>,
     },
     #[structopt(name = "transaction", about = "Transaction operations")]
     Transaction {
@@ -250,6 +272,18 @@ pub enum Command {
             help = "Number of block height to rollback the utxos in pending transactions"
         )]
         block_height_ensure: u64,
+        #[structopt(
+            name = "follow",
+            long,
+            help = "Keep synchronizing as new blocks are committed, until interrupted"
+        )]
+        follow: bool,
+        #[structopt(
+            name = "report-evidence",
+            long,
+            help = "Submit evidence to the node when light client witnesses report conflicting headers"
+        )]
+        report_evidence: bool,
     },
     #[structopt(name = "multisig", about = "MultiSig operations")]
     MultiSig {
@@ -296,23 +330,30 @@ impl Command {
     pub fn execute(&self) -> Result<()> {
         match self {
             Command::Wallet { wallet_command } => {
-                let storage = SledStorage::new(storage_path())?;
+                let storage = open_sled_storage(storage_path())?;
                 let wallet_client = DefaultWalletClient::new_read_only(storage);
                 wallet_command.execute(wallet_client)
             }
             Command::Address { address_command } => {
-                let storage = SledStorage::new(storage_path())?;
+                let storage = open_sled_storage(storage_path())?;
                 let wallet_client = DefaultWalletClient::new_read_only(storage);
                 address_command.execute(wallet_client)
             }
+            Command::AddressBook {
+                address_book_command,
+            } => {
+                let storage = open_sled_storage(storage_path())?;
+                let wallet_client = DefaultWalletClient::new_read_only(storage);
+                address_book_command.execute(wallet_client)
+            }
             Command::ViewKey { name, private } => {
-                let storage = SledStorage::new(storage_path())?;
+                let storage = open_sled_storage(storage_path())?;
                 let wallet_client = DefaultWalletClient::new_read_only(storage);
 
                 Self::get_view_key(wallet_client, name, *private)
             }
             Command::Balance { name } => {
-                let storage = SledStorage::new(storage_path())?;
+                let storage = open_sled_storage(storage_path())?;
                 let wallet_client = DefaultWalletClient::new_read_only(storage);
                 Self::get_balance(wallet_client, name)
             }
@@ -321,15 +362,23 @@ impl Command {
                 offset,
                 limit,
                 reversed,
+                history_command,
             } => {
-                let storage = SledStorage::new(storage_path())?;
+                let storage = open_sled_storage(storage_path())?;
                 let wallet_client = DefaultWalletClient::new_read_only(storage);
-                Self::get_history(wallet_client, name, *offset, *limit, *reversed)
+                match (history_command, name) {
+                    (Some(history_command), _) => history_command.execute(wallet_client),
+                    (None, Some(name)) => {
+                        Self::get_history(wallet_client, name, *offset, *limit, *reversed)
+                    }
+                    // `clap` requires the name when there is no subcommand
+                    (None, None) => unreachable!("wallet name is required"),
+                }
             }
             Command::Transaction {
                 transaction_command,
             } => {
-                let storage = SledStorage::new(storage_path())?;
+                let storage = open_sled_storage(storage_path())?;
                 let tendermint_client = WebsocketRpcClient::new(&tendermint_url())?;
                 let wallet_name = transaction_command.wallet_name();
                 let wallet_service = WalletService::new(storage.clone());
@@ -391,7 +440,7 @@ impl Command {
                     }
                     Some(HardwareKind::LocalOnly) => HwKeyService::default(),
                 };
-                let storage = SledStorage::new(storage_path())?;
+                let storage = open_sled_storage(storage_path())?;
                 let tendermint_client = WebsocketRpcClient::new(&tendermint_url())?;
                 let signer_manager =
                     WalletSignerManager::new(storage.clone(), hw_key_service.clone());
@@ -431,13 +480,16 @@ impl Command {
                 light_client_trusting_period_seconds,
                 light_client_trusting_height,
                 light_client_trusting_blockhash,
+                follow,
+                report_evidence,
             } => {
                 let rpc_url = tendermint_url();
                 let tendermint_client = WebsocketRpcClient::new(&rpc_url)?;
                 let tx_obfuscation = get_tx_query(tendermint_client.clone())?;
                 let db_path = storage_path();
-                let storage = SledStorage::new(&db_path)?;
-                let max_trusting_period = tendermint_client.genesis()?.trusting_period() / 2;
+                let storage = open_sled_storage(&db_path)?;
+                let genesis = tendermint_client.genesis()?;
+                let max_trusting_period = genesis.trusting_period() / 2;
 
                 let mut light_client_peers_user: String = "".into();
                 let mut light_client_trusting_period_seconds_user: u64 = 0;
@@ -537,25 +589,36 @@ impl Command {
                         .expect("get trusted block confirm")
                 };
 
+                let evidence_reporter = || -> Option<Box<dyn EvidenceReporter>> {
+                    if *report_evidence {
+                        Some(Box::new(tendermint_client.clone()))
+                    } else {
+                        None
+                    }
+                };
                 let handle = if automode && !disable_light_client {
                     Some(spawn_light_client_supervisor(
                         db_path.as_ref(),
+                        &genesis,
                         trusting_period,
                         light_client_peers_user.clone(),
                         light_client_trusting_period_seconds_user,
                         light_client_trusting_height_user,
                         "".into(),
                         None,
+                        evidence_reporter(),
                     )?)
                 } else if !automode && !disable_light_client {
                     Some(spawn_light_client_supervisor(
                         db_path.as_ref(),
+                        &genesis,
                         trusting_period,
                         light_client_peers_user.clone(),
                         light_client_trusting_period_seconds_user,
                         light_client_trusting_height_user,
                         light_client_trusting_blockhash_user.clone(),
                         Some(Arc::new(Mutex::new(Box::new(user_func)))),
+                        evidence_reporter(),
                     )?)
                 } else {
                     None
@@ -576,6 +639,8 @@ impl Command {
                             light_client_trusting_period_seconds_user,
                         light_client_trusting_height: light_client_trusting_height_user,
                         light_client_trusting_blockhash: light_client_trusting_blockhash_user,
+                        follow_new_blocks: *follow,
+                        light_client_report_evidence: *report_evidence,
                     },
                     handle.clone(),
                 );
@@ -588,9 +653,13 @@ impl Command {
                 Ok(())
             }
             Command::MultiSig { multisig_command } => {
-                let storage = SledStorage::new(storage_path())?;
-                let wallet_client = DefaultWalletClient::new_read_only(storage);
-                multisig_command.execute(wallet_client)
+                let storage = open_sled_storage(storage_path())?;
+                if multisig_command.is_online() {
+                    multisig_command.execute(get_wallet_client(storage)?)
+                } else {
+                    let wallet_client = DefaultWalletClient::new_read_only(storage);
+                    multisig_command.execute(wallet_client)
+                }
             }
         }
     }
@@ -735,6 +804,18 @@ impl Command {
                 ),
             ]),
         ];
+        let rows = rows
+            .into_iter()
+            .chain(balance.locked.iter().map(|locked| {
+                Row::new(vec![
+                    Cell::new(
+                        format!("Locked until {}", locked.locked_until).as_str(),
+                        Default::default(),
+                    ),
+                    Cell::new(format!("{}", locked.amount).as_str(), Default::default()),
+                ])
+            }))
+            .collect();
 
         let table = Table::new(rows, Default::default())
             .chain(|| (ErrorKind::InternalError, "Unable to create new table"))?;
@@ -770,6 +851,7 @@ impl Command {
                 Cell::new("Transaction Type", bold),
                 Cell::new("Block Height", bold),
                 Cell::new("Block Time", bold),
+                Cell::new("Label", bold),
             ]));
 
             for change in history {
@@ -805,6 +887,7 @@ impl Command {
                     Cell::new(&change.transaction_type, Default::default()),
                     Cell::new(&change.block_height, right_justify),
                     Cell::new(&change.block_time, Default::default()),
+                    Cell::new(&history_label(&change), Default::default()),
                 ]));
             }
 
@@ -821,7 +904,12 @@ impl Command {
         Ok(())
     }
 
-    fn resync<S: Storage, C: Client, O: TransactionObfuscation, L: Handle + Send + Sync + Clone>(
+    fn resync<
+        S: Storage,
+        C: Client + NewBlockSubscriber,
+        O: TransactionObfuscation,
+        L: LightClientHandle,
+    >(
         config: ObfuscationSyncerConfig<S, C, O, L>,
         name: String,
         enckey: SecKey,
@@ -829,6 +917,8 @@ impl Command {
         storage: SledStorage,
     ) -> Result<()> {
         let wallet_client = get_wallet_client(storage)?;
+        let follow = config.options.follow_new_blocks;
+        let subscriber = config.client.clone();
 
         let mut init_block_height = 0;
         let mut final_block_height = 0;
@@ -868,7 +958,28 @@ impl Command {
         if force {
             syncer.reset_state()?;
         }
-        syncer.sync(progress_callback)
+        if follow {
+            SyncDaemon::new(syncer, subscriber, FollowOptions::default()).run(progress_callback)
+        } else {
+            syncer.sync(progress_callback)
+        }
+    }
+}
+
+/// Returns the label of a transaction followed by the labels of its counterparties
+fn history_label(change: &TransactionChange) -> String {
+    let mut labels = change
+        .address_labels
+        .values()
+        .map(|label| format!("@{}", label))
+        .collect::<Vec<_>>();
+    labels.sort();
+    labels.dedup();
+
+    match change.label {
+        Some(ref label) if labels.is_empty() => label.label.clone(),
+        Some(ref label) => format!("{} ({})", label.label, labels.join(", ")),
+        None => labels.join(", "),
     }
 }
 
@@ -0,0 +1,198 @@
+use std::str::FromStr;
+
+use cli_table::format::CellFormat;
+use cli_table::{Cell, Row, Table};
+use quest::success;
+use structopt::StructOpt;
+
+use chain_core::tx::data::input::str2txid;
+use client_common::{Error, ErrorKind, Result, ResultExt};
+use client_core::types::{BookAddress, TransactionLabel};
+use client_core::WalletClient;
+
+use crate::ask_seckey;
+
+#[derive(Debug, StructOpt)]
+pub enum AddressBookCommand {
+    #[structopt(
+        name = "add",
+        about = "Labels an address, the label can then be used in place of the address (e.g. @alice)"
+    )]
+    Add {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(name = "label", short = "l", long = "label", help = "Label of address")]
+        label: String,
+        #[structopt(
+            name = "address",
+            short = "a",
+            long = "address",
+            help = "Transfer or staking address"
+        )]
+        address: String,
+    },
+    #[structopt(name = "remove", about = "Removes a label from the address book")]
+    Remove {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(name = "label", short = "l", long = "label", help = "Label of address")]
+        label: String,
+    },
+    #[structopt(name = "list", about = "List all the labelled addresses")]
+    List {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+    },
+    #[structopt(name = "label-tx", about = "Labels a transaction")]
+    LabelTransaction {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "transaction id",
+            short = "i",
+            long = "id",
+            help = "Transaction ID"
+        )]
+        transaction_id: String,
+        #[structopt(
+            name = "label",
+            short = "l",
+            long = "label",
+            help = "Label of transaction"
+        )]
+        label: String,
+        #[structopt(name = "note", long = "note", help = "Note on transaction")]
+        note: Option<String>,
+    },
+    #[structopt(name = "unlabel-tx", about = "Removes the label of a transaction")]
+    UnlabelTransaction {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "transaction id",
+            short = "i",
+            long = "id",
+            help = "Transaction ID"
+        )]
+        transaction_id: String,
+    },
+}
+
+impl AddressBookCommand {
+    pub fn execute<T: WalletClient>(&self, wallet_client: T) -> Result<()> {
+        match self {
+            AddressBookCommand::Add {
+                name,
+                label,
+                address,
+            } => {
+                let address = BookAddress::from_str(address)?;
+                let enckey = ask_seckey(None)?;
+                wallet_client.set_address_label(name, &enckey, label, &address)?;
+                success(&format!("Address {} labelled as @{}", address, label));
+                Ok(())
+            }
+            AddressBookCommand::Remove { name, label } => {
+                let enckey = ask_seckey(None)?;
+                wallet_client.remove_address_label(name, &enckey, label)?;
+                success(&format!("Label @{} removed", label));
+                Ok(())
+            }
+            AddressBookCommand::List { name } => Self::list(wallet_client, name),
+            AddressBookCommand::LabelTransaction {
+                name,
+                transaction_id,
+                label,
+                note,
+            } => {
+                let transaction_id = parse_transaction_id(transaction_id)?;
+                let enckey = ask_seckey(None)?;
+                let label = TransactionLabel {
+                    label: label.clone(),
+                    note: note.clone(),
+                };
+                wallet_client.set_transaction_label(name, &enckey, transaction_id, &label)?;
+                success("Transaction labelled");
+                Ok(())
+            }
+            AddressBookCommand::UnlabelTransaction {
+                name,
+                transaction_id,
+            } => {
+                let transaction_id = parse_transaction_id(transaction_id)?;
+                let enckey = ask_seckey(None)?;
+                wallet_client.remove_transaction_label(name, &enckey, &transaction_id)?;
+                success("Transaction label removed");
+                Ok(())
+            }
+        }
+    }
+
+    fn list<T: WalletClient>(wallet_client: T, name: &str) -> Result<()> {
+        let enckey = ask_seckey(None)?;
+        let book = wallet_client.address_book(name, &enckey)?;
+
+        if book.contacts.is_empty() {
+            success("No labelled address found!");
+            return Ok(());
+        }
+
+        let bold = CellFormat::builder().bold(true).build();
+        let mut rows = vec![Row::new(vec![
+            Cell::new("Label", bold),
+            Cell::new("Type", bold),
+            Cell::new("Address", bold),
+        ])];
+        for (label, address) in book.contacts.iter() {
+            let address_type = match address {
+                BookAddress::Transfer(_) => "transfer",
+                BookAddress::Staking(_) => "staking",
+            };
+            rows.push(Row::new(vec![
+                Cell::new(&format!("@{}", label), Default::default()),
+                Cell::new(address_type, Default::default()),
+                Cell::new(address, Default::default()),
+            ]));
+        }
+
+        let table = Table::new(rows, Default::default())
+            .chain(|| (ErrorKind::InternalError, "Unable to create new table"))?;
+        table
+            .print_stdout()
+            .chain(|| (ErrorKind::IoError, "Unable to print table"))
+    }
+}
+
+fn parse_transaction_id(transaction_id: &str) -> Result<[u8; 32]> {
+    str2txid(transaction_id).map_err(|_| {
+        Error::new(
+            ErrorKind::DeserializationError,
+            "Unable to deserialize transaction ID",
+        )
+    })
+}
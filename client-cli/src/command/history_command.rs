@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use quest::success;
+use structopt::StructOpt;
+
+use client_common::tendermint::types::Time;
+use client_common::{Error, ErrorKind, Result, ResultExt};
+use client_core::types::{HistoryFilter, HistoryFormat, HistoryRecordType};
+use client_core::WalletClient;
+
+use crate::ask_seckey;
+
+#[derive(Debug, StructOpt)]
+pub enum HistoryCommand {
+    #[structopt(
+        name = "export",
+        about = "Exports transaction history, staking rewards and slashes of a wallet as CSV or JSON"
+    )]
+    Export {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "from",
+            long = "from",
+            help = "Start of period, inclusive (e.g. 2020-01-01 or 2020-01-01T00:00:00Z)"
+        )]
+        from: Option<String>,
+        #[structopt(
+            name = "to",
+            long = "to",
+            help = "End of period, exclusive (e.g. 2020-02-01 or 2020-02-01T00:00:00Z)"
+        )]
+        to: Option<String>,
+        #[structopt(
+            name = "type",
+            short = "t",
+            long = "type",
+            help = "Only export records of this type (transfer, deposit, unbond, withdraw, unjail, nodejoin, reward or slash), can be repeated"
+        )]
+        types: Vec<HistoryRecordType>,
+        #[structopt(
+            name = "format",
+            long = "format",
+            default_value = "csv",
+            help = "Output format (csv or json)"
+        )]
+        format: HistoryFormat,
+        #[structopt(
+            name = "file",
+            short = "f",
+            long = "file",
+            help = "File to write the export to (printed if not given)"
+        )]
+        file: Option<PathBuf>,
+    },
+}
+
+impl HistoryCommand {
+    pub fn execute<T: WalletClient>(&self, wallet_client: T) -> Result<()> {
+        match self {
+            HistoryCommand::Export {
+                name,
+                from,
+                to,
+                types,
+                format,
+                file,
+            } => {
+                let filter = HistoryFilter {
+                    from: from.as_deref().map(parse_time).transpose()?,
+                    to: to.as_deref().map(parse_time).transpose()?,
+                    types: types.clone(),
+                };
+                let enckey = ask_seckey(None)?;
+                let records = wallet_client.history_records(name, &enckey, &filter)?;
+                let export = format.render(&records)?;
+
+                match file {
+                    None => print!("{}", export),
+                    Some(file) => {
+                        std::fs::write(file, export).chain(|| {
+                            (
+                                ErrorKind::IoError,
+                                format!("Unable to write history to {}", file.display()),
+                            )
+                        })?;
+                        success(&format!(
+                            "{} records exported to {}",
+                            records.len(),
+                            file.display()
+                        ));
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Parses a date (midnight UTC) or an RFC 3339 time
+fn parse_time(time: &str) -> Result<Time> {
+    let rfc3339 = if time.contains('T') {
+        time.to_owned()
+    } else {
+        format!("{}T00:00:00Z", time)
+    };
+    Time::from_str(&rfc3339).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid date or time: {}", time),
+        )
+    })
+}
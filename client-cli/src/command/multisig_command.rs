@@ -1,14 +1,46 @@
+#[cfg(feature = "experimental")]
+use quest::yesno;
 use quest::{ask, success, text};
+#[cfg(feature = "experimental")]
+use secp256k1::schnorrsig::SchnorrSignature;
+#[cfg(feature = "experimental")]
+use std::path::PathBuf;
 use std::str::FromStr;
 use structopt::StructOpt;
 
 use super::address_command::ask_public_key;
+#[cfg(feature = "experimental")]
+use super::transaction_command::{ask_outputs, ask_view_keys};
+#[cfg(feature = "experimental")]
+use chain_core::init::network::get_network_id;
+#[cfg(feature = "experimental")]
+use chain_core::tx::data::access::{TxAccess, TxAccessPolicy};
+#[cfg(feature = "experimental")]
+use chain_core::tx::data::attribute::TxAttributes;
+#[cfg(feature = "experimental")]
+use chain_core::tx::data::{input::str2txid, Tx};
 use client_common::{ErrorKind, PublicKey, Result, ResultExt};
+#[cfg(feature = "experimental")]
+use client_core::transaction_builder::RawTransferTransaction;
 use client_core::types::AddressType;
 use client_core::WalletClient;
+#[cfg(feature = "experimental")]
+use client_core::{multi_sig::SigningPackage, MultiSigWalletClient};
 
 use crate::ask_seckey;
 
+/// Wallet client needed by MultiSig commands
+#[cfg(feature = "experimental")]
+pub trait MultiSigCommandClient: MultiSigWalletClient {}
+#[cfg(feature = "experimental")]
+impl<T: MultiSigWalletClient> MultiSigCommandClient for T {}
+
+/// Wallet client needed by MultiSig commands
+#[cfg(not(feature = "experimental"))]
+pub trait MultiSigCommandClient: WalletClient {}
+#[cfg(not(feature = "experimental"))]
+impl<T: WalletClient> MultiSigCommandClient for T {}
+
 #[derive(Debug, StructOpt)]
 pub enum MultiSigCommand {
     #[structopt(
@@ -70,10 +102,185 @@ pub enum MultiSigCommand {
         )]
         required_signatures: Option<usize>,
     },
+
+    #[cfg(feature = "experimental")]
+    #[structopt(
+        name = "new-package",
+        about = "Create a signing package for a transaction spending from a MultiSig address"
+    )]
+    NewSigningPackage {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "transaction",
+            short = "t",
+            long = "transaction",
+            help = "Path to JSON file of unsigned transaction, with only one input",
+            parse(from_os_str)
+        )]
+        transaction: PathBuf,
+        #[structopt(
+            name = "public keys",
+            short = "p",
+            long = "public_keys",
+            help = "public keys of signers, included self public key, separated by commas"
+        )]
+        public_keys: Option<String>,
+    },
+
+    #[cfg(feature = "experimental")]
+    #[structopt(
+        name = "import-package",
+        about = "Import a signing package from a co-signer and contribute to it"
+    )]
+    ImportSigningPackage {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "package",
+            short = "k",
+            long = "package",
+            help = "Base64 encoded signing package"
+        )]
+        package: Option<String>,
+    },
+
+    #[cfg(feature = "experimental")]
+    #[structopt(
+        name = "export-package",
+        about = "Export the latest state of a signing package"
+    )]
+    ExportSigningPackage {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "transaction id",
+            short = "i",
+            long = "txid",
+            help = "Id of the transaction of signing package"
+        )]
+        transaction_id: String,
+    },
+
+    #[cfg(feature = "experimental")]
+    #[structopt(
+        name = "broadcast-package",
+        about = "Broadcast the transaction of a completely signed signing package"
+    )]
+    BroadcastSigningPackage {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "package",
+            short = "k",
+            long = "package",
+            help = "Base64 encoded signing package"
+        )]
+        package: Option<String>,
+    },
+
+    #[cfg(feature = "experimental")]
+    #[structopt(
+        name = "new-transfer",
+        about = "Create a transfer transaction spending from MultiSig addresses co-signed by given signers"
+    )]
+    NewTransfer {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "public keys",
+            short = "p",
+            long = "public_keys",
+            help = "public keys of signers of the MultiSig inputs, separated by commas"
+        )]
+        public_keys: Option<String>,
+    },
+
+    #[cfg(feature = "experimental")]
+    #[structopt(
+        name = "add-transfer-signature",
+        about = "Add the signature of co-signers to a transfer transaction spending from MultiSig addresses"
+    )]
+    AddTransferSignature {
+        #[structopt(
+            name = "transaction",
+            short = "t",
+            long = "transaction",
+            help = "Base64 encoded raw transfer transaction"
+        )]
+        transaction: Option<String>,
+        #[structopt(
+            name = "signature",
+            short = "s",
+            long = "signature",
+            help = "Hex encoded final signature of co-signers' multi-sig session"
+        )]
+        signature: Option<String>,
+    },
+
+    #[cfg(feature = "experimental")]
+    #[structopt(
+        name = "broadcast-transfer",
+        about = "Broadcast a completely signed transfer transaction spending from MultiSig addresses"
+    )]
+    BroadcastTransfer {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "transaction",
+            short = "t",
+            long = "transaction",
+            help = "Base64 encoded raw transfer transaction"
+        )]
+        transaction: Option<String>,
+    },
 }
 
 impl MultiSigCommand {
-    pub fn execute<T: WalletClient>(&self, wallet_client: T) -> Result<()> {
+    /// Returns true if the command needs a connection to the chain
+    pub fn is_online(&self) -> bool {
+        #[cfg(feature = "experimental")]
+        {
+            match self {
+                MultiSigCommand::BroadcastSigningPackage { .. }
+                | MultiSigCommand::BroadcastTransfer { .. } => return true,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    pub fn execute<T: MultiSigCommandClient>(&self, wallet_client: T) -> Result<()> {
         match self {
             MultiSigCommand::NewAddressPublicKey { name } => {
                 new_address_public_key(wallet_client, name)
@@ -93,6 +300,38 @@ impl MultiSigCommand {
                 self_public_key,
                 required_signatures,
             ),
+            #[cfg(feature = "experimental")]
+            MultiSigCommand::NewSigningPackage {
+                name,
+                transaction,
+                public_keys,
+            } => new_signing_package(wallet_client, name, transaction, public_keys),
+            #[cfg(feature = "experimental")]
+            MultiSigCommand::ImportSigningPackage { name, package } => {
+                import_signing_package(wallet_client, name, package)
+            }
+            #[cfg(feature = "experimental")]
+            MultiSigCommand::ExportSigningPackage {
+                name,
+                transaction_id,
+            } => export_signing_package(wallet_client, name, transaction_id),
+            #[cfg(feature = "experimental")]
+            MultiSigCommand::BroadcastSigningPackage { name, package } => {
+                broadcast_signing_package(wallet_client, name, package)
+            }
+            #[cfg(feature = "experimental")]
+            MultiSigCommand::NewTransfer { name, public_keys } => {
+                new_transfer(wallet_client, name, public_keys)
+            }
+            #[cfg(feature = "experimental")]
+            MultiSigCommand::AddTransferSignature {
+                transaction,
+                signature,
+            } => add_transfer_signature(wallet_client, transaction, signature),
+            #[cfg(feature = "experimental")]
+            MultiSigCommand::BroadcastTransfer { name, transaction } => {
+                broadcast_transfer(wallet_client, name, transaction)
+            }
         }
     }
 }
@@ -169,6 +408,261 @@ fn new_multisign_address<T: WalletClient>(
     Ok(())
 }
 
+#[cfg(feature = "experimental")]
+fn new_signing_package<T: MultiSigWalletClient>(
+    wallet_client: T,
+    name: &str,
+    transaction: &PathBuf,
+    public_keys: &Option<String>,
+) -> Result<()> {
+    let enckey = ask_seckey(None)?;
+    let transaction = std::fs::read_to_string(transaction)
+        .chain(|| (ErrorKind::IoError, "Unable to read transaction file"))?;
+    let transaction: Tx = serde_json::from_str(&transaction).chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            "Unable to deserialize unsigned transaction",
+        )
+    })?;
+    let public_keys_str = match public_keys {
+        None => ask_public_keys(Some(
+            "Enter public keys of signers (include self public key, separated by commas): ",
+        ))?,
+        Some(s) => s.clone(),
+    };
+    let public_keys = public_keys_str
+        .split(',')
+        .map(|s| PublicKey::from_str(s.trim()))
+        .collect::<Result<Vec<_>>>()
+        .chain(|| (ErrorKind::InvalidInput, "Invalid public key"))?;
+
+    let package = wallet_client.new_signing_package(name, &enckey, transaction, public_keys)?;
+    print_signing_package(&package);
+    Ok(())
+}
+
+#[cfg(feature = "experimental")]
+fn import_signing_package<T: MultiSigWalletClient>(
+    wallet_client: T,
+    name: &str,
+    package: &Option<String>,
+) -> Result<()> {
+    let package = read_signing_package(package)?;
+    let enckey = ask_seckey(None)?;
+
+    success(&format!("Transaction ID: {}", hex::encode(&package.id())));
+    for output in package.unsigned_transaction.outputs.iter() {
+        success(&format!("Output: {} to {}", output.value, output.address));
+    }
+    ask("Contribute to signing this transaction? [yN] ");
+    match yesno(false).chain(|| (ErrorKind::IoError, "Unable to read yes/no"))? {
+        Some(true) => {}
+        _ => return Err(ErrorKind::InvalidInput.into()),
+    }
+
+    let package = wallet_client.import_signing_package(name, &enckey, package)?;
+    print_signing_package(&package);
+    Ok(())
+}
+
+#[cfg(feature = "experimental")]
+fn export_signing_package<T: MultiSigWalletClient>(
+    wallet_client: T,
+    name: &str,
+    transaction_id: &str,
+) -> Result<()> {
+    let transaction_id =
+        str2txid(transaction_id).chain(|| (ErrorKind::InvalidInput, "Invalid transaction id"))?;
+    let enckey = ask_seckey(None)?;
+
+    let package = wallet_client.export_signing_package(name, &enckey, &transaction_id)?;
+    print_signing_package(&package);
+    Ok(())
+}
+
+#[cfg(feature = "experimental")]
+fn broadcast_signing_package<T: MultiSigWalletClient>(
+    wallet_client: T,
+    name: &str,
+    package: &Option<String>,
+) -> Result<()> {
+    let package = read_signing_package(package)?;
+    let enckey = ask_seckey(None)?;
+
+    let tx_aux = wallet_client.signing_package_transaction(name, &enckey, package)?;
+    wallet_client.broadcast_transaction(&tx_aux)?;
+    success(&format!(
+        "Transaction successfully broadcasted: {}",
+        hex::encode(&tx_aux.tx_id())
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "experimental")]
+fn new_transfer<T: MultiSigWalletClient>(
+    wallet_client: T,
+    name: &str,
+    public_keys: &Option<String>,
+) -> Result<()> {
+    let enckey = ask_seckey(None)?;
+    let public_keys_str = match public_keys {
+        None => ask_public_keys(Some(
+            "Enter public keys of signers of the MultiSig inputs (separated by commas): ",
+        ))?,
+        Some(s) => s.clone(),
+    };
+    let signer_public_keys = public_keys_str
+        .split(',')
+        .map(|s| PublicKey::from_str(s.trim()))
+        .collect::<Result<Vec<_>>>()
+        .chain(|| (ErrorKind::InvalidInput, "Invalid public key"))?;
+
+    let outputs = ask_outputs(&wallet_client, name, &enckey)?;
+    let mut view_keys = ask_view_keys()?;
+    view_keys.push(wallet_client.view_key(name, &enckey)?);
+    let access_policies = view_keys
+        .iter()
+        .map(|key| TxAccessPolicy {
+            view_key: key.into(),
+            access: TxAccess::AllData,
+        })
+        .collect();
+    let attributes = TxAttributes::new_with_access(get_network_id(), access_policies);
+    let return_address = wallet_client.new_transfer_address(name, &enckey)?;
+
+    let raw_transaction = wallet_client.create_multisig_transfer_transaction(
+        name,
+        &enckey,
+        outputs,
+        attributes,
+        None,
+        return_address,
+        signer_public_keys,
+    )?;
+    print_raw_transfer(&raw_transaction);
+    Ok(())
+}
+
+#[cfg(feature = "experimental")]
+fn add_transfer_signature<T: MultiSigWalletClient>(
+    wallet_client: T,
+    transaction: &Option<String>,
+    signature: &Option<String>,
+) -> Result<()> {
+    let raw_transaction = read_raw_transfer(transaction)?;
+    let signature = match signature {
+        None => {
+            ask("Enter signature of co-signers: ");
+            text().chain(|| (ErrorKind::InvalidInput, "Unable to read signature"))?
+        }
+        Some(signature) => signature.clone(),
+    };
+    let signature = hex::decode(signature.trim())
+        .ok()
+        .and_then(|signature| SchnorrSignature::from_default(&signature).ok())
+        .chain(|| (ErrorKind::InvalidInput, "Invalid signature"))?;
+
+    let raw_transaction = wallet_client.add_cosigners_signature(raw_transaction, signature)?;
+    print_raw_transfer(&raw_transaction);
+    Ok(())
+}
+
+#[cfg(feature = "experimental")]
+fn broadcast_transfer<T: MultiSigWalletClient>(
+    wallet_client: T,
+    name: &str,
+    transaction: &Option<String>,
+) -> Result<()> {
+    let raw_transaction = read_raw_transfer(transaction)?;
+    let enckey = ask_seckey(None)?;
+
+    let transaction_id =
+        wallet_client.broadcast_multisig_transfer_transaction(name, &enckey, raw_transaction)?;
+    success(&format!(
+        "Transaction successfully broadcasted: {}",
+        hex::encode(&transaction_id)
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "experimental")]
+fn read_raw_transfer(transaction: &Option<String>) -> Result<RawTransferTransaction> {
+    let transaction = match transaction {
+        None => {
+            ask("Enter raw transfer transaction: ");
+            text().chain(|| (ErrorKind::InvalidInput, "Unable to read raw transaction"))?
+        }
+        Some(transaction) => transaction.clone(),
+    };
+    RawTransferTransaction::from_str(transaction.trim())
+}
+
+#[cfg(feature = "experimental")]
+fn print_raw_transfer(raw_transaction: &RawTransferTransaction) {
+    let inputs = raw_transaction.inputs().len();
+    let signed_inputs = raw_transaction
+        .inputs()
+        .iter()
+        .filter(|input| input.witness.is_some())
+        .count();
+
+    success(&format!(
+        "Transaction ID: {}",
+        hex::encode(&raw_transaction.id())
+    ));
+    success(&format!("Signed inputs: {}/{}", signed_inputs, inputs));
+    if signed_inputs == inputs {
+        success("All inputs signed, the transaction can be broadcasted");
+    } else {
+        success("Sign the transaction ID in a multi-sig session with co-signers, then add their signature to:");
+    }
+    success(&raw_transaction.to_string());
+}
+
+#[cfg(feature = "experimental")]
+fn read_signing_package(package: &Option<String>) -> Result<SigningPackage> {
+    let package = match package {
+        None => {
+            ask("Enter signing package: ");
+            text().chain(|| (ErrorKind::InvalidInput, "Unable to read signing package"))?
+        }
+        Some(package) => package.clone(),
+    };
+    SigningPackage::from_base64(&package)
+}
+
+#[cfg(feature = "experimental")]
+fn print_signing_package(package: &SigningPackage) {
+    let signers = package.signers.len();
+    let nonce_commitments = package
+        .signers
+        .iter()
+        .filter(|signer| signer.nonce_commitment.is_some())
+        .count();
+    let nonces = package
+        .signers
+        .iter()
+        .filter(|signer| signer.nonce.is_some())
+        .count();
+    let partial_signatures = package
+        .signers
+        .iter()
+        .filter(|signer| signer.partial_signature.is_some())
+        .count();
+
+    success(&format!("Transaction ID: {}", hex::encode(&package.id())));
+    success(&format!(
+        "Nonce commitments: {}/{}, nonces: {}/{}, partial signatures: {}/{}",
+        nonce_commitments, signers, nonces, signers, partial_signatures, signers
+    ));
+    if partial_signatures == signers {
+        success("All signatures collected, the package can be broadcasted");
+    } else {
+        success("Send the signing package to co-signers:");
+    }
+    success(&package.to_base64());
+}
+
 fn ask_required_signature() -> Result<usize> {
     ask("how many signatures required: ");
     let n = text().err_kind(ErrorKind::InvalidInput, || {
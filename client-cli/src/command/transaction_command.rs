@@ -17,11 +17,11 @@ use chain_core::tx::data::access::{TxAccess, TxAccessPolicy};
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::attribute::TxAttributes;
 use chain_core::tx::data::input::TxoPointer;
-use chain_core::tx::data::output::TxOut;
+use chain_core::tx::data::output::{RelativeTimelock, TxOut};
 use chain_core::tx::TxAux;
 use client_common::{Error, ErrorKind, PublicKey, Result, ResultExt, SecKey, Transaction};
 use client_core::transaction_builder::SignedTransferTransaction;
-use client_core::types::{BalanceChange, TransactionPending};
+use client_core::types::{BalanceChange, BookAddress, TransactionPending};
 use client_core::WalletClient;
 use client_network::NetworkOpsClient;
 use mls::{Codec, DefaultCipherSuite, KeyPackage};
@@ -293,7 +293,7 @@ impl TransactionCommand {
                 Ok(())
             }
             TransactionCommand::Build { name, file } => {
-                let to_address = ask_transfer_address()?;
+                let to_address = ask_transfer_address(wallet_client, name, &enckey)?;
                 ask("Enter transfer amount (in CRO): ");
                 let amount_str = text().chain(|| (ErrorKind::IoError, "Unable to read amount"))?;
                 let amount = coin_from_str(&amount_str)?;
@@ -496,15 +496,28 @@ fn display_transaction<T: WalletClient>(
                 for (output, (spent_unspent, format)) in
                     outputs.into_iter().zip(spent_unspent.into_iter())
                 {
-                    let valid_from = match output.valid_from {
-                        None => "Not time-locked".to_string(),
-                        Some(valid_from) => {
-                            let valid_from = <DateTime<Local>>::from(DateTime::<Utc>::from_utc(
-                                NaiveDateTime::from_timestamp(valid_from.try_into().unwrap(), 0),
-                                Utc,
-                            ));
-                            valid_from.to_string()
-                        }
+                    let absolute = output.valid_from.map(|valid_from| {
+                        <DateTime<Local>>::from(DateTime::<Utc>::from_utc(
+                            NaiveDateTime::from_timestamp(valid_from.try_into().unwrap(), 0),
+                            Utc,
+                        ))
+                        .to_string()
+                    });
+                    let relative = output
+                        .relative_lock
+                        .map(|relative_lock| match relative_lock {
+                            RelativeTimelock::Seconds(seconds) => {
+                                format!("{} seconds after inclusion", seconds)
+                            }
+                            RelativeTimelock::Blocks(blocks) => {
+                                format!("{} blocks after inclusion", blocks)
+                            }
+                        });
+                    let valid_from = match (absolute, relative) {
+                        (None, None) => "Not time-locked".to_string(),
+                        (Some(absolute), None) => absolute,
+                        (None, Some(relative)) => relative,
+                        (Some(absolute), Some(relative)) => format!("{}, {}", absolute, relative),
                     };
 
                     outputs_rows.push(Row::new(vec![
@@ -603,7 +616,7 @@ fn new_withdraw_transaction<T: WalletClient, N: NetworkOpsClient>(
     enckey: &SecKey,
 ) -> Result<(TxAux, TransactionPending)> {
     let from_address = ask_staking_address()?;
-    let to_address = ask_transfer_address()?;
+    let to_address = ask_transfer_address(wallet_client, name, enckey)?;
     let mut view_keys = ask_view_keys()?;
     let self_view_key = wallet_client.view_key(name, enckey)?;
     view_keys.push(self_view_key);
@@ -705,7 +718,7 @@ fn new_deposit_transaction<T: WalletClient, N: NetworkOpsClient>(
 ) -> Result<(TxAux, TransactionPending)> {
     let attributes = StakedStateOpAttributes::new(get_network_id());
     let inputs = ask_inputs()?;
-    let to_address = ask_staking_address()?;
+    let to_address = ask_deposit_address(wallet_client, name, enckey)?;
     double_confirm_staking_address(wallet_client, network_ops_client, name, enckey, &to_address)?;
     if !wallet_client.has_unspent_transactions(name, enckey, &inputs)? {
         return Err(Error::new(
@@ -736,7 +749,7 @@ fn new_deposit_amount_transaction<T: WalletClient, N: NetworkOpsClient>(
     name: &str,
     enckey: &SecKey,
 ) -> Result<()> {
-    let to_staking_address = ask_staking_address()?;
+    let to_staking_address = ask_deposit_address(wallet_client, name, enckey)?;
     double_confirm_staking_address(
         wallet_client,
         network_ops_client,
@@ -808,7 +821,7 @@ fn new_transfer_transaction<T: WalletClient>(
     name: &str,
     enckey: &SecKey,
 ) -> Result<(TxAux, TransactionPending)> {
-    let outputs = ask_outputs()?;
+    let outputs = ask_outputs(wallet_client, name, enckey)?;
     let mut view_keys = ask_view_keys()?;
     let self_view_key = wallet_client.view_key(name, enckey)?;
     view_keys.push(self_view_key);
@@ -872,7 +885,7 @@ fn new_node_join_transaction<N: NetworkOpsClient>(
     )
 }
 
-fn ask_view_keys() -> Result<Vec<PublicKey>> {
+pub fn ask_view_keys() -> Result<Vec<PublicKey>> {
     ask(
         "Enter view keys (comma separated) (leave blank if you don't want any additional view keys in transaction): ",
     );
@@ -892,22 +905,23 @@ fn ask_view_keys() -> Result<Vec<PublicKey>> {
     }
 }
 
-fn ask_outputs() -> Result<Vec<TxOut>> {
+pub fn ask_outputs<T: WalletClient>(
+    wallet_client: &T,
+    name: &str,
+    enckey: &SecKey,
+) -> Result<Vec<TxOut>> {
     let mut outputs = Vec::new();
 
     let mut flag = true;
 
     while flag {
-        ask("Enter output address: ");
-        let address_encoded =
-            text().chain(|| (ErrorKind::IoError, "Unable to read output address"))?;
-
-        let address = address_encoded.parse::<ExtendedAddr>().chain(|| {
-            (
-                ErrorKind::DeserializationError,
-                "Unable to parse output address",
-            )
-        })?;
+        let address = ask_address(
+            wallet_client,
+            name,
+            enckey,
+            "Enter output address (or @label from address book): ",
+        )?
+        .into_transfer()?;
         let amount = ask_cro()?;
 
         ask(
@@ -915,20 +929,25 @@ fn ask_outputs() -> Result<Vec<TxOut>> {
         );
         let timelock = text().chain(|| (ErrorKind::IoError, "Unable to read timelock value"))?;
 
-        if timelock.is_empty() {
-            outputs.push(TxOut::new(address, amount));
-        } else {
-            outputs.push(TxOut::new_with_timelock(
-                address,
-                amount,
-                timelock.parse::<Timespec>().chain(|| {
-                    (
-                        ErrorKind::DeserializationError,
-                        "Unable to parse timelock into integer",
-                    )
-                })?,
-            ));
+        let mut output = TxOut::new(address, amount);
+        if !timelock.is_empty() {
+            output.valid_from = Some(timelock.parse::<Timespec>().chain(|| {
+                (
+                    ErrorKind::DeserializationError,
+                    "Unable to parse timelock into integer",
+                )
+            })?);
+        }
+
+        ask(
+            "Enter relative timelock since the inclusion of transaction (e.g. 3600s or 100b for seconds or blocks) (leave blank if output is not relatively time locked): ",
+        );
+        let relative_lock =
+            text().chain(|| (ErrorKind::IoError, "Unable to read relative timelock value"))?;
+        if !relative_lock.is_empty() {
+            output.relative_lock = Some(parse_relative_timelock(&relative_lock)?);
         }
+        outputs.push(output);
 
         ask("More outputs? [yN] ");
         match yesno(false).chain(|| (ErrorKind::IoError, "Unable to read yes/no"))? {
@@ -940,6 +959,26 @@ fn ask_outputs() -> Result<Vec<TxOut>> {
     Ok(outputs)
 }
 
+/// Parses a relative timelock in seconds (e.g. `3600s`) or blocks (e.g. `100b`)
+fn parse_relative_timelock(relative_lock: &str) -> Result<RelativeTimelock> {
+    let relative_lock = relative_lock.trim();
+    let (value, unit) = relative_lock.split_at(relative_lock.len().saturating_sub(1));
+    let value = value.parse::<u64>().chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            "Unable to parse relative timelock into integer",
+        )
+    });
+    match unit {
+        "s" => Ok(RelativeTimelock::Seconds(value?)),
+        "b" => Ok(RelativeTimelock::Blocks(value?)),
+        _ => Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Relative timelock should end with s (seconds) or b (blocks)",
+        )),
+    }
+}
+
 fn ask_cro() -> Result<Coin> {
     loop {
         ask("Enter amount (in CRO): ");
@@ -1025,18 +1064,48 @@ fn ask_staking_address() -> Result<StakedStateAddress> {
     Ok(address)
 }
 
-fn ask_transfer_address() -> Result<ExtendedAddr> {
-    ask("Enter transfer address: ");
-    let address = text()
-        .chain(|| (ErrorKind::IoError, "Unable to read transfer address"))?
-        .parse::<ExtendedAddr>()
-        .chain(|| {
-            (
-                ErrorKind::DeserializationError,
-                "Unable to deserialize transfer address",
-            )
-        })?;
+/// Asks for a staking address which can be taken from the address book of the wallet
+fn ask_deposit_address<T: WalletClient>(
+    wallet_client: &T,
+    name: &str,
+    enckey: &SecKey,
+) -> Result<StakedStateAddress> {
+    ask_address(
+        wallet_client,
+        name,
+        enckey,
+        "Enter staking address (or @label from address book): ",
+    )?
+    .into_staking()
+}
 
+fn ask_transfer_address<T: WalletClient>(
+    wallet_client: &T,
+    name: &str,
+    enckey: &SecKey,
+) -> Result<ExtendedAddr> {
+    ask_address(
+        wallet_client,
+        name,
+        enckey,
+        "Enter transfer address (or @label from address book): ",
+    )?
+    .into_transfer()
+}
+
+fn ask_address<T: WalletClient>(
+    wallet_client: &T,
+    name: &str,
+    enckey: &SecKey,
+    message: &str,
+) -> Result<BookAddress> {
+    ask(message);
+    let input = text().chain(|| (ErrorKind::IoError, "Unable to read address"))?;
+
+    let address = wallet_client.resolve_address(name, enckey, &input)?;
+    if input.trim().starts_with('@') {
+        success(&format!("{} resolved to {}", input.trim(), address));
+    }
     Ok(address)
 }
 
@@ -8,7 +8,7 @@ use client_core::{Mnemonic, WalletClient};
 
 use crate::{ask_hardware_kind, ask_passphrase, ask_seckey};
 use client_core::hd_wallet::HardwareKind;
-use client_core::service::WalletInfo;
+use client_core::service::{WalletBackup, WalletInfo};
 use client_core::wallet::WalletRequest;
 use std::fs::File;
 use std::io::Write;
@@ -82,6 +82,41 @@ pub enum WalletCommand {
         )]
         file: PathBuf,
     },
+    #[structopt(
+        name = "backup",
+        about = "Backup all the data of a wallet to an encrypted file"
+    )]
+    Backup {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "file",
+            short = "f",
+            long = "file",
+            parse(from_os_str),
+            help = "file to write the encrypted backup to"
+        )]
+        file: PathBuf,
+    },
+    #[structopt(
+        name = "restore-backup",
+        about = "Restore a wallet from an encrypted backup file"
+    )]
+    RestoreBackup {
+        #[structopt(
+            name = "file",
+            short = "f",
+            long = "file",
+            parse(from_os_str),
+            help = "file of the encrypted backup"
+        )]
+        file: PathBuf,
+    },
     #[structopt(name = "list", about = "List all wallets")]
     List,
     #[structopt(name = "restore", about = "Restore HD Wallet")]
@@ -124,6 +159,19 @@ pub enum WalletCommand {
         )]
         name: String,
     },
+    #[structopt(
+        name = "change-passphrase",
+        about = "Change passphrase of wallet (stop syncing the wallet before)"
+    )]
+    ChangePassphrase {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+    },
 }
 
 impl WalletCommand {
@@ -139,12 +187,17 @@ impl WalletCommand {
             WalletCommand::RestoreBasic { name } => Self::restore_basic_wallet(wallet_client, name),
             WalletCommand::AuthToken { name } => Self::auth_token(wallet_client, name),
             WalletCommand::Delete { name } => Self::delete(wallet_client, name),
+            WalletCommand::ChangePassphrase { name } => {
+                Self::change_passphrase(wallet_client, name)
+            }
             WalletCommand::Export {
                 name,
                 from_file,
                 to_file,
             } => Self::export(wallet_client, name, from_file, to_file),
             WalletCommand::Import { file } => Self::import(wallet_client, file),
+            WalletCommand::Backup { name, file } => Self::backup(wallet_client, name, file),
+            WalletCommand::RestoreBackup { file } => Self::restore_backup(wallet_client, file),
         }
     }
 
@@ -302,6 +355,41 @@ impl WalletCommand {
         Ok(())
     }
 
+    fn backup<T: WalletClient>(wallet_client: T, name: &str, file: &PathBuf) -> Result<()> {
+        let enckey = ask_seckey(None)?;
+        let backup_passphrase = ask_passphrase(Some("Enter backup passphrase: "))?;
+        let confirmed_passphrase = ask_passphrase(Some("Confirm backup passphrase: "))?;
+
+        if backup_passphrase != confirmed_passphrase {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Passphrases do not match",
+            ));
+        }
+
+        let backup = wallet_client.backup_wallet(name, &enckey, &backup_passphrase)?;
+        std::fs::write(file, backup.to_base64())
+            .chain(|| (ErrorKind::IoError, "Unable to write to file"))?;
+
+        success(&format!("Backup of wallet {} written to {:?}", name, file));
+        Ok(())
+    }
+
+    fn restore_backup<T: WalletClient>(wallet_client: T, file: &PathBuf) -> Result<()> {
+        let backup = std::fs::read_to_string(file)
+            .chain(|| (ErrorKind::IoError, "Unable to read from file"))?;
+        let backup = WalletBackup::from_base64(&backup)?;
+        let backup_passphrase = ask_passphrase(Some("Enter backup passphrase: "))?;
+
+        let name = wallet_client.restore_wallet_backup(&backup, &backup_passphrase)?;
+
+        success(&format!(
+            "Wallet {} restored, unlock it with its original passphrase",
+            name
+        ));
+        Ok(())
+    }
+
     fn restore_wallet<T: WalletClient>(wallet_client: T, name: &str) -> Result<()> {
         let passphrase = ask_passphrase(None)?;
         let confirmed_passphrase = ask_passphrase(Some("Confirm passphrase: "))?;
@@ -386,6 +474,26 @@ impl WalletCommand {
         wallet_client.delete_wallet(name, &passphrase)?;
         Ok(())
     }
+
+    fn change_passphrase<T: WalletClient>(wallet_client: T, name: &str) -> Result<()> {
+        let passphrase = ask_passphrase(Some("Enter current passphrase: "))?;
+        let new_passphrase = ask_passphrase(Some("Enter new passphrase: "))?;
+        let confirmed_passphrase = ask_passphrase(Some("Confirm new passphrase: "))?;
+
+        if new_passphrase != confirmed_passphrase {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Passphrases do not match",
+            ));
+        }
+
+        let enckey = wallet_client.change_passphrase(name, &passphrase, &new_passphrase)?;
+        success(&format!(
+            "Authentication token: {}",
+            &hex::encode(enckey.unsecure())
+        ));
+        Ok(())
+    }
 }
 
 fn ask_mnemonic(message: Option<&str>) -> Result<Mnemonic> {
@@ -1,6 +1,8 @@
 //! Chain client errors
 use std::fmt;
 
+use chain_core::tx::rejection::TxRejection;
+
 /// Alias of `Result` objects that return [`Error`]
 ///
 /// [`Error`]: self::Error
@@ -55,6 +57,13 @@ impl Error {
     pub fn kind(&self) -> ErrorKind {
         self.kind
     }
+
+    /// Returns the reason and details of a transaction rejected by the chain (if this error is one)
+    pub fn rejection(&self) -> Option<&TxRejection> {
+        self.origin
+            .as_ref()
+            .and_then(|origin| origin.downcast_ref::<TxRejection>())
+    }
 }
 
 impl fmt::Display for Error {
@@ -125,6 +134,8 @@ pub enum ErrorKind {
     RunEnclaveError,
     /// Ledger error
     LedgerError,
+    /// Transaction rejected by the chain
+    TransactionRejected,
 }
 
 impl fmt::Display for ErrorKind {
@@ -149,6 +160,7 @@ impl fmt::Display for ErrorKind {
             ErrorKind::VerifyError => write!(f, "Verify error"),
             ErrorKind::RunEnclaveError => write!(f, "Run enclave error"),
             ErrorKind::LedgerError => write!(f, "ledger error"),
+            ErrorKind::TransactionRejected => write!(f, "Transaction rejected"),
         }
     }
 }
@@ -1,10 +1,9 @@
 //! m-of-n multi-sig address
 use gcd::Gcd;
-#[cfg(feature = "experimental")]
 use itertools::Itertools;
 use parity_scale_codec::{Decode, Encode};
 
-use super::{Error, ErrorKind, PublicKey, Result};
+use super::{Error, ErrorKind, PublicKey, Result, ResultExt};
 use chain_core::common::{MerkleTree, Proof, H256};
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::witness::tree::RawXOnlyPubkey;
@@ -169,6 +168,49 @@ impl MultiSigAddress {
         Ok(self.merkle_tree.generate_proof(raw_pubkey))
     }
 
+    /// Selects the spending path of the MultiSig address among the combinations of available
+    /// co-signers, preferring the one with the shortest inclusion proof (i.e. the smallest
+    /// witness)
+    pub fn select_proof(&self, available_signers: &[PublicKey]) -> Result<Proof<RawXOnlyPubkey>> {
+        let mut available_signers = available_signers.to_vec();
+        available_signers.sort();
+        available_signers.dedup();
+
+        if available_signers.len() < self.required_signers() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Not enough co-signers available: {} public keys are required, {} given",
+                    self.required_signers(),
+                    available_signers.len()
+                ),
+            ));
+        }
+
+        let mut selected: Option<Proof<RawXOnlyPubkey>> = None;
+        for public_keys in available_signers
+            .into_iter()
+            .combinations(self.required_signers())
+        {
+            if let Some(proof) = self.generate_proof(public_keys)? {
+                let shorter = selected
+                    .as_ref()
+                    .map(|selected| proof.encode().len() < selected.encode().len())
+                    .unwrap_or(true);
+                if shorter {
+                    selected = Some(proof);
+                }
+            }
+        }
+
+        selected.chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                "Available co-signers are not part of the MultiSig address",
+            )
+        })
+    }
+
     /// Returns ExtendedAddr representation of the MultiSigAddress
     #[inline]
     pub fn to_extended_addr(&self) -> ExtendedAddr {
@@ -560,6 +602,65 @@ mod multi_sig_tests {
         assert!(proof.verify(&root_hash));
     }
 
+    #[test]
+    #[cfg(feature = "experimental")]
+    fn check_select_proof_flow() {
+        let public_keys = (1..=4)
+            .map(|i| {
+                PublicKey::from(
+                    &PrivateKey::deserialize_from(&[i; 32]).expect("32 bytes, within curve order"),
+                )
+            })
+            .collect::<Vec<_>>();
+        let multi_sig_address =
+            MultiSigAddress::new(public_keys.clone(), public_keys[0].clone(), 2)
+                .expect("Should create MultiSig address");
+        let root_hash = multi_sig_address.root_hash();
+
+        let available_signers = &public_keys[1..];
+        let proof = multi_sig_address
+            .select_proof(available_signers)
+            .expect("Should select a proof");
+        assert!(proof.verify(&root_hash));
+
+        // selected path is the shortest one among the available co-signers
+        let candidates = available_signers
+            .iter()
+            .cloned()
+            .combinations(2)
+            .map(|keys| multi_sig_address.generate_proof(keys).unwrap().unwrap())
+            .collect::<Vec<_>>();
+        assert!(candidates.contains(&proof));
+        assert!(candidates
+            .iter()
+            .all(|candidate| proof.encode().len() <= candidate.encode().len()));
+
+        // not enough co-signers
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            multi_sig_address
+                .select_proof(&public_keys[..1])
+                .unwrap_err()
+                .kind()
+        );
+
+        // co-signers outside of the address
+        let outsiders = (5..=6)
+            .map(|i| {
+                PublicKey::from(
+                    &PrivateKey::deserialize_from(&[i; 32]).expect("32 bytes, within curve order"),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            multi_sig_address
+                .select_proof(&outsiders)
+                .unwrap_err()
+                .kind()
+        );
+    }
+
     #[test]
     /// online check: https://www.dcode.fr/combinations
     fn check_calculate_combination() {
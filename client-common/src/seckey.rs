@@ -53,8 +53,13 @@ const SALT_CONTEXT: &str = "Crypto.com Chain Wallet 2020-03-30 16:59:10 salt fro
 pub fn derive_enckey(passphrase: &SecUtf8, name: &str) -> argon2::Result<SecKey> {
     let mut salt = [0; 32];
     blake3::derive_key(SALT_CONTEXT, name.as_bytes(), &mut salt);
+    derive_enckey_with_salt(passphrase, &salt)
+}
+
+/// derive encryption key from passphrase and a random salt (e.g. for wallet backups)
+pub fn derive_enckey_with_salt(passphrase: &SecUtf8, salt: &[u8]) -> argon2::Result<SecKey> {
     let mut extended =
-        argon2::hash_raw(passphrase.unsecure().as_bytes(), &salt, &Default::default())?;
+        argon2::hash_raw(passphrase.unsecure().as_bytes(), salt, &Default::default())?;
     let mut arr = GenericArray::clone_from_slice(&[0; 32]);
     blake3::derive_key(GLOBAL_DATA_CONTEXT, &extended, &mut arr);
     extended.zeroize();
@@ -22,6 +22,11 @@ use crate::{Error, ErrorKind, Result, ResultExt};
 /// Nonce size in bytes
 const NONCE_SIZE: usize = 12;
 
+/// Keyspace of storage schema metadata
+pub const SCHEMA_KEYSPACE: &str = "core_schema";
+/// Key of storage schema version in `SCHEMA_KEYSPACE`
+const SCHEMA_VERSION_KEY: &str = "version";
+
 /// Interface for a generic key-value storage
 pub trait Storage: Send + Sync + Clone {
     /// Clears all data in a keyspace.
@@ -80,6 +85,16 @@ pub trait Storage: Send + Sync + Clone {
         self.set(keyspace, key, value.encode()).map(|_| ())
     }
 
+    /// Returns the schema version of stored data (`None` if the storage was never stamped)
+    fn schema_version(&self) -> Result<Option<u32>> {
+        self.load(SCHEMA_KEYSPACE, SCHEMA_VERSION_KEY)
+    }
+
+    /// Records the schema version of stored data
+    fn set_schema_version(&self, version: u32) -> Result<()> {
+        self.save(SCHEMA_KEYSPACE, SCHEMA_VERSION_KEY, &version)
+    }
+
     /// flush db
     fn flush(&self) -> Result<()>;
 }
@@ -237,6 +252,13 @@ pub fn encrypt_bytes<K: AsRef<[u8]>>(key: K, enckey: &SecKey, bytes: &[u8]) -> R
 
 /// Decrypts bytes with given enckey
 pub fn decrypt_bytes<K: AsRef<[u8]>>(key: K, enckey: &SecKey, bytes: &[u8]) -> Result<Vec<u8>> {
+    if bytes.len() < NONCE_SIZE {
+        return Err(Error::new(
+            ErrorKind::DecryptionError,
+            "Encrypted value is too short",
+        ));
+    }
+
     let algo = get_algo(enckey);
 
     let payload = Payload {
@@ -2,6 +2,7 @@
 mod client;
 #[cfg(feature = "websocket-rpc")]
 mod rpc_client;
+mod subscriber;
 mod unauthorized_client;
 
 pub mod lite;
@@ -11,4 +12,5 @@ pub mod types;
 pub use client::Client;
 #[cfg(feature = "websocket-rpc")]
 pub use rpc_client::WebsocketRpcClient;
+pub use subscriber::NewBlockSubscriber;
 pub use unauthorized_client::UnauthorizedClient;
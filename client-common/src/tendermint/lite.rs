@@ -1,32 +1,129 @@
 //! Lite tendermint client
 use parity_scale_codec::{Decode, Encode, Error, Input, Output};
 use serde::{Deserialize, Serialize};
-use tendermint::{block::signed_header::SignedHeader, block::Header, lite, validator};
+use tendermint::lite::{self, Header as _, ValidatorSet as _};
+use tendermint::{block::signed_header::SignedHeader, block::Header, chain, validator, Time};
 
 use crate::tendermint::client::Client;
-use crate::Result as CommonResult;
+use crate::tendermint::types::Genesis;
+use crate::{Error as CommonError, ErrorKind, Result as CommonResult};
+
+/// Trust anchored in the genesis document, before the first block has been verified
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GenesisTrust {
+    chain_id: chain::Id,
+    genesis_time: Time,
+    app_hash: Vec<u8>,
+    validators: validator::Set,
+}
 
 ///
 #[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct TrustedState(pub(crate) Option<lite::TrustedState<SignedHeader, Header>>);
+pub enum TrustedState {
+    /// Nothing verified yet, the genesis validator set and app hash are trusted
+    Genesis(GenesisTrust),
+    /// Latest verified header and the validator set trusted to sign the next one
+    Verified(lite::TrustedState<SignedHeader, Header>),
+}
 
 impl TrustedState {
-    /// construct genesis trusted state
-    pub fn genesis(_genesis_validators: Vec<validator::Info>) -> TrustedState {
-        // FIXME verify the first block against genesis block.
-        TrustedState(None)
+    /// construct genesis trusted state from the genesis validator set and app hash
+    pub fn genesis(genesis: &Genesis) -> TrustedState {
+        TrustedState::Genesis(GenesisTrust {
+            chain_id: genesis.chain_id,
+            genesis_time: genesis.genesis_time,
+            app_hash: genesis.app_hash.clone(),
+            validators: validator::Set::new(genesis.validators.clone()),
+        })
     }
+
+    /// Verify the first block against the genesis trusted state, returns the trusted state
+    /// anchored at the first block
+    pub fn verify_first_block(&self, signed_header: &SignedHeader) -> CommonResult<TrustedState> {
+        let genesis = match self {
+            TrustedState::Genesis(genesis) => genesis,
+            TrustedState::Verified(_) => {
+                return Err(CommonError::new(
+                    ErrorKind::InvalidInput,
+                    "Trusted state is already anchored at a verified block",
+                ))
+            }
+        };
+        let header = &signed_header.header;
+
+        if header.height.value() != 1 {
+            return Err(verify_error(format!(
+                "Expected first block, got block at height {}",
+                header.height
+            )));
+        }
+        if header.chain_id != genesis.chain_id {
+            return Err(verify_error(format!(
+                "Chain id of first block ({}) does not match genesis ({})",
+                header.chain_id, genesis.chain_id
+            )));
+        }
+        if header.time < genesis.genesis_time {
+            return Err(verify_error("First block is older than genesis"));
+        }
+        if header.app_hash != genesis.app_hash {
+            return Err(verify_error(format!(
+                "App hash of first block ({}) does not match genesis ({})",
+                hex::encode_upper(&header.app_hash),
+                hex::encode_upper(&genesis.app_hash)
+            )));
+        }
+        if header.validators_hash != genesis.validators.hash()
+            || header.next_validators_hash != genesis.validators.hash()
+        {
+            return Err(verify_error(
+                "Validator set of first block does not match genesis validators",
+            ));
+        }
+        if signed_header.commit.block_id.hash != header.hash() {
+            return Err(verify_error("Commit of first block is for another block"));
+        }
+        lite::verifier::verify_commit_full(&genesis.validators, signed_header).map_err(|e| {
+            verify_error(format!(
+                "First block is not signed by genesis validators: {}",
+                e
+            ))
+        })?;
+
+        Ok(TrustedState::Verified(lite::TrustedState::new(
+            lite::SignedHeader::new(signed_header.clone(), header.clone()),
+            genesis.validators.clone(),
+        )))
+    }
+
+    /// The verified header (e.g. to anchor a light client at) and the validator set trusted to
+    /// sign the next one, `None` if nothing was verified yet
+    pub fn anchor(&self) -> Option<(&SignedHeader, &validator::Set)> {
+        match self {
+            TrustedState::Genesis(_) => None,
+            TrustedState::Verified(state) => {
+                Some((state.last_header().commit(), state.validators()))
+            }
+        }
+    }
+}
+
+fn verify_error<M>(message: M) -> CommonError
+where
+    String: From<M>,
+{
+    CommonError::new(ErrorKind::VerifyError, message)
 }
 
 impl From<lite::TrustedState<SignedHeader, Header>> for TrustedState {
     fn from(state: lite::TrustedState<SignedHeader, Header>) -> TrustedState {
-        TrustedState(Some(state))
+        TrustedState::Verified(state)
     }
 }
 
 impl Encode for TrustedState {
     fn encode_to<T: Output>(&self, dest: &mut T) {
-        serde_json::to_string(&self.0).unwrap().encode_to(dest)
+        serde_json::to_string(self).unwrap().encode_to(dest)
     }
 }
 
@@ -34,10 +131,72 @@ impl Decode for TrustedState {
     fn decode<I: Input>(value: &mut I) -> Result<Self, Error> {
         serde_json::from_str(&String::decode(value)?)
             .map_err(|_| "fail to decode trusted_state from json ".into())
-            .map(TrustedState)
     }
 }
 
+/// Evidence of a light client attack: two validly signed headers at the same height
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConflictingHeadersEvidence {
+    /// Header verified from the primary
+    #[serde(rename = "H1")]
+    pub h1: SignedHeader,
+    /// Conflicting header reported by a witness
+    #[serde(rename = "H2")]
+    pub h2: SignedHeader,
+}
+
+/// Result of cross-checking a header verified from the primary against a witness
+#[derive(Debug)]
+pub enum CrossCheck {
+    /// Witness reports the same header
+    Agree,
+    /// Witness reports a header which is not signed by the trusted validators, so the witness
+    /// itself is faulty
+    FaultyWitness(String),
+    /// Witness reports a different header signed by the trusted validators
+    Conflict(Box<ConflictingHeadersEvidence>),
+}
+
+/// Cross-check a header verified from the primary (and its validator set) against the header
+/// reported by a witness at the same height
+pub fn cross_check(
+    primary: &SignedHeader,
+    primary_validators: &validator::Set,
+    witness: &SignedHeader,
+) -> CrossCheck {
+    if primary.header.hash() == witness.header.hash() {
+        return CrossCheck::Agree;
+    }
+    if primary.header.height != witness.header.height {
+        return CrossCheck::FaultyWitness(format!(
+            "Witness reported height {} instead of {}",
+            witness.header.height, primary.header.height
+        ));
+    }
+    if witness.commit.block_id.hash != witness.header.hash() {
+        return CrossCheck::FaultyWitness("Witness commit is for another block".to_owned());
+    }
+
+    let trust_threshold =
+        lite::TrustThresholdFraction::new(1, 3).expect("1/3 is a valid trust threshold");
+    match lite::verifier::verify_commit_trusting(primary_validators, witness, trust_threshold) {
+        Ok(()) => CrossCheck::Conflict(Box::new(ConflictingHeadersEvidence {
+            h1: primary.clone(),
+            h2: witness.clone(),
+        })),
+        Err(e) => CrossCheck::FaultyWitness(format!(
+            "Witness header is not signed by trusted validators: {}",
+            e
+        )),
+    }
+}
+
+/// Submits light client attack evidence to a node
+pub trait EvidenceReporter: Send + Sync {
+    /// Broadcasts given evidence
+    fn report_evidence(&self, evidence: &ConflictingHeadersEvidence) -> CommonResult<()>;
+}
+
 /// get genesis validator set
 pub fn get_genesis_validators<C>(client: &C) -> CommonResult<validator::Set>
 where
@@ -45,3 +204,19 @@ where
 {
     Ok(validator::Set::new(client.genesis()?.validators))
 }
+
+/// Fetch the first block and its commit, and verify them against the given genesis
+/// (which should be pinned, e.g. by its fingerprint, rather than fetched from the same node)
+pub fn verify_first_block<C>(client: &C, genesis: &Genesis) -> CommonResult<TrustedState>
+where
+    C: Client,
+{
+    let header = client.block(1)?.header;
+    // the commit of a block is only included in the next one
+    let commit = client
+        .block(2)?
+        .last_commit
+        .ok_or_else(|| verify_error("Commit of first block is missing from the second block"))?;
+
+    TrustedState::genesis(genesis).verify_first_block(&SignedHeader { header, commit })
+}
@@ -1,6 +1,9 @@
 use std::{
     convert::TryFrom,
-    sync::{mpsc::sync_channel, Arc},
+    sync::{
+        mpsc::{channel, sync_channel, Receiver},
+        Arc,
+    },
     time::Duration,
 };
 
@@ -12,9 +15,13 @@ use tokio::runtime::Runtime;
 use chain_core::state::ChainState;
 use std::sync::Mutex;
 
-use super::async_rpc_client::AsyncRpcClient;
+use super::{async_rpc_client::AsyncRpcClient, websocket_rpc_loop};
 use crate::{
-    tendermint::{types::*, Client},
+    tendermint::{
+        lite::{ConflictingHeadersEvidence, EvidenceReporter},
+        types::*,
+        Client, NewBlockSubscriber,
+    },
     Error, ErrorKind, PrivateKey, Result, ResultExt, SignedTransaction, Transaction,
     TransactionObfuscation,
 };
@@ -249,7 +256,14 @@ impl Client for SyncRpcClient {
         let rsp = self.call::<BroadcastTxResponse>("broadcast_tx_sync", params)?;
 
         if rsp.code.is_err() {
-            Err(Error::new(ErrorKind::TendermintRpcError, rsp.log.as_ref()))
+            match rsp.rejection() {
+                Some(rejection) => Err(Error::new_with_source(
+                    ErrorKind::TransactionRejected,
+                    rsp.log.as_ref(),
+                    Box::new(rejection),
+                )),
+                None => Err(Error::new(ErrorKind::TendermintRpcError, rsp.log.as_ref())),
+            }
         } else {
             Ok(rsp)
         }
@@ -319,6 +333,33 @@ impl Client for SyncRpcClient {
     }
 }
 
+impl NewBlockSubscriber for SyncRpcClient {
+    fn subscribe_new_block(&self) -> Result<Receiver<u64>> {
+        let (sender, receiver) = channel();
+        let url = self.url.clone();
+
+        self.runtime.lock().unwrap().spawn(async move {
+            if let Err(e) = websocket_rpc_loop::new_block_loop(&url, sender).await {
+                log::warn!("New block subscription terminated: {:?}", e);
+            }
+        });
+
+        Ok(receiver)
+    }
+}
+
+impl EvidenceReporter for SyncRpcClient {
+    /// Makes `broadcast_evidence` call to tendermint
+    fn report_evidence(&self, evidence: &ConflictingHeadersEvidence) -> Result<()> {
+        let params = vec![json!({
+            "type": "tendermint/ConflictingHeadersEvidence",
+            "value": evidence,
+        })];
+        self.call::<Value>("broadcast_evidence", params)?;
+        Ok(())
+    }
+}
+
 impl Drop for SyncRpcClient {
     fn drop(&mut self) {
         if Arc::strong_count(&self.runtime) == 1 {
@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use futures_util::{sink::SinkExt, stream::StreamExt};
+use serde_json::{json, Value};
 use tokio::{
     sync::{oneshot::Sender, Mutex},
     task::JoinHandle,
@@ -12,11 +13,13 @@ use tokio_tungstenite::{connect_async, tungstenite::Message};
 
 use super::{
     async_rpc_client::{WebSocketReader, WebSocketWriter},
-    types::{ConnectionState, JsonRpcResponse},
+    types::{ConnectionState, JsonRpcRequest, JsonRpcResponse},
 };
 
 const MONITOR_RETRY_INTERVAL: Duration = Duration::from_secs(2);
 
+const NEW_BLOCK_QUERY: &str = "tm.event='NewBlock'";
+
 /// Creates a new websocket connection with given url
 pub async fn new_connection(url: &str) -> Result<(WebSocketWriter, WebSocketReader)> {
     let (websocket_stream, _) = connect_async(url).await.with_context(|| {
@@ -133,6 +136,70 @@ pub fn monitor(
     connection_state
 }
 
+/// Subscribes to `NewBlock` events on a dedicated websocket connection
+///
+/// # How it works
+///
+/// - Connects to websocket server at given `url` and sends a `subscribe` request for `NewBlock` events.
+/// - For each event received, sends the height of the new block to `sender`.
+/// - Returns when the connection is closed or when the receiving half of `sender` is dropped.
+pub async fn new_block_loop(url: &str, sender: std::sync::mpsc::Sender<u64>) -> Result<()> {
+    let (mut websocket_writer, mut websocket_reader) = new_connection(url).await?;
+
+    let params = [json!(NEW_BLOCK_QUERY)];
+    let request = JsonRpcRequest {
+        id: "new_block",
+        jsonrpc: "2.0",
+        method: "subscribe",
+        params: &params,
+    };
+    let request_json =
+        serde_json::to_string(&request).context("Unable to serialize RPC request to JSON")?;
+    websocket_writer
+        .send(Message::Text(request_json))
+        .await
+        .context("Unable to send subscribe request to websocket writer")?;
+
+    while let Some(message) = websocket_reader.next().await {
+        let response = match message.context("Websocket error message")? {
+            Message::Text(ref message) => parse_text(message)?,
+            Message::Binary(ref message) => parse_slice(message)?,
+            Message::Ping(data) => {
+                let _ = websocket_writer.send(Message::Pong(data)).await;
+                continue;
+            }
+            Message::Close(_) => break,
+            message => {
+                log::trace!("Received unknown message: {:?}", message);
+                continue;
+            }
+        };
+
+        if let Some(err) = response.error {
+            bail!(err);
+        }
+
+        // the first response only acknowledges the subscription and carries no block
+        if let Some(height) = response.result.as_ref().and_then(parse_new_block_height) {
+            if sender.send(height).is_err() {
+                log::debug!("New block receiver dropped, closing subscription");
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Extracts block height from the result of a `NewBlock` event
+fn parse_new_block_height(result: &Value) -> Option<u64> {
+    result
+        .pointer("/data/value/block/header/height")?
+        .as_str()?
+        .parse()
+        .ok()
+}
+
 /// Deserializes message from websocket into `JsonRpcResponse`
 fn parse_text(message: &str) -> Result<JsonRpcResponse> {
     serde_json::from_str(&message)
@@ -0,0 +1,12 @@
+use std::sync::mpsc::Receiver;
+
+use crate::Result;
+
+/// Subscribes to `NewBlock` events emitted by tendermint
+pub trait NewBlockSubscriber: Send + Sync + Clone {
+    /// Opens a new subscription and returns a receiver of new block heights.
+    ///
+    /// The sending half is dropped when the underlying connection is lost, so a disconnect is
+    /// observed as a closed channel and the caller is expected to subscribe again.
+    fn subscribe_new_block(&self) -> Result<Receiver<u64>>;
+}
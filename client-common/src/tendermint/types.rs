@@ -9,9 +9,10 @@ use crate::{ErrorKind, Result, ResultExt, Transaction};
 use chain_core::init::config::InitConfig;
 use chain_core::tx::data::TxId;
 use chain_core::tx::fee::LinearFee;
+use chain_core::tx::rejection::{RejectionCode, TxRejection, REJECTION_CODESPACE};
 use chain_core::tx::{TxAux, TxEnclaveAux, TxPublicAux};
 
-pub use self::block_results::BlockResults;
+pub use self::block_results::{BlockResults, StakingAdjustment, StakingAdjustmentKind};
 pub use tendermint::{
     abci, abci::transaction::Data, abci::Code, block::Header, block::Height, Block,
     Genesis as GenericGenesis, Hash, Time,
@@ -19,13 +20,30 @@ pub use tendermint::{
 pub use tendermint_rpc::endpoint::{
     abci_query::AbciQuery, abci_query::Response as AbciQueryResponse,
     block::Response as BlockResponse, block_results::Response as BlockResultsResponse, broadcast,
-    broadcast::tx_sync::Response as BroadcastTxResponse, commit::Response as CommitResponse,
-    status, status::Response as StatusResponse, validators::Response as ValidatorsResponse,
+    commit::Response as CommitResponse, status, status::Response as StatusResponse,
+    validators::Response as ValidatorsResponse,
 };
 
 /// crypto-com instantiated genesis type
 pub type Genesis = GenericGenesis<Option<InitConfig>>;
 
+/// `broadcast_tx_sync` response
+/// (`tendermint_rpc`'s one drops the codespace the rejection codes are scoped to)
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BroadcastTxResponse {
+    /// ABCI response code
+    pub code: Code,
+    /// ABCI response data
+    pub data: Data,
+    /// ABCI response log
+    pub log: abci::Log,
+    /// transaction hash
+    pub hash: abci::transaction::Hash,
+    /// codespace of the response code (empty for ABCI applications that don't set it)
+    #[serde(default)]
+    pub codespace: String,
+}
+
 /// crypto-com instantiated genesis type
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct GenesisResponse {
@@ -94,6 +112,25 @@ impl BlockExt for Block {
     }
 }
 
+/// crypto-chain specific methods.
+pub trait BroadcastTxResponseExt {
+    /// Returns the reason and details of the rejection (if the transaction was rejected)
+    fn rejection(&self) -> Option<TxRejection>;
+}
+
+impl BroadcastTxResponseExt for BroadcastTxResponse {
+    fn rejection(&self) -> Option<TxRejection> {
+        // codes from other codespaces (e.g. tendermint's own) aren't rejection codes
+        if self.code.is_ok() || self.codespace != REJECTION_CODESPACE {
+            return None;
+        }
+        // nodes without structured rejections only send the code (if any known)
+        TxRejection::decode(&mut self.data.value().as_slice())
+            .ok()
+            .or_else(|| RejectionCode::from_code(self.code.value()).map(TxRejection::new))
+    }
+}
+
 /// crypto-chain specific methods.
 pub trait GenesisExt {
     /// get fee policy
@@ -127,3 +164,35 @@ impl AbciQueryExt for AbciQuery {
         self.value.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn broadcast_response(code: u32, codespace: &str) -> BroadcastTxResponse {
+        serde_json::from_value(serde_json::json!({
+            "code": code,
+            "data": "",
+            "log": "rejected",
+            "hash": "0000000000000000000000000000000000000000000000000000000000000000",
+            "codespace": codespace,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn check_rejection_is_only_decoded_in_chain_codespace() {
+        let code = RejectionCode::IncorrectNonce.code();
+        assert_eq!(
+            Some(RejectionCode::IncorrectNonce),
+            broadcast_response(code, REJECTION_CODESPACE)
+                .rejection()
+                .map(|rejection| rejection.code)
+        );
+        assert!(broadcast_response(code, "sdk").rejection().is_none());
+        assert!(broadcast_response(code, "").rejection().is_none());
+        assert!(broadcast_response(0, REJECTION_CODESPACE)
+            .rejection()
+            .is_none());
+    }
+}
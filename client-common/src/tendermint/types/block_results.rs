@@ -1,5 +1,6 @@
 #![allow(missing_docs)]
 use indexmap::IndexMap;
+use serde::Deserialize;
 use std::convert::TryFrom;
 use std::str::{from_utf8, FromStr};
 
@@ -31,6 +32,37 @@ pub trait BlockResults {
 
     /// Returns block filter in block results
     fn block_filter(&self) -> Result<BlockFilter>;
+
+    /// Returns the rewards and slashes of staking addresses, which are reported in begin block
+    /// events instead of transaction events
+    fn staking_adjustments(&self) -> Result<Vec<StakingAdjustment>>;
+}
+
+/// Kind of a staking balance change which is not caused by a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StakingAdjustmentKind {
+    /// Reward distributed to the bonded balance
+    Reward,
+    /// Slash of the bonded and unbonded balances
+    Slash,
+}
+
+/// Reward or slash of a staking address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StakingAdjustment {
+    /// Rewarded or slashed staking address
+    pub staking_address: StakedStateAddress,
+    /// Reward or slash
+    pub kind: StakingAdjustmentKind,
+    /// Total amount rewarded or slashed
+    pub amount: Coin,
+}
+
+/// Entry of the `staking_diff` attribute of staking change events
+#[derive(Deserialize)]
+struct StakingDiffEntry {
+    key: String,
+    value: serde_json::Value,
 }
 
 impl BlockResults for BlockResultsResponse {
@@ -122,6 +154,39 @@ impl BlockResults for BlockResultsResponse {
             }
         }
     }
+
+    fn staking_adjustments(&self) -> Result<Vec<StakingAdjustment>> {
+        let mut adjustments = Vec::new();
+        let events = match &self.begin_block_events {
+            None => return Ok(adjustments),
+            Some(events) => events,
+        };
+        for event in events.iter() {
+            if event.type_str != TendermintEventType::StakingChange.to_string() {
+                continue;
+            }
+            let kind =
+                match find_staking_op_type_from_event_attributes(&event.attributes)?.as_deref() {
+                    Some("reward") => StakingAdjustmentKind::Reward,
+                    Some("slash") => StakingAdjustmentKind::Slash,
+                    _ => continue,
+                };
+            let staking_address = find_staking_address_from_event_attributes(&event.attributes)?
+                .chain(|| {
+                    (
+                        ErrorKind::DeserializationError,
+                        "Staking address is missing in staking change event",
+                    )
+                })?;
+            let amount = find_staking_diff_amount_from_event_attributes(&event.attributes)?;
+            adjustments.push(StakingAdjustment {
+                staking_address,
+                kind,
+                amount,
+            });
+        }
+        Ok(adjustments)
+    }
 }
 
 fn find_event_attribute_by_key(
@@ -237,6 +302,80 @@ fn find_staking_address_from_event_attributes(
     }
 }
 
+fn find_staking_op_type_from_event_attributes(attributes: &[Attribute]) -> Result<Option<String>> {
+    let maybe_attribute =
+        find_event_attribute_by_key(attributes, TendermintEventKey::StakingOpType)?;
+    match maybe_attribute {
+        None => Ok(None),
+        Some(attribute) => {
+            let op_type = base64::decode(attribute.value.as_ref()).chain(|| {
+                (
+                    ErrorKind::DeserializationError,
+                    "Unable to decode base64 bytes of staking operation type in block results",
+                )
+            })?;
+            let op_type = String::from_utf8(op_type).chain(|| {
+                (
+                    ErrorKind::DeserializationError,
+                    "Unable to decode string of staking operation type in block results",
+                )
+            })?;
+
+            Ok(Some(op_type))
+        }
+    }
+}
+
+/// Sums up the bonded and unbonded changes of a `staking_diff` attribute (e.g.
+/// `[{"key":"Bonded","value":"-10"},{"key":"Unbonded","value":"-5"}]` is 15)
+fn find_staking_diff_amount_from_event_attributes(attributes: &[Attribute]) -> Result<Coin> {
+    let attribute = find_event_attribute_by_key(attributes, TendermintEventKey::StakingDiff)?
+        .chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Staking diff is missing in staking change event",
+            )
+        })?;
+    let staking_diff = base64::decode(attribute.value.as_ref()).chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            "Unable to decode base64 bytes of staking diff in block results",
+        )
+    })?;
+    let entries: Vec<StakingDiffEntry> = serde_json::from_slice(&staking_diff).chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            "Unable to deserialize staking diff in block results",
+        )
+    })?;
+
+    let mut amount = Coin::zero();
+    for entry in entries {
+        if entry.key != "Bonded" && entry.key != "Unbonded" {
+            continue;
+        }
+        let value = entry
+            .value
+            .as_str()
+            .and_then(|value| value.trim_start_matches('-').parse::<u64>().ok())
+            .chain(|| {
+                (
+                    ErrorKind::DeserializationError,
+                    "Invalid coin amount in staking diff",
+                )
+            })?;
+        amount = Coin::new(value)
+            .and_then(|value| amount + value)
+            .chain(|| {
+                (
+                    ErrorKind::DeserializationError,
+                    "Invalid coin amount in staking diff",
+                )
+            })?;
+    }
+    Ok(amount)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -344,6 +483,32 @@ mod tests {
         assert!(block_results.fees().is_err());
     }
 
+    #[test]
+    fn check_staking_adjustments() {
+        let response_str = r#"{"height": "40", "txs_results": null, "begin_block_events": [{"type": "staking_change", "attributes": [{"key": "c3Rha2luZ19hZGRyZXNz", "value": "MHgzMzUwMmVkMzlkMGM0ZTIwNDRmYjM3ZmRjZDUxNjE0OTNmNTkwMGMz"}, {"key": "c3Rha2luZ19vcHR5cGU=", "value": "cmV3YXJk"}, {"key": "c3Rha2luZ19kaWZm", "value": "W3sia2V5IjoiQm9uZGVkIiwidmFsdWUiOiIzMCJ9XQ=="}]}, {"type": "staking_change", "attributes": [{"key": "c3Rha2luZ19hZGRyZXNz", "value": "MHgzMzUwMmVkMzlkMGM0ZTIwNDRmYjM3ZmRjZDUxNjE0OTNmNTkwMGMz"}, {"key": "c3Rha2luZ19vcHR5cGU=", "value": "ZGVwb3NpdA=="}, {"key": "c3Rha2luZ19kaWZm", "value": "W3sia2V5IjoiQm9uZGVkIiwidmFsdWUiOiIzMCJ9XQ=="}]}, {"type": "staking_change", "attributes": [{"key": "c3Rha2luZ19hZGRyZXNz", "value": "MHgzMzUwMmVkMzlkMGM0ZTIwNDRmYjM3ZmRjZDUxNjE0OTNmNTkwMGMz"}, {"key": "c3Rha2luZ19vcHR5cGU=", "value": "c2xhc2g="}, {"key": "c3Rha2luZ19kaWZm", "value": "W3sia2V5IjoiQm9uZGVkIiwidmFsdWUiOiItMTAifSx7ImtleSI6IlVuYm9uZGVkIiwidmFsdWUiOiItNSJ9XQ=="}]}], "end_block_events": null, "validator_updates": null, "consensus_param_updates": null}"#;
+        let block_results: BlockResultsResponse =
+            serde_json::from_str(response_str).expect("invalid response str");
+        let staking_address = StakedStateAddress::from(
+            RedeemAddress::from_str("0x33502ed39d0c4e2044fb37fdcd5161493f5900c3").unwrap(),
+        );
+
+        assert_eq!(
+            vec![
+                StakingAdjustment {
+                    staking_address,
+                    kind: StakingAdjustmentKind::Reward,
+                    amount: Coin::new(30).unwrap(),
+                },
+                StakingAdjustment {
+                    staking_address,
+                    kind: StakingAdjustmentKind::Slash,
+                    amount: Coin::new(15).unwrap(),
+                },
+            ],
+            block_results.staking_adjustments().unwrap()
+        );
+    }
+
     #[test]
     fn check_null_deliver_tx() {
         let block_results = BlockResultsResponse {
@@ -1,8 +1,10 @@
 //! MultiSig operations support
 mod builder;
+mod package;
 mod session;
 mod signer;
 
 pub use builder::MultiSigBuilder;
+pub use package::{SigningPackage, SIGNING_PACKAGE_VERSION};
 pub use session::MultiSigSession;
-use signer::Signer;
+pub use signer::Signer;
@@ -2,9 +2,9 @@ use parity_scale_codec::{Decode, Encode};
 use secp256k1::schnorrsig::SchnorrSignature;
 
 use chain_core::common::H256;
-use client_common::{ErrorKind, PrivateKey, PublicKey, Result, ResultExt};
+use client_common::{Error, ErrorKind, PrivateKey, PublicKey, Result, ResultExt};
 
-use super::MultiSigSession;
+use super::{MultiSigSession, Signer};
 
 /// MultiSig session builder tailored for Crypto.com chain flow
 ///
@@ -137,6 +137,82 @@ impl MultiSigBuilder {
         self.session.public_keys()
     }
 
+    /// Returns the message to be signed
+    pub fn message(&self) -> H256 {
+        self.session.message
+    }
+
+    /// Returns the round data of all signers in this session, sorted by public key
+    pub fn signers(&self) -> &[Signer] {
+        &self.session.signers
+    }
+
+    /// Adds the round data received from co-signers which is not in the session yet.
+    ///
+    /// This function will fail if received round data conflicts with the one already added.
+    pub fn merge(&mut self, signers: &[Signer]) -> Result<()> {
+        for received in signers {
+            let current = self
+                .signers()
+                .iter()
+                .find(|signer| signer.public_key == received.public_key)
+                .cloned()
+                .chain(|| {
+                    (
+                        ErrorKind::InvalidInput,
+                        format!("Signer with public key ({}) not found", received.public_key),
+                    )
+                })?;
+            let public_key = &received.public_key;
+
+            if let Some(nonce_commitment) = missing_round_data(
+                current.nonce_commitment,
+                received.nonce_commitment,
+                "nonce commitment",
+            )? {
+                self.add_nonce_commitment(public_key, nonce_commitment)?;
+            }
+            if let Some(nonce) = missing_round_data(current.nonce, received.nonce, "nonce")? {
+                self.add_nonce(public_key, &nonce)?;
+            }
+            if let Some(partial_signature) = missing_round_data(
+                current.partial_signature,
+                received.partial_signature,
+                "partial signature",
+            )? {
+                self.add_partial_signature(public_key, partial_signature)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds all the round data of current signer which can be computed with the round data
+    /// received so far: nonce commitment, then nonce once all nonce commitments are received,
+    /// then partial signature once all nonces are received.
+    pub fn contribute(&mut self) -> Result<()> {
+        self.nonce_commitment()?;
+        if self
+            .signers()
+            .iter()
+            .all(|signer| signer.nonce_commitment.is_some())
+        {
+            self.nonce()?;
+        }
+        if self.signers().iter().all(|signer| signer.nonce.is_some()) {
+            self.partial_signature()?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if partial signatures from all signers are received
+    pub fn is_complete(&self) -> bool {
+        self.signers()
+            .iter()
+            .all(|signer| signer.partial_signature.is_some())
+    }
+
     /// Returns true if nonce commitment for given public key is already set,
     /// false otherwise.
     pub fn has_nonce_commitment(&self, public_key: &PublicKey) -> Result<bool> {
@@ -179,6 +255,23 @@ impl MultiSigBuilder {
     }
 }
 
+/// Returns received round data if it is not in the session yet, fails if it conflicts with the
+/// round data in the session
+fn missing_round_data(
+    current: Option<H256>,
+    received: Option<H256>,
+    kind: &str,
+) -> Result<Option<H256>> {
+    match (current, received) {
+        (None, received) => Ok(received),
+        (Some(current), Some(received)) if current != received => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Received {} conflicts with the one in session", kind),
+        )),
+        (Some(_), _) => Ok(None),
+    }
+}
+
 #[cfg(test)]
 mod multi_sig_builder_tests {
     use super::*;
@@ -0,0 +1,158 @@
+use parity_scale_codec::{Decode, Encode};
+
+use chain_core::common::Proof;
+use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::output::TxOut;
+use chain_core::tx::data::{Tx, TxId};
+use chain_core::tx::witness::tree::RawXOnlyPubkey;
+use client_common::multi_sig_address::combine_to_raw_pubkey;
+use client_common::{Error, ErrorKind, PublicKey, Result, ResultExt};
+
+use super::Signer;
+
+/// Current version of signing package format
+pub const SIGNING_PACKAGE_VERSION: u8 = 1;
+
+/// Self-describing bundle exchanged between co-signers of a multi-sig transaction, so that the
+/// whole signing flow can be completed offline (e.g. across air-gapped machines)
+///
+/// It contains the unsigned transaction, the proof that the combined public key of the signers
+/// is part of the spent MultiSig address and the round data collected so far from every signer.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct SigningPackage {
+    /// Version of package format
+    pub version: u8,
+    /// Transaction to be signed
+    pub unsigned_transaction: Tx,
+    /// Output spent by the transaction
+    pub spent_output: TxOut,
+    /// Inclusion proof of combined public key of signers in the spent MultiSig address
+    pub proof: Proof<RawXOnlyPubkey>,
+    /// Round data of all the signers, sorted by public key of signer
+    pub signers: Vec<Signer>,
+}
+
+impl SigningPackage {
+    /// Creates a new signing package without any round data
+    pub fn new(
+        unsigned_transaction: Tx,
+        spent_output: TxOut,
+        proof: Proof<RawXOnlyPubkey>,
+        mut signer_public_keys: Vec<PublicKey>,
+    ) -> Result<Self> {
+        signer_public_keys.sort();
+        signer_public_keys.dedup();
+
+        let package = SigningPackage {
+            version: SIGNING_PACKAGE_VERSION,
+            unsigned_transaction,
+            spent_output,
+            proof,
+            signers: signer_public_keys
+                .into_iter()
+                .map(|public_key| Signer {
+                    public_key,
+                    nonce_commitment: None,
+                    nonce: None,
+                    partial_signature: None,
+                })
+                .collect(),
+        };
+        package.verify()?;
+
+        Ok(package)
+    }
+
+    /// Returns the id of transaction to be signed, which identifies the package
+    pub fn id(&self) -> TxId {
+        self.unsigned_transaction.id()
+    }
+
+    /// Returns public keys of all the signers
+    pub fn public_keys(&self) -> Vec<PublicKey> {
+        self.signers
+            .iter()
+            .map(|signer| signer.public_key.clone())
+            .collect()
+    }
+
+    /// Checks that the package is well-formed and that its signers are able to spend the output
+    pub fn verify(&self) -> Result<()> {
+        if self.version != SIGNING_PACKAGE_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unsupported signing package version: {}", self.version),
+            ));
+        }
+
+        if self.unsigned_transaction.inputs.len() != 1 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Multi-Sig Signing is only supported for transactions with only one input",
+            ));
+        }
+
+        if self.signers.len() <= 1 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Signing package should have at least 2 signers",
+            ));
+        }
+
+        let public_keys = self.public_keys();
+        if public_keys.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Signers of signing package should be unique and sorted by public key",
+            ));
+        }
+
+        let root_hash = match self.spent_output.address {
+            ExtendedAddr::OrTree(root_hash) => root_hash,
+            ExtendedAddr::HashTimeLock(_) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Hash-time-locked output cannot be spent with a signing package",
+                ))
+            }
+        };
+        if !self.proof.verify(&root_hash) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Proof of signing package does not belong to the spent MultiSig address",
+            ));
+        }
+        if *self.proof.value() != combine_to_raw_pubkey(&public_keys)? {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Proof of signing package is not for the public keys of its signers",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Encodes the package into base64 string
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.encode())
+    }
+
+    /// Decodes and verifies a package from base64 string
+    pub fn from_base64(package: &str) -> Result<Self> {
+        let bytes = base64::decode(package.trim()).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to decode base64 signing package",
+            )
+        })?;
+        let package = SigningPackage::decode(&mut bytes.as_slice()).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to deserialize signing package",
+            )
+        })?;
+        package.verify()?;
+
+        Ok(package)
+    }
+}
@@ -4,7 +4,7 @@ use chain_core::common::H256;
 use client_common::{Error, ErrorKind, PublicKey, Result};
 
 /// Individual MultiSig signer data
-#[derive(Debug, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
 pub struct Signer {
     /// Public key of signer
     pub public_key: PublicKey,
@@ -1,11 +1,16 @@
 //! Management services
+mod address_book_service;
+mod backup_service;
 mod hd_key_service;
 mod hw_key_service;
 mod key_service;
 mod ledger_service;
+mod migration_service;
 #[cfg(feature = "experimental")]
 mod multi_sig_session_service;
+mod passphrase_service;
 mod root_hash_service;
+mod staking_history_service;
 mod sync_state_service;
 mod wallet_service;
 mod wallet_state_service;
@@ -13,15 +18,22 @@ mod wallet_state_service;
 #[doc(hidden)]
 pub use self::wallet_state_service::WalletStateMemento;
 
+pub use self::address_book_service::AddressBookService;
+pub use self::backup_service::{BackupService, WalletBackup, WALLET_BACKUP_VERSION};
 pub use self::hd_key_service::{HDAccountType, HdKey, HdKeyService};
 pub use self::hw_key_service::{HwKeyService, UnauthorizedHwKeyService};
 pub use self::key_service::KeyService;
 pub use self::ledger_service::{
     LedgerServiceHID, LedgerServiceZemu, LedgerSignKeyHID, LedgerSignKeyZemu,
 };
+pub use self::migration_service::{open_sled_storage, MigrationService, STORAGE_SCHEMA_VERSION};
 #[cfg(feature = "experimental")]
 pub use self::multi_sig_session_service::MultiSigSessionService;
+pub use self::passphrase_service::PassphraseService;
 pub use self::root_hash_service::RootHashService;
+pub use self::staking_history_service::{
+    add_staking_history, delete_staking_history, StakingHistoryService,
+};
 pub use self::sync_state_service::{
     delete_sync_state, load_sync_state, save_sync_state, SyncState, SyncStateService,
 };
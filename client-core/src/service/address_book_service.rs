@@ -0,0 +1,290 @@
+use parity_scale_codec::{Decode, Encode};
+
+use chain_core::tx::data::TxId;
+use client_common::{Error, ErrorKind, Result, ResultExt, SecKey, SecureStorage, Storage};
+
+use crate::types::address_book::check_label;
+use crate::types::{AddressBook, BookAddress, TransactionChange, TransactionLabel};
+
+/// key space of address books
+pub(crate) const KEYSPACE: &str = "core_address_book";
+
+/// Maintains mapping `wallet-name -> address-book` (encrypted with the enckey of the wallet)
+#[derive(Debug, Default, Clone)]
+pub struct AddressBookService<S>
+where
+    S: Storage,
+{
+    storage: S,
+}
+
+impl<S> AddressBookService<S>
+where
+    S: Storage,
+{
+    /// Creates a new instance of address book service
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    /// Returns the address book of given wallet (empty if nothing was labelled yet)
+    pub fn get(&self, name: &str, enckey: &SecKey) -> Result<AddressBook> {
+        Ok(self
+            .storage
+            .load_secure(KEYSPACE, name, enckey)?
+            .unwrap_or_default())
+    }
+
+    /// Labels an address, replacing the previous address of the label
+    pub fn set_address_label(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        label: &str,
+        address: &BookAddress,
+    ) -> Result<()> {
+        check_label(label)?;
+
+        self.modify(name, enckey, |book| {
+            if let Some(other) = book.label_of(address) {
+                if other != label {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Address {} is already labelled as {}", address, other),
+                    ));
+                }
+            }
+            book.contacts.insert(label.to_owned(), address.clone());
+            Ok(())
+        })
+    }
+
+    /// Removes a label from the address book
+    pub fn remove_address_label(&self, name: &str, enckey: &SecKey, label: &str) -> Result<()> {
+        self.modify(name, enckey, |book| {
+            book.contacts.remove(label).map(|_| ()).chain(|| {
+                (
+                    ErrorKind::InvalidInput,
+                    format!("Label {} not found in address book", label),
+                )
+            })
+        })
+    }
+
+    /// Labels a transaction, replacing its previous label
+    pub fn set_transaction_label(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        transaction_id: TxId,
+        label: &TransactionLabel,
+    ) -> Result<()> {
+        if label.label.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Transaction label cannot be empty",
+            ));
+        }
+
+        self.modify(name, enckey, |book| {
+            book.transaction_labels
+                .insert(transaction_id, label.clone());
+            Ok(())
+        })
+    }
+
+    /// Removes the label of a transaction
+    pub fn remove_transaction_label(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        transaction_id: &TxId,
+    ) -> Result<()> {
+        self.modify(name, enckey, |book| {
+            book.transaction_labels
+                .remove(transaction_id)
+                .map(|_| ())
+                .chain(|| {
+                    (
+                        ErrorKind::InvalidInput,
+                        format!("Transaction {} has no label", hex::encode(transaction_id)),
+                    )
+                })
+        })
+    }
+
+    /// Attaches the labels of the address book to transaction changes
+    pub fn label_transaction_changes(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        changes: &mut [TransactionChange],
+    ) -> Result<()> {
+        let book = self.get(name, enckey)?;
+        if book.contacts.is_empty() && book.transaction_labels.is_empty() {
+            return Ok(());
+        }
+
+        for change in changes.iter_mut() {
+            change.label = book.transaction_labels.get(&change.transaction_id).cloned();
+
+            let addresses = change
+                .inputs
+                .iter()
+                .filter_map(|input| input.output.as_ref())
+                .chain(change.outputs.iter())
+                .map(|output| BookAddress::Transfer(output.address.clone()));
+            for address in addresses {
+                if let Some(label) = book.label_of(&address) {
+                    change
+                        .address_labels
+                        .insert(address.to_string(), label.to_owned());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes the address book of given wallet
+    #[inline]
+    pub fn delete_wallet(&self, name: &str) -> Result<()> {
+        self.storage.delete(KEYSPACE, name).map(|_| ())
+    }
+
+    fn modify<F>(&self, name: &str, enckey: &SecKey, f: F) -> Result<()>
+    where
+        F: Fn(&mut AddressBook) -> Result<()>,
+    {
+        self.storage
+            .fetch_and_update_secure(KEYSPACE, name, enckey, |bytes_optional| {
+                let mut book = match bytes_optional {
+                    Some(mut bytes) => AddressBook::decode(&mut bytes).chain(|| {
+                        (
+                            ErrorKind::DeserializationError,
+                            format!("Unable to deserialize address book of wallet {}", name),
+                        )
+                    })?,
+                    None => AddressBook::default(),
+                };
+                f(&mut book)?;
+                Ok(Some(book.encode()))
+            })
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::str::FromStr;
+
+    use secstr::SecUtf8;
+
+    use chain_core::init::coin::Coin;
+    use chain_core::state::account::StakedStateAddress;
+    use chain_core::tx::data::address::ExtendedAddr;
+    use chain_core::tx::data::output::TxOut;
+    use chain_core::tx::fee::Fee;
+    use client_common::seckey::derive_enckey;
+    use client_common::storage::MemoryStorage;
+    use client_common::tendermint::types::Time;
+
+    use crate::types::{BalanceChange, TransactionType};
+
+    #[test]
+    fn check_flow() {
+        let service = AddressBookService::new(MemoryStorage::default());
+        let name = "name";
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), name).unwrap();
+        let wrong_enckey = derive_enckey(&SecUtf8::from("wrong passphrase"), name).unwrap();
+
+        let alice = BookAddress::Transfer(ExtendedAddr::OrTree([1; 32]));
+        let bob = BookAddress::Staking(
+            StakedStateAddress::from_str("0x0e7c045110b8dbf29765047380898919c5cb56f4").unwrap(),
+        );
+
+        assert_eq!(AddressBook::default(), service.get(name, &enckey).unwrap());
+        service
+            .set_address_label(name, &enckey, "alice", &alice)
+            .unwrap();
+        service
+            .set_address_label(name, &enckey, "bob", &bob)
+            .unwrap();
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            service
+                .set_address_label(name, &enckey, "@carol", &alice)
+                .unwrap_err()
+                .kind()
+        );
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            service
+                .set_address_label(name, &enckey, "carol", &alice)
+                .unwrap_err()
+                .kind()
+        );
+        assert_eq!(
+            ErrorKind::DecryptionError,
+            service.get(name, &wrong_enckey).unwrap_err().kind()
+        );
+
+        let book = service.get(name, &enckey).unwrap();
+        assert_eq!(&alice, book.resolve("@alice").unwrap());
+        assert_eq!(&bob, book.resolve("bob").unwrap());
+        assert!(book.resolve("@carol").is_err());
+        assert_eq!(Some("alice"), book.label_of(&alice));
+
+        let label = TransactionLabel {
+            label: "rent".to_owned(),
+            note: Some("March".to_owned()),
+        };
+        service
+            .set_transaction_label(name, &enckey, [2; 32], &label)
+            .unwrap();
+
+        let mut changes = vec![TransactionChange {
+            transaction_id: [2; 32],
+            inputs: Vec::new(),
+            outputs: vec![TxOut::new(
+                ExtendedAddr::OrTree([1; 32]),
+                Coin::new(10).unwrap(),
+            )],
+            fee_paid: Fee::new(Coin::zero()),
+            balance_change: BalanceChange::Outgoing {
+                value: Coin::new(10).unwrap(),
+            },
+            transaction_type: TransactionType::Transfer,
+            block_height: 1,
+            block_time: Time::now(),
+            label: None,
+            address_labels: Default::default(),
+        }];
+        service
+            .label_transaction_changes(name, &enckey, &mut changes)
+            .unwrap();
+        assert_eq!(Some(label), changes[0].label);
+        assert_eq!(
+            Some(&"alice".to_owned()),
+            changes[0].address_labels.get(&alice.to_string())
+        );
+
+        service
+            .remove_transaction_label(name, &enckey, &[2; 32])
+            .unwrap();
+        service
+            .remove_address_label(name, &enckey, "alice")
+            .unwrap();
+        assert!(service
+            .remove_address_label(name, &enckey, "alice")
+            .is_err());
+        let book = service.get(name, &enckey).unwrap();
+        assert!(book.transaction_labels.is_empty());
+        assert_eq!(1, book.contacts.len());
+
+        service.delete_wallet(name).unwrap();
+        assert_eq!(AddressBook::default(), service.get(name, &enckey).unwrap());
+    }
+}
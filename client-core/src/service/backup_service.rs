@@ -0,0 +1,561 @@
+use parity_scale_codec::{Decode, Encode};
+use rand::rngs::OsRng;
+use rand::Rng;
+use secstr::SecUtf8;
+
+use client_common::seckey::derive_enckey_with_salt;
+use client_common::storage::{decrypt_bytes, encrypt_bytes, MemoryStorage, SCHEMA_KEYSPACE};
+use client_common::{Error, ErrorKind, Result, ResultExt, SecKey, Storage};
+
+use super::migration_service::{MigrationService, STORAGE_SCHEMA_VERSION};
+use super::wallet_service;
+use super::wallet_service::{
+    get_wallet_keyspace, get_wallet_keyspaces, shared_secure_keyspaces, wallet_name_keyspaces,
+};
+
+/// Current version of wallet backup format
+pub const WALLET_BACKUP_VERSION: u8 = 1;
+
+/// Header of a wallet backup, authenticated along with the encrypted entries
+#[derive(Debug, Clone, Encode, Decode)]
+struct BackupHeader {
+    version: u8,
+    schema_version: u32,
+    name: String,
+    salt: [u8; 32],
+}
+
+/// Raw (possibly already encrypted) value of a keyspace entry
+#[derive(Debug, Encode, Decode)]
+struct BackupEntry {
+    keyspace: Vec<u8>,
+    key: Vec<u8>,
+    value: Vec<u8>,
+}
+
+impl BackupEntry {
+    /// Returns `true` if the entry is in a keyspace shared by all the wallets
+    fn is_shared(&self) -> bool {
+        shared_secure_keyspaces()
+            .iter()
+            .any(|keyspace| keyspace.as_bytes() == self.keyspace.as_slice())
+    }
+
+    /// Returns `true` if the entry is part of the data of given wallet
+    fn belongs_to(&self, name: &str) -> bool {
+        let keyspace = String::from_utf8_lossy(&self.keyspace);
+
+        if get_wallet_keyspaces(name).contains(&keyspace.to_string())
+            || shared_secure_keyspaces().contains(&keyspace.to_string())
+        {
+            return true;
+        }
+        (keyspace == get_wallet_keyspace()
+            || wallet_name_keyspaces().contains(&keyspace.to_string()))
+            && self.key == name.as_bytes()
+    }
+}
+
+/// Passphrase-encrypted archive of all the data of a wallet
+///
+/// Values are archived as they are stored, so the secure ones stay encrypted with the enckey of
+/// the wallet, and the whole archive is encrypted with a key derived from the backup passphrase
+/// and a random salt. The header is used as associated data, so any modification of the
+/// archive is detected on restore.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct WalletBackup {
+    header: BackupHeader,
+    payload: Vec<u8>,
+}
+
+impl WalletBackup {
+    /// Returns the name of backed up wallet
+    pub fn name(&self) -> &str {
+        &self.header.name
+    }
+
+    /// Encodes the backup into base64 string
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.encode())
+    }
+
+    /// Decodes a backup from base64 string
+    pub fn from_base64(backup: &str) -> Result<Self> {
+        let bytes = base64::decode(backup.trim()).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to decode base64 wallet backup",
+            )
+        })?;
+        let backup = WalletBackup::decode(&mut bytes.as_slice()).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to deserialize wallet backup",
+            )
+        })?;
+
+        if backup.header.version != WALLET_BACKUP_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Unsupported wallet backup version: {}",
+                    backup.header.version
+                ),
+            ));
+        }
+
+        Ok(backup)
+    }
+
+    fn seal(
+        name: &str,
+        schema_version: u32,
+        entries: &[BackupEntry],
+        passphrase: &SecUtf8,
+    ) -> Result<Self> {
+        let mut salt = [0; 32];
+        OsRng.fill(&mut salt);
+
+        let header = BackupHeader {
+            version: WALLET_BACKUP_VERSION,
+            schema_version,
+            name: name.to_owned(),
+            salt,
+        };
+        let payload = encrypt_bytes(
+            header.encode(),
+            &backup_enckey(passphrase, &salt)?,
+            &entries.encode(),
+        )?;
+
+        Ok(WalletBackup { header, payload })
+    }
+
+    fn open(&self, passphrase: &SecUtf8) -> Result<Vec<BackupEntry>> {
+        let entries = decrypt_bytes(
+            self.header.encode(),
+            &backup_enckey(passphrase, &self.header.salt)?,
+            &self.payload,
+        )
+        .chain(|| {
+            (
+                ErrorKind::DecryptionError,
+                "Incorrect backup passphrase or corrupted wallet backup",
+            )
+        })?;
+
+        Vec::decode(&mut entries.as_slice()).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to deserialize wallet backup entries",
+            )
+        })
+    }
+}
+
+/// Upgrades the entries of a backup written with an older storage schema version, by applying
+/// the pending migrations to them in a temporary storage
+fn upgrade_entries(entries: Vec<BackupEntry>, schema_version: u32) -> Result<Vec<BackupEntry>> {
+    if schema_version > STORAGE_SCHEMA_VERSION {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Wallet backup of storage schema version {} is newer than supported version {}, please upgrade the client",
+                schema_version, STORAGE_SCHEMA_VERSION
+            ),
+        ));
+    }
+    if schema_version == STORAGE_SCHEMA_VERSION {
+        return Ok(entries);
+    }
+
+    let storage = MemoryStorage::default();
+    for entry in entries {
+        storage.set(entry.keyspace, entry.key, entry.value)?;
+    }
+    MigrationService::new(storage.clone()).upgrade(schema_version)?;
+
+    let mut entries = Vec::new();
+    for keyspace in storage.keyspaces()? {
+        if keyspace == SCHEMA_KEYSPACE.as_bytes() {
+            continue;
+        }
+        for key in storage.keys(&keyspace)? {
+            if let Some(value) = storage.get(&keyspace, &key)? {
+                entries.push(BackupEntry {
+                    keyspace: keyspace.clone(),
+                    key,
+                    value,
+                });
+            }
+        }
+    }
+    // the wallet is still registered last
+    entries.sort_by_key(|entry| entry.keyspace == get_wallet_keyspace().as_bytes());
+
+    Ok(entries)
+}
+
+fn backup_enckey(passphrase: &SecUtf8, salt: &[u8]) -> Result<SecKey> {
+    derive_enckey_with_salt(passphrase, salt).err_kind(ErrorKind::InvalidInput, || {
+        "unable to derive encryption key from backup passphrase"
+    })
+}
+
+/// Backs up and restores all the data of a wallet (keys, addresses, wallet and sync state,
+/// multi-sig sessions, ...) in a single encrypted archive
+#[derive(Debug, Default, Clone)]
+pub struct BackupService<S>
+where
+    S: Storage,
+{
+    storage: S,
+}
+
+impl<S> BackupService<S>
+where
+    S: Storage,
+{
+    /// Creates a new instance of backup service
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    /// Creates a backup of given wallet, encrypted with `backup_passphrase`
+    pub fn backup(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        backup_passphrase: &SecUtf8,
+    ) -> Result<WalletBackup> {
+        let wallet = self
+            .storage
+            .get(wallet_service::KEYSPACE, name)?
+            .chain(|| {
+                (
+                    ErrorKind::InvalidInput,
+                    format!("Wallet with name ({}) not found", name),
+                )
+            })?;
+        // the enckey is verified here.
+        decrypt_bytes(name, enckey, &wallet)?;
+
+        let mut entries = Vec::new();
+        for keyspace in get_wallet_keyspaces(name) {
+            for key in self.storage.keys(&keyspace)? {
+                self.push_entry(&mut entries, &keyspace, key)?;
+            }
+        }
+        for keyspace in wallet_name_keyspaces() {
+            self.push_entry(&mut entries, &keyspace, name.as_bytes().to_vec())?;
+        }
+        for keyspace in shared_secure_keyspaces() {
+            for key in self.storage.keys(&keyspace)? {
+                if let Some(value) = self.storage.get(&keyspace, &key)? {
+                    if decrypt_bytes(&key, enckey, &value).is_ok() {
+                        entries.push(BackupEntry {
+                            keyspace: keyspace.as_bytes().to_vec(),
+                            key,
+                            value,
+                        });
+                    }
+                }
+            }
+        }
+        // registered last, so that a partially restored wallet is not listed
+        self.push_entry(
+            &mut entries,
+            &get_wallet_keyspace(),
+            name.as_bytes().to_vec(),
+        )?;
+
+        WalletBackup::seal(name, STORAGE_SCHEMA_VERSION, &entries, backup_passphrase)
+    }
+
+    /// Restores a wallet from given backup, the wallet is then unlocked with its original
+    /// passphrase
+    pub fn restore(&self, backup: &WalletBackup, backup_passphrase: &SecUtf8) -> Result<()> {
+        let name = backup.name();
+        let entries = upgrade_entries(
+            backup.open(backup_passphrase)?,
+            backup.header.schema_version,
+        )?;
+
+        if let Some(entry) = entries.iter().find(|entry| !entry.belongs_to(name)) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Wallet backup of {} contains data of keyspace {} which does not belong to it",
+                    name,
+                    String::from_utf8_lossy(&entry.keyspace)
+                ),
+            ));
+        }
+        if self.storage.contains_key(get_wallet_keyspace(), name)? {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Wallet with name ({}) already exists", name),
+            ));
+        }
+
+        for entry in entries {
+            // multi-sig sessions have random ids, so an already stored one is left by a previous
+            // restore of this wallet and may have progressed since the backup
+            if entry.is_shared() && self.storage.contains_key(&entry.keyspace, &entry.key)? {
+                log::warn!(
+                    "skipping restore of shared entry in keyspace {} of wallet {}: already stored",
+                    String::from_utf8_lossy(&entry.keyspace),
+                    name
+                );
+                continue;
+            }
+            self.storage.set(entry.keyspace, entry.key, entry.value)?;
+        }
+        self.storage.flush()
+    }
+
+    fn push_entry(
+        &self,
+        entries: &mut Vec<BackupEntry>,
+        keyspace: &str,
+        key: Vec<u8>,
+    ) -> Result<()> {
+        if let Some(value) = self.storage.get(keyspace, &key)? {
+            entries.push(BackupEntry {
+                keyspace: keyspace.as_bytes().to_vec(),
+                key,
+                value,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use client_common::seckey::derive_enckey;
+    use client_common::storage::{MemoryStorage, SledStorage};
+
+    use crate::hd_wallet::HardwareKind;
+    use crate::types::WalletKind;
+    use crate::wallet::{DefaultWalletClient, WalletClient};
+
+    #[test]
+    fn check_backup_restore_flow() {
+        let name = "name";
+        let passphrase = SecUtf8::from("passphrase");
+        let backup_passphrase = SecUtf8::from("backup passphrase");
+
+        let storage = MemoryStorage::default();
+        let wallet_client = DefaultWalletClient::new_read_only(storage.clone());
+        let (enckey, _) = wallet_client
+            .new_wallet(
+                name,
+                &passphrase,
+                WalletKind::HD,
+                HardwareKind::LocalOnly,
+                None,
+            )
+            .unwrap();
+        let transfer_address = wallet_client.new_transfer_address(name, &enckey).unwrap();
+        let staking_address = wallet_client.new_staking_address(name, &enckey).unwrap();
+
+        let backup_service = BackupService::new(storage);
+        let wrong_enckey = derive_enckey(&backup_passphrase, name).unwrap();
+        assert_eq!(
+            ErrorKind::DecryptionError,
+            backup_service
+                .backup(name, &wrong_enckey, &backup_passphrase)
+                .unwrap_err()
+                .kind()
+        );
+        let backup = backup_service
+            .backup(name, &enckey, &backup_passphrase)
+            .unwrap();
+        let backup = WalletBackup::from_base64(&backup.to_base64()).unwrap();
+        assert_eq!(backup.name(), name);
+
+        let path =
+            std::env::temp_dir().join(format!("backup-service-test-{}", rand::random::<u64>()));
+        {
+            let storage = SledStorage::new(&path).unwrap();
+            let restore_service = BackupService::new(storage.clone());
+
+            // wrong passphrase
+            assert_eq!(
+                ErrorKind::DecryptionError,
+                restore_service
+                    .restore(&backup, &SecUtf8::from("wrong passphrase"))
+                    .unwrap_err()
+                    .kind()
+            );
+
+            // tampered payload and header
+            let mut tampered = backup.clone();
+            let last = tampered.payload.len() - 1;
+            tampered.payload[last] ^= 1;
+            assert!(restore_service
+                .restore(&tampered, &backup_passphrase)
+                .is_err());
+            let mut tampered = backup.clone();
+            tampered.header.name = "other".to_owned();
+            assert!(restore_service
+                .restore(&tampered, &backup_passphrase)
+                .is_err());
+            assert!(storage
+                .keyspaces()
+                .unwrap()
+                .iter()
+                .all(|keyspace| storage.keys(keyspace).unwrap().is_empty()));
+
+            restore_service
+                .restore(&backup, &backup_passphrase)
+                .unwrap();
+            assert_eq!(
+                ErrorKind::InvalidInput,
+                restore_service
+                    .restore(&backup, &backup_passphrase)
+                    .unwrap_err()
+                    .kind()
+            );
+
+            let wallet_client = DefaultWalletClient::new_read_only(storage);
+            assert_eq!(wallet_client.wallets().unwrap(), vec![name.to_owned()]);
+            let enckey = wallet_client.auth_token(name, &passphrase).unwrap();
+            assert!(wallet_client
+                .transfer_addresses(name, &enckey, 0, 0, false)
+                .unwrap()
+                .contains(&transfer_address));
+            assert!(wallet_client
+                .staking_addresses(name, &enckey, 0, 0, false)
+                .unwrap()
+                .contains(&staking_address));
+            // HD seed is restored as well
+            wallet_client.new_transfer_address(name, &enckey).unwrap();
+        }
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn check_restore_older_schema_version() {
+        use crate::service::{load_sync_state, save_sync_state, SyncState};
+
+        let name = "name";
+        let passphrase = SecUtf8::from("passphrase");
+        let backup_passphrase = SecUtf8::from("backup passphrase");
+
+        let storage = MemoryStorage::default();
+        let wallet_client = DefaultWalletClient::new_read_only(storage.clone());
+        let (enckey, _) = wallet_client
+            .new_wallet(
+                name,
+                &passphrase,
+                WalletKind::Basic,
+                HardwareKind::LocalOnly,
+                None,
+            )
+            .unwrap();
+        let transfer_address = wallet_client.new_transfer_address(name, &enckey).unwrap();
+        let mut sync_state = SyncState::genesis([0; 32]);
+        sync_state.last_block_height = 42;
+        save_sync_state(&storage, name, &sync_state).unwrap();
+
+        // backups of the same wallet written with schema version 1 and a future one
+        let entries = BackupService::new(storage)
+            .backup(name, &enckey, &backup_passphrase)
+            .unwrap()
+            .open(&backup_passphrase)
+            .unwrap();
+        let backup = WalletBackup::seal(name, 1, &entries, &backup_passphrase).unwrap();
+        let newer_backup = WalletBackup::seal(
+            name,
+            STORAGE_SCHEMA_VERSION + 1,
+            &entries,
+            &backup_passphrase,
+        )
+        .unwrap();
+
+        let storage = MemoryStorage::default();
+        let restore_service = BackupService::new(storage.clone());
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            restore_service
+                .restore(&newer_backup, &backup_passphrase)
+                .unwrap_err()
+                .kind()
+        );
+        restore_service
+            .restore(&backup, &backup_passphrase)
+            .unwrap();
+
+        // migrations are applied to the restored wallet: it is synced again from genesis
+        assert!(load_sync_state(&storage, name).unwrap().is_none());
+        let wallet_client = DefaultWalletClient::new_read_only(storage);
+        assert_eq!(wallet_client.wallets().unwrap(), vec![name.to_owned()]);
+        let enckey = wallet_client.auth_token(name, &passphrase).unwrap();
+        assert!(wallet_client
+            .transfer_addresses(name, &enckey, 0, 0, false)
+            .unwrap()
+            .contains(&transfer_address));
+    }
+
+    #[cfg(feature = "experimental")]
+    #[test]
+    fn check_restore_keeps_shared_entries() {
+        use client_common::SecureStorage;
+
+        let name = "name";
+        let passphrase = SecUtf8::from("passphrase");
+        let backup_passphrase = SecUtf8::from("backup passphrase");
+        let keyspace = &shared_secure_keyspaces()[0];
+
+        let storage = MemoryStorage::default();
+        let wallet_client = DefaultWalletClient::new_read_only(storage.clone());
+        let (enckey, _) = wallet_client
+            .new_wallet(
+                name,
+                &passphrase,
+                WalletKind::Basic,
+                HardwareKind::LocalOnly,
+                None,
+            )
+            .unwrap();
+        storage
+            .set_secure(keyspace, "session", b"session".to_vec(), &enckey)
+            .unwrap();
+        storage
+            .set_secure(keyspace, "shared", b"mine".to_vec(), &enckey)
+            .unwrap();
+        let backup = BackupService::new(storage)
+            .backup(name, &enckey, &backup_passphrase)
+            .unwrap();
+
+        // the same session is used by another wallet of the storage the backup is restored to
+        let storage = MemoryStorage::default();
+        let other_enckey = derive_enckey(&passphrase, "other").unwrap();
+        storage
+            .set_secure(keyspace, "shared", b"other".to_vec(), &other_enckey)
+            .unwrap();
+        BackupService::new(storage.clone())
+            .restore(&backup, &backup_passphrase)
+            .unwrap();
+
+        assert_eq!(
+            storage
+                .get_secure(keyspace, "shared", &other_enckey)
+                .unwrap()
+                .unwrap(),
+            b"other".to_vec()
+        );
+        assert_eq!(
+            storage
+                .get_secure(keyspace, "session", &enckey)
+                .unwrap()
+                .unwrap(),
+            b"session".to_vec()
+        );
+    }
+}
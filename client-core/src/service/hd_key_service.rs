@@ -12,7 +12,7 @@ use crate::{HDSeed, Mnemonic};
 use crate::hd_wallet::ChainPath;
 use std::convert::From;
 
-const KEYSPACE: &str = "core_hd_key";
+pub(crate) const KEYSPACE: &str = "core_hd_key";
 
 /// HD key
 #[derive(Debug, Clone, PartialEq, Default, Encode, Decode)]
@@ -3,7 +3,7 @@ use zeroize::Zeroize;
 use client_common::Result;
 use client_common::{PrivateKey, SecKey, SecureStorage, Storage};
 
-const KEYSPACE: &str = "core_key";
+pub(crate) const KEYSPACE: &str = "core_key";
 
 /// Maintains mapping `wallet-name -> private-key`
 #[derive(Debug, Default, Clone)]
@@ -0,0 +1,467 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use parity_scale_codec::{Decode, Encode};
+
+use client_common::storage::{SledStorage, SCHEMA_KEYSPACE};
+use client_common::{Error, ErrorKind, Result, ResultExt, Storage};
+
+use super::sync_state_service::reset_sync_progress;
+
+/// Current schema version of client storage
+///
+/// It has to be bumped (along with a new migration in `migrations`) on every structural change
+/// of stored data, e.g. new fields in a stored type or a new layout of keyspaces.
+pub const STORAGE_SCHEMA_VERSION: u32 = 2;
+
+/// Upgrade of stored data from `version - 1` to `version`
+struct Migration<S: Storage> {
+    version: u32,
+    description: &'static str,
+    migrate: fn(&S) -> Result<()>,
+}
+
+/// Returns all the migrations, ordered by version
+fn migrations<S: Storage>() -> Vec<Migration<S>> {
+    vec![
+        Migration {
+            version: 1,
+            description: "record schema version of unversioned storage",
+            // data layout of version 0 (before schema versioning) is unchanged
+            migrate: |_| Ok(()),
+        },
+        Migration {
+            version: 2,
+            description: "resync wallets synced before staking histories were recorded",
+            migrate: reset_sync_progress,
+        },
+    ]
+}
+
+/// Raw content of all the keyspaces of a storage
+#[derive(Debug, Encode, Decode)]
+struct StorageDump {
+    version: u32,
+    keyspaces: Vec<(Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>)>,
+}
+
+/// Upgrades stored data to `STORAGE_SCHEMA_VERSION`
+///
+/// Storages created before schema versioning have no version record and are treated as version
+/// `0`. Empty storages are stamped with current version directly. Before applying any migration,
+/// the whole storage is dumped to a backup file, which can be loaded back with `restore`.
+#[derive(Debug, Default, Clone)]
+pub struct MigrationService<S>
+where
+    S: Storage,
+{
+    storage: S,
+}
+
+impl<S> MigrationService<S>
+where
+    S: Storage,
+{
+    /// Creates a new instance of migration service
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    /// Returns current schema version of stored data
+    pub fn version(&self) -> Result<u32> {
+        match self.storage.schema_version()? {
+            Some(version) => Ok(version),
+            None if self.is_empty()? => Ok(STORAGE_SCHEMA_VERSION),
+            None => Ok(0),
+        }
+    }
+
+    /// Returns `true` if stored data is older than current schema version
+    pub fn needs_migration(&self) -> Result<bool> {
+        Ok(self.version()? < STORAGE_SCHEMA_VERSION)
+    }
+
+    /// Applies all the pending migrations, after dumping the storage to `backup_path`, and
+    /// returns the path of the backup if any migration was applied
+    pub fn migrate<P: AsRef<Path>>(&self, backup_path: P) -> Result<Option<PathBuf>> {
+        let version = self.version()?;
+
+        if version > STORAGE_SCHEMA_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Storage schema version {} is newer than supported version {}, please upgrade the client",
+                    version, STORAGE_SCHEMA_VERSION
+                ),
+            ));
+        }
+        if version == STORAGE_SCHEMA_VERSION {
+            if self.storage.schema_version()?.is_none() {
+                self.storage.set_schema_version(version)?;
+                self.storage.flush()?;
+            }
+            return Ok(None);
+        }
+
+        let backup_path = backup_path.as_ref();
+        self.backup(version, backup_path)?;
+        self.upgrade(version).chain(|| {
+            (
+                ErrorKind::StorageError,
+                format!(
+                    "Unable to migrate storage, backup of previous data is at {}",
+                    backup_path.display()
+                ),
+            )
+        })?;
+
+        Ok(Some(backup_path.to_owned()))
+    }
+
+    /// Applies the migrations newer than `version`, without backing up stored data first
+    ///
+    /// Only meant for storages whose data can be recovered otherwise, e.g. the temporary storage
+    /// a wallet backup of an older schema version is upgraded in.
+    pub(crate) fn upgrade(&self, version: u32) -> Result<()> {
+        for migration in migrations::<S>() {
+            if migration.version <= version {
+                continue;
+            }
+
+            log::info!(
+                "migrating storage to schema version {}: {}",
+                migration.version,
+                migration.description
+            );
+            (migration.migrate)(&self.storage).chain(|| {
+                (
+                    ErrorKind::StorageError,
+                    format!(
+                        "Unable to migrate storage to schema version {}",
+                        migration.version
+                    ),
+                )
+            })?;
+            self.storage.set_schema_version(migration.version)?;
+            self.storage.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces all the stored data with the content of a backup written by `migrate`
+    pub fn restore<P: AsRef<Path>>(&self, backup_path: P) -> Result<()> {
+        let bytes = fs::read(backup_path.as_ref()).chain(|| {
+            (
+                ErrorKind::IoError,
+                format!(
+                    "Unable to read storage backup: {}",
+                    backup_path.as_ref().display()
+                ),
+            )
+        })?;
+        let dump = StorageDump::decode(&mut bytes.as_slice()).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to deserialize storage backup",
+            )
+        })?;
+
+        for keyspace in self.storage.keyspaces()? {
+            self.storage.clear(keyspace)?;
+        }
+        for (keyspace, entries) in dump.keyspaces {
+            for (key, value) in entries {
+                self.storage.set(&keyspace, key, value)?;
+            }
+        }
+        if dump.version > 0 {
+            self.storage.set_schema_version(dump.version)?;
+        }
+        self.storage.flush()
+    }
+
+    fn backup(&self, version: u32, backup_path: &Path) -> Result<()> {
+        let mut keyspaces = Vec::new();
+        for keyspace in self.storage.keyspaces()? {
+            if keyspace == SCHEMA_KEYSPACE.as_bytes() {
+                continue;
+            }
+
+            let mut entries = Vec::new();
+            for key in self.storage.keys(&keyspace)? {
+                if let Some(value) = self.storage.get(&keyspace, &key)? {
+                    entries.push((key, value));
+                }
+            }
+            keyspaces.push((keyspace, entries));
+        }
+
+        let dump = StorageDump { version, keyspaces };
+        fs::write(backup_path, dump.encode()).chain(|| {
+            (
+                ErrorKind::IoError,
+                format!("Unable to write storage backup: {}", backup_path.display()),
+            )
+        })
+    }
+
+    fn is_empty(&self) -> Result<bool> {
+        for keyspace in self.storage.keyspaces()? {
+            if !self.storage.keys(&keyspace)?.is_empty() {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Opens sled storage at given path and upgrades its data to current schema version
+///
+/// The backup of previous data is written next to the storage directory
+/// (`<path>.schema-v<version>.backup`).
+pub fn open_sled_storage<P: AsRef<Path>>(path: P) -> Result<SledStorage> {
+    let storage = SledStorage::new(&path)?;
+    let service = MigrationService::new(storage.clone());
+
+    let mut backup_path = OsString::from(path.as_ref().as_os_str());
+    backup_path.push(format!(".schema-v{}.backup", service.version()?));
+
+    if let Some(backup_path) = service.migrate(backup_path)? {
+        log::info!(
+            "storage upgraded to schema version {}, previous data backed up to {}",
+            STORAGE_SCHEMA_VERSION,
+            backup_path.display()
+        );
+    }
+    Ok(storage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use secstr::SecUtf8;
+
+    use chain_core::init::coin::Coin;
+    use chain_core::tx::data::input::TxoPointer;
+    use chain_core::tx::data::output::TxOut;
+    use client_common::storage::MemoryStorage;
+
+    use super::super::sync_state_service::{load_sync_state, save_sync_state, SyncState};
+    use super::super::wallet_state_service::{WalletStateMemento, WalletStateService};
+    use crate::hd_wallet::HardwareKind;
+    use crate::types::TransactionPending;
+    use crate::types::WalletKind;
+    use crate::wallet::{DefaultWalletClient, WalletClient};
+
+    /// Creates a storage as written by the client before schema versioning
+    fn unversioned_fixture<S: Storage>(storage: S) -> S {
+        let wallet_client = DefaultWalletClient::new_read_only(storage.clone());
+        let (enckey, _) = wallet_client
+            .new_wallet(
+                "name",
+                &SecUtf8::from("passphrase"),
+                WalletKind::HD,
+                HardwareKind::LocalOnly,
+                None,
+            )
+            .unwrap();
+        wallet_client.new_transfer_address("name", &enckey).unwrap();
+        wallet_client.new_staking_address("name", &enckey).unwrap();
+
+        storage.clear(SCHEMA_KEYSPACE).unwrap();
+        storage.flush().unwrap();
+        storage
+    }
+
+    /// Creates a storage as written by the client with schema version 1, where a wallet is
+    /// synced up to block 42 with an unspent output and a pending transaction
+    fn schema_v1_fixture<S: Storage>(storage: S) -> S {
+        let wallet_client = DefaultWalletClient::new_read_only(storage.clone());
+        let (enckey, _) = wallet_client
+            .new_wallet(
+                "name",
+                &SecUtf8::from("passphrase"),
+                WalletKind::HD,
+                HardwareKind::LocalOnly,
+                None,
+            )
+            .unwrap();
+        let address = wallet_client.new_transfer_address("name", &enckey).unwrap();
+
+        let mut memento = WalletStateMemento::default();
+        memento.add_unspent_transaction(
+            TxoPointer::new([0; 32], 0),
+            TxOut::new(address, Coin::new(100).unwrap()),
+        );
+        memento.add_pending_transaction(
+            [1; 32],
+            TransactionPending {
+                used_inputs: vec![TxoPointer::new([2; 32], 0)],
+                block_height: 40,
+                return_amount: Coin::new(50).unwrap(),
+            },
+        );
+        WalletStateService::new(storage.clone())
+            .apply_memento("name", &enckey, &memento)
+            .unwrap();
+        let mut sync_state = SyncState::genesis([0; 32]);
+        sync_state.last_block_height = 42;
+        save_sync_state(&storage, "name", &sync_state).unwrap();
+
+        storage.set_schema_version(1).unwrap();
+        storage.flush().unwrap();
+        storage
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}-{}", name, rand::random::<u64>()))
+    }
+
+    #[test]
+    fn check_empty_storage_is_stamped() {
+        let storage = MemoryStorage::default();
+        let service = MigrationService::new(storage.clone());
+        let backup_path = temp_path("migration-service-empty-backup");
+
+        assert!(!service.needs_migration().unwrap());
+        assert_eq!(None, service.migrate(&backup_path).unwrap());
+        assert_eq!(
+            Some(STORAGE_SCHEMA_VERSION),
+            storage.schema_version().unwrap()
+        );
+        assert!(!backup_path.exists());
+    }
+
+    #[test]
+    fn check_newer_schema_version_is_rejected() {
+        let storage = MemoryStorage::default();
+        storage
+            .set_schema_version(STORAGE_SCHEMA_VERSION + 1)
+            .unwrap();
+
+        let service = MigrationService::new(storage);
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            service
+                .migrate(temp_path("migration-service-newer-backup"))
+                .unwrap_err()
+                .kind()
+        );
+    }
+
+    #[test]
+    fn check_unversioned_sled_storage_migration() {
+        let path = temp_path("migration-service-test");
+        {
+            let storage = unversioned_fixture(SledStorage::new(&path).unwrap());
+            let wallet_client = DefaultWalletClient::new_read_only(storage.clone());
+            let enckey = wallet_client
+                .auth_token("name", &SecUtf8::from("passphrase"))
+                .unwrap();
+            let addresses = wallet_client
+                .transfer_addresses("name", &enckey, 0, 0, false)
+                .unwrap();
+
+            let service = MigrationService::new(storage.clone());
+            assert_eq!(0, service.version().unwrap());
+            assert!(service.needs_migration().unwrap());
+
+            let backup_path = service
+                .migrate(temp_path("migration-service-backup"))
+                .unwrap()
+                .unwrap();
+            assert!(!service.needs_migration().unwrap());
+            assert_eq!(
+                Some(STORAGE_SCHEMA_VERSION),
+                storage.schema_version().unwrap()
+            );
+            // already migrated
+            assert_eq!(None, service.migrate(&backup_path).unwrap());
+
+            // data is still readable after migration
+            let enckey = wallet_client
+                .auth_token("name", &SecUtf8::from("passphrase"))
+                .unwrap();
+            assert_eq!(
+                addresses,
+                wallet_client
+                    .transfer_addresses("name", &enckey, 0, 0, false)
+                    .unwrap()
+            );
+
+            // restoring the backup brings back the unversioned data
+            wallet_client.new_transfer_address("name", &enckey).unwrap();
+            service.restore(&backup_path).unwrap();
+            assert_eq!(0, service.version().unwrap());
+            assert_eq!(
+                addresses,
+                wallet_client
+                    .transfer_addresses("name", &enckey, 0, 0, false)
+                    .unwrap()
+            );
+            std::fs::remove_file(&backup_path).unwrap();
+        }
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn check_schema_v1_migration() {
+        let storage = schema_v1_fixture(MemoryStorage::default());
+        let wallet_client = DefaultWalletClient::new_read_only(storage.clone());
+        let enckey = wallet_client
+            .auth_token("name", &SecUtf8::from("passphrase"))
+            .unwrap();
+        let balance = wallet_client.balance("name", &enckey).unwrap();
+        let synced_height = || {
+            load_sync_state(&storage, "name")
+                .unwrap()
+                .map(|state| state.last_block_height)
+        };
+
+        let service = MigrationService::new(storage.clone());
+        assert_eq!(1, service.version().unwrap());
+        assert!(service.needs_migration().unwrap());
+        assert_eq!(Some(42), synced_height());
+
+        let backup_path = service
+            .migrate(temp_path("migration-service-v1-backup"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(STORAGE_SCHEMA_VERSION, service.version().unwrap());
+        // the wallet is synced again from genesis, its wallet state (including the pending
+        // transaction) is kept
+        assert_eq!(None, synced_height());
+        assert_eq!(balance, wallet_client.balance("name", &enckey).unwrap());
+
+        // restoring the backup brings back the data of schema version 1
+        service.restore(&backup_path).unwrap();
+        assert_eq!(1, service.version().unwrap());
+        assert_eq!(Some(42), synced_height());
+        std::fs::remove_file(&backup_path).unwrap();
+    }
+
+    #[test]
+    fn check_open_sled_storage() {
+        let path = temp_path("migration-service-open-test");
+        {
+            let storage = unversioned_fixture(SledStorage::new(&path).unwrap());
+            drop(storage);
+
+            let storage = open_sled_storage(&path).unwrap();
+            assert_eq!(
+                Some(STORAGE_SCHEMA_VERSION),
+                storage.schema_version().unwrap()
+            );
+        }
+        let mut backup_path = OsString::from(path.as_os_str());
+        backup_path.push(".schema-v0.backup");
+        assert!(Path::new(&backup_path).exists());
+
+        std::fs::remove_file(&backup_path).unwrap();
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
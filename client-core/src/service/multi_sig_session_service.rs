@@ -1,13 +1,29 @@
+use parity_scale_codec::{Decode, Encode};
 use secp256k1::schnorrsig::SchnorrSignature;
 
 use chain_core::common::H256;
+use chain_core::tx::data::TxId;
 use client_common::{
-    ErrorKind, PrivateKey, PublicKey, Result, ResultExt, SecKey, SecureStorage, Storage,
+    Error, ErrorKind, PrivateKey, PublicKey, Result, ResultExt, SecKey, SecureStorage, Storage,
 };
 
-use crate::multi_sig::MultiSigBuilder;
+use crate::multi_sig::{MultiSigBuilder, SigningPackage};
 
-const KEYSPACE: &str = "core_multi_sig_address";
+pub(crate) const KEYSPACE: &str = "core_multi_sig_address";
+const PACKAGE_KEYSPACE: &str = "core_multi_sig_package";
+
+/// Returns the keyspace of the signing packages imported in given wallet (several co-signers of
+/// the same transaction may have their wallet in the same storage)
+pub(crate) fn get_package_keyspace(name: &str) -> String {
+    format!("{}_{}", PACKAGE_KEYSPACE, name)
+}
+
+/// Signing package imported in current wallet along with the id of the session of current signer
+#[derive(Debug, Encode, Decode)]
+struct PackageRecord {
+    session_id: H256,
+    package: SigningPackage,
+}
 
 /// Maintains mapping `multi-sig session-id -> multi-sig session`
 #[derive(Debug, Default, Clone)]
@@ -169,6 +185,147 @@ where
         Ok(session.public_keys())
     }
 
+    /// Imports a signing package: creates the session of current signer for it (unless already
+    /// imported before), merges the round data of co-signers and contributes all the round data
+    /// of current signer which can be computed so far. Returns the updated package.
+    ///
+    /// # Arguments
+    ///
+    /// - `name`: Name of the wallet of current signer
+    /// - `package`: Signing package received from a co-signer
+    /// - `self_public_key`: Public key of current signer
+    /// - `self_private_key`: Private key of current signer
+    /// - `enckey`: Passphrase for encryption
+    pub fn import_package(
+        &self,
+        name: &str,
+        package: SigningPackage,
+        self_public_key: PublicKey,
+        self_private_key: PrivateKey,
+        enckey: &SecKey,
+    ) -> Result<SigningPackage> {
+        package.verify()?;
+        let package_id = package.id();
+
+        let session_id = match self.get_package_record(name, &package_id, enckey)? {
+            Some(record) => {
+                if record.package.public_keys() != package.public_keys()
+                    || record.package.proof != package.proof
+                    || record.package.spent_output != package.spent_output
+                {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "Signing package conflicts with the one imported before for transaction ({})",
+                            hex::encode(&package_id)
+                        ),
+                    ));
+                }
+                record.session_id
+            }
+            None => self.new_session(
+                package_id,
+                package.public_keys(),
+                self_public_key,
+                self_private_key,
+                enckey,
+            )?,
+        };
+
+        let mut session = self.get_session(&session_id, enckey)?;
+        session.merge(&package.signers)?;
+        session.contribute()?;
+        let signers = session.signers().to_vec();
+        self.set_session(&session_id, session, enckey)?;
+
+        let record = PackageRecord {
+            session_id,
+            package: SigningPackage { signers, ..package },
+        };
+        self.storage.set_secure(
+            get_package_keyspace(name),
+            package_id,
+            record.encode(),
+            enckey,
+        )?;
+
+        Ok(record.package)
+    }
+
+    /// Returns the latest state of signing package of given transaction
+    pub fn export_package(
+        &self,
+        name: &str,
+        transaction_id: &TxId,
+        enckey: &SecKey,
+    ) -> Result<SigningPackage> {
+        let record = self
+            .get_package_record(name, transaction_id, enckey)?
+            .chain(|| {
+                (
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "Signing package for transaction ({}) not found",
+                        hex::encode(transaction_id)
+                    ),
+                )
+            })?;
+        let session = self.get_session(&record.session_id, enckey)?;
+
+        Ok(SigningPackage {
+            signers: session.signers().to_vec(),
+            ..record.package
+        })
+    }
+
+    /// Returns final signature of signing package of given transaction. This function will fail
+    /// if partial signatures from all co-signers are not received.
+    pub fn package_signature(
+        &self,
+        name: &str,
+        transaction_id: &TxId,
+        enckey: &SecKey,
+    ) -> Result<SchnorrSignature> {
+        let record = self
+            .get_package_record(name, transaction_id, enckey)?
+            .chain(|| {
+                (
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "Signing package for transaction ({}) not found",
+                        hex::encode(transaction_id)
+                    ),
+                )
+            })?;
+        self.signature(&record.session_id, enckey)
+    }
+
+    /// Deletes all the signing packages imported in given wallet
+    #[inline]
+    pub fn delete_wallet(&self, name: &str) -> Result<()> {
+        self.storage.clear(get_package_keyspace(name))
+    }
+
+    /// Retrieves an imported signing package from storage
+    fn get_package_record(
+        &self,
+        name: &str,
+        transaction_id: &TxId,
+        enckey: &SecKey,
+    ) -> Result<Option<PackageRecord>> {
+        self.storage
+            .get_secure(get_package_keyspace(name), transaction_id, enckey)?
+            .map(|bytes| {
+                PackageRecord::decode(&mut bytes.as_slice()).chain(|| {
+                    (
+                        ErrorKind::DeserializationError,
+                        "Unable to deserialize signing package",
+                    )
+                })
+            })
+            .transpose()
+    }
+
     /// Retrieves a session from storage
     fn get_session(&self, session_id: &H256, enckey: &SecKey) -> Result<MultiSigBuilder> {
         let session_bytes = self
@@ -200,11 +357,18 @@ where
 mod multi_sig_session_service_tests {
     use super::*;
 
+    use crate::multi_sig::Signer;
     use secp256k1::schnorrsig::schnorr_verify;
     use secp256k1::Message;
     use secstr::SecUtf8;
 
-    use client_common::{seckey::derive_enckey, storage::MemoryStorage, SECP};
+    use chain_core::init::coin::Coin;
+    use chain_core::tx::data::address::ExtendedAddr;
+    use chain_core::tx::data::attribute::TxAttributes;
+    use chain_core::tx::data::input::TxoPointer;
+    use chain_core::tx::data::output::TxOut;
+    use chain_core::tx::data::Tx;
+    use client_common::{seckey::derive_enckey, storage::MemoryStorage, MultiSigAddress, SECP};
 
     #[test]
     fn check_multi_sig_transaction_flow() {
@@ -317,4 +481,141 @@ mod multi_sig_session_service_tests {
         )
         .expect("Invalid signature");
     }
+
+    #[test]
+    fn check_signing_package_offline_flow() {
+        let enckey_1 = derive_enckey(&SecUtf8::from("passphrase"), "wallet_1").unwrap();
+        let enckey_3 = derive_enckey(&SecUtf8::from("passphrase"), "wallet_3").unwrap();
+
+        let private_keys = (0..3)
+            .map(|_| PrivateKey::new().unwrap())
+            .collect::<Vec<_>>();
+        let public_keys = private_keys.iter().map(PublicKey::from).collect::<Vec<_>>();
+        let address = MultiSigAddress::new(public_keys.clone(), public_keys[0].clone(), 2).unwrap();
+
+        // 2-of-3: first and third signers sign, each one on its own machine
+        let signer_public_keys = vec![public_keys[0].clone(), public_keys[2].clone()];
+        let proof = address
+            .generate_proof(signer_public_keys.clone())
+            .unwrap()
+            .unwrap();
+        let transaction = Tx::new_with(
+            vec![TxoPointer::new([0; 32], 0)],
+            vec![TxOut::new(ExtendedAddr::OrTree([1; 32]), Coin::unit())],
+            TxAttributes::new(0),
+        );
+        let spent_output = TxOut::new(address.to_extended_addr(), Coin::unit());
+        let package =
+            SigningPackage::new(transaction, spent_output, proof, signer_public_keys.clone())
+                .unwrap();
+        let transaction_id = package.id();
+
+        // the wallets of both signers are in the same storage
+        let service = MultiSigSessionService::new(MemoryStorage::default());
+        let import_1 = |package: &str| {
+            let package = SigningPackage::from_base64(package).unwrap();
+            service
+                .import_package(
+                    "wallet_1",
+                    package,
+                    public_keys[0].clone(),
+                    private_keys[0].clone(),
+                    &enckey_1,
+                )
+                .unwrap()
+        };
+        let import_3 = |package: &str| {
+            let package = SigningPackage::from_base64(package).unwrap();
+            service
+                .import_package(
+                    "wallet_3",
+                    package,
+                    public_keys[2].clone(),
+                    private_keys[2].clone(),
+                    &enckey_3,
+                )
+                .unwrap()
+        };
+
+        // nonce commitment of first signer
+        let package = import_1(&package.to_base64());
+        assert!(package.signers.iter().any(|s| s.nonce_commitment.is_some()));
+        assert!(package.signers.iter().all(|s| s.nonce.is_none()));
+
+        // third signer receives all nonce commitments, so it also reveals its nonce
+        let package = import_3(&package.to_base64());
+        assert!(package.signers.iter().all(|s| s.nonce_commitment.is_some()));
+        assert!(package.signers.iter().any(|s| s.nonce.is_some()));
+
+        // first signer receives all nonces, so it also signs
+        let package = import_1(&package.to_base64());
+        assert!(package.signers.iter().all(|s| s.nonce.is_some()));
+        assert!(package
+            .signers
+            .iter()
+            .any(|s| s.partial_signature.is_some()));
+        assert!(service
+            .package_signature("wallet_1", &transaction_id, &enckey_1)
+            .is_err());
+
+        // third signer completes the signature
+        let package = import_3(&package.to_base64());
+        assert!(package
+            .signers
+            .iter()
+            .all(|s| s.partial_signature.is_some()));
+
+        // first signer resumes the persisted session with the final package
+        let package = import_1(&package.to_base64());
+        assert_eq!(
+            package,
+            service
+                .export_package("wallet_1", &transaction_id, &enckey_1)
+                .unwrap()
+        );
+        let signature = service
+            .package_signature("wallet_1", &transaction_id, &enckey_1)
+            .unwrap();
+        assert_eq!(
+            signature,
+            service
+                .package_signature("wallet_3", &transaction_id, &enckey_3)
+                .unwrap()
+        );
+
+        let mut signer_public_keys = signer_public_keys;
+        signer_public_keys.sort();
+        let combined_public_key = PublicKey::combine(&signer_public_keys).unwrap().0;
+        let message = Message::from_slice(&transaction_id).unwrap();
+        schnorr_verify(
+            secp256k1::SECP256K1,
+            &message,
+            &signature,
+            &combined_public_key.into(),
+        )
+        .expect("Invalid signature");
+
+        // deleting a wallet keeps the packages of the other wallets
+        service.delete_wallet("wallet_1").unwrap();
+        assert!(service
+            .export_package("wallet_1", &transaction_id, &enckey_1)
+            .is_err());
+        assert!(service
+            .export_package("wallet_3", &transaction_id, &enckey_3)
+            .is_ok());
+
+        // package with a proof for other signers is rejected
+        let mut tampered = package;
+        tampered.signers.pop();
+        tampered.signers.push(Signer {
+            public_key: public_keys[1].clone(),
+            nonce_commitment: None,
+            nonce: None,
+            partial_signature: None,
+        });
+        tampered
+            .signers
+            .sort_by(|a, b| a.public_key.cmp(&b.public_key));
+        assert!(SigningPackage::from_base64(&tampered.to_base64()).is_err());
+    }
 }
@@ -0,0 +1,276 @@
+use std::iter;
+
+use parity_scale_codec::{Decode, Encode};
+
+use client_common::storage::{decrypt_bytes, encrypt_bytes};
+use client_common::{Error, ErrorKind, Result, SecKey, Storage};
+
+use super::wallet_service::{
+    get_wallet_keyspace, get_wallet_keyspaces, shared_secure_keyspaces, wallet_name_keyspaces,
+};
+
+/// Key space of pending passphrase changes
+const KEYSPACE: &str = "core_passphrase_journal";
+
+/// Re-encrypted value of one entry of a secure keyspace
+#[derive(Debug, Encode, Decode)]
+struct JournalEntry {
+    keyspace: Vec<u8>,
+    key: Vec<u8>,
+    value: Vec<u8>,
+}
+
+/// Re-encrypts all the secure data of a wallet when its passphrase is changed
+///
+/// # Crash safety
+///
+/// All the values are first re-encrypted in memory and written as a single journal record
+/// (`wallet-name -> re-encrypted entries`). Once the journal is written, the change is committed:
+/// the entries are then copied to their keyspaces and the journal is removed. If this is
+/// interrupted, `recover` replays the journal, which is idempotent.
+#[derive(Debug, Default, Clone)]
+pub struct PassphraseService<S>
+where
+    S: Storage,
+{
+    storage: S,
+}
+
+impl<S> PassphraseService<S>
+where
+    S: Storage,
+{
+    /// Creates a new instance of passphrase service
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    /// Re-encrypts all the values sealed with `enckey` under `new_enckey`
+    ///
+    /// The secure values of a wallet are spread over keyspaces of multiple services: the
+    /// keyspaces dedicated to the wallet, the ones storing values under the wallet name, and the
+    /// ones shared between wallets (e.g. multi-sig sessions), where only the values which can be
+    /// decrypted with `enckey` belong to the wallet.
+    pub fn change_enckey(&self, name: &str, enckey: &SecKey, new_enckey: &SecKey) -> Result<()> {
+        self.recover(name)?;
+        self.write_journal(name, enckey, new_enckey)?;
+        self.recover(name)
+    }
+
+    /// Returns `true` if there is an interrupted passphrase change for given wallet
+    #[inline]
+    pub fn has_pending_change(&self, name: &str) -> Result<bool> {
+        self.storage.contains_key(KEYSPACE, name)
+    }
+
+    /// Completes an interrupted passphrase change of given wallet, if any
+    pub fn recover(&self, name: &str) -> Result<()> {
+        let entries: Vec<JournalEntry> = match self.storage.load(KEYSPACE, name)? {
+            Some(entries) => entries,
+            None => return Ok(()),
+        };
+
+        log::info!(
+            "completing passphrase change of wallet {}: {} entries",
+            name,
+            entries.len()
+        );
+        for entry in entries {
+            self.storage.set(entry.keyspace, entry.key, entry.value)?;
+        }
+        self.storage.flush()?;
+
+        self.storage.delete(KEYSPACE, name)?;
+        self.storage.flush()
+    }
+
+    /// Re-encrypts the values in memory and writes them to the journal, which commits the change
+    fn write_journal(&self, name: &str, enckey: &SecKey, new_enckey: &SecKey) -> Result<()> {
+        let mut entries = Vec::new();
+        for keyspace in get_wallet_keyspaces(name)
+            .into_iter()
+            .chain(shared_secure_keyspaces())
+        {
+            for key in self.storage.keys(&keyspace)? {
+                self.push_entry(&mut entries, &keyspace, key, enckey, new_enckey)?;
+            }
+        }
+        for keyspace in wallet_name_keyspaces()
+            .into_iter()
+            .chain(iter::once(get_wallet_keyspace()))
+        {
+            self.push_entry(
+                &mut entries,
+                &keyspace,
+                name.as_bytes().to_vec(),
+                enckey,
+                new_enckey,
+            )?;
+        }
+
+        if entries.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("No data of wallet {} is encrypted with given key", name),
+            ));
+        }
+
+        self.storage.save(KEYSPACE, name, &entries)?;
+        self.storage.flush()
+    }
+
+    /// Adds the value re-encrypted with `new_enckey` to the entries, if it's encrypted with
+    /// `enckey`
+    fn push_entry(
+        &self,
+        entries: &mut Vec<JournalEntry>,
+        keyspace: &str,
+        key: Vec<u8>,
+        enckey: &SecKey,
+        new_enckey: &SecKey,
+    ) -> Result<()> {
+        let value = match self.storage.get(keyspace, &key)? {
+            Some(value) => value,
+            None => return Ok(()),
+        };
+        let plain = match decrypt_bytes(&key, enckey, &value) {
+            Ok(plain) => plain,
+            // not encrypted, or encrypted with another key
+            Err(_) => return Ok(()),
+        };
+        let value = encrypt_bytes(&key, new_enckey, &plain)?;
+
+        entries.push(JournalEntry {
+            keyspace: keyspace.as_bytes().to_vec(),
+            key,
+            value,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use secstr::SecUtf8;
+
+    use client_common::seckey::derive_enckey;
+    use client_common::storage::{MemoryStorage, SledStorage};
+    use client_common::SecureStorage;
+
+    fn check_change_enckey_flow<S: Storage>(storage: S) {
+        let service = PassphraseService::new(storage.clone());
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), "name").unwrap();
+        let new_enckey = derive_enckey(&SecUtf8::from("new passphrase"), "name").unwrap();
+        let other_enckey = derive_enckey(&SecUtf8::from("passphrase"), "other").unwrap();
+        let keyspace = get_wallet_keyspaces("name")[0].clone();
+        let name_keyspace = wallet_name_keyspaces()[0].clone();
+        let other_keyspace = get_wallet_keyspaces("other")[0].clone();
+
+        storage
+            .set_secure(&keyspace, "secret", b"secret".to_vec(), &enckey)
+            .unwrap();
+        storage
+            .set_secure(&name_keyspace, "name", b"wallet".to_vec(), &enckey)
+            .unwrap();
+        storage
+            .set_secure(&name_keyspace, "other", b"other".to_vec(), &other_enckey)
+            .unwrap();
+        storage
+            .set_secure(&other_keyspace, "secret", b"other".to_vec(), &other_enckey)
+            .unwrap();
+        // only the keyspaces of the wallet are re-encrypted
+        storage
+            .set_secure("unrelated", "secret", b"unrelated".to_vec(), &enckey)
+            .unwrap();
+        storage.set(&keyspace, "plain", b"plain".to_vec()).unwrap();
+
+        service.change_enckey("name", &enckey, &new_enckey).unwrap();
+        assert!(!service.has_pending_change("name").unwrap());
+
+        assert_eq!(
+            storage
+                .get_secure(&keyspace, "secret", &new_enckey)
+                .unwrap()
+                .unwrap(),
+            b"secret".to_vec()
+        );
+        assert_eq!(
+            storage
+                .get_secure(&name_keyspace, "name", &new_enckey)
+                .unwrap()
+                .unwrap(),
+            b"wallet".to_vec()
+        );
+        assert_eq!(
+            ErrorKind::DecryptionError,
+            storage
+                .get_secure(&keyspace, "secret", &enckey)
+                .unwrap_err()
+                .kind()
+        );
+        // data of other wallets, other keyspaces and plain data are left untouched
+        assert_eq!(
+            storage
+                .get_secure(&name_keyspace, "other", &other_enckey)
+                .unwrap()
+                .unwrap(),
+            b"other".to_vec()
+        );
+        assert_eq!(
+            storage
+                .get_secure(&other_keyspace, "secret", &other_enckey)
+                .unwrap()
+                .unwrap(),
+            b"other".to_vec()
+        );
+        assert_eq!(
+            storage
+                .get_secure("unrelated", "secret", &enckey)
+                .unwrap()
+                .unwrap(),
+            b"unrelated".to_vec()
+        );
+        assert_eq!(
+            storage.get(&keyspace, "plain").unwrap().unwrap(),
+            b"plain".to_vec()
+        );
+
+        // nothing is encrypted with the old key anymore
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            service
+                .change_enckey("name", &enckey, &new_enckey)
+                .unwrap_err()
+                .kind()
+        );
+
+        // interrupted after the journal is written
+        service.write_journal("name", &new_enckey, &enckey).unwrap();
+        assert!(service.has_pending_change("name").unwrap());
+        service.recover("name").unwrap();
+        assert!(!service.has_pending_change("name").unwrap());
+        assert_eq!(
+            storage
+                .get_secure(&keyspace, "secret", &enckey)
+                .unwrap()
+                .unwrap(),
+            b"secret".to_vec()
+        );
+    }
+
+    #[test]
+    fn check_change_enckey_flow_with_memory_storage() {
+        check_change_enckey_flow(MemoryStorage::default());
+    }
+
+    #[test]
+    fn check_change_enckey_flow_with_sled_storage() {
+        let path =
+            std::env::temp_dir().join(format!("passphrase-service-test-{}", rand::random::<u64>()));
+        check_change_enckey_flow(SledStorage::new(&path).unwrap());
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -80,6 +80,19 @@ where
             .chain(|| (ErrorKind::InvalidInput, "Unable to generate merkle proof"))
     }
 
+    /// Selects the shortest spending path of given root hash among the available co-signers
+    pub fn select_proof(
+        &self,
+        name: &str,
+        root_hash: &H256,
+        available_signers: &[PublicKey],
+        enckey: &SecKey,
+    ) -> Result<Proof<RawXOnlyPubkey>> {
+        let address = self.get_multi_sig_address_from_root_hash(name, root_hash, enckey)?;
+
+        address.select_proof(available_signers)
+    }
+
     /// Returns the number of required cosigners for given root_hash
     pub fn required_signers(&self, name: &str, root_hash: &H256, enckey: &SecKey) -> Result<usize> {
         let address = self.get_multi_sig_address_from_root_hash(name, root_hash, enckey)?;
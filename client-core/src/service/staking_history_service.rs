@@ -0,0 +1,149 @@
+use parity_scale_codec::{Decode, Encode};
+
+use client_common::{ErrorKind, Result, ResultExt, SecKey, SecureStorage, Storage};
+
+use crate::types::StakingHistoryEntry;
+
+/// key space of staking histories
+pub(crate) const KEYSPACE: &str = "core_staking_history";
+
+/// Maintains mapping `wallet-name -> rewards and slashes of staking addresses` (encrypted with
+/// the enckey of the wallet)
+#[derive(Debug, Default, Clone)]
+pub struct StakingHistoryService<S>
+where
+    S: Storage,
+{
+    storage: S,
+}
+
+impl<S> StakingHistoryService<S>
+where
+    S: Storage,
+{
+    /// Creates a new instance of staking history service
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    /// Returns the rewards and slashes of given wallet, ordered by block height
+    pub fn get(&self, name: &str, enckey: &SecKey) -> Result<Vec<StakingHistoryEntry>> {
+        Ok(self
+            .storage
+            .load_secure(KEYSPACE, name, enckey)?
+            .unwrap_or_default())
+    }
+
+    /// Deletes the staking history of given wallet
+    #[inline]
+    pub fn delete_wallet(&self, name: &str) -> Result<()> {
+        delete_staking_history(&self.storage, name)
+    }
+}
+
+/// Adds rewards and slashes to the staking history of a wallet, entries which are already
+/// recorded (e.g. when a wallet is synced again) are skipped
+pub fn add_staking_history<S: SecureStorage>(
+    storage: &S,
+    name: &str,
+    enckey: &SecKey,
+    entries: &[StakingHistoryEntry],
+) -> Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    storage
+        .fetch_and_update_secure(KEYSPACE, name, enckey, |bytes_optional| {
+            let mut history = match bytes_optional {
+                Some(mut bytes) => <Vec<StakingHistoryEntry>>::decode(&mut bytes).chain(|| {
+                    (
+                        ErrorKind::DeserializationError,
+                        format!("Unable to deserialize staking history of wallet {}", name),
+                    )
+                })?,
+                None => Vec::new(),
+            };
+            for entry in entries {
+                if !history.contains(entry) {
+                    history.push(entry.clone());
+                }
+            }
+            history.sort_by_key(|entry| entry.block_height);
+            Ok(Some(history.encode()))
+        })
+        .map(|_| ())
+}
+
+/// Deletes the staking history of a wallet
+pub fn delete_staking_history<S: Storage>(storage: &S, name: &str) -> Result<()> {
+    storage.delete(KEYSPACE, name)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::str::FromStr;
+
+    use secstr::SecUtf8;
+
+    use chain_core::init::coin::Coin;
+    use chain_core::state::account::StakedStateAddress;
+    use client_common::seckey::derive_enckey;
+    use client_common::storage::MemoryStorage;
+    use client_common::tendermint::types::Time;
+
+    use crate::types::HistoryRecordType;
+
+    #[test]
+    fn check_flow() {
+        let storage = MemoryStorage::default();
+        let service = StakingHistoryService::new(storage.clone());
+        let name = "name";
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), name).unwrap();
+
+        let entry = |block_height, record_type| StakingHistoryEntry {
+            block_height,
+            block_time: Time::from_str("2020-01-01T00:00:00Z").unwrap(),
+            staking_address: StakedStateAddress::from_str(
+                "0x0e7c045110b8dbf29765047380898919c5cb56f4",
+            )
+            .unwrap(),
+            record_type,
+            amount: Coin::new(10).unwrap(),
+        };
+
+        assert!(service.get(name, &enckey).unwrap().is_empty());
+        add_staking_history(
+            &storage,
+            name,
+            &enckey,
+            &[entry(5, HistoryRecordType::Slash)],
+        )
+        .unwrap();
+        // syncing again records the same entries
+        add_staking_history(
+            &storage,
+            name,
+            &enckey,
+            &[
+                entry(2, HistoryRecordType::Reward),
+                entry(5, HistoryRecordType::Slash),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                entry(2, HistoryRecordType::Reward),
+                entry(5, HistoryRecordType::Slash)
+            ],
+            service.get(name, &enckey).unwrap()
+        );
+
+        service.delete_wallet(name).unwrap();
+        assert!(service.get(name, &enckey).unwrap().is_empty());
+    }
+}
@@ -2,7 +2,7 @@ use chain_core::common::H256;
 use client_common::{ErrorKind, Result, ResultExt, Storage};
 use parity_scale_codec::{Decode, Encode};
 /// key space of wallet sync state
-const KEYSPACE: &str = "core_wallet_sync";
+pub(crate) const KEYSPACE: &str = "core_wallet_sync";
 
 /// Sync state for wallet
 #[derive(Debug, Encode, Decode)]
@@ -48,6 +48,32 @@ pub fn delete_sync_state<S: Storage>(storage: &S, name: &str) -> Result<()> {
     Ok(())
 }
 
+/// Deletes the sync state of every wallet synced past genesis, so that their next sync starts
+/// from genesis again and records the data which is only collected while syncing blocks (e.g.
+/// staking histories).
+///
+/// Wallet states (and their pending transactions) are kept, as applying the blocks again is
+/// idempotent.
+pub(crate) fn reset_sync_progress<S: Storage>(storage: &S) -> Result<()> {
+    for name in storage.keys(KEYSPACE)? {
+        if let Some(bytes) = storage.get(KEYSPACE, &name)? {
+            let state = SyncState::decode(&mut bytes.as_slice()).chain(|| {
+                (
+                    ErrorKind::DeserializationError,
+                    format!(
+                        "Unable to deserialize global state for wallet with name {}",
+                        String::from_utf8_lossy(&name)
+                    ),
+                )
+            })?;
+            if state.last_block_height > 0 {
+                storage.delete(KEYSPACE, &name)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Exposes functionalities for managing client's global state (for synchronization)
 ///
 /// Stores `wallet-name -> global-state`
@@ -1,6 +1,10 @@
 use indexmap::IndexSet;
 use parity_scale_codec::{Decode, Encode, Input, Output};
 
+use super::{
+    address_book_service, hd_key_service, key_service, staking_history_service, sync_state_service,
+    wallet_state_service,
+};
 use crate::hd_wallet::{ChainPath, HardwareKind};
 use crate::service::{load_wallet_state, HdKey, WalletState};
 use crate::types::WalletKind;
@@ -21,7 +25,7 @@ use std::fmt;
 use std::str;
 
 /// Key space of wallet
-const KEYSPACE: &str = "core_wallet";
+pub(crate) const KEYSPACE: &str = "core_wallet";
 
 fn get_public_keyspace(name: &str) -> String {
     format!("{}_{}_publickey", KEYSPACE, name)
@@ -59,10 +63,54 @@ fn get_info_keyspace(name: &str) -> String {
     format!("{}_{}_info", KEYSPACE, name)
 }
 
-fn get_wallet_keyspace() -> String {
+pub(crate) fn get_wallet_keyspace() -> String {
     format!("{}_walletname", KEYSPACE)
 }
 
+/// Returns all the keyspaces dedicated to given wallet
+pub(crate) fn get_wallet_keyspaces(name: &str) -> Vec<String> {
+    #[allow(unused_mut)]
+    let mut keyspaces = vec![
+        get_info_keyspace(name),
+        get_public_keyspace(name),
+        get_stakingkey_keyspace(name),
+        get_stakingkeyset_keyspace(name),
+        get_private_keyspace(name),
+        get_hdpath_keyspace(name),
+        get_roothash_keyspace(name),
+        get_roothashset_keyspace(name),
+        get_multisig_keyspace(name),
+    ];
+    #[cfg(feature = "experimental")]
+    keyspaces.push(super::multi_sig_session_service::get_package_keyspace(name));
+    keyspaces
+}
+
+/// Returns the keyspaces which store the data of a wallet under the name of the wallet
+pub(crate) fn wallet_name_keyspaces() -> Vec<String> {
+    vec![
+        KEYSPACE.to_owned(),
+        key_service::KEYSPACE.to_owned(),
+        hd_key_service::KEYSPACE.to_owned(),
+        wallet_state_service::KEYSPACE.to_owned(),
+        sync_state_service::KEYSPACE.to_owned(),
+        address_book_service::KEYSPACE.to_owned(),
+        staking_history_service::KEYSPACE.to_owned(),
+    ]
+}
+
+/// Returns the keyspaces shared by all the wallets, where the values of a wallet are only
+/// identified by being encrypted with its enckey
+#[cfg(feature = "experimental")]
+pub(crate) fn shared_secure_keyspaces() -> Vec<String> {
+    vec![super::multi_sig_session_service::KEYSPACE.to_owned()]
+}
+
+#[cfg(not(feature = "experimental"))]
+pub(crate) fn shared_secure_keyspaces() -> Vec<String> {
+    vec![]
+}
+
 fn serde_to_str<T, S>(value: &T, serializer: S) -> std::result::Result<S::Ok, S::Error>
 where
     T: Encode,
@@ -205,6 +253,8 @@ where
                     return Ok(true);
                 }
             }
+            // hash-time-locked addresses are never owned by a wallet
+            ExtendedAddr::HashTimeLock(_) => {}
         }
 
         Ok(false)
@@ -727,6 +777,7 @@ where
                     return Ok(Some(roothash_found));
                 }
             }
+            ExtendedAddr::HashTimeLock(_) => {}
         }
 
         Ok(None)
@@ -2,15 +2,23 @@ use parity_scale_codec::{Decode, Encode};
 use std::collections::BTreeMap;
 
 use chain_core::{
-    init::coin::{sum_coins, CoinError},
-    tx::data::{input::TxoPointer, output::TxOut, TxId},
+    common::Timespec,
+    init::coin::{sum_coins, Coin, CoinError},
+    tx::data::{
+        input::TxoPointer,
+        output::{RelativeTimelock, TxInclusion, TxOut},
+        TxId,
+    },
 };
+use client_common::tendermint::types::Time;
 use client_common::{Error, ErrorKind, Result, ResultExt, SecKey, SecureStorage, Storage};
 
-use crate::types::{TransactionChange, TransactionPending, WalletBalance};
+use crate::types::{
+    LockedBalance, LockedUntil, TransactionChange, TransactionPending, WalletBalance,
+};
 
 /// key space of wallet state
-const KEYSPACE: &str = "core_wallet_state";
+pub(crate) const KEYSPACE: &str = "core_wallet_state";
 
 /// Maintains mapping `wallet-name -> wallet-state`
 #[derive(Debug, Default, Clone)]
@@ -68,6 +76,20 @@ where
         }
     }
 
+    /// Returns currently available unspent transactions which can be spent in a block with given
+    /// time and height (i.e. are not timelocked)
+    pub fn get_unlocked_transactions(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        block_time: Timespec,
+        block_height: u64,
+    ) -> Result<BTreeMap<TxoPointer, TxOut>> {
+        Ok(self
+            .get_wallet_state(name, enckey)?
+            .get_unlocked_transactions(block_time, block_height))
+    }
+
     /// Returns `true` or `false` depending if input is unspent or not. `true` if the input is unspent, `false`
     /// otherwise
     pub fn are_inputs_unspent(
@@ -129,11 +151,18 @@ where
         self.get_wallet_state(name, enckey)?.get_output(input)
     }
 
-    /// Returns currently stored balance for given wallet
-    pub fn get_balance(&self, name: &str, enckey: &SecKey) -> Result<WalletBalance> {
+    /// Returns currently stored balance for given wallet (timelocks are checked against given
+    /// block time and height)
+    pub fn get_balance(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        block_time: Timespec,
+        block_height: u64,
+    ) -> Result<WalletBalance> {
         let wallet_state = self.get_wallet_state(name, enckey)?;
         let balance = wallet_state
-            .get_balance()
+            .get_balance(block_time, block_height)
             .chain(|| (ErrorKind::StorageError, "Calculate balance error"))?;
         Ok(balance)
     }
@@ -304,8 +333,72 @@ impl WalletState {
             .collect::<Vec<_>>();
         result
     }
-    /// get the balance info
-    pub fn get_balance(&self) -> std::result::Result<WalletBalance, CoinError> {
+    /// get transactions which are available and can be spent in a block with given time and height
+    pub fn get_unlocked_transactions(
+        &self,
+        block_time: Timespec,
+        block_height: u64,
+    ) -> BTreeMap<TxoPointer, TxOut> {
+        let mut result = self.get_available_transactions();
+        result.retain(|input, output| {
+            self.get_locked_until(input, output, block_time, block_height)
+                .is_none()
+        });
+        result
+    }
+    /// get the block the transaction which created given output was included in
+    pub fn get_inclusion(&self, input: &TxoPointer) -> Option<TxInclusion> {
+        self.transaction_history
+            .get(&input.id)
+            .map(|change| TxInclusion {
+                block_height: change.block_height.into(),
+                block_time: change
+                    .block_time
+                    .duration_since(Time::unix_epoch())
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or_default(),
+            })
+    }
+    /// get when given output can be spent, `None` if it can be spent in a block with given time and height
+    pub fn get_locked_until(
+        &self,
+        input: &TxoPointer,
+        output: &TxOut,
+        block_time: Timespec,
+        block_height: u64,
+    ) -> Option<LockedUntil> {
+        let absolute = output
+            .valid_from
+            .filter(|valid_from| *valid_from > block_time)
+            .map(LockedUntil::Time);
+        let relative = match (&output.relative_lock, self.get_inclusion(input)) {
+            (Some(relative_lock), Some(inclusion))
+                if !relative_lock.is_unlocked(&inclusion, block_time, block_height.into()) =>
+            {
+                Some(match relative_lock {
+                    RelativeTimelock::Seconds(seconds) => {
+                        LockedUntil::Time(inclusion.block_time.saturating_add(*seconds))
+                    }
+                    RelativeTimelock::Blocks(blocks) => LockedUntil::BlockHeight(
+                        inclusion.block_height.value().saturating_add(*blocks),
+                    ),
+                })
+            }
+            _ => None,
+        };
+        match (absolute, relative) {
+            (Some(LockedUntil::Time(absolute)), Some(LockedUntil::Time(relative))) => {
+                Some(LockedUntil::Time(absolute.max(relative)))
+            }
+            (absolute, relative) => relative.or(absolute),
+        }
+    }
+    /// get the balance info (timelocks are checked against given block time and height)
+    pub fn get_balance(
+        &self,
+        block_time: Timespec,
+        block_height: u64,
+    ) -> std::result::Result<WalletBalance, CoinError> {
         // pending amount
         let pending_coins = self
             .pending_transactions
@@ -313,22 +406,34 @@ impl WalletState {
             .map(|value| value.return_amount);
         let amount_pending = sum_coins(pending_coins)?;
 
-        // unavailable amount
-        let pending_inputs = self.get_pending_inputs();
-        let available_coins = self
-            .unspent_transactions
-            .iter()
-            .filter(|(key, _value)| !pending_inputs.contains(key))
-            .map(|(_key, value)| value.value);
-        let amount_available = sum_coins(available_coins)?;
+        // available and locked amounts
+        let mut amount_available = Coin::zero();
+        let mut locked = BTreeMap::new();
+        for (input, output) in self.get_available_transactions().iter() {
+            match self.get_locked_until(input, output, block_time, block_height) {
+                None => amount_available = (amount_available + output.value)?,
+                Some(locked_until) => {
+                    let amount = locked.entry(locked_until).or_insert_with(Coin::zero);
+                    *amount = (*amount + output.value)?;
+                }
+            }
+        }
+        let amount_locked = sum_coins(locked.values().copied())?;
 
         // total amount
-        let amount_total = (amount_pending + amount_available)?;
+        let amount_total = ((amount_pending + amount_available)? + amount_locked)?;
 
         let wallet_balances = WalletBalance {
             total: amount_total,
             available: amount_available,
             pending: amount_pending,
+            locked: locked
+                .into_iter()
+                .map(|(locked_until, amount)| LockedBalance {
+                    amount,
+                    locked_until,
+                })
+                .collect(),
         };
         Ok(wallet_balances)
     }
@@ -508,7 +613,9 @@ mod tests {
 
         assert_eq!(
             WalletBalance::default(),
-            wallet_state_service.get_balance(name, enckey).unwrap()
+            wallet_state_service
+                .get_balance(name, enckey, 0, 0)
+                .unwrap()
         );
 
         // Add an unspent transaction and check if it is added
@@ -584,6 +691,8 @@ mod tests {
             block_height: 0,
             fee_paid: Fee::new(Coin::new(10).unwrap()),
             block_time: Time::from_str("2019-04-09T09:38:41.735577Z").unwrap(),
+            label: None,
+            address_labels: Default::default(),
         });
 
         assert!(wallet_state_service
@@ -623,6 +732,8 @@ mod tests {
             block_height: 0,
             fee_paid: Fee::new(Coin::new(10).unwrap()),
             block_time: Time::from_str("2019-04-09T09:38:41.735577Z").unwrap(),
+            label: None,
+            address_labels: Default::default(),
         });
 
         assert!(wallet_state_service
@@ -658,11 +769,14 @@ mod tests {
             .apply_memento(name, enckey, &memento)
             .unwrap();
         assert_eq!(
-            wallet_state_service.get_balance(name, enckey).unwrap(),
+            wallet_state_service
+                .get_balance(name, enckey, 0, 0)
+                .unwrap(),
             WalletBalance {
                 total: Coin::new(140).unwrap(),
                 available: Coin::new(140).unwrap(),
                 pending: Coin::zero(),
+                locked: vec![],
             }
         );
 
@@ -681,11 +795,14 @@ mod tests {
             .unwrap();
 
         assert_eq!(
-            wallet_state_service.get_balance(name, enckey).unwrap(),
+            wallet_state_service
+                .get_balance(name, enckey, 0, 0)
+                .unwrap(),
             WalletBalance {
                 total: Coin::new(90).unwrap(),
                 available: Coin::new(40).unwrap(),
                 pending: Coin::new(50).unwrap(),
+                locked: vec![],
             }
         );
 
@@ -719,11 +836,14 @@ mod tests {
             .unwrap();
         // now, we can get the balance
         assert_eq!(
-            wallet_state_service.get_balance(name, enckey).unwrap(),
+            wallet_state_service
+                .get_balance(name, enckey, 0, 0)
+                .unwrap(),
             WalletBalance {
                 total: Coin::new(90).unwrap(),
                 available: Coin::new(90).unwrap(),
                 pending: Coin::zero(),
+                locked: vec![],
             }
         );
         let unspent_tx = wallet_state_service
@@ -753,11 +873,87 @@ mod tests {
             .apply_memento(name, enckey, &memento)
             .unwrap();
         assert_eq!(
-            wallet_state_service.get_balance(name, enckey).unwrap(),
+            wallet_state_service
+                .get_balance(name, enckey, 0, 0)
+                .unwrap(),
             WalletBalance {
                 total: Coin::new(140).unwrap(),
                 available: Coin::new(140).unwrap(),
                 pending: Coin::new(0).unwrap(),
+                locked: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_timelocked_balance() {
+        // included at 2020-01-01T00:00:00Z in block 10
+        let inclusion_time = 1_577_836_800;
+        let address = ExtendedAddr::OrTree([0; 32]);
+        let coin = |m: u64| Coin::new(m).unwrap();
+        let outputs = vec![
+            TxOut::new_with_relative_lock(
+                address.clone(),
+                coin(10),
+                RelativeTimelock::Seconds(100),
+            ),
+            TxOut::new_with_relative_lock(address.clone(), coin(20), RelativeTimelock::Blocks(5)),
+            TxOut::new_with_timelock(address.clone(), coin(30), inclusion_time + 100),
+            TxOut::new(address, coin(40)),
+        ];
+        let mut state = WalletState::default();
+        for (i, output) in outputs.iter().enumerate() {
+            state
+                .unspent_transactions
+                .insert(TxoPointer::new([1; 32], i), output.clone());
+        }
+        state.add_transaction_change(
+            [1; 32],
+            TransactionChange {
+                transaction_id: [1; 32],
+                inputs: Vec::new(),
+                outputs,
+                balance_change: BalanceChange::Incoming { value: coin(100) },
+                transaction_type: TransactionType::Transfer,
+                block_height: 10,
+                fee_paid: Fee::new(Coin::zero()),
+                block_time: Time::from_str("2020-01-01T00:00:00Z").unwrap(),
+                label: None,
+                address_labels: Default::default(),
+            },
+        );
+
+        assert_eq!(
+            state.get_balance(inclusion_time + 99, 14).unwrap(),
+            WalletBalance {
+                total: coin(100),
+                available: coin(40),
+                pending: Coin::zero(),
+                locked: vec![
+                    LockedBalance {
+                        amount: coin(40),
+                        locked_until: LockedUntil::Time(inclusion_time + 100),
+                    },
+                    LockedBalance {
+                        amount: coin(20),
+                        locked_until: LockedUntil::BlockHeight(15),
+                    },
+                ],
+            }
+        );
+        assert_eq!(
+            state
+                .get_unlocked_transactions(inclusion_time + 100, 14)
+                .len(),
+            3
+        );
+        assert_eq!(
+            state.get_balance(inclusion_time + 100, 15).unwrap(),
+            WalletBalance {
+                total: coin(100),
+                available: coin(100),
+                pending: Coin::zero(),
+                locked: vec![],
             }
         );
     }
@@ -1,5 +1,6 @@
 use crate::transaction_builder::WitnessedUTxO;
 use chain_core::common::MerkleTree;
+use chain_core::common::Proof;
 use chain_core::common::H256;
 use chain_core::init::address::RedeemAddress;
 use chain_core::state::account::{
@@ -50,13 +51,24 @@ impl DummySigner {
         Ok(TxInWitness::TreeSig(mock_signature, proof))
     }
 
+    /// Signs transaction with the mock signature and the actual spending path
+    fn sign_tx_with_proof(&self, proof: &Proof<RawXOnlyPubkey>) -> TxInWitness {
+        let mock_signature =
+            SchnorrSignature::from_default(&[0_u8; 64]).expect("set mock signature failed");
+        TxInWitness::TreeSig(mock_signature, proof.clone())
+    }
+
     /// Schnorr sign consecutive imaginary inputs of provided length
     pub fn schnorr_sign_inputs_len(&self, inputs: &[WitnessedUTxO]) -> Result<TxWitness> {
         Ok(inputs
             .iter()
             .map(|x| {
-                self.sign_tx(x.threshold as usize)
-                    .expect("would that ever fail? why this dummy has results?")
+                x.wrap_witness(match x.spend_proof {
+                    Some(ref proof) => self.sign_tx_with_proof(proof),
+                    None => self
+                        .sign_tx(x.threshold as usize)
+                        .expect("would that ever fail? why this dummy has results?"),
+                })
             })
             .collect::<Vec<TxInWitness>>()
             .into())
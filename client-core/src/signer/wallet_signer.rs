@@ -1,9 +1,12 @@
 //! Wallet signer responsible for signing as wallet
-use chain_core::common::H256;
+use std::collections::BTreeMap;
+
+use chain_core::common::{Proof, H256};
 use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::witness::tree::RawXOnlyPubkey;
 use chain_core::tx::witness::{TxInWitness, TxWitness};
 use client_common::Transaction;
-use client_common::{Error, ErrorKind, Result, ResultExt, SecKey, Storage};
+use client_common::{Error, ErrorKind, PublicKey, Result, ResultExt, SecKey, Storage};
 
 use crate::service::{HwKeyService, KeyService, RootHashService, WalletService};
 use crate::types::WalletKind;
@@ -51,6 +54,44 @@ where
             hw_key_service,
         )
     }
+
+    /// Selects the spending path of each multi-sig (m > 1) address of the wallet in given
+    /// addresses, among the combinations of available co-signers
+    pub fn select_spend_paths<'a, I>(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        addresses: I,
+        available_signers: &[PublicKey],
+    ) -> Result<BTreeMap<ExtendedAddr, Proof<RawXOnlyPubkey>>>
+    where
+        I: IntoIterator<Item = &'a ExtendedAddr>,
+    {
+        let mut spend_paths = BTreeMap::new();
+        for address in addresses {
+            if spend_paths.contains_key(address) {
+                continue;
+            }
+            let root_hash = match self.wallet_service.find_root_hash(name, enckey, address)? {
+                Some(root_hash) => root_hash,
+                None => continue,
+            };
+            if self
+                .root_hash_service
+                .required_signers(name, &root_hash, enckey)?
+                == 1
+            {
+                continue;
+            }
+
+            let proof =
+                self.root_hash_service
+                    .select_proof(name, &root_hash, available_signers, enckey)?;
+            spend_paths.insert(address.clone(), proof);
+        }
+
+        Ok(spend_paths)
+    }
 }
 
 /// A short-lived signer belonging to a wallet
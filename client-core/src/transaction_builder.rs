@@ -13,10 +13,12 @@ pub use unauthorized_wallet_transaction_builder::UnauthorizedWalletTransactionBu
 use chain_core::init::coin::Coin;
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::htlc::{HtlcCondition, HtlcSpend};
 use chain_core::tx::data::input::TxoPointer;
 use chain_core::tx::data::output::TxOut;
 use chain_core::tx::TxAux;
-use client_common::{PrivateKey, Result, SecKey, SignedTransaction, Transaction};
+use client_common::{PrivateKey, PublicKey, Result, SecKey, SignedTransaction, Transaction};
+use secp256k1::schnorrsig::SchnorrSignature;
 
 use crate::UnspentTransactions;
 use chain_core::tx::data::TxId;
@@ -49,6 +51,71 @@ pub trait WalletTransactionBuilder: Send + Sync + Clone {
         attributes: TxAttributes,
     ) -> Result<(TxAux, Vec<TxoPointer>, Coin)>;
 
+    /// Builds a transfer transaction which can also spend the outputs of multi-sig (m > 1)
+    /// addresses of the wallet, through the spending path of the available co-signers (see
+    /// `WalletSignerManager::select_spend_paths`). The outputs of multi-sig addresses the
+    /// co-signers can't spend aren't selected.
+    ///
+    /// # Attributes
+    ///
+    /// - `name`: Name of wallet
+    /// - `enckey`: Encryption key of wallet
+    /// - `unspent_transactions`: Unspent transactions
+    /// - `outputs`: Transaction outputs
+    /// - `return_address`: Address to which change amount will get returned
+    /// - `attributes`: Transaction attributes,
+    /// - `signer_public_keys`: Public keys of the available co-signers (including the wallet's)
+    ///
+    /// # return
+    /// - `RawTransferTransaction`: transaction with the inputs of single key addresses signed,
+    ///   the multi-sig ones are signed with `add_cosigners_signature`
+    #[allow(clippy::too_many_arguments)]
+    fn build_multisig_transfer_tx(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        unspent_transactions: UnspentTransactions,
+        outputs: Vec<TxOut>,
+        return_address: ExtendedAddr,
+        attributes: TxAttributes,
+        signer_public_keys: &[PublicKey],
+    ) -> Result<RawTransferTransaction>;
+
+    /// Adds the combined signature of co-signers (for the id of the transaction) to the
+    /// multi-sig inputs of given raw transaction it is valid for
+    fn add_cosigners_signature(
+        &self,
+        raw_transaction: RawTransferTransaction,
+        signature: SchnorrSignature,
+    ) -> Result<RawTransferTransaction>;
+
+    /// Obfuscates given raw transaction, once all of its inputs are signed
+    fn obfuscate_raw_transfer_tx(&self, raw_transaction: RawTransferTransaction) -> Result<TxAux>;
+
+    /// Builds a transaction spending a hash-time-locked output to given address (the whole
+    /// value, minus fee), claiming it with the preimage of its hash lock or refunding it
+    ///
+    /// # Attributes
+    ///
+    /// - `name`: Name of wallet
+    /// - `enckey`: Encryption key of wallet
+    /// - `input`: Hash-time-locked output to spend
+    /// - `condition`: Revealed condition of the address of the output
+    /// - `spend`: Branch of the condition to take (the wallet signs with a key of its root)
+    /// - `to_address`: Address receiving the output
+    /// - `attributes`: Transaction attributes
+    #[allow(clippy::too_many_arguments)]
+    fn build_htlc_spend_tx(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        input: (TxoPointer, TxOut),
+        condition: HtlcCondition,
+        spend: HtlcSpend,
+        to_address: ExtendedAddr,
+        attributes: TxAttributes,
+    ) -> Result<TxAux>;
+
     /// Obfuscates given signed transaction
     fn obfuscate(&self, signed_transaction: SignedTransaction) -> Result<TxAux>;
 
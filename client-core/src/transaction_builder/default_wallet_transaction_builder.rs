@@ -1,17 +1,23 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chain_core::common::Proof;
 use chain_core::init::coin::{sum_coins, Coin};
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::htlc::{HtlcCondition, HtlcSpend};
 use chain_core::tx::data::input::TxoPointer;
 use chain_core::tx::data::output::TxOut;
 use chain_core::tx::fee::FeeAlgorithm;
+use chain_core::tx::witness::tree::RawXOnlyPubkey;
 use chain_core::tx::TxAux;
 use client_common::{
-    ErrorKind, PrivateKey, Result, ResultExt, SecKey, SignedTransaction, Storage, Transaction,
-    TransactionObfuscation,
+    Error, ErrorKind, PrivateKey, PublicKey, Result, ResultExt, SecKey, SignedTransaction, Storage,
+    Transaction, TransactionObfuscation,
 };
+use secp256k1::schnorrsig::SchnorrSignature;
 
 use crate::signer::WalletSignerManager;
-use crate::transaction_builder::RawTransferTransactionBuilder;
+use crate::transaction_builder::{RawTransferTransaction, RawTransferTransactionBuilder};
 use crate::{SelectedUnspentTransactions, UnspentTransactions, WalletTransactionBuilder};
 use chain_core::tx::data::TxId;
 
@@ -47,7 +53,6 @@ where
     O: TransactionObfuscation,
 {
     /// FIXME: temporary for broken fee estimation
-    #[allow(clippy::too_many_arguments)]
     fn build_transfer_tx_ex(
         &self,
         name: &str,
@@ -56,15 +61,14 @@ where
         outputs: Vec<TxOut>,
         return_address: ExtendedAddr,
         attributes: TxAttributes,
-        // FIXME: this should be per unspent_transactions
-        threshold: u16,
     ) -> Result<(TxAux, Vec<TxoPointer>, Coin)> {
+        // multi-sig inputs can't be signed by a single wallet, see `select_and_build`
         let mut raw_builder = self.select_and_build(
             &unspent_transactions,
             outputs,
             return_address.clone(),
             attributes,
-            threshold,
+            &BTreeMap::new(),
         )?;
 
         let selected_inputs: Vec<TxoPointer> = raw_builder
@@ -111,10 +115,124 @@ where
             outputs,
             return_address,
             attributes,
-            1,
         )
     }
 
+    fn build_multisig_transfer_tx(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        mut unspent_transactions: UnspentTransactions,
+        outputs: Vec<TxOut>,
+        return_address: ExtendedAddr,
+        attributes: TxAttributes,
+        signer_public_keys: &[PublicKey],
+    ) -> Result<RawTransferTransaction> {
+        let addresses: BTreeSet<ExtendedAddr> = unspent_transactions
+            .iter()
+            .map(|(_, output)| output.address.clone())
+            .collect();
+        let mut spend_paths = BTreeMap::new();
+        let mut unspendable = BTreeSet::new();
+        for address in addresses {
+            match self.signer_manager.select_spend_paths(
+                name,
+                enckey,
+                std::iter::once(&address),
+                signer_public_keys,
+            ) {
+                Ok(paths) => spend_paths.extend(paths),
+                // not enough of the co-signers of the multi-sig address are available
+                Err(e) if e.kind() == ErrorKind::InvalidInput => {
+                    unspendable.insert(address);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        unspent_transactions.retain(|(_, output)| !unspendable.contains(&output.address));
+
+        let mut raw_builder = self.select_and_build(
+            &unspent_transactions,
+            outputs,
+            return_address,
+            attributes,
+            &spend_paths,
+        )?;
+
+        let signer =
+            self.signer_manager
+                .create_signer(name, enckey, &self.signer_manager.hw_key_service);
+        raw_builder.sign_all(signer)?;
+
+        Ok(raw_builder.into_raw())
+    }
+
+    fn add_cosigners_signature(
+        &self,
+        raw_transaction: RawTransferTransaction,
+        signature: SchnorrSignature,
+    ) -> Result<RawTransferTransaction> {
+        let mut raw_builder =
+            RawTransferTransactionBuilder::from_raw(raw_transaction, self.fee_algorithm.clone());
+        raw_builder.add_cosigners_signature(signature)?;
+
+        Ok(raw_builder.into_raw())
+    }
+
+    fn obfuscate_raw_transfer_tx(&self, raw_transaction: RawTransferTransaction) -> Result<TxAux> {
+        RawTransferTransactionBuilder::from_raw(raw_transaction, self.fee_algorithm.clone())
+            .to_tx_aux(self.transaction_obfuscation.clone())
+    }
+
+    fn build_htlc_spend_tx(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        input: (TxoPointer, TxOut),
+        condition: HtlcCondition,
+        spend: HtlcSpend,
+        to_address: ExtendedAddr,
+        attributes: TxAttributes,
+    ) -> Result<TxAux> {
+        let value = input.1.value;
+        let mut fees = Coin::zero();
+        let mut raw_builder = loop {
+            let mut raw_builder =
+                RawTransferTransactionBuilder::new(attributes.clone(), self.fee_algorithm.clone());
+            raw_builder.add_htlc_input(input.clone(), condition.clone(), spend.clone())?;
+            let amount = (value - fees).chain(|| {
+                (
+                    ErrorKind::IllegalInput,
+                    "Value of hash-time-locked output does not cover the fee",
+                )
+            })?;
+            raw_builder.add_output(TxOut::new(to_address.clone(), amount));
+
+            let new_fees = raw_builder.estimate_fee()?;
+            if new_fees > fees {
+                fees = new_fees;
+            } else {
+                break raw_builder;
+            }
+        };
+
+        let signer =
+            self.signer_manager
+                .create_signer(name, enckey, &self.signer_manager.hw_key_service);
+        raw_builder.sign_all(signer)?;
+        if !raw_builder.is_completed() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Wallet with name {} does not have a key to spend the hash-time-locked output",
+                    name
+                ),
+            ));
+        }
+
+        raw_builder.to_tx_aux(self.transaction_obfuscation.clone())
+    }
+
     #[inline]
     fn obfuscate(&self, signed_transaction: SignedTransaction) -> Result<TxAux> {
         self.transaction_obfuscation.encrypt(signed_transaction)
@@ -154,14 +272,18 @@ where
 
     /// Create a `DummySigner` which signs a transaction with dummy values for fees calculation.
     /// Returns a result of unsigned raw transfer transaction builder
+    ///
+    /// Inputs of the addresses in `spend_paths` (see `WalletSignerManager::select_spend_paths`)
+    /// are added as multi-sig inputs spent through the given proof, so that their fee is estimated
+    /// with the exact witness size; their signatures have to be completed by the co-signers.
+    /// Other inputs are expected to be signed with a single key.
     pub fn select_and_build<'a>(
         &self,
         unspent_transactions: &'a UnspentTransactions,
         outputs: Vec<TxOut>,
         return_address: ExtendedAddr,
         attributes: TxAttributes,
-        // FIXME: this should be per UnspentTransactions
-        threshold: u16,
+        spend_paths: &BTreeMap<ExtendedAddr, Proof<RawXOnlyPubkey>>,
     ) -> Result<RawTransferTransactionBuilder<F>> {
         let output_value = sum_coins(outputs.iter().map(|output| output.value)).chain(|| {
             (
@@ -184,8 +306,8 @@ where
                 return_address.clone(),
                 change_amount,
                 attributes.clone(),
-                threshold,
-            );
+                spend_paths,
+            )?;
 
             let new_fees = raw_tx_builder.estimate_fee()?;
             if new_fees > fees {
@@ -205,13 +327,15 @@ where
         return_address: ExtendedAddr,
         change_amount: Coin,
         attributes: TxAttributes,
-        // FIXME: this should be per SelectedUnspentTransactions
-        threshold: u16,
-    ) -> RawTransferTransactionBuilder<F> {
+        spend_paths: &BTreeMap<ExtendedAddr, Proof<RawXOnlyPubkey>>,
+    ) -> Result<RawTransferTransactionBuilder<F>> {
         let mut raw_tx_builder =
             RawTransferTransactionBuilder::new(attributes, self.fee_algorithm.clone());
         for input in selected_unspent_transactions.iter() {
-            raw_tx_builder.add_input(input.clone(), threshold);
+            match spend_paths.get(&input.1.address) {
+                Some(proof) => raw_tx_builder.add_multisig_input(input.clone(), proof.clone())?,
+                None => raw_tx_builder.add_input(input.clone(), 1),
+            }
         }
         for output in outputs.iter() {
             raw_tx_builder.add_output(output.clone());
@@ -220,7 +344,7 @@ where
             raw_tx_builder.add_output(TxOut::new(return_address, change_amount));
         }
 
-        raw_tx_builder
+        Ok(raw_tx_builder)
     }
 }
 
@@ -234,6 +358,7 @@ mod default_wallet_transaction_builder_tests {
     use chain_core::tx::data::input::{TxoPointer, TxoSize};
     use chain_core::tx::data::TxId;
     use chain_core::tx::fee::{LinearFee, Milli};
+    use chain_core::tx::witness::TxInWitness;
     use chain_core::tx::{PlainTxAux, TransactionId, TxAux, TxEnclaveAux, TxObfuscated};
     use chain_tx_validation::witness::verify_tx_address;
     use client_common::storage::MemoryStorage;
@@ -363,7 +488,6 @@ mod default_wallet_transaction_builder_tests {
                 outputs,
                 return_address,
                 attributes,
-                2,
             )
             .unwrap();
 
@@ -419,6 +543,441 @@ mod default_wallet_transaction_builder_tests {
         }
     }
 
+    #[test]
+    fn check_htlc_spend_building_flow() {
+        let name = "name";
+        let passphrase = SecUtf8::from("passphrase");
+
+        let storage = MemoryStorage::default();
+        let wallet_client = DefaultWalletClient::new_read_only(storage.clone());
+
+        let (enckey, _) = wallet_client
+            .new_wallet(
+                name,
+                &passphrase,
+                WalletKind::Basic,
+                HardwareKind::LocalOnly,
+                None,
+            )
+            .unwrap();
+
+        let root_hash = |address: ExtendedAddr| match address {
+            ExtendedAddr::OrTree(root_hash) => root_hash,
+            _ => unreachable!(),
+        };
+        let claim_root = root_hash(wallet_client.new_transfer_address(name, &enckey).unwrap());
+        let refund_root = root_hash(wallet_client.new_transfer_address(name, &enckey).unwrap());
+        let condition = HtlcCondition::new(&[0x42; 32], 100, claim_root, refund_root);
+        let input = (
+            TxoPointer::new([0; 32], 0),
+            TxOut::new(condition.address(), Coin::new(1000).unwrap()),
+        );
+        let to_address = wallet_client.new_transfer_address(name, &enckey).unwrap();
+
+        let signer_manager = WalletSignerManager::new(storage.clone(), HwKeyService::default());
+        let fee_algorithm =
+            LinearFee::new(Milli::try_new(1, 1).unwrap(), Milli::try_new(1, 1).unwrap());
+        let transaction_builder = DefaultWalletTransactionBuilder::new(
+            signer_manager,
+            fee_algorithm,
+            MockTransactionCipher,
+        );
+
+        for spend in &[HtlcSpend::Claim([0x42; 32]), HtlcSpend::Refund] {
+            let tx_aux = transaction_builder
+                .build_htlc_spend_tx(
+                    name,
+                    &enckey,
+                    input.clone(),
+                    condition.clone(),
+                    spend.clone(),
+                    to_address.clone(),
+                    TxAttributes::new(171),
+                )
+                .unwrap();
+            let fee = fee_algorithm
+                .calculate_for_txaux(&tx_aux)
+                .unwrap()
+                .to_coin();
+
+            match tx_aux {
+                TxAux::EnclaveTx(TxEnclaveAux::TransferTx {
+                    payload: TxObfuscated { txpayload, .. },
+                    ..
+                }) => {
+                    let (transaction, witness) =
+                        match PlainTxAux::decode(&mut txpayload.as_slice()).unwrap() {
+                            PlainTxAux::TransferTx(transaction, witness) => (transaction, witness),
+                            _ => unreachable!(),
+                        };
+                    assert_eq!(vec![input.0.clone()], transaction.inputs);
+                    assert_eq!(to_address, transaction.outputs[0].address);
+                    assert!((transaction.outputs[0].value + fee).unwrap() <= input.1.value);
+                    match witness[0] {
+                        TxInWitness::HashTimeLock(_, ref witness_spend, ..) => {
+                            assert_eq!(*spend, *witness_spend)
+                        }
+                        _ => unreachable!(),
+                    }
+                    assert!(
+                        verify_tx_address(&witness[0], &transaction.id(), &input.1.address).is_ok()
+                    );
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        // incorrect preimage
+        assert!(transaction_builder
+            .build_htlc_spend_tx(
+                name,
+                &enckey,
+                input.clone(),
+                condition.clone(),
+                HtlcSpend::Claim([0x43; 32]),
+                to_address.clone(),
+                TxAttributes::new(171),
+            )
+            .is_err());
+
+        // no key of the wallet can refund
+        let condition = HtlcCondition::new(&[0x42; 32], 100, claim_root, [0; 32]);
+        assert!(transaction_builder
+            .build_htlc_spend_tx(
+                name,
+                &enckey,
+                (
+                    TxoPointer::new([0; 32], 0),
+                    TxOut::new(condition.address(), Coin::new(1000).unwrap()),
+                ),
+                condition,
+                HtlcSpend::Refund,
+                to_address,
+                TxAttributes::new(171),
+            )
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "experimental")]
+    fn check_mixed_single_key_and_multisig_inputs_flow() {
+        use client_common::multi_sig_address::combine_to_raw_pubkey;
+
+        let name = "name";
+        let passphrase = SecUtf8::from("passphrase");
+
+        let storage = MemoryStorage::default();
+        let wallet_client = DefaultWalletClient::new_read_only(storage.clone());
+
+        let (enckey, _) = wallet_client
+            .new_wallet(
+                name,
+                &passphrase,
+                WalletKind::Basic,
+                HardwareKind::LocalOnly,
+                None,
+            )
+            .unwrap();
+
+        let public_keys = vec![
+            wallet_client.new_public_key(name, &enckey, None).unwrap(),
+            wallet_client.new_public_key(name, &enckey, None).unwrap(),
+            wallet_client.new_public_key(name, &enckey, None).unwrap(),
+        ];
+
+        let single_key_address = wallet_client.new_transfer_address(name, &enckey).unwrap();
+        let multisig_address = wallet_client
+            .new_multisig_transfer_address(
+                name,
+                &enckey,
+                public_keys.clone(),
+                public_keys[0].clone(),
+                2,
+            )
+            .unwrap();
+
+        let unspent_transactions = UnspentTransactions::new(vec![
+            (
+                TxoPointer::new([0; 32], 0),
+                TxOut::new(single_key_address, Coin::new(500).unwrap()),
+            ),
+            (
+                TxoPointer::new([1; 32], 0),
+                TxOut::new(multisig_address.clone(), Coin::new(1250).unwrap()),
+            ),
+        ]);
+
+        let signer_manager = WalletSignerManager::new(storage.clone(), HwKeyService::default());
+        let fee_algorithm =
+            LinearFee::new(Milli::try_new(1, 1).unwrap(), Milli::try_new(1, 1).unwrap());
+        let transaction_builder = DefaultWalletTransactionBuilder::new(
+            signer_manager.clone(),
+            fee_algorithm,
+            MockTransactionCipher,
+        );
+
+        // only the co-signers of the second and third keys are available
+        let spend_paths = signer_manager
+            .select_spend_paths(
+                name,
+                &enckey,
+                unspent_transactions
+                    .iter()
+                    .map(|(_, output)| &output.address),
+                &public_keys[1..],
+            )
+            .unwrap();
+        assert_eq!(spend_paths.len(), 1);
+        let proof = spend_paths[&multisig_address].clone();
+        let mut available_signers = public_keys[1..].to_vec();
+        available_signers.sort();
+        assert_eq!(
+            *proof.value(),
+            combine_to_raw_pubkey(&available_signers).unwrap()
+        );
+
+        let outputs = vec![TxOut::new(
+            wallet_client.new_transfer_address(name, &enckey).unwrap(),
+            Coin::new(1000).unwrap(),
+        )];
+        let return_address = wallet_client.new_transfer_address(name, &enckey).unwrap();
+        let mut raw_builder = transaction_builder
+            .select_and_build(
+                &unspent_transactions,
+                outputs,
+                return_address,
+                TxAttributes::new(171),
+                &spend_paths,
+            )
+            .unwrap();
+        assert_eq!(raw_builder.inputs_len(), 2);
+
+        raw_builder
+            .sign_all(signer_manager.create_signer(name, &enckey, &HwKeyService::default()))
+            .unwrap();
+        for input in raw_builder.iter_inputs() {
+            if input.prev_tx_out.address == multisig_address {
+                assert_eq!(input.spend_proof, Some(proof.clone()));
+                assert!(!input.has_witness());
+            } else {
+                assert!(input.spend_proof.is_none());
+                assert!(input.has_witness());
+            }
+        }
+
+        // not enough co-signers to spend the multi-sig address
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            signer_manager
+                .select_spend_paths(
+                    name,
+                    &enckey,
+                    unspent_transactions
+                        .iter()
+                        .map(|(_, output)| &output.address),
+                    &public_keys[..1],
+                )
+                .unwrap_err()
+                .kind()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "experimental")]
+    fn check_multisig_transfer_with_cosigners_signature_flow() {
+        use crate::service::{WalletStateMemento, WalletStateService};
+        use crate::MultiSigWalletClient;
+        use client_common::tendermint::UnauthorizedClient;
+        use std::str::FromStr;
+
+        let name = "name";
+        let passphrase = SecUtf8::from("passphrase");
+
+        let storage = MemoryStorage::default();
+        let signer_manager = WalletSignerManager::new(storage.clone(), HwKeyService::default());
+        let fee_algorithm =
+            LinearFee::new(Milli::try_new(1, 1).unwrap(), Milli::try_new(1, 1).unwrap());
+        let transaction_builder = DefaultWalletTransactionBuilder::new(
+            signer_manager,
+            fee_algorithm,
+            MockTransactionCipher,
+        );
+        let wallet_client = DefaultWalletClient::new(
+            storage.clone(),
+            UnauthorizedClient,
+            transaction_builder,
+            None,
+            HwKeyService::default(),
+        );
+
+        let (enckey, _) = wallet_client
+            .new_wallet(
+                name,
+                &passphrase,
+                WalletKind::Basic,
+                HardwareKind::LocalOnly,
+                None,
+            )
+            .unwrap();
+
+        let public_keys = vec![
+            wallet_client.new_public_key(name, &enckey, None).unwrap(),
+            wallet_client.new_public_key(name, &enckey, None).unwrap(),
+            wallet_client.new_public_key(name, &enckey, None).unwrap(),
+        ];
+
+        let single_key_address = wallet_client.new_transfer_address(name, &enckey).unwrap();
+        let multisig_address = wallet_client
+            .new_multisig_transfer_address(
+                name,
+                &enckey,
+                public_keys.clone(),
+                public_keys[0].clone(),
+                2,
+            )
+            .unwrap();
+        // needs the first key, whose holder does not take part in signing
+        let unspendable_address = wallet_client
+            .new_multisig_transfer_address(
+                name,
+                &enckey,
+                public_keys[..2].to_vec(),
+                public_keys[0].clone(),
+                2,
+            )
+            .unwrap();
+
+        let mut memento = WalletStateMemento::default();
+        memento.add_unspent_transaction(
+            TxoPointer::new([0; 32], 0),
+            TxOut::new(single_key_address.clone(), Coin::new(500).unwrap()),
+        );
+        memento.add_unspent_transaction(
+            TxoPointer::new([1; 32], 0),
+            TxOut::new(multisig_address.clone(), Coin::new(1250).unwrap()),
+        );
+        memento.add_unspent_transaction(
+            TxoPointer::new([2; 32], 0),
+            TxOut::new(unspendable_address.clone(), Coin::new(5000).unwrap()),
+        );
+        WalletStateService::new(storage)
+            .apply_memento(name, &enckey, &memento)
+            .unwrap();
+
+        let outputs = vec![TxOut::new(
+            wallet_client.new_transfer_address(name, &enckey).unwrap(),
+            Coin::new(1000).unwrap(),
+        )];
+        let return_address = wallet_client.new_transfer_address(name, &enckey).unwrap();
+        let signers = public_keys[1..].to_vec();
+        let raw_transaction = wallet_client
+            .create_multisig_transfer_transaction(
+                name,
+                &enckey,
+                outputs,
+                TxAttributes::new(171),
+                None,
+                return_address,
+                signers.clone(),
+            )
+            .unwrap();
+
+        assert_eq!(raw_transaction.inputs().len(), 2);
+        for input in raw_transaction.inputs() {
+            assert_ne!(input.prev_tx_out.address, unspendable_address);
+            assert_eq!(
+                input.witness.is_some(),
+                input.prev_tx_out.address == single_key_address
+            );
+        }
+        // the multi-sig input is not signed yet
+        assert!(wallet_client
+            .multisig_transfer_transaction(raw_transaction.clone())
+            .is_err());
+
+        // co-signers sign the transaction id in a multi-sig session
+        let transaction_id = raw_transaction.id();
+        let sessions = signers
+            .iter()
+            .map(|public_key| {
+                wallet_client
+                    .new_multi_sig_session(
+                        name,
+                        &enckey,
+                        transaction_id,
+                        signers.clone(),
+                        public_key.clone(),
+                    )
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let (session_1, session_2) = (&sessions[0], &sessions[1]);
+
+        let nonce_commitment_1 = wallet_client.nonce_commitment(session_1, &enckey).unwrap();
+        let nonce_commitment_2 = wallet_client.nonce_commitment(session_2, &enckey).unwrap();
+        wallet_client
+            .add_nonce_commitment(session_1, &enckey, nonce_commitment_2, &signers[1])
+            .unwrap();
+        wallet_client
+            .add_nonce_commitment(session_2, &enckey, nonce_commitment_1, &signers[0])
+            .unwrap();
+
+        let nonce_1 = wallet_client.nonce(session_1, &enckey).unwrap();
+        let nonce_2 = wallet_client.nonce(session_2, &enckey).unwrap();
+        wallet_client
+            .add_nonce(session_1, &enckey, &nonce_2, &signers[1])
+            .unwrap();
+        wallet_client
+            .add_nonce(session_2, &enckey, &nonce_1, &signers[0])
+            .unwrap();
+
+        let partial_signature_1 = wallet_client.partial_signature(session_1, &enckey).unwrap();
+        let partial_signature_2 = wallet_client.partial_signature(session_2, &enckey).unwrap();
+        wallet_client
+            .add_partial_signature(session_1, &enckey, partial_signature_2, &signers[1])
+            .unwrap();
+        wallet_client
+            .add_partial_signature(session_2, &enckey, partial_signature_1, &signers[0])
+            .unwrap();
+
+        let signature = wallet_client.signature(session_1, &enckey).unwrap();
+        let raw_transaction = RawTransferTransaction::from_str(
+            &wallet_client
+                .add_cosigners_signature(raw_transaction, signature)
+                .unwrap()
+                .to_string(),
+        )
+        .unwrap();
+        assert!(raw_transaction
+            .inputs()
+            .iter()
+            .all(|input| input.witness.is_some()));
+
+        let tx_aux = wallet_client
+            .multisig_transfer_transaction(raw_transaction)
+            .unwrap();
+        match tx_aux {
+            TxAux::EnclaveTx(TxEnclaveAux::TransferTx {
+                payload: TxObfuscated { txpayload, .. },
+                ..
+            }) => match PlainTxAux::decode(&mut txpayload.as_slice()).unwrap() {
+                PlainTxAux::TransferTx(transaction, witness) => {
+                    assert_eq!(transaction.id(), transaction_id);
+                    for (i, input) in transaction.inputs.iter().enumerate() {
+                        let address = if input.id == [0; 32] {
+                            &single_key_address
+                        } else {
+                            &multisig_address
+                        };
+                        assert!(verify_tx_address(&witness[i], &transaction_id, address).is_ok());
+                    }
+                }
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+
     #[test]
     fn check_insufficient_balance_flow() {
         let name = "name";
@@ -5,13 +5,17 @@ use std::str::FromStr;
 use std::string::ToString;
 
 use parity_scale_codec::{Decode, Encode};
+use secp256k1::schnorrsig::SchnorrSignature;
 
+use chain_core::common::Proof;
 use chain_core::init::coin::{sum_coins, Coin};
 use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::htlc::{HtlcCondition, HtlcSpend};
 use chain_core::tx::data::input::TxoPointer;
 use chain_core::tx::data::output::TxOut;
 use chain_core::tx::data::{Tx, TxId};
 use chain_core::tx::fee::FeeAlgorithm;
+use chain_core::tx::witness::tree::RawXOnlyPubkey;
 use chain_core::tx::witness::{TxInWitness, TxWitness};
 use chain_core::tx::{TransactionId, TxAux};
 use chain_tx_validation::witness::verify_tx_address;
@@ -37,6 +41,12 @@ pub struct WitnessedUTxO {
     pub witness: Option<TxInWitness>,
     /// max options -- for determining the merkle proof size estimate in the witness, e.g. 2-of-3 threshold has 3 leaves (each is combined 2-of-2 pubkey)
     pub threshold: u16,
+    /// selected spending path of multi-sig input, used for signing and estimating the exact
+    /// witness size (`threshold` is only used when it is not set)
+    pub spend_proof: Option<Proof<RawXOnlyPubkey>>,
+    /// revealed condition and branch of hash-time-locked input, which is signed with a key of
+    /// the claim (or refund) root of the condition
+    pub htlc: Option<(HtlcCondition, HtlcSpend)>,
 }
 
 impl WitnessedUTxO {
@@ -52,6 +62,29 @@ impl WitnessedUTxO {
             prev_tx_out: TxOut::new(ExtendedAddr::OrTree([0u8; 32]), Default::default()),
             witness: None,
             threshold: 1,
+            spend_proof: None,
+            htlc: None,
+        }
+    }
+
+    /// Returns the address whose keys sign the input (the claim or refund root of
+    /// hash-time-locked input)
+    pub fn signing_address(&self) -> ExtendedAddr {
+        match self.htlc {
+            Some((ref condition, ref spend)) => {
+                ExtendedAddr::OrTree(*condition.spending_root(spend))
+            }
+            None => self.prev_tx_out.address.clone(),
+        }
+    }
+
+    /// Wraps the signature of the signing address into the witness of the input
+    pub(crate) fn wrap_witness(&self, witness: TxInWitness) -> TxInWitness {
+        match (&self.htlc, witness) {
+            (Some((condition, spend)), TxInWitness::TreeSig(signature, proof)) => {
+                TxInWitness::HashTimeLock(condition.clone(), spend.clone(), signature, proof)
+            }
+            (_, witness) => witness,
         }
     }
 }
@@ -132,6 +165,62 @@ pub struct RawTransferTransaction {
     attributes: TxAttributes,
 }
 
+impl RawTransferTransaction {
+    /// Returns the id of transfer transaction (the message its inputs are signed for)
+    pub fn id(&self) -> TxId {
+        self.to_tx().id()
+    }
+
+    /// Returns the inputs of raw transaction
+    pub fn inputs(&self) -> &[WitnessedUTxO] {
+        &self.inputs
+    }
+
+    /// Returns the outputs of raw transaction
+    pub fn outputs(&self) -> &[TxOut] {
+        &self.outputs
+    }
+
+    fn to_tx(&self) -> Tx {
+        Tx {
+            inputs: self
+                .inputs
+                .iter()
+                .map(|input| input.prev_txo_pointer.clone())
+                .collect(),
+            outputs: self.outputs.clone(),
+            attributes: self.attributes.clone(),
+        }
+    }
+}
+
+impl ToString for RawTransferTransaction {
+    fn to_string(&self) -> String {
+        let raw_data = self.encode();
+        base64::encode(&raw_data)
+    }
+}
+
+impl FromStr for RawTransferTransaction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let raw_data = base64::decode(s.trim()).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to decode base64 raw transaction",
+            )
+        })?;
+        let tx = Self::decode(&mut raw_data.as_slice()).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to deserialize raw transaction",
+            )
+        })?;
+        Ok(tx)
+    }
+}
+
 /// Raw transfer transaction builder
 #[derive(Debug)]
 pub struct RawTransferTransactionBuilder<F>
@@ -160,6 +249,20 @@ where
         }
     }
 
+    /// Create an instance of raw transfer transaction builder from a raw transaction
+    /// (e.g. a partially signed one)
+    pub fn from_raw(raw_transaction: RawTransferTransaction, fee_algorithm: F) -> Self {
+        RawTransferTransactionBuilder {
+            raw_transaction,
+            fee_algorithm,
+        }
+    }
+
+    /// Returns the raw transaction, e.g. to pass it to co-signers of its multi-sig inputs
+    pub fn into_raw(self) -> RawTransferTransaction {
+        self.raw_transaction
+    }
+
     /// Create an iterator over inputs
     pub fn iter_inputs(&self) -> Iter<WitnessedUTxO> {
         self.raw_transaction.inputs.iter()
@@ -180,11 +283,91 @@ where
             prev_tx_out: input.1,
             threshold,
             witness: None,
+            spend_proof: None,
+            htlc: None,
         });
 
         self.clear_witness();
     }
 
+    /// Append multi-sig input to raw transaction, which is going to be spent with the
+    /// combined public key of given proof
+    /// # Warning
+    /// When a new input is appended, any previous witness will be cleared
+    /// because transaction id will be changed
+    pub fn add_multisig_input(
+        &mut self,
+        input: (TxoPointer, TxOut),
+        spend_proof: Proof<RawXOnlyPubkey>,
+    ) -> Result<()> {
+        let root_hash = match input.1.address {
+            ExtendedAddr::OrTree(root_hash) => root_hash,
+            ExtendedAddr::HashTimeLock(_) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Hash-time-locked input cannot be spent as multi-sig input",
+                ))
+            }
+        };
+        if !spend_proof.verify(&root_hash) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Spending path does not belong to the address of input",
+            ));
+        }
+
+        self.raw_transaction.inputs.push(WitnessedUTxO {
+            prev_txo_pointer: input.0,
+            prev_tx_out: input.1,
+            threshold: 1,
+            witness: None,
+            spend_proof: Some(spend_proof),
+            htlc: None,
+        });
+
+        self.clear_witness();
+        Ok(())
+    }
+
+    /// Append hash-time-locked input to raw transaction, which is going to be claimed (with the
+    /// preimage of its hash lock) or refunded with a key of the corresponding root of `condition`
+    /// # Warning
+    /// When a new input is appended, any previous witness will be cleared
+    /// because transaction id will be changed
+    pub fn add_htlc_input(
+        &mut self,
+        input: (TxoPointer, TxOut),
+        condition: HtlcCondition,
+        spend: HtlcSpend,
+    ) -> Result<()> {
+        if input.1.address != condition.address() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Hash-time-locked condition does not belong to the address of input",
+            ));
+        }
+        if let HtlcSpend::Claim(ref preimage) = spend {
+            if !condition.check_preimage(preimage) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Preimage does not match the hash lock of input",
+                ));
+            }
+        }
+
+        self.raw_transaction.inputs.push(WitnessedUTxO {
+            prev_txo_pointer: input.0,
+            prev_tx_out: input.1,
+            threshold: 1,
+            witness: None,
+            spend_proof: None,
+            htlc: Some((condition, spend)),
+        });
+
+        self.clear_witness();
+        Ok(())
+    }
+
     /// Append output to raw transaction
     /// # Warning
     /// When a new output is appended, any previous witness will be cleared
@@ -238,7 +421,8 @@ where
         Ok(&self.raw_transaction.outputs[index])
     }
 
-    /// Sign all signable inputs with signer
+    /// Sign all signable inputs with signer, except the multi-sig inputs which are signed with
+    /// `add_multisig_signature`
     pub fn sign_all<S>(&mut self, signer: S) -> Result<()>
     where
         S: Signer,
@@ -248,12 +432,17 @@ where
             .iter_inputs()
             .enumerate()
             .map(|(i, input)| {
-                let signing_addr = &input.prev_tx_out.address;
-                if SignCondition::SingleSignUnlock != signer.schnorr_sign_condition(signing_addr)? {
+                if input.spend_proof.is_some() {
+                    return Ok(None);
+                }
+                let signing_addr = input.signing_address();
+                if SignCondition::SingleSignUnlock
+                    != signer.schnorr_sign_condition(&signing_addr)?
+                {
                     return Ok(None);
                 }
 
-                let witness = signer.schnorr_sign(&tx, signing_addr)?;
+                let witness = input.wrap_witness(signer.schnorr_sign(&tx, &signing_addr)?);
 
                 Ok(Some((i, witness)))
             })
@@ -287,6 +476,52 @@ where
         Ok(())
     }
 
+    /// Add combined multi-sig signature to provided input index, using the spending path
+    /// selected when the input was added
+    pub fn add_multisig_signature(
+        &mut self,
+        index: usize,
+        signature: SchnorrSignature,
+    ) -> Result<()> {
+        let proof = self.input_at_index(index)?.spend_proof.clone().chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                "Input does not have a multi-sig spending path",
+            )
+        })?;
+
+        self.add_witness(index, TxInWitness::TreeSig(signature, proof))
+    }
+
+    /// Add combined multi-sig signature to all unsigned multi-sig inputs whose spending path
+    /// it is valid for (the inputs spent with the same path share the signature, as they sign
+    /// the same transaction id with the same combined key)
+    ///
+    /// # Return
+    /// the number of inputs signed
+    pub fn add_cosigners_signature(&mut self, signature: SchnorrSignature) -> Result<usize> {
+        let unsigned_inputs: Vec<usize> = self
+            .iter_inputs()
+            .enumerate()
+            .filter(|(_, input)| input.spend_proof.is_some() && !input.has_witness())
+            .map(|(index, _)| index)
+            .collect();
+        let mut signed = 0;
+        for index in unsigned_inputs {
+            if self.add_multisig_signature(index, signature).is_ok() {
+                signed += 1;
+            }
+        }
+        if signed == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Signature is not valid for the spending path of any unsigned multi-sig input",
+            ));
+        }
+
+        Ok(signed)
+    }
+
     /// Get mutable input at provided index
     fn mut_input_at_index(&mut self, index: usize) -> Result<&mut WitnessedUTxO> {
         if self.inputs_len() < index {
@@ -508,14 +743,7 @@ where
     }
 
     fn to_tx(&self) -> Tx {
-        Tx {
-            inputs: self
-                .iter_inputs()
-                .map(|input| input.prev_txo_pointer.clone())
-                .collect(),
-            outputs: self.raw_transaction.outputs.clone(),
-            attributes: self.raw_transaction.attributes.clone(),
-        }
+        self.raw_transaction.to_tx()
     }
 
     /// Returns  transaction
@@ -982,6 +1210,126 @@ mod raw_transfer_transaction_builder_tests {
         }
     }
 
+    mod multisig_input {
+        use super::*;
+
+        use client_common::PrivateKeyAction;
+
+        /// Creates 1-of-3 address of given key and two random ones, returns the address and the
+        /// spending path of given key
+        fn create_one_of_three_addr(
+            public_key: &PublicKey,
+        ) -> (ExtendedAddr, Proof<RawXOnlyPubkey>) {
+            let mut public_keys = vec![public_key.clone()];
+            for _ in 0..2 {
+                public_keys.push(PublicKey::from(&PrivateKey::new().unwrap()));
+            }
+            let multi_sig_address = MultiSigAddress::new(public_keys, public_key.clone(), 1)
+                .expect("should create multi sig address");
+            let proof = multi_sig_address
+                .select_proof(&[public_key.clone()])
+                .expect("should select spending path");
+
+            (ExtendedAddr::from(multi_sig_address), proof)
+        }
+
+        #[test]
+        fn should_return_error_when_spend_proof_does_not_belong_to_input() {
+            let (_, public_key, transfer_addr) = create_key_pair_and_transfer_addr();
+            let (_, proof) = create_one_of_three_addr(&public_key);
+            let mut builder = create_2in2out_testing_raw_transaction_builder(transfer_addr.clone());
+
+            let err = builder
+                .add_multisig_input(
+                    (
+                        TxoPointer::new(random(), 0),
+                        TxOut::new(transfer_addr, Coin::new(100).unwrap()),
+                    ),
+                    proof,
+                )
+                .unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidInput);
+            assert_eq!(builder.inputs_len(), 2);
+        }
+
+        #[test]
+        fn should_sign_mixed_single_key_and_multisig_inputs() {
+            let (private_key, public_key, transfer_addr) = create_key_pair_and_transfer_addr();
+            let (multisig_addr, proof) = create_one_of_three_addr(&public_key);
+            let mut builder = create_2in2out_testing_raw_transaction_builder(transfer_addr);
+            builder
+                .add_multisig_input(
+                    (
+                        TxoPointer::new(random(), 0),
+                        TxOut::new(multisig_addr, Coin::new(500).unwrap()),
+                    ),
+                    proof.clone(),
+                )
+                .expect("should add multi-sig input");
+
+            // single key inputs can't be signed with a multi-sig signature
+            let tx = builder.to_transaction();
+            let signature = private_key.schnorr_sign(&tx).unwrap();
+            assert_eq!(
+                builder
+                    .add_multisig_signature(0, signature.clone())
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::InvalidInput
+            );
+
+            let key_pair_signer = KeyPairSigner::new(private_key, public_key).unwrap();
+            builder
+                .sign_all(key_pair_signer)
+                .expect("sign_all should work");
+            assert!(!builder.input_at_index(2).unwrap().has_witness());
+
+            builder
+                .add_multisig_signature(2, signature)
+                .expect("should add multi-sig signature");
+            assert!(builder.is_completed());
+            match builder.input_at_index(2).unwrap().witness {
+                Some(TxInWitness::TreeSig(_, ref witness_proof)) => {
+                    assert_eq!(*witness_proof, proof)
+                }
+                _ => unreachable!(),
+            }
+
+            let required_fee = builder.required_fee(MockTransactionCipher).unwrap();
+            let estimated_fee = builder.estimate_fee().unwrap();
+            assert!(estimated_fee >= required_fee);
+        }
+
+        #[test]
+        fn estimate_fee_should_use_selected_spend_path() {
+            let (_, public_key, _) = create_key_pair_and_transfer_addr();
+            let (multisig_addr, proof) = create_one_of_three_addr(&public_key);
+            let input = (
+                TxoPointer::new(random(), 0),
+                TxOut::new(multisig_addr, Coin::new(500).unwrap()),
+            );
+            let output = TxOut::new(ExtendedAddr::OrTree(random()), Coin::new(100).unwrap());
+
+            let mut threshold_builder = RawTransferTransactionBuilder::new(
+                TxAttributes::default(),
+                create_testing_fee_algorithm(),
+            );
+            threshold_builder.add_input(input.clone(), 3);
+            threshold_builder.add_output(output.clone());
+
+            let mut path_builder = RawTransferTransactionBuilder::new(
+                TxAttributes::default(),
+                create_testing_fee_algorithm(),
+            );
+            path_builder.add_multisig_input(input, proof).unwrap();
+            path_builder.add_output(output);
+
+            assert!(
+                path_builder.estimate_fee().unwrap() <= threshold_builder.estimate_fee().unwrap()
+            );
+        }
+    }
+
     mod add_witness {
         use super::*;
 
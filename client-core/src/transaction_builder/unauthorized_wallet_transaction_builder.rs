@@ -1,11 +1,16 @@
 use chain_core::init::coin::Coin;
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::htlc::{HtlcCondition, HtlcSpend};
 use chain_core::tx::data::input::TxoPointer;
 use chain_core::tx::data::output::TxOut;
 use chain_core::tx::TxAux;
-use client_common::{ErrorKind, PrivateKey, Result, SecKey, SignedTransaction, Transaction};
+use client_common::{
+    ErrorKind, PrivateKey, PublicKey, Result, SecKey, SignedTransaction, Transaction,
+};
+use secp256k1::schnorrsig::SchnorrSignature;
 
+use crate::transaction_builder::RawTransferTransaction;
 use crate::{UnspentTransactions, WalletTransactionBuilder};
 use chain_core::tx::data::TxId;
 
@@ -27,6 +32,44 @@ impl WalletTransactionBuilder for UnauthorizedWalletTransactionBuilder {
         Err(ErrorKind::PermissionDenied.into())
     }
 
+    fn build_multisig_transfer_tx(
+        &self,
+        _: &str,
+        _: &SecKey,
+        _: UnspentTransactions,
+        _: Vec<TxOut>,
+        _: ExtendedAddr,
+        _: TxAttributes,
+        _: &[PublicKey],
+    ) -> Result<RawTransferTransaction> {
+        Err(ErrorKind::PermissionDenied.into())
+    }
+
+    fn add_cosigners_signature(
+        &self,
+        _: RawTransferTransaction,
+        _: SchnorrSignature,
+    ) -> Result<RawTransferTransaction> {
+        Err(ErrorKind::PermissionDenied.into())
+    }
+
+    fn obfuscate_raw_transfer_tx(&self, _: RawTransferTransaction) -> Result<TxAux> {
+        Err(ErrorKind::PermissionDenied.into())
+    }
+
+    fn build_htlc_spend_tx(
+        &self,
+        _: &str,
+        _: &SecKey,
+        _: (TxoPointer, TxOut),
+        _: HtlcCondition,
+        _: HtlcSpend,
+        _: ExtendedAddr,
+        _: TxAttributes,
+    ) -> Result<TxAux> {
+        Err(ErrorKind::PermissionDenied.into())
+    }
+
     fn obfuscate(&self, _: SignedTransaction) -> Result<TxAux> {
         Err(ErrorKind::PermissionDenied.into())
     }
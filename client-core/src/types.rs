@@ -2,12 +2,18 @@
 mod address_type;
 mod wallet_type;
 
+pub mod address_book;
+pub mod history;
 pub mod transaction_change;
 
+pub use self::address_book::{AddressBook, BookAddress, TransactionLabel};
 pub use self::address_type::AddressType;
+pub use self::history::{
+    HistoryFilter, HistoryFormat, HistoryRecord, HistoryRecordType, StakingHistoryEntry,
+};
 #[doc(inline)]
 pub use self::transaction_change::{
-    BalanceChange, TransactionChange, TransactionInput, TransactionPending, TransactionType,
-    WalletBalance,
+    BalanceChange, LockedBalance, LockedUntil, TransactionChange, TransactionInput,
+    TransactionPending, TransactionType, WalletBalance,
 };
 pub use self::wallet_type::WalletKind;
@@ -0,0 +1,155 @@
+//! Types for labelling addresses and transactions of a wallet
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use parity_scale_codec::{Decode, Encode};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use chain_core::state::account::StakedStateAddress;
+use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::TxId;
+use client_common::{Error, ErrorKind, Result};
+
+/// Prefix of address book labels when they are used in place of an address (e.g. `@alice`)
+pub const LABEL_PREFIX: char = '@';
+
+/// Address saved in an address book
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum BookAddress {
+    /// Transfer address
+    Transfer(ExtendedAddr),
+    /// Staking address
+    Staking(StakedStateAddress),
+}
+
+impl BookAddress {
+    /// Returns the transfer address, or an error if it is a staking address
+    pub fn into_transfer(self) -> Result<ExtendedAddr> {
+        match self {
+            BookAddress::Transfer(address) => Ok(address),
+            BookAddress::Staking(address) => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{} is a staking address, transfer address is expected",
+                    address
+                ),
+            )),
+        }
+    }
+
+    /// Returns the staking address, or an error if it is a transfer address
+    pub fn into_staking(self) -> Result<StakedStateAddress> {
+        match self {
+            BookAddress::Staking(address) => Ok(address),
+            BookAddress::Transfer(address) => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{} is a transfer address, staking address is expected",
+                    address
+                ),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for BookAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BookAddress::Transfer(address) => write!(f, "{}", address),
+            BookAddress::Staking(address) => write!(f, "{}", address),
+        }
+    }
+}
+
+impl FromStr for BookAddress {
+    type Err = Error;
+
+    fn from_str(address: &str) -> Result<Self> {
+        if let Ok(address) = ExtendedAddr::from_str(address) {
+            return Ok(BookAddress::Transfer(address));
+        }
+        StakedStateAddress::from_str(address)
+            .map(BookAddress::Staking)
+            .map_err(|_| {
+                Error::new(
+                    ErrorKind::DeserializationError,
+                    format!("Unable to parse address: {}", address),
+                )
+            })
+    }
+}
+
+impl Serialize for BookAddress {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for BookAddress {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let address = String::deserialize(deserializer)?;
+        BookAddress::from_str(&address).map_err(de::Error::custom)
+    }
+}
+
+/// Free-form label and note of a transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct TransactionLabel {
+    /// Short label of transaction
+    pub label: String,
+    /// Longer note on transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Address book and transaction labels of a wallet
+#[derive(Debug, Default, Clone, PartialEq, Encode, Decode)]
+pub struct AddressBook {
+    /// `label -> address`
+    pub contacts: BTreeMap<String, BookAddress>,
+    /// `transaction-id -> label`
+    pub transaction_labels: BTreeMap<TxId, TransactionLabel>,
+}
+
+impl AddressBook {
+    /// Returns the label of given address, if it is in the address book
+    pub fn label_of(&self, address: &BookAddress) -> Option<&str> {
+        self.contacts
+            .iter()
+            .find(|(_, contact)| *contact == address)
+            .map(|(label, _)| label.as_str())
+    }
+
+    /// Returns the address of given label (with or without `@` prefix)
+    pub fn resolve(&self, label: &str) -> Result<&BookAddress> {
+        let label = label.trim_start_matches(LABEL_PREFIX);
+        self.contacts.get(label).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Label {}{} not found in address book", LABEL_PREFIX, label),
+            )
+        })
+    }
+}
+
+/// Checks that a label can be used in the address book
+pub fn check_label(label: &str) -> Result<()> {
+    if label.is_empty() || label.starts_with(LABEL_PREFIX) || label.chars().any(char::is_whitespace)
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Invalid label ({}): labels must be non-empty, without whitespaces and must not start with {}",
+                label, LABEL_PREFIX
+            ),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,542 @@
+//! Types for exporting the transaction history of a wallet
+use std::fmt;
+use std::str::FromStr;
+
+use indexmap::IndexSet;
+use parity_scale_codec::{Decode, Encode, Error as CodecError, Input, Output};
+use serde::{Deserialize, Serialize, Serializer};
+
+use chain_core::init::coin::Coin;
+use chain_core::state::account::StakedStateAddress;
+use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::TxId;
+use client_common::tendermint::types::{StakingAdjustmentKind, Time};
+use client_common::{Error, ErrorKind, Result, ResultExt};
+
+use super::transaction_change::{BalanceChange, TransactionChange, TransactionType};
+
+/// Type of a history record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub enum HistoryRecordType {
+    /// Transfer transaction
+    Transfer,
+    /// Withdraw transaction
+    Withdraw,
+    /// Unbond transaction
+    Unbond,
+    /// Deposit transaction
+    Deposit,
+    /// Unjail transaction
+    Unjail,
+    /// Nodejoin transaction
+    Nodejoin,
+    /// Reward of a staking address of the wallet
+    Reward,
+    /// Slash of a staking address of the wallet
+    Slash,
+}
+
+impl From<TransactionType> for HistoryRecordType {
+    fn from(transaction_type: TransactionType) -> Self {
+        match transaction_type {
+            TransactionType::Transfer => HistoryRecordType::Transfer,
+            TransactionType::Withdraw => HistoryRecordType::Withdraw,
+            TransactionType::Unbond => HistoryRecordType::Unbond,
+            TransactionType::Deposit => HistoryRecordType::Deposit,
+            TransactionType::Unjail => HistoryRecordType::Unjail,
+            TransactionType::Nodejoin => HistoryRecordType::Nodejoin,
+        }
+    }
+}
+
+impl From<StakingAdjustmentKind> for HistoryRecordType {
+    fn from(kind: StakingAdjustmentKind) -> Self {
+        match kind {
+            StakingAdjustmentKind::Reward => HistoryRecordType::Reward,
+            StakingAdjustmentKind::Slash => HistoryRecordType::Slash,
+        }
+    }
+}
+
+impl fmt::Display for HistoryRecordType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryRecordType::Transfer => write!(f, "Transfer"),
+            HistoryRecordType::Withdraw => write!(f, "Withdraw"),
+            HistoryRecordType::Unbond => write!(f, "Unbond"),
+            HistoryRecordType::Deposit => write!(f, "Deposit"),
+            HistoryRecordType::Unjail => write!(f, "Unjail"),
+            HistoryRecordType::Nodejoin => write!(f, "Nodejoin"),
+            HistoryRecordType::Reward => write!(f, "Reward"),
+            HistoryRecordType::Slash => write!(f, "Slash"),
+        }
+    }
+}
+
+impl FromStr for HistoryRecordType {
+    type Err = Error;
+
+    fn from_str(record_type: &str) -> Result<Self> {
+        match record_type.to_lowercase().as_str() {
+            "transfer" => Ok(HistoryRecordType::Transfer),
+            "withdraw" => Ok(HistoryRecordType::Withdraw),
+            "unbond" => Ok(HistoryRecordType::Unbond),
+            "deposit" => Ok(HistoryRecordType::Deposit),
+            "unjail" => Ok(HistoryRecordType::Unjail),
+            "nodejoin" => Ok(HistoryRecordType::Nodejoin),
+            "reward" => Ok(HistoryRecordType::Reward),
+            "slash" => Ok(HistoryRecordType::Slash),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unknown history record type: {}", record_type),
+            )),
+        }
+    }
+}
+
+/// Reward or slash of a staking address of the wallet, recorded while syncing
+#[derive(Debug, Clone, PartialEq)]
+pub struct StakingHistoryEntry {
+    /// Height of block which has the reward or slash
+    pub block_height: u64,
+    /// Time of block which has the reward or slash
+    pub block_time: Time,
+    /// Rewarded or slashed staking address
+    pub staking_address: StakedStateAddress,
+    /// `Reward` or `Slash`
+    pub record_type: HistoryRecordType,
+    /// Rewarded or slashed amount
+    pub amount: Coin,
+}
+
+impl Encode for StakingHistoryEntry {
+    fn encode_to<W: Output>(&self, dest: &mut W) {
+        self.block_height.encode_to(dest);
+        self.block_time.to_rfc3339().encode_to(dest);
+        self.staking_address.encode_to(dest);
+        self.record_type.encode_to(dest);
+        self.amount.encode_to(dest);
+    }
+}
+
+impl Decode for StakingHistoryEntry {
+    fn decode<I: Input>(input: &mut I) -> std::result::Result<Self, CodecError> {
+        let block_height = u64::decode(input)?;
+        let block_time = Time::from_str(&String::decode(input)?)
+            .map_err(|_| CodecError::from("Unable to parse block time"))?;
+        let staking_address = StakedStateAddress::decode(input)?;
+        let record_type = HistoryRecordType::decode(input)?;
+        let amount = Coin::decode(input)?;
+        Ok(StakingHistoryEntry {
+            block_height,
+            block_time,
+            staking_address,
+            record_type,
+            amount,
+        })
+    }
+}
+
+/// Filter of exported history records
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryFilter {
+    /// Only records at or after this time
+    pub from: Option<Time>,
+    /// Only records before this time
+    pub to: Option<Time>,
+    /// Only records of these types (all the types if empty)
+    pub types: Vec<HistoryRecordType>,
+}
+
+impl HistoryFilter {
+    /// Returns `true` if the record passes the filter
+    pub fn matches(&self, record: &HistoryRecord) -> bool {
+        self.from.map_or(true, |from| record.block_time >= from)
+            && self.to.map_or(true, |to| record.block_time < to)
+            && (self.types.is_empty() || self.types.contains(&record.record_type))
+    }
+}
+
+/// Line of an exported history
+///
+/// `running_balance` is the transfer balance of the wallet after the record. Rewards and slashes
+/// change the bonded balance of a staking address, so they leave it unchanged until the staked
+/// coins are withdrawn.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryRecord {
+    /// Time of block
+    pub block_time: Time,
+    /// Height of block
+    pub block_height: u64,
+    /// Type of record
+    #[serde(rename = "type")]
+    pub record_type: HistoryRecordType,
+    /// Transaction ID (not available for rewards and slashes)
+    #[serde(serialize_with = "serialize_transaction_id")]
+    pub transaction_id: Option<TxId>,
+    /// Rewarded or slashed staking address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub staking_address: Option<StakedStateAddress>,
+    /// Addresses of inputs and outputs which do not belong to the wallet
+    pub counterparties: Vec<String>,
+    /// Fee paid by the transaction
+    pub fee: Coin,
+    /// Change of balance caused by the record
+    #[serde(flatten)]
+    pub balance_change: BalanceChange,
+    /// Transfer balance after the record
+    pub running_balance: Coin,
+    /// Label of transaction in the address book
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+fn serialize_transaction_id<S>(
+    transaction_id: &Option<TxId>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match transaction_id {
+        Some(transaction_id) => serializer.serialize_str(&hex::encode(transaction_id)),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Output format of history export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryFormat {
+    /// Comma separated values, one line per record (amounts in CRO)
+    Csv,
+    /// JSON array of records (amounts in base units)
+    Json,
+}
+
+impl FromStr for HistoryFormat {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format.to_lowercase().as_str() {
+            "csv" => Ok(HistoryFormat::Csv),
+            "json" => Ok(HistoryFormat::Json),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unknown history format: {} (expected csv or json)", format),
+            )),
+        }
+    }
+}
+
+impl HistoryFormat {
+    /// Renders history records
+    pub fn render(self, records: &[HistoryRecord]) -> Result<String> {
+        match self {
+            HistoryFormat::Csv => Ok(render_csv(records)),
+            HistoryFormat::Json => serde_json::to_string_pretty(records).chain(|| {
+                (
+                    ErrorKind::SerializationError,
+                    "Unable to serialize history records",
+                )
+            }),
+        }
+    }
+}
+
+fn render_csv(records: &[HistoryRecord]) -> String {
+    let mut csv = String::from(
+        "block_time,block_height,type,transaction_id,staking_address,counterparties,fee,balance_change,running_balance,label\n",
+    );
+    for record in records {
+        let balance_change = match record.balance_change {
+            BalanceChange::Incoming { value } => value.to_string(),
+            BalanceChange::Outgoing { value } => format!("-{}", value),
+            BalanceChange::NoChange => Coin::zero().to_string(),
+        };
+        let fields = [
+            record.block_time.to_rfc3339(),
+            record.block_height.to_string(),
+            record.record_type.to_string(),
+            record.transaction_id.map(hex::encode).unwrap_or_default(),
+            record
+                .staking_address
+                .map(|address| address.to_string())
+                .unwrap_or_default(),
+            record.counterparties.join(" "),
+            record.fee.to_string(),
+            balance_change,
+            record.running_balance.to_string(),
+            record.label.clone().unwrap_or_default(),
+        ];
+        let line = fields
+            .iter()
+            .map(|field| escape_csv_field(field))
+            .collect::<Vec<_>>()
+            .join(",");
+        csv.push_str(&line);
+        csv.push('\n');
+    }
+    csv
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Builds history records from the transaction changes (in block order) and the staking history
+/// of a wallet
+///
+/// Running balance is accumulated over the whole history, before the records are filtered.
+pub fn build_history<I>(
+    changes: I,
+    staking_history: &[StakingHistoryEntry],
+    own_addresses: &IndexSet<ExtendedAddr>,
+    filter: &HistoryFilter,
+) -> Result<Vec<HistoryRecord>>
+where
+    I: IntoIterator<Item = TransactionChange>,
+{
+    let mut records = Vec::new();
+    let mut running_balance = Coin::zero();
+    let mut staking_history = staking_history.iter().peekable();
+
+    for change in changes {
+        // rewards and slashes are distributed at the beginning of blocks
+        while let Some(entry) = staking_history.peek() {
+            if entry.block_height > change.block_height {
+                break;
+            }
+            push_staking_record(&mut records, entry, running_balance, filter);
+            staking_history.next();
+        }
+
+        running_balance = (running_balance + change.balance_change)?;
+        let record = HistoryRecord {
+            block_time: change.block_time,
+            block_height: change.block_height,
+            record_type: change.transaction_type.into(),
+            transaction_id: Some(change.transaction_id),
+            staking_address: None,
+            counterparties: counterparties(&change, own_addresses),
+            fee: change.fee_paid.to_coin(),
+            balance_change: change.balance_change,
+            running_balance,
+            label: change.label.map(|label| label.label),
+        };
+        if filter.matches(&record) {
+            records.push(record);
+        }
+    }
+    for entry in staking_history {
+        push_staking_record(&mut records, entry, running_balance, filter);
+    }
+
+    Ok(records)
+}
+
+fn push_staking_record(
+    records: &mut Vec<HistoryRecord>,
+    entry: &StakingHistoryEntry,
+    running_balance: Coin,
+    filter: &HistoryFilter,
+) {
+    let balance_change = match entry.record_type {
+        HistoryRecordType::Slash => BalanceChange::Outgoing {
+            value: entry.amount,
+        },
+        _ => BalanceChange::Incoming {
+            value: entry.amount,
+        },
+    };
+    let record = HistoryRecord {
+        block_time: entry.block_time,
+        block_height: entry.block_height,
+        record_type: entry.record_type,
+        transaction_id: None,
+        staking_address: Some(entry.staking_address),
+        counterparties: Vec::new(),
+        fee: Coin::zero(),
+        balance_change,
+        running_balance,
+        label: None,
+    };
+    if filter.matches(&record) {
+        records.push(record);
+    }
+}
+
+/// Returns the (deduplicated) addresses of inputs and outputs which do not belong to the wallet
+fn counterparties(
+    change: &TransactionChange,
+    own_addresses: &IndexSet<ExtendedAddr>,
+) -> Vec<String> {
+    let mut counterparties = change
+        .inputs
+        .iter()
+        .filter_map(|input| input.output.as_ref())
+        .chain(change.outputs.iter())
+        .map(|output| &output.address)
+        .filter(|address| !own_addresses.contains(*address))
+        .map(|address| address.to_string())
+        .collect::<Vec<_>>();
+    counterparties.sort();
+    counterparties.dedup();
+    counterparties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chain_core::tx::data::output::TxOut;
+    use chain_core::tx::fee::Fee;
+
+    fn time(time: &str) -> Time {
+        Time::from_str(time).unwrap()
+    }
+
+    fn change(
+        transaction_id: TxId,
+        block_height: u64,
+        block_time: &str,
+        balance_change: BalanceChange,
+    ) -> TransactionChange {
+        TransactionChange {
+            transaction_id,
+            inputs: Vec::new(),
+            outputs: vec![
+                TxOut::new(ExtendedAddr::OrTree([1; 32]), Coin::new(10).unwrap()),
+                TxOut::new(ExtendedAddr::OrTree([2; 32]), Coin::new(20).unwrap()),
+            ],
+            fee_paid: Fee::new(Coin::new(1).unwrap()),
+            balance_change,
+            transaction_type: TransactionType::Transfer,
+            block_height,
+            block_time: time(block_time),
+            label: None,
+            address_labels: Default::default(),
+        }
+    }
+
+    fn history() -> (Vec<TransactionChange>, Vec<StakingHistoryEntry>) {
+        let changes = vec![
+            change(
+                [1; 32],
+                1,
+                "2020-01-10T00:00:00Z",
+                BalanceChange::Incoming {
+                    value: Coin::new(100).unwrap(),
+                },
+            ),
+            change(
+                [2; 32],
+                5,
+                "2020-02-10T00:00:00Z",
+                BalanceChange::Outgoing {
+                    value: Coin::new(30).unwrap(),
+                },
+            ),
+        ];
+        let staking_history = vec![StakingHistoryEntry {
+            block_height: 3,
+            block_time: time("2020-02-01T00:00:00Z"),
+            staking_address: StakedStateAddress::from_str(
+                "0x0e7c045110b8dbf29765047380898919c5cb56f4",
+            )
+            .unwrap(),
+            record_type: HistoryRecordType::Reward,
+            amount: Coin::new(7).unwrap(),
+        }];
+        (changes, staking_history)
+    }
+
+    #[test]
+    fn check_build_history() {
+        let (changes, staking_history) = history();
+        let mut own_addresses = IndexSet::new();
+        own_addresses.insert(ExtendedAddr::OrTree([2; 32]));
+
+        let records = build_history(
+            changes.clone(),
+            &staking_history,
+            &own_addresses,
+            &HistoryFilter::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                HistoryRecordType::Transfer,
+                HistoryRecordType::Reward,
+                HistoryRecordType::Transfer
+            ],
+            records
+                .iter()
+                .map(|record| record.record_type)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![100, 100, 70],
+            records
+                .iter()
+                .map(|record| u64::from(record.running_balance))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![ExtendedAddr::OrTree([1; 32]).to_string()],
+            records[0].counterparties
+        );
+
+        // running balance is accumulated before filtering
+        let filter = HistoryFilter {
+            from: Some(time("2020-02-01T00:00:00Z")),
+            to: Some(time("2020-03-01T00:00:00Z")),
+            types: vec![HistoryRecordType::Transfer],
+        };
+        let records = build_history(changes, &staking_history, &own_addresses, &filter).unwrap();
+        assert_eq!(1, records.len());
+        assert_eq!(Some([2; 32]), records[0].transaction_id);
+        assert_eq!(Coin::new(70).unwrap(), records[0].running_balance);
+    }
+
+    #[test]
+    fn check_render() {
+        let (changes, staking_history) = history();
+        let mut records = build_history(
+            changes,
+            &staking_history,
+            &IndexSet::new(),
+            &HistoryFilter::default(),
+        )
+        .unwrap();
+        records[0].label = Some("rent, March".to_owned());
+
+        let csv = HistoryFormat::Csv.render(&records).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(4, lines.len());
+        assert!(lines[1].ends_with(",0.00000100,\"rent, March\""));
+        assert!(lines[3].contains(",-0.00000030,0.00000070,"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&HistoryFormat::Json.render(&records).unwrap()).unwrap();
+        assert_eq!("Reward", json[1]["type"]);
+        assert_eq!(serde_json::Value::Null, json[1]["transaction_id"]);
+        assert_eq!("Outgoing", json[2]["kind"]);
+        assert_eq!("70", json[2]["running_balance"]);
+    }
+
+    #[test]
+    fn check_staking_history_entry_encoding() {
+        let (_, staking_history) = history();
+        let encoded = staking_history.encode();
+        assert_eq!(
+            staking_history,
+            <Vec<StakingHistoryEntry>>::decode(&mut encoded.as_slice()).unwrap()
+        );
+    }
+}
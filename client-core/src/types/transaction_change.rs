@@ -1,4 +1,5 @@
 //! Types for tracking balance change in a wallet
+use std::collections::BTreeMap;
 use std::fmt;
 use std::ops::Add;
 use std::str::FromStr;
@@ -7,6 +8,7 @@ use parity_scale_codec::{Decode, Encode, Error, Input, Output};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 use chain_core::{
+    common::Timespec,
     init::coin::{Coin, CoinError},
     tx::data::{input::TxoPointer, output::TxOut, TxId},
     tx::fee::Fee,
@@ -14,14 +16,17 @@ use chain_core::{
 use client_common::tendermint::types::Time;
 use client_common::{ErrorKind, Result, ResultExt, Transaction};
 
+use super::address_book::TransactionLabel;
+
 /// Wallet balance info
 ///
 /// The semantic of `WalletBalance` is like this:
 ///
 /// ```plain
-/// total = available + pending
+/// total = available + pending + sum(locked)
 /// pending = sum(incoming coins of pending tx)
-/// available = sum(synced utxo - spent by pending tx)
+/// available = sum(synced utxo - spent by pending tx - timelocked utxo)
+/// locked = sum(timelocked utxo - spent by pending tx), grouped by the time or block they unlock
 /// ```
 ///
 /// For pending tx with n incoming coins (transfer from other wallet to our wallet or withdraw):
@@ -51,6 +56,39 @@ pub struct WalletBalance {
     pub available: Coin,
     /// The pending amount balance
     pub pending: Coin,
+    /// The amount balances which are still timelocked (ordered by the time or block they unlock)
+    #[serde(default)]
+    pub locked: Vec<LockedBalance>,
+}
+
+/// Amount balance which is timelocked
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Encode, Decode)]
+pub struct LockedBalance {
+    /// The timelocked amount
+    pub amount: Coin,
+    /// When the amount can be used
+    pub locked_until: LockedUntil,
+}
+
+/// When timelocked outputs can be spent
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Encode, Decode,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum LockedUntil {
+    /// Block time (seconds since UNIX epoch)
+    Time(Timespec),
+    /// Block height
+    BlockHeight(u64),
+}
+
+impl fmt::Display for LockedUntil {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockedUntil::Time(time) => write!(f, "time {}", time),
+            LockedUntil::BlockHeight(block_height) => write!(f, "block {}", block_height),
+        }
+    }
 }
 
 /// Transaction pending infomation
@@ -87,6 +125,12 @@ pub struct TransactionChange {
     pub block_height: u64,
     /// Time of block which has this transaction
     pub block_time: Time,
+    /// Label of transaction in the address book (not encoded, labels are stored separately)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<TransactionLabel>,
+    /// Address book labels of input and output addresses, indexed by address
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub address_labels: BTreeMap<String, String>,
 }
 
 /// Transaction input
@@ -217,6 +261,8 @@ impl Decode for TransactionChange {
             transaction_type,
             block_height,
             block_time,
+            label: None,
+            address_labels: BTreeMap::new(),
         })
     }
 }
@@ -282,6 +328,8 @@ mod tests {
             fee_paid: Fee::new(Coin::one()),
             block_height: 0,
             block_time: Time::now(),
+            label: None,
+            address_labels: BTreeMap::new(),
         };
 
         let encoded = transaction_change.encode();
@@ -1,5 +1,7 @@
 //! Wallet management
 mod default_wallet_client;
+/// Continuous wallet synchronization driven by new block events
+pub mod sync_daemon;
 /// Wallet synchronizer
 pub mod syncer;
 mod syncer_logic;
@@ -18,6 +20,7 @@ use chain_core::init::coin::Coin;
 use chain_core::state::account::StakedStateAddress;
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::htlc::{HtlcCondition, HtlcSpend};
 use chain_core::tx::data::input::TxoPointer;
 use chain_core::tx::data::output::TxOut;
 #[cfg(feature = "experimental")]
@@ -33,9 +36,16 @@ use client_common::{
 use serde::{Deserialize, Serialize};
 
 use crate::hd_wallet::HardwareKind;
-use crate::service::{SyncState, WalletInfo};
+#[cfg(feature = "experimental")]
+use crate::multi_sig::SigningPackage;
+use crate::service::{SyncState, WalletBackup, WalletInfo};
+#[cfg(feature = "experimental")]
+use crate::transaction_builder::RawTransferTransaction;
 use crate::transaction_builder::{SignedTransferTransaction, UnsignedTransferTransaction};
-use crate::types::{AddressType, TransactionChange, TransactionPending, WalletBalance, WalletKind};
+use crate::types::{
+    AddressBook, AddressType, BookAddress, HistoryFilter, HistoryRecord, TransactionChange,
+    TransactionLabel, TransactionPending, WalletBalance, WalletKind,
+};
 use crate::{InputSelectionStrategy, Mnemonic, UnspentTransactions};
 
 /// information needed when create/delete a wallet
@@ -120,6 +130,21 @@ pub trait WalletClient: Send + Sync {
         wallet_info: &mut WalletInfo,
     ) -> Result<SecKey>;
 
+    /// Creates a backup of all the data of a wallet, encrypted with given backup passphrase
+    fn backup_wallet(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        backup_passphrase: &SecUtf8,
+    ) -> Result<WalletBackup>;
+
+    /// Restores a wallet from its backup, returns the name of restored wallet
+    fn restore_wallet_backup(
+        &self,
+        backup: &WalletBackup,
+        backup_passphrase: &SecUtf8,
+    ) -> Result<String>;
+
     /// Restores a HD wallet from given mnemonic
     fn restore_wallet(
         &self,
@@ -142,6 +167,15 @@ pub trait WalletClient: Send + Sync {
     /// get auth token client
     fn auth_token(&self, name: &str, passphrase: &SecUtf8) -> Result<SecKey>;
 
+    /// Changes the passphrase of a wallet by re-encrypting all of its data, returns the new
+    /// enckey
+    fn change_passphrase(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        new_passphrase: &SecUtf8,
+    ) -> Result<SecKey>;
+
     /// Retrieves view key corresponding to a given wallet
     fn view_key(&self, name: &str, enckey: &SecKey) -> Result<PublicKey>;
 
@@ -301,6 +335,50 @@ pub trait WalletClient: Send + Sync {
         transaction_id: &TxId,
     ) -> Result<Option<TransactionChange>>;
 
+    /// Retrieves the history records of wallet which pass given filter, including the rewards
+    /// and slashes of its staking addresses
+    fn history_records(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        filter: &HistoryFilter,
+    ) -> Result<Vec<HistoryRecord>>;
+
+    /// Retrieves the address book (address and transaction labels) of wallet
+    fn address_book(&self, name: &str, enckey: &SecKey) -> Result<AddressBook>;
+
+    /// Labels an address in the address book of wallet
+    fn set_address_label(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        label: &str,
+        address: &BookAddress,
+    ) -> Result<()>;
+
+    /// Removes a label from the address book of wallet
+    fn remove_address_label(&self, name: &str, enckey: &SecKey, label: &str) -> Result<()>;
+
+    /// Labels a transaction of wallet
+    fn set_transaction_label(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        transaction_id: TxId,
+        label: &TransactionLabel,
+    ) -> Result<()>;
+
+    /// Removes the label of a transaction of wallet
+    fn remove_transaction_label(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        transaction_id: &TxId,
+    ) -> Result<()>;
+
+    /// Parses an address, or resolves it from the address book if it is a label (e.g. `@alice`)
+    fn resolve_address(&self, name: &str, enckey: &SecKey, address: &str) -> Result<BookAddress>;
+
     /// Retrieves all unspent transactions of wallet
     fn unspent_transactions(&self, name: &str, enckey: &SecKey) -> Result<UnspentTransactions>;
 
@@ -344,6 +422,44 @@ pub trait WalletClient: Send + Sync {
         return_address: ExtendedAddr,
     ) -> Result<(TxAux, Vec<TxoPointer>, Coin)>;
 
+    /// Builds a transaction funding a hash-time-locked output with given amount
+    ///
+    /// # Attributes
+    ///
+    /// - `name`: Name of wallet
+    /// - `enckey`: Passphrase of wallet
+    /// - `condition`: Condition of the output (its refund root has to be a transfer address of
+    ///   the wallet)
+    /// - `amount`: Value of the output
+    /// - `attributes`: Transaction attributes,
+    /// - `input_selection_strategy`: Strategy to use while selecting unspent transactions
+    /// - `return_address`: Address to which change amount will get returned
+    #[allow(clippy::too_many_arguments)]
+    fn create_htlc_funding_transaction(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        condition: &HtlcCondition,
+        amount: Coin,
+        attributes: TxAttributes,
+        input_selection_strategy: Option<InputSelectionStrategy>,
+        return_address: ExtendedAddr,
+    ) -> Result<(TxAux, Vec<TxoPointer>, Coin)>;
+
+    /// Builds a transaction claiming (with the preimage of the hash lock, before the deadline)
+    /// or refunding (from the deadline on) a hash-time-locked output to given address
+    #[allow(clippy::too_many_arguments)]
+    fn create_htlc_spend_transaction(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        input: (TxoPointer, TxOut),
+        condition: HtlcCondition,
+        spend: HtlcSpend,
+        to_address: ExtendedAddr,
+        attributes: TxAttributes,
+    ) -> Result<TxAux>;
+
     /// Broadcasts a transaction to Crypto.com Chain
     fn broadcast_transaction(&self, tx_aux: &TxAux) -> Result<BroadcastTxResponse>;
 
@@ -491,4 +607,98 @@ pub trait MultiSigWalletClient: WalletClient {
         enckey: &SecKey,
         unsigned_transaction: Tx,
     ) -> Result<TxAux>;
+
+    /// Creates a transfer transaction which can also spend the outputs of MultiSig addresses
+    /// of current wallet, through the spending path of the available co-signers. The inputs of
+    /// single key addresses are signed by current wallet; each multi-sig one needs the
+    /// signature of a session of its co-signers with the transaction id as message (see
+    /// `add_cosigners_signature`).
+    ///
+    /// # Arguments
+    ///
+    /// `name`: Name of wallet
+    /// `enckey`: enckey of wallet
+    /// `outputs`: Transaction outputs
+    /// `attributes`: Transaction attributes
+    /// `input_selection_strategy`: Strategy to use while selecting unspent transactions
+    /// `return_address`: Address to which change amount will get returned
+    /// `signer_public_keys`: Public keys of the available co-signers (including current signer)
+    #[allow(clippy::too_many_arguments)]
+    fn create_multisig_transfer_transaction(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        outputs: Vec<TxOut>,
+        attributes: TxAttributes,
+        input_selection_strategy: Option<InputSelectionStrategy>,
+        return_address: ExtendedAddr,
+        signer_public_keys: Vec<PublicKey>,
+    ) -> Result<RawTransferTransaction>;
+
+    /// Adds the final signature of a multi-sig session (with the transaction id as message)
+    /// to the multi-sig inputs of given transaction spent through the path of its co-signers
+    fn add_cosigners_signature(
+        &self,
+        raw_transaction: RawTransferTransaction,
+        signature: SchnorrSignature,
+    ) -> Result<RawTransferTransaction>;
+
+    /// Returns obfuscated transaction of given transfer transaction, once all of its inputs are
+    /// signed
+    fn multisig_transfer_transaction(
+        &self,
+        raw_transaction: RawTransferTransaction,
+    ) -> Result<TxAux>;
+
+    /// Broadcasts given transfer transaction, once all of its inputs are signed, and marks its
+    /// inputs as pending in the wallet state
+    fn broadcast_multisig_transfer_transaction(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        raw_transaction: RawTransferTransaction,
+    ) -> Result<TxId>;
+
+    /// Creates a signing package for given transaction spending an output of a MultiSig address
+    /// of current wallet and contributes the round data of current signer
+    ///
+    /// # Arguments
+    ///
+    /// `name`: Name of wallet
+    /// `enckey`: enckey of wallet
+    /// `unsigned_transaction`: Transaction to be signed, with only one input
+    /// `signer_public_keys`: Public keys of all co-signers (including current signer)
+    fn new_signing_package(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        unsigned_transaction: Tx,
+        signer_public_keys: Vec<PublicKey>,
+    ) -> Result<SigningPackage>;
+
+    /// Imports a signing package received from a co-signer, merges its round data and
+    /// contributes the round data of current signer. Returns the updated package.
+    fn import_signing_package(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        package: SigningPackage,
+    ) -> Result<SigningPackage>;
+
+    /// Returns the latest state of signing package of given transaction
+    fn export_signing_package(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        transaction_id: &TxId,
+    ) -> Result<SigningPackage>;
+
+    /// Returns obfuscated transaction signed with the signature of given signing package. This
+    /// function will fail if partial signatures from all co-signers are not received.
+    fn signing_package_transaction(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        package: SigningPackage,
+    ) -> Result<TxAux>;
 }
@@ -1,9 +1,16 @@
 use crate::hd_wallet::{ChainPath, HardwareKind};
+#[cfg(feature = "experimental")]
+use crate::multi_sig::SigningPackage;
 use crate::service::*;
+#[cfg(feature = "experimental")]
+use crate::transaction_builder::RawTransferTransaction;
 use crate::transaction_builder::UnauthorizedWalletTransactionBuilder;
 use crate::transaction_builder::{SignedTransferTransaction, UnsignedTransferTransaction};
+use crate::types::address_book::LABEL_PREFIX;
+use crate::types::history::build_history;
 use crate::types::{
-    AddressType, BalanceChange, TransactionChange, TransactionPending, WalletBalance, WalletKind,
+    AddressBook, AddressType, BalanceChange, BookAddress, HistoryFilter, HistoryRecord,
+    TransactionChange, TransactionLabel, TransactionPending, WalletBalance, WalletKind,
 };
 use crate::wallet::syncer::{get_genesis_sync_state, AddressRecovery};
 use crate::wallet::syncer_logic::create_transaction_change;
@@ -13,13 +20,14 @@ use crate::{
     InputSelectionStrategy, Mnemonic, UnspentTransactions, WalletClient, WalletTransactionBuilder,
 };
 use bit_vec::BitVec;
-use chain_core::common::{Proof, H256};
+use chain_core::common::{Proof, Timespec, H256};
 use chain_core::init::address::RedeemAddress;
 use chain_core::init::coin::Coin;
 use chain_core::state::account::StakedStateAddress;
 use chain_core::tx::data::access::{TxAccess, TxAccessPolicy};
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::htlc::{HtlcCondition, HtlcSpend};
 use chain_core::tx::data::input::{str2txid, TxoPointer};
 use chain_core::tx::data::output::TxOut;
 #[cfg(feature = "experimental")]
@@ -47,6 +55,7 @@ use secstr::SecUtf8;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::convert::TryInto;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use zxcvbn::{feedback::Feedback, zxcvbn as estimate_password_strength};
@@ -65,6 +74,10 @@ where
     wallet_state_service: WalletStateService<S>,
     sync_state_service: SyncStateService<S>,
     root_hash_service: RootHashService<S>,
+    passphrase_service: PassphraseService<S>,
+    backup_service: BackupService<S>,
+    address_book_service: AddressBookService<S>,
+    staking_history_service: StakingHistoryService<S>,
     #[cfg(feature = "experimental")]
     multi_sig_session_service: MultiSigSessionService<S>,
 
@@ -98,6 +111,10 @@ where
             #[cfg(feature = "experimental")]
             multi_sig_session_service: MultiSigSessionService::new(storage.clone()),
             root_hash_service: RootHashService::new(storage.clone()),
+            passphrase_service: PassphraseService::new(storage.clone()),
+            backup_service: BackupService::new(storage.clone()),
+            address_book_service: AddressBookService::new(storage.clone()),
+            staking_history_service: StakingHistoryService::new(storage.clone()),
             tendermint_client,
             transaction_builder,
             block_height_ensure,
@@ -119,6 +136,39 @@ where
             });
         Ok(tx_change.is_ok())
     }
+
+    /// Returns the time (approximated by the local clock) and the height (of the last synced
+    /// block) which timelocks of outputs are checked against
+    fn timelock_reference(&self, name: &str) -> Result<(Timespec, u64)> {
+        let block_height = self
+            .sync_state_service
+            .get_global_state(name)?
+            .map(|state| state.last_block_height)
+            .unwrap_or_default();
+        let block_time = Time::now()
+            .duration_since(Time::unix_epoch())
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        Ok((block_time, block_height))
+    }
+
+    /// Returns unspent transactions which are not timelocked (i.e. can be selected as inputs)
+    fn unlocked_transactions(&self, name: &str, enckey: &SecKey) -> Result<UnspentTransactions> {
+        // Check if wallet exists
+        self.wallet_service.view_key(name, enckey)?;
+
+        let (block_time, block_height) = self.timelock_reference(name)?;
+        let unspent_transactions = self.wallet_state_service.get_unlocked_transactions(
+            name,
+            enckey,
+            block_time,
+            block_height,
+        )?;
+
+        Ok(UnspentTransactions::new(
+            unspent_transactions.into_iter().collect(),
+        ))
+    }
 }
 
 impl<S> DefaultWalletClient<S, UnauthorizedClient, UnauthorizedWalletTransactionBuilder>
@@ -551,6 +601,24 @@ where
         }
     }
 
+    fn backup_wallet(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        backup_passphrase: &SecUtf8,
+    ) -> Result<WalletBackup> {
+        self.backup_service.backup(name, enckey, backup_passphrase)
+    }
+
+    fn restore_wallet_backup(
+        &self,
+        backup: &WalletBackup,
+        backup_passphrase: &SecUtf8,
+    ) -> Result<String> {
+        self.backup_service.restore(backup, backup_passphrase)?;
+        Ok(backup.name().to_owned())
+    }
+
     fn restore_wallet(
         &self,
         name: &str,
@@ -624,6 +692,10 @@ where
             self.hd_key_service.delete_wallet(name, &enckey)?;
         }
         self.key_service.delete_wallet_private_key(name, &enckey)?;
+        self.address_book_service.delete_wallet(name)?;
+        self.staking_history_service.delete_wallet(name)?;
+        #[cfg(feature = "experimental")]
+        self.multi_sig_session_service.delete_wallet(name)?;
 
         Ok(())
     }
@@ -633,11 +705,34 @@ where
             "unable to derive encryption key from passphrase"
         })?;
 
+        // complete a passphrase change interrupted after it was committed
+        self.passphrase_service.recover(name)?;
+
         // test validity of enckey
         self.view_key(name, &enckey)?;
         Ok(enckey)
     }
 
+    fn change_passphrase(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        new_passphrase: &SecUtf8,
+    ) -> Result<SecKey> {
+        check_passphrase_strength(name, new_passphrase)?;
+
+        // the passphrase is verified here.
+        let enckey = self.auth_token(name, passphrase)?;
+        let new_enckey = derive_enckey(new_passphrase, name)
+            .err_kind(ErrorKind::InvalidInput, || {
+                "unable to derive encryption key from passphrase"
+            })?;
+
+        self.passphrase_service
+            .change_enckey(name, &enckey, &new_enckey)?;
+        Ok(new_enckey)
+    }
+
     #[inline]
     fn view_key(&self, name: &str, enckey: &SecKey) -> Result<PublicKey> {
         self.wallet_service.view_key(name, enckey)
@@ -1013,6 +1108,10 @@ where
                 self.root_hash_service
                     .generate_proof(name, address, public_keys, enckey)
             }
+            ExtendedAddr::HashTimeLock(_) => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Proofs can only be generated for tree addresses",
+            )),
         }
     }
 
@@ -1028,7 +1127,9 @@ where
     fn balance(&self, name: &str, enckey: &SecKey) -> Result<WalletBalance> {
         // Check if wallet exists
         self.wallet_service.view_key(name, enckey)?;
-        self.wallet_state_service.get_balance(name, enckey)
+        let (block_time, block_height) = self.timelock_reference(name)?;
+        self.wallet_state_service
+            .get_balance(name, enckey, block_time, block_height)
     }
 
     fn history(
@@ -1042,26 +1143,118 @@ where
         // Check if wallet exists
         self.wallet_service.view_key(name, enckey)?;
 
-        let history = self
+        let mut history = self
             .wallet_state_service
             .get_transaction_history(name, enckey, reversed)?
             .filter(|change| BalanceChange::NoChange != change.balance_change)
             .skip(offset)
             .take(limit)
             .collect::<Vec<_>>();
+        self.address_book_service
+            .label_transaction_changes(name, enckey, &mut history)?;
 
         Ok(history)
     }
 
-    #[inline]
     fn get_transaction_change(
         &self,
         name: &str,
         enckey: &SecKey,
         transaction_id: &TxId,
     ) -> Result<Option<TransactionChange>> {
-        self.wallet_state_service
-            .get_transaction_change(name, enckey, transaction_id)
+        let mut change =
+            self.wallet_state_service
+                .get_transaction_change(name, enckey, transaction_id)?;
+        if let Some(ref mut change) = change {
+            self.address_book_service.label_transaction_changes(
+                name,
+                enckey,
+                std::slice::from_mut(change),
+            )?;
+        }
+        Ok(change)
+    }
+
+    fn history_records(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        filter: &HistoryFilter,
+    ) -> Result<Vec<HistoryRecord>> {
+        let wallet = self.wallet_service.get_wallet(name, enckey)?;
+        let own_addresses = wallet.get_transfer_addresses()?;
+
+        let mut changes = self
+            .wallet_state_service
+            .get_transaction_history(name, enckey, false)?
+            .filter(|change| BalanceChange::NoChange != change.balance_change)
+            .collect::<Vec<_>>();
+        self.address_book_service
+            .label_transaction_changes(name, enckey, &mut changes)?;
+        let staking_history = self.staking_history_service.get(name, enckey)?;
+
+        build_history(changes, &staking_history, &own_addresses, filter)
+    }
+
+    fn address_book(&self, name: &str, enckey: &SecKey) -> Result<AddressBook> {
+        // Check if wallet exists
+        self.wallet_service.view_key(name, enckey)?;
+        self.address_book_service.get(name, enckey)
+    }
+
+    fn set_address_label(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        label: &str,
+        address: &BookAddress,
+    ) -> Result<()> {
+        // Check if wallet exists
+        self.wallet_service.view_key(name, enckey)?;
+        self.address_book_service
+            .set_address_label(name, enckey, label, address)
+    }
+
+    #[inline]
+    fn remove_address_label(&self, name: &str, enckey: &SecKey, label: &str) -> Result<()> {
+        self.address_book_service
+            .remove_address_label(name, enckey, label)
+    }
+
+    fn set_transaction_label(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        transaction_id: TxId,
+        label: &TransactionLabel,
+    ) -> Result<()> {
+        // Check if wallet exists
+        self.wallet_service.view_key(name, enckey)?;
+        self.address_book_service
+            .set_transaction_label(name, enckey, transaction_id, label)
+    }
+
+    #[inline]
+    fn remove_transaction_label(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        transaction_id: &TxId,
+    ) -> Result<()> {
+        self.address_book_service
+            .remove_transaction_label(name, enckey, transaction_id)
+    }
+
+    fn resolve_address(&self, name: &str, enckey: &SecKey, address: &str) -> Result<BookAddress> {
+        let address = address.trim();
+        if address.starts_with(LABEL_PREFIX) {
+            self.address_book_service
+                .get(name, enckey)?
+                .resolve(address)
+                .map(Clone::clone)
+        } else {
+            BookAddress::from_str(address)
+        }
     }
 
     fn unspent_transactions(&self, name: &str, enckey: &SecKey) -> Result<UnspentTransactions> {
@@ -1127,7 +1320,7 @@ where
         input_selection_strategy: Option<InputSelectionStrategy>,
         return_address: ExtendedAddr,
     ) -> Result<(TxAux, Vec<TxoPointer>, Coin)> {
-        let mut unspent_transactions = self.unspent_transactions(name, enckey)?;
+        let mut unspent_transactions = self.unlocked_transactions(name, enckey)?;
         unspent_transactions.apply_all(input_selection_strategy.unwrap_or_default().as_ref());
 
         self.transaction_builder.build_transfer_tx(
@@ -1140,6 +1333,59 @@ where
         )
     }
 
+    fn create_htlc_funding_transaction(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        condition: &HtlcCondition,
+        amount: Coin,
+        attributes: TxAttributes,
+        input_selection_strategy: Option<InputSelectionStrategy>,
+        return_address: ExtendedAddr,
+    ) -> Result<(TxAux, Vec<TxoPointer>, Coin)> {
+        let refund_address = ExtendedAddr::OrTree(condition.refund_root);
+        if self
+            .wallet_service
+            .find_root_hash(name, enckey, &refund_address)?
+            .is_none()
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Refund address ({}) of hash-time-locked output does not belong to wallet with name: {}",
+                    refund_address, name
+                ),
+            ));
+        }
+
+        self.create_transaction(
+            name,
+            enckey,
+            vec![TxOut::new(condition.address(), amount)],
+            attributes,
+            input_selection_strategy,
+            return_address,
+        )
+    }
+
+    fn create_htlc_spend_transaction(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        input: (TxoPointer, TxOut),
+        condition: HtlcCondition,
+        spend: HtlcSpend,
+        to_address: ExtendedAddr,
+        attributes: TxAttributes,
+    ) -> Result<TxAux> {
+        // To verify if the enckey is correct or not
+        self.wallet_service.view_key(name, enckey)?;
+
+        self.transaction_builder.build_htlc_spend_tx(
+            name, enckey, input, condition, spend, to_address, attributes,
+        )
+    }
+
     #[inline]
     fn broadcast_transaction(&self, tx_aux: &TxAux) -> Result<BroadcastTxResponse> {
         self.tendermint_client
@@ -1263,7 +1509,7 @@ where
         view_keys: Vec<PublicKey>,
         network_id: u8,
     ) -> Result<UnsignedTransferTransaction> {
-        let unspent_transactions = self.unspent_transactions(name, enckey)?;
+        let unspent_transactions = self.unlocked_transactions(name, enckey)?;
         let return_address = self.new_transfer_address(name, enckey)?;
         let unsigned = UnsignedTransferTransaction {
             unspent_transactions,
@@ -1531,6 +1777,203 @@ where
 
         self.transaction_builder.obfuscate(signed_transaction)
     }
+
+    fn create_multisig_transfer_transaction(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        outputs: Vec<TxOut>,
+        attributes: TxAttributes,
+        input_selection_strategy: Option<InputSelectionStrategy>,
+        return_address: ExtendedAddr,
+        signer_public_keys: Vec<PublicKey>,
+    ) -> Result<RawTransferTransaction> {
+        let mut unspent_transactions = self.unlocked_transactions(name, enckey)?;
+        unspent_transactions.apply_all(input_selection_strategy.unwrap_or_default().as_ref());
+
+        self.transaction_builder.build_multisig_transfer_tx(
+            name,
+            enckey,
+            unspent_transactions,
+            outputs,
+            return_address,
+            attributes,
+            &signer_public_keys,
+        )
+    }
+
+    fn add_cosigners_signature(
+        &self,
+        raw_transaction: RawTransferTransaction,
+        signature: SchnorrSignature,
+    ) -> Result<RawTransferTransaction> {
+        self.transaction_builder
+            .add_cosigners_signature(raw_transaction, signature)
+    }
+
+    fn multisig_transfer_transaction(
+        &self,
+        raw_transaction: RawTransferTransaction,
+    ) -> Result<TxAux> {
+        self.transaction_builder
+            .obfuscate_raw_transfer_tx(raw_transaction)
+    }
+
+    fn broadcast_multisig_transfer_transaction(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        raw_transaction: RawTransferTransaction,
+    ) -> Result<TxId> {
+        let current_block_height = self.get_current_block_height()?;
+        let tx_id = raw_transaction.id();
+        let used_inputs = raw_transaction
+            .inputs()
+            .iter()
+            .map(|input| input.prev_txo_pointer.clone())
+            .collect();
+        let mut return_amount = Coin::zero();
+        for output in raw_transaction.outputs() {
+            if self
+                .wallet_service
+                .find_root_hash(name, enckey, &output.address)?
+                .is_some()
+            {
+                return_amount = (return_amount + output.value).chain(|| {
+                    (
+                        ErrorKind::IllegalInput,
+                        "Sum of returned output values exceeds maximum allowed amount",
+                    )
+                })?;
+            }
+        }
+
+        let transaction = self.multisig_transfer_transaction(raw_transaction)?;
+        self.broadcast_transaction(&transaction)?;
+        let tx_pending = TransactionPending {
+            used_inputs,
+            block_height: current_block_height,
+            return_amount,
+        };
+        self.update_tx_pending_state(name, enckey, tx_id, tx_pending)?;
+
+        Ok(tx_id)
+    }
+
+    fn new_signing_package(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        unsigned_transaction: Tx,
+        signer_public_keys: Vec<PublicKey>,
+    ) -> Result<SigningPackage> {
+        if unsigned_transaction.inputs.len() != 1 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Multi-Sig Signing is only supported for transactions with only one input",
+            ));
+        }
+
+        let spent_output = self.output(name, enckey, &unsigned_transaction.inputs[0])?;
+        let root_hash = self
+            .wallet_service
+            .find_root_hash(name, enckey, &spent_output.address)?
+            .chain(|| {
+                (
+                    ErrorKind::IllegalInput,
+                    "Output address is not owned by current wallet; cannot spend output in given transaction",
+                )
+            })?;
+        let proof = self.root_hash_service.generate_proof(
+            name,
+            &root_hash,
+            signer_public_keys.clone(),
+            enckey,
+        )?;
+        let package = SigningPackage::new(
+            unsigned_transaction,
+            spent_output,
+            proof,
+            signer_public_keys,
+        )?;
+
+        self.import_signing_package(name, enckey, package)
+    }
+
+    fn import_signing_package(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        package: SigningPackage,
+    ) -> Result<SigningPackage> {
+        // Check if wallet exists and the enckey is correct
+        self.wallet_service.view_key(name, enckey)?;
+
+        // the spent output is only trusted if it matches the one in local wallet state
+        let input = match package.unsigned_transaction.inputs.as_slice() {
+            [input] => input,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Multi-Sig Signing is only supported for transactions with only one input",
+                ))
+            }
+        };
+        if self.output(name, enckey, input)? != package.spent_output {
+            return Err(Error::new(
+                ErrorKind::IllegalInput,
+                "Spent output in signing package does not match the output of transaction input",
+            ));
+        }
+
+        for public_key in package.public_keys() {
+            if let Some(private_key) = self.private_key(name, enckey, &public_key)? {
+                return self.multi_sig_session_service.import_package(
+                    name,
+                    package,
+                    public_key,
+                    private_key,
+                    enckey,
+                );
+            }
+        }
+
+        Err(Error::new(
+            ErrorKind::InvalidInput,
+            "None of the signers of signing package is owned by current wallet",
+        ))
+    }
+
+    fn export_signing_package(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        transaction_id: &TxId,
+    ) -> Result<SigningPackage> {
+        // Check if wallet exists and the enckey is correct
+        self.wallet_service.view_key(name, enckey)?;
+
+        self.multi_sig_session_service
+            .export_package(name, transaction_id, enckey)
+    }
+
+    fn signing_package_transaction(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        package: SigningPackage,
+    ) -> Result<TxAux> {
+        let package = self.import_signing_package(name, enckey, package)?;
+        let signature =
+            self.multi_sig_session_service
+                .package_signature(name, &package.id(), enckey)?;
+
+        let witness = TxWitness::from(vec![TxInWitness::TreeSig(signature, package.proof)]);
+        let signed_transaction =
+            SignedTransaction::TransferTransaction(package.unsigned_transaction, witness);
+
+        self.transaction_builder.obfuscate(signed_transaction)
+    }
 }
 
 fn check_passphrase_strength(name: &str, passphrase: &SecUtf8) -> Result<()> {
@@ -1653,6 +2096,43 @@ mod tests {
             .expect("restore wallet");
     }
 
+    #[test]
+    fn check_change_passphrase() {
+        let words = Mnemonic::from_secstr(&SecUtf8::from("pony thank pluck sweet bless tuna couple eight stove fluid essay debate cinnamon elite only")).unwrap();
+        let name = "Default";
+        let passphrase = SecUtf8::from("123456");
+        let new_passphrase = SecUtf8::from("654321");
+        let client = DefaultWalletClient::new_read_only(MemoryStorage::default());
+        let enckey = client
+            .restore_wallet(name, &passphrase, &words)
+            .expect("restore wallet");
+        let transfer_address = client.new_transfer_address(name, &enckey).unwrap();
+
+        assert!(client
+            .change_passphrase(name, &new_passphrase, &passphrase)
+            .is_err());
+        let new_enckey = client
+            .change_passphrase(name, &passphrase, &new_passphrase)
+            .expect("change passphrase");
+
+        assert!(client.auth_token(name, &passphrase).is_err());
+        assert!(client
+            .transfer_addresses(name, &enckey, 0, 0, false)
+            .is_err());
+        assert_eq!(
+            client.auth_token(name, &new_passphrase).unwrap(),
+            new_enckey
+        );
+        assert!(client
+            .transfer_addresses(name, &new_enckey, 0, 0, false)
+            .unwrap()
+            .contains(&transfer_address));
+        client.new_transfer_address(name, &new_enckey).unwrap();
+        client
+            .delete_wallet(name, &new_passphrase)
+            .expect("delete wallet");
+    }
+
     #[test]
     fn check_restore_wallet_twice() {
         let words = Mnemonic::from_secstr(&SecUtf8::from("pony thank pluck sweet bless tuna couple eight stove fluid essay debate cinnamon elite only")).unwrap();
@@ -1755,4 +2235,74 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[cfg(feature = "experimental")]
+    #[test]
+    fn check_signing_package_spent_output_is_checked() {
+        use crate::types::TransactionType;
+
+        let words = Mnemonic::from_secstr(&SecUtf8::from("pony thank pluck sweet bless tuna couple eight stove fluid essay debate cinnamon elite only")).unwrap();
+        let name = "Default";
+        let passphrase = SecUtf8::from("123456");
+        let client = DefaultWalletClient::new_read_only(MemoryStorage::default());
+        let enckey = client
+            .restore_wallet(name, &passphrase, &words)
+            .expect("restore wallet");
+
+        let public_key = client
+            .new_public_key(name, &enckey, Some(AddressType::Transfer))
+            .unwrap();
+        let other_public_key = PublicKey::from(&PrivateKey::new().unwrap());
+        let signer_public_keys = vec![public_key.clone(), other_public_key];
+        let address = client
+            .new_multisig_transfer_address(name, &enckey, signer_public_keys.clone(), public_key, 2)
+            .unwrap();
+
+        // output to the multi-sig address known to the wallet
+        let spent_output = TxOut::new(address, Coin::unit());
+        let mut memento = WalletStateMemento::default();
+        memento.add_transaction_change(TransactionChange {
+            transaction_id: [0; 32],
+            inputs: Vec::new(),
+            outputs: vec![spent_output],
+            balance_change: BalanceChange::NoChange,
+            transaction_type: TransactionType::Transfer,
+            block_height: 0,
+            fee_paid: Fee::new(Coin::zero()),
+            block_time: Time::from_str("2019-04-09T09:38:41.735577Z").unwrap(),
+            label: None,
+            address_labels: Default::default(),
+        });
+        client
+            .wallet_state_service
+            .apply_memento(name, &enckey, &memento)
+            .unwrap();
+
+        let transaction = Tx::new_with(
+            vec![TxoPointer::new([0; 32], 0)],
+            vec![TxOut::new(ExtendedAddr::OrTree([1; 32]), Coin::unit())],
+            TxAttributes::new(0),
+        );
+        let package = client
+            .new_signing_package(name, &enckey, transaction, signer_public_keys)
+            .unwrap();
+        let transaction_id = package.id();
+        assert_eq!(
+            client
+                .export_signing_package(name, &enckey, &transaction_id)
+                .unwrap(),
+            package
+        );
+        assert!(client
+            .export_signing_package("Other", &enckey, &transaction_id)
+            .is_err());
+
+        // a co-signer can't make the wallet sign for an output different from the spent one
+        let mut forged_package = package;
+        forged_package.spent_output.value = Coin::zero();
+        let error = client
+            .import_signing_package(name, &enckey, forged_package)
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::IllegalInput);
+    }
 }
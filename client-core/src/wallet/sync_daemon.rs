@@ -0,0 +1,338 @@
+use std::cmp;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use client_common::tendermint::{Client, NewBlockSubscriber};
+use client_common::{ErrorKind, Result, SecureStorage};
+
+use super::syncer::{
+    AddressRecovery, LightClientHandle, ProgressReport, TxDecryptor, WalletSyncer,
+};
+
+/// Options for following the chain
+#[derive(Clone, Debug)]
+pub struct FollowOptions {
+    /// Delay before the first re-subscription attempt after a disconnect
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between re-subscription attempts
+    pub max_backoff: Duration,
+    /// How long to wait for a new block event before checking the stop flag again
+    pub poll_interval: Duration,
+}
+
+impl Default for FollowOptions {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Keeps a wallet synchronized by applying every new block as soon as tendermint announces it
+///
+/// # How it works
+///
+/// - Subscribes to `NewBlock` events and catches up with blocks committed while not subscribed.
+/// - For each event, runs an incremental `WalletSyncer::sync`, which verifies the new blocks
+///   through the light client (unless disabled) and rolls back pending transactions which were
+///   not included after `block_height_ensure` blocks.
+/// - When the subscription is lost or a sync fails, subscribes again with exponential back-off.
+/// - Verification errors are not retried, as they indicate that the node can't be trusted.
+pub struct SyncDaemon<S, C, D, T, L, B>
+where
+    S: SecureStorage + 'static,
+    C: Client,
+    D: TxDecryptor,
+    T: AddressRecovery,
+    L: LightClientHandle,
+    B: NewBlockSubscriber,
+{
+    syncer: WalletSyncer<S, C, D, T, L>,
+    subscriber: B,
+    options: FollowOptions,
+    stop: Arc<AtomicBool>,
+}
+
+impl<S, C, D, T, L, B> SyncDaemon<S, C, D, T, L, B>
+where
+    S: SecureStorage + 'static,
+    C: Client,
+    D: TxDecryptor,
+    T: AddressRecovery,
+    L: LightClientHandle,
+    B: NewBlockSubscriber,
+{
+    /// Creates a new daemon for given wallet syncer
+    pub fn new(
+        syncer: WalletSyncer<S, C, D, T, L>,
+        subscriber: B,
+        options: FollowOptions,
+    ) -> SyncDaemon<S, C, D, T, L, B> {
+        Self::with_stop_handle(
+            syncer,
+            subscriber,
+            options,
+            Arc::new(AtomicBool::new(false)),
+        )
+    }
+
+    /// Creates a new daemon which stops when `stop` is set to `true`, so that the flag can be
+    /// registered before the daemon is created
+    pub fn with_stop_handle(
+        syncer: WalletSyncer<S, C, D, T, L>,
+        subscriber: B,
+        options: FollowOptions,
+        stop: Arc<AtomicBool>,
+    ) -> SyncDaemon<S, C, D, T, L, B> {
+        SyncDaemon {
+            syncer,
+            subscriber,
+            options,
+            stop,
+        }
+    }
+
+    /// Returns a flag which stops the daemon when set to `true`
+    pub fn stop_handle(&self) -> Arc<AtomicBool> {
+        self.stop.clone()
+    }
+
+    /// Follows the chain until stopped, either through the stop handle or by returning `false`
+    /// from `callback`
+    pub fn run<F: FnMut(ProgressReport) -> bool>(&mut self, mut callback: F) -> Result<()> {
+        let stop = self.stop.clone();
+        let mut callback = move |report| {
+            if !callback(report) {
+                stop.store(true, Ordering::SeqCst);
+            }
+            !stop.load(Ordering::SeqCst)
+        };
+
+        let mut backoff = self.options.initial_backoff;
+
+        while !self.is_stopped() {
+            match self.follow(&mut callback) {
+                Ok(0) => {}
+                Ok(_) => backoff = self.options.initial_backoff,
+                Err(err) if self.is_stopped() => {
+                    log::debug!("wallet {} sync cancelled: {}", self.syncer.name(), err);
+                    break;
+                }
+                Err(err) if err.kind() == ErrorKind::VerifyError => return Err(err),
+                Err(err) => log::warn!("wallet {} sync failed: {}", self.syncer.name(), err),
+            }
+
+            if self.is_stopped() {
+                break;
+            }
+
+            log::info!(
+                "wallet {} re-subscribing to new blocks in {:?}",
+                self.syncer.name(),
+                backoff
+            );
+            self.sleep(backoff);
+            backoff = cmp::min(backoff * 2, self.options.max_backoff);
+        }
+
+        Ok(())
+    }
+
+    /// Subscribes to new blocks and syncs on each of them until disconnected, returns the number
+    /// of new block events handled
+    fn follow<F: FnMut(ProgressReport) -> bool>(&mut self, callback: &mut F) -> Result<usize> {
+        let receiver = self.subscriber.subscribe_new_block()?;
+
+        if self.syncer.is_ahead_of_node()? {
+            log::warn!(
+                "node is behind wallet {}, waiting for it to catch up",
+                self.syncer.name()
+            );
+            return Ok(0);
+        }
+
+        // catch up with the blocks committed while not subscribed
+        self.syncer.sync(&mut *callback)?;
+
+        let mut events = 0;
+        while !self.is_stopped() {
+            match receiver.recv_timeout(self.options.poll_interval) {
+                Ok(height) => {
+                    events += 1;
+                    // one sync catches up to the latest block, so queued events can be skipped
+                    let height = receiver.try_iter().last().unwrap_or(height);
+                    log::debug!(
+                        "new block {}, syncing wallet {}",
+                        height,
+                        self.syncer.name()
+                    );
+                    self.syncer.sync(&mut *callback)?;
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    log::warn!(
+                        "new block subscription of wallet {} disconnected",
+                        self.syncer.name()
+                    );
+                    break;
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::SeqCst)
+    }
+
+    /// Sleeps for given duration, waking up early if stopped
+    fn sleep(&self, duration: Duration) {
+        let deadline = Instant::now() + duration;
+        while !self.is_stopped() {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            thread::sleep(cmp::min(deadline - now, self.options.poll_interval));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc::{channel, Receiver, Sender};
+    use std::sync::Mutex;
+
+    use secstr::SecUtf8;
+
+    use chain_core::tx::data::TxId;
+    use client_common::storage::MemoryStorage;
+    use client_common::Transaction;
+    use test_common::block_generator::{BlockGenerator, GeneratorClient};
+
+    use crate::hd_wallet::HardwareKind;
+    use crate::service::load_sync_state;
+    use crate::types::WalletKind;
+    use crate::wallet::syncer::{compute_genesis_fingerprint, SyncerConfig, SyncerOptions};
+    use crate::wallet::{DefaultWalletClient, WalletClient};
+
+    /// Hands out one subscription per call, keeping the senders so the test can emit events and
+    /// simulate disconnects by dropping them
+    #[derive(Clone, Default)]
+    struct MockSubscriber {
+        senders: Arc<Mutex<Vec<Sender<u64>>>>,
+    }
+
+    impl NewBlockSubscriber for MockSubscriber {
+        fn subscribe_new_block(&self) -> Result<Receiver<u64>> {
+            let (sender, receiver) = channel();
+            self.senders.lock().unwrap().push(sender);
+            Ok(receiver)
+        }
+    }
+
+    fn wait_until<P: Fn() -> bool>(predicate: P) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !predicate() {
+            assert!(Instant::now() < deadline, "timed out");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn check_sync_daemon_follows_new_blocks() {
+        let storage = MemoryStorage::default();
+        let name = "name";
+        let passphrase = SecUtf8::from("passphrase");
+
+        let wallet = DefaultWalletClient::new_read_only(storage.clone());
+        let (enckey, _) = wallet
+            .new_wallet(
+                name,
+                &passphrase,
+                WalletKind::Basic,
+                HardwareKind::LocalOnly,
+                None,
+            )
+            .unwrap();
+
+        let client = GeneratorClient::new(BlockGenerator::one_node());
+        // the first block is verified with the commit included in the second one
+        client.gen.write().unwrap().gen_block(&[]);
+        client.gen.write().unwrap().gen_block(&[]);
+        let genesis = client.genesis().unwrap();
+        let hash = compute_genesis_fingerprint(&genesis).unwrap();
+        std::env::set_var("CRYPTO_GENESIS_FINGERPRINT", hash);
+
+        let syncer = WalletSyncer::with_config(
+            SyncerConfig {
+                storage: storage.clone(),
+                client: client.clone(),
+                light_client: Some(client.clone()),
+                options: SyncerOptions {
+                    enable_fast_forward: false,
+                    disable_light_client: false,
+                    enable_address_recovery: false,
+                    batch_size: 20,
+                    block_height_ensure: 50,
+                    light_client_peers: "".into(),
+                    light_client_trusting_period_seconds: 36000000,
+                    light_client_trusting_height: 1,
+                    light_client_trusting_blockhash: "".into(),
+                    follow_new_blocks: true,
+                    light_client_report_evidence: false,
+                },
+            },
+            |_txids: &[TxId]| -> Result<Vec<Transaction>> { Ok(vec![]) },
+            name.to_owned(),
+            enckey,
+            wallet,
+        );
+        let subscriber = MockSubscriber::default();
+        let mut daemon = SyncDaemon::new(
+            syncer,
+            subscriber.clone(),
+            FollowOptions {
+                initial_backoff: Duration::from_millis(10),
+                max_backoff: Duration::from_millis(100),
+                poll_interval: Duration::from_millis(10),
+            },
+        );
+        let stop = daemon.stop_handle();
+        let handle = thread::spawn(move || daemon.run(|_| true).is_ok());
+
+        let synced_height = || {
+            load_sync_state(&storage, name)
+                .unwrap()
+                .map(|sync_state| sync_state.last_block_height)
+                .unwrap_or_default()
+        };
+
+        // catches up on subscription
+        wait_until(|| synced_height() == 2);
+
+        // follows new blocks
+        for height in 3..=4 {
+            client.gen.write().unwrap().gen_block(&[]);
+            subscriber.senders.lock().unwrap()[0].send(height).unwrap();
+            wait_until(|| synced_height() == height);
+        }
+
+        // re-subscribes and catches up after a disconnect
+        client.gen.write().unwrap().gen_block(&[]);
+        subscriber.senders.lock().unwrap().clear();
+        wait_until(|| !subscriber.senders.lock().unwrap().is_empty());
+        wait_until(|| synced_height() == 5);
+
+        stop.store(true, Ordering::SeqCst);
+        assert!(handle.join().unwrap());
+    }
+}
@@ -38,6 +38,7 @@ use chain_core::tx::fee::Fee;
 use chain_storage::jellyfish::compute_staking_root;
 use chain_tx_filter::BlockFilter;
 use chain_util::NonEmpty;
+use client_common::tendermint::lite::{cross_check, CrossCheck, EvidenceReporter, TrustedState};
 use client_common::tendermint::types::{
     Block, BlockExt, BlockResults, BlockResultsResponse, Genesis, Time,
 };
@@ -50,11 +51,17 @@ use client_common::{
 use super::syncer_logic::handle_blocks;
 use crate::service;
 use crate::service::{KeyService, SyncState, Wallet, WalletState, WalletStateMemento};
+use crate::types::StakingHistoryEntry;
 use std::sync::Mutex;
 type BlockConfirmFunc = Arc<Mutex<Box<dyn Fn(u64, String) -> bool>>>; // height, blockhash
 
-pub trait LightClientHandle: Handle + Send + Sync + Clone {}
-impl<T: Handle + Send + Sync + Clone> LightClientHandle for T {}
+pub trait LightClientHandle: Handle + Send + Sync + Clone {
+    /// Cross-check a light block verified from the primary against the witnesses, fails with
+    /// `ErrorKind::VerifyError` if a witness reports a conflicting header
+    fn cross_check(&self, _light_block: &LightBlock) -> Result<()> {
+        Ok(())
+    }
+}
 
 pub trait AddressRecovery: Clone + Send + Sync {
     // new_address: transfer address in TxOut
@@ -118,6 +125,8 @@ pub struct SyncerOptions {
     pub light_client_trusting_period_seconds: u64,
     pub light_client_trusting_height: u64,
     pub light_client_trusting_blockhash: String,
+    pub follow_new_blocks: bool,
+    pub light_client_report_evidence: bool,
 }
 
 /// Common configs for wallet syncer with `TransactionObfuscation`
@@ -163,12 +172,12 @@ impl<S: SecureStorage, C: Client, O: TransactionObfuscation, L: LightClientHandl
 #[derive(Clone)]
 pub struct SyncerConfig<S: SecureStorage, C: Client, L: LightClientHandle> {
     // services
-    storage: S,
-    client: C,
-    light_client: Option<L>,
+    pub(crate) storage: S,
+    pub(crate) client: C,
+    pub(crate) light_client: Option<L>,
 
     // configs
-    options: SyncerOptions,
+    pub(crate) options: SyncerOptions,
 }
 
 /// Wallet Syncer
@@ -221,10 +230,11 @@ where
         }
     }
 
-    /// Delete sync state and wallet state.
+    /// Delete sync state, wallet state and staking history.
     pub fn reset_state(&self) -> Result<()> {
         service::delete_sync_state(&self.storage, &self.name)?;
         service::delete_wallet_state(&self.storage, &self.name)?;
+        service::delete_staking_history(&self.storage, &self.name)?;
         Ok(())
     }
 
@@ -232,6 +242,21 @@ where
     pub fn sync<F: FnMut(ProgressReport) -> bool>(&mut self, callback: F) -> Result<()> {
         WalletSyncerImpl::new(self, callback)?.sync()
     }
+
+    /// Name of the wallet being synchronized
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns true if the connected node has not reached the height this wallet is already
+    /// synchronized to, e.g. after failing over to a lagging node.
+    pub fn is_ahead_of_node(&self) -> Result<bool> {
+        let synced_height = service::load_sync_state(&self.storage, &self.name)?
+            .map(|sync_state| sync_state.last_block_height)
+            .unwrap_or_default();
+        let status = self.client.status()?;
+        Ok(status.sync_info.latest_block_height.value() < synced_height)
+    }
 }
 
 fn load_view_key<S: SecureStorage>(storage: &S, name: &str, enckey: &SecKey) -> Result<PrivateKey> {
@@ -446,6 +471,17 @@ impl<
             handle_blocks_time.elapsed().as_micros()
         );
 
+        let staking_history = blocks
+            .iter()
+            .flat_map(|block| block.staking_history.iter().cloned())
+            .collect::<Vec<_>>();
+        service::add_staking_history(
+            &self.env.storage,
+            &self.env.name,
+            &self.env.enckey,
+            &staking_history,
+        )?;
+
         let block = blocks.last();
         self.sync_state.last_block_height = block.block_height;
         self.sync_state.last_app_hash = block.app_hash.clone();
@@ -500,7 +536,12 @@ impl<
                     .as_ref()
                     .expect("get light client")
                     .verify_to_highest()
-                    .map_err(|e| Error::new(ErrorKind::VerifyError, format!("{}", e)))?;
+                    .map_err(light_client_error)?;
+                self.env
+                    .light_client
+                    .as_ref()
+                    .expect("get light client")
+                    .cross_check(&light_block)?;
 
                 let target_height = light_block.signed_header.header.height.value();
                 let target_app_hash = hex::encode_upper(&light_block.signed_header.header.app_hash);
@@ -785,7 +826,8 @@ pub fn compute_genesis_fingerprint(genesis: &Genesis) -> Result<String> {
     Ok(result)
 }
 
-fn check_genesis_fingerprint(genesis: &Genesis) -> Result<()> {
+/// checks the genesis fetched from a node against the preset genesis fingerprint
+pub fn check_genesis_fingerprint(genesis: &Genesis) -> Result<()> {
     let hash_setted = std::env::var("CRYPTO_GENESIS_FINGERPRINT")
         .unwrap_or_else(|_| CRYPTO_GENESIS_FINGERPRINT.into());
     let hash_online = compute_genesis_fingerprint(genesis)?;
@@ -866,6 +908,8 @@ pub(crate) struct FilteredBlock {
     pub staking_transactions: Vec<Transaction>,
     /// staking root after this block
     pub staking_root: H256,
+    /// Rewards and slashes of the staking addresses of the wallet in this block
+    pub staking_history: Vec<StakingHistoryEntry>,
 }
 
 impl FilteredBlock {
@@ -920,6 +964,19 @@ impl FilteredBlock {
 
         let valid_transaction_fees = block_result.fees()?;
 
+        let mut staking_history = Vec::new();
+        for adjustment in block_result.staking_adjustments()? {
+            if wallet.staking_addresses_contains(&adjustment.staking_address)? {
+                staking_history.push(StakingHistoryEntry {
+                    block_height,
+                    block_time,
+                    staking_address: adjustment.staking_address,
+                    record_type: adjustment.kind.into(),
+                    amount: adjustment.amount,
+                });
+            }
+        }
+
         let enclave_transaction_ids =
             if block_filter.check_view_key(&wallet.view_key.clone().into()) {
                 block.enclave_transaction_ids()?
@@ -939,6 +996,7 @@ impl FilteredBlock {
             block_filter,
             staking_transactions,
             staking_root: state.account_root,
+            staking_history,
         })
     }
 }
@@ -989,10 +1047,29 @@ fn filter_incomming_staking_transactions(
     Ok(Default::default())
 }
 
+/// Verifies the first block fetched by the light client against the pinned genesis, and returns
+/// the light block built from the resulting trusted state
+fn anchor_first_block(genesis: &Genesis, light_block: LightBlock) -> Result<LightBlock> {
+    let trusted_state =
+        TrustedState::genesis(genesis).verify_first_block(&light_block.signed_header)?;
+    let (signed_header, validators) = trusted_state
+        .anchor()
+        .expect("trusted state of a verified first block is anchored");
+    Ok(LightBlock {
+        signed_header: signed_header.clone(),
+        validators: validators.clone(),
+        // the first block has to have the same next validators as the genesis ones
+        next_validators: validators.clone(),
+        provider: light_block.provider,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
 fn make_light_client_instance(
     peer_id: PeerId,
     addr: tendermint::net::Address,
     db_path: impl AsRef<Path>,
+    genesis: &Genesis,
     trusting_period: Duration,
     trusting_height: u64,
     trusting_blockhash: String,
@@ -1021,12 +1098,18 @@ fn make_light_client_instance(
     })?;
 
     if light_store.latest(Status::Verified).is_none() {
-        // FIXME trust height 1 automatically
-        let trusted_state = io
+        let mut trusted_state = io
             .fetch_light_block(peer_id, AtHeight::At(trusting_height))
             .err_kind(ErrorKind::InitializationError, || {
-                "could not retrieve trusted header of block 1"
+                format!(
+                    "could not retrieve trusted header of block {}",
+                    trusting_height
+                )
             })?;
+        if trusting_height == 1 {
+            // anchored in the pinned genesis rather than the header the peer sent
+            trusted_state = anchor_first_block(genesis, trusted_state)?;
+        }
         let db_height = trusted_state.signed_header.commit.height;
         let db_hash = trusted_state.signed_header.commit.block_id.hash.as_bytes();
         if db_height.value() == height
@@ -1150,6 +1233,8 @@ mod tests {
                     light_client_trusting_period_seconds: 36000000,
                     light_client_trusting_height: 1,
                     light_client_trusting_blockhash: "".into(),
+                    follow_new_blocks: false,
+                    light_client_report_evidence: false,
                 },
             },
             |_txids: &[TxId]| -> Result<Vec<Transaction>> { Ok(vec![]) },
@@ -1233,6 +1318,7 @@ mod tests {
             }
         }
         impl Handle for MockTendermintClient {}
+        impl LightClientHandle for MockTendermintClient {}
 
         let storage = MemoryStorage::default();
         let name = "name";
@@ -1283,6 +1369,8 @@ mod tests {
                     light_client_trusting_period_seconds: 36000000,
                     light_client_trusting_height: 1,
                     light_client_trusting_blockhash: "".into(),
+                    follow_new_blocks: false,
+                    light_client_report_evidence: false,
                 },
             },
             |_txids: &[TxId]| -> Result<Vec<Transaction>> { Ok(vec![]) },
@@ -1340,6 +1428,8 @@ mod tests {
                     light_client_trusting_period_seconds: 36000000,
                     light_client_trusting_height: 1,
                     light_client_trusting_blockhash: "".into(),
+                    follow_new_blocks: false,
+                    light_client_report_evidence: false,
                 },
             },
             |_txids: &[TxId]| -> Result<Vec<Transaction>> { Ok(vec![]) },
@@ -1356,6 +1446,7 @@ mod tests {
             address: ExtendedAddr::OrTree([0; 32]),
             value: Coin::new(10).unwrap(),
             valid_from: None,
+            relative_lock: None,
         };
         tx_core.outputs.push(output);
         let tx = Transaction::TransferTransaction(tx_core);
@@ -1404,6 +1495,8 @@ mod tests {
                     light_client_trusting_period_seconds: 36000000,
                     light_client_trusting_height: 1,
                     light_client_trusting_blockhash: "".into(),
+                    follow_new_blocks: false,
+                    light_client_report_evidence: false,
                 },
             },
             |_txids: &[TxId]| -> Result<Vec<Transaction>> { Ok(vec![]) },
@@ -1425,6 +1518,7 @@ mod tests {
 
             value: Coin::new(10).unwrap(),
             valid_from: None,
+            relative_lock: None,
         };
         tx_core.outputs.push(output);
         let tx = Transaction::TransferTransaction(tx_core);
@@ -1498,14 +1592,20 @@ fn parse_node(nodelist_info: &str) -> Result<Vec<(String, String, u32)>> {
 }
 
 /// [new light client design](https://github.com/informalsystems/tendermint-rs/blob/master/docs/architecture/adr-006-light-client-refactor.md)
+///
+/// `genesis` is checked against the preset genesis fingerprint; when trusting height 1,
+/// the light clients are anchored at the first block verified against it.
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_light_client_supervisor(
     db_path: &Path,
+    genesis: &Genesis,
     trusting_period_default: Duration,
     light_client_peers: String,
     light_client_trusting_period_seconds: u64,
     light_client_trusting_height: u64,
     light_client_trusting_blockhash: String,
     light_client_block_confirm: Option<BlockConfirmFunc>,
+    evidence_reporter: Option<Box<dyn EvidenceReporter>>,
 ) -> Result<LightClientWrapper<impl Handle + 'static>> {
     let trusting_period = if 0 == light_client_trusting_period_seconds {
         trusting_period_default
@@ -1513,6 +1613,7 @@ pub fn spawn_light_client_supervisor(
         Duration::from_secs(light_client_trusting_period_seconds)
     };
 
+    check_genesis_fingerprint(genesis)?;
     if "" == light_client_peers {
         return Err(Error::new(
             ErrorKind::InvalidInput,
@@ -1547,6 +1648,7 @@ pub fn spawn_light_client_supervisor(
     let mut peerlist_builder = PeerListBuilder::<Instance>::default();
     // key: nodeid,  data: Address <- ip, port
     let mut all_peers_map = HashMap::new();
+    let mut witness_peers = vec![];
     //for peer_info in peers
     for (i, peer_info) in peers.iter().enumerate() {
         let (nodeid, nodeip, nodeport) = peer_info;
@@ -1567,6 +1669,7 @@ pub fn spawn_light_client_supervisor(
             node_peerid,
             node_address.clone(),
             node_db_path,
+            genesis,
             trusting_period,
             light_client_trusting_height,
             light_client_trusting_blockhash.clone(),
@@ -1577,8 +1680,22 @@ pub fn spawn_light_client_supervisor(
             peerlist_builder = peerlist_builder.primary(node_peerid, node_instance);
         } else {
             peerlist_builder = peerlist_builder.witness(node_peerid, node_instance);
+            witness_peers.push(node_peerid);
         }
     }
+    // witnesses every verified header is cross-checked against
+    let io = ProdIo::new(all_peers_map.clone(), Some(Duration::from_secs(10)));
+    let witnesses = Witnesses {
+        fetch_light_block: Box::new(move |peer_id, height| {
+            io.fetch_light_block(peer_id, AtHeight::At(height))
+                .map_err(|e| e.to_string())
+        }),
+        peers: witness_peers,
+        evidence_reporter,
+    };
+    if witnesses.peers.is_empty() {
+        log::warn!("no light client witness, headers from the primary can't be cross-checked");
+    }
     // get peer_list
     let all_peers_list = peerlist_builder.build();
     let mut supervisor = Supervisor::new(
@@ -1591,22 +1708,122 @@ pub fn spawn_light_client_supervisor(
     std::thread::spawn(|| supervisor.run());
     Ok(LightClientWrapper {
         inner: Arc::new(light_client_core_handle),
+        witnesses: Arc::new(witnesses),
     })
 }
 
+/// Only a detected fork or an invalid light block is a verification failure (which halts
+/// syncing), other light client errors (e.g. unreachable peers) can be retried
+fn light_client_error(e: tendermint_light_client::errors::Error) -> Error {
+    use tendermint_light_client::errors::ErrorKind as LightClientErrorKind;
+    let kind = match e.kind() {
+        LightClientErrorKind::ForkDetected(_) | LightClientErrorKind::InvalidLightBlock(_) => {
+            ErrorKind::VerifyError
+        }
+        _ => ErrorKind::ConnectionError,
+    };
+    Error::new(kind, format!("{}", e))
+}
+
+/// Fetches the light block at given height from a peer
+type FetchLightBlock =
+    dyn Fn(PeerId, u64) -> result::Result<LightBlock, String> + Send + Sync + 'static;
+
+/// Witnesses of the light client, queried directly to detect light client attacks on the primary
+struct Witnesses {
+    fetch_light_block: Box<FetchLightBlock>,
+    peers: Vec<PeerId>,
+    evidence_reporter: Option<Box<dyn EvidenceReporter>>,
+}
+
+impl Witnesses {
+    /// Compares the light block verified from the primary with the header of every witness at
+    /// the same height; witnesses which can't be reached or which are faulty themselves are
+    /// skipped, a conflicting header signed by the trusted validators halts syncing.
+    /// At least one witness (if any is configured) has to agree with the primary.
+    fn cross_check(&self, light_block: &LightBlock) -> Result<()> {
+        if self.peers.is_empty() {
+            return Ok(());
+        }
+        let height = light_block.signed_header.header.height.value();
+        let mut agreed = 0;
+        for peer_id in self.peers.iter() {
+            let witness_block = match (self.fetch_light_block)(*peer_id, height) {
+                Ok(witness_block) => witness_block,
+                Err(e) => {
+                    log::warn!(
+                        "unable to fetch block {} from witness {}: {}",
+                        height,
+                        peer_id,
+                        e
+                    );
+                    continue;
+                }
+            };
+            match cross_check(
+                &light_block.signed_header,
+                &light_block.validators,
+                &witness_block.signed_header,
+            ) {
+                CrossCheck::Agree => agreed += 1,
+                CrossCheck::FaultyWitness(reason) => {
+                    log::warn!("faulty witness {} at block {}: {}", peer_id, height, reason);
+                }
+                CrossCheck::Conflict(evidence) => {
+                    if let Some(reporter) = &self.evidence_reporter {
+                        match reporter.report_evidence(&evidence) {
+                            Ok(()) => log::info!("light client attack evidence submitted"),
+                            Err(e) => log::error!("unable to submit evidence: {}", e),
+                        }
+                    }
+                    return Err(Error::new(
+                        ErrorKind::VerifyError,
+                        format!(
+                            "Light client attack detected at block {}: primary {} and witness {} \
+                             report conflicting headers, syncing halted",
+                            height, light_block.provider, peer_id
+                        ),
+                    ));
+                }
+            }
+        }
+        if agreed == 0 {
+            return Err(Error::new(
+                ErrorKind::ConnectionError,
+                format!(
+                    "Block {} from primary {} couldn't be cross-checked: none of the {} witnesses \
+                     agreed with it",
+                    height,
+                    light_block.provider,
+                    self.peers.len()
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// A wrapper over light client `Handle` which supports `Clone`
 pub struct LightClientWrapper<L> {
     inner: Arc<L>,
+    witnesses: Arc<Witnesses>,
 }
 
 impl<L> Clone for LightClientWrapper<L> {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            witnesses: self.witnesses.clone(),
         }
     }
 }
 
+impl<L: Handle + Send + Sync> LightClientHandle for LightClientWrapper<L> {
+    fn cross_check(&self, light_block: &LightBlock) -> Result<()> {
+        self.witnesses.cross_check(light_block)
+    }
+}
+
 impl<L: Handle> Handle for LightClientWrapper<L> {
     fn latest_trusted(
         &self,
@@ -1639,6 +1856,12 @@ impl<L: Handle> Handle for LightClientWrapper<L> {
 mod test_syncer {
     use super::*;
 
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+    use std::time::UNIX_EPOCH;
+
+    use client_common::tendermint::lite::ConflictingHeadersEvidence;
+    use test_common::block_generator::{BlockGenerator, TestnetSpec, GENESIS_TIMESTAMP};
+
     #[test]
     fn check_parse_node() {
         let a=parse_node("1ADFADAD0BEFEEDC0C0ADEADBEEFC0FFEEFACADE@1.2.3.4:1000,2ADFADAD0BEFEEDC0C0ADEADBEEFC0FFEEFACADF@5.6.7.8:2000").unwrap();
@@ -1659,4 +1882,85 @@ mod test_syncer {
         let a = parse_node("");
         assert!(a.is_err());
     }
+
+    struct CountingReporter(Arc<AtomicUsize>);
+
+    impl EvidenceReporter for CountingReporter {
+        fn report_evidence(&self, _evidence: &ConflictingHeadersEvidence) -> Result<()> {
+            self.0.fetch_add(1, AtomicOrdering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn light_block(gen: &BlockGenerator, height: u64, provider: PeerId) -> LightBlock {
+        LightBlock {
+            signed_header: gen.blocks[height as usize - 1].signed_header(),
+            validators: gen.validators.clone(),
+            next_validators: gen.validators.clone(),
+            provider,
+        }
+    }
+
+    #[test]
+    fn check_witnesses_cross_check() {
+        let primary_id: PeerId = "1ADFADAD0BEFEEDC0C0ADEADBEEFC0FFEEFACADE".parse().unwrap();
+        let honest_id: PeerId = "2ADFADAD0BEFEEDC0C0ADEADBEEFC0FFEEFACADE".parse().unwrap();
+        let forked_id: PeerId = "3ADFADAD0BEFEEDC0C0ADEADBEEFC0FFEEFACADE".parse().unwrap();
+        let offline_id: PeerId = "4ADFADAD0BEFEEDC0C0ADEADBEEFC0FFEEFACADE".parse().unwrap();
+
+        let mut honest = BlockGenerator::one_node();
+        // same validators, but the chain diverges from the first block on
+        let mut spec = TestnetSpec::new(honest.spec.nodes.clone());
+        spec.genesis_time = (UNIX_EPOCH + Duration::from_secs(GENESIS_TIMESTAMP + 1)).into();
+        let mut forked = BlockGenerator::new(spec);
+        for gen in [&mut honest, &mut forked].iter_mut() {
+            gen.gen_block(&[]);
+            gen.gen_block(&[]);
+        }
+        let honest = Arc::new(honest);
+        let forked = Arc::new(forked);
+        let primary_block = light_block(&honest, 2, primary_id);
+
+        let witnesses = |peers: Vec<PeerId>, reported: Arc<AtomicUsize>| {
+            let (honest, forked) = (honest.clone(), forked.clone());
+            Witnesses {
+                fetch_light_block: Box::new(move |peer_id, height| {
+                    if peer_id == honest_id {
+                        Ok(light_block(&honest, height, peer_id))
+                    } else if peer_id == forked_id {
+                        Ok(light_block(&forked, height, peer_id))
+                    } else {
+                        Err("connection refused".to_owned())
+                    }
+                }),
+                peers,
+                evidence_reporter: Some(Box::new(CountingReporter(reported))),
+            }
+        };
+
+        // no witness configured
+        let reported = Arc::new(AtomicUsize::new(0));
+        assert!(witnesses(vec![], reported.clone())
+            .cross_check(&primary_block)
+            .is_ok());
+
+        // agreeing witness, unreachable ones are skipped
+        assert!(witnesses(vec![offline_id, honest_id], reported.clone())
+            .cross_check(&primary_block)
+            .is_ok());
+        assert_eq!(reported.load(AtomicOrdering::SeqCst), 0);
+
+        // no witness could be reached, which can be retried
+        let err = witnesses(vec![offline_id], reported.clone())
+            .cross_check(&primary_block)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionError);
+
+        // conflicting witness, even if another one agrees
+        let err = witnesses(vec![honest_id, forked_id], reported.clone())
+            .cross_check(&primary_block)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::VerifyError);
+        assert_eq!(reported.load(AtomicOrdering::SeqCst), 1);
+    }
 }
@@ -107,6 +107,8 @@ pub fn create_transaction_change(
         transaction_type,
         block_height,
         block_time,
+        label: None,
+        address_labels: Default::default(),
     };
     Ok(transaction_change)
 }
@@ -309,6 +311,7 @@ mod tests {
             attributes: StakedStateOpAttributes {
                 chain_hex_id: 0,
                 app_version: 0,
+                priority_fee: Coin::zero(),
             },
         })
     }
@@ -371,6 +374,7 @@ mod tests {
             block_filter,
             staking_transactions: other_txs.to_vec(),
             staking_root,
+            staking_history: Vec::new(),
         }
     }
 
@@ -514,14 +518,14 @@ mod tests {
         }
 
         assert_eq!(
-            states[0].get_balance().unwrap().total,
+            states[0].get_balance(0, 0).unwrap().total,
             Coin::new(0).unwrap()
         );
         assert_eq!(states[0].transaction_history.len(), 2);
         assert_eq!(states[0].unspent_transactions.len(), 0);
 
         assert_eq!(
-            states[1].get_balance().unwrap().total,
+            states[1].get_balance(0, 0).unwrap().total,
             Coin::new(100).unwrap()
         );
         assert_eq!(states[1].transaction_history.len(), 1);
@@ -784,6 +784,7 @@ mod tests {
             address: ExtendedAddr::OrTree([0; 32]),
             value: Coin::new(10).unwrap(),
             valid_from: None,
+            relative_lock: None,
         };
         let transactions = vec![(input, output)];
 
@@ -117,6 +117,18 @@ pub struct Options {
         help = "Number of block height to rollback the utxos in the pending transactions"
     )]
     pub block_height_ensure: u64,
+    #[structopt(
+        name = "sync-follow",
+        long,
+        help = "Keep looping wallet syncs running by subscribing to new block events instead of polling"
+    )]
+    pub sync_follow: bool,
+    #[structopt(
+        name = "light-client-report-evidence",
+        long,
+        help = "Submit evidence to the node when light client witnesses report conflicting headers"
+    )]
+    pub light_client_report_evidence: bool,
 }
 
 #[allow(dead_code)]
@@ -53,6 +53,8 @@ impl Server {
                 light_client_trusting_period_seconds: options.light_client_trusting_period_seconds,
                 light_client_trusting_height: options.light_client_trusting_height,
                 light_client_trusting_blockhash: options.light_client_trusting_blockhash,
+                follow_new_blocks: options.sync_follow,
+                light_client_report_evidence: options.light_client_report_evidence,
             },
         })
     }
@@ -5,10 +5,11 @@ use crate::rpc::multisig_rpc::{MultiSigRpc, MultiSigRpcImpl};
 use chain_core::tx::fee::FeeAlgorithm;
 use client_common::cipher::TransactionObfuscation;
 use client_common::storage::SledStorage;
+use client_common::tendermint::lite::EvidenceReporter;
 use client_common::tendermint::{types::GenesisExt, Client, WebsocketRpcClient};
 use client_common::Result;
 use client_common::Storage;
-use client_core::service::HwKeyService;
+use client_core::service::{open_sled_storage, HwKeyService};
 use client_core::signer::WalletSignerManager;
 use client_core::transaction_builder::DefaultWalletTransactionBuilder;
 use client_core::wallet::syncer::{
@@ -21,6 +22,7 @@ use crate::rpc::{
     info_rpc::{InfoRpc, InfoRpcImpl},
     staking_rpc::{StakingRpc, StakingRpcImpl},
     sync_rpc::{CBindingCore, SyncRpc, SyncRpcImpl},
+    sync_worker::WorkerShared,
     transaction_rpc::{TransactionRpc, TransactionRpcImpl},
     wallet_rpc::{WalletRpc, WalletRpcImpl},
 };
@@ -48,7 +50,7 @@ impl RpcHandler {
         progress_callback: Option<CBindingCore>,
     ) -> Result<Self> {
         let mut io = IoHandler::new();
-        let storage = SledStorage::new(&storage_dir)?;
+        let storage = open_sled_storage(&storage_dir)?;
 
         let polling_storage = storage.clone();
         std::thread::spawn(move || {
@@ -75,17 +77,26 @@ impl RpcHandler {
             fee_policy.clone(),
             tendermint_client.clone(),
         )?;
+        let evidence_reporter: Option<Box<dyn EvidenceReporter>> =
+            if sync_options.light_client_report_evidence {
+                Some(Box::new(tendermint_client.clone()))
+            } else {
+                None
+            };
         let handle = if sync_options.disable_light_client {
             None
         } else {
+            let genesis = tendermint_client.genesis()?;
             Some(spawn_light_client_supervisor(
                 storage_dir.as_ref(),
-                tendermint_client.genesis()?.trusting_period() / 2,
+                &genesis,
+                genesis.trusting_period() / 2,
                 sync_options.light_client_peers.clone(),
                 sync_options.light_client_trusting_period_seconds,
                 sync_options.light_client_trusting_height,
                 sync_options.light_client_trusting_blockhash.clone(),
                 None,
+                evidence_reporter,
             )?)
         };
         let syncer_config = AppSyncerConfig::new(
@@ -97,7 +108,7 @@ impl RpcHandler {
         );
 
         #[cfg(feature = "experimental")]
-        let multisig_rpc = MultiSigRpcImpl::new(wallet_client.clone());
+        let multisig_rpc = MultiSigRpcImpl::new(wallet_client.clone(), network_id);
         let transaction_rpc = TransactionRpcImpl::new(network_id);
         let staking_rpc =
             StakingRpcImpl::new(wallet_client.clone(), ops_client.clone(), network_id);
@@ -106,9 +117,16 @@ impl RpcHandler {
         let sync_wallet_client =
             make_wallet_client(storage, tendermint_client, fee_policy, obfuscation)?;
 
-        let sync_rpc =
-            SyncRpcImpl::new(syncer_config, progress_callback, sync_wallet_client, handle);
-        let wallet_rpc = WalletRpcImpl::new(wallet_client, network_id);
+        // shared, so that the wallet rpc knows which wallets are being synced
+        let sync_worker = WorkerShared::default();
+        let sync_rpc = SyncRpcImpl::new(
+            syncer_config,
+            progress_callback,
+            sync_wallet_client,
+            handle,
+            sync_worker.clone(),
+        );
+        let wallet_rpc = WalletRpcImpl::new(wallet_client, network_id, sync_worker);
 
         #[cfg(feature = "experimental")]
         io.extend_with(multisig_rpc.to_delegate());
@@ -1,19 +1,37 @@
+use std::any::Any;
 use std::fmt::Debug;
 
+use chain_core::tx::rejection::{TxRejection, REJECTION_CODESPACE};
+use serde_json::json;
+
 pub mod handler;
 pub mod rpc;
 
 pub use handler::RpcHandler;
 
-pub fn to_rpc_error<E: ToString + Debug>(error: E) -> jsonrpc_core::Error {
+pub fn to_rpc_error<E: ToString + Debug + 'static>(error: E) -> jsonrpc_core::Error {
     log::error!("{:?}", error);
+    // transactions rejected by the chain carry their reason and details in the error data
+    let data = (&error as &dyn Any)
+        .downcast_ref::<client_common::Error>()
+        .and_then(client_common::Error::rejection)
+        .map(rejection_data);
     jsonrpc_core::Error {
         code: jsonrpc_core::ErrorCode::InternalError,
         message: error.to_string(),
-        data: None,
+        data,
     }
 }
 
+fn rejection_data(rejection: &TxRejection) -> serde_json::Value {
+    json!({
+        "codespace": REJECTION_CODESPACE,
+        "code": rejection.code.code(),
+        "reason": rejection.code,
+        "detail": rejection.detail,
+    })
+}
+
 pub fn rpc_error_from_string(error: String) -> jsonrpc_core::Error {
     log::error!("{}", error);
     jsonrpc_core::Error {
@@ -22,3 +40,36 @@ pub fn rpc_error_from_string(error: String) -> jsonrpc_core::Error {
         data: None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chain_core::tx::rejection::{RejectionCode, RejectionDetail};
+    use client_common::{Error, ErrorKind};
+
+    #[test]
+    fn check_rejected_transaction_error_data() {
+        let rejection = TxRejection::with_detail(
+            RejectionCode::InputSpent,
+            RejectionDetail::Input { index: 1 },
+        );
+        let error = Error::new_with_source(
+            ErrorKind::TransactionRejected,
+            "transaction spends an input that was already spent (input 1)",
+            Box::new(rejection),
+        );
+        assert_eq!(
+            Some(json!({
+                "codespace": "chain",
+                "code": 13,
+                "reason": "input_spent",
+                "detail": { "type": "input", "index": 1 },
+            })),
+            to_rpc_error(error).data
+        );
+
+        let error = Error::new(ErrorKind::TendermintRpcError, "connection refused");
+        assert_eq!(None, to_rpc_error(error).data);
+    }
+}
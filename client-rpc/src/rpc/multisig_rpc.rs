@@ -1,16 +1,36 @@
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
 use hex::{decode, encode};
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
+use serde::{Deserialize, Serialize};
 
 use chain_core::common::{H256, HASH_SIZE_256};
+use chain_core::init::coin::Coin;
+use chain_core::tx::data::access::{TxAccess, TxAccessPolicy};
+use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::output::TxOut;
 use chain_core::tx::data::Tx;
 use client_common::{Error, ErrorKind, PublicKey, Result as CommonResult, ResultExt, SecKey};
-use client_core::types::AddressType;
+use client_core::multi_sig::SigningPackage;
+use client_core::transaction_builder::RawTransferTransaction;
+use client_core::types::{AddressType, BookAddress};
 use client_core::wallet::WalletRequest;
 use client_core::{MultiSigWalletClient, WalletClient};
 
 use crate::to_rpc_error;
 
+/// Transfer transaction spending (multi-sig) inputs of a wallet, which still needs the signature
+/// of co-signers
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MultiSigTransfer {
+    /// Hex encoded transaction id, i.e. the message co-signers sign in a multi-sig session
+    tx_id: String,
+    /// Base64 encoded raw transaction
+    raw_transaction: String,
+}
+
 #[rpc(server)]
 pub trait MultiSigRpc: Send + Sync {
     #[rpc(name = "multiSig_newAddressPublicKey")]
@@ -84,6 +104,49 @@ pub trait MultiSigRpc: Send + Sync {
         session_id: String,
         unsigned_transaction: Tx,
     ) -> Result<String>;
+
+    #[rpc(name = "multiSig_newSigningPackage")]
+    fn new_signing_package(
+        &self,
+        request: WalletRequest,
+        unsigned_transaction: Tx,
+        signer_public_keys: Vec<String>,
+    ) -> Result<String>;
+
+    #[rpc(name = "multiSig_importSigningPackage")]
+    fn import_signing_package(&self, request: WalletRequest, package: String) -> Result<String>;
+
+    #[rpc(name = "multiSig_exportSigningPackage")]
+    fn export_signing_package(
+        &self,
+        request: WalletRequest,
+        transaction_id: String,
+    ) -> Result<String>;
+
+    #[rpc(name = "multiSig_broadcastSigningPackage")]
+    fn broadcast_signing_package(&self, request: WalletRequest, package: String) -> Result<String>;
+
+    #[rpc(name = "multiSig_createTransfer")]
+    fn create_transfer(
+        &self,
+        request: WalletRequest,
+        to_address: String,
+        amount: Coin,
+        view_keys: Vec<String>,
+        signer_public_keys: Vec<String>,
+    ) -> Result<MultiSigTransfer>;
+
+    #[rpc(name = "multiSig_addCosignersSignature")]
+    fn add_cosigners_signature(
+        &self,
+        session_id: String,
+        enckey: SecKey,
+        raw_transaction: String,
+    ) -> Result<String>;
+
+    #[rpc(name = "multiSig_broadcastTransfer")]
+    fn broadcast_transfer(&self, request: WalletRequest, raw_transaction: String)
+        -> Result<String>;
 }
 
 pub struct MultiSigRpcImpl<T>
@@ -91,14 +154,15 @@ where
     T: WalletClient,
 {
     client: T,
+    network_id: u8,
 }
 
 impl<T> MultiSigRpcImpl<T>
 where
     T: WalletClient,
 {
-    pub fn new(client: T) -> Self {
-        MultiSigRpcImpl { client }
+    pub fn new(client: T, network_id: u8) -> Self {
+        MultiSigRpcImpl { client, network_id }
     }
 }
 
@@ -287,6 +351,160 @@ where
             .map(|result| result.data.to_string())
             .map_err(to_rpc_error)
     }
+
+    fn new_signing_package(
+        &self,
+        request: WalletRequest,
+        unsigned_transaction: Tx,
+        signer_public_keys: Vec<String>,
+    ) -> Result<String> {
+        let signer_public_keys = parse_public_keys(signer_public_keys).map_err(to_rpc_error)?;
+
+        self.client
+            .new_signing_package(
+                &request.name,
+                &request.enckey,
+                unsigned_transaction,
+                signer_public_keys,
+            )
+            .map(|package| package.to_base64())
+            .map_err(to_rpc_error)
+    }
+
+    fn import_signing_package(&self, request: WalletRequest, package: String) -> Result<String> {
+        let package = SigningPackage::from_base64(&package).map_err(to_rpc_error)?;
+
+        self.client
+            .import_signing_package(&request.name, &request.enckey, package)
+            .map(|package| package.to_base64())
+            .map_err(to_rpc_error)
+    }
+
+    fn export_signing_package(
+        &self,
+        request: WalletRequest,
+        transaction_id: String,
+    ) -> Result<String> {
+        let transaction_id = parse_hash_256(transaction_id).map_err(to_rpc_error)?;
+
+        self.client
+            .export_signing_package(&request.name, &request.enckey, &transaction_id)
+            .map(|package| package.to_base64())
+            .map_err(to_rpc_error)
+    }
+
+    fn broadcast_signing_package(&self, request: WalletRequest, package: String) -> Result<String> {
+        let package = SigningPackage::from_base64(&package).map_err(to_rpc_error)?;
+
+        let tx_aux = self
+            .client
+            .signing_package_transaction(&request.name, &request.enckey, package)
+            .map_err(to_rpc_error)?;
+
+        self.client
+            .broadcast_transaction(&tx_aux)
+            .map(|result| result.data.to_string())
+            .map_err(to_rpc_error)
+    }
+
+    fn create_transfer(
+        &self,
+        request: WalletRequest,
+        to_address: String,
+        amount: Coin,
+        view_keys: Vec<String>,
+        signer_public_keys: Vec<String>,
+    ) -> Result<MultiSigTransfer> {
+        let to_address = self
+            .client
+            .resolve_address(&request.name, &request.enckey, &to_address)
+            .and_then(BookAddress::into_transfer)
+            .map_err(to_rpc_error)?;
+        let mut view_keys = view_keys
+            .iter()
+            .map(|view_key| PublicKey::from_str(view_key))
+            .collect::<CommonResult<BTreeSet<PublicKey>>>()
+            .map_err(to_rpc_error)?;
+        view_keys.insert(
+            self.client
+                .view_key(&request.name, &request.enckey)
+                .map_err(to_rpc_error)?,
+        );
+        let signer_public_keys = parse_public_keys(signer_public_keys).map_err(to_rpc_error)?;
+
+        let access_policies = view_keys
+            .iter()
+            .map(|key| TxAccessPolicy {
+                view_key: key.into(),
+                access: TxAccess::AllData,
+            })
+            .collect();
+        let attributes = TxAttributes::new_with_access(self.network_id, access_policies);
+        let return_address = self
+            .client
+            .new_transfer_address(&request.name, &request.enckey)
+            .map_err(to_rpc_error)?;
+
+        let raw_transaction = self
+            .client
+            .create_multisig_transfer_transaction(
+                &request.name,
+                &request.enckey,
+                vec![TxOut::new(to_address, amount)],
+                attributes,
+                None,
+                return_address,
+                signer_public_keys,
+            )
+            .map_err(to_rpc_error)?;
+        self.client.flush_database().map_err(to_rpc_error)?;
+
+        Ok(MultiSigTransfer {
+            tx_id: encode(raw_transaction.id()),
+            raw_transaction: raw_transaction.to_string(),
+        })
+    }
+
+    fn add_cosigners_signature(
+        &self,
+        session_id: String,
+        enckey: SecKey,
+        raw_transaction: String,
+    ) -> Result<String> {
+        let session_id = parse_hash_256(session_id).map_err(to_rpc_error)?;
+        let raw_transaction =
+            RawTransferTransaction::from_str(&raw_transaction).map_err(to_rpc_error)?;
+
+        let signature = self
+            .client
+            .signature(&session_id, &enckey)
+            .map_err(to_rpc_error)?;
+
+        self.client
+            .add_cosigners_signature(raw_transaction, signature)
+            .map(|raw_transaction| raw_transaction.to_string())
+            .map_err(to_rpc_error)
+    }
+
+    fn broadcast_transfer(
+        &self,
+        request: WalletRequest,
+        raw_transaction: String,
+    ) -> Result<String> {
+        let raw_transaction =
+            RawTransferTransaction::from_str(&raw_transaction).map_err(to_rpc_error)?;
+
+        let tx_id = self
+            .client
+            .broadcast_multisig_transfer_transaction(
+                &request.name,
+                &request.enckey,
+                raw_transaction,
+            )
+            .map_err(to_rpc_error)?;
+        self.client.flush_database().map_err(to_rpc_error)?;
+        Ok(encode(tx_id))
+    }
 }
 
 fn serialize_hash_256(hash: H256) -> String {
@@ -423,7 +641,7 @@ mod test {
     fn setup_multisig_rpc() -> MultiSigRpcImpl<TestWalletClient> {
         let storage = MemoryStorage::default();
         let wallet_client = make_test_wallet_client(storage);
-        MultiSigRpcImpl::new(wallet_client)
+        MultiSigRpcImpl::new(wallet_client, 0xab)
     }
 
     #[derive(Default, Clone)]
@@ -1,16 +1,18 @@
-use super::sync_worker::SyncWorker;
 use super::sync_worker::WorkerShared;
 use crate::to_rpc_error;
-use client_common::tendermint::Client;
+use client_common::tendermint::{Client, NewBlockSubscriber};
 use client_common::Storage;
 use client_common::TransactionObfuscation;
+use client_common::{Error, ErrorKind};
+use client_core::wallet::sync_daemon::{FollowOptions, SyncDaemon};
 use client_core::wallet::syncer::{
-    AddressRecovery, Handle, ObfuscationSyncerConfig, ProgressReport, WalletSyncer,
+    AddressRecovery, LightClientHandle, ObfuscationSyncerConfig, ProgressReport, WalletSyncer,
 };
 use client_core::wallet::WalletRequest;
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread;
@@ -81,7 +83,7 @@ where
     C: Client,
     O: TransactionObfuscation,
     T: AddressRecovery,
-    L: Handle + Send + Sync + Clone,
+    L: LightClientHandle,
 {
     config: ObfuscationSyncerConfig<S, C, O, L>,
 
@@ -94,10 +96,10 @@ where
 impl<S, C, O, T, L> SyncRpcImpl<S, C, O, T, L>
 where
     S: Storage + 'static,
-    C: Client + 'static,
+    C: Client + NewBlockSubscriber + 'static,
     O: TransactionObfuscation + 'static,
     T: AddressRecovery + 'static,
-    L: Handle + Send + Sync + Clone + 'static,
+    L: LightClientHandle + 'static,
 {
     pub fn new(
         config: ObfuscationSyncerConfig<S, C, O, L>,
@@ -105,12 +107,13 @@ where
 
         recover_address: T,
         light_client_handle: Option<L>,
+        worker: WorkerShared,
     ) -> Self {
         SyncRpcImpl {
             config,
 
             progress_callback,
-            worker: Arc::new(Mutex::new(SyncWorker::new())),
+            worker,
 
             recover_address,
             light_client_handle,
@@ -118,88 +121,143 @@ where
     }
 }
 
+/// Forwards sync progress reports to the binding callback, if any
+fn progress_reporter(
+    progress_callback: Option<CBindingCore>,
+) -> impl FnMut(ProgressReport) -> bool {
+    let mut init_block_height = 0;
+    let mut final_block_height = 0;
+    move |report: ProgressReport| -> bool {
+        match report {
+            ProgressReport::Init {
+                start_block_height,
+                finish_block_height,
+                ..
+            } => {
+                init_block_height = start_block_height;
+                final_block_height = finish_block_height;
+                if let Some(delegator) = &progress_callback {
+                    {
+                        let mut user_callback =
+                            delegator.data.lock().expect("get cbinding callback");
+                        user_callback.progress(0, init_block_height, final_block_height);
+                        return true;
+                    }
+                }
+                true
+            }
+            ProgressReport::Update {
+                current_block_height,
+                ..
+            } => {
+                if let Some(delegator) = &progress_callback {
+                    {
+                        let mut user_callback =
+                            delegator.data.lock().expect("get cbinding callback");
+                        return 1
+                            == user_callback.progress(
+                                current_block_height,
+                                init_block_height,
+                                final_block_height,
+                            );
+                    }
+                }
+                true
+            }
+        }
+    }
+}
+
 fn process_sync<S, C, O, T, L>(
     config: ObfuscationSyncerConfig<S, C, O, L>,
     request: WalletRequest,
     reset: bool,
     progress_callback: Option<CBindingCore>,
     recover_address: T,
-) -> Result<()>
+) -> client_common::Result<()>
 where
     S: Storage + 'static,
     C: Client,
     O: TransactionObfuscation,
     T: AddressRecovery,
-    L: Handle + Send + Sync + Clone,
+    L: LightClientHandle,
 {
     let mut syncer = WalletSyncer::with_obfuscation_config(
         config,
         request.name,
         request.enckey,
         recover_address,
-    )
-    .map_err(to_rpc_error)?;
+    )?;
     if reset {
-        syncer.reset_state().map_err(to_rpc_error)?;
+        syncer.reset_state()?;
     }
 
     if progress_callback.is_none() {
-        return syncer.sync(|_| true).map_err(to_rpc_error);
+        return syncer.sync(|_| true);
     }
 
-    let mut init_block_height = 0;
-    let mut final_block_height = 0;
-    syncer
-        .sync(|report: ProgressReport| -> bool {
-            match report {
-                ProgressReport::Init {
-                    start_block_height,
-                    finish_block_height,
-                    ..
-                } => {
-                    init_block_height = start_block_height;
-                    final_block_height = finish_block_height;
-                    if let Some(delegator) = &progress_callback {
-                        {
-                            let mut user_callback =
-                                delegator.data.lock().expect("get cbinding callback");
-                            user_callback.progress(0, init_block_height, final_block_height);
-                            return true;
-                        }
-                    }
-                    true
-                }
-                ProgressReport::Update {
-                    current_block_height,
-                    ..
-                } => {
-                    if let Some(delegator) = &progress_callback {
-                        {
-                            let mut user_callback =
-                                delegator.data.lock().expect("get cbinding callback");
-                            return 1
-                                == user_callback.progress(
-                                    current_block_height,
-                                    init_block_height,
-                                    final_block_height,
-                                );
-                        }
-                    }
-                    true
-                }
-            }
-        })
+    syncer.sync(progress_reporter(progress_callback))
+}
+
+/// Keeps the wallet synchronized with new blocks until stopped through the sync worker
+fn process_follow<S, C, O, T, L>(
+    config: ObfuscationSyncerConfig<S, C, O, L>,
+    request: WalletRequest,
+    reset: bool,
+    progress_callback: Option<CBindingCore>,
+    recover_address: T,
+    stop: Arc<AtomicBool>,
+) -> Result<()>
+where
+    S: Storage + 'static,
+    C: Client + NewBlockSubscriber,
+    O: TransactionObfuscation,
+    T: AddressRecovery,
+    L: LightClientHandle,
+{
+    let subscriber = config.client.clone();
+    let mut syncer = WalletSyncer::with_obfuscation_config(
+        config,
+        request.name,
+        request.enckey,
+        recover_address,
+    )
+    .map_err(to_rpc_error)?;
+    if reset {
+        syncer.reset_state().map_err(to_rpc_error)?;
+    }
+
+    SyncDaemon::with_stop_handle(syncer, subscriber, FollowOptions::default(), stop)
+        .run(progress_reporter(progress_callback))
         .map_err(to_rpc_error)
 }
 
 impl<S, C, O, T, L> SyncRpcImpl<S, C, O, T, L>
 where
     S: Storage + 'static,
-    C: Client + 'static,
+    C: Client + NewBlockSubscriber + 'static,
     O: TransactionObfuscation + 'static,
     T: AddressRecovery + 'static,
-    L: Handle + Send + Sync + Clone + 'static,
+    L: LightClientHandle + 'static,
 {
+    /// Refuses to sync a wallet whose syncing was halted by a failed verification (e.g. a detected
+    /// light client attack), unless its state is reset: the wallet is then synced again from
+    /// scratch and every block is verified anew
+    fn check_not_halted(&self, name: &str, reset: bool) -> Result<()> {
+        let mut worker = self.worker.lock().expect("get sync worker lock");
+        if reset {
+            worker.resume(name);
+            return Ok(());
+        }
+        match worker.get_halt(name) {
+            Some(reason) => Err(to_rpc_error(Error::new(
+                ErrorKind::VerifyError,
+                format!("syncing of wallet {} is halted: {}", name, reason),
+            ))),
+            None => Ok(()),
+        }
+    }
+
     fn do_run_sync(
         &self,
         request: WalletRequest,
@@ -214,6 +272,8 @@ where
         let worker = self.worker.clone();
         let userrequest = request.clone();
 
+        self.check_not_halted(&name, reset)?;
+
         let progress = worker
             .lock()
             .expect("get sync worker lock")
@@ -226,50 +286,100 @@ where
             });
         }
 
+        let follow = do_loop && config.options.follow_new_blocks;
         thread::spawn(move || {
             let localworker = worker;
             localworker.lock().expect("get sync worker lock").add(&name);
             let node = localworker.lock().expect("get sync worker lock").get(&name);
             let syncnode = node.expect("get progress callback");
             let usercallback = Some(CBindingCore { data: syncnode });
-            loop {
-                let result = process_sync(
-                    config.clone(),
-                    userrequest.clone(),
-                    reset,
-                    usercallback.clone(),
-                    recover_address.clone(),
-                );
-                log::info!("process_sync finished {} {:?}", name, result);
-                if let Err(error_message) = result {
-                    localworker
-                        .lock()
-                        .expect("get sync worker lock")
-                        .set_error_message(&name, &error_message.message.to_string());
-
-                    log::info!("wait for error notification {}", name);
-                    std::thread::sleep(std::time::Duration::from_secs(ERROR_NOTIFICATION_TIME));
-                }
-
-                if localworker
+            if follow {
+                // registered before following, so that a stop request is never lost
+                let stop = Arc::new(AtomicBool::new(false));
+                localworker
                     .lock()
                     .expect("get sync worker lock")
-                    .get_stop(&name)
-                {
-                    break;
-                }
+                    .set_stop_handle(&name, stop.clone());
+                loop {
+                    let result = process_follow(
+                        config.clone(),
+                        userrequest.clone(),
+                        reset,
+                        usercallback.clone(),
+                        recover_address.clone(),
+                        stop.clone(),
+                    );
+                    log::info!("process_follow finished {} {:?}", name, result);
+                    if let Err(error) = result {
+                        let mut worker = localworker.lock().expect("get sync worker lock");
+                        worker.set_error_message(&name, &error.to_string());
+                        // a light client attack was detected, resyncing would trust the attacker
+                        if error.kind() == ErrorKind::VerifyError {
+                            worker.halt(&name, &error.to_string());
+                            break;
+                        }
+                        drop(worker);
 
+                        log::info!("wait for error notification {}", name);
+                        std::thread::sleep(std::time::Duration::from_secs(ERROR_NOTIFICATION_TIME));
+                    }
+
+                    if localworker
+                        .lock()
+                        .expect("get sync worker lock")
+                        .get_stop(&name)
+                    {
+                        break;
+                    }
+                }
                 localworker
                     .lock()
                     .expect("get sync worker lock")
                     .set_complete(&name);
+            } else {
+                loop {
+                    let result = process_sync(
+                        config.clone(),
+                        userrequest.clone(),
+                        reset,
+                        usercallback.clone(),
+                        recover_address.clone(),
+                    );
+                    log::info!("process_sync finished {} {:?}", name, result);
+                    if let Err(error) = result {
+                        let mut worker = localworker.lock().expect("get sync worker lock");
+                        worker.set_error_message(&name, &error.to_string());
+                        // a light client attack was detected, resyncing would trust the attacker
+                        if error.kind() == ErrorKind::VerifyError {
+                            worker.halt(&name, &error.to_string());
+                            break;
+                        }
+                        drop(worker);
 
-                // notify
-                log::info!("wait for notification {}", name);
-                std::thread::sleep(std::time::Duration::from_secs(NOTIFICATION_TIME));
+                        log::info!("wait for error notification {}", name);
+                        std::thread::sleep(std::time::Duration::from_secs(ERROR_NOTIFICATION_TIME));
+                    }
 
-                if !do_loop {
-                    break;
+                    if localworker
+                        .lock()
+                        .expect("get sync worker lock")
+                        .get_stop(&name)
+                    {
+                        break;
+                    }
+
+                    localworker
+                        .lock()
+                        .expect("get sync worker lock")
+                        .set_complete(&name);
+
+                    // notify
+                    log::info!("wait for notification {}", name);
+                    std::thread::sleep(std::time::Duration::from_secs(NOTIFICATION_TIME));
+
+                    if !do_loop {
+                        break;
+                    }
                 }
             }
             localworker
@@ -290,22 +400,33 @@ where
 impl<S, C, O, T, L> SyncRpc for SyncRpcImpl<S, C, O, T, L>
 where
     S: Storage + 'static,
-    C: Client + 'static,
+    C: Client + NewBlockSubscriber + 'static,
     O: TransactionObfuscation + 'static,
     T: AddressRecovery + 'static,
-    L: Handle + Send + Sync + Clone + 'static,
+    L: LightClientHandle + 'static,
 {
     #[inline]
     fn sync(&self, request: WalletRequest, sync_request: SyncRequest) -> Result<RunSyncResult> {
         log::info!("sync {:?}", sync_request);
         if sync_request.blocking {
+            self.check_not_halted(&request.name, sync_request.reset)?;
+            let name = request.name.clone();
             process_sync(
                 self.config.clone(),
                 request,
                 sync_request.reset,
                 self.progress_callback.clone(),
                 self.recover_address.clone(),
-            )?;
+            )
+            .map_err(|error| {
+                if error.kind() == ErrorKind::VerifyError {
+                    self.worker
+                        .lock()
+                        .expect("get sync worker lock")
+                        .halt(&name, &error.to_string());
+                }
+                to_rpc_error(error)
+            })?;
 
             Ok(RunSyncResult::default())
         } else {
@@ -336,7 +457,7 @@ where
     C: Client,
     O: TransactionObfuscation,
     T: AddressRecovery,
-    L: Handle + Send + Sync + Clone,
+    L: LightClientHandle,
 {
     fn drop(&mut self) {
         if self.light_client_handle.is_some() {
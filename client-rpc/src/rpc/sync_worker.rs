@@ -2,6 +2,7 @@ use super::sync_rpc::{CBindingCallback, RunSyncProgressResult};
 use crate::rpc_error_from_string;
 use jsonrpc_core::Result;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Instant;
@@ -10,6 +11,8 @@ pub struct SyncWorkerNode {
     pub progress: RunSyncProgressResult,
     pub stop: bool,
     counter: Instant,
+    // set when the wallet follows new blocks, stops the sync daemon
+    stop_handle: Option<Arc<AtomicBool>>,
 }
 impl SyncWorkerNode {
     fn new(name: &str) -> Self {
@@ -18,6 +21,7 @@ impl SyncWorkerNode {
             user_data: 0,
             stop: false,
             counter: Instant::now(),
+            stop_handle: None,
         };
         ret.progress.name = name.to_string();
         ret
@@ -25,6 +29,9 @@ impl SyncWorkerNode {
     fn set_stop(&mut self, flag: bool) {
         log::info!("stop sync wallet {} flag {}", self.progress.name, flag);
         self.stop = flag;
+        if let Some(stop_handle) = &self.stop_handle {
+            stop_handle.store(flag, Ordering::SeqCst);
+        }
     }
     fn set_complete(&mut self) {
         self.progress.percent = 100.0;
@@ -83,12 +90,15 @@ pub type NodeShared = Arc<Mutex<SyncWorkerNode>>;
 #[derive(Default)]
 pub struct SyncWorker {
     works: HashMap<String, NodeShared>,
+    // wallets whose syncing was halted (light client attack detected), with the reason
+    halted: HashMap<String, String>,
 }
 
 impl SyncWorker {
     pub fn new() -> Self {
         SyncWorker {
             works: HashMap::new(),
+            halted: HashMap::new(),
         }
     }
 
@@ -114,9 +124,19 @@ impl SyncWorker {
             self.works.len()
         );
     }
+    /// Returns `true` if the wallet is being synced (or followed)
+    pub fn is_syncing(&self, key: &str) -> bool {
+        self.works.contains_key(key)
+    }
+
     pub fn get_progress(&self, key: &str) -> Result<RunSyncProgressResult> {
         if let Some(value) = self.works.get(key) {
             Ok(value.lock().unwrap().progress.clone())
+        } else if let Some(reason) = self.halted.get(key) {
+            Err(rpc_error_from_string(format!(
+                "wallet sync halted: {}",
+                reason
+            )))
         } else {
             Err(rpc_error_from_string(
                 "wallet is not running sync".to_owned(),
@@ -135,6 +155,15 @@ impl SyncWorker {
         }
     }
 
+    pub fn set_stop_handle(&self, key: &str, stop_handle: Arc<AtomicBool>) {
+        if let Some(value) = self.works.get(key) {
+            let mut node = value.lock().unwrap();
+            // a stop requested before the handle was registered still applies
+            stop_handle.store(node.stop, Ordering::SeqCst);
+            node.stop_handle = Some(stop_handle);
+        }
+    }
+
     pub fn get_stop(&self, key: &str) -> bool {
         if let Some(value) = self.works.get(key) {
             value.lock().unwrap().stop
@@ -155,6 +184,28 @@ impl SyncWorker {
         }
     }
 
+    /// Keeps the wallet from syncing again until the process is restarted
+    pub fn halt(&mut self, key: &str, reason: &str) {
+        log::error!("sync wallet {} halted: {}", key, reason);
+        self.halted.insert(key.to_string(), reason.to_string());
+    }
+
+    pub fn get_halt(&self, key: &str) -> Option<String> {
+        self.halted.get(key).cloned()
+    }
+
+    /// Lifts the halt of a wallet, so that it can be synced again (from scratch), returns `true`
+    /// if the wallet was halted
+    pub fn resume(&mut self, key: &str) -> bool {
+        match self.halted.remove(key) {
+            Some(reason) => {
+                log::warn!("sync wallet {} resumed, it was halted: {}", key, reason);
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn set_complete(&self, key: &str) {
         if let Some(value) = self.works.get(key) {
             value.lock().unwrap().set_complete();
@@ -163,3 +214,45 @@ impl SyncWorker {
 }
 
 pub type WorkerShared = Arc<Mutex<SyncWorker>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_halted_wallet_reports_reason() {
+        let mut worker = SyncWorker::new();
+        worker.add("wallet");
+        worker.halt("wallet", "light client attack detected");
+        worker.remove("wallet");
+
+        assert_eq!(
+            worker.get_halt("wallet"),
+            Some("light client attack detected".to_owned())
+        );
+        assert!(worker
+            .get_progress("wallet")
+            .unwrap_err()
+            .message
+            .contains("light client attack detected"));
+        assert_eq!(worker.get_halt("other"), None);
+
+        assert!(worker.resume("wallet"));
+        assert_eq!(worker.get_halt("wallet"), None);
+        assert!(!worker.resume("wallet"));
+    }
+
+    #[test]
+    fn check_stop_before_stop_handle_is_kept() {
+        let mut worker = SyncWorker::new();
+        worker.add("wallet");
+        worker.stop("wallet").unwrap();
+
+        let stop_handle = Arc::new(AtomicBool::new(false));
+        worker.set_stop_handle("wallet", stop_handle.clone());
+        assert!(stop_handle.load(Ordering::SeqCst));
+
+        worker.set_complete("wallet");
+        assert_eq!(worker.get_progress("wallet").unwrap().percent, 100.0);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::str::FromStr;
 
 use jsonrpc_core::Result;
@@ -6,17 +6,21 @@ use jsonrpc_derive::rpc;
 use secstr::SecUtf8;
 
 use chain_core::init::coin::Coin;
-use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::input::str2txid;
 use client_common::{PrivateKey, PublicKey, Result as CommonResult, SecKey};
-use client_core::service::WalletInfo;
+use client_core::service::{WalletBackup, WalletInfo};
 use client_core::transaction_builder::SignedTransferTransaction;
-use client_core::types::{TransactionChange, WalletBalance, WalletKind};
+use client_core::types::{
+    BookAddress, HistoryFilter, HistoryFormat, HistoryRecord, TransactionChange, TransactionLabel,
+    WalletBalance, WalletKind,
+};
 use client_core::wallet::{CreateWalletRequest, WalletRequest};
 #[cfg(feature = "experimental")]
 use client_core::MultiSigWalletClient;
 use client_core::{Mnemonic, UnspentTransactions, WalletClient};
 use parity_scale_codec::{Decode, Encode};
 
+use crate::rpc::sync_worker::WorkerShared;
 use crate::{rpc_error_from_string, to_rpc_error};
 use client_core::hd_wallet::HardwareKind;
 
@@ -42,6 +46,13 @@ pub trait WalletRpc: Send + Sync {
     #[rpc(name = "wallet_delete")]
     fn delete(&self, request: CreateWalletRequest) -> Result<()>;
 
+    #[rpc(name = "wallet_changePassphrase")]
+    fn change_passphrase(
+        &self,
+        request: CreateWalletRequest,
+        new_passphrase: SecUtf8,
+    ) -> Result<SecKey>;
+
     #[rpc(name = "wallet_createStakingAddress")]
     fn create_staking_address(&self, request: WalletRequest) -> Result<String>;
 
@@ -132,6 +143,47 @@ pub trait WalletRpc: Send + Sync {
         reversed: bool,
     ) -> Result<Vec<TransactionChange>>;
 
+    #[rpc(name = "wallet_historyRecords")]
+    fn history_records(
+        &self,
+        request: WalletRequest,
+        filter: HistoryFilter,
+    ) -> Result<Vec<HistoryRecord>>;
+
+    #[rpc(name = "wallet_exportHistory")]
+    fn export_history(
+        &self,
+        request: WalletRequest,
+        filter: HistoryFilter,
+        format: HistoryFormat,
+    ) -> Result<String>;
+
+    #[rpc(name = "wallet_listAddressLabels")]
+    fn list_address_labels(&self, request: WalletRequest) -> Result<BTreeMap<String, BookAddress>>;
+
+    #[rpc(name = "wallet_setAddressLabel")]
+    fn set_address_label(
+        &self,
+        request: WalletRequest,
+        label: String,
+        address: String,
+    ) -> Result<()>;
+
+    #[rpc(name = "wallet_removeAddressLabel")]
+    fn remove_address_label(&self, request: WalletRequest, label: String) -> Result<()>;
+
+    #[rpc(name = "wallet_setTransactionLabel")]
+    fn set_transaction_label(
+        &self,
+        request: WalletRequest,
+        txid: String,
+        label: String,
+        note: Option<String>,
+    ) -> Result<()>;
+
+    #[rpc(name = "wallet_removeTransactionLabel")]
+    fn remove_transaction_label(&self, request: WalletRequest, txid: String) -> Result<()>;
+
     #[rpc(name = "wallet_exportTransaction")]
     fn export_plain_tx(&self, request: WalletRequest, txid: String) -> Result<String>;
 
@@ -146,6 +198,12 @@ pub trait WalletRpc: Send + Sync {
 
     #[rpc(name = "wallet_import")]
     fn import(&self, request: CreateWalletRequest, wallet_info: WalletInfo) -> Result<SecKey>;
+
+    #[rpc(name = "wallet_backup")]
+    fn backup(&self, request: WalletRequest, backup_passphrase: SecUtf8) -> Result<String>;
+
+    #[rpc(name = "wallet_restoreBackup")]
+    fn restore_backup(&self, backup: String, backup_passphrase: SecUtf8) -> Result<String>;
 }
 
 pub struct WalletRpcImpl<T>
@@ -154,14 +212,19 @@ where
 {
     client: T,
     network_id: u8,
+    sync_worker: WorkerShared,
 }
 
 impl<T> WalletRpcImpl<T>
 where
     T: WalletClient,
 {
-    pub fn new(client: T, network_id: u8) -> Self {
-        WalletRpcImpl { client, network_id }
+    pub fn new(client: T, network_id: u8, sync_worker: WorkerShared) -> Self {
+        WalletRpcImpl {
+            client,
+            network_id,
+            sync_worker,
+        }
     }
 }
 
@@ -251,6 +314,29 @@ where
         ret
     }
 
+    fn change_passphrase(
+        &self,
+        request: CreateWalletRequest,
+        new_passphrase: SecUtf8,
+    ) -> Result<SecKey> {
+        // the sync worker keeps writing the wallet data with the old enckey, the lock also
+        // keeps it from being started during the change
+        let sync_worker = self.sync_worker.lock().expect("get sync worker lock");
+        if sync_worker.is_syncing(&request.name) {
+            return Err(rpc_error_from_string(format!(
+                "Wallet {} is syncing, stop syncing before changing its passphrase",
+                request.name
+            )));
+        }
+        let ret = self
+            .client
+            .change_passphrase(&request.name, &request.passphrase, &new_passphrase)
+            .map_err(to_rpc_error);
+        drop(sync_worker);
+        self.client.flush_database().map_err(to_rpc_error)?;
+        ret
+    }
+
     fn create_staking_address(&self, request: WalletRequest) -> Result<String> {
         let ret = self
             .client
@@ -422,9 +508,11 @@ where
         amount: Coin,
         view_keys: Vec<String>,
     ) -> Result<String> {
-        let address = to_address
-            .parse::<ExtendedAddr>()
-            .map_err(|err| rpc_error_from_string(format!("{}", err)))?;
+        let address = self
+            .client
+            .resolve_address(&request.name, &request.enckey, &to_address)
+            .and_then(BookAddress::into_transfer)
+            .map_err(to_rpc_error)?;
         let mut view_keys = view_keys
             .iter()
             .map(|view_key| PublicKey::from_str(view_key))
@@ -452,9 +540,11 @@ where
         amount: Coin,
         view_keys: Vec<String>,
     ) -> Result<String> {
-        let to_address = to_address
-            .parse::<ExtendedAddr>()
-            .map_err(|err| rpc_error_from_string(format!("{}", err)))?;
+        let to_address = self
+            .client
+            .resolve_address(&request.name, &request.enckey, &to_address)
+            .and_then(BookAddress::into_transfer)
+            .map_err(to_rpc_error)?;
         let view_keys = view_keys
             .iter()
             .map(|view_key| PublicKey::from_str(view_key))
@@ -523,6 +613,88 @@ where
             .map_err(to_rpc_error)
     }
 
+    fn history_records(
+        &self,
+        request: WalletRequest,
+        filter: HistoryFilter,
+    ) -> Result<Vec<HistoryRecord>> {
+        self.client
+            .history_records(&request.name, &request.enckey, &filter)
+            .map_err(to_rpc_error)
+    }
+
+    fn export_history(
+        &self,
+        request: WalletRequest,
+        filter: HistoryFilter,
+        format: HistoryFormat,
+    ) -> Result<String> {
+        let records = self.history_records(request, filter)?;
+        format.render(&records).map_err(to_rpc_error)
+    }
+
+    fn list_address_labels(&self, request: WalletRequest) -> Result<BTreeMap<String, BookAddress>> {
+        self.client
+            .address_book(&request.name, &request.enckey)
+            .map(|book| book.contacts)
+            .map_err(to_rpc_error)
+    }
+
+    fn set_address_label(
+        &self,
+        request: WalletRequest,
+        label: String,
+        address: String,
+    ) -> Result<()> {
+        let address = BookAddress::from_str(&address).map_err(to_rpc_error)?;
+        let ret = self
+            .client
+            .set_address_label(&request.name, &request.enckey, &label, &address)
+            .map_err(to_rpc_error);
+        self.client.flush_database().map_err(to_rpc_error)?;
+        ret
+    }
+
+    fn remove_address_label(&self, request: WalletRequest, label: String) -> Result<()> {
+        let ret = self
+            .client
+            .remove_address_label(&request.name, &request.enckey, &label)
+            .map_err(to_rpc_error);
+        self.client.flush_database().map_err(to_rpc_error)?;
+        ret
+    }
+
+    fn set_transaction_label(
+        &self,
+        request: WalletRequest,
+        txid: String,
+        label: String,
+        note: Option<String>,
+    ) -> Result<()> {
+        let txid = str2txid(&txid).map_err(to_rpc_error)?;
+        let ret = self
+            .client
+            .set_transaction_label(
+                &request.name,
+                &request.enckey,
+                txid,
+                &TransactionLabel { label, note },
+            )
+            .map_err(to_rpc_error);
+        self.client.flush_database().map_err(to_rpc_error)?;
+        ret
+    }
+
+    fn remove_transaction_label(&self, request: WalletRequest, txid: String) -> Result<()> {
+        let txid = str2txid(&txid).map_err(to_rpc_error)?;
+        let ret = self
+            .client
+            .remove_transaction_label(&request.name, &request.enckey, &txid)
+            .map_err(to_rpc_error);
+        self.client.flush_database().map_err(to_rpc_error)?;
+        ret
+    }
+
     fn get_enc_key(&self, request: CreateWalletRequest) -> Result<SecKey> {
         self.client
             .auth_token(&request.name, &request.passphrase)
@@ -547,6 +719,23 @@ where
         self.client.flush_database().map_err(to_rpc_error)?;
         ret
     }
+
+    fn backup(&self, request: WalletRequest, backup_passphrase: SecUtf8) -> Result<String> {
+        self.client
+            .backup_wallet(&request.name, &request.enckey, &backup_passphrase)
+            .map(|backup| backup.to_base64())
+            .map_err(to_rpc_error)
+    }
+
+    fn restore_backup(&self, backup: String, backup_passphrase: SecUtf8) -> Result<String> {
+        let backup = WalletBackup::from_base64(&backup).map_err(to_rpc_error)?;
+        let ret = self
+            .client
+            .restore_wallet_backup(&backup, &backup_passphrase)
+            .map_err(to_rpc_error);
+        self.client.flush_database().map_err(to_rpc_error)?;
+        ret
+    }
 }
 
 #[cfg(test)]
@@ -1021,7 +1210,7 @@ pub mod tests {
         let wallet_client = make_test_wallet_client(storage.clone());
         let chain_id = 171u8;
 
-        WalletRpcImpl::new(wallet_client, chain_id)
+        WalletRpcImpl::new(wallet_client, chain_id, Default::default())
     }
 
     fn create_wallet_request(name: &str, passphrase: &str) -> (CreateWalletRequest, WalletRequest) {
@@ -1038,6 +1227,25 @@ pub mod tests {
         )
     }
 
+    #[test]
+    fn change_passphrase_should_fail_while_syncing() {
+        let wallet_rpc = setup_wallet_rpc();
+        let (create_request, _) = create_wallet_request("Default", "123456");
+        wallet_rpc
+            .create(create_request.clone(), WalletKind::Basic, None)
+            .unwrap();
+
+        wallet_rpc.sync_worker.lock().unwrap().add("Default");
+        assert!(wallet_rpc
+            .change_passphrase(create_request.clone(), SecUtf8::from("654321"))
+            .is_err());
+
+        wallet_rpc.sync_worker.lock().unwrap().remove("Default");
+        wallet_rpc
+            .change_passphrase(create_request, SecUtf8::from("654321"))
+            .unwrap();
+    }
+
     #[test]
     fn hdwallet_should_create_hd_wallet() {
         let wallet_rpc = setup_wallet_rpc();
@@ -1106,4 +1314,80 @@ pub mod tests {
         );
         assert!(send_result.is_err());
     }
+
+    #[test]
+    fn wallet_address_labels_can_be_used_as_send_targets() {
+        let wallet_rpc = setup_wallet_rpc();
+        let (create_request, wallet_request) = create_wallet_request("Default", "123456");
+        wallet_rpc
+            .create(create_request, WalletKind::HD, None)
+            .unwrap();
+
+        let address = wallet_rpc
+            .create_transfer_address(wallet_request.clone())
+            .unwrap();
+        wallet_rpc
+            .set_address_label(wallet_request.clone(), "alice".to_owned(), address.clone())
+            .unwrap();
+        assert_eq!(
+            address,
+            wallet_rpc
+                .list_address_labels(wallet_request.clone())
+                .unwrap()["alice"]
+                .to_string()
+        );
+
+        // unknown label is rejected before building the transaction
+        let send_result = wallet_rpc.send_to_address(
+            wallet_request.clone(),
+            "@bob".to_owned(),
+            Coin::from(1_0000u32),
+            vec![],
+        );
+        assert!(send_result
+            .unwrap_err()
+            .message
+            .contains("not found in address book"));
+
+        wallet_rpc
+            .remove_address_label(wallet_request.clone(), "alice".to_owned())
+            .unwrap();
+        assert!(wallet_rpc
+            .list_address_labels(wallet_request)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn wallet_export_history_of_new_wallet_is_empty() {
+        let wallet_rpc = setup_wallet_rpc();
+        let (create_request, wallet_request) = create_wallet_request("Default", "123456");
+        wallet_rpc
+            .create(create_request, WalletKind::HD, None)
+            .unwrap();
+
+        assert!(wallet_rpc
+            .history_records(wallet_request.clone(), HistoryFilter::default())
+            .unwrap()
+            .is_empty());
+        let csv = wallet_rpc
+            .export_history(
+                wallet_request.clone(),
+                HistoryFilter::default(),
+                HistoryFormat::Csv,
+            )
+            .unwrap();
+        assert_eq!(1, csv.lines().count());
+        assert!(csv.starts_with("block_time,block_height,type,"));
+        assert_eq!(
+            "[]",
+            wallet_rpc
+                .export_history(
+                    wallet_request,
+                    HistoryFilter::default(),
+                    HistoryFormat::Json
+                )
+                .unwrap()
+        );
+    }
 }
@@ -23,6 +23,7 @@ fn do_cro_basic_create_staking_address(private: PrivateKey) -> Box<CroAddress> {
             };
             Box::new(ret)
         }
+        ExtendedAddr::HashTimeLock(_) => unreachable!("multi-sig addresses are tree addresses"),
     }
 }
 
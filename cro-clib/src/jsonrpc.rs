@@ -225,6 +225,8 @@ unsafe fn create_rpc(
         light_client_trusting_period_seconds:3_600_000_000_000,
         light_client_trusting_height: 1,
         light_client_trusting_blockhash: "".into(),
+        follow_new_blocks: false,
+        light_client_report_evidence: false,
     };
     let handler = RpcHandler::new(
         &storage_dir,
@@ -70,6 +70,8 @@ fn add_txin(tx: &mut CroTx, txid_hex: &str, txindex: u16, addr: &str, coin: u64)
         prev_tx_out: txin,
         witness: None,
         threshold: 1,
+        spend_proof: None,
+        htlc: None,
     };
     tx.txin.push(utxo);
     assert!(tx.tx.inputs.len() == tx.txin.len());
@@ -128,6 +130,8 @@ pub unsafe extern "C" fn cro_tx_add_txin_raw(
         prev_tx_out: txin,
         witness: None,
         threshold: 1,
+        spend_proof: None,
+        htlc: None,
     };
     tx.txin.push(utxo);
     assert!(tx.tx.inputs.len() == tx.txin.len());
@@ -124,6 +124,8 @@ fn add_txin_deposit(
         prev_tx_out: txin,
         witness: None,
         threshold: 1,
+        spend_proof: None,
+        htlc: None,
     };
     tx.txin.push(utxo);
     assert!(tx.tx.inputs.len() == tx.txin.len());
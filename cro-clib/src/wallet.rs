@@ -89,6 +89,7 @@ pub unsafe extern "C" fn cro_create_staking_address(
             ptr::write(address_out, Box::into_raw(address_box));
             CroResult::success()
         }
+        ExtendedAddr::HashTimeLock(_) => unreachable!("multi-sig addresses are tree addresses"),
     }
 }
 
@@ -1,11 +1,15 @@
+mod devnet_command;
+mod devnet_spec;
 mod genesis_command;
 mod genesis_dev_config;
+mod genesis_spec;
 mod init_command;
 mod keypackage_command;
 mod run_command;
 mod stop_command;
 mod test_vector_command;
 
+pub use self::devnet_command::DevnetCommand;
 pub use self::genesis_command::GenesisCommand;
 pub use self::genesis_dev_config::{GenesisDevConfig, InitialFeePolicy};
 pub use self::init_command::InitCommand;
@@ -0,0 +1,1157 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use secstr::SecUtf8;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use signatory::ed25519;
+use signatory::public_key::PublicKeyed;
+use signatory_dalek::Ed25519Signer;
+use structopt::StructOpt;
+
+use chain_core::init::address::RedeemAddress;
+use chain_core::init::coin::{sum_coins, Coin};
+use chain_core::init::network::init_chain_id;
+use chain_core::state::account::{ConfidentialInit, MLSInit, StakedStateAddress};
+use chain_core::state::tendermint::{TendermintValidatorAddress, TendermintValidatorPubKey};
+use client_common::tendermint::types::{Genesis, Time};
+use client_common::{Error, ErrorKind, Result, ResultExt};
+use client_core::service::{open_sled_storage, HDAccountType};
+use client_core::wallet::syncer::compute_genesis_fingerprint;
+use client_core::wallet::{DefaultWalletClient, WalletClient};
+use client_core::{HDSeed, Mnemonic};
+use test_common::chain_env::KEYPACKAGE_VECTOR;
+
+use super::devnet_spec::{parse_seed, DevnetSpec, EnclaveSpec};
+use super::genesis_command::{generate_genesis, tendermint_genesis};
+use super::genesis_dev_config::GenesisDevConfig;
+use crate::gen_keypackage;
+
+/// generated configuration and addresses (written in the root directory)
+const INFO_FILE: &str = "devnet.json";
+/// ids of the running processes
+const PIDS_FILE: &str = "pids.json";
+const LOGS_DIR: &str = "logs";
+/// name of the validators' wallets in their client-rpc storage
+const VALIDATOR_WALLET_NAME: &str = "Default";
+/// genesis time within the lifetime of the test vector keypackage
+const MOCK_GENESIS_TIME: &str = "2020-05-26T10:48:04Z";
+/// the programs are started (and stopped in reverse) in this order
+const PROGRAM_KINDS: [&str; 3] = ["chain-abci", "tendermint", "client-rpc"];
+/// time given to a program to exit before it's killed
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, StructOpt)]
+pub enum DevnetCommand {
+    #[structopt(
+        name = "init",
+        about = "Generate the node homes, keys, genesis and wallets from a devnet spec"
+    )]
+    Init {
+        #[structopt(
+            name = "spec",
+            short,
+            long,
+            help = "Path to the devnet spec (YAML) -- see example-devnet.yaml"
+        )]
+        spec: PathBuf,
+    },
+    #[structopt(
+        name = "start",
+        about = "Start all the processes of an initialized devnet and wait until they are healthy"
+    )]
+    Start {
+        #[structopt(
+            name = "root_path",
+            short,
+            long,
+            default_value = "./devnet",
+            help = "Root directory of the devnet"
+        )]
+        root_path: PathBuf,
+    },
+    #[structopt(name = "stop", about = "Stop all the processes of a devnet")]
+    Stop {
+        #[structopt(
+            name = "root_path",
+            short,
+            long,
+            default_value = "./devnet",
+            help = "Root directory of the devnet"
+        )]
+        root_path: PathBuf,
+    },
+    #[structopt(name = "status", about = "Print the state of the devnet processes")]
+    Status {
+        #[structopt(
+            name = "root_path",
+            short,
+            long,
+            default_value = "./devnet",
+            help = "Root directory of the devnet"
+        )]
+        root_path: PathBuf,
+    },
+    #[structopt(name = "up", about = "Initialize and start a devnet from a spec")]
+    Up {
+        #[structopt(
+            name = "spec",
+            short,
+            long,
+            help = "Path to the devnet spec (YAML) -- see example-devnet.yaml"
+        )]
+        spec: PathBuf,
+    },
+    #[structopt(
+        name = "down",
+        about = "Stop all the processes of a devnet and remove its root directory"
+    )]
+    Down {
+        #[structopt(
+            name = "root_path",
+            short,
+            long,
+            default_value = "./devnet",
+            help = "Root directory of the devnet"
+        )]
+        root_path: PathBuf,
+    },
+}
+
+impl DevnetCommand {
+    pub fn execute(&self) -> Result<()> {
+        match self {
+            DevnetCommand::Init { spec } => {
+                let spec = DevnetSpec::from_file(spec)?;
+                init_devnet(&spec).map(|_| ())
+            }
+            DevnetCommand::Start { root_path } => start_devnet(root_path),
+            DevnetCommand::Stop { root_path } => stop_devnet(root_path),
+            DevnetCommand::Status { root_path } => print_status(root_path),
+            DevnetCommand::Up { spec } => {
+                let spec = DevnetSpec::from_file(spec)?;
+                let root_path = init_devnet(&spec)?;
+                start_devnet(&root_path)
+            }
+            DevnetCommand::Down { root_path } => remove_devnet(root_path),
+        }
+    }
+}
+
+/// Ports of a node: the i-th node uses `base_port + 10 * i + offset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodePorts {
+    pub tx_query: u16,
+    pub p2p: u16,
+    pub tendermint_rpc: u16,
+    pub abci: u16,
+    pub client_rpc: u16,
+}
+
+impl NodePorts {
+    fn new(base_port: u16, index: usize) -> Self {
+        let base = base_port + 10 * index as u16;
+        NodePorts {
+            tx_query: base + 1,
+            p2p: base + 6,
+            tendermint_rpc: base + 7,
+            abci: base + 8,
+            client_rpc: base + 9,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeInfo {
+    pub name: String,
+    /// p2p node id
+    pub node_id: String,
+    pub validator_address: String,
+    /// bonded and unbonded from genesis
+    pub staking_addresses: Vec<RedeemAddress>,
+    pub transfer_address: Option<String>,
+    pub ports: NodePorts,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletInfo {
+    pub name: String,
+    pub staking_address: RedeemAddress,
+    pub transfer_address: Option<String>,
+}
+
+/// Condition for a started program to be considered healthy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthCheck {
+    /// accepts TCP connections on the local port
+    Port(u16),
+    /// the Tendermint RPC on the local port reports a committed block
+    Blocks(u16),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Program {
+    /// unique, e.g. `tendermint-node0`
+    pub name: String,
+    /// one of `PROGRAM_KINDS`
+    pub kind: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    pub health_check: HealthCheck,
+}
+
+/// Everything `start`/`stop`/`status` need, generated by `init`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevnetInfo {
+    pub chain_id: String,
+    pub genesis_app_hash: String,
+    pub genesis_fingerprint: String,
+    pub health_timeout: u64,
+    pub nodes: Vec<NodeInfo>,
+    pub wallets: Vec<WalletInfo>,
+    pub programs: Vec<Program>,
+}
+
+impl DevnetInfo {
+    fn load(root_path: &Path) -> Result<Self> {
+        let path = root_path.join(INFO_FILE);
+        let contents = fs::read_to_string(&path).chain(|| {
+            (
+                ErrorKind::IoError,
+                format!(
+                    "Unable to read {} (run `dev-utils devnet init` first)",
+                    path.display()
+                ),
+            )
+        })?;
+        serde_json::from_str(&contents).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                format!("Failed to parse {}", path.display()),
+            )
+        })
+    }
+}
+
+/// ed25519 key pair in the Tendermint key files
+#[derive(Debug, Clone)]
+struct Ed25519Key {
+    seed: [u8; 32],
+    public: [u8; 32],
+}
+
+impl Ed25519Key {
+    fn from_seed(seed: [u8; 32]) -> Result<Self> {
+        let public = Ed25519Signer::from(&ed25519::Seed::new(seed))
+            .public_key()
+            .chain(|| (ErrorKind::InvalidInput, "Invalid ed25519 seed"))?
+            .into_bytes();
+        Ok(Ed25519Key { seed, public })
+    }
+
+    fn pub_key(&self) -> TendermintValidatorPubKey {
+        TendermintValidatorPubKey::Ed25519(self.public)
+    }
+
+    fn address(&self) -> [u8; 20] {
+        <[u8; 20]>::from(&TendermintValidatorAddress::from(&self.pub_key()))
+    }
+
+    fn priv_key_json(&self) -> serde_json::Value {
+        let mut keypair = self.seed.to_vec();
+        keypair.extend_from_slice(&self.public);
+        json!({
+            "type": "tendermint/PrivKeyEd25519",
+            "value": base64::encode(&keypair),
+        })
+    }
+
+    fn priv_validator_key_json(&self) -> serde_json::Value {
+        json!({
+            "address": hex::encode_upper(self.address()),
+            "pub_key": {
+                "type": "tendermint/PubKeyEd25519",
+                "value": base64::encode(&self.public),
+            },
+            "priv_key": self.priv_key_json(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct NodeKeys {
+    validator: Ed25519Key,
+    node: Ed25519Key,
+}
+
+/// The generated devnet before it's written to the disk
+#[derive(Debug)]
+struct DevnetPlan {
+    info: DevnetInfo,
+    genesis: serde_json::Value,
+    keys: Vec<NodeKeys>,
+}
+
+fn staking_address(spec: &DevnetSpec, mnemonic: &str, index: u32) -> Result<RedeemAddress> {
+    let mnemonic = Mnemonic::from_secstr(&SecUtf8::from(mnemonic))?;
+    let (public_key, _) = HDSeed::from(&mnemonic).derive_key_pair(
+        spec.network(),
+        HDAccountType::Staking.index(),
+        index,
+    )?;
+    Ok(RedeemAddress::from(&public_key))
+}
+
+fn genesis_dev_config(
+    spec: &DevnetSpec,
+    nodes: &[NodeInfo],
+    wallets: &[WalletInfo],
+    keys: &[NodeKeys],
+    keypackages: &[Vec<u8>],
+) -> Result<GenesisDevConfig> {
+    let mut config = GenesisDevConfig::new(spec.expansion_cap);
+    config.required_council_node_stake = spec.required_council_node_stake;
+    config.required_community_node_stake = spec.required_community_node_stake;
+    config.jailing_config = spec.jailing_config;
+    config.slashing_config = spec.slashing_config;
+    config.rewards_config.reward_period_seconds = spec.reward_period_seconds;
+    config.initial_fee_policy = spec.initial_fee_policy.clone();
+    config.evidence = spec.evidence.clone();
+
+    let mut distribution = vec![];
+    for ((validator, node), (node_keys, keypackage)) in spec
+        .validators
+        .iter()
+        .zip(nodes.iter())
+        .zip(keys.iter().zip(keypackages.iter()))
+    {
+        distribution.push((node.staking_addresses[0], validator.bonded_coin));
+        distribution.push((node.staking_addresses[1], validator.unbonded_coin));
+        if validator.bonded_coin > Coin::zero() {
+            config.council_nodes.insert(
+                node.staking_addresses[0],
+                (
+                    validator.name.clone(),
+                    Some(format!("{}@example.com", validator.name)),
+                    node_keys.validator.pub_key(),
+                    ConfidentialInit {
+                        init_payload: MLSInit::Genesis(keypackage.clone()),
+                    },
+                ),
+            );
+        }
+    }
+    for (wallet_spec, wallet) in spec.wallets.iter().zip(wallets.iter()) {
+        distribution.push((wallet.staking_address, wallet_spec.amount));
+    }
+
+    let total = sum_coins(
+        distribution
+            .iter()
+            .map(|(_, amount)| *amount)
+            .chain(std::iter::once(spec.expansion_cap)),
+    )
+    .chain(|| {
+        (
+            ErrorKind::InvalidInput,
+            "The distribution and the expansion cap exceed the maximum supply",
+        )
+    })?;
+    // burn the rest of the maximum supply
+    let burned = (Coin::max() - total).expect("total is within the maximum supply");
+    if burned > Coin::zero() {
+        distribution.push((RedeemAddress::default(), burned));
+    }
+    for (address, amount) in distribution {
+        if amount == Coin::zero() {
+            continue;
+        }
+        if config.distribution.insert(address, amount).is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Address {} is funded twice (duplicate mnemonic?)", address),
+            ));
+        }
+    }
+    Ok(config)
+}
+
+fn programs(spec: &DevnetSpec, root_path: &Path, info: &DevnetInfo) -> Vec<Program> {
+    let mut env = BTreeMap::new();
+    env.insert("RUST_LOG".to_owned(), "info".to_owned());
+    env.insert("RUST_BACKTRACE".to_owned(), "1".to_owned());
+    let mut programs = vec![];
+    for node in info.nodes.iter() {
+        let node_path = root_path.join(&node.name);
+        let mut args = vec![
+            "run".to_owned(),
+            "--host".to_owned(),
+            "127.0.0.1".to_owned(),
+            "--port".to_owned(),
+            node.ports.abci.to_string(),
+            "--genesis_app_hash".to_owned(),
+            info.genesis_app_hash.clone(),
+            "--chain_id".to_owned(),
+            info.chain_id.clone(),
+            "--data".to_owned(),
+            node_path.join("chain").display().to_string(),
+        ];
+        if !spec.is_mock_enclave() {
+            args.push("--tx_query".to_owned());
+            args.push(format!("127.0.0.1:{}", node.ports.tx_query));
+        }
+        programs.push(Program {
+            name: format!("chain-abci-{}", node.name),
+            kind: "chain-abci".to_owned(),
+            command: spec.binaries.chain_abci.clone(),
+            args,
+            env: env.clone(),
+            health_check: HealthCheck::Port(node.ports.abci),
+        });
+        programs.push(Program {
+            name: format!("tendermint-{}", node.name),
+            kind: "tendermint".to_owned(),
+            command: spec.binaries.tendermint.clone(),
+            args: vec![
+                "node".to_owned(),
+                "--home".to_owned(),
+                node_path.join("tendermint").display().to_string(),
+            ],
+            env: env.clone(),
+            health_check: HealthCheck::Blocks(node.ports.tendermint_rpc),
+        });
+        if spec.client_rpc {
+            let mut client_env = env.clone();
+            client_env.insert(
+                "CRYPTO_GENESIS_FINGERPRINT".to_owned(),
+                info.genesis_fingerprint.clone(),
+            );
+            programs.push(Program {
+                name: format!("client-rpc-{}", node.name),
+                kind: "client-rpc".to_owned(),
+                command: spec.binaries.client_rpc.clone(),
+                args: vec![
+                    "--host".to_owned(),
+                    "127.0.0.1".to_owned(),
+                    "--port".to_owned(),
+                    node.ports.client_rpc.to_string(),
+                    "--chain-id".to_owned(),
+                    info.chain_id.clone(),
+                    "--storage-dir".to_owned(),
+                    node_path.join("wallet").display().to_string(),
+                    "--websocket-url".to_owned(),
+                    format!("ws://127.0.0.1:{}/websocket", node.ports.tendermint_rpc),
+                    // all the nodes are local
+                    "--disable-light-client".to_owned(),
+                ],
+                env: client_env,
+                health_check: HealthCheck::Port(node.ports.client_rpc),
+            });
+        }
+    }
+    programs
+}
+
+fn tendermint_config(spec: &DevnetSpec, node: &NodeInfo, peers: &str) -> String {
+    format!(
+        r#"proxy_app = "tcp://127.0.0.1:{abci}"
+moniker = "{moniker}"
+fast_sync = true
+db_backend = "goleveldb"
+log_level = "main:info,state:info,*:error"
+
+[rpc]
+laddr = "tcp://127.0.0.1:{rpc}"
+
+[p2p]
+laddr = "tcp://127.0.0.1:{p2p}"
+persistent_peers = "{peers}"
+addr_book_strict = false
+allow_duplicate_ip = true
+
+[consensus]
+timeout_commit = "{timeout_commit}"
+create_empty_blocks = true
+
+[tx_index]
+indexer = "kv"
+index_all_keys = true
+"#,
+        abci = node.ports.abci,
+        moniker = node.name,
+        rpc = node.ports.tendermint_rpc,
+        p2p = node.ports.p2p,
+        peers = peers,
+        timeout_commit = spec.timeout_commit,
+    )
+}
+
+fn plan_devnet(
+    spec: &DevnetSpec,
+    root_path: &Path,
+    keypackages: &[Vec<u8>],
+    genesis_time: Time,
+) -> Result<DevnetPlan> {
+    let mut keys = vec![];
+    let mut nodes = vec![];
+    for (index, validator) in spec.validators.iter().enumerate() {
+        let node_keys = NodeKeys {
+            validator: Ed25519Key::from_seed(parse_seed(&validator.validator_seed)?)?,
+            node: Ed25519Key::from_seed(parse_seed(&validator.node_seed)?)?,
+        };
+        nodes.push(NodeInfo {
+            name: validator.name.clone(),
+            node_id: hex::encode(node_keys.node.address()),
+            validator_address: hex::encode_upper(node_keys.validator.address()),
+            staking_addresses: vec![
+                staking_address(spec, &validator.mnemonic, 0)?,
+                staking_address(spec, &validator.mnemonic, 1)?,
+            ],
+            transfer_address: None,
+            ports: NodePorts::new(spec.base_port, index),
+        });
+        keys.push(node_keys);
+    }
+    let wallets = spec
+        .wallets
+        .iter()
+        .map(|wallet| {
+            Ok(WalletInfo {
+                name: wallet.name.clone(),
+                staking_address: staking_address(spec, &wallet.mnemonic, 0)?,
+                transfer_address: None,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let config = genesis_dev_config(spec, &nodes, &wallets, &keys, keypackages)?;
+    let genesis_seconds = genesis_time
+        .duration_since(Time::unix_epoch())
+        .chain(|| (ErrorKind::InvalidInput, "Invalid genesis time"))?
+        .as_secs();
+    let (app_hash, app_state, validators) = generate_genesis(&config, genesis_seconds, &None)?;
+    let genesis = tendermint_genesis(
+        &spec.chain_id,
+        genesis_time,
+        &config.evidence,
+        &app_hash,
+        &app_state,
+        &validators,
+    )?;
+    let parsed: Genesis = serde_json::from_value(genesis.clone()).chain(|| {
+        (
+            ErrorKind::SerializationError,
+            "Invalid generated Tendermint genesis",
+        )
+    })?;
+    let genesis_fingerprint = compute_genesis_fingerprint(&parsed)?;
+
+    let mut info = DevnetInfo {
+        chain_id: spec.chain_id.clone(),
+        genesis_app_hash: app_hash,
+        genesis_fingerprint,
+        health_timeout: spec.health_timeout,
+        nodes,
+        wallets,
+        programs: vec![],
+    };
+    info.programs = programs(spec, root_path, &info);
+    Ok(DevnetPlan {
+        info,
+        genesis,
+        keys,
+    })
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> Result<()> {
+    let contents = serde_json::to_string_pretty(value)
+        .chain(|| (ErrorKind::SerializationError, "Failed to serialize JSON"))?;
+    write_file(path, &contents)
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).chain(|| {
+            (
+                ErrorKind::IoError,
+                format!("Failed to create directory {}", parent.display()),
+            )
+        })?;
+    }
+    fs::write(path, contents).chain(|| {
+        (
+            ErrorKind::IoError,
+            format!("Failed to write {}", path.display()),
+        )
+    })
+}
+
+fn write_tendermint_home(
+    spec: &DevnetSpec,
+    plan: &DevnetPlan,
+    index: usize,
+    home: &Path,
+) -> Result<()> {
+    let node = &plan.info.nodes[index];
+    let keys = &plan.keys[index];
+    let peers = plan
+        .info
+        .nodes
+        .iter()
+        .filter(|peer| peer.name != node.name)
+        .map(|peer| format!("{}@127.0.0.1:{}", peer.node_id, peer.ports.p2p))
+        .collect::<Vec<_>>()
+        .join(",");
+    let config = home.join("config");
+    write_json(&config.join("genesis.json"), &plan.genesis)?;
+    write_json(
+        &config.join("node_key.json"),
+        &json!({ "priv_key": keys.node.priv_key_json() }),
+    )?;
+    write_json(
+        &config.join("priv_validator_key.json"),
+        &keys.validator.priv_validator_key_json(),
+    )?;
+    write_file(
+        &config.join("config.toml"),
+        &tendermint_config(spec, node, &peers),
+    )?;
+    write_json(
+        &home.join("data").join("priv_validator_state.json"),
+        &json!({
+            "height": "0",
+            "round": "0",
+            "step": 0
+        }),
+    )
+}
+
+/// Restores the HD wallet in a client-rpc storage and checks it derives the planned staking addresses;
+/// returns its first transfer address
+fn restore_wallet(
+    spec: &DevnetSpec,
+    storage_path: &Path,
+    name: &str,
+    mnemonic: &str,
+    staking_addresses: &[RedeemAddress],
+) -> Result<String> {
+    let storage = open_sled_storage(storage_path)?;
+    let wallet_client = DefaultWalletClient::new_read_only(storage);
+    let passphrase = SecUtf8::from(spec.passphrase.as_str());
+    let mnemonic = Mnemonic::from_secstr(&SecUtf8::from(mnemonic))?;
+    let enckey = wallet_client.restore_wallet(name, &passphrase, &mnemonic)?;
+    for expected in staking_addresses {
+        let address = wallet_client.new_staking_address(name, &enckey)?;
+        if address != StakedStateAddress::BasicRedeem(*expected) {
+            return Err(Error::new(
+                ErrorKind::InternalError,
+                format!(
+                    "Wallet {} derived {} instead of {}",
+                    name, address, expected
+                ),
+            ));
+        }
+    }
+    let transfer_address = wallet_client.new_transfer_address(name, &enckey)?;
+    Ok(transfer_address.to_string())
+}
+
+fn keypackages(spec: &DevnetSpec) -> Result<Vec<Vec<u8>>> {
+    spec.validators
+        .iter()
+        .map(|validator| match &spec.enclave {
+            EnclaveSpec::Mock => Ok(KEYPACKAGE_VECTOR.to_vec()),
+            EnclaveSpec::Sgx { mls_enclave_path } => {
+                if validator.bonded_coin > Coin::zero() {
+                    gen_keypackage(mls_enclave_path)
+                } else {
+                    Ok(vec![])
+                }
+            }
+        })
+        .collect()
+}
+
+/// Generates the devnet files, returns its root directory
+fn init_devnet(spec: &DevnetSpec) -> Result<PathBuf> {
+    let root_path = spec.root_path.clone();
+    if root_path.exists() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "{} already exists (run `dev-utils devnet down` to remove it)",
+                root_path.display()
+            ),
+        ));
+    }
+    let genesis_time = match &spec.genesis_time {
+        Some(time) => Time::from_str(time).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid genesis time: {}", time),
+            )
+        })?,
+        // the test vector keypackage expires at the end of 2020
+        None if spec.is_mock_enclave() => Time::from_str(MOCK_GENESIS_TIME).unwrap(),
+        None => Time::now(),
+    };
+    // client-rpc wallets derive the addresses for the network of the chain id
+    init_chain_id(&spec.chain_id);
+
+    let keypackages = keypackages(spec)?;
+    let mut plan = plan_devnet(spec, &root_path, &keypackages, genesis_time)?;
+    for index in 0..plan.info.nodes.len() {
+        let node_path = root_path.join(&plan.info.nodes[index].name);
+        write_tendermint_home(spec, &plan, index, &node_path.join("tendermint"))?;
+        let node = &plan.info.nodes[index];
+        let transfer_address = restore_wallet(
+            spec,
+            &node_path.join("wallet"),
+            VALIDATOR_WALLET_NAME,
+            &spec.validators[index].mnemonic,
+            &node.staking_addresses,
+        )?;
+        plan.info.nodes[index].transfer_address = Some(transfer_address);
+    }
+    let first_wallet_path = root_path.join(&plan.info.nodes[0].name).join("wallet");
+    for (wallet_spec, wallet) in spec.wallets.iter().zip(plan.info.wallets.iter_mut()) {
+        wallet.transfer_address = Some(restore_wallet(
+            spec,
+            &first_wallet_path,
+            &wallet_spec.name,
+            &wallet_spec.mnemonic,
+            &[wallet.staking_address],
+        )?);
+    }
+    fs::create_dir_all(root_path.join(LOGS_DIR))
+        .chain(|| (ErrorKind::IoError, "Failed to create the logs directory"))?;
+    write_json(&root_path.join(INFO_FILE), &plan.info)?;
+
+    println!(
+        "devnet {} initialized in {}",
+        spec.chain_id,
+        root_path.display()
+    );
+    println!("genesis app hash: {}", plan.info.genesis_app_hash);
+    println!("genesis fingerprint: {}", plan.info.genesis_fingerprint);
+    for node in plan.info.nodes.iter() {
+        println!(
+            "{}: tendermint rpc {}, client-rpc {}, staking addresses {} {}",
+            node.name,
+            node.ports.tendermint_rpc,
+            node.ports.client_rpc,
+            node.staking_addresses[0],
+            node.staking_addresses[1],
+        );
+    }
+    Ok(root_path)
+}
+
+fn load_pids(root_path: &Path) -> Result<BTreeMap<String, u32>> {
+    let path = root_path.join(PIDS_FILE);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let contents =
+        fs::read_to_string(&path).chain(|| (ErrorKind::IoError, "Unable to read pids file"))?;
+    serde_json::from_str(&contents)
+        .chain(|| (ErrorKind::DeserializationError, "Failed to parse pids file"))
+}
+
+/// Command line of a process (`None` if it doesn't exist or already exited)
+fn process_command_line(pid: u32) -> Option<String> {
+    let output = Command::new("ps")
+        .args(&["-o", "stat=", "-o", "args=", "-p", &pid.to_string()])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let output = String::from_utf8_lossy(&output.stdout);
+    let mut columns = output.trim().splitn(2, char::is_whitespace);
+    // zombies exited, they are only not reaped yet
+    if columns.next()?.starts_with('Z') {
+        return None;
+    }
+    Some(columns.next()?.trim_start().to_owned())
+}
+
+/// Whether the process of a stored pid is still running the program
+/// (the pid may have been reused by another process after the program exited)
+fn is_running(program: &Program, pid: u32) -> bool {
+    process_command_line(pid).map_or(false, |command_line| {
+        command_line.starts_with(&program.command)
+    })
+}
+
+fn kill(pid: u32, signal: &str) {
+    let _ = Command::new("kill")
+        .args(&[signal, &pid.to_string()])
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// Terminates the process of a stored pid, killing it if it doesn't exit in time
+fn terminate(program: &Program, pid: u32) {
+    if !is_running(program, pid) {
+        return;
+    }
+    kill(pid, "-TERM");
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    while is_running(program, pid) && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(200));
+    }
+    if is_running(program, pid) {
+        kill(pid, "-KILL");
+    }
+    println!("{} stopped", program.name);
+}
+
+/// Terminates a process started by `start` and reaps it, killing it if it doesn't exit in time
+fn terminate_child(name: &str, child: &mut Child) {
+    if let Ok(None) = child.try_wait() {
+        kill(child.id(), "-TERM");
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        while let Ok(None) = child.try_wait() {
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                break;
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+    }
+    let _ = child.wait();
+    println!("{} stopped", name);
+}
+
+fn spawn(root_path: &Path, program: &Program) -> Result<Child> {
+    let log_path = root_path
+        .join(LOGS_DIR)
+        .join(format!("{}.log", program.name));
+    let stdout =
+        File::create(&log_path).chain(|| (ErrorKind::IoError, "Failed to create log file"))?;
+    let stderr = stdout
+        .try_clone()
+        .chain(|| (ErrorKind::IoError, "Failed to open log file"))?;
+    let child = Command::new(&program.command)
+        .args(&program.args)
+        .envs(&program.env)
+        .stdin(Stdio::null())
+        .stdout(stdout)
+        .stderr(stderr)
+        .spawn()
+        .chain(|| {
+            (
+                ErrorKind::IoError,
+                format!("Command {} failed to spawn", program.command),
+            )
+        })?;
+    println!("{} started (pid {})", program.name, child.id());
+    Ok(child)
+}
+
+/// Response body of a local HTTP GET request (if the status is 200)
+fn http_get(port: u16, path: &str) -> Option<String> {
+    let address = SocketAddr::from(([127, 0, 0, 1], port));
+    let mut stream = TcpStream::connect_timeout(&address, Duration::from_millis(500)).ok()?;
+    stream.set_read_timeout(Some(Duration::from_secs(2))).ok()?;
+    write!(
+        stream,
+        "GET {} HTTP/1.0\r\nHost: 127.0.0.1:{}\r\n\r\n",
+        path, port
+    )
+    .ok()?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok()?;
+    let mut parts = response.splitn(2, "\r\n\r\n");
+    let head = parts.next()?;
+    let body = parts.next()?;
+    if head.lines().next()?.split_whitespace().nth(1) == Some("200") {
+        Some(body.to_owned())
+    } else {
+        None
+    }
+}
+
+fn latest_block_height(rpc_port: u16) -> Option<u64> {
+    let body = http_get(rpc_port, "/status")?;
+    let status: serde_json::Value = serde_json::from_str(&body).ok()?;
+    status["result"]["sync_info"]["latest_block_height"]
+        .as_str()?
+        .parse()
+        .ok()
+}
+
+fn is_healthy(health_check: HealthCheck) -> bool {
+    match health_check {
+        HealthCheck::Port(port) => {
+            let address = SocketAddr::from(([127, 0, 0, 1], port));
+            TcpStream::connect_timeout(&address, Duration::from_millis(500)).is_ok()
+        }
+        HealthCheck::Blocks(port) => latest_block_height(port).map_or(false, |height| height > 0),
+    }
+}
+
+fn wait_healthy(program: &Program, child: &mut Child, deadline: Instant) -> Result<()> {
+    loop {
+        let exit_status = child.try_wait().chain(|| {
+            (
+                ErrorKind::IoError,
+                format!("Failed to check if {} exited", program.name),
+            )
+        })?;
+        if let Some(exit_status) = exit_status {
+            return Err(Error::new(
+                ErrorKind::InitializationError,
+                format!("{} exited with {} (see its log)", program.name, exit_status),
+            ));
+        }
+        if is_healthy(program.health_check) {
+            println!("{} is healthy", program.name);
+            return Ok(());
+        }
+        if Instant::now() > deadline {
+            return Err(Error::new(
+                ErrorKind::InitializationError,
+                format!("{} is not healthy in time (see its log)", program.name),
+            ));
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+}
+
+fn start_programs(
+    root_path: &Path,
+    info: &DevnetInfo,
+    children: &mut BTreeMap<String, Child>,
+) -> Result<()> {
+    let deadline = Instant::now() + Duration::from_secs(info.health_timeout);
+    for kind in PROGRAM_KINDS.iter() {
+        // start all the programs of a kind before waiting on them
+        // (e.g. Tendermint nodes can't produce blocks without the others)
+        let programs = info
+            .programs
+            .iter()
+            .filter(|program| program.kind == *kind)
+            .collect::<Vec<_>>();
+        for program in programs.iter() {
+            let child = spawn(root_path, program)?;
+            children.insert(program.name.clone(), child);
+            let pids = children
+                .iter()
+                .map(|(name, child)| (name.clone(), child.id()))
+                .collect::<BTreeMap<_, _>>();
+            write_json(&root_path.join(PIDS_FILE), &pids)?;
+        }
+        for program in programs.iter() {
+            let child = children.get_mut(&program.name).expect("program is started");
+            wait_healthy(program, child, deadline)?;
+        }
+    }
+    Ok(())
+}
+
+fn start_devnet(root_path: &Path) -> Result<()> {
+    let info = DevnetInfo::load(root_path)?;
+    let pids = load_pids(root_path)?;
+    if info.programs.iter().any(|program| {
+        pids.get(&program.name)
+            .map_or(false, |pid| is_running(program, *pid))
+    }) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "The devnet is already running (run `dev-utils devnet stop` first)",
+        ));
+    }
+    let mut children = BTreeMap::new();
+    if let Err(err) = start_programs(root_path, &info, &mut children) {
+        println!("failed to start the devnet, stopping it");
+        for program in stop_order(&info) {
+            if let Some(child) = children.get_mut(&program.name) {
+                terminate_child(&program.name, child);
+            }
+        }
+        remove_pids(root_path)?;
+        return Err(err);
+    }
+    println!("devnet {} is running", info.chain_id);
+    Ok(())
+}
+
+fn stop_devnet(root_path: &Path) -> Result<()> {
+    let info = DevnetInfo::load(root_path)?;
+    let pids = load_pids(root_path)?;
+    for program in stop_order(&info) {
+        if let Some(pid) = pids.get(&program.name) {
+            terminate(program, *pid);
+        }
+    }
+    remove_pids(root_path)
+}
+
+/// The programs in the reverse order of their kinds
+fn stop_order(info: &DevnetInfo) -> impl Iterator<Item = &Program> {
+    PROGRAM_KINDS.iter().rev().flat_map(move |kind| {
+        info.programs
+            .iter()
+            .filter(move |program| program.kind == *kind)
+    })
+}
+
+fn remove_pids(root_path: &Path) -> Result<()> {
+    let path = root_path.join(PIDS_FILE);
+    if path.exists() {
+        fs::remove_file(&path).chain(|| (ErrorKind::IoError, "Failed to remove pids file"))?;
+    }
+    Ok(())
+}
+
+fn print_status(root_path: &Path) -> Result<()> {
+    let info = DevnetInfo::load(root_path)?;
+    let pids = load_pids(root_path)?;
+    println!("devnet {} in {}", info.chain_id, root_path.display());
+    for program in info.programs.iter() {
+        let state = match pids.get(&program.name) {
+            Some(pid) if is_running(program, *pid) => {
+                let health = match program.health_check {
+                    HealthCheck::Blocks(port) => latest_block_height(port)
+                        .map_or_else(|| "unreachable".to_owned(), |h| format!("height {}", h)),
+                    check => {
+                        if is_healthy(check) {
+                            "healthy".to_owned()
+                        } else {
+                            "unreachable".to_owned()
+                        }
+                    }
+                };
+                format!("running (pid {}), {}", pid, health)
+            }
+            _ => "stopped".to_owned(),
+        };
+        println!("{}: {}", program.name, state);
+    }
+    Ok(())
+}
+
+fn remove_devnet(root_path: &Path) -> Result<()> {
+    // only remove directories generated by `init`
+    DevnetInfo::load(root_path)?;
+    stop_devnet(root_path)?;
+    fs::remove_dir_all(root_path).chain(|| {
+        (
+            ErrorKind::IoError,
+            format!("Failed to remove {}", root_path.display()),
+        )
+    })?;
+    println!("{} removed", root_path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_spec() -> DevnetSpec {
+        let spec: DevnetSpec =
+            serde_yaml::from_str(include_str!("../../example-devnet.yaml")).unwrap();
+        spec.validate().unwrap();
+        spec
+    }
+
+    #[test]
+    fn check_devnet_plan() {
+        let spec = example_spec();
+        let keypackages = keypackages(&spec).unwrap();
+        let genesis_time = Time::from_str("2020-10-01T00:00:00Z").unwrap();
+        let plan = plan_devnet(&spec, Path::new("/tmp/devnet"), &keypackages, genesis_time)
+            .expect("plan devnet");
+        let info = &plan.info;
+
+        // deterministic
+        let again = plan_devnet(&spec, Path::new("/tmp/devnet"), &keypackages, genesis_time)
+            .expect("plan devnet");
+        assert_eq!(info.genesis_app_hash, again.info.genesis_app_hash);
+        assert_eq!(info.genesis_fingerprint, again.info.genesis_fingerprint);
+
+        assert_eq!(4, info.nodes.len());
+        assert_eq!(4, plan.genesis["validators"].as_array().unwrap().len());
+        assert_eq!(
+            plan.genesis["app_hash"].as_str().unwrap(),
+            info.genesis_app_hash
+        );
+        assert_eq!(NodePorts::new(26650, 1), info.nodes[1].ports);
+        assert_eq!(26667, info.nodes[1].ports.tendermint_rpc);
+        for (node, keys) in info.nodes.iter().zip(plan.keys.iter()) {
+            let key = keys.validator.priv_validator_key_json();
+            assert_eq!(node.validator_address, key["address"].as_str().unwrap());
+        }
+
+        // the whole supply is distributed
+        let app_state: chain_core::init::config::InitConfig =
+            serde_json::from_value(plan.genesis["app_state"].clone()).unwrap();
+        let total = sum_coins(app_state.distribution.values().map(|(_, amount)| *amount)).unwrap();
+        assert_eq!(Coin::max(), (total + spec.expansion_cap).unwrap());
+        assert_eq!(4, app_state.council_nodes.len());
+
+        // 3 programs per node, all with distinct ports
+        assert_eq!(12, info.programs.len());
+        let mut ports = std::collections::BTreeSet::new();
+        for node in info.nodes.iter() {
+            let p = node.ports;
+            for port in [p.tx_query, p.p2p, p.tendermint_rpc, p.abci, p.client_rpc].iter() {
+                assert!(ports.insert(*port));
+            }
+        }
+        let config = tendermint_config(&spec, &info.nodes[0], "peers");
+        assert!(config.contains("proxy_app = \"tcp://127.0.0.1:26658\""));
+    }
+
+    #[test]
+    fn check_process_identity() {
+        let program = |command: &str| Program {
+            name: "program".to_owned(),
+            kind: PROGRAM_KINDS[0].to_owned(),
+            command: command.to_owned(),
+            args: vec![],
+            env: BTreeMap::new(),
+            health_check: HealthCheck::Port(0),
+        };
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        assert!(is_running(&program("sleep"), child.id()));
+        // the pid was reused by another program
+        assert!(!is_running(&program("chain-abci"), child.id()));
+
+        kill(child.id(), "-KILL");
+        thread::sleep(Duration::from_millis(200));
+        // not reaped yet
+        assert!(!is_running(&program("sleep"), child.id()));
+        child.wait().unwrap();
+        assert!(!is_running(&program("sleep"), child.id()));
+    }
+
+    #[test]
+    fn check_devnet_spec_validation() {
+        let mut spec = example_spec();
+        spec.validators[1].name = spec.validators[0].name.clone();
+        assert!(spec.validate().is_err());
+
+        let mut spec = example_spec();
+        spec.validators[0].validator_seed = "00".to_owned();
+        assert!(spec.validate().is_err());
+
+        let mut spec = example_spec();
+        spec.wallets[0].amount = Coin::max();
+        let keypackages = keypackages(&spec).unwrap();
+        assert!(plan_devnet(&spec, Path::new("/tmp/devnet"), &keypackages, Time::now()).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use chain_core::init::coin::Coin;
+use chain_core::init::network::{Network, MAINNET_CHAIN_ID, TESTNET_CHAIN_ID};
+use chain_core::init::params::{JailingParameters, SlashRatio, SlashingParameters};
+use client_common::{Error, ErrorKind, Result, ResultExt};
+
+use super::genesis_dev_config::{Evidence, InitialFeePolicy};
+
+/// Declarative description of a local multi-node network (`dev-utils devnet`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DevnetSpec {
+    /// the name convention is "...some-name...-<TWO_HEX_DIGITS>"
+    pub chain_id: String,
+    /// directory with all the generated node homes, logs and process ids
+    #[serde(default = "default_root_path")]
+    pub root_path: PathBuf,
+    /// RFC 3339, the current time if not set
+    /// (with the mock enclave: a time the test vector keypackage is valid at)
+    #[serde(default)]
+    pub genesis_time: Option<String>,
+    /// the i-th node listens on `base_port + 10 * i + offset` (see `NodePorts`)
+    #[serde(default = "default_base_port")]
+    pub base_port: u16,
+    /// passphrase of the generated client-rpc wallets
+    #[serde(default = "default_passphrase")]
+    pub passphrase: String,
+    #[serde(default)]
+    pub enclave: EnclaveSpec,
+    #[serde(default)]
+    pub binaries: BinariesSpec,
+    /// whether to start a client-rpc server for each node
+    #[serde(default = "default_true")]
+    pub client_rpc: bool,
+    /// seconds to wait for the processes to become healthy
+    #[serde(default = "default_health_timeout")]
+    pub health_timeout: u64,
+    pub expansion_cap: Coin,
+    #[serde(default = "default_council_node_stake")]
+    pub required_council_node_stake: Coin,
+    #[serde(default = "default_community_node_stake")]
+    pub required_community_node_stake: Coin,
+    #[serde(default = "default_fee_policy")]
+    pub initial_fee_policy: InitialFeePolicy,
+    #[serde(default = "default_jailing_config")]
+    pub jailing_config: JailingParameters,
+    #[serde(default = "default_slashing_config")]
+    pub slashing_config: SlashingParameters,
+    #[serde(default = "default_reward_period")]
+    pub reward_period_seconds: u64,
+    #[serde(default = "default_evidence")]
+    pub evidence: Evidence,
+    /// Tendermint's `consensus.timeout_commit`
+    #[serde(default = "default_timeout_commit")]
+    pub timeout_commit: String,
+    pub validators: Vec<ValidatorSpec>,
+    /// funded wallets (restored in the first node's client-rpc storage)
+    #[serde(default)]
+    pub wallets: Vec<WalletSpec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorSpec {
+    pub name: String,
+    pub mnemonic: String,
+    /// hex-encoded 32-byte ed25519 seed of the validator key
+    pub validator_seed: String,
+    /// hex-encoded 32-byte ed25519 seed of the p2p node key
+    pub node_seed: String,
+    /// bonded from genesis in the first staking address
+    /// (the validator is a genesis council node if it's not zero)
+    pub bonded_coin: Coin,
+    /// unbonded from genesis in the second staking address
+    #[serde(default = "Coin::zero")]
+    pub unbonded_coin: Coin,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WalletSpec {
+    pub name: String,
+    pub mnemonic: String,
+    /// unbonded from genesis in the first staking address
+    pub amount: Coin,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum EnclaveSpec {
+    /// chain-abci built with the `mock-enclave` feature, test vector keypackages
+    Mock,
+    /// keypackages generated by the MLS enclave, transaction query enabled
+    Sgx {
+        /// e.g. `target/x86_64-fortanix-unknown-sgx/debug/mls.sgxs`
+        mls_enclave_path: String,
+    },
+}
+
+impl Default for EnclaveSpec {
+    fn default() -> Self {
+        EnclaveSpec::Mock
+    }
+}
+
+/// Commands (or paths) of the started programs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BinariesSpec {
+    pub chain_abci: String,
+    pub tendermint: String,
+    pub client_rpc: String,
+}
+
+impl Default for BinariesSpec {
+    fn default() -> Self {
+        BinariesSpec {
+            chain_abci: "chain-abci".to_owned(),
+            tendermint: "tendermint".to_owned(),
+            client_rpc: "client-rpc".to_owned(),
+        }
+    }
+}
+
+fn default_root_path() -> PathBuf {
+    PathBuf::from("./devnet")
+}
+
+fn default_base_port() -> u16 {
+    26650
+}
+
+fn default_passphrase() -> String {
+    "devnet passphrase 123456".to_owned()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_health_timeout() -> u64 {
+    60
+}
+
+fn default_council_node_stake() -> Coin {
+    Coin::new(10_0000_0000).unwrap()
+}
+
+fn default_community_node_stake() -> Coin {
+    Coin::new(1_0000_0000).unwrap()
+}
+
+fn default_fee_policy() -> InitialFeePolicy {
+    InitialFeePolicy {
+        base_fee: "1.1".to_owned(),
+        per_byte_fee: "1.25".to_owned(),
+    }
+}
+
+fn default_jailing_config() -> JailingParameters {
+    JailingParameters {
+        block_signing_window: 20,
+        missed_block_threshold: 5,
+    }
+}
+
+fn default_slashing_config() -> SlashingParameters {
+    SlashingParameters {
+        liveness_slash_percent: SlashRatio::from_str("0.1").unwrap(),
+        byzantine_slash_percent: SlashRatio::from_str("0.2").unwrap(),
+        invalid_commit_slash_percent: SlashRatio::from_str("0.3").unwrap(),
+    }
+}
+
+fn default_reward_period() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_evidence() -> Evidence {
+    Evidence {
+        max_age_duration: "9000000000".to_owned(),
+        max_age_num_blocks: "100000".to_owned(),
+    }
+}
+
+fn default_timeout_commit() -> String {
+    "1s".to_owned()
+}
+
+/// Decodes a hex-encoded 32-byte seed
+pub fn parse_seed(seed: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(seed).chain(|| (ErrorKind::InvalidInput, "Invalid hex seed"))?;
+    if bytes.len() != 32 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Seed should be 32 bytes, got {}", bytes.len()),
+        ));
+    }
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&bytes);
+    Ok(result)
+}
+
+impl DevnetSpec {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).chain(|| {
+            (
+                ErrorKind::IoError,
+                format!("Unable to read devnet spec {}", path.display()),
+            )
+        })?;
+        let spec: DevnetSpec = serde_yaml::from_str(&contents).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Failed to parse devnet spec",
+            )
+        })?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let length = self.chain_id.len();
+        if length < 6 || hex::decode(&self.chain_id[(length - 2)..]).is_err() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Chain ID should end with two hex digits such as AB",
+            ));
+        }
+        if self.validators.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "At least one validator is required",
+            ));
+        }
+        if self
+            .validators
+            .iter()
+            .all(|validator| validator.bonded_coin == Coin::zero())
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "At least one validator should be bonded from genesis",
+            ));
+        }
+        let mut names = BTreeSet::new();
+        for name in self
+            .validators
+            .iter()
+            .map(|validator| &validator.name)
+            .chain(self.wallets.iter().map(|wallet| &wallet.name))
+        {
+            if !names.insert(name) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Duplicate name: {}", name),
+                ));
+            }
+        }
+        for validator in self.validators.iter() {
+            parse_seed(&validator.validator_seed)?;
+            parse_seed(&validator.node_seed)?;
+        }
+        let last_port = u32::from(self.base_port) + 10 * self.validators.len() as u32;
+        if last_port > u32::from(u16::max_value()) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Not enough ports after the base port",
+            ));
+        }
+        Ok(())
+    }
+
+    /// HD wallet addresses depend on the network type
+    pub fn network(&self) -> Network {
+        match self.chain_id.as_str() {
+            MAINNET_CHAIN_ID => Network::Mainnet,
+            TESTNET_CHAIN_ID => Network::Testnet,
+            _ => Network::Devnet,
+        }
+    }
+
+    pub fn is_mock_enclave(&self) -> bool {
+        matches!(self.enclave, EnclaveSpec::Mock)
+    }
+}
@@ -19,9 +19,10 @@ use chain_core::state::tendermint::{
 };
 use chain_core::tx::fee::{LinearFee, Milli};
 use client_common::tendermint::types::{Genesis, Time};
-use client_common::{ErrorKind, Result, ResultExt};
+use client_common::{Error, ErrorKind, Result, ResultExt};
 
-use crate::commands::genesis_dev_config::GenesisDevConfig;
+use crate::commands::genesis_dev_config::{Evidence, GenesisDevConfig};
+use crate::commands::genesis_spec::GenesisSpec;
 use client_core::wallet::syncer::compute_genesis_fingerprint;
 
 #[derive(Debug, StructOpt)]
@@ -84,6 +85,27 @@ pub enum GenesisCommand {
         )]
         no_evidence_overwrite: bool,
     },
+    #[structopt(
+        name = "from-spec",
+        about = "Generate a complete genesis.json from a declarative genesis spec"
+    )]
+    FromSpec {
+        #[structopt(
+            name = "spec_path",
+            short,
+            long,
+            help = "Path to the genesis spec (YAML or JSON) -- see example-genesis-spec.yaml"
+        )]
+        spec_path: PathBuf,
+
+        #[structopt(
+            name = "output",
+            short,
+            long,
+            help = "Path to write genesis.json to (printed to the standard output if not set)"
+        )]
+        output: Option<PathBuf>,
+    },
     #[structopt(
         name = "fingerprint",
         about = "Calculate the genesis' fingerprint from genesis.json"
@@ -133,6 +155,9 @@ impl GenesisCommand {
                 *no_evidence_overwrite,
             )
             .map(|_| ()),
+            GenesisCommand::FromSpec { spec_path, output } => {
+                generate_genesis_from_spec(spec_path, output)
+            }
             GenesisCommand::Fingerprint {
                 tendermint_genesis_path,
             } => get_genesis_fingerprint(tendermint_genesis_path),
@@ -290,6 +315,103 @@ fn generate_genesis_command(
     Ok(())
 }
 
+fn generate_genesis_from_spec(spec_path: &Path, output: &Option<PathBuf>) -> Result<()> {
+    let spec = GenesisSpec::from_file(spec_path)?;
+    let base_dir = spec_path.parent().unwrap_or_else(|| Path::new("."));
+    let genesis = spec.build(base_dir).map_err(|errors| {
+        Error::new(
+            ErrorKind::ValidationError,
+            format_errors("Invalid genesis spec", &errors),
+        )
+    })?;
+    let app_hash = encode_upper(init_app_hash(&genesis.config, genesis.genesis_seconds));
+    let tendermint_genesis = tendermint_genesis(
+        &spec.chain_id,
+        genesis.genesis_time,
+        &spec.evidence,
+        &app_hash,
+        &genesis.config,
+        &genesis.validators,
+    )?;
+    let tendermint_genesis_string =
+        serde_json::to_string_pretty(&tendermint_genesis).chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                "Invalid generated Tendermint genesis",
+            )
+        })?;
+
+    match output {
+        Some(path) => {
+            write_tendermint_genesis(path, &tendermint_genesis_string)?;
+            let parsed: Genesis = serde_json::from_value(tendermint_genesis).chain(|| {
+                (
+                    ErrorKind::DeserializationError,
+                    "failed to parse generated Tendermint genesis",
+                )
+            })?;
+            println!("genesis app hash: {}", app_hash);
+            println!(
+                "genesis fingerprint: {}",
+                compute_genesis_fingerprint(&parsed)?
+            );
+        }
+        None => println!("{}", tendermint_genesis_string),
+    }
+    Ok(())
+}
+
+/// Complete Tendermint genesis.json (with the default consensus parameters)
+pub fn tendermint_genesis(
+    chain_id: &str,
+    genesis_time: Time,
+    evidence: &Evidence,
+    app_hash: &str,
+    app_state: &InitConfig,
+    validators: &[TendermintValidator],
+) -> Result<serde_json::Value> {
+    let app_state = serde_json::to_value(app_state).chain(|| {
+        (
+            ErrorKind::SerializationError,
+            "failed to convert generated app state into json value",
+        )
+    })?;
+    let validators = serde_json::to_value(validators).chain(|| {
+        (
+            ErrorKind::SerializationError,
+            "failed to convert generated validators into json value",
+        )
+    })?;
+    Ok(serde_json::json!({
+        "genesis_time": genesis_time.to_string(),
+        "chain_id": chain_id,
+        "consensus_params": {
+            "block": {
+                "max_bytes": "22020096",
+                "max_gas": "-1",
+                "time_iota_ms": "1000"
+            },
+            "evidence": evidence,
+            "validator": {
+                "pub_key_types": ["ed25519"]
+            }
+        },
+        "validators": validators,
+        "app_hash": app_hash,
+        "app_state": app_state,
+    }))
+}
+
+/// One problem per line
+fn format_errors(message: &str, errors: &[String]) -> String {
+    let mut formatted = format!("{} ({} problems):", message, errors.len());
+    for error in errors {
+        formatted.push_str("\n  - ");
+        formatted.push_str(error);
+    }
+    formatted
+}
+
 fn generate_light_genesis(genesis_dev_config_path: &PathBuf) -> Result<()> {
     let genesis_dev_config_string = fs::read_to_string(genesis_dev_config_path).chain(|| {
         (
@@ -386,6 +508,17 @@ pub fn generate_genesis(
         network_params,
         genesis_dev_config.council_nodes.clone(),
     );
+    let errors = config
+        .get_config_errors(genesis_time)
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        return Err(Error::new(
+            ErrorKind::ValidationError,
+            format_errors("Invalid genesis configuration", &errors),
+        ));
+    }
     let genesis_app_hash = init_app_hash(&config, genesis_time);
 
     let validators = generate_validators(&genesis_dev_config)?;
@@ -462,6 +595,7 @@ fn write_tendermint_genesis(path: &PathBuf, genesis_str: &str) -> Result<()> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use chain_core::state::tendermint::TendermintValidatorPubKey;
 
     #[test]
     fn test_dev_config_file() {
@@ -470,4 +604,49 @@ mod test {
             serde_json::from_str(genesis_dev_config_str);
         assert!(genesis_dev_config.is_ok());
     }
+
+    fn example_spec() -> GenesisSpec {
+        serde_yaml::from_str(include_str!("../../example-genesis-spec.yaml")).unwrap()
+    }
+
+    #[test]
+    fn test_genesis_spec_file() {
+        let spec = example_spec();
+        let genesis = spec.build(Path::new(".")).expect("valid genesis spec");
+        assert_eq!(1, genesis.validators.len());
+        let app_hash = encode_upper(init_app_hash(&genesis.config, genesis.genesis_seconds));
+        let tendermint_genesis = tendermint_genesis(
+            &spec.chain_id,
+            genesis.genesis_time,
+            &spec.evidence,
+            &app_hash,
+            &genesis.config,
+            &genesis.validators,
+        )
+        .unwrap();
+        assert_eq!(app_hash, tendermint_genesis["app_hash"].as_str().unwrap());
+        let parsed: Genesis = serde_json::from_value(tendermint_genesis).unwrap();
+        assert_eq!(Some(genesis.config), parsed.app_state);
+    }
+
+    #[test]
+    fn test_genesis_spec_errors_are_collected() {
+        let mut spec = example_spec();
+        spec.chain_id = "no-hex".to_owned();
+        // duplicate
+        spec.distribution.push(spec.distribution[1].clone());
+        // not in the distribution
+        let mut node = spec.council_nodes[0].clone();
+        node.staking_address = RedeemAddress::default();
+        node.consensus_pubkey = TendermintValidatorPubKey::Ed25519([1; 32]);
+        spec.council_nodes.push(node);
+        spec.network_params.jailing_config.missed_block_threshold = 200;
+
+        let errors = spec.build(Path::new(".")).unwrap_err();
+        assert_eq!(4, errors.len(), "{:?}", errors);
+        assert!(errors[0].contains("Chain ID"));
+        assert!(errors[1].contains("Duplicate account"));
+        assert!(errors[2].contains("punishment"));
+        assert!(errors[3].contains("not found in the distribution"));
+    }
 }
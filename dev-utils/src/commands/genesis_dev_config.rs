@@ -76,13 +76,13 @@ impl GenesisDevConfig {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InitialFeePolicy {
     pub base_fee: String,
     pub per_byte_fee: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Evidence {
     pub max_age_duration: String,
     pub max_age_num_blocks: String,
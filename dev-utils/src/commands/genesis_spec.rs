@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use chain_core::common::Timespec;
+use chain_core::init::address::RedeemAddress;
+use chain_core::init::coin::Coin;
+use chain_core::init::config::{DistributionError, InitConfig, InitNetworkParameters};
+use chain_core::state::account::{ConfidentialInit, MLSInit, StakedStateDestination};
+use chain_core::state::tendermint::{
+    TendermintValidator, TendermintValidatorAddress, TendermintValidatorPubKey, TendermintVotePower,
+};
+use client_common::tendermint::types::Time;
+use client_common::{ErrorKind, Result, ResultExt};
+use test_common::chain_env::KEYPACKAGE_VECTOR;
+
+use super::genesis_dev_config::Evidence;
+
+/// Declarative description of a genesis (`dev-utils genesis from-spec`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisSpec {
+    /// the name convention is "...some-name...-<TWO_HEX_DIGITS>"
+    pub chain_id: String,
+    /// RFC 3339 (fixed, so that the app hash is reproducible)
+    pub genesis_time: String,
+    /// the same as `network_params` in the generated `app_state`
+    pub network_params: InitNetworkParameters,
+    #[serde(default = "default_evidence")]
+    pub evidence: Evidence,
+    /// doesn't include the monetary expansion cap
+    pub distribution: Vec<DistributionEntry>,
+    pub council_nodes: Vec<CouncilNodeSpec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DistributionEntry {
+    pub address: RedeemAddress,
+    pub amount: Coin,
+    #[serde(default = "default_destination")]
+    pub destination: StakedStateDestination,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CouncilNodeSpec {
+    /// should be `Bonded` in the distribution
+    pub staking_address: RedeemAddress,
+    pub name: String,
+    #[serde(default)]
+    pub security_contact: Option<String>,
+    pub consensus_pubkey: TendermintValidatorPubKey,
+    pub keypackage: KeypackageSpec,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum KeypackageSpec {
+    /// base64-encoded keypackage
+    Base64(String),
+    /// file written by `dev-utils keypackage generate` (relative to the spec file)
+    Path(PathBuf),
+    /// the test vector keypackage (only accepted by the mock enclave)
+    Mock,
+}
+
+fn default_evidence() -> Evidence {
+    Evidence {
+        max_age_duration: "5400000000000".to_owned(),
+        max_age_num_blocks: "200".to_owned(),
+    }
+}
+
+fn default_destination() -> StakedStateDestination {
+    StakedStateDestination::UnbondedFromGenesis
+}
+
+impl KeypackageSpec {
+    fn load(&self, base_dir: &Path) -> Result<Vec<u8>> {
+        let encoded = match self {
+            KeypackageSpec::Base64(encoded) => encoded.clone(),
+            KeypackageSpec::Path(path) => {
+                let path = base_dir.join(path);
+                fs::read_to_string(&path).chain(|| {
+                    (
+                        ErrorKind::IoError,
+                        format!("Unable to read keypackage {}", path.display()),
+                    )
+                })?
+            }
+            KeypackageSpec::Mock => return Ok(KEYPACKAGE_VECTOR.to_vec()),
+        };
+        base64::decode(encoded.trim())
+            .chain(|| (ErrorKind::DeserializationError, "Invalid base64 keypackage"))
+    }
+}
+
+/// The genesis generated from a spec
+#[derive(Debug)]
+pub struct SpecGenesis {
+    pub genesis_time: Time,
+    /// `genesis_time` in seconds since the unix epoch
+    pub genesis_seconds: Timespec,
+    pub config: InitConfig,
+    pub validators: Vec<TendermintValidator>,
+}
+
+impl GenesisSpec {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).chain(|| {
+            (
+                ErrorKind::IoError,
+                format!("Unable to read genesis spec {}", path.display()),
+            )
+        })?;
+        // JSON is valid YAML
+        serde_yaml::from_str(&contents).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Failed to parse genesis spec",
+            )
+        })
+    }
+
+    /// Builds the initial config and the Tendermint validators, or returns all the problems found
+    /// (`base_dir` is where the keypackage paths are relative to)
+    pub fn build(&self, base_dir: &Path) -> std::result::Result<SpecGenesis, Vec<String>> {
+        let mut errors = vec![];
+
+        let length = self.chain_id.len();
+        if length < 6 || hex::decode(&self.chain_id[(length - 2)..]).is_err() {
+            errors.push("Chain ID should end with two hex digits such as AB".to_owned());
+        }
+        let genesis_time = Time::from_str(&self.genesis_time)
+            .ok()
+            .and_then(|time| Some((time, timespec(time)?)));
+        if genesis_time.is_none() {
+            errors.push(format!("Invalid genesis time: {}", self.genesis_time));
+        }
+
+        let mut distribution = BTreeMap::new();
+        for entry in self.distribution.iter() {
+            if distribution
+                .insert(entry.address, (entry.destination.clone(), entry.amount))
+                .is_some()
+            {
+                errors.push(DistributionError::DuplicateAccount(entry.address).to_string());
+            }
+        }
+        let mut council_nodes = BTreeMap::new();
+        for node in self.council_nodes.iter() {
+            let keypackage = match node.keypackage.load(base_dir) {
+                Ok(keypackage) => keypackage,
+                Err(e) => {
+                    errors.push(format!("Council node {}: {}", node.name, e));
+                    continue;
+                }
+            };
+            let details = (
+                node.name.clone(),
+                node.security_contact.clone(),
+                node.consensus_pubkey.clone(),
+                ConfidentialInit {
+                    init_payload: MLSInit::Genesis(keypackage),
+                },
+            );
+            if council_nodes
+                .insert(node.staking_address, details)
+                .is_some()
+            {
+                errors.push(format!(
+                    "Council node {}: staking address {} is used twice",
+                    node.name, node.staking_address
+                ));
+            }
+        }
+
+        let config = InitConfig::new(distribution, self.network_params.clone(), council_nodes);
+        if let Some((_, genesis_seconds)) = genesis_time {
+            errors.extend(
+                config
+                    .get_config_errors(genesis_seconds)
+                    .iter()
+                    .map(ToString::to_string),
+            );
+        }
+        match genesis_time {
+            Some((genesis_time, genesis_seconds)) if errors.is_empty() => Ok(SpecGenesis {
+                genesis_time,
+                genesis_seconds,
+                validators: validators(&config),
+                config,
+            }),
+            _ => Err(errors),
+        }
+    }
+}
+
+/// seconds since the unix epoch (if not before it)
+fn timespec(time: Time) -> Option<Timespec> {
+    time.duration_since(Time::unix_epoch())
+        .ok()
+        .map(|duration| duration.as_secs())
+}
+
+/// Tendermint validators of a valid config
+fn validators(config: &InitConfig) -> Vec<TendermintValidator> {
+    config
+        .council_nodes
+        .iter()
+        .map(|(address, (name, _, pubkey, _))| TendermintValidator {
+            address: TendermintValidatorAddress::from(pubkey),
+            name: name.clone(),
+            power: TendermintVotePower::from(config.distribution[address].1),
+            pub_key: pubkey.clone(),
+        })
+        .collect()
+}
@@ -12,9 +12,9 @@ use crate::verify_keypackage;
 use chain_core::init::{address::RedeemAddress, coin::Coin, config::InitConfig};
 use chain_core::state::account::{ConfidentialInit, MLSInit};
 use chain_core::state::tendermint::{TendermintValidator, TendermintValidatorPubKey};
-use client_common::storage::SledStorage;
 use client_common::tendermint::types::Time;
 use client_common::{Error, ErrorKind, Result, ResultExt};
+use client_core::service::open_sled_storage;
 use client_core::types::WalletKind;
 use client_core::wallet::{DefaultWalletClient, WalletClient};
 
@@ -344,7 +344,7 @@ impl InitCommand {
     }
 
     fn read_staking_address(&mut self) -> Result<()> {
-        let storage = SledStorage::new(InitCommand::storage_path())?;
+        let storage = open_sled_storage(InitCommand::storage_path())?;
         let wallet_client = DefaultWalletClient::new_read_only(storage);
 
         let name = self.ask_string("please enter wallet name=", "my");
@@ -1,3 +1,7 @@
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+
 use parity_scale_codec::Encode;
 use serde::Serialize;
 
@@ -6,10 +10,10 @@ use chain_core::init::address::{CroAddress, RedeemAddress};
 use chain_core::init::coin::Coin;
 use chain_core::init::network::Network;
 use chain_core::state::account::{
-    CouncilNodeMeta, DepositBondTx, NodeMetadata, StakedStateAddress, StakedStateOpAttributes,
-    StakedStateOpWitness, UnbondTx, WithdrawUnbondedTx,
+    CouncilNodeMeta, DepositBondTx, MLSInit, NodeMetadata, StakedState, StakedStateAddress,
+    StakedStateOpAttributes, StakedStateOpWitness, UnbondTx, WithdrawUnbondedTx,
 };
-use chain_core::state::tendermint::TendermintValidatorPubKey;
+use chain_core::state::tendermint::{BlockHeight, TendermintValidatorPubKey};
 use chain_core::state::validator::NodeJoinRequestTx;
 use chain_core::tx::data::access::{TxAccess, TxAccessPolicy};
 use chain_core::tx::data::address::ExtendedAddr;
@@ -17,26 +21,46 @@ use chain_core::tx::data::attribute::TxAttributes;
 use chain_core::tx::data::input::TxoPointer;
 use chain_core::tx::data::output::TxOut;
 use chain_core::tx::data::{Tx, TxId};
+use chain_core::tx::fee::Fee;
 use chain_core::tx::witness::tree::RawXOnlyPubkey;
 use chain_core::tx::witness::{TxInWitness, TxWitness};
 use chain_core::tx::TransactionId;
-use chain_core::tx::{PlainTxAux, TxAux, TxPublicAux};
+use chain_core::tx::{PlainTxAux, TxAux, TxEnclaveAux, TxObfuscated, TxPublicAux};
+use chain_core::ChainInfo;
 use client_common::key::PrivateKeyAction;
-use client_common::{MultiSigAddress, PrivateKey, PublicKey, Result, Transaction};
+use client_common::{
+    ErrorKind, MultiSigAddress, PrivateKey, PublicKey, Result, ResultExt, Transaction,
+};
 use client_core::service::{HDAccountType, HdKey};
 use client_core::HDSeed;
+use enclave_protocol::tdbe_protocol::{
+    PersistenceCommand, TrustedTdbeRequest, UntrustedTdbeRequest, UntrustedTdbeResponse,
+};
+use enclave_protocol::{DecryptionRequest, IntraEnclaveRequest};
+use mls::ciphersuite::CipherSuite;
+use mls::keypackage::{KeyPackage, PROTOCOL_VERSION_MLS10};
+use mls::message::{EncryptedGroupSecrets, HPKECiphertext, Welcome};
+use mls::{Codec, DefaultCipherSuite};
 use secp256k1::{key::XOnlyPublicKey, SecretKey};
-use test_common::chain_env::mock_confidential_init;
+use test_common::chain_env::{
+    mock_confidential_init, mock_confidential_init_node_join, KEYPACKAGE_VECTOR,
+};
 
 #[derive(Debug)]
 pub struct TestVectorCommand {
     network: Network,
     seed: Vec<u8>,
     aux_payload: Vec<u8>,
+    corpus_dir: Option<PathBuf>,
 }
 
 impl TestVectorCommand {
-    pub fn new(network: String, seed: String, aux_payload: &str) -> Self {
+    pub fn new(
+        network: String,
+        seed: String,
+        aux_payload: &str,
+        corpus_dir: Option<PathBuf>,
+    ) -> Self {
         let network = if network == "devnet" {
             Network::Devnet
         } else if network == "testnet" {
@@ -52,12 +76,17 @@ impl TestVectorCommand {
             network,
             seed,
             aux_payload,
+            corpus_dir,
         }
     }
 
     pub fn execute(&self) -> Result<()> {
         let mut vector_factory = VectorFactory::new(self.network, self.seed.clone());
-        vector_factory.create_test_vectors(&self.aux_payload)
+        vector_factory.create_test_vectors(&self.aux_payload)?;
+        if let Some(corpus_dir) = &self.corpus_dir {
+            vector_factory.write_corpus(corpus_dir)?;
+        }
+        Ok(())
     }
 }
 
@@ -120,6 +149,15 @@ struct TestVectors {
     unbonded_stake_vector: Option<UnboundedStakeVector>,
 }
 
+/// Encoded test vector used as a fuzzing seed input
+#[derive(Debug, Clone)]
+struct SeedInput {
+    /// name of the fuzz target that decodes it
+    target: &'static str,
+    name: &'static str,
+    data: Vec<u8>,
+}
+
 struct TestVectorWallet {
     pub hd_key: HdKey,
     pub view_key: (PublicKey, PrivateKey),
@@ -176,6 +214,7 @@ pub struct VectorFactory {
     chain_hex_id: u8,
     wallet: TestVectorWallet,
     test_vectors: TestVectors,
+    corpus: Vec<SeedInput>,
 }
 
 impl VectorFactory {
@@ -210,9 +249,47 @@ impl VectorFactory {
             chain_hex_id,
             wallet,
             test_vectors,
+            corpus: vec![],
+        }
+    }
+
+    fn add_seed_input(&mut self, target: &'static str, name: &'static str, data: Vec<u8>) {
+        self.corpus.push(SeedInput { target, name, data });
+    }
+
+    /// The first byte of a `decode-tdbe-protocol` input selects the message type
+    fn add_tdbe_seed_input(&mut self, name: &'static str, selector: u8, message: Vec<u8>) {
+        let mut data = vec![selector];
+        data.extend(message);
+        self.add_seed_input("decode-tdbe-protocol", name, data);
+    }
+
+    fn seed_chain_info(&self) -> ChainInfo {
+        ChainInfo {
+            min_fee_computed: Fee::new(Coin::zero()),
+            chain_hex_id: self.chain_hex_id,
+            block_time: 0,
+            block_height: BlockHeight::genesis(),
+            max_evidence_age: 0,
         }
     }
 
+    /// Writes the encoded test vectors as `<corpus_dir>/<fuzz target>/<vector name>`
+    pub fn write_corpus(&self, corpus_dir: &Path) -> Result<()> {
+        for input in self.corpus.iter() {
+            let dir = corpus_dir.join(input.target);
+            fs::create_dir_all(&dir).chain(|| {
+                (
+                    ErrorKind::IoError,
+                    format!("Failed to create corpus directory {}", dir.display()),
+                )
+            })?;
+            fs::write(dir.join(input.name), &input.data)
+                .chain(|| (ErrorKind::IoError, "Failed to write corpus file"))?;
+        }
+        Ok(())
+    }
+
     pub fn create_withdraw_unbonded_tx(&mut self) -> Result<TxId> {
         let amount = Coin::from(1000);
         let view_key = self.wallet.view_key.clone();
@@ -224,11 +301,28 @@ impl VectorFactory {
             self.chain_hex_id,
             vec![TxAccessPolicy::new(view_key.0.into(), TxAccess::AllData)],
         );
+        self.add_seed_input("decode-tx-attributes", "withdraw", attributes.encode());
         let transaction = WithdrawUnbondedTx::new(nonce, vec![output], attributes);
         let tx = Transaction::WithdrawUnbondedStakeTransaction(transaction.clone());
         let txid = tx.id();
         let witness = sign_key.sign(&tx).map(StakedStateOpWitness::new)?;
         let plain_tx_aux = PlainTxAux::WithdrawUnbondedStakeTx(transaction);
+        self.add_seed_input("decode-plain-tx-aux", "withdraw", plain_tx_aux.encode());
+        // the payload is left unencrypted: the decoding targets treat it as opaque bytes
+        let tx = TxEnclaveAux::WithdrawUnbondedStakeTx {
+            no_of_outputs: 1,
+            witness: witness.clone(),
+            payload: TxObfuscated {
+                key_from: BlockHeight::genesis(),
+                init_vector: [0u8; 12],
+                txpayload: plain_tx_aux.encode(),
+                txid,
+            },
+        };
+        let account = StakedState::new(nonce, Coin::zero(), amount, 0, from_addr, None);
+        let request =
+            IntraEnclaveRequest::new_validate_withdraw(tx, self.seed_chain_info(), account);
+        self.add_seed_input("decode-intra-enclave-request", "withdraw", request.encode());
         let withdraw_unbonded_vector = WithdrawUnboundedVector {
             to_address: to_addr.to_cro(self.network).unwrap(),
             from_address: format!("{}", from_addr),
@@ -267,6 +361,7 @@ impl VectorFactory {
             })
             .collect();
         let attributes = TxAttributes::new_with_access(self.chain_hex_id, access_policies);
+        self.add_seed_input("decode-tx-attributes", "transfer", attributes.encode());
         let tx = Tx::new_with(inputs.clone(), outputs.clone(), attributes);
         let tx_id = tx.id();
         let proof = TestVectorWallet::gen_proof(public_key)?.unwrap();
@@ -277,6 +372,18 @@ impl VectorFactory {
         )]
         .into();
         let plain_tx_aux = PlainTxAux::TransferTx(tx, witness.clone());
+        self.add_seed_input("decode-plain-tx-aux", "transfer", plain_tx_aux.encode());
+        let request = DecryptionRequest::create(
+            secp256k1::SECP256K1,
+            vec![withdraw_unbonded_tx_id, tx_id],
+            [0u8; 32],
+            &SecretKey::from(&self.wallet.view_key.1),
+        );
+        self.add_seed_input("decode-decryption-request", "transfer", request.encode());
+        let request = TrustedTdbeRequest::GetTransactionsWithOutputs {
+            transaction_ids: Cow::Owned(vec![withdraw_unbonded_tx_id]),
+        };
+        self.add_tdbe_seed_input("get-transactions", 1, request.encode());
         let transfer_vector = TransferVector {
             to_address: to_address.to_cro(self.network).unwrap(),
             return_address: return_address.to_cro(self.network).unwrap(),
@@ -313,6 +420,11 @@ impl VectorFactory {
         )]
         .into();
         let tx_id = tx.id();
+        self.add_seed_input(
+            "decode-plain-tx-aux",
+            "deposit",
+            PlainTxAux::DepositStakeTx(witness.clone()).encode(),
+        );
 
         let deposit_vector = DepositStakeVector {
             staking_address: format!("{}", staking_address),
@@ -347,6 +459,7 @@ impl VectorFactory {
             .sign(&Transaction::NodejoinTransaction(tx.clone()))
             .map(StakedStateOpWitness::new)?;
         let nodejoin_tx = TxAux::PublicTx(TxPublicAux::NodeJoinTx(tx, witness.clone()));
+        self.add_seed_input("decode-tx-aux", "nodejoin", nodejoin_tx.encode());
         let nodejoin_vector = NodeJoinVector {
             staking_address: format!("{}", staking_address),
             tendermint_validator_pubkey: hex::encode(tendermint_validator_pubkey.encode()),
@@ -370,6 +483,7 @@ impl VectorFactory {
         let transaction = Transaction::UnbondStakeTransaction(tx.clone());
         let witness = sign_key.sign(&transaction).map(StakedStateOpWitness::new)?;
         let unbond_tx = TxAux::PublicTx(TxPublicAux::UnbondStakeTx(tx, witness.clone()));
+        self.add_seed_input("decode-tx-aux", "unbond", unbond_tx.encode());
         let unbonded_stake_vector = UnboundedStakeVector {
             staking_address: format!("{}", staking_address),
             witness: hex::encode(witness.encode()),
@@ -380,6 +494,58 @@ impl VectorFactory {
         Ok(())
     }
 
+    /// Seed inputs for the account state, enclave protocol and MLS messages
+    /// (not part of the printed test vectors)
+    fn create_state_seed_inputs(&mut self) {
+        let staking_address = self.wallet.staking_address.clone().unwrap().0;
+        let account = StakedState::new(1, Coin::from(1000), Coin::zero(), 0, staking_address, None);
+        self.add_seed_input("decode-staked-state", "account", account.encode());
+
+        let request = IntraEnclaveRequest::InitChainCheck(self.chain_hex_id);
+        self.add_seed_input(
+            "decode-intra-enclave-request",
+            "init-chain",
+            request.encode(),
+        );
+        self.add_tdbe_seed_input(
+            "finish",
+            0,
+            PersistenceCommand::Finish {
+                last_fetched_block: 1,
+            }
+            .encode(),
+        );
+        self.add_tdbe_seed_input(
+            "get-keypackage",
+            3,
+            UntrustedTdbeRequest::GetKeyPackage.encode(),
+        );
+        let response = UntrustedTdbeResponse::GetKeyPackage {
+            key_package: Cow::Borrowed(KEYPACKAGE_VECTOR),
+        };
+        self.add_tdbe_seed_input("keypackage", 4, response.encode());
+
+        self.add_seed_input("parse-keypackage", "keypackage", KEYPACKAGE_VECTOR.to_vec());
+        if let MLSInit::NodeJoin { add, .. } = mock_confidential_init_node_join().init_payload {
+            self.add_seed_input("parse-mls-plaintext", "add", add);
+        }
+        // mock welcome addressed to the test keypackage (the secrets are not encrypted)
+        let kp = KeyPackage::<DefaultCipherSuite>::read_bytes(KEYPACKAGE_VECTOR).unwrap();
+        let welcome = Welcome::<DefaultCipherSuite> {
+            version: PROTOCOL_VERSION_MLS10,
+            cipher_suite: DefaultCipherSuite::tag(),
+            secrets: vec![EncryptedGroupSecrets {
+                encrypted_group_secrets: HPKECiphertext {
+                    kem_output: kp.payload.init_key.marshal(),
+                    ciphertext: vec![],
+                },
+                key_package_hash: DefaultCipherSuite::hash(&kp.get_encoding()),
+            }],
+            encrypted_group_info: vec![],
+        };
+        self.add_seed_input("parse-welcome", "welcome", welcome.get_encoding());
+    }
+
     pub fn create_test_vectors(&mut self, aux_payload: &[u8]) -> Result<()> {
         self.test_vectors.wallet_view_key = Some(hex::encode(self.wallet.view_key.0.serialize()));
         let tx_id = self.create_withdraw_unbonded_tx().unwrap();
@@ -387,6 +553,7 @@ impl VectorFactory {
         self.create_deposit_stake_tx(tx_id, aux_payload)?;
         self.create_nodejoin_tx()?;
         self.create_unbonded_stake_tx()?;
+        self.create_state_seed_inputs();
         println!(
             "view secret key: {}",
             hex::encode(self.wallet.view_key.1.serialize())
@@ -422,6 +589,7 @@ impl VectorFactory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use parity_scale_codec::Decode;
 
     #[test]
     fn test_vectors() {
@@ -577,5 +745,42 @@ mod tests {
             unbonded_stake_vector.tx_id,
             "7600e018d9f225fac168ef73708150b590f12105b1408f16eb2aaa88a42b50d7"
         );
+
+        // check the fuzzing seed inputs
+        let seed_input = |target: &str, name: &str| {
+            work_flow
+                .corpus
+                .iter()
+                .find(|input| input.target == target && input.name == name)
+                .map(|input| hex::encode(&input.data))
+                .unwrap()
+        };
+        assert_eq!(seed_input("decode-tx-aux", "nodejoin"), nodejoin_vector.tx);
+        assert_eq!(
+            seed_input("decode-tx-aux", "unbond"),
+            unbonded_stake_vector.tx
+        );
+        assert_eq!(
+            seed_input("decode-plain-tx-aux", "withdraw"),
+            withdraw_unbounded_vector.plain_tx_aux
+        );
+        assert_eq!(
+            seed_input("decode-plain-tx-aux", "transfer"),
+            transfer_vector.plain_tx_aux
+        );
+        assert_eq!(
+            seed_input("parse-keypackage", "keypackage"),
+            hex::encode(KEYPACKAGE_VECTOR)
+        );
+        let request = hex::decode(seed_input("decode-intra-enclave-request", "withdraw")).unwrap();
+        assert!(IntraEnclaveRequest::decode(&mut request.as_slice()).is_ok());
+        let request = hex::decode(seed_input("decode-decryption-request", "transfer")).unwrap();
+        let request = DecryptionRequest::decode(&mut request.as_slice()).unwrap();
+        assert!(request.verify(secp256k1::SECP256K1, [0u8; 32]).is_ok());
+        let keypackage = hex::decode(seed_input("decode-tdbe-protocol", "keypackage")).unwrap();
+        assert_eq!(keypackage[0], 4);
+        assert!(UntrustedTdbeResponse::decode(&mut &keypackage[1..]).is_ok());
+        let welcome = hex::decode(seed_input("parse-welcome", "welcome")).unwrap();
+        assert!(Welcome::<DefaultCipherSuite>::read_bytes(&welcome).is_some());
     }
 }
@@ -1,9 +1,12 @@
+use std::path::PathBuf;
+
 use structopt::StructOpt;
 
 use client_common::Result;
 
 use crate::commands::{
-    GenesisCommand, InitCommand, KeypackageCommand, RunCommand, StopCommand, TestVectorCommand,
+    DevnetCommand, GenesisCommand, InitCommand, KeypackageCommand, RunCommand, StopCommand,
+    TestVectorCommand,
 };
 
 const NETWORKS: [&str; 3] = ["devnet", "testnet", "mainnet"];
@@ -70,6 +73,12 @@ pub enum DevUtils {
             help = "hex format aux payload used in schnorr_sign"
         )]
         aux_payload: String,
+        #[structopt(
+            name = "corpus_dir",
+            long = "corpus-dir",
+            help = "Directory to write the encoded test vectors to as a fuzzing seed corpus (one sub-directory per fuzz target)"
+        )]
+        corpus_dir: Option<PathBuf>,
     },
 
     /// Used for working with tendermint's genesis.json
@@ -78,6 +87,16 @@ pub enum DevUtils {
         #[structopt(subcommand)]
         keypackage_command: KeypackageCommand,
     },
+
+    /// Used for running a local multi-node network
+    #[structopt(
+        name = "devnet",
+        about = "Commands for a local multi-node network (see example-devnet.yaml)"
+    )]
+    Devnet {
+        #[structopt(subcommand)]
+        devnet_command: DevnetCommand,
+    },
 }
 
 impl DevUtils {
@@ -100,12 +119,18 @@ impl DevUtils {
                 network,
                 seed,
                 aux_payload,
+                corpus_dir,
             } => {
-                let test_vectors_command =
-                    TestVectorCommand::new(network.clone(), seed.clone(), aux_payload);
+                let test_vectors_command = TestVectorCommand::new(
+                    network.clone(),
+                    seed.clone(),
+                    aux_payload,
+                    corpus_dir.clone(),
+                );
                 test_vectors_command.execute()
             }
             DevUtils::Keypackage { keypackage_command } => keypackage_command.execute(),
+            DevUtils::Devnet { devnet_command } => devnet_command.execute(),
         }
     }
 }
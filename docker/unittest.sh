@@ -21,6 +21,8 @@ else
     cargo test $CARGO_ARGS --features mock-enclave --manifest-path client-cli/Cargo.toml
     cargo test $CARGO_ARGS --features mock-enclave --manifest-path dev-utils/Cargo.toml
     cargo test $CARGO_ARGS --features mock-enclave --manifest-path chain-abci/Cargo.toml
+    cargo test $CARGO_ARGS --features mock-enclave,priority-fee --manifest-path chain-abci/Cargo.toml
+    cargo test $CARGO_ARGS --features priority-fee --manifest-path chain-core/Cargo.toml
     for pkg in \
         chain-util \
         client-network \
@@ -0,0 +1,8 @@
+#![no_main]
+use enclave_protocol::DecryptionRequest;
+use fuzz_common::scale_bytes as roundtrip;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    roundtrip::check_roundtrip::<DecryptionRequest>(data);
+});
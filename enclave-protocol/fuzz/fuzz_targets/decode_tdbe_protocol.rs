@@ -0,0 +1,20 @@
+#![no_main]
+use enclave_protocol::tdbe_protocol::{
+    PersistenceCommand, TrustedTdbeRequest, TrustedTdbeResponse, UntrustedTdbeRequest,
+    UntrustedTdbeResponse,
+};
+use fuzz_common::scale_bytes as roundtrip;
+use libfuzzer_sys::fuzz_target;
+
+// the first byte selects the message type
+fuzz_target!(|data: &[u8]| {
+    if let Some((selector, data)) = data.split_first() {
+        match selector % 5 {
+            0 => roundtrip::check_roundtrip::<PersistenceCommand>(data),
+            1 => roundtrip::check_roundtrip::<TrustedTdbeRequest<'static>>(data),
+            2 => roundtrip::check_roundtrip::<TrustedTdbeResponse<'static>>(data),
+            3 => roundtrip::check_roundtrip::<UntrustedTdbeRequest>(data),
+            _ => roundtrip::check_roundtrip::<UntrustedTdbeResponse<'static>>(data),
+        }
+    }
+});
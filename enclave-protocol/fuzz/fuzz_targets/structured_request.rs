@@ -0,0 +1,242 @@
+#![no_main]
+use std::borrow::Cow;
+
+use chain_core::init::address::RedeemAddress;
+use chain_core::init::coin::Coin;
+use chain_core::state::account::{
+    DepositBondTx, StakedState, StakedStateAddress, StakedStateOpAttributes, StakedStateOpWitness,
+};
+use chain_core::state::tendermint::BlockHeight;
+use chain_core::tx::data::input::TxoPointer;
+use chain_core::tx::data::output::TxInclusion;
+use chain_core::tx::fee::Fee;
+use chain_core::tx::{TxEnclaveAux, TxObfuscated};
+use chain_core::ChainInfo;
+use enclave_protocol::tdbe_protocol::{
+    PersistenceCommand, TrustedTdbeRequest, UntrustedTdbeResponse,
+};
+use enclave_protocol::{DecryptionRequest, IntraEnclaveRequest};
+use fuzz_common::scale_bytes as roundtrip;
+use libfuzzer_sys::arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use parity_scale_codec::{Decode, Encode};
+use secp256k1::{key::SecretKey, Message, Secp256k1};
+
+#[derive(Arbitrary, Debug)]
+struct Info {
+    min_fee: u64,
+    chain_hex_id: u8,
+    block_time: u64,
+    block_height: u64,
+    max_evidence_age: u64,
+}
+
+#[derive(Arbitrary, Debug)]
+struct Payload {
+    key_from: u64,
+    init_vector: [u8; 12],
+    txpayload: Vec<u8>,
+    txid: [u8; 32],
+}
+
+#[derive(Arbitrary, Debug)]
+struct Account {
+    address: [u8; 20],
+    nonce: u64,
+    bonded: u64,
+    unbonded: u64,
+    unbonded_from: u64,
+}
+
+/// Generates well-formed protocol messages (instead of random bytes
+/// that mostly fail to decode early)
+#[derive(Arbitrary, Debug)]
+enum Input {
+    Decryption {
+        txs: Vec<[u8; 32]>,
+        challenge: [u8; 32],
+        view_secret_key: [u8; 32],
+    },
+    InitChainCheck(u8),
+    EndBlock,
+    ValidateWithdraw {
+        info: Info,
+        account: Account,
+        no_of_outputs: u16,
+        payload: Payload,
+        secret_key: [u8; 32],
+    },
+    ValidateDeposit {
+        info: Info,
+        account: Option<Account>,
+        inputs: Vec<([u8; 32], u16)>,
+        to: [u8; 20],
+        priority_fee: u64,
+        payload: Payload,
+        sealed_inputs: Vec<Vec<u8>>,
+        inclusions: Vec<Option<(u64, u64)>>,
+    },
+    Store {
+        transaction_id: [u8; 32],
+        sealed_log: Vec<u8>,
+    },
+    Finish {
+        last_fetched_block: u32,
+    },
+    GetTransactions {
+        transaction_ids: Vec<[u8; 32]>,
+    },
+    KeyPackage {
+        key_package: Vec<u8>,
+    },
+}
+
+fn coin(value: u64) -> Coin {
+    Coin::new(value).unwrap_or_else(|_| Coin::max())
+}
+
+fn staking_address(address: [u8; 20]) -> StakedStateAddress {
+    StakedStateAddress::BasicRedeem(RedeemAddress(address))
+}
+
+fn chain_info(info: Info) -> ChainInfo {
+    ChainInfo {
+        min_fee_computed: Fee::new(coin(info.min_fee)),
+        chain_hex_id: info.chain_hex_id,
+        block_time: info.block_time,
+        block_height: BlockHeight::new(info.block_height),
+        max_evidence_age: info.max_evidence_age,
+    }
+}
+
+fn payload(payload: Payload) -> TxObfuscated {
+    TxObfuscated {
+        key_from: BlockHeight::new(payload.key_from),
+        init_vector: payload.init_vector,
+        txpayload: payload.txpayload,
+        txid: payload.txid,
+    }
+}
+
+fn staked_state(account: Account) -> StakedState {
+    StakedState::new(
+        account.nonce,
+        coin(account.bonded),
+        coin(account.unbonded),
+        account.unbonded_from,
+        staking_address(account.address),
+        None,
+    )
+}
+
+fuzz_target!(|input: Input| {
+    let secp = Secp256k1::new();
+    match input {
+        Input::Decryption {
+            txs,
+            challenge,
+            view_secret_key,
+        } => {
+            if let Ok(secret_key) = SecretKey::from_slice(&view_secret_key) {
+                let request = DecryptionRequest::create(&secp, txs, challenge, &secret_key);
+                roundtrip::check_encoding(&request);
+                // the signature must survive the round-trip
+                let decoded = DecryptionRequest::decode(&mut request.encode().as_slice())
+                    .expect("decode own encoding");
+                decoded
+                    .verify(&secp, challenge)
+                    .expect("verify decoded request");
+            }
+        }
+        Input::InitChainCheck(chain_hex_id) => {
+            roundtrip::check_encoding(&IntraEnclaveRequest::InitChainCheck(chain_hex_id));
+        }
+        Input::EndBlock => {
+            roundtrip::check_encoding(&IntraEnclaveRequest::EndBlock);
+        }
+        Input::ValidateWithdraw {
+            info,
+            account,
+            no_of_outputs,
+            payload: obfuscated,
+            secret_key,
+        } => {
+            if let Ok(secret_key) = SecretKey::from_slice(&secret_key) {
+                let message = Message::from_slice(&obfuscated.txid).expect("32 bytes");
+                let witness =
+                    StakedStateOpWitness::new(secp.sign_recoverable(&message, &secret_key));
+                let tx = TxEnclaveAux::WithdrawUnbondedStakeTx {
+                    no_of_outputs,
+                    witness,
+                    payload: payload(obfuscated),
+                };
+                roundtrip::check_encoding(&IntraEnclaveRequest::new_validate_withdraw(
+                    tx,
+                    chain_info(info),
+                    staked_state(account),
+                ));
+            }
+        }
+        Input::ValidateDeposit {
+            info,
+            account,
+            inputs,
+            to,
+            priority_fee,
+            payload: obfuscated,
+            sealed_inputs,
+            inclusions,
+        } => {
+            let inclusions = inclusions
+                .into_iter()
+                .map(|inclusion| {
+                    inclusion.map(|(block_height, block_time)| TxInclusion {
+                        block_height: BlockHeight::new(block_height),
+                        block_time,
+                    })
+                })
+                .collect();
+            let inputs = inputs
+                .into_iter()
+                .map(|(id, index)| TxoPointer::new(id, index as usize))
+                .collect();
+            let attributes = StakedStateOpAttributes::new_with_priority_fee(
+                info.chain_hex_id,
+                coin(priority_fee),
+            );
+            let tx = TxEnclaveAux::DepositStakeTx {
+                tx: DepositBondTx::new(inputs, staking_address(to), attributes),
+                payload: payload(obfuscated),
+            };
+            roundtrip::check_encoding(&IntraEnclaveRequest::new_validate_deposit(
+                tx,
+                chain_info(info),
+                account.map(staked_state),
+                sealed_inputs,
+                inclusions,
+            ));
+        }
+        Input::Store {
+            transaction_id,
+            sealed_log,
+        } => {
+            roundtrip::check_encoding(&PersistenceCommand::Store {
+                transaction_id,
+                sealed_log,
+            });
+        }
+        Input::Finish { last_fetched_block } => {
+            roundtrip::check_encoding(&PersistenceCommand::Finish { last_fetched_block });
+        }
+        Input::GetTransactions { transaction_ids } => {
+            roundtrip::check_encoding(&TrustedTdbeRequest::GetTransactionsWithOutputs {
+                transaction_ids: Cow::Owned(transaction_ids),
+            });
+        }
+        Input::KeyPackage { key_package } => {
+            roundtrip::check_encoding(&UntrustedTdbeResponse::GetKeyPackage {
+                key_package: Cow::Owned(key_package),
+            });
+        }
+    }
+});
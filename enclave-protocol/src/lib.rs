@@ -17,6 +17,7 @@ use chain_core::state::account::StakedState;
 use chain_core::state::account::StakedStateOpWitness;
 use chain_core::state::account::WithdrawUnbondedTx;
 use chain_core::tx::data::input::TxoPointer;
+use chain_core::tx::data::output::TxInclusion;
 use chain_core::tx::data::{Tx, TxId};
 use chain_core::tx::witness::TxWitness;
 use chain_core::tx::TxObfuscated;
@@ -45,6 +46,8 @@ pub struct IntraEncryptRequest {
     pub sealed_enc_request: SealedLog,
     /// transaction inputs (if any)
     pub tx_inputs: Option<Vec<SealedLog>>,
+    /// blocks the transactions of inputs were included in (for relative timelocks)
+    pub tx_inputs_inclusion: Vec<Option<TxInclusion>>,
     /// related account if any
     pub account: Option<StakedState>,
     /// last chain info
@@ -58,6 +61,7 @@ pub enum IntraEnclaveRequest {
     ValidateTx {
         request: Box<VerifyTxRequest>,
         tx_inputs: Option<Vec<SealedLog>>,
+        tx_inputs_inclusion: Vec<Option<TxInclusion>>,
     },
     EndBlock,
     Encrypt(Box<IntraEncryptRequest>),
@@ -68,9 +72,11 @@ impl IntraEnclaveRequest {
         tx: TxEnclaveAux,
         info: ChainInfo,
         tx_inputs: Vec<SealedLog>,
+        tx_inputs_inclusion: Vec<Option<TxInclusion>>,
     ) -> Self {
         Self::ValidateTx {
             tx_inputs: Some(tx_inputs),
+            tx_inputs_inclusion,
             request: Box::new(VerifyTxRequest {
                 tx,
                 account: None,
@@ -84,9 +90,11 @@ impl IntraEnclaveRequest {
         info: ChainInfo,
         account: Option<StakedState>,
         tx_inputs: Vec<SealedLog>,
+        tx_inputs_inclusion: Vec<Option<TxInclusion>>,
     ) -> Self {
         Self::ValidateTx {
             tx_inputs: Some(tx_inputs),
+            tx_inputs_inclusion,
             request: Box::new(VerifyTxRequest { tx, account, info }),
         }
     }
@@ -94,6 +102,7 @@ impl IntraEnclaveRequest {
     pub fn new_validate_withdraw(tx: TxEnclaveAux, info: ChainInfo, account: StakedState) -> Self {
         Self::ValidateTx {
             tx_inputs: None,
+            tx_inputs_inclusion: Vec::new(),
             request: Box::new(VerifyTxRequest {
                 tx,
                 account: Some(account),
@@ -0,0 +1,79 @@
+#![deny(missing_docs, unsafe_code, unstable_features)]
+//! Round-trip checks shared by the fuzzing targets: whatever decodes successfully must re-decode
+//! from its own encoding, and re-encode to the same bytes.
+
+/// decode(encode(value)) re-encodes to the same bytes, returns the re-decoded value
+pub fn check_encoding_with<T, E, D>(value: &T, encode: E, decode: D) -> T
+where
+    E: Fn(&T) -> Vec<u8>,
+    D: Fn(&[u8]) -> Option<T>,
+{
+    let encoded = encode(value);
+    let decoded = decode(&encoded).expect("decode own encoding");
+    assert_eq!(encoded, encode(&decoded));
+    decoded
+}
+
+/// Checks of the SCALE encoded types, which are compared by value
+#[cfg(feature = "scale")]
+pub mod scale {
+    use parity_scale_codec::{Decode, Encode};
+    use std::fmt::Debug;
+
+    pub(crate) fn decode<T: Decode>(data: &[u8]) -> Option<T> {
+        T::decode(&mut &data[..]).ok()
+    }
+
+    /// Whatever decodes successfully must re-decode from its own encoding
+    /// to the same value, and the encoding must be stable.
+    pub fn check_roundtrip<T: Decode + Encode + PartialEq + Debug>(data: &[u8]) {
+        if let Some(decoded) = decode::<T>(data) {
+            check_encoding(&decoded);
+        }
+    }
+
+    /// decode(encode(value)) == value
+    pub fn check_encoding<T: Decode + Encode + PartialEq + Debug>(value: &T) {
+        let decoded = super::check_encoding_with(value, T::encode, decode);
+        assert_eq!(value, &decoded);
+    }
+}
+
+/// Checks of the SCALE encoded types which don't implement `PartialEq` (e.g. the enclave
+/// protocol messages), which are compared by their encodings
+#[cfg(feature = "scale")]
+pub mod scale_bytes {
+    use parity_scale_codec::{Decode, Encode};
+
+    /// Whatever decodes successfully must re-decode from its own encoding,
+    /// and re-encode to the same bytes.
+    pub fn check_roundtrip<T: Decode + Encode>(data: &[u8]) {
+        if let Some(decoded) = super::scale::decode::<T>(data) {
+            check_encoding(&decoded);
+        }
+    }
+
+    /// decode(encode(value)) re-encodes to the same bytes
+    pub fn check_encoding<T: Decode + Encode>(value: &T) {
+        super::check_encoding_with(value, T::encode, super::scale::decode);
+    }
+}
+
+/// Checks of the mls messages, which are compared by their encodings
+#[cfg(feature = "mls")]
+pub mod mls {
+    use rustls::internal::msgs::codec::Codec;
+
+    /// Whatever parses successfully must re-parse from its own encoding,
+    /// and re-encode to the same bytes.
+    pub fn check_roundtrip<T: Codec>(data: &[u8]) {
+        if let Some(parsed) = T::read_bytes(data) {
+            check_encoding(&parsed);
+        }
+    }
+
+    /// read(encode(value)) re-encodes to the same bytes
+    pub fn check_encoding<T: Codec>(value: &T) {
+        super::check_encoding_with(value, T::get_encoding, T::read_bytes);
+    }
+}
@@ -191,6 +191,29 @@ class Wallet:
     def delete(self, name=DEFAULT_WALLET, passphrase=None):
         return self.client.call('wallet_delete', [name, passphrase or get_passphrase()])
 
+    def backup(self, backup_passphrase, name=DEFAULT_WALLET, enckey=None):
+        '''create encrypted backup of wallet, returns base64 encoded backup
+        :param backup_passphrase: passphrase to encrypt the backup with
+        '''
+        return self.client.call('wallet_backup', [name, enckey or get_enckey()], backup_passphrase)
+
+    def restore_backup(self, backup, backup_passphrase):
+        '''restore wallet from encrypted backup, returns name of the wallet
+        :param backup: base64 encoded backup
+        :param backup_passphrase: passphrase the backup is encrypted with
+        '''
+        return self.client.call('wallet_restoreBackup', backup, backup_passphrase)
+
+    def change_passphrase(self, new_passphrase, name=DEFAULT_WALLET, passphrase=None):
+        '''change passphrase of wallet, returns the new enckey
+        :param new_passphrase: new passphrase of the wallet
+        :param name: Name of the wallet. [defualt: Default]
+        '''
+        return self.client.call(
+            'wallet_changePassphrase',
+            [name, passphrase or get_passphrase()], new_passphrase
+        )
+
     def view_key(self, name=DEFAULT_WALLET, private=False, enckey=None):
         return self.client.call(
             'wallet_getViewKey',
@@ -203,12 +226,49 @@ class Wallet:
     def transactions(self, name=DEFAULT_WALLET, offset=0, limit=100, reversed=False, enckey=None):
         return self.client.call('wallet_transactions', [name, enckey or get_enckey()], offset, limit, reversed)
 
+    def history_records(self, name=DEFAULT_WALLET, start=None, end=None, types=None, enckey=None):
+        '''
+        history records including staking rewards and slashes
+        start/end: RFC 3339 time, types: e.g. ["Transfer", "Reward"]
+        '''
+        return self.client.call(
+            'wallet_historyRecords',
+            [name, enckey or get_enckey()],
+            {"from": start, "to": end, "types": types or []})
+
+    def export_history(self, name=DEFAULT_WALLET, start=None, end=None, types=None, format='csv', enckey=None):
+        return self.client.call(
+            'wallet_exportHistory',
+            [name, enckey or get_enckey()],
+            {"from": start, "to": end, "types": types or []},
+            format)
+
     def send(self, to_address, amount, name=DEFAULT_WALLET, view_keys=None, enckey=None):
         return self.client.call(
             'wallet_sendToAddress',
             [name, enckey or get_enckey()],
             to_address, str(amount), view_keys or [])
 
+    def address_labels(self, name=DEFAULT_WALLET, enckey=None):
+        '''list labelled addresses of the address book'''
+        return self.client.call('wallet_listAddressLabels', [name, enckey or get_enckey()])
+
+    def set_address_label(self, label, address, name=DEFAULT_WALLET, enckey=None):
+        '''label an address, the label can then be used as send target (e.g. @alice)
+        :param label: label of the address, without @
+        :param address: transfer or staking address
+        '''
+        return self.client.call('wallet_setAddressLabel', [name, enckey or get_enckey()], label, address)
+
+    def remove_address_label(self, label, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('wallet_removeAddressLabel', [name, enckey or get_enckey()], label)
+
+    def set_transaction_label(self, txid, label, note=None, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('wallet_setTransactionLabel', [name, enckey or get_enckey()], txid, label, note)
+
+    def remove_transaction_label(self, txid, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('wallet_removeTransactionLabel', [name, enckey or get_enckey()], txid)
+
     def sync(self, name=DEFAULT_WALLET, enckey=None):
         return self.client.call('sync', [name, enckey or get_enckey()],{"blocking":True, "reset":False, "do_loop":False})
 
@@ -335,6 +395,22 @@ class MultiSig:
             session_id,
             unsigned_transaction)
 
+    def new_signing_package(self, unsigned_transaction, signer_public_keys, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call(
+            'multiSig_newSigningPackage',
+            [name, enckey or get_enckey()],
+            unsigned_transaction,
+            signer_public_keys)
+
+    def import_signing_package(self, package, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('multiSig_importSigningPackage', [name, enckey or get_enckey()], package)
+
+    def export_signing_package(self, transaction_id, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('multiSig_exportSigningPackage', [name, enckey or get_enckey()], transaction_id)
+
+    def broadcast_signing_package(self, package, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('multiSig_broadcastSigningPackage', [name, enckey or get_enckey()], package)
+
 
 class Blockchain:
     def __init__(self, tendermint_port):
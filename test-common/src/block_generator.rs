@@ -41,7 +41,7 @@ use chain_storage::jellyfish::{put_stakings, StakingGetter};
 use client_common::tendermint::types::{AbciQuery, BroadcastTxResponse, Genesis};
 use client_common::tendermint::Client;
 use client_common::Result;
-use client_core::wallet::syncer::Handle;
+use client_core::wallet::syncer::{Handle, LightClientHandle};
 use client_core::{service::HDAccountType, HDSeed, Mnemonic};
 use tendermint::block::{CommitSig, CommitSigs};
 
@@ -272,6 +272,7 @@ impl TestnetSpec {
                 self.expansion_cap,
             ),
             council_nodes,
+            exported_state: None,
         }
     }
 
@@ -620,6 +621,8 @@ impl Handle for GeneratorClient {
     }
 }
 
+impl LightClientHandle for GeneratorClient {}
+
 fn gen_network_params(
     base_fee: Milli,
     per_byte_fee: Milli,
@@ -655,6 +658,9 @@ fn gen_network_params(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use client_common::tendermint::lite::{
+        cross_check, verify_first_block, CrossCheck, TrustedState,
+    };
     use std::time::{Duration, SystemTime};
     use tendermint::lite;
 
@@ -688,4 +694,110 @@ mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    fn check_first_block_verified_against_genesis() {
+        let c = GeneratorClient::new(BlockGenerator::one_node());
+        {
+            let mut gen = c.gen.write().unwrap();
+            gen.gen_block(&[]);
+            gen.gen_block(&[]);
+        }
+        let genesis = c.gen.read().unwrap().genesis.clone();
+        let trusted = verify_first_block(&c, &genesis).unwrap();
+
+        let gen = c.gen.read().unwrap();
+        let genesis_trust = TrustedState::genesis(&gen.genesis);
+        let header1 = gen.signed_header(Height::default());
+        assert!(genesis_trust.verify_first_block(&header1).is_ok());
+        // the verified first block is the anchor
+        let (anchor, validators) = trusted.anchor().unwrap();
+        assert_eq!(anchor.header.hash(), header1.header.hash());
+        assert_eq!(validators.hash(), header1.header.next_validators_hash);
+        assert!(genesis_trust.anchor().is_none());
+
+        // block from another height
+        let header2 = gen.signed_header(Height::default().increment());
+        assert!(genesis_trust.verify_first_block(&header2).is_err());
+
+        // app hash tampered after signing
+        let mut forged = header1.clone();
+        forged.header.app_hash = vec![0xff; 32];
+        assert!(genesis_trust.verify_first_block(&forged).is_err());
+
+        // well-formed first block produced by validators outside of genesis
+        let mut other = BlockGenerator::new(TestnetSpec::new(vec![Node::new(
+            0,
+            &"zone fiber glory option pause arrive buyer stone match neutral obvious already deer equip depth".into(),
+            &[1; 32],
+            &[2; 32],
+        )]));
+        other.gen_block(&[]);
+        let forged = other.signed_header(Height::default());
+        assert!(genesis_trust.verify_first_block(&forged).is_err());
+
+        // genesis validators hash, but signed by validators outside of genesis
+        let mut forged = forged;
+        forged.header.validators_hash = header1.header.validators_hash;
+        forged.header.next_validators_hash = header1.header.next_validators_hash;
+        forged.header.app_hash = header1.header.app_hash.clone();
+        forged.commit.block_id.hash = forged.header.hash();
+        assert!(genesis_trust.verify_first_block(&forged).is_err());
+
+        // the node's first block doesn't match another pinned genesis
+        drop(gen);
+        assert!(verify_first_block(&c, &other.genesis).is_err());
+    }
+
+    #[test]
+    fn check_cross_check_detects_diverging_chains() {
+        let mut honest = BlockGenerator::one_node();
+        // same validators, but the chain diverges from the first block on
+        let mut spec = TestnetSpec::new(DEFAULT_NODES.clone());
+        spec.genesis_time = (UNIX_EPOCH + Duration::from_secs(GENESIS_TIMESTAMP + 1)).into();
+        let mut forked = BlockGenerator::new(spec);
+        // another chain with validators outside of the trusted set
+        let mut outsider = BlockGenerator::new(TestnetSpec::new(vec![Node::new(
+            0,
+            &"zone fiber glory option pause arrive buyer stone match neutral obvious already deer equip depth".into(),
+            &[1; 32],
+            &[2; 32],
+        )]));
+        for gen in [&mut honest, &mut forked, &mut outsider].iter_mut() {
+            gen.gen_block(&[]);
+            gen.gen_block(&[]);
+        }
+
+        let height = Height::default().increment();
+        let primary = honest.signed_header(height);
+        let validators = &honest.validators;
+
+        assert!(matches!(
+            cross_check(&primary, validators, &honest.signed_header(height)),
+            CrossCheck::Agree
+        ));
+        match cross_check(&primary, validators, &forked.signed_header(height)) {
+            CrossCheck::Conflict(evidence) => {
+                assert_eq!(evidence.h1.header.hash(), primary.header.hash());
+                assert_eq!(
+                    evidence.h2.header.hash(),
+                    forked.signed_header(height).header.hash()
+                );
+            }
+            other => panic!("expected conflict, got {:?}", other),
+        }
+        assert!(matches!(
+            cross_check(&primary, validators, &outsider.signed_header(height)),
+            CrossCheck::FaultyWitness(_)
+        ));
+        // witness answering with another height
+        assert!(matches!(
+            cross_check(
+                &primary,
+                validators,
+                &honest.signed_header(Height::default())
+            ),
+            CrossCheck::FaultyWitness(_)
+        ));
+    }
 }
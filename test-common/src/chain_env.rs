@@ -16,7 +16,7 @@ use chain_abci::enclave_bridge::mock::MockClient;
 use chain_core::common::{MerkleTree, Timespec, H256};
 use chain_core::compute_app_hash;
 use chain_core::init::address::RedeemAddress;
-use chain_core::init::coin::Coin;
+use chain_core::init::coin::{sum_coins, Coin};
 use chain_core::init::config::{
     InitConfig, InitNetworkParameters, JailingParameters, NetworkParameters, RewardsParameters,
     SlashRatio, SlashingParameters,
@@ -42,16 +42,18 @@ use mls::{
     DefaultCipherSuite, KeyPackage,
 };
 
-const TEST_CHAIN_ID: &str = "test-00";
+pub(crate) const TEST_CHAIN_ID: &str = "test-00";
 
 pub const DEFAULT_GENESIS_TIME: u64 = 1563148800;
 
 /// Need to add more seed and validator public keys, if need more validator nodes.
-const SEEDS: [[u8; 32]; 2] = [[0xcd; 32], [0xab; 32]];
+const SEEDS: [[u8; 32]; 4] = [[0xcd; 32], [0xab; 32], [0x12; 32], [0x34; 32]];
 lazy_static! {
     static ref VALIDATOR_PUB_KEYS: Vec<TendermintValidatorPubKey> = [
         b"EIosObgfONUsnWCBGRpFlRFq5lSxjGIChRlVrVWVkcE=",
-        b"Vcrw/tEI0JOXw2SZGeowDxw5+Eot8qndCJoh2m6RC/M="
+        b"Vcrw/tEI0JOXw2SZGeowDxw5+Eot8qndCJoh2m6RC/M=",
+        b"jbUduSYiMJtf/N14eYgfMwi6zPgrm/cJ7uQEK0zrnJk=",
+        b"rHJfHxvggzia2hdABgAmZHwx3HCuY05TUuzos2uQa4w="
     ]
     .iter()
     .map(|s| TendermintValidatorPubKey::from_base64(*s).unwrap())
@@ -210,6 +212,7 @@ pub fn get_nodes(
         .collect()
 }
 
+#[derive(Clone)]
 pub struct Account {
     pub secret_key: SecretKey,
     pub address: RedeemAddress,
@@ -249,6 +252,7 @@ pub struct ChainEnv {
     pub init_config: InitConfig,
     pub max_evidence_age: Timespec,
     pub council_nodes: Vec<(StakedStateAddress, CouncilNodeMeta)>,
+    pub genesis_validators: Vec<ValidatorUpdate>,
 
     pub accounts: Vec<Account>,
 }
@@ -264,7 +268,6 @@ impl ChainEnv {
         count: usize,
         customize_network_params: F,
     ) -> (ChainEnv, Storage) {
-        let mut storage = create_storage();
         let locked = (Coin::max() - dist_coin - expansion_cap).unwrap();
         let accounts: Vec<Account> = (0..count)
             .map(|i| {
@@ -293,12 +296,19 @@ impl ChainEnv {
             distribution.insert(acct.address, (StakedStateDestination::Bonded, share));
         }
 
-        let init_config = InitConfig::new(
-            distribution,
-            init_network_params.clone(),
-            get_nodes(&accounts),
-        );
+        let init_config = InitConfig::new(distribution, init_network_params, get_nodes(&accounts));
+        let (mut env, storage) = ChainEnv::new_from_init_config(init_config, accounts);
+        env.dist_coin = dist_coin;
+        env.expansion_cap = expansion_cap;
+        (env, storage)
+    }
 
+    /// starts from the provided initial configuration (e.g. exported from another chain)
+    pub fn new_from_init_config(
+        init_config: InitConfig,
+        accounts: Vec<Account>,
+    ) -> (ChainEnv, Storage) {
+        let mut storage = create_storage();
         let timestamp = Timestamp {
             seconds: DEFAULT_GENESIS_TIME as i64,
             ..Default::default()
@@ -308,21 +318,52 @@ impl ChainEnv {
             .expect("Error while validating distribution");
 
         let new_account_root = storage.put_stakings(0, &genesis_state.accounts);
+        let network_params = NetworkParameters::Genesis(init_config.network_params.clone());
         let genesis_app_hash = compute_app_hash(
             &MerkleTree::empty(),
             &new_account_root,
             &genesis_state.rewards_pool,
-            &NetworkParameters::Genesis(init_network_params),
+            &network_params,
         );
+        let bonded = |address: &StakedStateAddress| {
+            genesis_state
+                .accounts
+                .iter()
+                .find(|account| &account.address == address)
+                .expect("validator account")
+                .bonded
+        };
+        let genesis_validators = genesis_state
+            .validators
+            .iter()
+            .map(|(address, node)| ValidatorUpdate {
+                pub_key: Some(PubKey {
+                    field_type: "ed25519".to_owned(),
+                    data: node.consensus_pubkey.as_bytes().to_vec(),
+                    ..Default::default()
+                })
+                .into(),
+                power: TendermintVotePower::from(bonded(address)).into(),
+                ..Default::default()
+            })
+            .collect();
+        let dist_coin = sum_coins(
+            genesis_state
+                .validators
+                .iter()
+                .map(|(address, _)| bonded(address)),
+        )
+        .unwrap();
         (
             ChainEnv {
                 dist_coin,
-                expansion_cap,
+                expansion_cap: network_params.get_rewards_monetary_expansion_cap(),
                 genesis_app_hash,
                 timestamp,
                 init_config,
                 max_evidence_age: 172_800,
                 council_nodes: genesis_state.validators,
+                genesis_validators,
                 accounts,
             },
             storage,
@@ -363,11 +404,21 @@ impl ChainEnv {
     }
 
     pub fn unbond_tx(&self, coin: Coin, nonce: u64, account_index: usize) -> TxAux {
+        self.unbond_tx_with_priority_fee(coin, nonce, account_index, Coin::zero())
+    }
+
+    pub fn unbond_tx_with_priority_fee(
+        &self,
+        coin: Coin,
+        nonce: u64,
+        account_index: usize,
+        priority_fee: Coin,
+    ) -> TxAux {
         let tx = UnbondTx::new(
             self.accounts[account_index].staking_address(),
             nonce,
             coin,
-            StakedStateOpAttributes::new(0),
+            StakedStateOpAttributes::new_with_priority_fee(0, priority_fee),
         );
         let secp = secp256k1::SECP256K1;
         let witness = StakedStateOpWitness::new(get_ecdsa_witness(
@@ -379,26 +430,11 @@ impl ChainEnv {
     }
 
     pub fn req_init_chain(&self) -> RequestInitChain {
-        let share = Coin::new(u64::from(self.dist_coin) / self.accounts.len() as u64).unwrap();
-        let validators = self
-            .accounts
-            .iter()
-            .map(|acct| ValidatorUpdate {
-                pub_key: Some(PubKey {
-                    field_type: "ed25519".to_owned(),
-                    data: acct.validator_pub_key.as_bytes().to_vec(),
-                    ..Default::default()
-                })
-                .into(),
-                power: TendermintVotePower::from(share).into(),
-                ..Default::default()
-            })
-            .collect();
         RequestInitChain {
             time: Some(self.timestamp.clone()).into(),
             app_state_bytes: serde_json::to_vec(&self.init_config).unwrap(),
             chain_id: TEST_CHAIN_ID.to_owned(),
-            validators,
+            validators: self.genesis_validators.clone().into(),
             consensus_params: Some(ConsensusParams {
                 evidence: Some(EvidenceParams {
                     max_age_duration: Some(::protobuf::well_known_types::Duration {
@@ -3,3 +3,4 @@ extern crate lazy_static;
 
 pub mod block_generator;
 pub mod chain_env;
+pub mod simulator;
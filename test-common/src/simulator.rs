@@ -0,0 +1,293 @@
+//! Deterministic in-process network simulator:
+//! runs several `ChainNodeApp` instances in lockstep, synthesizing the Tendermint block requests
+//! (proposer rotation, last commit votes, evidence) from the validator updates the apps return.
+use std::collections::{BTreeMap, BTreeSet};
+
+use abci::*;
+use parity_scale_codec::Encode;
+use protobuf::well_known_types::Timestamp;
+
+use chain_abci::app::ChainNodeApp;
+use chain_abci::enclave_bridge::mock::MockClient;
+use chain_core::common::Timespec;
+use chain_core::state::tendermint::{TendermintValidatorAddress, TendermintValidatorPubKey};
+use chain_core::tx::TxAux;
+
+use crate::chain_env::{create_storage, ChainEnv, DEFAULT_GENESIS_TIME, TEST_CHAIN_ID};
+
+/// validator address -> vote power
+pub type ValidatorSet = BTreeMap<TendermintValidatorAddress, i64>;
+
+/// Outcome of a block (the same on all nodes)
+#[derive(Debug, Clone)]
+pub struct BlockResult {
+    pub height: i64,
+    pub proposer: Option<TendermintValidatorAddress>,
+    pub tx_results: Vec<ResponseDeliverTx>,
+    pub validator_updates: Vec<ValidatorUpdate>,
+    pub app_hash: Vec<u8>,
+}
+
+pub struct Simulator {
+    pub env: ChainEnv,
+    pub nodes: Vec<ChainNodeApp<MockClient>>,
+    /// last committed block height
+    pub height: i64,
+    /// last block time
+    pub time: Timespec,
+    /// seconds between blocks
+    pub block_interval: Timespec,
+
+    /// validator set of the last committed block (who vote in the next block's last commit)
+    last_validators: ValidatorSet,
+    /// validator set of the next block
+    validators: ValidatorSet,
+    /// validator set of the block after the next one
+    /// (Tendermint applies the updates returned in EndBlock(H) at H+2)
+    next_validators: ValidatorSet,
+    offline: BTreeSet<TendermintValidatorAddress>,
+    evidence: Vec<Evidence>,
+    /// (inclusion height, transaction)
+    pending_txs: Vec<(i64, TxAux)>,
+}
+
+fn validator_address(update: &ValidatorUpdate) -> TendermintValidatorAddress {
+    let data = &update.pub_key.as_ref().expect("validator pubkey").data;
+    let mut key = [0u8; 32];
+    key.copy_from_slice(data);
+    TendermintValidatorPubKey::Ed25519(key).into()
+}
+
+fn apply_updates(validators: &mut ValidatorSet, updates: &[ValidatorUpdate]) {
+    for update in updates {
+        let address = validator_address(update);
+        if update.power == 0 {
+            validators.remove(&address);
+        } else {
+            validators.insert(address, update.power);
+        }
+    }
+}
+
+fn timestamp(seconds: Timespec) -> Timestamp {
+    Timestamp {
+        seconds: seconds as i64,
+        ..Default::default()
+    }
+}
+
+fn abci_validator(address: &TendermintValidatorAddress, power: i64) -> Validator {
+    Validator {
+        address: <[u8; 20]>::from(address).to_vec(),
+        power,
+        ..Default::default()
+    }
+}
+
+impl Simulator {
+    /// Starts `node_count` nodes from the environment's genesis
+    pub fn new(env: ChainEnv, node_count: usize) -> Simulator {
+        let req_init_chain = env.req_init_chain();
+        let nodes = (0..node_count)
+            .map(|_| {
+                let mut app = env.chain_node(create_storage());
+                app.init_chain(&req_init_chain);
+                app
+            })
+            .collect();
+        let mut validators = ValidatorSet::new();
+        apply_updates(&mut validators, &env.genesis_validators);
+        Simulator {
+            env,
+            nodes,
+            height: 0,
+            time: DEFAULT_GENESIS_TIME,
+            block_interval: 1,
+            last_validators: ValidatorSet::new(),
+            validators: validators.clone(),
+            next_validators: validators,
+            offline: BTreeSet::new(),
+            evidence: vec![],
+            pending_txs: vec![],
+        }
+    }
+
+    /// Validator set of the next block
+    pub fn validators(&self) -> &ValidatorSet {
+        &self.validators
+    }
+
+    /// Vote power of the account's validator in the next block
+    pub fn validator_power(&self, account_index: usize) -> Option<i64> {
+        self.validators
+            .get(&self.env.validator_address(account_index))
+            .copied()
+    }
+
+    /// Offline validators don't sign the blocks (they still process them)
+    pub fn set_offline(&mut self, account_index: usize, offline: bool) {
+        let address = self.env.validator_address(account_index);
+        if offline {
+            self.offline.insert(address);
+        } else {
+            self.offline.remove(&address);
+        }
+    }
+
+    /// Reports the account's validator for signing two different blocks
+    /// at the last committed height (included in the next block)
+    pub fn double_sign(&mut self, account_index: usize) {
+        let address = self.env.validator_address(account_index);
+        let power = self
+            .last_validators
+            .get(&address)
+            .or_else(|| self.validators.get(&address))
+            .copied()
+            .unwrap_or_default();
+        self.evidence.push(Evidence {
+            validator: Some(abci_validator(&address, power)).into(),
+            height: self.height,
+            time: Some(timestamp(self.time)).into(),
+            ..Default::default()
+        });
+    }
+
+    /// Includes the transaction in the next block
+    pub fn submit_tx(&mut self, tx: TxAux) {
+        self.submit_tx_delayed(tx, 0);
+    }
+
+    /// Includes the transaction `delay` blocks after the next one
+    pub fn submit_tx_delayed(&mut self, tx: TxAux, delay: i64) {
+        self.pending_txs.push((self.height + 1 + delay, tx));
+    }
+
+    fn proposer(&self, height: i64) -> Option<TendermintValidatorAddress> {
+        if self.validators.is_empty() {
+            return None;
+        }
+        let index = (height - 1) as usize % self.validators.len();
+        self.validators.keys().nth(index).cloned()
+    }
+
+    fn req_begin_block(
+        &mut self,
+        height: i64,
+        time: Timespec,
+        proposer: Option<&TendermintValidatorAddress>,
+    ) -> RequestBeginBlock {
+        let last_commit_info = if height > 1 {
+            let votes = self
+                .last_validators
+                .iter()
+                .map(|(address, power)| VoteInfo {
+                    validator: Some(abci_validator(address, *power)).into(),
+                    signed_last_block: !self.offline.contains(address),
+                    ..Default::default()
+                })
+                .collect();
+            Some(LastCommitInfo {
+                votes,
+                ..Default::default()
+            })
+        } else {
+            None
+        };
+        RequestBeginBlock {
+            header: Some(Header {
+                time: Some(timestamp(time)).into(),
+                chain_id: TEST_CHAIN_ID.to_owned(),
+                height,
+                proposer_address: proposer
+                    .map(|address| <[u8; 20]>::from(address).to_vec())
+                    .unwrap_or_default(),
+                ..Default::default()
+            })
+            .into(),
+            last_commit_info: last_commit_info.into(),
+            byzantine_validators: std::mem::take(&mut self.evidence).into(),
+            ..Default::default()
+        }
+    }
+
+    /// Runs the next block on all nodes,
+    /// panicking if any of them disagrees with the first one
+    pub fn next_block(&mut self) -> BlockResult {
+        let height = self.height + 1;
+        let time = self.time + self.block_interval;
+        let proposer = self.proposer(height);
+        let req_begin_block = self.req_begin_block(height, time, proposer.as_ref());
+        let (txs, pending) = std::mem::take(&mut self.pending_txs)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(inclusion, _)| *inclusion <= height);
+        self.pending_txs = pending;
+        let txs = txs
+            .into_iter()
+            .map(|(_, tx)| RequestDeliverTx {
+                tx: tx.encode(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let req_end_block = RequestEndBlock {
+            height,
+            ..Default::default()
+        };
+
+        let mut result: Option<BlockResult> = None;
+        for (index, node) in self.nodes.iter_mut().enumerate() {
+            node.begin_block(&req_begin_block);
+            let tx_results = txs
+                .iter()
+                .map(|req| node.deliver_tx(req))
+                .collect::<Vec<_>>();
+            let validator_updates = node.end_block(&req_end_block).validator_updates.into_vec();
+            let app_hash = node.commit(&RequestCommit::new()).data;
+            match &result {
+                None => {
+                    result = Some(BlockResult {
+                        height,
+                        proposer: proposer.clone(),
+                        tx_results,
+                        validator_updates,
+                        app_hash,
+                    })
+                }
+                Some(expected) => {
+                    assert_eq!(
+                        expected.tx_results, tx_results,
+                        "node {} delivered different results at height {}",
+                        index, height
+                    );
+                    assert_eq!(
+                        expected.validator_updates, validator_updates,
+                        "node {} returned different validator updates at height {}",
+                        index, height
+                    );
+                    assert_eq!(
+                        hex::encode(&expected.app_hash),
+                        hex::encode(&app_hash),
+                        "node {} diverged at height {}",
+                        index,
+                        height
+                    );
+                }
+            }
+        }
+        let result = result.expect("at least one node");
+
+        let mut next_validators = self.next_validators.clone();
+        apply_updates(&mut next_validators, &result.validator_updates);
+        self.last_validators = std::mem::replace(
+            &mut self.validators,
+            std::mem::replace(&mut self.next_validators, next_validators),
+        );
+        self.height = height;
+        self.time = time;
+        result
+    }
+
+    /// Runs `count` blocks, returning their results
+    pub fn run(&mut self, count: usize) -> Vec<BlockResult> {
+        (0..count).map(|_| self.next_block()).collect()
+    }
+}